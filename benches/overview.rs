@@ -0,0 +1,129 @@
+//! Benchmarks for OVER/XOVER overview line parsing
+//!
+//! Overview parsing dominates header indexing. The previous split-and-collect
+//! parser and its date parsing are copied here verbatim as a baseline for
+//! before/after comparison, so the baseline does not pick up library changes.
+
+use chrono::{DateTime, Utc};
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use nntp_rs::commands::{parse_xover_line, parse_xover_line_ref};
+use nntp_rs::{Result, XoverEntry};
+
+/// Generate realistic overview lines (binary post subjects, Xref field)
+fn generate_overview_lines(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            format!(
+                "{}\t[{}/{}] - \"archive.part{:03}.rar\" yEnc ({}/{})\tposter{}@example.com (Poster)\t\
+                 Tue, 02 Jan 2024 12:{:02}:00 +0000\t<part{}of{}.abc{}@example.com>\t\t{}\t{}\t\
+                 Xref: news.example.com alt.binaries.test:{}",
+                1_000_000 + i,
+                i % 50 + 1,
+                50,
+                i % 50,
+                i % 100 + 1,
+                100,
+                i % 7,
+                i % 60,
+                i % 100 + 1,
+                100,
+                i,
+                750_000 + i,
+                5_800 + i % 10,
+                1_000_000 + i,
+            )
+        })
+        .collect()
+}
+
+/// `validation::parse_date` before the memchr rework
+fn parse_date_legacy(date_str: &str) -> Result<DateTime<Utc>> {
+    match DateTime::parse_from_rfc2822(date_str) {
+        Ok(dt) => Ok(dt.with_timezone(&Utc)),
+        Err(_) => {
+            if date_str.contains("GMT") {
+                let normalized = date_str.replace("GMT", "+0000");
+                if let Ok(dt) = DateTime::parse_from_rfc2822(&normalized) {
+                    return Ok(dt.with_timezone(&Utc));
+                }
+            }
+
+            Err(nntp_rs::NntpError::InvalidResponse(format!(
+                "Invalid date format: {} (expected RFC 5322 format)",
+                date_str
+            )))
+        }
+    }
+}
+
+/// `parse_overview_date` before the memchr rework
+fn parse_overview_date_legacy(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+    if let Ok(timestamp) = parse_date_legacy(date) {
+        return Some(timestamp);
+    }
+
+    let without_comment = date
+        .rfind('(')
+        .filter(|_| date.ends_with(')'))
+        .map(|start| date[..start].trim_end())?;
+    parse_date_legacy(without_comment).ok()
+}
+
+/// Parser before the memchr rework: collect tab-split fields, parse via `str::parse`
+fn parse_xover_line_legacy(line: &str) -> Result<XoverEntry> {
+    let parts: Vec<&str> = line.split('\t').collect();
+    if parts.len() < 8 {
+        return Err(nntp_rs::NntpError::InvalidResponse(line.to_string()));
+    }
+
+    let mut entry = XoverEntry::new(parts[0].parse().unwrap_or(0), parts[4])
+        .with_subject(parts[1])
+        .with_author(parts[2])
+        .with_references(parts[5])
+        .with_size(parts[6].parse().unwrap_or(0), parts[7].parse().unwrap_or(0));
+    // Set directly: `with_date` would use the current date parser
+    entry.date = parts[3].to_string();
+    entry.timestamp = parse_overview_date_legacy(parts[3]);
+    Ok(entry)
+}
+
+fn bench_overview_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("overview_parse");
+
+    for count in [1_000, 10_000].iter() {
+        group.throughput(Throughput::Elements(*count as u64));
+
+        let lines = generate_overview_lines(*count);
+
+        group.bench_with_input(BenchmarkId::new("legacy", count), count, |b, _| {
+            b.iter(|| {
+                for line in &lines {
+                    black_box(parse_xover_line_legacy(black_box(line)).ok());
+                }
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("owned", count), count, |b, _| {
+            b.iter(|| {
+                for line in &lines {
+                    black_box(parse_xover_line(black_box(line)).ok());
+                }
+            });
+        });
+
+        // Typical indexing access: number, message-id and size, no date parsing
+        group.bench_with_input(BenchmarkId::new("borrowed", count), count, |b, _| {
+            b.iter(|| {
+                for line in &lines {
+                    black_box(parse_xover_line_ref(black_box(line)).ok());
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_overview_parse);
+criterion_main!(benches);
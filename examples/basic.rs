@@ -12,17 +12,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Configure the NNTP server
     // Replace with your actual server credentials
-    let config = ServerConfig {
-        host: std::env::var("NNTP_HOST").unwrap_or_else(|_| "news.example.com".to_string()),
-        port: std::env::var("NNTP_PORT")
-            .ok()
-            .and_then(|p| p.parse().ok())
-            .unwrap_or(563),
-        tls: true,
-        allow_insecure_tls: false,
-        username: std::env::var("NNTP_USER").unwrap_or_else(|_| "user".to_string()),
-        password: std::env::var("NNTP_PASS").unwrap_or_else(|_| "pass".to_string()),
-    };
+    let host = std::env::var("NNTP_HOST").unwrap_or_else(|_| "news.example.com".to_string());
+    let port = std::env::var("NNTP_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(563);
+    let username = std::env::var("NNTP_USER").unwrap_or_else(|_| "user".to_string());
+    let password = std::env::var("NNTP_PASS").unwrap_or_else(|_| "pass".to_string());
+    let config = ServerConfig::new(host, port, true, username, password);
 
     println!("Connecting to {}:{}...", config.host, config.port);
 
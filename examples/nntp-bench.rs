@@ -0,0 +1,87 @@
+//! Measure download throughput through a connection pool
+//!
+//! Fetches the given article repeatedly over all pooled connections and
+//! reports articles per second and MB/s, plus the local yEnc decode rate.
+//!
+//! Run with: cargo run --features cli --example nntp-bench -- <message-id> [count]
+
+mod common;
+
+use nntp_rs::{NntpPool, yenc_decode};
+use std::error::Error;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+#[tokio::main]
+async fn main() -> Result<(), BoxError> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let (Some(message_id), count) = (args.next(), args.next()) else {
+        eprintln!("usage: nntp-bench <message-id> [count]");
+        std::process::exit(2);
+    };
+    let count: u64 = count.map(|c| c.parse()).transpose()?.unwrap_or(100);
+    let message_id = common::bracketed(&message_id);
+    let connections = common::connections_from_env();
+
+    let config = common::config_from_env();
+    println!(
+        "Fetching {} {} times over {} connection(s) to {}:{}",
+        message_id, count, connections, config.host, config.port
+    );
+    let pool = Arc::new(NntpPool::new(config, connections).await?);
+
+    // Warm up the pool and keep one body for the decode benchmark
+    let sample = pool.get().await?.fetch_body_binary(&message_id).await?.data;
+
+    let remaining = Arc::new(AtomicU64::new(count));
+    let bytes = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
+    let mut workers = JoinSet::new();
+    for _ in 0..connections {
+        let (pool, remaining, bytes) = (pool.clone(), remaining.clone(), bytes.clone());
+        let message_id = message_id.clone();
+        workers.spawn(async move {
+            let mut conn = pool.get().await?;
+            while remaining
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                let body = conn.fetch_body_binary(&message_id).await?;
+                bytes.fetch_add(body.data.len() as u64, Ordering::Relaxed);
+            }
+            Ok::<_, nntp_rs::NntpError>(())
+        });
+    }
+    while let Some(result) = workers.join_next().await {
+        result??;
+    }
+
+    let elapsed = started.elapsed();
+    let bytes = bytes.load(Ordering::Relaxed);
+    println!(
+        "Network: {} articles in {:.2?} ({:.1} articles/s, {})",
+        count,
+        elapsed,
+        count as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        common::rate(bytes, elapsed)
+    );
+
+    if yenc_decode(&sample).is_ok() {
+        let started = Instant::now();
+        let rounds = 100;
+        for _ in 0..rounds {
+            yenc_decode(&sample)?;
+        }
+        println!(
+            "yEnc decode: {}",
+            common::rate(sample.len() as u64 * rounds, started.elapsed())
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! Download the files described by an NZB
+//!
+//! Fetches every segment through a connection pool, decodes and CRC-checks
+//! the yEnc parts and writes the reassembled files to the output directory.
+//!
+//! Run with: cargo run --features cli --example nntp-get -- <nzb> [output-dir]
+
+mod common;
+
+use nntp_rs::{NntpPool, NzbFile, YencDecoded, YencMultipartAssembler, parse_nzb, yenc_decode};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+#[tokio::main]
+async fn main() -> Result<(), BoxError> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let (Some(nzb_path), out_dir) = (args.next(), args.next()) else {
+        eprintln!("usage: nntp-get <nzb> [output-dir]");
+        std::process::exit(2);
+    };
+    let out_dir = PathBuf::from(out_dir.unwrap_or_else(|| ".".to_string()));
+
+    let nzb = parse_nzb(&std::fs::read_to_string(&nzb_path)?)?;
+    let config = common::config_from_env();
+    println!(
+        "Downloading {} file(s) from {}:{}",
+        nzb.files.len(),
+        config.host,
+        config.port
+    );
+    let pool = Arc::new(NntpPool::new(config, common::connections_from_env()).await?);
+
+    let started = Instant::now();
+    let mut total = 0;
+    for file in &nzb.files {
+        let (path, bytes) = download_file(&pool, file, &out_dir).await?;
+        println!("  {} ({} bytes)", path.display(), bytes);
+        total += bytes;
+    }
+    println!(
+        "Done: {} bytes in {:.1?} ({})",
+        total,
+        started.elapsed(),
+        common::rate(total, started.elapsed())
+    );
+    Ok(())
+}
+
+/// Fetch all segments of `file` in parallel and write the decoded file
+async fn download_file(
+    pool: &Arc<NntpPool>,
+    file: &NzbFile,
+    out_dir: &Path,
+) -> Result<(PathBuf, u64), BoxError> {
+    let mut fetches = JoinSet::new();
+    for segment in &file.segments {
+        let pool = pool.clone();
+        let message_id = common::bracketed(&segment.message_id);
+        fetches.spawn(async move {
+            let mut conn = pool.get().await?;
+            let response = conn.fetch_body_binary(&message_id).await?;
+            yenc_decode(&response.data)
+        });
+    }
+
+    let mut parts = Vec::new();
+    while let Some(result) = fetches.join_next().await {
+        parts.push(result??);
+    }
+    let (name, data) = assemble(parts)?;
+
+    // Never let a crafted yEnc name escape the output directory
+    let name = Path::new(&name)
+        .file_name()
+        .ok_or_else(|| format!("invalid file name in yEnc header: {:?}", name))?;
+    let path = out_dir.join(name);
+    std::fs::write(&path, &data)?;
+    Ok((path, data.len() as u64))
+}
+
+/// Join decoded parts into the original file, verifying every CRC32
+fn assemble(mut parts: Vec<YencDecoded>) -> Result<(String, Vec<u8>), BoxError> {
+    if parts.len() == 1 && !parts[0].is_multipart() {
+        let part = parts.remove(0);
+        if part.verify_crc32() == Some(false) {
+            return Err(format!("{}: CRC32 mismatch", part.header.name).into());
+        }
+        return Ok((part.header.name, part.data));
+    }
+
+    let mut assembler = YencMultipartAssembler::new();
+    for part in parts {
+        assembler.add_part(part)?;
+    }
+    let missing = assembler.missing_parts();
+    if !missing.is_empty() {
+        return Err(format!("missing parts: {:?}", missing).into());
+    }
+    let name = assembler.filename().unwrap_or("download.bin").to_string();
+    Ok((name, assembler.assemble()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nntp_rs::{NzbSegment, yenc_encode};
+
+    #[tokio::test]
+    async fn test_downloads_multipart_file() {
+        let data: Vec<u8> = (0..3000u32).map(|i| (i % 251) as u8).collect();
+        let store = common::mock::Store::default();
+        let mut segments = Vec::new();
+        for (i, chunk) in data.chunks(1000).enumerate() {
+            let begin = (i * 1000) as u64 + 1;
+            let end = begin + chunk.len() as u64 - 1;
+            let part = (i + 1) as u32;
+            let body = yenc_encode(chunk, "data.bin", 128, Some((part, 3, begin, end, 3000)));
+            let message_id = format!("part{}@test", part);
+            store
+                .lock()
+                .unwrap()
+                .insert(common::bracketed(&message_id), body.unwrap());
+            segments.push(NzbSegment {
+                bytes: chunk.len() as u64,
+                number: part,
+                message_id,
+            });
+        }
+
+        let config = common::mock::server(store).await;
+        let pool = Arc::new(NntpPool::new(config, 2).await.unwrap());
+        let file = NzbFile {
+            poster: "poster@test".to_string(),
+            date: 0,
+            subject: "data.bin".to_string(),
+            groups: vec!["alt.test".to_string()],
+            segments,
+        };
+        let dir = std::env::temp_dir().join(format!("nntp-get-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (path, bytes) = download_file(&pool, &file, &dir).await.unwrap();
+        assert_eq!(path, dir.join("data.bin"));
+        assert_eq!(bytes, 3000);
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_segment_fails() {
+        let config = common::mock::server(common::mock::Store::default()).await;
+        let pool = Arc::new(NntpPool::new(config, 1).await.unwrap());
+        let file = NzbFile {
+            poster: String::new(),
+            date: 0,
+            subject: String::new(),
+            groups: Vec::new(),
+            segments: vec![NzbSegment {
+                bytes: 10,
+                number: 1,
+                message_id: "gone@test".to_string(),
+            }],
+        };
+        let result = download_file(&pool, &file, &std::env::temp_dir()).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Post a file as yEnc-encoded articles and write an NZB for it
+//!
+//! The file is split into parts of `NNTP_PART_SIZE` bytes (default 700000),
+//! each part is yEnc-encoded and posted through a connection pool, and the
+//! resulting NZB is written next to the file as `<file>.nzb`.
+//!
+//! Run with: cargo run --features cli --example nntp-post -- <file> [newsgroup]
+
+mod common;
+
+use nntp_rs::{NntpPool, Nzb, NzbFile, NzbSegment, yenc_encode};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// Default size of each posted part in bytes
+const DEFAULT_PART_SIZE: usize = 700_000;
+
+/// yEnc line length used for posting
+const LINE_LENGTH: usize = 128;
+
+#[tokio::main]
+async fn main() -> Result<(), BoxError> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let (Some(path), group) = (args.next(), args.next()) else {
+        eprintln!("usage: nntp-post <file> [newsgroup]");
+        std::process::exit(2);
+    };
+    let group = group.unwrap_or_else(|| "alt.binaries.test".to_string());
+    let part_size = std::env::var("NNTP_PART_SIZE")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_PART_SIZE);
+    let poster = std::env::var("NNTP_FROM").unwrap_or_else(|_| "poster@example.com".to_string());
+
+    let config = common::config_from_env();
+    let pool = Arc::new(NntpPool::new(config, common::connections_from_env()).await?);
+
+    let started = Instant::now();
+    let data = std::fs::read(&path)?;
+    let file = post_file(&pool, Path::new(&path), &data, &group, &poster, part_size).await?;
+    println!(
+        "Posted {} part(s), {} bytes in {:.1?} ({})",
+        file.segments.len(),
+        data.len(),
+        started.elapsed(),
+        common::rate(data.len() as u64, started.elapsed())
+    );
+
+    let nzb = Nzb {
+        meta: HashMap::new(),
+        files: vec![file],
+    };
+    let nzb_path = format!("{}.nzb", path);
+    std::fs::write(&nzb_path, nzb.to_xml())?;
+    println!("Wrote {}", nzb_path);
+    Ok(())
+}
+
+/// Post `data` in parts and describe the posted segments
+async fn post_file(
+    pool: &Arc<NntpPool>,
+    path: &Path,
+    data: &[u8],
+    group: &str,
+    poster: &str,
+    part_size: usize,
+) -> Result<NzbFile, BoxError> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("file name is not valid UTF-8")?;
+    let total = data.len().div_ceil(part_size).max(1) as u32;
+
+    let mut posts = JoinSet::new();
+    for (index, chunk) in data.chunks(part_size.max(1)).enumerate() {
+        let number = index as u32 + 1;
+        let begin = (index * part_size) as u64 + 1;
+        let end = begin + chunk.len() as u64 - 1;
+        let body = yenc_encode(
+            chunk,
+            name,
+            LINE_LENGTH,
+            Some((number, total, begin, end, data.len() as u64)),
+        )?;
+        let message_id = format!("<{}@nntp-rs>", uuid::Uuid::new_v4());
+        let subject = format!("{} yEnc ({}/{})", name, number, total);
+        let article = raw_article(poster, group, &subject, &message_id, &body);
+
+        let pool = pool.clone();
+        let bytes = chunk.len() as u64;
+        posts.spawn(async move {
+            pool.get().await?.post_raw(&article).await?;
+            Ok::<_, nntp_rs::NntpError>(NzbSegment {
+                bytes,
+                number,
+                message_id: message_id.trim_matches(['<', '>']).to_string(),
+            })
+        });
+    }
+
+    let mut segments = Vec::new();
+    while let Some(result) = posts.join_next().await {
+        segments.push(result??);
+    }
+    segments.sort_by_key(|s| s.number);
+
+    Ok(NzbFile {
+        poster: poster.to_string(),
+        date: chrono::Utc::now().timestamp(),
+        subject: format!("{} yEnc (1/{})", name, total),
+        groups: vec![group.to_string()],
+        segments,
+    })
+}
+
+/// Serialize an article with a binary body in wire format (CRLF, dot-stuffed)
+fn raw_article(from: &str, group: &str, subject: &str, message_id: &str, body: &[u8]) -> Vec<u8> {
+    let mut article = format!(
+        "From: {}\r\nNewsgroups: {}\r\nSubject: {}\r\nMessage-ID: {}\r\n\r\n",
+        from, group, subject, message_id
+    )
+    .into_bytes();
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b".") {
+            article.push(b'.');
+        }
+        article.extend_from_slice(line);
+    }
+    article
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nntp_rs::{YencMultipartAssembler, yenc_decode};
+
+    /// Undo dot-stuffing of a stored article body
+    fn unstuff(body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(body.len());
+        for line in body.split_inclusive(|&b| b == b'\n') {
+            let line = if line.starts_with(b"..") {
+                &line[1..]
+            } else {
+                line
+            };
+            out.extend_from_slice(line);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn test_posts_file_in_parts() {
+        let data: Vec<u8> = (0..2500u32).map(|i| (i * 7 % 256) as u8).collect();
+        let store = common::mock::Store::default();
+        let config = common::mock::server(store.clone()).await;
+        let pool = Arc::new(NntpPool::new(config, 2).await.unwrap());
+
+        let file = post_file(
+            &pool,
+            Path::new("dir/a.bin"),
+            &data,
+            "alt.test",
+            "me@test",
+            1000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(file.segments.len(), 3);
+        assert_eq!(file.total_bytes(), 2500);
+        file.validate_segments().unwrap();
+
+        // Every posted part decodes and the parts reassemble to the file
+        let mut assembler = YencMultipartAssembler::new();
+        for segment in &file.segments {
+            let body = store.lock().unwrap()[&common::bracketed(&segment.message_id)].clone();
+            let unstuffed = unstuff(&body);
+            assembler
+                .add_part(yenc_decode(&unstuffed).unwrap())
+                .unwrap();
+        }
+        assert_eq!(assembler.filename(), Some("a.bin"));
+        assert_eq!(assembler.assemble().unwrap(), data);
+    }
+
+    #[test]
+    fn test_raw_article_dot_stuffs_body() {
+        let article = raw_article("a@b", "alt.test", "s", "<m@b>", b"=ybegin\r\n.x\r\ny\r\n");
+        assert!(article.ends_with(b"\r\n\r\n=ybegin\r\n..x\r\ny\r\n"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Verify downloaded files against the PAR2 sets in a directory
+//!
+//! Every `*.par2` file without `.vol` in its name starts a set; its volumes
+//! are merged, the described files are read from the directory and checked
+//! slice by slice. Exits with status 1 if any file is damaged or missing.
+//!
+//! Run with: cargo run --features cli --example nntp-verify -- <dir>
+
+use nntp_rs::{FileStatus, Par2Set};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let Some(dir) = std::env::args().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: nntp-verify <dir>");
+        std::process::exit(2);
+    };
+
+    let mut all_ok = true;
+    for base in par2_base_names(&dir)? {
+        all_ok &= verify_set(&dir, &base)?;
+    }
+    if !all_ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Base names of the main PAR2 files in `dir`, sorted
+fn par2_base_names(dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| !name.contains(".vol"))
+        .filter_map(|name| name.strip_suffix(".par2").map(str::to_string))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Verify one PAR2 set, printing a line per file; returns whether all files are intact
+fn verify_set(dir: &Path, base: &str) -> Result<bool, Box<dyn Error>> {
+    let set = Par2Set::discover(dir, base)?;
+    println!(
+        "{}: {} file(s), {} recovery slice(s) in {} PAR2 file(s)",
+        base,
+        set.main.file_descriptions.len(),
+        set.total_recovery_slices,
+        set.files.len()
+    );
+
+    // Descriptions only carry the file name, never a path
+    let mut data = HashMap::new();
+    for (id, description) in &set.main.file_descriptions {
+        if let Some(name) = Path::new(&*description.name).file_name()
+            && let Ok(contents) = std::fs::read(dir.join(name))
+        {
+            data.insert(*id, contents);
+        }
+    }
+
+    let mut results = set.main.verify_all(&data)?;
+    results.sort_by(|a, b| a.filename.cmp(&b.filename));
+    let mut intact = true;
+    for result in &results {
+        let status = match &result.status {
+            FileStatus::Complete => "ok".to_string(),
+            FileStatus::Damaged(slices) => format!("damaged ({} slice(s))", slices.len()),
+            FileStatus::Missing => "missing".to_string(),
+        };
+        intact &= result.status == FileStatus::Complete;
+        println!("  {}: {}", result.filename, status);
+    }
+
+    if !intact {
+        let repairable = set.can_recover(&data)?;
+        println!(
+            "  {}",
+            if repairable {
+                "repair possible"
+            } else {
+                "not enough recovery data"
+            }
+        );
+    }
+    Ok(intact)
+}
@@ -14,17 +14,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
     // Configure the NNTP server
-    let config = ServerConfig {
-        host: std::env::var("NNTP_HOST").unwrap_or_else(|_| "news.example.com".to_string()),
-        port: std::env::var("NNTP_PORT")
-            .ok()
-            .and_then(|p| p.parse().ok())
-            .unwrap_or(563),
-        tls: true,
-        allow_insecure_tls: false,
-        username: std::env::var("NNTP_USER").unwrap_or_else(|_| "user".to_string()),
-        password: std::env::var("NNTP_PASS").unwrap_or_else(|_| "pass".to_string()),
-    };
+    let host = std::env::var("NNTP_HOST").unwrap_or_else(|_| "news.example.com".to_string());
+    let port = std::env::var("NNTP_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(563);
+    let username = std::env::var("NNTP_USER").unwrap_or_else(|_| "user".to_string());
+    let password = std::env::var("NNTP_PASS").unwrap_or_else(|_| "pass".to_string());
+    let config = ServerConfig::new(host, port, true, username, password);
 
     // Create a connection pool with custom retry config
     let retry_config = RetryConfig {
@@ -33,6 +30,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         max_backoff_ms: 5000,
         backoff_multiplier: 2.0,
         jitter: true,
+        connection_limit_cooldown_ms: 60_000,
     };
 
     let pool_size = 5;
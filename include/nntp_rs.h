@@ -0,0 +1,124 @@
+/*
+ * nntp_rs.h - C ABI for the nntp-rs decoding and verification primitives
+ *
+ * Build the library with the `ffi` feature:
+ *
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ *   cargo rustc --release --lib --features ffi --crate-type staticlib
+ *
+ * Functions returning int32_t return NNTP_OK on success or a negative
+ * NNTP_ERR_* code; nntp_last_error() describes the last failure on the
+ * calling thread. Everything allocated by the library must be released with
+ * the matching nntp_*_free function. Input pointers may be NULL only when the
+ * accompanying length is 0.
+ */
+
+#ifndef NNTP_RS_H
+#define NNTP_RS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define NNTP_OK 0
+#define NNTP_ERR_INVALID_ARGUMENT (-1)
+#define NNTP_ERR_INVALID_DATA (-2)
+#define NNTP_ERR_NOT_FOUND (-3)
+#define NNTP_ERR_PANIC (-99)
+
+#define NNTP_CRC_UNKNOWN (-1)
+#define NNTP_CRC_MISMATCH 0
+#define NNTP_CRC_OK 1
+
+#define NNTP_PAR2_COMPLETE 0
+#define NNTP_PAR2_DAMAGED 1
+#define NNTP_PAR2_MISSING 2
+
+/* Byte buffer allocated by the library; release with nntp_buffer_free */
+typedef struct NntpBuffer {
+    uint8_t *data;
+    size_t len;
+} NntpBuffer;
+
+void nntp_buffer_free(NntpBuffer *buffer);
+
+/* Last error message on this thread, or NULL */
+const char *nntp_last_error(void);
+
+/* ---- yEnc ---------------------------------------------------------------- */
+
+typedef struct NntpYencDecoded {
+    NntpBuffer data;     /* decoded bytes; release with nntp_buffer_free */
+    uint32_t crc32;      /* CRC32 of the decoded bytes */
+    int32_t crc_status;  /* NNTP_CRC_OK, NNTP_CRC_MISMATCH or NNTP_CRC_UNKNOWN */
+    uint64_t file_size;  /* total file size from =ybegin */
+    uint32_t part;       /* part number (0 for single-part files) */
+    uint32_t total;      /* total parts (0 if not given) */
+    uint64_t begin;      /* 1-based offset of the part in the file */
+} NntpYencDecoded;
+
+typedef struct NntpYencPartInfo {
+    uint32_t part;
+    uint32_t total;
+    uint64_t begin;      /* 1-based, inclusive */
+    uint64_t end;        /* 1-based, inclusive */
+    uint64_t file_size;
+} NntpYencPartInfo;
+
+int32_t nntp_yenc_decode(const uint8_t *input, size_t len, NntpYencDecoded *out);
+
+/* line_length 0 uses 128; part_info NULL encodes a single-part file */
+int32_t nntp_yenc_encode(const uint8_t *data, size_t len, const char *filename,
+                         size_t line_length, const NntpYencPartInfo *part_info,
+                         NntpBuffer *out);
+
+/* ---- Streaming CRC32 / MD5 ----------------------------------------------- */
+
+typedef struct NntpCrc32 NntpCrc32;
+typedef struct NntpMd5 NntpMd5;
+
+NntpCrc32 *nntp_crc32_new(void);
+int32_t nntp_crc32_update(NntpCrc32 *state, const uint8_t *data, size_t len);
+uint32_t nntp_crc32_value(const NntpCrc32 *state);
+void nntp_crc32_free(NntpCrc32 *state);
+
+NntpMd5 *nntp_md5_new(void);
+int32_t nntp_md5_update(NntpMd5 *state, const uint8_t *data, size_t len);
+int32_t nntp_md5_value(const NntpMd5 *state, uint8_t digest[16]);
+void nntp_md5_free(NntpMd5 *state);
+
+/* ---- PAR2 ---------------------------------------------------------------- */
+
+typedef struct NntpPar2 NntpPar2;
+
+typedef struct NntpPar2FileInfo {
+    uint8_t file_id[16];
+    const char *name;    /* owned by the NntpPar2 handle */
+    uint64_t length;
+} NntpPar2FileInfo;
+
+typedef struct NntpPar2Verification {
+    int32_t status;           /* NNTP_PAR2_COMPLETE, _DAMAGED or _MISSING */
+    uint64_t *damaged_slices; /* file-local slice indices, NULL if none */
+    size_t damaged_count;
+} NntpPar2Verification;
+
+/* Returns NULL on failure or if no files are described (see nntp_last_error) */
+NntpPar2 *nntp_par2_parse(const uint8_t *data, size_t len);
+size_t nntp_par2_file_count(const NntpPar2 *par2);
+int32_t nntp_par2_file_info(const NntpPar2 *par2, size_t index, NntpPar2FileInfo *out);
+/* Pass len 0 for a missing file */
+int32_t nntp_par2_verify_file(const NntpPar2 *par2, const uint8_t file_id[16],
+                              const uint8_t *data, size_t len,
+                              NntpPar2Verification *out);
+void nntp_par2_verification_free(NntpPar2Verification *result);
+void nntp_par2_free(NntpPar2 *par2);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* NNTP_RS_H */
@@ -2,9 +2,10 @@
 //!
 //! This module provides the ArticleBuilder for creating RFC 5536 compliant articles.
 
-use std::collections::HashMap;
-
-use super::types::{Article, Headers};
+use super::HeaderMap;
+use super::policy::InjectionInfo;
+use super::types::{Article, ControlMessage, Headers};
+use crate::cancel_lock::CancelSecret;
 use crate::{NntpError, Result};
 
 #[must_use]
@@ -14,6 +15,7 @@ pub struct ArticleBuilder {
     subject: Option<String>,
     newsgroups: Vec<String>,
     body: String,
+    body_bytes: Option<Vec<u8>>,
     // Optional headers
     date: Option<String>,
     message_id: Option<String>,
@@ -30,7 +32,9 @@ pub struct ArticleBuilder {
     supersedes: Option<String>,
     approved: Option<String>,
     user_agent: Option<String>,
-    extra: HashMap<String, String>,
+    extra: HeaderMap,
+    cancel_secret: Option<CancelSecret>,
+    generate_injection_date: bool,
 }
 
 impl Default for ArticleBuilder {
@@ -47,6 +51,7 @@ impl ArticleBuilder {
             subject: None,
             newsgroups: Vec::new(),
             body: String::new(),
+            body_bytes: None,
             date: None,
             message_id: None,
             path: None,
@@ -62,7 +67,9 @@ impl ArticleBuilder {
             supersedes: None,
             approved: None,
             user_agent: None,
-            extra: HashMap::new(),
+            extra: HeaderMap::new(),
+            cancel_secret: None,
+            generate_injection_date: false,
         }
     }
 
@@ -93,9 +100,63 @@ impl ArticleBuilder {
     /// Set the article body
     pub fn body(mut self, body: impl Into<String>) -> Self {
         self.body = body.into();
+        self.body_bytes = None;
+        self
+    }
+
+    /// Set the article body as bytes, e.g. yEnc-encoded data or 8-bit text
+    ///
+    /// The bytes are posted as they are, without a lossy UTF-8 conversion;
+    /// see [`Article::with_body_bytes`].
+    pub fn body_bytes(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body_bytes = Some(body.into());
+        self.body = String::new();
         self
     }
 
+    /// Set the article body as format=flowed text (RFC 3676)
+    ///
+    /// Each line of `text` is a paragraph, wrapped at
+    /// [`flowed::LINE_WIDTH`](crate::flowed::LINE_WIDTH) columns with soft
+    /// line breaks (see [`flowed::flow`](crate::flowed::flow)), so readers
+    /// that support format=flowed can rewrap it. Sets Content-Type to
+    /// `text/plain; charset=utf-8; format=flowed`, and MIME-Version if it
+    /// is not set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let paragraph = "word ".repeat(30);
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Flowed")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .flowed_body(paragraph.trim_end())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(article.body.lines().count() > 1);
+    /// assert_eq!(article.unflowed_body().unwrap(), paragraph.trim_end());
+    /// ```
+    pub fn flowed_body(mut self, text: impl AsRef<str>) -> Self {
+        let text = text.as_ref();
+        if !self.extra.contains_key("MIME-Version") {
+            self.extra
+                .insert("MIME-Version".to_string(), "1.0".to_string());
+        }
+        self.extra.insert(
+            "Content-Type".to_string(),
+            "text/plain; charset=utf-8; format=flowed".to_string(),
+        );
+        if !text.is_ascii() && !self.extra.contains_key("Content-Transfer-Encoding") {
+            self.extra
+                .insert("Content-Transfer-Encoding".to_string(), "8bit".to_string());
+        }
+        self.body(crate::flowed::flow(text, crate::flowed::LINE_WIDTH))
+    }
+
     /// Set the Date header (auto-generated if not provided)
     pub fn date(mut self, date: impl Into<String>) -> Self {
         self.date = Some(date.into());
@@ -189,12 +250,211 @@ impl ArticleBuilder {
         self
     }
 
+    /// Add `X-No-Archive: yes`, asking archives not to keep the article
+    pub fn x_no_archive(mut self) -> Self {
+        self.extra
+            .insert("X-No-Archive".to_string(), "yes".to_string());
+        self
+    }
+
+    /// Set the Archive header to `yes` or `no` (RFC 5536 Section 3.2.2)
+    pub fn archive(mut self, allowed: bool) -> Self {
+        let value = if allowed { "yes" } else { "no" };
+        self.extra.insert("Archive".to_string(), value.to_string());
+        self
+    }
+
+    /// Set the Injection-Date header (RFC 5536 Section 3.2.7)
+    ///
+    /// Only an injecting agent should set this; see
+    /// [`generate_injection_date`](Self::generate_injection_date).
+    pub fn injection_date(mut self, date: impl Into<String>) -> Self {
+        self.extra.insert("Injection-Date".to_string(), date.into());
+        self
+    }
+
+    /// Set the Injection-Info header (RFC 5536 Section 3.2.8)
+    pub fn injection_info(mut self, info: &InjectionInfo) -> Self {
+        self.extra
+            .insert("Injection-Info".to_string(), info.to_string());
+        self
+    }
+
+    /// Add an Injection-Date of the time of [`build`](Self::build), unless
+    /// one is set
+    ///
+    /// For gateways and feeders that inject articles themselves, e.g. with
+    /// IHAVE (RFC 5537 Section 3.5). Articles sent with POST get their
+    /// Injection-Date from the server.
+    pub fn generate_injection_date(mut self) -> Self {
+        self.generate_injection_date = true;
+        self
+    }
+
     /// Add a custom header
     pub fn extra_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
         self.extra.insert(name.into(), value.into());
         self
     }
 
+    /// Start a cancel control message for `original` (RFC 5537 Section 5.3)
+    ///
+    /// Sets Control to `cancel <message-id>`, Subject to
+    /// `cmsg cancel <message-id>`, and Newsgroups and Distribution to those
+    /// of the original. From is still required; add
+    /// [`cancel_secret`](Self::cancel_secret) so servers that check
+    /// Cancel-Lock honor the cancel.
+    pub fn cancel_of(original: &Article) -> Self {
+        let message_id = &original.headers.message_id;
+        let mut builder = Self::new()
+            .control(format!("cancel {message_id}"))
+            .subject(format!("cmsg cancel {message_id}"))
+            .newsgroups(original.headers.newsgroups.clone());
+        builder.distribution = original.headers.distribution.clone();
+        builder
+    }
+
+    /// Start a replacement for `original` (RFC 5537 Section 5.4)
+    ///
+    /// Copies the original's headers and body and sets Supersedes to its
+    /// Message-ID. Date, Message-ID and Path are generated afresh on
+    /// [`build`](Self::build), and headers a server added on injection
+    /// (Injection-Info, NNTP-Posting-Host, X-Trace, ...) and the old
+    /// Cancel-Lock and Cancel-Key are left out. Change what needs editing,
+    /// then build; add [`cancel_secret`](Self::cancel_secret) so servers
+    /// that check Cancel-Lock honor the replacement.
+    ///
+    /// A control message cannot be superseded: its Control header is kept,
+    /// so [`build`](Self::build) fails as for any article with both headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let original = ArticleBuilder::new()
+    ///     .from("alice@example.com")
+    ///     .subject("Meeting on Mondy")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .message_id("<meeting@example.com>")
+    ///     .body("See you there.")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let fixed = ArticleBuilder::supersede(&original)
+    ///     .subject("Meeting on Monday")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(fixed.headers.supersedes.as_deref(), Some("<meeting@example.com>"));
+    /// assert_ne!(fixed.headers.message_id, original.headers.message_id);
+    /// assert_eq!(fixed.body, "See you there.");
+    /// ```
+    pub fn supersede(original: &Article) -> Self {
+        let headers = original.headers.clone();
+        let mut builder = Self::new()
+            .from(headers.from)
+            .subject(headers.subject)
+            .newsgroups(headers.newsgroups)
+            .supersedes(headers.message_id);
+        builder.references = headers.references;
+        builder.reply_to = headers.reply_to;
+        builder.organization = headers.organization;
+        builder.followup_to = headers.followup_to;
+        builder.expires = headers.expires;
+        builder.control = headers.control;
+        builder.distribution = headers.distribution;
+        builder.keywords = headers.keywords;
+        builder.summary = headers.summary;
+        builder.approved = headers.approved;
+        builder.user_agent = headers.user_agent;
+        for (name, value) in headers.extra {
+            if !NOT_SUPERSEDED
+                .iter()
+                .any(|dropped| name.eq_ignore_ascii_case(dropped))
+            {
+                builder.extra.append(name, value);
+            }
+        }
+        if original.has_binary_body() {
+            builder.body_bytes(original.body_bytes().to_vec())
+        } else {
+            builder.body(original.body.clone())
+        }
+    }
+
+    /// Add Cancel-Lock and Cancel-Key headers derived from `secret` (RFC 8315)
+    ///
+    /// On [`build`](Self::build), a cancel gets a Cancel-Key for the article
+    /// it cancels. Any other article gets a Cancel-Lock for its own
+    /// Message-ID, so it can be cancelled or superseded later, and, if it
+    /// supersedes one, a Cancel-Key for that one. Headers already set with
+    /// [`extra_header`](Self::extra_header) are kept.
+    pub fn cancel_secret(mut self, secret: &CancelSecret) -> Self {
+        self.cancel_secret = Some(secret.clone());
+        self
+    }
+
+    /// Fill in the headers of a followup to `parent` (RFC 5537 Section 3.4.3)
+    ///
+    /// - References: the parent's References with its Message-ID appended,
+    ///   shortened if the header would exceed 998 octets by dropping the
+    ///   oldest IDs after the first and keeping at least the first and
+    ///   last three
+    /// - Subject: the parent's, with "Re: " prepended unless it already
+    ///   starts with "Re:" (in any case)
+    /// - Newsgroups: the parent's Followup-To, or its Newsgroups if it has
+    ///   none
+    /// - Distribution: copied from the parent
+    ///
+    /// A Followup-To of `poster` asks for replies by email rather than in
+    /// the newsgroups, so Newsgroups is left empty and [`build`](Self::build)
+    /// fails unless newsgroups are set explicitly afterwards. Headers set
+    /// after this call override the derived ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let parent = ArticleBuilder::new()
+    ///     .from("alice@example.com")
+    ///     .subject("Lifetimes")
+    ///     .newsgroups(vec!["comp.lang.rust"])
+    ///     .message_id("<q1@example.com>")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let reply = ArticleBuilder::new()
+    ///     .reply_to_article(&parent)
+    ///     .from("bob@example.com")
+    ///     .body("Use 'static.")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(reply.headers.subject, "Re: Lifetimes");
+    /// assert_eq!(reply.headers.newsgroups, vec!["comp.lang.rust"]);
+    /// assert_eq!(reply.headers.references, Some(vec!["<q1@example.com>".to_string()]));
+    /// ```
+    pub fn reply_to_article(mut self, parent: &Article) -> Self {
+        let parent_headers = &parent.headers;
+
+        let mut references = parent_headers.references.clone().unwrap_or_default();
+        references.push(parent_headers.message_id.clone());
+        self.references = Some(trim_references(references));
+
+        self.subject = Some(reply_subject(&parent_headers.subject));
+
+        self.newsgroups = match parent_headers.followup_to.as_deref() {
+            Some([poster]) if poster.trim().eq_ignore_ascii_case("poster") => Vec::new(),
+            Some(followup_to) if !followup_to.is_empty() => followup_to.to_vec(),
+            _ => parent_headers.newsgroups.clone(),
+        };
+
+        if let Some(ref distribution) = parent_headers.distribution {
+            self.distribution = Some(distribution.clone());
+        }
+        self
+    }
+
     /// Build the article, validating required fields and generating defaults
     ///
     /// Returns an error if required fields (From, Subject, Newsgroups) are missing.
@@ -222,10 +482,10 @@ impl ArticleBuilder {
         }
 
         // Auto-generate Date if not provided
-        let date = self.date.unwrap_or_else(|| {
-            use chrono::Utc;
-            Utc::now().format("%a, %d %b %Y %H:%M:%S %z").to_string()
-        });
+        let now = chrono::Utc::now()
+            .format("%a, %d %b %Y %H:%M:%S %z")
+            .to_string();
+        let date = self.date.unwrap_or_else(|| now.clone());
 
         // Auto-generate Message-ID if not provided
         let message_id = self.message_id.unwrap_or_else(|| {
@@ -243,6 +503,20 @@ impl ArticleBuilder {
         // Default Path to "not-for-mail" (will be updated by news server)
         let path = self.path.unwrap_or_else(|| "not-for-mail".to_string());
 
+        let mut extra = self.extra;
+        if self.generate_injection_date && !extra.contains_key("Injection-Date") {
+            extra.insert("Injection-Date".to_string(), now);
+        }
+        if let Some(ref secret) = self.cancel_secret {
+            add_cancel_headers(
+                &mut extra,
+                secret,
+                &message_id,
+                self.control.as_deref(),
+                self.supersedes.as_deref(),
+            );
+        }
+
         let headers = Headers {
             date,
             from,
@@ -264,13 +538,49 @@ impl ArticleBuilder {
             lines: None, // Will be calculated by server
             user_agent: self.user_agent,
             xref: None, // Will be added by server
-            extra: self.extra,
+            extra,
         };
 
-        Ok(Article {
-            headers,
-            body: self.body,
-            raw: None,
+        Ok(match self.body_bytes {
+            Some(body) => Article::with_body_bytes(headers, body),
+            None => Article::new(headers, self.body),
+        })
+    }
+
+    /// Build the article as parts that each fit in `max_article_size` bytes
+    ///
+    /// See [`Article::split`] for how the parts are cut, numbered and
+    /// named. With a [`cancel_secret`](Self::cancel_secret), every part
+    /// gets the Cancel-Lock of its own Message-ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let parts = ArticleBuilder::new()
+    ///     .from("poster@example.com")
+    ///     .subject("Long text")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .body("paragraph\n".repeat(5000))
+    ///     .build_parts(16 * 1024)
+    ///     .unwrap();
+    /// assert_eq!(parts.len(), 4);
+    /// assert!(parts[3].headers.subject.ends_with("(4/4)"));
+    /// ```
+    pub fn build_parts(self, max_article_size: usize) -> Result<Vec<Article>> {
+        let secret = self.cancel_secret.clone();
+        let article = self.build()?;
+        // Only a lock the secret added is replaced, not one set explicitly
+        let secret = secret.filter(|secret| {
+            article.headers.extra.get("Cancel-Lock")
+                == Some(&secret.lock(&article.headers.message_id))
+        });
+        article.split_with(max_article_size, |headers| {
+            if let Some(ref secret) = secret {
+                let lock = secret.lock(&headers.message_id);
+                headers.extra.insert("Cancel-Lock".to_string(), lock);
+            }
         })
     }
 
@@ -283,3 +593,78 @@ impl ArticleBuilder {
         article.serialize_for_posting()
     }
 }
+
+/// Headers of an original that its replacement does not inherit: those
+/// added by the injecting server, and the original's own cancel headers
+const NOT_SUPERSEDED: &[&str] = &[
+    "Injection-Date",
+    "Injection-Info",
+    "NNTP-Posting-Date",
+    "NNTP-Posting-Host",
+    "X-Complaints-To",
+    "X-Trace",
+    "Cancel-Lock",
+    "Cancel-Key",
+];
+
+/// Add the RFC 8315 headers an article with `message_id` should carry
+fn add_cancel_headers(
+    extra: &mut HeaderMap,
+    secret: &CancelSecret,
+    message_id: &str,
+    control: Option<&str>,
+    supersedes: Option<&str>,
+) {
+    if let Some(control) = control {
+        if let Some(ControlMessage::Cancel { message_id: target }) = ControlMessage::parse(control)
+            && !extra.contains_key("Cancel-Key")
+        {
+            extra.insert("Cancel-Key".to_string(), secret.key(&target));
+        }
+        return;
+    }
+    if !extra.contains_key("Cancel-Lock") {
+        extra.insert("Cancel-Lock".to_string(), secret.lock(message_id));
+    }
+    if let Some(target) = supersedes
+        && !extra.contains_key("Cancel-Key")
+    {
+        extra.insert("Cancel-Key".to_string(), secret.key(target.trim()));
+    }
+}
+
+/// Longest References header line, name included (RFC 5322 Section 2.1.1)
+const MAX_REFERENCES_LINE: usize = 998;
+
+/// Message-IDs at the end of References that are never dropped
+const KEPT_RECENT_REFERENCES: usize = 3;
+
+/// Subject of a followup to an article with `subject`
+fn reply_subject(subject: &str) -> String {
+    let has_prefix = subject
+        .get(..3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("re:"));
+    if has_prefix {
+        subject.to_string()
+    } else {
+        format!("Re: {subject}")
+    }
+}
+
+/// Shorten References to fit one header line (RFC 5537 Section 3.4.4)
+///
+/// The first Message-ID names the thread root and the last ones the
+/// nearest ancestors, so IDs are dropped from just after the first.
+fn trim_references(mut references: Vec<String>) -> Vec<String> {
+    let line_len = |references: &[String]| {
+        "References: ".len()
+            + references.iter().map(String::len).sum::<usize>()
+            + references.len().saturating_sub(1)
+    };
+    while line_len(&references) > MAX_REFERENCES_LINE
+        && references.len() > KEPT_RECENT_REFERENCES + 1
+    {
+        references.remove(1);
+    }
+    references
+}
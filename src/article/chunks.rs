@@ -0,0 +1,184 @@
+//! Chunked article serialization for streaming uploads
+//!
+//! Large articles (e.g. yEnc-encoded binaries) are serialized piecewise so an
+//! upload never needs the whole wire-format article in memory at once.
+
+use super::Article;
+
+/// Default chunk size used when streaming articles to the server (64 KiB)
+pub const DEFAULT_POSTING_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Iterator over an article in posting wire format, in bounded chunks
+///
+/// Created by [`Article::posting_chunks`]. The first chunk contains the headers
+/// and the blank separator line; the remaining chunks contain whole body lines
+/// with CRLF line endings and dot-stuffing applied on the fly. The terminating
+/// ".\r\n" line is not included.
+#[derive(Debug)]
+pub struct PostingChunks<'a> {
+    article: &'a Article,
+    body_lines: BodyLines<'a>,
+    chunk_size: usize,
+    headers_sent: bool,
+}
+
+impl<'a> PostingChunks<'a> {
+    pub(super) fn new(article: &'a Article, chunk_size: usize) -> Self {
+        Self {
+            article,
+            body_lines: BodyLines::new(article.body_bytes()),
+            chunk_size: chunk_size.max(1),
+            headers_sent: false,
+        }
+    }
+}
+
+impl Iterator for PostingChunks<'_> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.headers_sent {
+            self.headers_sent = true;
+            let mut headers = String::with_capacity(1024);
+            self.article.write_posting_headers(&mut headers);
+            return Some(headers.into_bytes());
+        }
+
+        let mut chunk = Vec::with_capacity(self.chunk_size + 128);
+        // A single line longer than chunk_size is emitted whole
+        while chunk.len() < self.chunk_size {
+            let Some(line) = self.body_lines.next() else {
+                break;
+            };
+            if line.starts_with(b".") {
+                chunk.push(b'.');
+            }
+            chunk.extend_from_slice(line);
+            chunk.extend_from_slice(b"\r\n");
+        }
+
+        (!chunk.is_empty()).then_some(chunk)
+    }
+}
+
+/// Lines of a body as bytes, split like [`str::lines`]
+///
+/// Lines end at "\n" or "\r\n", which are not part of them; a final line
+/// ending does not start another line.
+#[derive(Debug)]
+pub(super) struct BodyLines<'a> {
+    rest: Option<&'a [u8]>,
+}
+
+impl<'a> BodyLines<'a> {
+    pub(super) fn new(body: &'a [u8]) -> Self {
+        Self {
+            rest: (!body.is_empty()).then_some(body),
+        }
+    }
+}
+
+impl<'a> Iterator for BodyLines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest?;
+        let Some(end) = memchr::memchr(b'\n', rest) else {
+            self.rest = None;
+            return Some(rest);
+        };
+        let tail = &rest[end + 1..];
+        self.rest = (!tail.is_empty()).then_some(tail);
+        let line = &rest[..end];
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::article::ArticleBuilder;
+
+    fn article_with_body(body: &str) -> crate::article::Article {
+        ArticleBuilder::new()
+            .from("user@example.com")
+            .subject("Chunks")
+            .newsgroups(vec!["test.group"])
+            .body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_posting_chunks_match_serialize_for_posting() {
+        let body = (0..500)
+            .map(|i| {
+                if i % 7 == 0 {
+                    format!(".line {}", i)
+                } else {
+                    format!("line {}", i)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let article = article_with_body(&body);
+        let expected = article.serialize_for_posting().unwrap().into_bytes();
+
+        for chunk_size in [1, 16, 100, 4096] {
+            let streamed: Vec<u8> = article.posting_chunks(chunk_size).flatten().collect();
+            assert_eq!(streamed, expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn test_posting_chunks_bounded_size() {
+        let body = "0123456789\n".repeat(1000);
+        let article = article_with_body(&body);
+
+        let chunks: Vec<Vec<u8>> = article.posting_chunks(256).collect();
+        assert!(chunks[0].ends_with(b"\r\n\r\n"));
+        // Body chunks stop at the first line that reaches the limit
+        assert!(chunks[1..].iter().all(|c| c.len() < 256 + 12));
+        assert!(chunks[1..].iter().all(|c| c.ends_with(b"\r\n")));
+    }
+
+    #[test]
+    fn test_body_lines_match_str_lines() {
+        for body in [
+            "",
+            "\n",
+            "a",
+            "a\n",
+            "a\r\nb",
+            "a\n\nb\r\n",
+            "a\rb\r",
+            "\r\n\r\n",
+        ] {
+            let expected: Vec<&[u8]> = body.lines().map(str::as_bytes).collect();
+            let lines: Vec<&[u8]> = super::BodyLines::new(body.as_bytes()).collect();
+            assert_eq!(lines, expected, "body {:?}", body);
+        }
+    }
+
+    #[test]
+    fn test_posting_chunks_binary_body() {
+        let article = ArticleBuilder::new()
+            .from("user@example.com")
+            .subject("Binary")
+            .newsgroups(vec!["alt.binaries.test"])
+            .body_bytes(b"=ybegin\n\xff\x00\xe9\r\n.\x80\n".to_vec())
+            .build()
+            .unwrap();
+        let expected = article.serialize_for_posting_bytes().unwrap();
+        assert!(expected.ends_with(b"\r\n\r\n=ybegin\r\n\xff\x00\xe9\r\n..\x80\r\n"));
+
+        let streamed: Vec<u8> = article.posting_chunks(4).flatten().collect();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_posting_chunks_empty_body() {
+        let article = article_with_body("");
+        let chunks: Vec<Vec<u8>> = article.posting_chunks(1024).collect();
+        assert_eq!(chunks.len(), 1);
+    }
+}
@@ -0,0 +1,215 @@
+//! Converting articles to and from RFC 5322 messages (.eml files)
+//!
+//! An article is a mail message with a few headers of its own (RFC 5536
+//! Section 1), so mail tools can archive and read it. Path and Xref only
+//! mean something to news servers and are carried as X-Original-Path and
+//! X-Original-Xref in mail; Newsgroups is kept, as mail readers show it.
+
+use crate::Result;
+
+use super::chunks::BodyLines;
+use super::parsing::{headers_from_fields, parse_header_fields, split_article_bytes};
+use super::types::Article;
+
+/// Mail header carrying the Path of an article
+const MAIL_PATH: &str = "X-Original-Path";
+
+/// Mail header carrying the Xref of an article
+const MAIL_XREF: &str = "X-Original-Xref";
+
+/// Trace headers of mail delivery, which do not belong in an article
+const MAIL_TRACE: &[&str] = &["Received", "Return-Path", "Delivered-To"];
+
+impl Article {
+    /// Export the article as an RFC 5322 message, as stored in .eml files
+    ///
+    /// Headers are written in posting order with Path renamed to
+    /// X-Original-Path, and Xref, if any, added as X-Original-Xref. The
+    /// body is written as it is with CRLF line endings, without the
+    /// dot-stuffing of the NNTP wire format. Non-ASCII header values are
+    /// kept as UTF-8 (RFC 6532).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::{Article, ArticleBuilder};
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Archived")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .body(".starts with a dot\n")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let eml = article.to_eml();
+    /// assert!(eml.ends_with(b"\r\n\r\n.starts with a dot\r\n"));
+    ///
+    /// let restored = Article::from_eml(&eml).unwrap();
+    /// assert_eq!(restored.headers.subject, "Archived");
+    /// assert_eq!(restored.headers.path, "not-for-mail");
+    /// ```
+    pub fn to_eml(&self) -> Vec<u8> {
+        let body = self.body_bytes();
+        let mut eml = Vec::with_capacity(1024 + body.len() + body.len() / 64);
+
+        let mut write = |name: &str, value: &str| {
+            eml.extend_from_slice(name.as_bytes());
+            eml.extend_from_slice(b": ");
+            eml.extend_from_slice(value.as_bytes());
+            eml.extend_from_slice(b"\r\n");
+        };
+        for (name, value) in self.posting_headers() {
+            if name.eq_ignore_ascii_case("Path") {
+                write(MAIL_PATH, &value);
+            } else {
+                write(name, &value);
+            }
+        }
+        if let Some(ref xref) = self.headers.xref {
+            write(MAIL_XREF, xref);
+        }
+        eml.extend_from_slice(b"\r\n");
+
+        for line in BodyLines::new(body) {
+            eml.extend_from_slice(line);
+            eml.extend_from_slice(b"\r\n");
+        }
+        eml
+    }
+
+    /// Import an RFC 5322 message, such as the contents of an .eml file
+    ///
+    /// X-Original-Path and X-Original-Xref become Path and Xref again,
+    /// unless the message has those headers itself, and the mail trace
+    /// headers Received, Return-Path and Delivered-To are dropped. A
+    /// message that never was an article gets an empty Newsgroups, Path
+    /// `not-for-mail` and, if it has none, an empty Subject; set the
+    /// newsgroups before posting it. A body that is not UTF-8 is kept as
+    /// bytes (see [`Article::with_body_bytes`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`](crate::NntpError::InvalidResponse)
+    /// if the message lacks a Date, From or Message-ID header.
+    pub fn from_eml(eml: &[u8]) -> Result<Article> {
+        let (header_bytes, body) = split_article_bytes(eml);
+        let mut fields = parse_header_fields(&String::from_utf8_lossy(header_bytes));
+
+        let has = |fields: &[(String, String)], name: &str| {
+            fields
+                .iter()
+                .any(|(field, _)| field.eq_ignore_ascii_case(name))
+        };
+        let has_path = has(&fields, "Path");
+        let has_xref = has(&fields, "Xref");
+        fields.retain_mut(|(name, _)| {
+            if MAIL_TRACE
+                .iter()
+                .any(|trace| name.eq_ignore_ascii_case(trace))
+            {
+                return false;
+            }
+            if !has_path && name.eq_ignore_ascii_case(MAIL_PATH) {
+                *name = "Path".to_string();
+            } else if !has_xref && name.eq_ignore_ascii_case(MAIL_XREF) {
+                *name = "Xref".to_string();
+            }
+            true
+        });
+        for (name, default) in [
+            ("Newsgroups", ""),
+            ("Path", "not-for-mail"),
+            ("Subject", ""),
+        ] {
+            if !has(&fields, name) {
+                fields.push((name.to_string(), default.to_string()));
+            }
+        }
+
+        // Xref is added by the server, so a repost leaves it out
+        let header_order = fields
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| !name.eq_ignore_ascii_case("Xref"))
+            .collect();
+        let headers = headers_from_fields(fields)?;
+
+        let mut article = match std::str::from_utf8(body) {
+            Ok(text) => Article::new(headers, text.to_string()),
+            Err(_) => Article::with_body_bytes(headers, body.to_vec()),
+        };
+        article.header_order = Some(header_order);
+        Ok(article)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article::parse_article;
+
+    const ARTICLE: &str = "Path: news.example.com!not-for-mail\r\n\
+                           From: Alice <alice@example.com>\r\n\
+                           Newsgroups: comp.lang.rust,misc.test\r\n\
+                           Subject: Round trip\r\n\
+                           Date: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+                           Message-ID: <eml@example.com>\r\n\
+                           X-Custom: kept\r\n\
+                           Xref: news.example.com comp.lang.rust:42\r\n\
+                           \r\n\
+                           Hello\r\n\
+                           .dot line\r\n";
+
+    #[test]
+    fn test_to_eml_maps_news_headers() {
+        let eml = parse_article(ARTICLE).unwrap().to_eml();
+        let text = String::from_utf8(eml).unwrap();
+        assert!(text.starts_with("X-Original-Path: news.example.com!not-for-mail\r\n"));
+        assert!(text.contains("\r\nNewsgroups: comp.lang.rust,misc.test\r\n"));
+        assert!(text.contains("\r\nX-Original-Xref: news.example.com comp.lang.rust:42\r\n"));
+        assert!(!text.contains("\r\nPath:"));
+        assert!(!text.contains("\r\nXref:"));
+        assert!(text.ends_with("\r\n\r\nHello\r\n.dot line\r\n"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let original = parse_article(ARTICLE).unwrap();
+        let restored = Article::from_eml(&original.to_eml()).unwrap();
+        assert_eq!(restored.headers.path, "news.example.com!not-for-mail");
+        assert_eq!(
+            restored.headers.xref.as_deref(),
+            Some("news.example.com comp.lang.rust:42")
+        );
+        assert_eq!(restored.headers.extra.get("X-Custom").unwrap(), "kept");
+        assert_eq!(restored.body, "Hello\r\n.dot line\r\n");
+        assert_eq!(
+            restored.serialize_for_posting().unwrap(),
+            original.serialize_for_posting().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_mail_message() {
+        let mail = b"Return-Path: <bounce@example.org>\n\
+                     Received: from mx.example.org by mail.example.com\n\
+                     From: bob@example.org\n\
+                     To: list@example.com\n\
+                     Date: Tue, 21 Jan 2025 08:30:00 +0100\n\
+                     Message-ID: <mail@example.org>\n\
+                     \n\
+                     Caf\xe9 in Latin-1\n";
+        let article = Article::from_eml(mail).unwrap();
+        assert!(article.headers.newsgroups.is_empty());
+        assert_eq!(article.headers.path, "not-for-mail");
+        assert_eq!(article.headers.subject, "");
+        assert_eq!(article.headers.extra.get("To").unwrap(), "list@example.com");
+        assert!(!article.headers.extra.contains_key("Received"));
+        assert!(!article.headers.extra.contains_key("Return-Path"));
+        assert!(article.has_binary_body());
+        assert_eq!(article.body_bytes(), b"Caf\xe9 in Latin-1\n");
+
+        assert!(Article::from_eml(b"Subject: no sender\r\n\r\nbody").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Ordered, case-preserving header storage
+//!
+//! Header field names are case-insensitive (RFC 5322 Section 1.2.2), but
+//! relays and signature checks want articles passed on exactly as received.
+//! [`HeaderMap`] keeps fields in the order they were added, with the name
+//! spelled as given, and looks them up regardless of case.
+
+use std::ops::Index;
+
+/// Header fields in insertion order, looked up case-insensitively
+///
+/// Used for [`Headers::extra`](super::Headers::extra). It offers the
+/// `HashMap` methods callers used before, so `insert`, `get`, indexing and
+/// iteration work as they did, but iteration follows the article's order
+/// and names keep their original case.
+///
+/// A name may occur more than once when [`append`](Self::append)ed, as
+/// with repeated trace headers; [`get`](Self::get) returns the first.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::article::HeaderMap;
+///
+/// let mut extra = HeaderMap::new();
+/// extra.insert("X-Trace".to_string(), "one".to_string());
+/// extra.insert("content-type".to_string(), "text/plain".to_string());
+///
+/// assert_eq!(extra["Content-Type"], "text/plain");
+/// let names: Vec<&String> = extra.keys().collect();
+/// assert_eq!(names, ["X-Trace", "content-type"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    fields: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of `name`, returning the previous one
+    ///
+    /// An existing field keeps its position and spelling, and any later
+    /// duplicates of it are removed; a new field is added at the end.
+    pub fn insert(&mut self, name: String, value: String) -> Option<String> {
+        let Some(pos) = self.position(&name) else {
+            self.fields.push((name, value));
+            return None;
+        };
+        let old = std::mem::replace(&mut self.fields[pos].1, value);
+        let mut index = 0;
+        self.fields.retain(|(field, _)| {
+            index += 1;
+            index <= pos + 1 || !field.eq_ignore_ascii_case(&name)
+        });
+        Some(old)
+    }
+
+    /// Add a field at the end, even if one with the same name exists
+    pub fn append(&mut self, name: String, value: String) {
+        self.fields.push((name, value));
+    }
+
+    /// Value of the first field called `name`, ignoring case
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.position(name).map(|pos| &self.fields[pos].1)
+    }
+
+    /// Mutable value of the first field called `name`, ignoring case
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut String> {
+        self.position(name).map(|pos| &mut self.fields[pos].1)
+    }
+
+    /// Values of every field called `name`, in order
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a String> {
+        self.fields
+            .iter()
+            .filter(move |(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// Check if a field called `name` exists, ignoring case
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Remove every field called `name`, returning the first value
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let first = self.position(name).map(|pos| self.fields[pos].1.clone());
+        self.fields
+            .retain(|(field, _)| !field.eq_ignore_ascii_case(name));
+        first
+    }
+
+    /// Number of fields, duplicates included
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Check if there are no fields
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Fields as `(name, value)` pairs in order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.fields.iter().map(|(name, value)| (name, value))
+    }
+
+    /// Field names in order, as spelled when added
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.fields.iter().map(|(name, _)| name)
+    }
+
+    /// Field values in order
+    pub fn values(&self) -> impl Iterator<Item = &String> {
+        self.fields.iter().map(|(_, value)| value)
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|(field, _)| field.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Index<&str> for HeaderMap {
+    type Output = String;
+
+    /// Value of the first field called `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such field, like indexing a `HashMap`.
+    fn index(&self, name: &str) -> &String {
+        match self.get(name) {
+            Some(value) => value,
+            None => panic!("no header named {name:?}"),
+        }
+    }
+}
+
+impl FromIterator<(String, String)> for HeaderMap {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl Extend<(String, String)> for HeaderMap {
+    /// Insert each field, replacing earlier values of the same name
+    fn extend<I: IntoIterator<Item = (String, String)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl IntoIterator for HeaderMap {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+    type Item = (&'a String, &'a String);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, String)>,
+        fn(&'a (String, String)) -> (&'a String, &'a String),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.iter().map(|(name, value)| (name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(fields: &[(&str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in fields {
+            map.append(name.to_string(), value.to_string());
+        }
+        map
+    }
+
+    #[test]
+    fn test_insert_replaces_in_place() {
+        let mut headers = map(&[("X-A", "1"), ("X-B", "2"), ("x-a", "3")]);
+        assert_eq!(headers.insert("X-a".into(), "4".into()), Some("1".into()));
+        let fields: Vec<_> = headers.iter().collect();
+        assert_eq!(
+            fields,
+            [(&"X-A".into(), &"4".into()), (&"X-B".into(), &"2".into())]
+        );
+
+        assert_eq!(headers.insert("X-C".into(), "5".into()), None);
+        assert_eq!(headers.keys().last().unwrap(), "X-C");
+    }
+
+    #[test]
+    fn test_duplicates_and_remove() {
+        let mut headers = map(&[("Received", "a"), ("X-A", "1"), ("RECEIVED", "b")]);
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers.get("received").unwrap(), "a");
+        assert_eq!(headers.get_all("Received").collect::<Vec<_>>(), ["a", "b"]);
+
+        assert_eq!(headers.remove("received"), Some("a".into()));
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.remove("received"), None);
+    }
+}
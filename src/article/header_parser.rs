@@ -0,0 +1,226 @@
+//! Incremental header parsing
+//!
+//! [`HeaderParser`] takes header text in chunks of any size, as it comes
+//! off a socket or out of an [`AsyncRead`](tokio::io::AsyncRead), and
+//! returns each header block as soon as its last line is in. Only the
+//! current line and the fields of the current block are kept, so a
+//! harvester can run millions of HEAD responses through one parser.
+
+use std::mem;
+
+use crate::Result;
+
+use super::parsing::{headers_from_fields, unfold_header};
+use super::types::Headers;
+
+/// Push parser for a stream of header blocks
+///
+/// A block ends at a blank line or at the "." line that terminates an NNTP
+/// multi-line response, so the data of several HEAD responses can be fed
+/// one after the other. Lines may end in CRLF or LF; a leading dot-stuffed
+/// "." is removed and lines that are not fields, such as status lines,
+/// are skipped. Folded fields are unfolded and each block is turned into
+/// [`Headers`] like [`parse_headers`](super::parse_headers) does.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::article::HeaderParser;
+///
+/// let mut parser = HeaderParser::new();
+/// let mut blocks = Vec::new();
+/// let data = "221 0 <a@example.com>\r\nPath: not-for-mail\r\nFrom: a@example.com\r\n\
+///             Subject: First\r\nNewsgroups: misc.test\r\nDate: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+///             Message-ID: <a@example.com>\r\n.\r\n";
+/// for chunk in data.as_bytes().chunks(7) {
+///     blocks.extend(parser.feed(chunk));
+/// }
+/// assert_eq!(blocks.len(), 1);
+/// assert_eq!(blocks[0].as_ref().unwrap().subject, "First");
+/// assert!(parser.finish().is_none());
+/// ```
+#[derive(Debug, Default)]
+pub struct HeaderParser {
+    /// Start of a line whose end has not arrived yet
+    partial: Vec<u8>,
+    /// Completed fields of the current block, unfolded
+    fields: Vec<(String, String)>,
+    /// Field being read, still folded: more lines may continue it
+    current: Option<(String, String)>,
+}
+
+impl HeaderParser {
+    /// Create a parser waiting for the first block
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next chunk of data, returning the blocks it completes
+    ///
+    /// A block missing a required header is returned as the error
+    /// [`parse_headers`](super::parse_headers) gives; parsing carries on
+    /// with the next block.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Result<Headers>> {
+        let mut completed = Vec::new();
+        let mut rest = chunk;
+        while let Some(end) = memchr::memchr(b'\n', rest) {
+            let (line, tail) = rest.split_at(end + 1);
+            rest = tail;
+            let block = if self.partial.is_empty() {
+                self.line(&line[..end])
+            } else {
+                let mut partial = mem::take(&mut self.partial);
+                partial.extend_from_slice(&line[..end]);
+                let block = self.line(&partial);
+                // Keep the allocation for the next split line
+                partial.clear();
+                self.partial = partial;
+                block
+            };
+            completed.extend(block);
+        }
+        self.partial.extend_from_slice(rest);
+        completed
+    }
+
+    /// Parse what is left once the data has ended
+    ///
+    /// Returns the block the data ended in the middle of, if any, and
+    /// resets the parser.
+    pub fn finish(&mut self) -> Option<Result<Headers>> {
+        let partial = mem::take(&mut self.partial);
+        let block = if partial.is_empty() {
+            None
+        } else {
+            self.line(&partial)
+        };
+        block.or_else(|| self.end_block())
+    }
+
+    /// Check if the parser is in the middle of a block
+    pub fn in_block(&self) -> bool {
+        !self.partial.is_empty() || self.current.is_some() || !self.fields.is_empty()
+    }
+
+    /// Process one line without its LF, returning a block it completes
+    fn line(&mut self, line: &[u8]) -> Option<Result<Headers>> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() || line == b"." {
+            return self.end_block();
+        }
+        let line = String::from_utf8_lossy(line);
+
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = self.current.as_mut() {
+                value.push('\n');
+                value.push_str(&line);
+            }
+            return None;
+        }
+
+        let line = line.strip_prefix("..").map_or(&*line, |_| &line[1..]);
+        // A field name has no spaces, unlike a status line
+        if let Some((name, value)) = line.split_once(':')
+            && !name.trim().contains([' ', '\t'])
+        {
+            self.finish_field();
+            self.current = Some((name.trim().to_string(), value.trim_start().to_string()));
+        }
+        None
+    }
+
+    /// Move the field being read to the completed fields
+    fn finish_field(&mut self) {
+        if let Some((name, value)) = self.current.take() {
+            self.fields.push((name, unfold_header(&value)));
+        }
+    }
+
+    /// Turn the fields read into headers, if there are any
+    fn end_block(&mut self) -> Option<Result<Headers>> {
+        self.finish_field();
+        if self.fields.is_empty() {
+            return None;
+        }
+        Some(headers_from_fields(mem::take(&mut self.fields)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NntpError;
+    use crate::article::parse_headers;
+
+    const BLOCK: &str = "Path: news.example.com!not-for-mail\r\n\
+                         From: Alice <alice@example.com>\r\n\
+                         Newsgroups: misc.test\r\n\
+                         Subject: A folded\r\n\
+                         \tsubject line\r\n\
+                         Date: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+                         Message-ID: <one@example.com>\r\n\
+                         X-Custom: value\r\n";
+
+    #[test]
+    fn test_matches_parse_headers_at_any_chunk_size() {
+        let expected = parse_headers(BLOCK).unwrap();
+        let data = format!("{}\r\n", BLOCK);
+        for size in [1, 2, 5, 64, data.len()] {
+            let mut parser = HeaderParser::new();
+            let blocks: Vec<_> = data
+                .as_bytes()
+                .chunks(size)
+                .flat_map(|chunk| parser.feed(chunk))
+                .collect();
+            assert_eq!(blocks.len(), 1, "chunk size {}", size);
+            let headers = blocks[0].as_ref().unwrap();
+            assert_eq!(headers.subject, "A folded subject line");
+            assert_eq!(headers.subject, expected.subject);
+            assert_eq!(headers.extra, expected.extra);
+            assert!(!parser.in_block());
+        }
+    }
+
+    #[test]
+    fn test_several_responses() {
+        let second = BLOCK.replace("<one@", "<two@").replace("\r\n", "\n");
+        let data = format!(
+            "221 0 <one@example.com>\r\n{}.\r\n221 0 <two@example.com> head: follows\n{}.\n",
+            BLOCK, second
+        );
+        let mut parser = HeaderParser::new();
+        let blocks = parser.feed(data.as_bytes());
+        let ids: Vec<_> = blocks
+            .iter()
+            .map(|headers| headers.as_ref().unwrap().message_id.as_str())
+            .collect();
+        assert_eq!(ids, ["<one@example.com>", "<two@example.com>"]);
+        assert!(parser.finish().is_none());
+    }
+
+    #[test]
+    fn test_bad_block_does_not_stop_parsing() {
+        let data = format!("From: a@example.com\r\n\r\n{}\r\n", BLOCK);
+        let blocks = HeaderParser::new().feed(data.as_bytes());
+        assert_eq!(blocks.len(), 2);
+        assert!(matches!(blocks[0], Err(NntpError::InvalidResponse(_))));
+        assert!(blocks[1].is_ok());
+    }
+
+    #[test]
+    fn test_finish_without_terminator() {
+        let mut parser = HeaderParser::new();
+        assert!(parser.feed(BLOCK.trim_end().as_bytes()).is_empty());
+        assert!(parser.in_block());
+        let headers = parser.finish().unwrap().unwrap();
+        assert_eq!(headers.extra.get("X-Custom").unwrap(), "value");
+        assert!(!parser.in_block());
+    }
+
+    #[test]
+    fn test_dot_stuffed_line() {
+        let data = format!("{}..X-Dotted: yes\r\n.\r\n", BLOCK);
+        let headers = HeaderParser::new().feed(data.as_bytes()).remove(0).unwrap();
+        assert_eq!(headers.extra.get(".X-Dotted").unwrap(), "yes");
+    }
+}
@@ -0,0 +1,171 @@
+//! yEnc blocks inside article bodies
+//!
+//! A binary post's body is usually a single yEnc block, but blocks also
+//! turn up after introductory text, or several to a body.
+//! [`Article::extract_yenc_parts`] finds and decodes each of them.
+
+use crate::Result;
+use crate::yenc::{self, YencDecoded};
+
+use super::types::Article;
+
+impl Article {
+    /// Decode every yEnc block (`=ybegin` ... `=yend`) in the body
+    ///
+    /// Blocks are returned in body order, each decoded on its own, so one
+    /// damaged block does not hide the others. Text around the blocks is
+    /// ignored. Check [`YencDecoded::verify_crc32`] before trusting the
+    /// data.
+    ///
+    /// yEnc data is not text, so fetch the article with
+    /// [`fetch_article_binary`](crate::NntpClient::fetch_article_binary) and
+    /// make it into an article with
+    /// [`NntpBinaryResponse::into_article`](crate::NntpBinaryResponse::into_article),
+    /// which keeps every byte of the body. The lines of
+    /// [`fetch_article`](crate::NntpClient::fetch_article) are decoded as
+    /// text, which replaces most yEnc bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    /// use nntp_rs::yenc;
+    ///
+    /// let encoded = yenc::encode(b"hello", "hello.txt", 128, None).unwrap();
+    /// let mut body = b"Here is the file:\r\n".to_vec();
+    /// body.extend_from_slice(&encoded);
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("poster@example.com")
+    ///     .subject("hello.txt")
+    ///     .newsgroups(vec!["alt.binaries.test"])
+    ///     .body_bytes(body)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let parts = article.extract_yenc_parts();
+    /// let decoded = parts[0].as_ref().unwrap();
+    /// assert_eq!(decoded.header.name, "hello.txt");
+    /// assert_eq!(decoded.data, b"hello");
+    /// assert_eq!(decoded.verify_crc32(), Some(true));
+    /// ```
+    pub fn extract_yenc_parts(&self) -> Vec<Result<YencDecoded>> {
+        yenc_blocks(self.body_bytes())
+            .into_iter()
+            .map(yenc::decode)
+            .collect()
+    }
+}
+
+/// Slices of `body` from each `=ybegin` line through its `=yend` line
+///
+/// A block missing its `=yend` runs to the next `=ybegin` or the end of
+/// the body, and fails to decode.
+fn yenc_blocks(body: &[u8]) -> Vec<&[u8]> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive(|&byte| byte == b'\n') {
+        if line.starts_with(b"=ybegin ") {
+            if let Some(start) = start {
+                blocks.push(&body[start..offset]);
+            }
+            start = Some(offset);
+        } else if line.starts_with(b"=yend ")
+            && let Some(begin) = start.take()
+        {
+            blocks.push(&body[begin..offset + line.len()]);
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        blocks.push(&body[start..]);
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NntpError;
+    use crate::article::ArticleBuilder;
+
+    fn encoded(data: &[u8], name: &str) -> Vec<u8> {
+        yenc::encode(data, name, 128, None).unwrap()
+    }
+
+    fn article(body: Vec<u8>) -> Article {
+        ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject("Files")
+            .newsgroups(vec!["alt.binaries.test"])
+            .body_bytes(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_several_blocks_among_text() {
+        let first: Vec<u8> = (0..=255).collect();
+        let mut body = b"Two files follow.\r\n\r\n".to_vec();
+        body.extend(encoded(&first, "bytes.bin"));
+        body.extend_from_slice(b"\r\nand the second:\r\n");
+        body.extend(encoded(b"second file", "second.txt"));
+        body.extend_from_slice(b"-- \r\nsig\r\n");
+
+        let parts = article(body).extract_yenc_parts();
+        assert_eq!(parts.len(), 2);
+        let first_part = parts[0].as_ref().unwrap();
+        assert_eq!(first_part.data, first);
+        assert_eq!(first_part.verify_crc32(), Some(true));
+        let second_part = parts[1].as_ref().unwrap();
+        assert_eq!(second_part.header.name, "second.txt");
+        assert_eq!(second_part.data, b"second file");
+    }
+
+    #[test]
+    fn test_unterminated_block_does_not_hide_others() {
+        let mut body = encoded(b"lost", "lost.bin");
+        let yend = memchr::memmem::find(&body, b"=yend").unwrap();
+        body.truncate(yend);
+        body.extend(encoded(b"kept", "kept.bin"));
+
+        let parts = article(body).extract_yenc_parts();
+        assert_eq!(parts.len(), 2);
+        assert!(matches!(parts[0], Err(NntpError::InvalidResponse(_))));
+        assert_eq!(parts[1].as_ref().unwrap().data, b"kept");
+    }
+
+    #[test]
+    fn test_text_body_without_yenc() {
+        let article = ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject("Text")
+            .newsgroups(vec!["misc.test"])
+            .body("Nothing to see\n=ybeginning is not a header\n")
+            .build()
+            .unwrap();
+        assert!(article.extract_yenc_parts().is_empty());
+    }
+
+    #[test]
+    fn test_fetched_binary_article_keeps_yenc_bytes() {
+        let data = [0xd6u8, 0x80, 0x41];
+        let headers = b"Path: not-for-mail\r\n\
+From: poster@example.com\r\n\
+Newsgroups: alt.binaries.test\r\n\
+Subject: Raw\r\n\
+Date: Mon, 1 Jan 2024 00:00:00 +0000\r\n\
+Message-ID: <raw@example.com>\r\n\r\n";
+        let response = crate::NntpBinaryResponse {
+            code: 220,
+            message: "0 <raw@example.com>".to_string(),
+            data: [&headers[..], &encoded(&data, "raw.bin")].concat(),
+        };
+        let parts = response.into_article().unwrap().extract_yenc_parts();
+        let part = parts[0].as_ref().unwrap();
+        assert_eq!(part.data, data);
+        assert_eq!(part.verify_crc32(), Some(true));
+    }
+}
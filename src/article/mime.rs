@@ -0,0 +1,337 @@
+//! MIME multipart bodies (RFC 2045, RFC 2046)
+//!
+//! [`parse_multipart`] splits a multipart body at its boundary into
+//! [`MimePart`]s, each with its own headers and its content decoded from
+//! the Content-Transfer-Encoding. Parts that are multiparts themselves are
+//! split in turn, so the result is a tree.
+
+use base64::Engine;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+use super::parsing::unfold_header;
+use crate::encoded_words::decode_charset;
+use crate::error::{NntpError, Result};
+
+/// Deepest nesting of multiparts accepted
+const MAX_DEPTH: usize = 16;
+
+/// Base64 as posters produce it: padded or not
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// One part of a multipart body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimePart {
+    /// Header fields of the part, unfolded, in the order they appear
+    pub headers: Vec<(String, String)>,
+    /// Decoded content, or the nested parts of a multipart
+    pub content: MimeContent,
+}
+
+/// Content of a [`MimePart`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MimeContent {
+    /// Content with its transfer encoding removed; text lines end in "\n"
+    Data(Vec<u8>),
+    /// Parts of a nested multipart
+    Multipart(Vec<MimePart>),
+}
+
+impl MimePart {
+    /// Value of header `name` (case-insensitive), the first if repeated
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Content-Type of the part, `text/plain` if it has none (RFC 2045 Section 5.2)
+    pub fn content_type(&self) -> &str {
+        self.header("Content-Type").unwrap_or("text/plain")
+    }
+
+    /// Lowercase `type/subtype` of the Content-Type, without parameters
+    pub fn media_type(&self) -> String {
+        media_type(self.content_type())
+    }
+
+    /// Value of parameter `name` of the Content-Type (e.g. `charset`)
+    pub fn param(&self, name: &str) -> Option<String> {
+        header_param(self.content_type(), name)
+    }
+
+    /// File name from Content-Disposition `filename=`, else Content-Type `name=`
+    pub fn filename(&self) -> Option<String> {
+        self.header("Content-Disposition")
+            .and_then(|value| header_param(value, "filename"))
+            .or_else(|| self.param("name"))
+    }
+
+    /// Decoded content, unless the part is a multipart
+    pub fn data(&self) -> Option<&[u8]> {
+        match &self.content {
+            MimeContent::Data(data) => Some(data),
+            MimeContent::Multipart(_) => None,
+        }
+    }
+
+    /// Decoded content as text, if it is valid UTF-8
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(self.data()?).ok()
+    }
+
+    /// Decoded content as text in the part's `charset` (UTF-8 if none)
+    ///
+    /// See [`decode_charset`] for the charsets supported. Returns `None`
+    /// for a multipart.
+    pub fn decoded_text(&self) -> Option<String> {
+        let charset = self.param("charset").unwrap_or_else(|| "utf-8".to_string());
+        Some(decode_charset(self.data()?, &charset))
+    }
+
+    /// Nested parts; empty unless the part is a multipart
+    pub fn parts(&self) -> &[MimePart] {
+        match &self.content {
+            MimeContent::Multipart(parts) => parts,
+            MimeContent::Data(_) => &[],
+        }
+    }
+
+    /// Every part below this one that is not a multipart, depth first
+    pub fn leaves(&self) -> Vec<&MimePart> {
+        match &self.content {
+            MimeContent::Data(_) => vec![self],
+            MimeContent::Multipart(parts) => parts.iter().flat_map(MimePart::leaves).collect(),
+        }
+    }
+}
+
+/// Split a multipart body into its parts
+///
+/// `content_type` is the Content-Type of the body, which names the
+/// boundary. The preamble before the first boundary and the epilogue after
+/// the closing one are dropped; a body missing the closing boundary ends
+/// its last part at the end of the body.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::article::mime::parse_multipart;
+///
+/// let body = "preamble\r\n--xyz\r\nContent-Type: text/plain\r\n\r\nhello\r\n\
+///             --xyz\r\nContent-Transfer-Encoding: base64\r\n\r\naGk=\r\n--xyz--\r\n";
+/// let parts = parse_multipart("multipart/mixed; boundary=xyz", body).unwrap();
+/// assert_eq!(parts.len(), 2);
+/// assert_eq!(parts[0].text(), Some("hello"));
+/// assert_eq!(parts[1].data(), Some(&b"hi"[..]));
+/// ```
+///
+/// # Errors
+///
+/// Returns [`NntpError::InvalidResponse`] if `content_type` is not a
+/// multipart type with a boundary, a base64 part does not decode, or
+/// multiparts are nested more than 16 deep.
+pub fn parse_multipart(content_type: &str, body: &str) -> Result<Vec<MimePart>> {
+    let boundary = multipart_boundary(content_type)?;
+    split_parts(body, &boundary, 0)
+}
+
+/// Boundary of a multipart Content-Type
+fn multipart_boundary(content_type: &str) -> Result<String> {
+    if !media_type(content_type).starts_with("multipart/") {
+        return Err(NntpError::InvalidResponse(format!(
+            "Not a multipart content type: {}",
+            content_type
+        )));
+    }
+    header_param(content_type, "boundary")
+        .ok_or_else(|| NntpError::InvalidResponse("Multipart without boundary".to_string()))
+}
+
+/// Split `body` at `boundary` and parse each part
+fn split_parts(body: &str, boundary: &str, depth: usize) -> Result<Vec<MimePart>> {
+    if depth >= MAX_DEPTH {
+        return Err(NntpError::InvalidResponse(format!(
+            "MIME multiparts nested more than {} deep",
+            MAX_DEPTH
+        )));
+    }
+
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in body.lines() {
+        let Some(closing) = delimiter_line(line, &delimiter) else {
+            if let Some(lines) = current.as_mut() {
+                lines.push(line);
+            }
+            continue;
+        };
+        if let Some(lines) = current.take() {
+            parts.push(parse_part(&lines, depth)?);
+        }
+        if closing {
+            return Ok(parts);
+        }
+        current = Some(Vec::new());
+    }
+    if let Some(lines) = current {
+        parts.push(parse_part(&lines, depth)?);
+    }
+    Ok(parts)
+}
+
+/// `Some(false)` for a boundary line, `Some(true)` for the closing one
+fn delimiter_line(line: &str, delimiter: &str) -> Option<bool> {
+    // Transport padding after the boundary is allowed (RFC 2046 Section 5.1.1)
+    match line.strip_prefix(delimiter)?.trim_end() {
+        "" => Some(false),
+        "--" => Some(true),
+        _ => None,
+    }
+}
+
+/// Parse the lines between two boundaries
+fn parse_part(lines: &[&str], depth: usize) -> Result<MimePart> {
+    let blank = lines
+        .iter()
+        .position(|line| line.is_empty())
+        .unwrap_or(lines.len());
+    let headers = parse_part_headers(&lines[..blank]);
+    let body = lines.get(blank + 1..).unwrap_or_default();
+
+    let mut part = MimePart {
+        headers,
+        content: MimeContent::Data(Vec::new()),
+    };
+    part.content = if part.media_type().starts_with("multipart/")
+        && let Some(boundary) = part.param("boundary")
+    {
+        MimeContent::Multipart(split_parts(&body.join("\n"), &boundary, depth + 1)?)
+    } else {
+        let encoding = part
+            .header("Content-Transfer-Encoding")
+            .unwrap_or("7bit")
+            .trim()
+            .to_ascii_lowercase();
+        MimeContent::Data(decode_content(&encoding, body)?)
+    };
+    Ok(part)
+}
+
+/// Parse header lines, joining folded ones
+fn parse_part_headers(lines: &[&str]) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push('\n');
+                value.push_str(line);
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim_start().to_string()));
+        }
+    }
+    for (_, value) in &mut headers {
+        *value = unfold_header(value).trim_end().to_string();
+    }
+    headers
+}
+
+/// Remove the Content-Transfer-Encoding of a part's body lines
+pub(super) fn decode_content(encoding: &str, lines: &[&str]) -> Result<Vec<u8>> {
+    match encoding {
+        "base64" => {
+            let encoded: String = lines
+                .iter()
+                .flat_map(|line| line.chars())
+                .filter(|c| !c.is_ascii_whitespace())
+                .collect();
+            LENIENT_BASE64
+                .decode(encoded)
+                .map_err(|e| NntpError::InvalidResponse(format!("Invalid base64 MIME part: {}", e)))
+        }
+        "quoted-printable" => Ok(decode_quoted_printable(lines)),
+        // 7bit, 8bit, binary and anything unknown are taken as they are
+        _ => Ok(lines.join("\n").into_bytes()),
+    }
+}
+
+/// Decode quoted-printable body lines (RFC 2045 Section 6.7)
+///
+/// Malformed escapes are kept as they are.
+fn decode_quoted_printable(lines: &[&str]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        // Trailing whitespace was added in transport
+        let line = line.trim_end_matches([' ', '\t']);
+        let (line, soft_break) = match line.strip_suffix('=') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        let mut rest = line.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            let escaped = (byte == b'=')
+                .then(|| tail.get(..2))
+                .flatten()
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match escaped {
+                Some(value) => {
+                    decoded.push(value);
+                    rest = &tail[2..];
+                }
+                None => {
+                    decoded.push(byte);
+                    rest = tail;
+                }
+            }
+        }
+        if !soft_break && index + 1 < lines.len() {
+            decoded.push(b'\n');
+        }
+    }
+    decoded
+}
+
+/// Lowercase `type/subtype` of a Content-Type value
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Value of parameter `name` in a structured header value
+///
+/// Parameters are split at semicolons outside quotes; quoted values are
+/// unquoted.
+pub(crate) fn header_param(value: &str, name: &str) -> Option<String> {
+    let mut in_quotes = false;
+    let params = value.split(|c: char| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ';' && !in_quotes
+    });
+    params.skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case(name) {
+            return None;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        Some(value.to_string())
+    })
+}
@@ -9,13 +9,41 @@
 //! - `types`: Core article data structures (Article, Headers, ControlMessage)
 //! - `parsing`: Article and header parsing functions
 //! - `builder`: ArticleBuilder for constructing valid articles
+//! - `chunks`: Chunked serialization for streaming uploads
+//! - `eml`: Conversion to and from RFC 5322 mail messages
+//! - `header_map`: Ordered, case-preserving header storage
+//! - `header_parser`: Incremental parsing of header blocks fed in chunks
+//! - `inline_yenc`: Extracting yEnc blocks from article bodies
+//! - `mime`: MIME multipart body parsing
+//! - `policy`: Archive and injection header accessors
+//! - `sanitize`: Fixing up outgoing articles before posting
+//! - `split`: Splitting articles that exceed a size limit into parts
+//! - `structured`: Typed accessors for the Date, From and Xref headers
 
 // Module declarations - will be populated in subsequent refactoring steps
 mod builder;
-mod parsing;
+mod chunks;
+mod eml;
+mod header_map;
+mod header_parser;
+mod inline_yenc;
+pub mod mime;
+pub(crate) mod parsing;
+mod policy;
+mod sanitize;
+mod split;
+mod structured;
 mod types;
 
 // Re-export public API
 pub use self::builder::ArticleBuilder;
+pub use self::chunks::{DEFAULT_POSTING_CHUNK_SIZE, PostingChunks};
+pub use self::header_map::HeaderMap;
+pub use self::header_parser::HeaderParser;
+pub use self::mime::{MimeContent, MimePart};
 pub use self::parsing::{parse_article, parse_headers};
+pub(crate) use self::parsing::{split_article_bytes, unfold_header};
+pub use self::policy::InjectionInfo;
+pub use self::sanitize::{MAX_LINE_LENGTH, SanitizeReport};
+pub use self::structured::Mailbox;
 pub use self::types::{Article, ControlMessage, Headers};
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use crate::encoded_words::decode_header_value;
 use crate::{NntpError, Result};
 
+use super::HeaderMap;
 use super::types::{Article, Headers};
 
 /// Parse raw article text into headers and body
@@ -28,6 +29,23 @@ pub fn split_article(raw: &str) -> (&str, &str) {
     (raw, "")
 }
 
+/// Split raw article bytes into headers and body
+///
+/// Byte-oriented counterpart of [`split_article`] for binary responses, where
+/// the body may not be valid UTF-8. Returns `(headers, body)` slices; the blank
+/// separator line is in neither.
+pub(crate) fn split_article_bytes(raw: &[u8]) -> (&[u8], &[u8]) {
+    if let Some(pos) = memchr::memmem::find(raw, b"\r\n\r\n") {
+        return (&raw[..pos], &raw[pos + 4..]);
+    }
+
+    if let Some(pos) = memchr::memmem::find(raw, b"\n\n") {
+        return (&raw[..pos], &raw[pos + 2..]);
+    }
+
+    (raw, &[])
+}
+
 /// Parse comma-separated list (for Newsgroups, Followup-To, etc.)
 ///
 /// RFC 5536: Values are comma-separated, whitespace around commas is optional
@@ -109,7 +127,14 @@ pub fn unfold_header(value: &str) -> String {
 ///
 /// Parsed `Headers` struct or error if required headers are missing
 pub fn parse_headers(headers_text: &str) -> Result<Headers> {
-    let mut raw_headers: HashMap<String, String> = HashMap::new();
+    headers_from_fields(parse_header_fields(headers_text))
+}
+
+/// Split raw header text into unfolded `(name, value)` fields in order
+///
+/// Names keep their original spelling; repeated fields are all returned.
+pub(crate) fn parse_header_fields(headers_text: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
     let mut current_name: Option<String> = None;
     let mut current_value = String::new();
 
@@ -128,8 +153,7 @@ pub fn parse_headers(headers_text: &str) -> Result<Headers> {
             // New header field
             // Save previous header if any
             if let Some(name) = current_name.take() {
-                let unfolded = unfold_header(&current_value);
-                raw_headers.insert(name.to_lowercase(), unfolded);
+                fields.push((name, unfold_header(&current_value)));
             }
 
             // Parse new header: "name: value"
@@ -145,10 +169,22 @@ pub fn parse_headers(headers_text: &str) -> Result<Headers> {
 
     // Save last header
     if let Some(name) = current_name {
-        let unfolded = unfold_header(&current_value);
-        raw_headers.insert(name.to_lowercase(), unfolded);
+        fields.push((name, unfold_header(&current_value)));
     }
 
+    fields
+}
+
+/// Build `Headers` from parsed fields
+///
+/// Standard headers take the last value of their name; every other field
+/// goes to `extra` in order, with its original spelling.
+pub(super) fn headers_from_fields(fields: Vec<(String, String)>) -> Result<Headers> {
+    let raw_headers: HashMap<String, String> = fields
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.clone()))
+        .collect();
+
     // Extract required headers
     let date = raw_headers
         .get("date")
@@ -216,7 +252,7 @@ pub fn parse_headers(headers_text: &str) -> Result<Headers> {
     let xref = raw_headers.get("xref").cloned();
 
     // Collect non-standard headers (X-* and others)
-    let mut extra = HashMap::new();
+    let mut extra = HeaderMap::new();
     let standard_headers = [
         "date",
         "from",
@@ -240,9 +276,9 @@ pub fn parse_headers(headers_text: &str) -> Result<Headers> {
         "xref",
     ];
 
-    for (name, value) in raw_headers {
-        if !standard_headers.contains(&name.as_str()) {
-            extra.insert(name, value);
+    for (name, value) in fields {
+        if !standard_headers.contains(&name.to_lowercase().as_str()) {
+            extra.append(name, value);
         }
     }
 
@@ -284,11 +320,15 @@ pub fn parse_headers(headers_text: &str) -> Result<Headers> {
 /// Parsed `Article` with headers and body, or error if malformed
 pub fn parse_article(raw: &str) -> Result<Article> {
     let (headers_text, body_text) = split_article(raw);
-    let headers = parse_headers(headers_text)?;
+    let fields = parse_header_fields(headers_text);
+    let header_order = fields.iter().map(|(name, _)| name.clone()).collect();
+    let headers = headers_from_fields(fields)?;
 
     Ok(Article {
         headers,
         body: body_text.to_string(),
         raw: Some(raw.to_string()),
+        binary_body: None,
+        header_order: Some(header_order),
     })
 }
@@ -0,0 +1,262 @@
+//! Archiving and injection headers
+//!
+//! Archive (RFC 5536 Section 3.2.2) and the older X-No-Archive convention
+//! tell archives whether to keep an article. Injection-Date and
+//! Injection-Info (RFC 5536 Sections 3.2.7 and 3.2.8) are added by the
+//! injecting agent, the server that first accepts the article (RFC 5537
+//! Section 3.5).
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use super::mime::header_param;
+use super::types::Headers;
+
+/// Parsed Injection-Info header (RFC 5536 Section 3.2.8)
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::article::InjectionInfo;
+///
+/// let info = InjectionInfo::parse(
+///     "news.example.com; posting-host=\"dial-up.example.net\"; \
+///      mail-complaints-to=\"abuse@example.com\"",
+/// )
+/// .unwrap();
+/// assert_eq!(info.path_identity, "news.example.com");
+/// assert_eq!(info.posting_host.as_deref(), Some("dial-up.example.net"));
+/// assert_eq!(info.mail_complaints_to.as_deref(), Some("abuse@example.com"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InjectionInfo {
+    /// Path identity of the injecting agent
+    pub path_identity: String,
+    /// Account the article was posted from (`posting-account`)
+    pub posting_account: Option<String>,
+    /// Host the article was posted from (`posting-host`)
+    pub posting_host: Option<String>,
+    /// Data to find the posting in the injecting agent's logs (`logging-data`)
+    pub logging_data: Option<String>,
+    /// Where to send complaints about the article (`mail-complaints-to`)
+    pub mail_complaints_to: Option<String>,
+}
+
+impl InjectionInfo {
+    /// Create an Injection-Info with only a path identity
+    pub fn new(path_identity: impl Into<String>) -> Self {
+        Self {
+            path_identity: path_identity.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Parse an Injection-Info header value
+    ///
+    /// Returns `None` if the value has no path identity. Parameters other
+    /// than the four of RFC 5536 are ignored.
+    pub fn parse(value: &str) -> Option<Self> {
+        let path_identity = value.split(';').next()?.trim();
+        if path_identity.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path_identity: path_identity.to_string(),
+            posting_account: header_param(value, "posting-account"),
+            posting_host: header_param(value, "posting-host"),
+            logging_data: header_param(value, "logging-data"),
+            mail_complaints_to: header_param(value, "mail-complaints-to"),
+        })
+    }
+}
+
+impl fmt::Display for InjectionInfo {
+    /// Formats the header value with every parameter quoted; quotes and
+    /// backslashes in parameter values are dropped
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path_identity)?;
+        let params = [
+            ("posting-account", &self.posting_account),
+            ("posting-host", &self.posting_host),
+            ("logging-data", &self.logging_data),
+            ("mail-complaints-to", &self.mail_complaints_to),
+        ];
+        for (name, value) in params {
+            if let Some(value) = value {
+                write!(f, "; {}=\"{}\"", name, value.replace(['"', '\\'], ""))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Headers {
+    /// Archive header as a yes or no (RFC 5536 Section 3.2.2)
+    ///
+    /// Returns `Some(false)` for `no`, `Some(true)` for `yes`, and `None`
+    /// if the header is missing or holds neither. Parameters after the
+    /// keyword are ignored.
+    pub fn archive(&self) -> Option<bool> {
+        let value = self.extra.get("Archive")?;
+        let keyword = value.split(';').next().unwrap_or_default().trim();
+        if keyword.eq_ignore_ascii_case("yes") {
+            Some(true)
+        } else if keyword.eq_ignore_ascii_case("no") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Check for `X-No-Archive: yes`
+    pub fn x_no_archive(&self) -> bool {
+        self.extra
+            .get("X-No-Archive")
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("yes"))
+    }
+
+    /// Check if the poster allows the article to be archived
+    ///
+    /// `false` if either `Archive: no` or `X-No-Archive: yes` is present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Off the record")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .x_no_archive()
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(!article.headers.allows_archiving());
+    /// ```
+    pub fn allows_archiving(&self) -> bool {
+        !self.x_no_archive() && self.archive() != Some(false)
+    }
+
+    /// Injection-Date header as a timestamp (RFC 5536 Section 3.2.7)
+    ///
+    /// Returns `None` if the header is missing or not a valid date.
+    pub fn injection_date(&self) -> Option<DateTime<Utc>> {
+        let value = self.extra.get("Injection-Date")?;
+        crate::validation::parse_date(value.trim()).ok()
+    }
+
+    /// Parsed Injection-Info header (RFC 5536 Section 3.2.8)
+    pub fn injection_info(&self) -> Option<InjectionInfo> {
+        InjectionInfo::parse(self.extra.get("Injection-Info")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article::{ArticleBuilder, parse_article};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_article_policy_headers() {
+        let article = parse_article(
+            "From: user@example.com\r\n\
+             Subject: Policies\r\n\
+             Newsgroups: misc.test\r\n\
+             Message-ID: <policy@example.com>\r\n\
+             Date: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+             Path: news.example.com!not-for-mail\r\n\
+             Archive: no; filename=ignored\r\n\
+             Injection-Date: Mon, 20 Jan 2025 12:00:05 +0000\r\n\
+             Injection-Info: news.example.com; posting-account=\"alice\";\r\n \
+             logging-data=\"12345\"\r\n\
+             \r\n\
+             body\r\n",
+        )
+        .unwrap();
+        let headers = &article.headers;
+
+        assert_eq!(headers.archive(), Some(false));
+        assert!(!headers.x_no_archive());
+        assert!(!headers.allows_archiving());
+        assert_eq!(
+            headers.injection_date(),
+            Some(Utc.with_ymd_and_hms(2025, 1, 20, 12, 0, 5).unwrap())
+        );
+        let info = headers.injection_info().unwrap();
+        assert_eq!(info.path_identity, "news.example.com");
+        assert_eq!(info.posting_account.as_deref(), Some("alice"));
+        assert_eq!(info.logging_data.as_deref(), Some("12345"));
+        assert_eq!(info.posting_host, None);
+    }
+
+    #[test]
+    fn test_missing_policy_headers() {
+        let article = ArticleBuilder::new()
+            .from("user@example.com")
+            .subject("Plain")
+            .newsgroups(vec!["misc.test"])
+            .extra_header("Archive", "maybe")
+            .build()
+            .unwrap();
+        let headers = &article.headers;
+        assert_eq!(headers.archive(), None);
+        assert!(headers.allows_archiving());
+        assert_eq!(headers.injection_date(), None);
+        assert_eq!(headers.injection_info(), None);
+    }
+
+    #[test]
+    fn test_injection_info_round_trip() {
+        let info = InjectionInfo {
+            posting_host: Some("203.0.113.7".to_string()),
+            mail_complaints_to: Some("abuse@example.com".to_string()),
+            ..InjectionInfo::new("news.example.com")
+        };
+        let value = info.to_string();
+        assert_eq!(
+            value,
+            "news.example.com; posting-host=\"203.0.113.7\"; \
+             mail-complaints-to=\"abuse@example.com\""
+        );
+        assert_eq!(InjectionInfo::parse(&value), Some(info));
+        assert_eq!(InjectionInfo::parse(" ; posting-host=x"), None);
+    }
+
+    #[test]
+    fn test_builder_policy_headers() {
+        let info = InjectionInfo::new("gateway.example.com");
+        let article = ArticleBuilder::new()
+            .from("user@example.com")
+            .subject("Gatewayed")
+            .newsgroups(vec!["misc.test"])
+            .archive(true)
+            .injection_info(&info)
+            .generate_injection_date()
+            .build()
+            .unwrap();
+        let headers = &article.headers;
+        assert_eq!(headers.archive(), Some(true));
+        assert_eq!(headers.injection_info(), Some(info));
+        assert_eq!(
+            headers.extra.get("Injection-Date"),
+            Some(&headers.date),
+            "generated Injection-Date matches the generated Date"
+        );
+        assert!(headers.injection_date().is_some());
+
+        let explicit = ArticleBuilder::new()
+            .from("user@example.com")
+            .subject("Dated")
+            .newsgroups(vec!["misc.test"])
+            .injection_date("Mon, 20 Jan 2025 12:00:00 +0000")
+            .generate_injection_date()
+            .build()
+            .unwrap();
+        assert_eq!(
+            explicit.headers.injection_date(),
+            Some(Utc.with_ymd_and_hms(2025, 1, 20, 12, 0, 0).unwrap())
+        );
+    }
+}
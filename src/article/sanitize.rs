@@ -0,0 +1,380 @@
+//! Sanitizing outgoing articles
+//!
+//! Servers reject articles with NUL bytes, bare CR or LF line breaks, or
+//! lines over 998 octets (RFC 5536 Section 2.2), and a header value with an
+//! embedded line break would inject header lines of its own.
+//! [`Article::sanitize_for_posting`] fixes what can be fixed, refuses what
+//! cannot, and reports what it changed.
+
+use crate::{NntpError, Result};
+
+use super::types::Article;
+
+/// Longest line an article may contain, excluding CRLF (RFC 5322 Section 2.1.1)
+pub const MAX_LINE_LENGTH: usize = 998;
+
+/// What [`Article::sanitize_for_posting`] changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// NUL bytes removed from header values and the body
+    pub nul_bytes_removed: usize,
+    /// Bare LF or CR line breaks in the body turned into CRLF
+    pub line_endings_normalized: usize,
+    /// Names of the headers folded to fit the line length
+    pub headers_folded: Vec<String>,
+    /// Body lines broken up to fit the line length
+    pub body_lines_wrapped: usize,
+}
+
+impl SanitizeReport {
+    /// Check if the article needed no changes
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Article {
+    /// Serialize the article for posting, fixing what servers would reject
+    ///
+    /// Returns the wire bytes for [`post_raw`](crate::NntpClient::post_raw)
+    /// or [`ihave_raw`](crate::NntpClient::ihave_raw), and a report of the
+    /// fixes:
+    ///
+    /// - NUL bytes are removed from header values and the body.
+    /// - Bare LF and bare CR line breaks in the body become CRLF.
+    /// - Header lines longer than `max_line_length` octets are folded at
+    ///   whitespace (RFC 5322 Section 2.2.3).
+    /// - Body lines longer than `max_line_length` are broken into several
+    ///   lines, at a character boundary unless the body is binary.
+    ///
+    /// Pass [`MAX_LINE_LENGTH`] for the limit of RFC 5536, or a smaller
+    /// value for servers that enforce one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidArgument`] if a header name is not
+    /// printable ASCII without a colon, a header value contains a CR or LF
+    /// that is not part of a fold (a header injection), or a header has a
+    /// word too long to fold within `max_line_length`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    /// use nntp_rs::article::MAX_LINE_LENGTH;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Hello")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .body("one\rtwo\0\n")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let (bytes, report) = article.sanitize_for_posting(MAX_LINE_LENGTH).unwrap();
+    /// assert!(bytes.ends_with(b"\r\n\r\none\r\ntwo\r\n"));
+    /// assert_eq!((report.nul_bytes_removed, report.line_endings_normalized), (1, 2));
+    ///
+    /// let injected = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Hello\r\nApproved: yes")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(injected.sanitize_for_posting(MAX_LINE_LENGTH).is_err());
+    /// ```
+    pub fn sanitize_for_posting(
+        &self,
+        max_line_length: usize,
+    ) -> Result<(Vec<u8>, SanitizeReport)> {
+        let mut report = SanitizeReport::default();
+        let body = self.body_bytes();
+        let mut out = Vec::with_capacity(1024 + body.len() + body.len() / 64);
+
+        for (name, value) in self.posting_headers() {
+            check_header_name(name)?;
+            let mut value = strip_nul(value.as_bytes(), &mut report.nul_bytes_removed);
+            check_header_value(name, &value)?;
+            if let Some(folded) = fold_header(name, &value, max_line_length)? {
+                report.headers_folded.push(name.to_string());
+                value = folded;
+            }
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(b": ");
+            out.extend_from_slice(&value);
+            out.extend_from_slice(b"\r\n");
+        }
+        // Blank line separates headers from body
+        out.extend_from_slice(b"\r\n");
+
+        let text = !self.has_binary_body();
+        let mut rest = body;
+        while !rest.is_empty() {
+            let (line, next) = match memchr::memchr2(b'\r', b'\n', rest) {
+                None => (rest, &[][..]),
+                Some(end) => match &rest[end..] {
+                    [b'\r', b'\n', tail @ ..] => (&rest[..end], tail),
+                    [_, tail @ ..] => {
+                        report.line_endings_normalized += 1;
+                        (&rest[..end], tail)
+                    }
+                    [] => (rest, &[][..]),
+                },
+            };
+            let line = strip_nul(line, &mut report.nul_bytes_removed);
+            write_body_line(&mut out, &line, max_line_length, text, &mut report);
+            rest = next;
+        }
+
+        Ok((out, report))
+    }
+}
+
+/// Copy of `bytes` without NUL bytes, counting the ones removed
+fn strip_nul(bytes: &[u8], removed: &mut usize) -> Vec<u8> {
+    let nuls = memchr::memchr_iter(0, bytes).count();
+    *removed += nuls;
+    if nuls == 0 {
+        return bytes.to_vec();
+    }
+    bytes.iter().copied().filter(|&byte| byte != 0).collect()
+}
+
+/// Check that a header name is printable ASCII without a colon (RFC 5322 Section 2.2)
+fn check_header_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .bytes()
+            .all(|byte| (33..=126).contains(&byte) && byte != b':')
+    {
+        return Err(NntpError::InvalidArgument(format!(
+            "invalid header name {:?}",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Check that every CR or LF in a header value is part of a fold
+///
+/// A fold is CRLF followed by a space or tab; any other line break would
+/// end the header early and let the rest of the value pose as headers.
+fn check_header_value(name: &str, value: &[u8]) -> Result<()> {
+    let mut index = 0;
+    while let Some(offset) = memchr::memchr2(b'\r', b'\n', &value[index..]) {
+        let at = index + offset;
+        if !matches!(&value[at..], [b'\r', b'\n', b' ' | b'\t', ..]) {
+            return Err(NntpError::InvalidArgument(format!(
+                "line break in {} header would inject header lines",
+                name
+            )));
+        }
+        index = at + 2;
+    }
+    Ok(())
+}
+
+/// Refold a header whose lines exceed `max_line_length` octets
+///
+/// Returns `None` if the header fits as it is, or the folded value. Lines
+/// are broken before runs of whitespace, never right after the colon.
+fn fold_header(name: &str, value: &[u8], max_line_length: usize) -> Result<Option<Vec<u8>>> {
+    let prefix_len = name.len() + 2;
+    let fits = value
+        .split(|&byte| byte == b'\n')
+        .enumerate()
+        .all(|(index, line)| {
+            let prefix = if index == 0 { prefix_len } else { 0 };
+            prefix + line.strip_suffix(b"\r").unwrap_or(line).len() <= max_line_length
+        });
+    if fits {
+        return Ok(None);
+    }
+
+    let mut line = Vec::with_capacity(prefix_len + value.len());
+    line.extend_from_slice(name.as_bytes());
+    line.extend_from_slice(b": ");
+    // Folds were validated to be CRLF followed by whitespace
+    line.extend(
+        value
+            .iter()
+            .copied()
+            .filter(|&byte| byte != b'\r' && byte != b'\n'),
+    );
+    let content_end = line
+        .iter()
+        .rposition(|&byte| byte != b' ' && byte != b'\t')
+        .map_or(0, |index| index + 1);
+    let is_break = |index: usize| {
+        index > prefix_len
+            && index < content_end
+            && matches!(line[index], b' ' | b'\t')
+            && !matches!(line[index - 1], b' ' | b'\t')
+    };
+
+    let mut folded = Vec::with_capacity(line.len() + line.len() / 32);
+    let mut start = 0;
+    while line.len() - start > max_line_length {
+        let end = (start + max_line_length).min(line.len() - 1);
+        let Some(split) = (start + 1..=end).rev().find(|&index| is_break(index)) else {
+            return Err(NntpError::InvalidArgument(format!(
+                "{} header has a word too long to fold within {} octets",
+                name, max_line_length
+            )));
+        };
+        folded.extend_from_slice(&line[start..split]);
+        folded.extend_from_slice(b"\r\n");
+        start = split;
+    }
+    folded.extend_from_slice(&line[start..]);
+    Ok(Some(folded.split_off(prefix_len)))
+}
+
+/// Write a body line dot-stuffed, broken into lines of at most `max_line_length` octets
+///
+/// With `text`, lines are only broken at UTF-8 character boundaries.
+fn write_body_line(
+    out: &mut Vec<u8>,
+    line: &[u8],
+    max_line_length: usize,
+    text: bool,
+    report: &mut SanitizeReport,
+) {
+    let mut rest = line;
+    let mut wrapped = false;
+    loop {
+        let stuffed = rest.starts_with(b".");
+        let limit = max_line_length.saturating_sub(usize::from(stuffed));
+        let cut = if rest.len() <= limit {
+            rest.len()
+        } else {
+            wrapped = true;
+            let mut cut = limit;
+            if text {
+                while cut > 1 && (rest[cut] & 0xC0) == 0x80 {
+                    cut -= 1;
+                }
+            }
+            cut.max(1)
+        };
+        if stuffed {
+            out.push(b'.');
+        }
+        out.extend_from_slice(&rest[..cut]);
+        out.extend_from_slice(b"\r\n");
+        rest = &rest[cut..];
+        if rest.is_empty() {
+            break;
+        }
+    }
+    if wrapped {
+        report.body_lines_wrapped += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article::{ArticleBuilder, parse_article};
+
+    fn builder() -> ArticleBuilder {
+        ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject("Sanitize")
+            .newsgroups(vec!["misc.test"])
+            .message_id("<sanitize@example.com>")
+    }
+
+    fn body_of(posted: &[u8]) -> &[u8] {
+        let start = memchr::memmem::find(posted, b"\r\n\r\n").unwrap() + 4;
+        &posted[start..]
+    }
+
+    #[test]
+    fn test_clean_article_matches_serializer() {
+        let article = builder().body(".dot\r\nline\r\n").build().unwrap();
+        let (posted, report) = article.sanitize_for_posting(MAX_LINE_LENGTH).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(posted, article.serialize_for_posting_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_line_endings_and_nul_bytes() {
+        let article = builder()
+            .subject("Nul\0 in subject")
+            .body("a\nb\rc\r\nd\0\0")
+            .build()
+            .unwrap();
+        let (posted, report) = article.sanitize_for_posting(MAX_LINE_LENGTH).unwrap();
+        assert_eq!(body_of(&posted), b"a\r\nb\r\nc\r\nd\r\n");
+        assert!(memchr::memmem::find(&posted, b"Subject: Nul in subject\r\n").is_some());
+        assert_eq!(report.nul_bytes_removed, 3);
+        assert_eq!(report.line_endings_normalized, 2);
+    }
+
+    #[test]
+    fn test_header_injection_is_rejected() {
+        for subject in ["Hi\r\nApproved: yes", "Hi\nX: y", "Hi\rX", "Hi\r\n\r\nbody"] {
+            let article = builder().subject(subject).build().unwrap();
+            let result = article.sanitize_for_posting(MAX_LINE_LENGTH);
+            assert!(
+                matches!(result, Err(NntpError::InvalidArgument(_))),
+                "{:?}",
+                subject
+            );
+        }
+
+        // An existing fold is kept
+        let article = builder().subject("Folded\r\n subject").build().unwrap();
+        let (posted, report) = article.sanitize_for_posting(MAX_LINE_LENGTH).unwrap();
+        assert!(report.is_clean());
+        assert!(memchr::memmem::find(&posted, b"Subject: Folded\r\n subject\r\n").is_some());
+    }
+
+    #[test]
+    fn test_invalid_header_name_is_rejected() {
+        let article = builder().extra_header("Bad Name", "value").build().unwrap();
+        assert!(article.sanitize_for_posting(MAX_LINE_LENGTH).is_err());
+    }
+
+    #[test]
+    fn test_long_header_is_folded() {
+        let subject = "word ".repeat(40);
+        let article = builder().subject(subject.trim_end()).build().unwrap();
+        let (posted, report) = article.sanitize_for_posting(60).unwrap();
+        assert_eq!(report.headers_folded, vec!["Subject".to_string()]);
+
+        let text = String::from_utf8(posted).unwrap();
+        assert!(text.split("\r\n").all(|line| line.len() <= 60));
+        let parsed = parse_article(&text).unwrap();
+        assert_eq!(parsed.headers.subject, subject.trim_end());
+
+        let article = builder().subject("x".repeat(100)).build().unwrap();
+        assert!(article.sanitize_for_posting(60).is_err());
+    }
+
+    #[test]
+    fn test_long_body_lines_are_wrapped() {
+        let article = builder()
+            .body(format!(
+                "{}\r\n.{}\r\nshort\r\n",
+                "é".repeat(40),
+                "a".repeat(70)
+            ))
+            .build()
+            .unwrap();
+        let (posted, report) = article.sanitize_for_posting(59).unwrap();
+        assert_eq!(report.body_lines_wrapped, 2);
+        assert_eq!(
+            body_of(&posted),
+            format!(
+                "{}\r\n{}\r\n..{}\r\n{}\r\nshort\r\n",
+                "é".repeat(29),
+                "é".repeat(11),
+                "a".repeat(57),
+                "a".repeat(13)
+            )
+            .as_bytes()
+        );
+    }
+}
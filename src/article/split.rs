@@ -0,0 +1,317 @@
+//! Splitting articles that exceed a server's size limit
+//!
+//! Servers reject articles above a configured size (often around 1 MB), so
+//! a large body is posted as a series of parts with "(i/N)" appended to the
+//! subject, the convention newsreaders use to reassemble them.
+
+use crate::{NntpError, Result};
+
+use super::chunks::BodyLines;
+use super::types::{Article, Headers};
+
+/// Length of the ".\r\n" line that terminates a posted article
+const TERMINATOR_LEN: usize = 3;
+
+/// Smallest body budget that always fits a byte: a dot-stuffed line of one
+const MIN_LINE_COST: usize = 4;
+
+impl Article {
+    /// Split the article into parts that each fit in `max_article_size` bytes
+    ///
+    /// Sizes are measured as posted: headers, CRLF line endings,
+    /// dot-stuffing and the terminating ".\r\n" line. Part `i` of `N` gets
+    /// the subject "`subject` (i/N)" and the Message-ID `<local.i@domain>`
+    /// derived from the article's own, so the parts of a post can be told
+    /// apart and found again. The other headers are copied to every part.
+    ///
+    /// The body is cut at line boundaries. A line too long for any part is
+    /// cut as well, which adds a line break when the parts are joined, so
+    /// binary data should be yEnc-encoded first
+    /// ([`yenc::encode`](crate::yenc::encode())) rather than posted raw. An
+    /// article that fits is still numbered, as "(1/1)".
+    ///
+    /// # Errors
+    ///
+    /// [`NntpError::PostingFailed`] if the headers alone leave no room for
+    /// a body within `max_article_size`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("poster@example.com")
+    ///     .subject("Big post")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .message_id("<big@example.com>")
+    ///     .body("line of text\n".repeat(1000))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let parts = article.split(4000).unwrap();
+    /// assert!(parts.len() > 1);
+    /// assert_eq!(parts[0].headers.subject, format!("Big post (1/{})", parts.len()));
+    /// assert_eq!(parts[1].headers.message_id, "<big.2@example.com>");
+    /// for part in &parts {
+    ///     assert!(part.serialize_for_posting_bytes().unwrap().len() + 3 <= 4000);
+    /// }
+    /// ```
+    pub fn split(&self, max_article_size: usize) -> Result<Vec<Article>> {
+        self.split_with(max_article_size, |_| {})
+    }
+
+    /// [`split`](Self::split), with `finish` applied to the headers of each
+    /// part before it is measured
+    pub(crate) fn split_with(
+        &self,
+        max_article_size: usize,
+        finish: impl Fn(&mut Headers),
+    ) -> Result<Vec<Article>> {
+        let body = self.body_bytes();
+        let mut total = 1;
+        // Headers grow with the digits of the part count, so repeat until
+        // the count the body was split for is the count it needs
+        loop {
+            let widest = self.part(total, total, &[], &finish);
+            let overhead = widest.serialize_for_posting_bytes()?.len() + TERMINATOR_LEN;
+            let budget = max_article_size
+                .checked_sub(overhead)
+                .filter(|&budget| budget >= MIN_LINE_COST)
+                .ok_or_else(|| {
+                    NntpError::PostingFailed(format!(
+                        "article headers of {} bytes leave no room for a body within the \
+                         limit of {} bytes",
+                        overhead, max_article_size
+                    ))
+                })?;
+
+            let chunks = split_body(body, budget, !self.has_binary_body());
+            if chunks.len() <= total {
+                return Ok(chunks
+                    .iter()
+                    .enumerate()
+                    .map(|(index, chunk)| self.part(index + 1, chunks.len(), chunk, &finish))
+                    .collect());
+            }
+            total = chunks.len();
+        }
+    }
+
+    /// Part `number` of `total` with `body`
+    fn part(
+        &self,
+        number: usize,
+        total: usize,
+        body: &[u8],
+        finish: &impl Fn(&mut Headers),
+    ) -> Article {
+        let mut headers = self.headers.clone();
+        headers.subject = format!("{} ({}/{})", self.headers.subject, number, total);
+        headers.message_id = part_message_id(&self.headers.message_id, number);
+        // The line count of the whole article does not apply to a part
+        headers.lines = None;
+        finish(&mut headers);
+
+        let mut part = if self.has_binary_body() {
+            Article::with_body_bytes(headers, body.to_vec())
+        } else {
+            Article::new(headers, String::from_utf8_lossy(body).into_owned())
+        };
+        part.header_order = self.header_order.clone();
+        part
+    }
+}
+
+/// Message-ID of part `number`: `<local.number@domain>`
+fn part_message_id(message_id: &str, number: usize) -> String {
+    let id = message_id
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>');
+    match id.rsplit_once('@') {
+        Some((local, domain)) => format!("<{}.{}@{}>", local, number, domain),
+        None => format!("<{}.{}>", id, number),
+    }
+}
+
+/// Cut `body` into slices whose posted size is at most `budget` bytes
+///
+/// Slices end at line ends where possible. With `text`, a long line is only
+/// cut at a UTF-8 character boundary.
+fn split_body(body: &[u8], budget: usize, text: bool) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut used = 0;
+    let mut position = 0;
+
+    for line in BodyLines::new(body) {
+        let line_start = line.as_ptr() as usize - body.as_ptr() as usize;
+        let line_end = next_line_start(body, line_start + line.len());
+        let cost = posted_len(line);
+        if used + cost <= budget {
+            used += cost;
+            position = line_end;
+            continue;
+        }
+
+        if position > start {
+            chunks.push(&body[start..position]);
+        }
+        start = line_start;
+
+        // Cut a line too long for a part of its own
+        let mut rest = line;
+        while posted_len(rest) > budget {
+            let cut = cut_point(rest, budget, text);
+            chunks.push(&body[start..start + cut]);
+            start += cut;
+            rest = &rest[cut..];
+        }
+        used = posted_len(rest);
+        position = line_end;
+    }
+
+    if position > start || chunks.is_empty() {
+        chunks.push(&body[start..position.max(start)]);
+    }
+    chunks
+}
+
+/// Offset just past the line break (if any) at `end`
+fn next_line_start(body: &[u8], end: usize) -> usize {
+    match body.get(end..) {
+        Some([b'\r', b'\n', ..]) => end + 2,
+        Some([b'\n', ..]) => end + 1,
+        _ => end,
+    }
+}
+
+/// Bytes a body line takes when posted: dot-stuffing and CRLF included
+fn posted_len(line: &[u8]) -> usize {
+    line.len() + 2 + usize::from(line.starts_with(b"."))
+}
+
+/// Length of the longest prefix of `line` that posts within `budget`
+fn cut_point(line: &[u8], budget: usize, text: bool) -> usize {
+    let mut cut = budget - 2 - usize::from(line.starts_with(b"."));
+    if text {
+        while cut > 1 && (line[cut] & 0xC0) == 0x80 {
+            cut -= 1;
+        }
+    }
+    cut.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article::ArticleBuilder;
+
+    fn article(body: &str) -> Article {
+        ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject("Split")
+            .newsgroups(vec!["misc.test"])
+            .message_id("<split@example.com>")
+            .body(body)
+            .build()
+            .unwrap()
+    }
+
+    fn posted_body(part: &Article) -> Vec<u8> {
+        let posted = part.serialize_for_posting_bytes().unwrap();
+        let start = memchr::memmem::find(&posted, b"\r\n\r\n").unwrap() + 4;
+        posted[start..].to_vec()
+    }
+
+    #[test]
+    fn test_parts_fit_and_rejoin() {
+        let body: String = (0..400)
+            .map(|i| format!("{}line {}\n", if i % 9 == 0 { "." } else { "" }, i))
+            .collect();
+        let original = article(&body);
+
+        for limit in [600, 1000, 4096] {
+            let parts = original.split(limit).unwrap();
+            assert!(parts.len() > 1);
+            let mut joined = Vec::new();
+            for part in &parts {
+                assert!(
+                    part.serialize_for_posting_bytes().unwrap().len() + TERMINATOR_LEN <= limit
+                );
+                joined.extend(posted_body(part));
+            }
+            assert_eq!(joined, posted_body(&original));
+        }
+    }
+
+    #[test]
+    fn test_numbering_and_message_ids() {
+        let parts = article(&"x\n".repeat(2000)).split(800).unwrap();
+        let total = parts.len();
+        assert!(total >= 10);
+        for (index, part) in parts.iter().enumerate() {
+            assert_eq!(
+                part.headers.subject,
+                format!("Split ({}/{})", index + 1, total)
+            );
+            assert_eq!(
+                part.headers.message_id,
+                format!("<split.{}@example.com>", index + 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_small_article_is_one_part() {
+        let parts = article("short\n").split(10_000).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].headers.subject, "Split (1/1)");
+        assert_eq!(parts[0].body, "short\n");
+
+        let parts = article("").split(10_000).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].body, "");
+    }
+
+    #[test]
+    fn test_long_lines_are_cut_at_char_boundaries() {
+        let body = "é".repeat(2000);
+        let parts = article(&body).split(1000).unwrap();
+        assert!(parts.len() > 4);
+        let joined: String = parts.iter().map(|part| part.body.as_str()).collect();
+        assert_eq!(joined, body);
+    }
+
+    #[test]
+    fn test_binary_body_stays_binary() {
+        let original = ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject("Bin")
+            .newsgroups(vec!["alt.binaries.test"])
+            .body_bytes([0xffu8, 0xfe, b'\n'].repeat(500))
+            .build()
+            .unwrap();
+        let parts = original.split(700).unwrap();
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(Article::has_binary_body));
+        let joined: Vec<u8> = parts
+            .iter()
+            .flat_map(|part| part.body_bytes().to_vec())
+            .collect();
+        assert_eq!(joined, original.body_bytes());
+    }
+
+    #[test]
+    fn test_limit_below_headers_is_an_error() {
+        let result = article("body").split(100);
+        assert!(matches!(result, Err(NntpError::PostingFailed(_))));
+    }
+
+    #[test]
+    fn test_part_message_id_without_domain() {
+        assert_eq!(part_message_id("<abc>", 3), "<abc.3>");
+        assert_eq!(part_message_id("<a@b@c>", 1), "<a@b.1@c>");
+    }
+}
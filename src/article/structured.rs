@@ -0,0 +1,280 @@
+//! Typed views of structured header fields
+//!
+//! [`Headers`] keeps Date, From and Xref as the strings they arrived as;
+//! the accessors here parse them on demand.
+
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::Result;
+use crate::validation::parse_date_with_offset;
+
+use super::types::Headers;
+
+/// Characters that make a display name need quotes (RFC 5322 Section 3.2.3)
+const SPECIALS: &[char] = &[
+    '(', ')', '<', '>', '[', ']', ':', ';', '@', '\\', ',', '.', '"',
+];
+
+/// An author's mailbox: display name and address (RFC 5322 Section 3.4)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mailbox {
+    /// Display name, unquoted, if the mailbox has one
+    pub display_name: Option<String>,
+    /// Address, without angle brackets
+    pub address: String,
+}
+
+impl Mailbox {
+    /// Parse a mailbox in any of the forms found in From headers
+    ///
+    /// Accepts `Name <address>`, `"Quoted Name" <address>`, the older
+    /// `address (Name)`, and a bare `address`. Returns `None` if no address
+    /// can be found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::Mailbox;
+    ///
+    /// let mailbox = Mailbox::parse("\"Doe, Jane\" <jane@example.com>").unwrap();
+    /// assert_eq!(mailbox.display_name.as_deref(), Some("Doe, Jane"));
+    /// assert_eq!(mailbox.address, "jane@example.com");
+    ///
+    /// let mailbox = Mailbox::parse("joe@example.com (Joe Bloggs)").unwrap();
+    /// assert_eq!(mailbox.display_name.as_deref(), Some("Joe Bloggs"));
+    /// ```
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(open) = value.rfind('<') {
+            let (name, rest) = value.split_at(open);
+            let address = rest[1..].split_once('>')?.0.trim();
+            if address.is_empty() {
+                return None;
+            }
+            return Some(Self {
+                display_name: display_name(name),
+                address: address.to_string(),
+            });
+        }
+
+        let (address, comment) = match value.split_once('(') {
+            Some((address, comment)) => (address.trim(), comment.rsplit_once(')')?.0),
+            None => (value, ""),
+        };
+        if address.is_empty() || address.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Self {
+            display_name: display_name(comment),
+            address: address.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Mailbox {
+    /// Formats `Name <address>`, quoting the name where needed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.display_name {
+            Some(ref name) if name.contains(SPECIALS) => {
+                let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "\"{}\" <{}>", escaped, self.address)
+            }
+            Some(ref name) => write!(f, "{} <{}>", name, self.address),
+            None => f.write_str(&self.address),
+        }
+    }
+}
+
+/// Display name with its quotes and escapes removed, `None` if empty
+fn display_name(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let name = match raw
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+    {
+        Some(quoted) => {
+            let mut name = String::with_capacity(quoted.len());
+            let mut chars = quoted.chars();
+            while let Some(ch) = chars.next() {
+                name.push(if ch == '\\' {
+                    chars.next().unwrap_or(ch)
+                } else {
+                    ch
+                });
+            }
+            name
+        }
+        None => raw.to_string(),
+    };
+    (!name.trim().is_empty()).then(|| name.trim().to_string())
+}
+
+impl Headers {
+    /// Date header as a timestamp with its original offset (RFC 5536 Section 3.1.1)
+    ///
+    /// Accepts RFC 5322 dates, including the obsolete zone names, a "GMT"
+    /// zone, and a trailing comment such as "(UTC)".
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`](crate::NntpError::InvalidResponse)
+    /// if the date does not parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Dated")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .date("Mon, 20 Jan 2025 12:00:00 +0100 (CET)")
+    ///     .build()
+    ///     .unwrap();
+    /// let date = article.headers.parsed_date().unwrap();
+    /// assert_eq!(date.offset().local_minus_utc(), 3600);
+    /// ```
+    pub fn parsed_date(&self) -> Result<DateTime<FixedOffset>> {
+        parse_date_with_offset(&self.date)
+    }
+
+    /// From header as a display name and address (RFC 5536 Section 3.1.2)
+    ///
+    /// Returns `None` if the header holds no recognizable address.
+    pub fn from_mailbox(&self) -> Option<Mailbox> {
+        Mailbox::parse(&self.from)
+    }
+
+    /// Xref header as (newsgroup, article number) pairs (RFC 5536 Section 3.2.14)
+    ///
+    /// The server name that starts the header is left out, as are entries
+    /// that do not parse. Empty if the article has no Xref.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::parse_article;
+    ///
+    /// let article = parse_article(
+    ///     "From: a@example.com\r\nSubject: x\r\nNewsgroups: a.b,c.d\r\n\
+    ///      Message-ID: <x@example.com>\r\nDate: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+    ///      Path: news!not-for-mail\r\nXref: news.example.com a.b:17 c.d:4\r\n\r\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     article.headers.xref_entries(),
+    ///     vec![("a.b".to_string(), 17), ("c.d".to_string(), 4)]
+    /// );
+    /// ```
+    pub fn xref_entries(&self) -> Vec<(String, u64)> {
+        let Some(ref xref) = self.xref else {
+            return Vec::new();
+        };
+        xref.split_whitespace()
+            .skip(1)
+            .filter_map(|entry| {
+                let (group, number) = entry.rsplit_once(':')?;
+                let number = number.parse().ok()?;
+                (!group.is_empty()).then(|| (group.to_string(), number))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(date: &str, from: &str, xref: Option<&str>) -> Headers {
+        let mut headers = Headers::new(
+            date.to_string(),
+            from.to_string(),
+            "<typed@example.com>".to_string(),
+            vec!["misc.test".to_string()],
+            "not-for-mail".to_string(),
+            "Typed".to_string(),
+        );
+        headers.xref = xref.map(str::to_string);
+        headers
+    }
+
+    #[test]
+    fn test_parsed_date_forms() {
+        let expected = DateTime::parse_from_rfc3339("2025-01-20T12:00:00+00:00").unwrap();
+        for date in [
+            "Mon, 20 Jan 2025 12:00:00 +0000",
+            "20 Jan 2025 12:00:00 GMT",
+            "Mon, 20 Jan 2025 07:00:00 EST",
+            "Mon, 20 Jan 2025 12:00:00 +0000 (UTC)",
+        ] {
+            let parsed = headers(date, "a@b", None).parsed_date().unwrap();
+            assert_eq!(parsed, expected, "{}", date);
+        }
+        let offset = headers("Mon, 20 Jan 2025 13:00:00 +0100", "a@b", None)
+            .parsed_date()
+            .unwrap();
+        assert_eq!(offset.offset().local_minus_utc(), 3600);
+        assert!(headers("yesterday", "a@b", None).parsed_date().is_err());
+    }
+
+    #[test]
+    fn test_from_mailbox_forms() {
+        let cases = [
+            (
+                "Jane Doe <jane@example.com>",
+                Some("Jane Doe"),
+                "jane@example.com",
+            ),
+            (
+                "\"Doe, \\\"JD\\\" Jane\" <jane@example.com>",
+                Some("Doe, \"JD\" Jane"),
+                "jane@example.com",
+            ),
+            ("<jane@example.com>", None, "jane@example.com"),
+            (
+                "jane@example.com (Jane Doe)",
+                Some("Jane Doe"),
+                "jane@example.com",
+            ),
+            ("jane@example.com", None, "jane@example.com"),
+        ];
+        for (from, name, address) in cases {
+            let mailbox = headers("", from, None).from_mailbox().unwrap();
+            assert_eq!(mailbox.display_name.as_deref(), name, "{}", from);
+            assert_eq!(mailbox.address, address, "{}", from);
+        }
+        assert_eq!(headers("", "", None).from_mailbox(), None);
+        assert_eq!(headers("", "Jane <", None).from_mailbox(), None);
+        assert_eq!(headers("", "just a name", None).from_mailbox(), None);
+    }
+
+    #[test]
+    fn test_mailbox_display() {
+        let mailbox = Mailbox {
+            display_name: Some("Doe, \"JD\" Jane".to_string()),
+            address: "jane@example.com".to_string(),
+        };
+        let formatted = mailbox.to_string();
+        assert_eq!(formatted, "\"Doe, \\\"JD\\\" Jane\" <jane@example.com>");
+        assert_eq!(Mailbox::parse(&formatted), Some(mailbox));
+        assert_eq!(
+            Mailbox::parse("Jane Doe <jane@example.com>")
+                .unwrap()
+                .to_string(),
+            "Jane Doe <jane@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_xref_entries() {
+        let xref = "news.example.com alt.test:12 bad comp.lang.rust:x misc.test:3";
+        assert_eq!(
+            headers("", "", Some(xref)).xref_entries(),
+            vec![("alt.test".to_string(), 12), ("misc.test".to_string(), 3)]
+        );
+        assert!(headers("", "", None).xref_entries().is_empty());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This module contains the core data structures for representing Usenet articles.
 
-use std::collections::HashMap;
+use super::HeaderMap;
 use std::fmt::Write;
 
 use crate::{NntpError, Result};
@@ -25,7 +25,7 @@ use crate::{NntpError, Result};
 ///
 /// ```
 /// use nntp_rs::article::{Article, Headers};
-/// use std::collections::HashMap;
+/// use nntp_rs::article::HeaderMap;
 ///
 /// let headers = Headers {
 ///     date: "Mon, 20 Jan 2025 12:00:00 +0000".to_string(),
@@ -48,7 +48,7 @@ use crate::{NntpError, Result};
 ///     lines: None,
 ///     user_agent: None,
 ///     xref: None,
-///     extra: HashMap::new(),
+///     extra: HeaderMap::new(),
 /// };
 ///
 /// // In practice, use ArticleBuilder to create articles
@@ -67,15 +67,23 @@ pub struct Article {
     /// Article headers
     pub headers: Headers,
     /// Article body (after blank line separator)
+    ///
+    /// For an article with a binary body (see
+    /// [`with_body_bytes`](Self::with_body_bytes)) this is a lossy UTF-8
+    /// rendering for display; the bytes are what gets posted.
     pub body: String,
     /// Original raw article text for round-trip preservation
     pub(crate) raw: Option<String>,
+    /// Body bytes of an article built from bytes, which may not be UTF-8
+    pub(crate) binary_body: Option<Vec<u8>>,
+    /// Header names in their original order and spelling, for a parsed article
+    pub(crate) header_order: Option<Vec<String>>,
 }
 
 /// Netnews article headers (RFC 5536)
 ///
 /// Contains all standard headers defined in RFC 5536, plus an `extra`
-/// HashMap for non-standard extension headers.
+/// [`HeaderMap`] for non-standard extension headers.
 #[derive(Debug, Clone)]
 pub struct Headers {
     // Required headers (RFC 5536 Section 3.1)
@@ -158,8 +166,9 @@ pub struct Headers {
     pub xref: Option<String>,
 
     /// Additional non-standard headers
-    /// Includes X-* headers and other extensions
-    pub extra: HashMap<String, String>,
+    /// Includes X-* headers and other extensions, in article order and with
+    /// their names spelled as received
+    pub extra: HeaderMap,
 }
 
 impl Article {
@@ -169,9 +178,56 @@ impl Article {
             headers,
             body,
             raw: None,
+            binary_body: None,
+            header_order: None,
+        }
+    }
+
+    /// Create an article whose body is raw bytes, such as yEnc or 8-bit text
+    ///
+    /// The bytes are posted exactly as given, only with CRLF line endings
+    /// and dot-stuffing applied; [`body`](Self::body) holds a lossy UTF-8
+    /// copy. Bytes received with
+    /// [`NntpBinaryResponse::split_article`](crate::NntpBinaryResponse::split_article)
+    /// can be reposted this way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::{Article, Headers};
+    ///
+    /// let headers = Headers::new(
+    ///     "Mon, 20 Jan 2025 12:00:00 +0000".to_string(),
+    ///     "poster@example.com".to_string(),
+    ///     "<bin@example.com>".to_string(),
+    ///     vec!["alt.binaries.test".to_string()],
+    ///     "not-for-mail".to_string(),
+    ///     "file.bin".to_string(),
+    /// );
+    /// let article = Article::with_body_bytes(headers, b"=ybegin\n\xff\xfe\n".to_vec());
+    /// assert_eq!(article.body_bytes(), b"=ybegin\n\xff\xfe\n");
+    /// assert!(article.serialize_for_posting_bytes().unwrap().ends_with(b"\xff\xfe\r\n"));
+    /// ```
+    pub fn with_body_bytes(headers: Headers, body: Vec<u8>) -> Self {
+        Self {
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+            raw: None,
+            binary_body: Some(body),
+            header_order: None,
         }
     }
 
+    /// Body as bytes: the binary body if the article has one, else [`body`](Self::body)
+    pub fn body_bytes(&self) -> &[u8] {
+        self.binary_body.as_deref().unwrap_or(self.body.as_bytes())
+    }
+
+    /// Check if the article was built from body bytes
+    pub fn has_binary_body(&self) -> bool {
+        self.binary_body.is_some()
+    }
+
     /// Get the raw article text if available
     pub fn raw(&self) -> Option<&str> {
         self.raw.as_deref()
@@ -339,6 +395,152 @@ impl Article {
             .unwrap_or(false)
     }
 
+    /// Body as text, with its transfer encoding and charset undone (RFC 2045)
+    ///
+    /// A quoted-printable or base64 body is decoded to bytes, which are
+    /// then read in the charset of the Content-Type (UTF-8 if none; see
+    /// [`decode_charset`](crate::encoded_words::decode_charset) for the
+    /// charsets supported). Any other body is read in that charset from its
+    /// raw bytes when the article has them (see
+    /// [`has_binary_body`](Self::has_binary_body)), and returned as it is
+    /// otherwise.
+    ///
+    /// **Note:** [`NntpClient::fetch_article`](crate::NntpClient::fetch_article)
+    /// reads the body as UTF-8, replacing invalid bytes, so 8-bit text in a
+    /// legacy charset such as ISO-8859-1 or KOI8-R is already lost. Fetch it
+    /// with [`fetch_article_binary`](crate::NntpClient::fetch_article_binary)
+    /// and [`NntpBinaryResponse::into_article`](crate::NntpBinaryResponse::into_article)
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Grüße")
+    ///     .newsgroups(vec!["de.test"])
+    ///     .extra_header("Content-Type", "text/plain; charset=iso-8859-1")
+    ///     .extra_header("Content-Transfer-Encoding", "quoted-printable")
+    ///     .body("Gr=FC=DFe")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(article.decoded_body().unwrap(), "Grüße");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if a base64 body does not decode.
+    pub fn decoded_body(&self) -> Result<String> {
+        let encoding = self
+            .headers
+            .extra
+            .get("Content-Transfer-Encoding")
+            .map(|encoding| encoding.trim().to_ascii_lowercase());
+        let Some(encoding @ ("quoted-printable" | "base64")) = encoding.as_deref() else {
+            return Ok(match &self.binary_body {
+                Some(bytes) => {
+                    crate::encoded_words::decode_charset(bytes, self.charset().unwrap_or("utf-8"))
+                }
+                None => self.body.clone(),
+            });
+        };
+
+        let lines: Vec<&str> = self.body.lines().collect();
+        let bytes = super::mime::decode_content(encoding, &lines)?;
+        Ok(crate::encoded_words::decode_charset(
+            &bytes,
+            self.charset().unwrap_or("utf-8"),
+        ))
+    }
+
+    /// Check if the body is format=flowed text (RFC 3676)
+    pub fn is_flowed(&self) -> bool {
+        self.content_type_param("format")
+            .is_some_and(|format| format.eq_ignore_ascii_case("flowed"))
+    }
+
+    /// Body text with format=flowed paragraphs joined for display (RFC 3676)
+    ///
+    /// Starts from [`decoded_body`](Self::decoded_body); if the
+    /// Content-Type has `format=flowed`, soft line breaks are removed (with
+    /// the trailing space too for `delsp=yes`) so each paragraph is one
+    /// line that the reader can wrap to its width. See
+    /// [`flowed::unflow`](crate::flowed::unflow). Other bodies are returned
+    /// as decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Flowed")
+    ///     .newsgroups(vec!["misc.test"])
+    ///     .extra_header("Content-Type", "text/plain; charset=utf-8; format=flowed")
+    ///     .body("A soft \nbreak.\n> Quoted \n> too.")
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(article.is_flowed());
+    /// assert_eq!(article.unflowed_body().unwrap(), "A soft break.\n> Quoted too.");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// As for [`decoded_body`](Self::decoded_body).
+    pub fn unflowed_body(&self) -> Result<String> {
+        let body = self.decoded_body()?;
+        if !self.is_flowed() {
+            return Ok(body);
+        }
+        let delsp = self
+            .content_type_param("delsp")
+            .is_some_and(|delsp| delsp.eq_ignore_ascii_case("yes"));
+        Ok(crate::flowed::unflow(&body, delsp))
+    }
+
+    fn content_type_param(&self, name: &str) -> Option<String> {
+        super::mime::header_param(self.content_type()?, name)
+    }
+
+    /// Split a multipart MIME body into its parts (RFC 2046 Section 5.1)
+    ///
+    /// Each part carries its own headers and its content with the transfer
+    /// encoding removed; nested multiparts are split recursively. See
+    /// [`mime::parse_multipart`](super::mime::parse_multipart).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Attachment")
+    ///     .newsgroups(vec!["alt.test"])
+    ///     .extra_header("Content-Type", "multipart/mixed; boundary=\"b1\"")
+    ///     .body("--b1\n\nSee attached\n--b1\nContent-Disposition: attachment; filename=\"a.txt\"\n\nhello\n--b1--\n")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let parts = article.mime_parts().unwrap();
+    /// assert_eq!(parts[0].text(), Some("See attached"));
+    /// assert_eq!(parts[1].filename().as_deref(), Some("a.txt"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the article is not a
+    /// multipart with a boundary or a part cannot be decoded.
+    pub fn mime_parts(&self) -> Result<Vec<super::mime::MimePart>> {
+        let content_type = self
+            .content_type()
+            .ok_or_else(|| NntpError::InvalidResponse("Article has no Content-Type".to_string()))?;
+        super::mime::parse_multipart(content_type, &self.body)
+    }
+
     /// Extract the charset parameter from the Content-Type header (RFC 5536 Section 4)
     ///
     /// Returns the charset parameter value if present in the Content-Type header.
@@ -411,86 +613,181 @@ impl Article {
     /// let wire_format = article.serialize_for_posting().unwrap();
     /// assert!(wire_format.contains("\r\n"));
     /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::Utf8`] if the article has a binary body that is
+    /// not valid UTF-8; use
+    /// [`serialize_for_posting_bytes`](Self::serialize_for_posting_bytes)
+    /// for those.
     pub fn serialize_for_posting(&self) -> Result<String> {
+        Ok(String::from_utf8(self.serialize_for_posting_bytes()?)?)
+    }
+
+    /// Serialize the article for posting as bytes
+    ///
+    /// Same wire format as [`serialize_for_posting`](Self::serialize_for_posting),
+    /// but binary bodies (see [`with_body_bytes`](Self::with_body_bytes))
+    /// are kept byte for byte.
+    ///
+    /// # Errors
+    ///
+    /// Does not currently fail; returns `Result` like
+    /// [`serialize_for_posting`](Self::serialize_for_posting).
+    pub fn serialize_for_posting_bytes(&self) -> Result<Vec<u8>> {
+        let body = self.body_bytes();
         // Pre-allocate capacity: typical headers are ~1KB, body varies
-        let mut result = String::with_capacity(1024 + self.body.len());
-
-        // Write required headers
-        // SAFETY: write! to String is infallible (OOM aside)
-        #[expect(clippy::unwrap_used)]
-        {
-            write!(result, "Date: {}\r\n", self.headers.date).unwrap();
-            write!(result, "From: {}\r\n", self.headers.from).unwrap();
-            write!(result, "Message-ID: {}\r\n", self.headers.message_id).unwrap();
-            write!(
-                result,
-                "Newsgroups: {}\r\n",
-                self.headers.newsgroups.join(",")
-            )
-            .unwrap();
-            write!(result, "Path: {}\r\n", self.headers.path).unwrap();
-            write!(result, "Subject: {}\r\n", self.headers.subject).unwrap();
-
-            // Write optional headers
-            if let Some(ref references) = self.headers.references {
-                write!(result, "References: {}\r\n", references.join(" ")).unwrap();
-            }
-            if let Some(ref reply_to) = self.headers.reply_to {
-                write!(result, "Reply-To: {}\r\n", reply_to).unwrap();
-            }
-            if let Some(ref organization) = self.headers.organization {
-                write!(result, "Organization: {}\r\n", organization).unwrap();
-            }
-            if let Some(ref followup_to) = self.headers.followup_to {
-                write!(result, "Followup-To: {}\r\n", followup_to.join(",")).unwrap();
-            }
-            if let Some(ref expires) = self.headers.expires {
-                write!(result, "Expires: {}\r\n", expires).unwrap();
-            }
-            if let Some(ref control) = self.headers.control {
-                write!(result, "Control: {}\r\n", control).unwrap();
-            }
-            if let Some(ref distribution) = self.headers.distribution {
-                write!(result, "Distribution: {}\r\n", distribution).unwrap();
-            }
-            if let Some(ref keywords) = self.headers.keywords {
-                write!(result, "Keywords: {}\r\n", keywords).unwrap();
-            }
-            if let Some(ref summary) = self.headers.summary {
-                write!(result, "Summary: {}\r\n", summary).unwrap();
-            }
-            if let Some(ref supersedes) = self.headers.supersedes {
-                write!(result, "Supersedes: {}\r\n", supersedes).unwrap();
-            }
-            if let Some(ref approved) = self.headers.approved {
-                write!(result, "Approved: {}\r\n", approved).unwrap();
-            }
-            if let Some(ref user_agent) = self.headers.user_agent {
-                write!(result, "User-Agent: {}\r\n", user_agent).unwrap();
-            }
+        let mut headers = String::with_capacity(1024);
+        self.write_posting_headers(&mut headers);
+        let mut result = headers.into_bytes();
+        result.reserve(body.len() + body.len() / 64 + 2);
 
-            // Write extra headers
-            for (name, value) in &self.headers.extra {
-                write!(result, "{}: {}\r\n", name, value).unwrap();
+        // Write body with dot-stuffing
+        for line in super::chunks::BodyLines::new(body) {
+            if line.starts_with(b".") {
+                result.push(b'.');
             }
+            result.extend_from_slice(line);
+            result.extend_from_slice(b"\r\n");
+        }
+
+        Ok(result)
+    }
+
+    /// Serialize the article for posting in bounded chunks
+    ///
+    /// Yields the same bytes as [`serialize_for_posting`](Self::serialize_for_posting),
+    /// but without building the whole article in memory: the first chunk holds the
+    /// headers and blank separator line, and each following chunk holds whole
+    /// dot-stuffed body lines totalling roughly `chunk_size` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nntp_rs::article::ArticleBuilder;
+    ///
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Test")
+    ///     .newsgroups(vec!["test.group"])
+    ///     .body(".hidden\nvisible")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let streamed: Vec<u8> = article.posting_chunks(1024).flatten().collect();
+    /// assert_eq!(streamed, article.serialize_for_posting().unwrap().into_bytes());
+    /// ```
+    pub fn posting_chunks(&self, chunk_size: usize) -> super::PostingChunks<'_> {
+        super::PostingChunks::new(self, chunk_size)
+    }
+
+    /// Write the headers and the blank separator line in posting wire format
+    ///
+    /// A parsed article writes its headers in the order and spelling they
+    /// arrived in; headers added since then, and all headers of a built
+    /// article, follow in the standard order with extras last.
+    pub(crate) fn write_posting_headers(&self, result: &mut String) {
+        for (name, value) in self.posting_headers() {
+            write_header(result, name, &value);
         }
 
         // Blank line separates headers from body
         result.push_str("\r\n");
+    }
 
-        // Write body with dot-stuffing
-        for line in self.body.lines() {
-            if line.starts_with('.') {
-                result.push('.');
+    /// Header fields to post, in the order described at
+    /// [`write_posting_headers`](Self::write_posting_headers)
+    pub(super) fn posting_headers(&self) -> Vec<(&str, String)> {
+        let mut standard = self.standard_fields();
+        let mut extra_written = vec![false; self.headers.extra.len()];
+        let mut fields = Vec::with_capacity(standard.len() + self.headers.extra.len());
+
+        for name in self.header_order.iter().flatten() {
+            if let Some(field) = standard
+                .iter_mut()
+                .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            {
+                if let Some(value) = field.1.take() {
+                    fields.push((name.as_str(), value));
+                }
+            } else if let Some((index, (_, value))) =
+                self.headers
+                    .extra
+                    .iter()
+                    .enumerate()
+                    .find(|(index, (field, _))| {
+                        !extra_written[*index] && field.eq_ignore_ascii_case(name)
+                    })
+            {
+                fields.push((name.as_str(), value.clone()));
+                extra_written[index] = true;
             }
-            result.push_str(line);
-            result.push_str("\r\n");
         }
 
-        Ok(result)
+        for (name, value) in standard {
+            if let Some(value) = value {
+                fields.push((name, value));
+            }
+        }
+        for (index, (name, value)) in self.headers.extra.iter().enumerate() {
+            if !extra_written[index] {
+                fields.push((name.as_str(), value.clone()));
+            }
+        }
+        fields
+    }
+
+    /// Standard headers to post, in standard order, with their values if set
+    ///
+    /// Lines is only reposted where the original article had it; Xref is
+    /// local to the server that added it and never reposted.
+    fn standard_fields(&self) -> Vec<(&'static str, Option<String>)> {
+        let h = &self.headers;
+        let mut fields = vec![
+            ("Date", Some(h.date.clone())),
+            ("From", Some(h.from.clone())),
+            ("Message-ID", Some(h.message_id.clone())),
+            ("Newsgroups", Some(h.newsgroups.join(","))),
+            ("Path", Some(h.path.clone())),
+            ("Subject", Some(h.subject.clone())),
+            (
+                "References",
+                h.references.as_ref().map(|refs| refs.join(" ")),
+            ),
+            ("Reply-To", h.reply_to.clone()),
+            ("Organization", h.organization.clone()),
+            (
+                "Followup-To",
+                h.followup_to.as_ref().map(|groups| groups.join(",")),
+            ),
+            ("Expires", h.expires.clone()),
+            ("Control", h.control.clone()),
+            ("Distribution", h.distribution.clone()),
+            ("Keywords", h.keywords.clone()),
+            ("Summary", h.summary.clone()),
+            ("Supersedes", h.supersedes.clone()),
+            ("Approved", h.approved.clone()),
+            ("User-Agent", h.user_agent.clone()),
+        ];
+        let had_lines = self
+            .header_order
+            .iter()
+            .flatten()
+            .any(|name| name.eq_ignore_ascii_case("Lines"));
+        if had_lines {
+            fields.push(("Lines", h.lines.map(|lines| lines.to_string())));
+        }
+        fields
     }
 }
 
+/// Write one `name: value` header line
+fn write_header(result: &mut String, name: &str, value: &str) {
+    // SAFETY: write! to String is infallible (OOM aside)
+    #[expect(clippy::unwrap_used)]
+    write!(result, "{}: {}\r\n", name, value).unwrap();
+}
+
 /// Control message types (RFC 5537 Section 5)
 ///
 /// Control messages are special articles that trigger administrative actions
@@ -725,6 +1022,35 @@ impl ControlMessage {
             }
         }
     }
+
+    /// Verify the PGP signature of a signed control message (pgpverify)
+    ///
+    /// Checks the X-PGP-Sig header that `signcontrol` adds to newgroup,
+    /// rmgroup and checkgroups messages against the trusted keys of
+    /// `keyring`, and returns the user ID of the key that made it. Only act
+    /// on a control message after this succeeds and the signer is the
+    /// administrator of the hierarchy it affects.
+    ///
+    /// The article should come from [`parse_article`](crate::parse_article)
+    /// so the signed headers are checked as they were received. See
+    /// [`pgpverify`](crate::pgpverify) for the signature format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if:
+    /// - the article has no Control or X-PGP-Sig header, or the Control
+    ///   header is not among the signed ones
+    /// - a signed header appears more than once
+    /// - the signature does not parse or uses an unsupported algorithm
+    /// - no key of `keyring` made the signature
+    /// - the signature does not match the article
+    #[cfg(feature = "pgpverify")]
+    pub fn verify_signature(
+        article: &Article,
+        keyring: &crate::pgpverify::Keyring,
+    ) -> Result<String> {
+        crate::pgpverify::verify_article(article, keyring)
+    }
 }
 
 impl Headers {
@@ -767,7 +1093,7 @@ impl Headers {
             lines: None,
             user_agent: None,
             xref: None,
-            extra: HashMap::new(),
+            extra: HeaderMap::new(),
         }
     }
 
@@ -789,7 +1115,7 @@ impl Headers {
     /// ```
     /// use nntp_rs::article::Headers;
     /// use nntp_rs::validation::ValidationConfig;
-    /// use std::collections::HashMap;
+    /// use nntp_rs::article::HeaderMap;
     ///
     /// let headers = Headers {
     ///     date: "Tue, 20 Jan 2026 12:00:00 +0000".to_string(),
@@ -812,7 +1138,7 @@ impl Headers {
     ///     lines: None,
     ///     user_agent: None,
     ///     xref: None,
-    ///     extra: HashMap::new(),
+    ///     extra: HeaderMap::new(),
     /// };
     ///
     /// let config = ValidationConfig::default();
@@ -913,7 +1239,7 @@ impl Headers {
     ///
     /// ```
     /// use nntp_rs::article::Headers;
-    /// use std::collections::HashMap;
+    /// use nntp_rs::article::HeaderMap;
     ///
     /// let headers = Headers {
     ///     date: "Mon, 20 Jan 2025 12:00:00 +0000".to_string(),
@@ -936,7 +1262,7 @@ impl Headers {
     ///     lines: None,
     ///     user_agent: None,
     ///     xref: None,
-    ///     extra: HashMap::new(),
+    ///     extra: HeaderMap::new(),
     /// };
     ///
     /// let path_components = headers.parse_path();
@@ -960,7 +1286,7 @@ impl Headers {
     ///
     /// ```
     /// use nntp_rs::article::Headers;
-    /// use std::collections::HashMap;
+    /// use nntp_rs::article::HeaderMap;
     ///
     /// let headers = Headers {
     ///     date: "Mon, 20 Jan 2025 12:00:00 +0000".to_string(),
@@ -983,7 +1309,7 @@ impl Headers {
     ///     lines: None,
     ///     user_agent: None,
     ///     xref: None,
-    ///     extra: HashMap::new(),
+    ///     extra: HeaderMap::new(),
     /// };
     ///
     /// assert_eq!(headers.originating_server(), Some("news1.example.com"));
@@ -1001,7 +1327,7 @@ impl Headers {
     ///
     /// ```
     /// use nntp_rs::article::Headers;
-    /// use std::collections::HashMap;
+    /// use nntp_rs::article::HeaderMap;
     ///
     /// let headers = Headers {
     ///     date: "Mon, 20 Jan 2025 12:00:00 +0000".to_string(),
@@ -1024,7 +1350,7 @@ impl Headers {
     ///     lines: None,
     ///     user_agent: None,
     ///     xref: None,
-    ///     extra: HashMap::new(),
+    ///     extra: HeaderMap::new(),
     /// };
     ///
     /// assert_eq!(headers.path_length(), 3);
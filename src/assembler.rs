@@ -4,10 +4,14 @@
 //! articles into complete files. It handles part collection, yEnc decoding,
 //! CRC32 verification, and file assembly.
 
+use crate::completeness::subject_filename;
 use crate::error::{NntpError, Result};
 use crate::nzb::{NzbFile, NzbSegment};
+use crate::output::OutputSink;
+use crate::par2::{IfscPacket, PAR2_MAX_SLICES, Par2File};
 use crate::yenc::{YencDecoded, YencMultipartAssembler, decode};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Status of an article part
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,6 +37,56 @@ pub struct PartInfo {
     pub decoded: Option<YencDecoded>,
 }
 
+/// PAR2 slice checksums of the target file, verified as parts arrive
+#[derive(Debug, Clone)]
+struct SliceVerifier {
+    /// PAR2 slice size in bytes
+    slice_size: u64,
+    /// Expected length of the target file
+    file_length: u64,
+    /// Expected CRC32 per slice (from the IFSC packet)
+    checksums: Vec<u32>,
+    /// Per-slice result: `None` until every byte of the slice has arrived
+    results: Vec<Option<bool>>,
+}
+
+impl SliceVerifier {
+    fn new(ifsc: &IfscPacket, slice_size: u64, file_length: u64) -> Self {
+        // A valid set never has more slices than the format allows, so the
+        // cap only guards the allocation against a crafted file length
+        let slice_count = if slice_size == 0 {
+            0
+        } else {
+            file_length.div_ceil(slice_size).min(PAR2_MAX_SLICES) as usize
+        };
+        Self {
+            slice_size,
+            file_length,
+            checksums: ifsc.checksums.clone(),
+            results: vec![None; slice_count],
+        }
+    }
+
+    /// Byte range (0-based, end exclusive) of a slice within the file
+    fn slice_range(&self, index: usize) -> (u64, u64) {
+        let start = index as u64 * self.slice_size;
+        let end = (start + self.slice_size).min(self.file_length);
+        (start, end)
+    }
+
+    /// Indices of unverified slices overlapping the byte range `start..end`
+    fn pending_slices_in(&self, start: u64, end: u64) -> Vec<usize> {
+        if self.slice_size == 0 || end <= start {
+            return Vec::new();
+        }
+        let first = (start / self.slice_size) as usize;
+        let last = ((end - 1) / self.slice_size) as usize;
+        (first..=last.min(self.results.len().saturating_sub(1)))
+            .filter(|&index| self.results.get(index) == Some(&None))
+            .collect()
+    }
+}
+
 /// Article assembler that collects and assembles parts
 ///
 /// This assembler:
@@ -49,6 +103,8 @@ pub struct ArticleAssembler {
     parts: HashMap<u32, PartInfo>,
     /// yEnc multi-part assembler for combining parts
     yenc_assembler: YencMultipartAssembler,
+    /// PAR2 slice checksums, if the assembler verifies slices in place
+    slices: Option<SliceVerifier>,
 }
 
 impl ArticleAssembler {
@@ -101,9 +157,62 @@ impl ArticleAssembler {
             file,
             parts,
             yenc_assembler: YencMultipartAssembler::new(),
+            slices: None,
         }
     }
 
+    /// Create an assembler that verifies PAR2 slices as parts arrive
+    ///
+    /// Each slice is CRC32-checked against the IFSC checksums as soon as all of
+    /// its bytes have been added, so [`damaged_slices`](Self::damaged_slices)
+    /// is available without a separate verification pass over the finished file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The NZB file containing segment information
+    /// * `ifsc` - The IFSC packet of the target file
+    /// * `slice_size` - The PAR2 slice size (from the Main packet)
+    /// * `file_length` - The target file length (from the File Description packet)
+    pub fn with_slice_checksums(
+        file: NzbFile,
+        ifsc: &IfscPacket,
+        slice_size: u64,
+        file_length: u64,
+    ) -> Self {
+        let mut assembler = Self::new(file);
+        assembler.slices = Some(SliceVerifier::new(ifsc, slice_size, file_length));
+        assembler
+    }
+
+    /// Create an assembler that verifies slices of `file_id` from a PAR2 index
+    ///
+    /// Convenience wrapper around [`with_slice_checksums`](Self::with_slice_checksums)
+    /// that takes the slice size, file length and IFSC checksums from `par2`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the PAR2 file has no Main packet,
+    /// or no File Description or IFSC packet for `file_id`.
+    pub fn with_par2(file: NzbFile, par2: &Par2File, file_id: &[u8; 16]) -> Result<Self> {
+        let slice_size = par2.slice_size().ok_or_else(|| {
+            NntpError::InvalidResponse("No main packet found in PAR2".to_string())
+        })?;
+        let file_desc = par2
+            .file_descriptions
+            .get(file_id)
+            .ok_or_else(|| NntpError::InvalidResponse("File ID not found in PAR2".to_string()))?;
+        let ifsc = par2.ifsc_packets.get(file_id).ok_or_else(|| {
+            NntpError::InvalidResponse("No IFSC packet for file in PAR2".to_string())
+        })?;
+
+        Ok(Self::with_slice_checksums(
+            file,
+            ifsc,
+            slice_size,
+            file_desc.length,
+        ))
+    }
+
     /// Add a downloaded article part from raw bytes
     ///
     /// The article content should be the raw bytes from the NNTP server article body.
@@ -154,6 +263,12 @@ impl ArticleAssembler {
         // Update part info
         part_info.status = PartStatus::Downloaded;
 
+        // Byte range this part covers (yEnc offsets are 1-based, end inclusive)
+        let (start, end) = match &decoded.part {
+            Some(part) => (part.begin.saturating_sub(1), part.end),
+            None => (0, decoded.data.len() as u64),
+        };
+
         // Add to yEnc assembler if multi-part, otherwise store decoded data
         if decoded.is_multipart() {
             self.yenc_assembler.add_part(decoded)?;
@@ -161,9 +276,81 @@ impl ArticleAssembler {
             part_info.decoded = Some(decoded);
         }
 
+        self.verify_slices_in(start, end);
+
         Ok(())
     }
 
+    /// CRC32-check every slice overlapping `start..end` that is now fully available
+    fn verify_slices_in(&mut self, start: u64, end: u64) {
+        let Some(slices) = &self.slices else {
+            return;
+        };
+
+        let mut results = Vec::new();
+        for index in slices.pending_slices_in(start, end) {
+            let (slice_start, slice_end) = slices.slice_range(index);
+            if let Some(crc) = self.crc32_range(slice_start, slice_end) {
+                let ok = slices.checksums.get(index) == Some(&crc);
+                results.push((index, ok));
+            }
+        }
+
+        if let Some(slices) = &mut self.slices {
+            for (index, ok) in results {
+                slices.results[index] = Some(ok);
+            }
+        }
+    }
+
+    /// CRC32 of the decoded file bytes in `start..end`, if all have been received
+    fn crc32_range(&self, start: u64, end: u64) -> Option<u32> {
+        // Single-part files are stored directly as part 1
+        if let Some(decoded) = self.parts.get(&1).and_then(|p| p.decoded.as_ref()) {
+            let from = usize::try_from(start).ok()?;
+            let to = usize::try_from(end).ok()?;
+            let mut hasher = crc32fast::Hasher::new();
+            hasher.update(decoded.data.get(from..to)?);
+            return Some(hasher.finalize());
+        }
+        self.yenc_assembler.crc32_range(start, end)
+    }
+
+    /// Get PAR2 slices that failed verification (0-based, file-local indices)
+    ///
+    /// Only meaningful for assemblers created with
+    /// [`with_slice_checksums`](Self::with_slice_checksums) or
+    /// [`with_par2`](Self::with_par2); returns an empty list otherwise.
+    ///
+    /// While parts are still pending, only slices that were checked and found
+    /// damaged are reported. Once every part has been processed, slices that
+    /// could not be checked (because a covering part is missing or corrupted,
+    /// or there is no checksum for them) are reported as damaged too. The
+    /// indices match [`FileStatus::Damaged`](crate::FileStatus::Damaged).
+    pub fn damaged_slices(&self) -> Vec<usize> {
+        let Some(slices) = &self.slices else {
+            return Vec::new();
+        };
+        let complete = self.is_complete();
+        slices
+            .results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| match result {
+                Some(ok) => !ok,
+                None => complete,
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Get the number of PAR2 slices verified intact so far
+    pub fn verified_slices(&self) -> usize {
+        self.slices.as_ref().map_or(0, |slices| {
+            slices.results.iter().filter(|r| **r == Some(true)).count()
+        })
+    }
+
     /// Mark a segment as missing
     ///
     /// Call this when a segment cannot be downloaded (e.g., 430 Not Found error)
@@ -368,6 +555,33 @@ impl ArticleAssembler {
         Ok(assembled)
     }
 
+    /// Name to give the assembled file
+    ///
+    /// The `name` from the yEnc header of a downloaded part if there is one,
+    /// otherwise the quoted file name in the subject, otherwise the subject.
+    pub fn output_filename(&self) -> &str {
+        let mut numbers: Vec<&u32> = self.parts.keys().collect();
+        numbers.sort_unstable();
+        numbers
+            .into_iter()
+            .filter_map(|number| self.parts.get(number)?.decoded.as_ref())
+            .map(|decoded| decoded.header.name.as_str())
+            .find(|name| !name.is_empty())
+            .or_else(|| subject_filename(&self.file.subject))
+            .unwrap_or(&self.file.subject)
+    }
+
+    /// Assemble the file and write it through `sink`
+    ///
+    /// The file is named by [`output_filename`](Self::output_filename) and
+    /// passes through the sink's transform, if any. Returns the path written,
+    /// which is in the sink's staging directory if it has one; see
+    /// [`OutputSink::finalize`].
+    pub fn write_to(&self, sink: &OutputSink) -> Result<PathBuf> {
+        let data = self.assemble()?;
+        sink.write(self.output_filename(), &data)
+    }
+
     /// Get the status of a specific part
     pub fn part_status(&self, segment_number: u32) -> Option<&PartStatus> {
         self.parts.get(&segment_number).map(|p| &p.status)
@@ -438,6 +652,31 @@ mod tests {
         assert_eq!(result, test_data);
     }
 
+    #[test]
+    fn test_write_to_sink() {
+        let test_data = b"Hello, World!";
+        let encoded_bytes = encode(test_data, "real.bin", 128, None).unwrap();
+
+        let mut file = create_test_file(vec![NzbSegment {
+            bytes: test_data.len() as u64,
+            number: 1,
+            message_id: "<part1@example.com>".to_string(),
+        }]);
+        file.subject = "[1/1] \"subject.bin\" yEnc (1/1)".to_string();
+
+        let mut assembler = ArticleAssembler::new(file);
+        assert_eq!(assembler.output_filename(), "subject.bin");
+        assembler.add_part_bytes(1, &encoded_bytes).unwrap();
+        assert_eq!(assembler.output_filename(), "real.bin");
+
+        let dir = std::env::temp_dir().join(format!("nntp-rs-assembler-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = assembler.write_to(&OutputSink::new(&dir)).unwrap();
+        assert_eq!(path, dir.join("real.bin"));
+        assert_eq!(std::fs::read(&path).unwrap(), test_data);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_assembler_multi_part() {
         let test_data = b"Hello, World! This is a test of multi-part encoding.";
@@ -607,4 +846,141 @@ mod tests {
         let result = assembler.assemble();
         assert!(result.is_err());
     }
+
+    fn slice_crcs(data: &[u8], slice_size: usize) -> Vec<u32> {
+        data.chunks(slice_size).map(crc32fast::hash).collect()
+    }
+
+    fn two_part_setup(test_data: &[u8]) -> (NzbFile, Vec<u8>, Vec<u8>) {
+        let total = test_data.len() as u64;
+        let part1 = encode(
+            &test_data[..20],
+            "test.bin",
+            128,
+            Some((1, 2, 1, 20, total)),
+        )
+        .unwrap();
+        let part2 = encode(
+            &test_data[20..],
+            "test.bin",
+            128,
+            Some((2, 2, 21, total, total)),
+        )
+        .unwrap();
+        let file = create_test_file(vec![
+            NzbSegment {
+                bytes: 20,
+                number: 1,
+                message_id: "<part1@example.com>".to_string(),
+            },
+            NzbSegment {
+                bytes: total - 20,
+                number: 2,
+                message_id: "<part2@example.com>".to_string(),
+            },
+        ]);
+        (file, part1, part2)
+    }
+
+    #[test]
+    fn test_assembler_slice_verification_in_place() {
+        let test_data = b"Hello, World! This is a test of multi-part encoding.";
+        let (file, part1, part2) = two_part_setup(test_data);
+        let ifsc = IfscPacket {
+            file_id: [0; 16],
+            checksums: slice_crcs(test_data, 16),
+        };
+
+        let mut assembler =
+            ArticleAssembler::with_slice_checksums(file, &ifsc, 16, test_data.len() as u64);
+
+        // Part 1 (bytes 0..20) fully covers slice 0 only
+        assembler.add_part_bytes(1, &part1).unwrap();
+        assert_eq!(assembler.verified_slices(), 1);
+        assert!(assembler.damaged_slices().is_empty());
+
+        // Part 2 completes slice 1 (spanning both parts) and the rest
+        assembler.add_part_bytes(2, &part2).unwrap();
+        assert_eq!(assembler.verified_slices(), 4);
+        assert!(assembler.damaged_slices().is_empty());
+    }
+
+    #[test]
+    fn test_assembler_slice_verification_detects_damage() {
+        let test_data = b"Hello, World! This is a test of multi-part encoding.";
+        let (file, part1, part2) = two_part_setup(test_data);
+        let mut checksums = slice_crcs(test_data, 16);
+        checksums[2] ^= 1;
+        let ifsc = IfscPacket {
+            file_id: [0; 16],
+            checksums,
+        };
+
+        let mut assembler =
+            ArticleAssembler::with_slice_checksums(file, &ifsc, 16, test_data.len() as u64);
+        assembler.add_part_bytes(2, &part2).unwrap();
+        // Slice 2 lies entirely in part 2 and is already known to be damaged
+        assert_eq!(assembler.damaged_slices(), vec![2]);
+
+        assembler.add_part_bytes(1, &part1).unwrap();
+        assert_eq!(assembler.damaged_slices(), vec![2]);
+        assert_eq!(assembler.verified_slices(), 3);
+    }
+
+    #[test]
+    fn test_assembler_slice_verification_missing_part() {
+        let test_data = b"Hello, World! This is a test of multi-part encoding.";
+        let (file, part1, _) = two_part_setup(test_data);
+        let ifsc = IfscPacket {
+            file_id: [0; 16],
+            checksums: slice_crcs(test_data, 16),
+        };
+
+        let mut assembler =
+            ArticleAssembler::with_slice_checksums(file, &ifsc, 16, test_data.len() as u64);
+        assembler.add_part_bytes(1, &part1).unwrap();
+        assembler.mark_missing(2).unwrap();
+
+        // Slices touching the missing part cannot be verified and count as damaged
+        assert_eq!(assembler.damaged_slices(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_assembler_slice_verification_single_part() {
+        let test_data = b"Single part file contents for slice checks";
+        let encoded = encode(test_data, "test.bin", 128, None).unwrap();
+        let file = create_test_file(vec![NzbSegment {
+            bytes: test_data.len() as u64,
+            number: 1,
+            message_id: "<part1@example.com>".to_string(),
+        }]);
+        let ifsc = IfscPacket {
+            file_id: [0; 16],
+            checksums: slice_crcs(test_data, 10),
+        };
+
+        let mut assembler =
+            ArticleAssembler::with_slice_checksums(file, &ifsc, 10, test_data.len() as u64);
+        assembler.add_part_bytes(1, &encoded).unwrap();
+        assert_eq!(assembler.verified_slices(), 5);
+        assert!(assembler.damaged_slices().is_empty());
+    }
+
+    #[test]
+    fn test_assembler_without_slice_checksums() {
+        let test_data = b"Hello, World! This is a test of multi-part encoding.";
+        let (file, part1, _) = two_part_setup(test_data);
+        let mut assembler = ArticleAssembler::new(file);
+        assembler.add_part_bytes(1, &part1).unwrap();
+        assembler.mark_missing(2).unwrap();
+        assert!(assembler.damaged_slices().is_empty());
+        assert_eq!(assembler.verified_slices(), 0);
+    }
+
+    #[test]
+    fn test_assembler_with_par2_requires_main_packet() {
+        let (file, _, _) = two_part_setup(b"Hello, World! This is a test of multi-part encoding.");
+        let par2 = Par2File::new();
+        assert!(ArticleAssembler::with_par2(file, &par2, &[0; 16]).is_err());
+    }
 }
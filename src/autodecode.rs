@@ -0,0 +1,263 @@
+//! Automatic decoding of fetched bodies
+//!
+//! Binaries on Usenet come yEnc encoded, uuencoded or as base64 MIME
+//! attachments, and text posts come as is. [`decode_body_auto`] looks for the
+//! markers of each format in a BODY or ARTICLE response and runs the matching
+//! decoder, so a downloader does not have to guess.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::error::{NntpError, Result};
+use crate::response::NntpBinaryResponse;
+use crate::uuencode::{self, UuDecoded};
+use crate::yenc::{self, YencDecoded};
+
+/// Content of a body, decoded according to its detected format
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedBody {
+    /// A yEnc block (`=ybegin` ... `=yend`), whole file or one part
+    Yenc(YencDecoded),
+    /// A uuencoded file (`begin <mode> <name>` ... `end`)
+    Uuencode(UuDecoded),
+    /// A base64 MIME section (`Content-Transfer-Encoding: base64`)
+    Base64 {
+        /// File name from Content-Disposition `filename=`, else Content-Type `name=`
+        filename: Option<String>,
+        /// Decoded contents
+        data: Vec<u8>,
+    },
+    /// No encoding detected; the body as received
+    Plain(Vec<u8>),
+}
+
+impl DecodedBody {
+    /// The decoded bytes, whatever the format
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Self::Yenc(decoded) => &decoded.data,
+            Self::Uuencode(decoded) => &decoded.data,
+            Self::Base64 { data, .. } | Self::Plain(data) => data,
+        }
+    }
+
+    /// File name announced by the encoding, if any
+    pub fn filename(&self) -> Option<&str> {
+        match self {
+            Self::Yenc(decoded) => Some(&decoded.header.name),
+            Self::Uuencode(decoded) => Some(&decoded.name),
+            Self::Base64 { filename, .. } => filename.as_deref(),
+            Self::Plain(_) => None,
+        }
+    }
+}
+
+/// Detect the encoding of a fetched body and decode it
+///
+/// Checked in order: a line starting with `=ybegin `, a `begin <mode> <name>`
+/// line, and a `Content-Transfer-Encoding: base64` header (in the article
+/// headers or a MIME part). Text before the encoded block is skipped. A body
+/// without any of these is returned as [`DecodedBody::Plain`].
+///
+/// # Errors
+///
+/// Returns the decoder's error if a format is detected but its data is
+/// corrupt, e.g. a yEnc CRC mismatch or invalid base64.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nntp_rs::{DecodedBody, NntpClient, ServerConfig, decode_body_auto};
+/// # use std::sync::Arc;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+/// let response = client.fetch_body_binary("<part1@example.com>").await?;
+/// match decode_body_auto(&response)? {
+///     DecodedBody::Plain(text) => println!("text post, {} bytes", text.len()),
+///     decoded => println!("{:?}: {} bytes", decoded.filename(), decoded.data().len()),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn decode_body_auto(response: &NntpBinaryResponse) -> Result<DecodedBody> {
+    decode_bytes_auto(&response.data)
+}
+
+/// [`decode_body_auto`] on body bytes from any source
+pub(crate) fn decode_bytes_auto(data: &[u8]) -> Result<DecodedBody> {
+    if let Some(start) = find_line(data, |line| line.starts_with(b"=ybegin ")) {
+        return yenc::decode(&data[start..]).map(DecodedBody::Yenc);
+    }
+
+    let is_begin =
+        |line: &[u8]| std::str::from_utf8(line).is_ok_and(|l| uuencode::parse_begin(l).is_some());
+    if let Some(start) = find_line(data, is_begin) {
+        return uuencode::decode(&data[start..]).map(DecodedBody::Uuencode);
+    }
+
+    if let Some(body) = decode_base64_section(data)? {
+        return Ok(body);
+    }
+
+    Ok(DecodedBody::Plain(data.to_vec()))
+}
+
+/// Offset of the first line matching `pred`
+fn find_line(data: &[u8], pred: impl Fn(&[u8]) -> bool) -> Option<usize> {
+    let mut offset = 0;
+    for line in data.split(|&b| b == b'\n') {
+        if pred(line) {
+            return Some(offset);
+        }
+        offset += line.len() + 1;
+    }
+    None
+}
+
+/// Decode the first header block declaring base64 and the data following it
+///
+/// A header block is a run of lines ended by a blank line, either the
+/// article headers or the headers of a MIME part after a boundary.
+fn decode_base64_section(data: &[u8]) -> Result<Option<DecodedBody>> {
+    let mut lines = data
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+
+    let mut is_base64 = false;
+    let mut filename = None;
+    let mut type_name = None;
+    loop {
+        let Some(line) = lines.next() else {
+            return Ok(None);
+        };
+        let line = String::from_utf8_lossy(line);
+        if line.is_empty() || line.starts_with("--") {
+            if is_base64 && line.is_empty() {
+                break;
+            }
+            is_base64 = false;
+            filename = None;
+            type_name = None;
+            continue;
+        }
+
+        let lower = line.to_ascii_lowercase();
+        if lower.starts_with("content-transfer-encoding:") && lower.contains("base64") {
+            is_base64 = true;
+        } else if lower.starts_with("content-disposition:") {
+            filename = header_param(&line, "filename=");
+        } else if lower.starts_with("content-type:") {
+            type_name = header_param(&line, "name=");
+        }
+    }
+
+    let encoded: Vec<u8> = lines
+        .take_while(|line| !line.starts_with(b"--"))
+        .flat_map(|line| line.iter().copied().filter(|b| !b.is_ascii_whitespace()))
+        .collect();
+    let data = STANDARD
+        .decode(&encoded)
+        .map_err(|e| NntpError::InvalidResponse(format!("Invalid base64 body: {}", e)))?;
+    Ok(Some(DecodedBody::Base64 {
+        filename: filename.or(type_name),
+        data,
+    }))
+}
+
+/// Value of the `param` (e.g. `name=`) parameter on a header line
+fn header_param(line: &str, param: &str) -> Option<String> {
+    let start = line.to_ascii_lowercase().find(param)? + param.len();
+    let value = line[start..].trim_start();
+    let value = match value.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+        None => value.split([';', ' ']).next().unwrap_or_default(),
+    };
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(data: &[u8]) -> NntpBinaryResponse {
+        NntpBinaryResponse {
+            code: 222,
+            message: "0 <a@b> body".to_string(),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_detects_yenc_after_preamble() {
+        let encoded = yenc::encode(b"hello yenc", "hello.bin", 128, None).unwrap();
+        let mut data = b"Posted with a newsreader\r\n\r\n".to_vec();
+        data.extend_from_slice(&encoded);
+
+        let decoded = decode_body_auto(&body(&data)).unwrap();
+        assert!(matches!(decoded, DecodedBody::Yenc(_)));
+        assert_eq!(decoded.data(), b"hello yenc");
+        assert_eq!(decoded.filename(), Some("hello.bin"));
+    }
+
+    #[test]
+    fn test_detects_uuencode() {
+        let decoded =
+            decode_body_auto(&body(b"begin 644 cat.txt\r\n#0V%T\r\n`\r\nend\r\n")).unwrap();
+        assert!(matches!(decoded, DecodedBody::Uuencode(_)));
+        assert_eq!(decoded.data(), b"Cat");
+        assert_eq!(decoded.filename(), Some("cat.txt"));
+    }
+
+    #[test]
+    fn test_detects_base64_mime_part() {
+        let data = b"This is a multi-part message in MIME format.\r\n\
+--frontier\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+See attachment.\r\n\
+--frontier\r\n\
+Content-Type: application/octet-stream; name=\"fallback.bin\"\r\n\
+Content-Disposition: attachment; filename=\"data.bin\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+aGVsbG8g\r\n\
+YmFzZTY0\r\n\
+--frontier--\r\n";
+        let decoded = decode_body_auto(&body(data)).unwrap();
+        assert_eq!(
+            decoded,
+            DecodedBody::Base64 {
+                filename: Some("data.bin".to_string()),
+                data: b"hello base64".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_base64_article_uses_content_type_name() {
+        let data = b"Subject: picture\r\n\
+Content-Type: image/png; name=pic.png\r\n\
+Content-Transfer-Encoding: BASE64\r\n\
+\r\n\
+iVBORw==\r\n";
+        let decoded = decode_body_auto(&body(data)).unwrap();
+        assert_eq!(decoded.filename(), Some("pic.png"));
+        assert_eq!(decoded.data(), b"\x89PNG");
+    }
+
+    #[test]
+    fn test_plain_body() {
+        let data = b"Just some text.\r\nbegin the story here\r\n";
+        assert_eq!(
+            decode_body_auto(&body(data)).unwrap(),
+            DecodedBody::Plain(data.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_corrupt_base64_is_an_error() {
+        let data = b"Content-Transfer-Encoding: base64\r\n\r\n!!!not base64!!!\r\n";
+        assert!(decode_body_auto(&body(data)).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Backfill of historical overview data
+//!
+//! Building an index of a group with hundreds of millions of articles means
+//! fetching overview data in many bounded ranges, spread over several
+//! connections, and surviving restarts halfway through. [`BackfillState`]
+//! records the group watermarks and the article ranges already processed;
+//! [`BackfillCoordinator`] walks the remaining ranges in chunks over a
+//! [`NntpPool`] and hands each chunk to the caller together with the updated
+//! state. With the `serde` feature the state serializes, so it can be saved
+//! after every chunk and passed back in to resume.
+
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::commands::XoverEntry;
+use crate::error::{NntpError, Result};
+use crate::pool::NntpPool;
+use crate::rangeset::RangeSet;
+use crate::response::codes;
+
+/// Default number of articles requested per OVER command
+const DEFAULT_CHUNK_SIZE: u64 = 10_000;
+
+/// Default number of chunks fetched at once
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Direction in which a backfill walks the article range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BackfillOrder {
+    /// Start at the high watermark and walk backwards (recent articles first)
+    #[default]
+    NewestFirst,
+    /// Start at the low watermark and walk forwards
+    OldestFirst,
+}
+
+/// Resumable progress of a backfill over one group
+///
+/// The watermarks are taken from the GROUP response on the first run and then
+/// kept fixed, so articles arriving later are left to incremental updates.
+/// Chunks can finish out of order; `completed` holds exactly the ranges whose
+/// entries were handed to the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackfillState {
+    /// Newsgroup being backfilled
+    pub group: String,
+    /// Walk direction
+    pub order: BackfillOrder,
+    /// Articles requested per OVER command
+    pub chunk_size: u64,
+    /// Lowest article number to cover (`None` until the group is selected)
+    pub low: Option<u64>,
+    /// Highest article number to cover (`None` until the group is selected)
+    pub high: Option<u64>,
+    /// Article ranges already processed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub completed: RangeSet,
+}
+
+impl BackfillState {
+    /// Start a backfill of `group` over its current article range
+    pub fn new(group: impl Into<String>, order: BackfillOrder) -> Self {
+        Self {
+            group: group.into(),
+            order,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            low: None,
+            high: None,
+            completed: RangeSet::new(),
+        }
+    }
+
+    /// Set the number of articles requested per OVER command (at least 1)
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Limit the backfill to `low..=high` instead of the group watermarks
+    pub fn with_bounds(mut self, low: u64, high: u64) -> Self {
+        self.low = Some(low);
+        self.high = Some(high);
+        self
+    }
+
+    /// Article ranges not processed yet, in ascending order
+    ///
+    /// Empty while the watermarks are unknown.
+    pub fn remaining(&self) -> Vec<RangeInclusive<u64>> {
+        match (self.low, self.high) {
+            (Some(low), Some(high)) => self.completed.gaps(low..=high),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Number of article numbers not processed yet
+    pub fn remaining_articles(&self) -> u64 {
+        self.remaining()
+            .iter()
+            .map(|range| range.end() - range.start() + 1)
+            .sum()
+    }
+
+    /// Check whether the watermarks are known and every range is processed
+    pub fn is_complete(&self) -> bool {
+        self.low.is_some() && self.high.is_some() && self.remaining().is_empty()
+    }
+
+    /// Plan the next chunk, skipping ranges in `in_flight`
+    ///
+    /// Returns `None` when nothing is left to request.
+    pub fn next_chunk(&self, in_flight: &RangeSet) -> Option<RangeInclusive<u64>> {
+        let (low, high) = (self.low?, self.high?);
+        let busy = self.completed.union(in_flight);
+        let gaps = busy.gaps(low..=high);
+        let span = self.chunk_size.max(1) - 1;
+        match self.order {
+            BackfillOrder::NewestFirst => {
+                let gap = gaps.last()?;
+                let start = (*gap.start()).max(gap.end().saturating_sub(span));
+                Some(start..=*gap.end())
+            }
+            BackfillOrder::OldestFirst => {
+                let gap = gaps.first()?;
+                let end = (*gap.end()).min(gap.start().saturating_add(span));
+                Some(*gap.start()..=end)
+            }
+        }
+    }
+}
+
+/// Overview entries of one backfilled range
+#[derive(Debug, Clone)]
+pub struct BackfillChunk {
+    /// Article range requested
+    pub range: RangeInclusive<u64>,
+    /// Entries returned by the server (empty if every article expired)
+    pub entries: Vec<XoverEntry>,
+}
+
+/// Runs a [`BackfillState`] to completion over a connection pool
+///
+/// # Example
+///
+/// ```no_run
+/// use nntp_rs::backfill::{BackfillCoordinator, BackfillOrder, BackfillState};
+/// use nntp_rs::{NntpPool, ServerConfig};
+/// use std::sync::Arc;
+///
+/// # async fn example() -> nntp_rs::Result<()> {
+/// let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// let pool = Arc::new(NntpPool::new(config, 8).await?);
+///
+/// let mut state = BackfillState::new("alt.binaries.test", BackfillOrder::NewestFirst)
+///     .with_chunk_size(20_000);
+/// BackfillCoordinator::new(pool)
+///     .with_concurrency(8)
+///     .run(&mut state, |chunk, state| {
+///         println!("{:?}: {} entries", chunk.range, chunk.entries.len());
+///         // Store the entries, then persist `state` to resume after a restart
+///         println!("{} articles left", state.remaining_articles());
+///         Ok(())
+///     })
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BackfillCoordinator {
+    pool: Arc<NntpPool>,
+    concurrency: usize,
+}
+
+impl BackfillCoordinator {
+    /// Create a coordinator fetching through `pool`
+    pub fn new(pool: Arc<NntpPool>) -> Self {
+        Self {
+            pool,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Set the number of chunks fetched at once, each on its own connection
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Fetch every remaining chunk of `state`
+    ///
+    /// The group watermarks are read first if `state` has none. Each chunk is
+    /// marked completed and passed to `on_chunk` together with the updated
+    /// state as soon as it arrives; chunks may arrive out of order when
+    /// several are in flight.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Progress to continue; updated as chunks complete
+    /// * `on_chunk` - Called with each chunk and the state including it
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first error, leaving the failed chunk out of `state`:
+    /// - Errors from the pool, GROUP or OVER (a 423 for an expired range counts as empty)
+    /// - Errors returned by `on_chunk`; the chunk is unmarked again
+    ///
+    /// The chunks still in flight are fetched to the end first, so their
+    /// connections go back to the pool in sync, but are not recorded.
+    pub async fn run<F>(&self, state: &mut BackfillState, mut on_chunk: F) -> Result<()>
+    where
+        F: FnMut(BackfillChunk, &BackfillState) -> Result<()>,
+    {
+        if state.low.is_none() || state.high.is_none() {
+            let mut conn = self.pool.get().await?;
+            let info = conn.select_group(&state.group).await?;
+            state.low = Some(info.first);
+            state.high = Some(info.last);
+        }
+
+        let mut in_flight = RangeSet::new();
+        let mut tasks = JoinSet::new();
+        loop {
+            while tasks.len() < self.concurrency {
+                let Some(range) = state.next_chunk(&in_flight) else {
+                    break;
+                };
+                in_flight.insert_range(range.clone());
+                let (pool, group) = (self.pool.clone(), state.group.clone());
+                tasks.spawn(async move {
+                    let entries = fetch_chunk(&pool, &group, &range).await;
+                    (range, entries)
+                });
+            }
+
+            let Some(joined) = tasks.join_next().await else {
+                return Ok(());
+            };
+            if let Err(e) = take_chunk(joined, state, &mut in_flight, &mut on_chunk) {
+                // Let the other fetches finish: an aborted one would hand its
+                // connection back to the pool with the OVER response unread
+                while tasks.join_next().await.is_some() {}
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// What a chunk fetch task returns
+type ChunkOutcome = (RangeInclusive<u64>, Result<Vec<XoverEntry>>);
+
+/// Record a finished chunk fetch in `state` and pass it to `on_chunk`
+fn take_chunk<F>(
+    joined: std::result::Result<ChunkOutcome, tokio::task::JoinError>,
+    state: &mut BackfillState,
+    in_flight: &mut RangeSet,
+    on_chunk: &mut F,
+) -> Result<()>
+where
+    F: FnMut(BackfillChunk, &BackfillState) -> Result<()>,
+{
+    let (range, entries) =
+        joined.map_err(|e| NntpError::Other(format!("Backfill task failed: {}", e)))?;
+    in_flight.remove_range(range.clone());
+    let entries = entries?;
+
+    debug!(
+        "Backfilled {} {:?}: {} entries",
+        state.group,
+        range,
+        entries.len()
+    );
+    state.completed.insert_range(range.clone());
+    let chunk = BackfillChunk {
+        range: range.clone(),
+        entries,
+    };
+    if let Err(e) = on_chunk(chunk, state) {
+        state.completed.remove_range(range);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Fetch the overview of `range` on a pooled connection
+async fn fetch_chunk(
+    pool: &NntpPool,
+    group: &str,
+    range: &RangeInclusive<u64>,
+) -> Result<Vec<XoverEntry>> {
+    let mut conn = pool.get().await?;
+    if conn.current_group() != Some(group) {
+        conn.select_group(group).await?;
+    }
+    match conn
+        .over(&format!("{}-{}", range.start(), range.end()))
+        .await
+    {
+        Err(NntpError::Protocol {
+            code: codes::NO_SUCH_ARTICLE_NUMBER,
+            ..
+        }) => Ok(Vec::new()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_chunk_newest_first() {
+        let mut state = BackfillState::new("alt.test", BackfillOrder::NewestFirst)
+            .with_chunk_size(10)
+            .with_bounds(1, 25);
+        let mut in_flight = RangeSet::new();
+        assert_eq!(state.next_chunk(&in_flight), Some(16..=25));
+        in_flight.insert_range(16..=25);
+        assert_eq!(state.next_chunk(&in_flight), Some(6..=15));
+        in_flight.insert_range(6..=15);
+        assert_eq!(state.next_chunk(&in_flight), Some(1..=5));
+
+        state.completed.insert_range(1..=25);
+        assert!(state.is_complete());
+        assert_eq!(state.next_chunk(&RangeSet::new()), None);
+    }
+
+    #[test]
+    fn test_next_chunk_oldest_first_skips_completed() {
+        let mut state = BackfillState::new("alt.test", BackfillOrder::OldestFirst)
+            .with_chunk_size(10)
+            .with_bounds(100, 130);
+        state.completed.insert_range(100..=104);
+        assert_eq!(state.next_chunk(&RangeSet::new()), Some(105..=114));
+        assert_eq!(state.remaining_articles(), 26);
+        assert!(!state.is_complete());
+    }
+
+    #[test]
+    fn test_unknown_bounds_plan_nothing() {
+        let state = BackfillState::new("alt.test", BackfillOrder::NewestFirst);
+        assert_eq!(state.next_chunk(&RangeSet::new()), None);
+        assert!(state.remaining().is_empty());
+        assert!(!state.is_complete());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_serde_roundtrip() {
+        let mut state =
+            BackfillState::new("alt.test", BackfillOrder::OldestFirst).with_bounds(1, 500);
+        state.completed.insert_range(1..=200);
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("\"1-200\""));
+        assert_eq!(serde_json::from_str::<BackfillState>(&json).unwrap(), state);
+    }
+}
@@ -0,0 +1,870 @@
+//! Blocking client for synchronous code
+//!
+//! [`NntpClient`] wraps the async [`crate::NntpClient`] together with a
+//! private single-threaded Tokio runtime and runs every call to completion,
+//! so CLI tools and non-async programs can talk NNTP without setting up an
+//! executor. Methods mirror the async client and take the same arguments.
+//!
+//! The blocking client must not be used from within an async context: running
+//! a runtime inside another panics. Async code should use
+//! [`crate::NntpClient`] directly.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use nntp_rs::ServerConfig;
+//! use nntp_rs::blocking::NntpClient;
+//! use std::sync::Arc;
+//!
+//! # fn example() -> nntp_rs::Result<()> {
+//! let config = ServerConfig::tls("news.example.com", "user", "pass");
+//! let mut client = NntpClient::connect(Arc::new(config))?;
+//! client.authenticate()?;
+//!
+//! let info = client.select_group("alt.test")?;
+//! for entry in client.over(&format!("{}-{}", info.first, info.last))? {
+//!     println!("{}: {}", entry.article_number, entry.subject);
+//! }
+//! client.quit()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::runtime::Runtime;
+
+use crate::article::Article;
+use crate::cancel::CancellationToken;
+use crate::capabilities::Capabilities;
+use crate::client::{BodyPrefix, Pipeline};
+use crate::commands::{self, XoverEntry};
+use crate::compression::GzipDetection;
+use crate::config::ServerConfig;
+use crate::error::{NntpError, Result};
+use crate::latency::LatencyStats;
+use crate::observer::ConnectionObserver;
+use crate::quirks::ServerQuirks;
+use crate::ratelimit::BandwidthLimiter;
+use crate::renumber::GroupWatermarks;
+use crate::response::{NntpBinaryResponse, NntpResponse, ParseMode};
+use crate::sasl::SaslMechanism;
+use crate::warnings::ParseWarning;
+
+/// Blocking NNTP client
+///
+/// Each connection owns its runtime, so clients can be moved to and used
+/// from separate threads for parallel downloads.
+#[must_use]
+pub struct NntpClient {
+    // Declared first so the connection is dropped before its runtime
+    inner: crate::NntpClient,
+    runtime: Runtime,
+}
+
+impl std::fmt::Debug for NntpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NntpClient")
+            .field("current_group", &self.inner.current_group())
+            .field("authenticated", &self.inner.is_authenticated())
+            .finish_non_exhaustive()
+    }
+}
+
+impl NntpClient {
+    /// Connect to an NNTP server
+    ///
+    /// See [`crate::NntpClient::connect`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::Io`] if the runtime cannot be created, and
+    /// otherwise the same errors as the async version.
+    pub fn connect(config: Arc<ServerConfig>) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(NntpError::Io)?;
+        let inner = runtime.block_on(crate::NntpClient::connect(config))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The wrapped async client, for accessors not mirrored here
+    pub fn get_ref(&self) -> &crate::NntpClient {
+        &self.inner
+    }
+
+    /// Check if this connection is broken and should be discarded
+    pub fn is_broken(&self) -> bool {
+        self.inner.is_broken()
+    }
+
+    /// Check if the client is currently authenticated
+    pub fn is_authenticated(&self) -> bool {
+        self.inner.is_authenticated()
+    }
+
+    /// Check if the connection uses TLS
+    pub fn is_tls(&self) -> bool {
+        self.inner.is_tls()
+    }
+
+    /// Get the currently selected newsgroup, if any
+    pub fn current_group(&self) -> Option<&str> {
+        self.inner.current_group()
+    }
+
+    /// Get the server workarounds in effect for this connection
+    pub fn quirks(&self) -> &ServerQuirks {
+        self.inner.quirks()
+    }
+
+    /// Get the configuration of the server this connection is to
+    pub fn config(&self) -> &ServerConfig {
+        self.inner.config()
+    }
+
+    /// Override the server workarounds for this connection
+    pub fn set_quirks(&mut self, quirks: ServerQuirks) {
+        self.inner.set_quirks(quirks);
+    }
+
+    /// Get how malformed server output is handled on this connection
+    pub fn parse_mode(&self) -> ParseMode {
+        self.inner.parse_mode()
+    }
+
+    /// Override how malformed server output is handled on this connection
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.inner.set_parse_mode(mode);
+    }
+
+    /// Set or clear the observer for this connection
+    pub fn set_observer(&mut self, observer: Option<Arc<dyn ConnectionObserver>>) {
+        self.inner.set_observer(observer);
+    }
+
+    /// Get the bytes received on the socket
+    pub fn bytes_received(&self) -> u64 {
+        self.inner.bytes_received()
+    }
+
+    /// Get the bytes sent on the socket
+    pub fn bytes_sent(&self) -> u64 {
+        self.inner.bytes_sent()
+    }
+
+    /// Get the watermarks recorded from GROUP responses on this connection
+    pub fn group_watermarks(&self) -> &GroupWatermarks {
+        self.inner.group_watermarks()
+    }
+
+    /// Replace the recorded watermarks, e.g. with ones saved earlier
+    pub fn set_group_watermarks(&mut self, watermarks: GroupWatermarks) {
+        self.inner.set_group_watermarks(watermarks);
+    }
+
+    /// Get the recent status line latencies of this connection
+    pub fn response_latency(&self) -> &LatencyStats {
+        self.inner.response_latency()
+    }
+
+    /// Get the timeout applied while waiting for the next status line
+    pub fn response_timeout(&self) -> Duration {
+        self.inner.response_timeout()
+    }
+
+    /// Get the parse warnings collected on this connection
+    pub fn parse_warnings(&self) -> &[ParseWarning] {
+        self.inner.parse_warnings()
+    }
+
+    /// Take the collected parse warnings, leaving none
+    pub fn take_parse_warnings(&mut self) -> Vec<ParseWarning> {
+        self.inner.take_parse_warnings()
+    }
+
+    /// Check if compression is enabled
+    pub fn is_compression_enabled(&self) -> bool {
+        self.inner.is_compression_enabled()
+    }
+
+    /// Get `(compressed, decompressed)` byte totals
+    pub fn get_bandwidth_stats(&self) -> (u64, u64) {
+        self.inner.get_bandwidth_stats()
+    }
+
+    /// Get how headers-only compressed responses are recognized
+    pub fn gzip_detection(&self) -> &GzipDetection {
+        self.inner.gzip_detection()
+    }
+
+    /// Override how headers-only compressed responses are recognized
+    pub fn set_gzip_detection(&mut self, detection: GzipDetection) {
+        self.inner.set_gzip_detection(detection);
+    }
+
+    /// Time since the last command was sent
+    pub fn idle_time(&self) -> Duration {
+        self.inner.idle_time()
+    }
+
+    /// Upgrade a plain connection to TLS, see [`crate::NntpClient::starttls`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn starttls(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.starttls())
+    }
+
+    /// Authenticate with AUTHINFO USER/PASS, see [`crate::NntpClient::authenticate`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn authenticate(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.authenticate())
+    }
+
+    /// Authenticate with SASL, see [`crate::NntpClient::authenticate_sasl`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn authenticate_sasl(
+        &mut self,
+        mechanism: impl SaslMechanism + Clone + 'static,
+    ) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.authenticate_sasl(mechanism))
+    }
+
+    /// Enable compression, see [`crate::NntpClient::try_enable_compression`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn try_enable_compression(&mut self) -> Result<bool> {
+        self.runtime.block_on(self.inner.try_enable_compression())
+    }
+
+    /// Re-establish the connection, see [`crate::NntpClient::reconnect`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.reconnect())
+    }
+
+    /// Request server capabilities, see [`crate::NntpClient::capabilities`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn capabilities(&mut self) -> Result<Capabilities> {
+        self.runtime.block_on(self.inner.capabilities())
+    }
+
+    /// Server capabilities, requested only when not cached, see
+    /// [`crate::NntpClient::capability_set`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn capability_set(&mut self) -> Result<&Capabilities> {
+        self.runtime.block_on(self.inner.capability_set())
+    }
+
+    /// Cached server capabilities, see [`crate::NntpClient::cached_capabilities`]
+    pub fn cached_capabilities(&self) -> Option<&Capabilities> {
+        self.inner.cached_capabilities()
+    }
+
+    /// Switch to reader mode, see [`crate::NntpClient::mode_reader`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn mode_reader(&mut self) -> Result<bool> {
+        self.runtime.block_on(self.inner.mode_reader())
+    }
+
+    /// Switch to streaming mode, see [`crate::NntpClient::mode_stream`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn mode_stream(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.mode_stream())
+    }
+
+    /// Get server date/time, see [`crate::NntpClient::date`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn date(&mut self) -> Result<DateTime<Utc>> {
+        self.runtime.block_on(self.inner.date())
+    }
+
+    /// Difference between the server and local clocks, see
+    /// [`crate::NntpClient::clock_skew`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn clock_skew(&mut self) -> Result<TimeDelta> {
+        self.runtime.block_on(self.inner.clock_skew())
+    }
+
+    /// Send DATE if the connection is idle, see [`crate::NntpClient::keepalive`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn keepalive(&mut self) -> Result<bool> {
+        self.runtime.block_on(self.inner.keepalive())
+    }
+
+    /// Request help text, see [`crate::NntpClient::help`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn help(&mut self) -> Result<NntpResponse> {
+        self.runtime.block_on(self.inner.help())
+    }
+
+    /// Close the session, see [`crate::NntpClient::quit`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn quit(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.quit())
+    }
+
+    /// Select a newsgroup, see [`crate::NntpClient::select_group`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn select_group(&mut self, newsgroup: &str) -> Result<commands::GroupInfo> {
+        self.runtime.block_on(self.inner.select_group(newsgroup))
+    }
+
+    /// List article numbers in a group, see [`crate::NntpClient::listgroup`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn listgroup(&mut self, newsgroup: &str, range: Option<&str>) -> Result<Vec<u64>> {
+        self.runtime
+            .block_on(self.inner.listgroup(newsgroup, range))
+    }
+
+    /// Fetch watermarks of many groups, see [`crate::NntpClient::group_snapshot`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn group_snapshot(&mut self, newsgroups: &[&str]) -> Result<Vec<commands::GroupSnapshot>> {
+        self.runtime.block_on(self.inner.group_snapshot(newsgroups))
+    }
+
+    /// Fetch an article, see [`crate::NntpClient::fetch_article`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_article(&mut self, id: &str) -> Result<NntpResponse> {
+        self.runtime.block_on(self.inner.fetch_article(id))
+    }
+
+    /// Fetch article headers, see [`crate::NntpClient::fetch_head`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_head(&mut self, id: &str) -> Result<NntpResponse> {
+        self.runtime.block_on(self.inner.fetch_head(id))
+    }
+
+    /// Fetch an article body, see [`crate::NntpClient::fetch_body`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_body(&mut self, id: &str) -> Result<NntpResponse> {
+        self.runtime.block_on(self.inner.fetch_body(id))
+    }
+
+    /// Fetch an article of the selected group by number, see [`crate::NntpClient::fetch_article_number`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_article_number(&mut self, number: u64) -> Result<NntpResponse> {
+        self.runtime
+            .block_on(self.inner.fetch_article_number(number))
+    }
+
+    /// Fetch an article body of the selected group by number, see [`crate::NntpClient::fetch_body_number`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_body_number(&mut self, number: u64) -> Result<NntpResponse> {
+        self.runtime.block_on(self.inner.fetch_body_number(number))
+    }
+
+    /// Fetch the start of a body, see [`crate::NntpClient::fetch_body_prefix`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_body_prefix(&mut self, id: &str, max_bytes: usize) -> Result<BodyPrefix> {
+        self.runtime
+            .block_on(self.inner.fetch_body_prefix(id, max_bytes))
+    }
+
+    /// Fetch an article as bytes, see [`crate::NntpClient::fetch_article_binary`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_article_binary(&mut self, id: &str) -> Result<NntpBinaryResponse> {
+        self.runtime.block_on(self.inner.fetch_article_binary(id))
+    }
+
+    /// Cancellable [`fetch_article_binary`](Self::fetch_article_binary)
+    ///
+    /// `token` can be cancelled from another thread.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`crate::NntpClient::fetch_article_binary_cancellable`].
+    pub fn fetch_article_binary_cancellable(
+        &mut self,
+        id: &str,
+        token: &CancellationToken,
+    ) -> Result<NntpBinaryResponse> {
+        self.runtime
+            .block_on(self.inner.fetch_article_binary_cancellable(id, token))
+    }
+
+    /// Fetch a body as bytes, see [`crate::NntpClient::fetch_body_binary`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_body_binary(&mut self, id: &str) -> Result<NntpBinaryResponse> {
+        self.runtime.block_on(self.inner.fetch_body_binary(id))
+    }
+
+    /// Fetch articles pipelined, see [`crate::NntpClient::fetch_articles_pipelined`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_articles_pipelined(
+        &mut self,
+        ids: &[&str],
+        max_pipeline: usize,
+    ) -> Result<Vec<NntpBinaryResponse>> {
+        self.runtime
+            .block_on(self.inner.fetch_articles_pipelined(ids, max_pipeline))
+    }
+
+    /// Cancellable [`fetch_articles_pipelined`](Self::fetch_articles_pipelined)
+    ///
+    /// `token` can be cancelled from another thread.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`crate::NntpClient::fetch_articles_pipelined_cancellable`].
+    pub fn fetch_articles_pipelined_cancellable(
+        &mut self,
+        ids: &[&str],
+        max_pipeline: usize,
+        token: &CancellationToken,
+    ) -> Result<Vec<NntpBinaryResponse>> {
+        self.runtime
+            .block_on(
+                self.inner
+                    .fetch_articles_pipelined_cancellable(ids, max_pipeline, token),
+            )
+    }
+
+    /// Fetch articles with a size-bounded window, see
+    /// [`crate::NntpClient::fetch_articles_pipelined_sized`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_articles_pipelined_sized(
+        &mut self,
+        articles: &[(&str, u64)],
+        max_pipeline: usize,
+        max_inflight_bytes: u64,
+    ) -> Result<Vec<Result<NntpBinaryResponse>>> {
+        self.runtime
+            .block_on(self.inner.fetch_articles_pipelined_sized(
+                articles,
+                max_pipeline,
+                max_inflight_bytes,
+            ))
+    }
+
+    /// Check article status, see [`crate::NntpClient::stat`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn stat(&mut self, id: &str) -> Result<commands::ArticleInfo> {
+        self.runtime.block_on(self.inner.stat(id))
+    }
+
+    /// Check many articles, see [`crate::NntpClient::stat_many`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn stat_many(
+        &mut self,
+        ids: &[&str],
+        pipeline_depth: usize,
+    ) -> Result<Vec<Result<commands::ArticleInfo>>> {
+        self.runtime
+            .block_on(self.inner.stat_many(ids, pipeline_depth))
+    }
+
+    /// Move to the next article, see [`crate::NntpClient::next`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    // Named after the NNTP command, like the async method
+    #[expect(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<commands::ArticleInfo> {
+        self.runtime.block_on(self.inner.next())
+    }
+
+    /// Move to the previous article, see [`crate::NntpClient::last`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn last(&mut self) -> Result<commands::ArticleInfo> {
+        self.runtime.block_on(self.inner.last())
+    }
+
+    /// Fetch overview data (XOVER), see [`crate::NntpClient::fetch_xover`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_xover(&mut self, range: &str) -> Result<Vec<XoverEntry>> {
+        self.runtime.block_on(self.inner.fetch_xover(range))
+    }
+
+    /// Fetch overview data (OVER), see [`crate::NntpClient::over`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn over(&mut self, range_or_msgid: &str) -> Result<Vec<XoverEntry>> {
+        self.runtime.block_on(self.inner.over(range_or_msgid))
+    }
+
+    /// Fetch overview of one article, see [`crate::NntpClient::over_by_msgid`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn over_by_msgid(&mut self, message_id: &str) -> Result<Option<XoverEntry>> {
+        self.runtime.block_on(self.inner.over_by_msgid(message_id))
+    }
+
+    /// Fetch one header field, see [`crate::NntpClient::hdr`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn hdr(&mut self, field: &str, range_or_msgid: &str) -> Result<Vec<commands::HdrEntry>> {
+        self.runtime.block_on(self.inner.hdr(field, range_or_msgid))
+    }
+
+    /// Fetch one header of many articles, see [`crate::NntpClient::hdr_many`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn hdr_many(
+        &mut self,
+        field: &str,
+        message_ids: &[&str],
+    ) -> Result<HashMap<String, Result<String>>> {
+        self.runtime
+            .block_on(self.inner.hdr_many(field, message_ids))
+    }
+
+    /// Fetch selected header fields, see [`crate::NntpClient::fetch_headers_fields`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn fetch_headers_fields(
+        &mut self,
+        id: &str,
+        fields: &[&str],
+    ) -> Result<Vec<(String, String)>> {
+        self.runtime
+            .block_on(self.inner.fetch_headers_fields(id, fields))
+    }
+
+    /// List active groups, see [`crate::NntpClient::list_active`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_active(&mut self, wildmat: &str) -> Result<Vec<commands::ActiveGroup>> {
+        self.runtime.block_on(self.inner.list_active(wildmat))
+    }
+
+    /// List group descriptions, see [`crate::NntpClient::list_newsgroups`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_newsgroups(&mut self, wildmat: &str) -> Result<Vec<commands::NewsgroupInfo>> {
+        self.runtime.block_on(self.inner.list_newsgroups(wildmat))
+    }
+
+    /// List overview fields, see [`crate::NntpClient::list_overview_fmt`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_overview_fmt(&mut self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.list_overview_fmt())
+    }
+
+    /// List fields usable with HDR, see [`crate::NntpClient::list_headers`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_headers(&mut self, keyword: Option<&str>) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.list_headers(keyword))
+    }
+
+    /// List group creation times, see [`crate::NntpClient::list_active_times`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_active_times(&mut self, wildmat: &str) -> Result<Vec<commands::GroupTime>> {
+        self.runtime.block_on(self.inner.list_active_times(wildmat))
+    }
+
+    /// List groups with counts, see [`crate::NntpClient::list_counts`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_counts(&mut self, wildmat: &str) -> Result<Vec<commands::CountsGroup>> {
+        self.runtime.block_on(self.inner.list_counts(wildmat))
+    }
+
+    /// List default distribution patterns, see [`crate::NntpClient::list_distrib_pats`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_distrib_pats(&mut self) -> Result<Vec<commands::DistribPat>> {
+        self.runtime.block_on(self.inner.list_distrib_pats())
+    }
+
+    /// List distributions, see [`crate::NntpClient::list_distributions`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_distributions(&mut self) -> Result<Vec<commands::DistributionInfo>> {
+        self.runtime.block_on(self.inner.list_distributions())
+    }
+
+    /// List moderators, see [`crate::NntpClient::list_moderators`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_moderators(&mut self) -> Result<Vec<commands::ModeratorInfo>> {
+        self.runtime.block_on(self.inner.list_moderators())
+    }
+
+    /// Fetch the message of the day, see [`crate::NntpClient::list_motd`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_motd(&mut self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.list_motd())
+    }
+
+    /// Fetch default subscriptions, see [`crate::NntpClient::list_subscriptions`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn list_subscriptions(&mut self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.list_subscriptions())
+    }
+
+    /// List groups created since a date, see [`crate::NntpClient::newgroups`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn newgroups(
+        &mut self,
+        date: &str,
+        time: &str,
+        gmt: bool,
+    ) -> Result<Vec<commands::ActiveGroup>> {
+        self.runtime.block_on(self.inner.newgroups(date, time, gmt))
+    }
+
+    /// List articles posted since a date, see [`crate::NntpClient::newnews`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn newnews(
+        &mut self,
+        wildmat: &str,
+        date: &str,
+        time: &str,
+        gmt: bool,
+    ) -> Result<Vec<String>> {
+        self.runtime
+            .block_on(self.inner.newnews(wildmat, date, time, gmt))
+    }
+
+    /// Post an article, see [`crate::NntpClient::post`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn post(&mut self, article: &Article) -> Result<()> {
+        self.runtime.block_on(self.inner.post(article))
+    }
+
+    /// Post with a throttled upload, see [`crate::NntpClient::post_throttled`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn post_throttled(&mut self, article: &Article, limiter: &BandwidthLimiter) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.post_throttled(article, limiter))
+    }
+
+    /// Post a pre-serialized article, see [`crate::NntpClient::post_raw`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn post_raw(&mut self, article: &[u8]) -> Result<()> {
+        self.runtime.block_on(self.inner.post_raw(article))
+    }
+
+    /// Offer an article with IHAVE, see [`crate::NntpClient::ihave`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn ihave(&mut self, message_id: &str, article: &Article) -> Result<()> {
+        self.runtime.block_on(self.inner.ihave(message_id, article))
+    }
+
+    /// Offer a pre-serialized article with IHAVE, see [`crate::NntpClient::ihave_raw`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn ihave_raw(&mut self, message_id: &str, article: &[u8]) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.ihave_raw(message_id, article))
+    }
+
+    /// Ask whether the server wants an article, see [`crate::NntpClient::check`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn check(&mut self, message_id: &str) -> Result<NntpResponse> {
+        self.runtime.block_on(self.inner.check(message_id))
+    }
+
+    /// Offer many articles with pipelined CHECK, see [`crate::NntpClient::check_many`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn check_many(&mut self, message_ids: &[&str]) -> Result<Vec<commands::CheckResult>> {
+        self.runtime.block_on(self.inner.check_many(message_ids))
+    }
+
+    /// Send queued commands pipelined, see [`crate::NntpClient::run_pipeline`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn run_pipeline(&mut self, pipeline: &Pipeline) -> Result<Vec<NntpResponse>> {
+        self.runtime.block_on(self.inner.run_pipeline(pipeline))
+    }
+
+    /// Send an article in streaming mode, see [`crate::NntpClient::takethis`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn takethis(&mut self, message_id: &str, article: &Article) -> Result<NntpResponse> {
+        self.runtime
+            .block_on(self.inner.takethis(message_id, article))
+    }
+
+    /// Send a throttled article in streaming mode, see
+    /// [`crate::NntpClient::takethis_throttled`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn takethis_throttled(
+        &mut self,
+        message_id: &str,
+        article: &Article,
+        limiter: &BandwidthLimiter,
+    ) -> Result<NntpResponse> {
+        self.runtime
+            .block_on(self.inner.takethis_throttled(message_id, article, limiter))
+    }
+
+    /// Send a pre-serialized article in streaming mode, see
+    /// [`crate::NntpClient::takethis_raw`]
+    ///
+    /// # Errors
+    ///
+    /// Same as the async version.
+    pub fn takethis_raw(&mut self, message_id: &str, article: &[u8]) -> Result<NntpResponse> {
+        self.runtime
+            .block_on(self.inner.takethis_raw(message_id, article))
+    }
+}
@@ -1,7 +1,9 @@
-//! Header caching for NNTP client
+//! Header and article caching for NNTP client
 //!
 //! This module provides in-memory caching for article metadata to reduce redundant network requests.
-//! The cache stores XOVER entries (article metadata) indexed by article number within a newsgroup context.
+//! The header cache stores XOVER entries (article metadata) indexed by article number within a newsgroup context.
+//! The [`ArticleCache`] stores article bodies keyed by server and message-id, along with their
+//! verification status, so damaged copies can be refetched from other servers.
 //!
 //! # Cache Strategy
 //!
@@ -19,16 +21,11 @@
 //! let mut cache = LruHeaderCache::new(1000); // Cache up to 1000 entries
 //!
 //! // Store article metadata
-//! let entry = XoverEntry {
-//!     article_number: 12345,
-//!     subject: "Test Article".to_string(),
-//!     author: "user@example.com".to_string(),
-//!     date: "2024-01-01".to_string(),
-//!     message_id: "<test@example.com>".to_string(),
-//!     references: "".to_string(),
-//!     bytes: 1024,
-//!     lines: 50,
-//! };
+//! let entry = XoverEntry::new(12345, "<test@example.com>")
+//!     .with_subject("Test Article")
+//!     .with_author("user@example.com")
+//!     .with_date("2024-01-01")
+//!     .with_size(1024, 50);
 //! cache.put(12345, entry.clone());
 //!
 //! // Retrieve from cache
@@ -41,7 +38,9 @@
 //! ```
 
 use crate::XoverEntry;
+use bytes::Bytes;
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 
 /// Trait for header caching implementations
 pub trait HeaderCache {
@@ -60,6 +59,18 @@ pub trait HeaderCache {
     /// Clear all cached entries
     fn clear(&mut self);
 
+    /// Remove every entry numbered within `range`
+    ///
+    /// Returns the number of entries removed. The default clears the whole
+    /// cache, since a generic cache cannot list its keys; implementations
+    /// should override it.
+    fn remove_range(&mut self, range: RangeInclusive<u64>) -> usize {
+        let _ = range;
+        let removed = self.len();
+        self.clear();
+        removed
+    }
+
     /// Get the number of cached entries
     fn len(&self) -> usize;
 
@@ -86,27 +97,17 @@ pub trait HeaderCache {
 ///
 /// let mut cache = LruHeaderCache::new(2); // Max 2 entries
 ///
-/// let entry1 = XoverEntry {
-///     article_number: 1,
-///     subject: "First".to_string(),
-///     author: "author1@example.com".to_string(),
-///     date: "2024-01-01".to_string(),
-///     message_id: "<1@example.com>".to_string(),
-///     references: "".to_string(),
-///     bytes: 100,
-///     lines: 10,
-/// };
+/// let entry1 = XoverEntry::new(1, "<1@example.com>")
+///     .with_subject("First")
+///     .with_author("author1@example.com")
+///     .with_date("2024-01-01")
+///     .with_size(100, 10);
 ///
-/// let entry2 = XoverEntry {
-///     article_number: 2,
-///     subject: "Second".to_string(),
-///     author: "author2@example.com".to_string(),
-///     date: "2024-01-02".to_string(),
-///     message_id: "<2@example.com>".to_string(),
-///     references: "".to_string(),
-///     bytes: 200,
-///     lines: 20,
-///     };
+/// let entry2 = XoverEntry::new(2, "<2@example.com>")
+///     .with_subject("Second")
+///     .with_author("author2@example.com")
+///     .with_date("2024-01-02")
+///     .with_size(200, 20);
 ///
 /// cache.put(1, entry1);
 /// cache.put(2, entry2);
@@ -116,16 +117,11 @@ pub trait HeaderCache {
 /// cache.get(&1);
 ///
 /// // Adding a third entry will evict entry 2 (least recently used)
-/// let entry3 = XoverEntry {
-///     article_number: 3,
-///     subject: "Third".to_string(),
-///     author: "author3@example.com".to_string(),
-///     date: "2024-01-03".to_string(),
-///     message_id: "<3@example.com>".to_string(),
-///     references: "".to_string(),
-///     bytes: 300,
-///     lines: 30,
-/// };
+/// let entry3 = XoverEntry::new(3, "<3@example.com>")
+///     .with_subject("Third")
+///     .with_author("author3@example.com")
+///     .with_date("2024-01-03")
+///     .with_size(300, 30);
 /// cache.put(3, entry3);
 ///
 /// assert_eq!(cache.len(), 2);
@@ -241,6 +237,14 @@ impl HeaderCache for LruHeaderCache {
         self.access_counter = 0;
     }
 
+    fn remove_range(&mut self, range: RangeInclusive<u64>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|number, _| !range.contains(number));
+        self.access_order
+            .retain(|number, _| !range.contains(number));
+        before - self.entries.len()
+    }
+
     fn len(&self) -> usize {
         self.entries.len()
     }
@@ -250,6 +254,328 @@ impl HeaderCache for LruHeaderCache {
     }
 }
 
+/// Verification state of a cached article body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerificationStatus {
+    /// The body carries no checksum (or is not yEnc), so it could not be checked
+    Unverified,
+    /// The yEnc CRC32 matched the decoded data
+    Verified,
+    /// The body is damaged: the yEnc CRC32 did not match or decoding failed
+    CrcFailed,
+}
+
+impl VerificationStatus {
+    /// Verify a raw article body by decoding it as yEnc and checking its CRC32
+    ///
+    /// Bodies without a `=ybegin` line are [`Unverified`](Self::Unverified);
+    /// yEnc bodies that fail to decode are [`CrcFailed`](Self::CrcFailed).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::cache::VerificationStatus;
+    ///
+    /// let body = nntp_rs::yenc_encode(b"hello", "a.bin", 128, None).unwrap();
+    /// assert_eq!(VerificationStatus::of_yenc(&body), VerificationStatus::Verified);
+    /// assert_eq!(VerificationStatus::of_yenc(b"plain text\r\n"), VerificationStatus::Unverified);
+    /// ```
+    pub fn of_yenc(body: &[u8]) -> Self {
+        let starts_yenc = body
+            .split(|&b| b == b'\n')
+            .find(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .is_some_and(|line| line.starts_with(b"=ybegin "));
+        if !starts_yenc {
+            return Self::Unverified;
+        }
+
+        match crate::yenc::decode(body).map(|decoded| decoded.verify_crc32()) {
+            Ok(Some(true)) => Self::Verified,
+            Ok(None) => Self::Unverified,
+            Ok(Some(false)) | Err(_) => Self::CrcFailed,
+        }
+    }
+
+    /// Check whether a copy with this status is worth replacing from another server
+    pub fn needs_refetch(self) -> bool {
+        self != Self::Verified
+    }
+
+    /// Rank used to pick the best copy (higher is better)
+    fn rank(self) -> u8 {
+        match self {
+            Self::Verified => 2,
+            Self::Unverified => 1,
+            Self::CrcFailed => 0,
+        }
+    }
+}
+
+/// An article body cached together with the server that supplied it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedBody {
+    /// Server that supplied this copy (host:port format)
+    pub server_id: String,
+    /// Raw body bytes as received
+    pub data: Bytes,
+    /// Verification status of this copy
+    pub status: VerificationStatus,
+}
+
+/// Cache key: (server id, message-id)
+type BodyKey = (String, String);
+
+/// LRU cache of article bodies keyed by (server, message-id)
+///
+/// Each entry remembers which server supplied it and whether it verified, so a
+/// fetcher can keep every copy of a message-id it has seen and refetch only
+/// unverified or CRC-failed articles from servers that have not been tried yet.
+/// The cache is bounded by the total size of the cached bodies; bodies larger
+/// than the whole cache are not stored.
+///
+/// See [`ServerGroup::fetch_body_verified`](crate::ServerGroup::fetch_body_verified)
+/// for cross-server healing built on this cache.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::cache::{ArticleCache, VerificationStatus};
+///
+/// let mut cache = ArticleCache::new(64 * 1024 * 1024);
+/// cache.put("news1:563", "<a@b>", b"damaged".to_vec(), VerificationStatus::CrcFailed);
+///
+/// assert!(cache.needs_refetch("<a@b>"));
+/// let servers = vec!["news1:563".to_string(), "news2:563".to_string()];
+/// assert_eq!(cache.refetch_servers("<a@b>", &servers), vec!["news2:563".to_string()]);
+///
+/// cache.put("news2:563", "<a@b>", b"good".to_vec(), VerificationStatus::Verified);
+/// assert!(!cache.needs_refetch("<a@b>"));
+/// assert_eq!(cache.best("<a@b>").unwrap().server_id, "news2:563");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArticleCache {
+    /// Maximum total size of cached bodies in bytes
+    max_bytes: usize,
+    /// Current total size of cached bodies in bytes
+    total_bytes: usize,
+    /// Storage for cached bodies
+    entries: HashMap<BodyKey, CachedBody>,
+    /// Servers holding a copy of each message-id
+    by_message: HashMap<String, Vec<String>>,
+    /// Access order tracking (higher access_count means more recently used)
+    access_order: HashMap<BodyKey, u64>,
+    /// Current access counter
+    access_counter: u64,
+}
+
+impl ArticleCache {
+    /// Create a new article cache holding at most `max_bytes` of body data
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_bytes` is 0
+    pub fn new(max_bytes: usize) -> Self {
+        assert!(max_bytes > 0, "Cache size must be greater than 0");
+        Self {
+            max_bytes,
+            total_bytes: 0,
+            entries: HashMap::new(),
+            by_message: HashMap::new(),
+            access_order: HashMap::new(),
+            access_counter: 0,
+        }
+    }
+
+    /// Store a body fetched from `server_id`, replacing any earlier copy from that server
+    ///
+    /// Least recently used bodies are evicted to make room. A body larger than
+    /// the cache capacity is not stored.
+    pub fn put(
+        &mut self,
+        server_id: &str,
+        message_id: &str,
+        data: impl Into<Bytes>,
+        status: VerificationStatus,
+    ) {
+        let data = data.into();
+        self.remove(server_id, message_id);
+        if data.len() > self.max_bytes {
+            return;
+        }
+        while self.total_bytes + data.len() > self.max_bytes {
+            if !self.evict_lru() {
+                break;
+            }
+        }
+
+        let key = (server_id.to_string(), message_id.to_string());
+        self.total_bytes += data.len();
+        self.by_message
+            .entry(message_id.to_string())
+            .or_default()
+            .push(server_id.to_string());
+        self.entries.insert(
+            key.clone(),
+            CachedBody {
+                server_id: server_id.to_string(),
+                data,
+                status,
+            },
+        );
+        self.touch(key);
+    }
+
+    /// Get the copy of `message_id` supplied by `server_id`
+    pub fn get(&mut self, server_id: &str, message_id: &str) -> Option<&CachedBody> {
+        let key = (server_id.to_string(), message_id.to_string());
+        if !self.entries.contains_key(&key) {
+            return None;
+        }
+        self.touch(key.clone());
+        self.entries.get(&key)
+    }
+
+    /// Get the best cached copy of `message_id` from any server
+    ///
+    /// Verified copies are preferred over unverified ones, and unverified
+    /// copies over CRC-failed ones.
+    pub fn best(&mut self, message_id: &str) -> Option<&CachedBody> {
+        let server_id = self
+            .copies(message_id)
+            .max_by_key(|body| body.status.rank())?
+            .server_id
+            .clone();
+        self.get(&server_id, message_id)
+    }
+
+    /// Update the verification status of a cached copy
+    ///
+    /// Returns `false` if no copy from `server_id` is cached.
+    pub fn set_status(
+        &mut self,
+        server_id: &str,
+        message_id: &str,
+        status: VerificationStatus,
+    ) -> bool {
+        let key = (server_id.to_string(), message_id.to_string());
+        match self.entries.get_mut(&key) {
+            Some(body) => {
+                body.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether `message_id` still lacks a verified copy
+    pub fn needs_refetch(&self, message_id: &str) -> bool {
+        !self
+            .copies(message_id)
+            .any(|body| body.status == VerificationStatus::Verified)
+    }
+
+    /// Select the servers from `servers` that should be asked for `message_id`
+    ///
+    /// Returns an empty list when a verified copy is cached. Otherwise returns,
+    /// in the given order, every server that has not already supplied a copy:
+    /// refetching a damaged article from the same server yields the same bytes.
+    pub fn refetch_servers(&self, message_id: &str, servers: &[String]) -> Vec<String> {
+        if !self.needs_refetch(message_id) {
+            return Vec::new();
+        }
+        let tried = self.by_message.get(message_id);
+        servers
+            .iter()
+            .filter(|server| tried.is_none_or(|tried| !tried.contains(server)))
+            .cloned()
+            .collect()
+    }
+
+    /// Remove the copy of `message_id` supplied by `server_id`
+    pub fn remove(&mut self, server_id: &str, message_id: &str) -> Option<CachedBody> {
+        let key = (server_id.to_string(), message_id.to_string());
+        let body = self.entries.remove(&key)?;
+        self.access_order.remove(&key);
+        self.total_bytes -= body.data.len();
+        if let Some(servers) = self.by_message.get_mut(message_id) {
+            servers.retain(|s| s != server_id);
+            if servers.is_empty() {
+                self.by_message.remove(message_id);
+            }
+        }
+        Some(body)
+    }
+
+    /// Remove every cached copy of `message_id`
+    pub fn remove_all(&mut self, message_id: &str) {
+        let servers = self.by_message.get(message_id).cloned().unwrap_or_default();
+        for server_id in servers {
+            self.remove(&server_id, message_id);
+        }
+    }
+
+    /// Clear all cached bodies
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.by_message.clear();
+        self.access_order.clear();
+        self.total_bytes = 0;
+        self.access_counter = 0;
+    }
+
+    /// Get the number of cached bodies
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if cache is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the total size of cached bodies in bytes
+    pub fn size_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Get the maximum total size of cached bodies in bytes
+    pub fn capacity(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Iterate over every cached copy of `message_id`
+    fn copies<'a>(&'a self, message_id: &str) -> impl Iterator<Item = &'a CachedBody> {
+        self.by_message
+            .get(message_id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |server_id| {
+                self.entries
+                    .get(&(server_id.clone(), message_id.to_string()))
+            })
+    }
+
+    /// Evict the least recently used body, returning whether one was evicted
+    fn evict_lru(&mut self) -> bool {
+        let Some((server_id, message_id)) = self
+            .access_order
+            .iter()
+            .min_by_key(|&(_, &access_count)| access_count)
+            .map(|(key, _)| key.clone())
+        else {
+            return false;
+        };
+        self.remove(&server_id, &message_id).is_some()
+    }
+
+    /// Update access time for an entry
+    fn touch(&mut self, key: BodyKey) {
+        self.access_counter = self.access_counter.wrapping_add(1);
+        self.access_order.insert(key, self.access_counter);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,10 +586,12 @@ mod tests {
             subject: subject.to_string(),
             author: format!("author{}@example.com", article_number),
             date: format!("2024-01-{:02}", article_number % 31 + 1),
+            timestamp: None,
             message_id: format!("<{}@example.com>", article_number),
             references: String::new(),
             bytes: (article_number * 100) as usize,
             lines: (article_number * 10) as usize,
+            source_id: None,
         }
     }
 
@@ -488,4 +816,150 @@ mod tests {
         assert!(cache.contains(&1) || cache.contains(&2)); // At least one is still there
         assert!(!cache.contains(&3));
     }
+
+    fn servers(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_verification_status_of_yenc() {
+        let good = crate::yenc::encode(b"segment data", "a.bin", 128, None).unwrap();
+        assert_eq!(
+            VerificationStatus::of_yenc(&good),
+            VerificationStatus::Verified
+        );
+
+        let mut bad = good.clone();
+        let pos = bad.iter().position(|&b| b == b'\n').unwrap() + 1;
+        bad[pos] = bad[pos].wrapping_add(1);
+        assert_eq!(
+            VerificationStatus::of_yenc(&bad),
+            VerificationStatus::CrcFailed
+        );
+
+        assert_eq!(
+            VerificationStatus::of_yenc(b"just text\r\n"),
+            VerificationStatus::Unverified
+        );
+        assert_eq!(
+            VerificationStatus::of_yenc(b"=ybegin line=128 size=5 name=x\r\n"),
+            VerificationStatus::CrcFailed
+        );
+    }
+
+    #[test]
+    fn test_article_cache_keys_by_server() {
+        let mut cache = ArticleCache::new(1024);
+        cache.put(
+            "s1:119",
+            "<a@b>",
+            b"one".to_vec(),
+            VerificationStatus::CrcFailed,
+        );
+        cache.put(
+            "s2:119",
+            "<a@b>",
+            b"two".to_vec(),
+            VerificationStatus::Unverified,
+        );
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.size_bytes(), 6);
+
+        assert_eq!(cache.get("s1:119", "<a@b>").unwrap().data, &b"one"[..]);
+        assert!(cache.get("s3:119", "<a@b>").is_none());
+        assert_eq!(cache.best("<a@b>").unwrap().server_id, "s2:119");
+
+        // Replacing a copy from the same server does not duplicate it
+        cache.put(
+            "s1:119",
+            "<a@b>",
+            b"fixed".to_vec(),
+            VerificationStatus::Verified,
+        );
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.size_bytes(), 8);
+        assert_eq!(cache.best("<a@b>").unwrap().data, &b"fixed"[..]);
+    }
+
+    #[test]
+    fn test_article_cache_refetch_servers() {
+        let mut cache = ArticleCache::new(1024);
+        let all = servers(&["s1:119", "s2:119", "s3:119"]);
+        assert!(cache.needs_refetch("<a@b>"));
+        assert_eq!(cache.refetch_servers("<a@b>", &all), all);
+
+        cache.put(
+            "s1:119",
+            "<a@b>",
+            b"bad".to_vec(),
+            VerificationStatus::CrcFailed,
+        );
+        cache.put(
+            "s2:119",
+            "<a@b>",
+            b"raw".to_vec(),
+            VerificationStatus::Unverified,
+        );
+        assert_eq!(cache.refetch_servers("<a@b>", &all), servers(&["s3:119"]));
+
+        assert!(cache.set_status("s2:119", "<a@b>", VerificationStatus::Verified));
+        assert!(!cache.needs_refetch("<a@b>"));
+        assert!(cache.refetch_servers("<a@b>", &all).is_empty());
+        assert!(!cache.set_status("s3:119", "<a@b>", VerificationStatus::Verified));
+    }
+
+    #[test]
+    fn test_article_cache_evicts_by_size() {
+        let mut cache = ArticleCache::new(10);
+        cache.put("s1:119", "<1@b>", vec![0; 4], VerificationStatus::Verified);
+        cache.put("s1:119", "<2@b>", vec![0; 4], VerificationStatus::Verified);
+        cache.get("s1:119", "<1@b>");
+
+        cache.put("s2:119", "<3@b>", vec![0; 4], VerificationStatus::Verified);
+        assert!(cache.get("s1:119", "<1@b>").is_some());
+        assert!(cache.get("s1:119", "<2@b>").is_none());
+        assert!(cache.needs_refetch("<2@b>"));
+        assert_eq!(cache.size_bytes(), 8);
+
+        // Larger than the whole cache: not stored, nothing evicted
+        cache.put("s1:119", "<4@b>", vec![0; 11], VerificationStatus::Verified);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_article_cache_remove_and_clear() {
+        let mut cache = ArticleCache::new(1024);
+        cache.put(
+            "s1:119",
+            "<a@b>",
+            b"x".to_vec(),
+            VerificationStatus::Verified,
+        );
+        cache.put(
+            "s2:119",
+            "<a@b>",
+            b"y".to_vec(),
+            VerificationStatus::Verified,
+        );
+        cache.put(
+            "s1:119",
+            "<c@d>",
+            b"z".to_vec(),
+            VerificationStatus::Verified,
+        );
+
+        cache.remove_all("<a@b>");
+        assert_eq!(cache.len(), 1);
+        assert!(cache.best("<a@b>").is_none());
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.size_bytes(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cache size must be greater than 0")]
+    fn test_article_cache_zero_size_panics() {
+        ArticleCache::new(0);
+    }
 }
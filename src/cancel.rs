@@ -0,0 +1,136 @@
+//! Cancellation of long-running operations
+//!
+//! A [`CancellationToken`] is shared between the task running a download and
+//! whoever may want to stop it. Client methods with a `_cancellable` suffix
+//! return [`NntpError::Cancelled`](crate::NntpError::Cancelled) as soon as the
+//! token fires, instead of the caller having to drop the future mid-read.
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::Poll;
+
+use tokio::sync::Notify;
+
+/// Token to abort an operation from another task
+///
+/// Cheap to clone; every clone observes the same cancellation, which cannot
+/// be undone.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nntp_rs::{CancellationToken, NntpClient, ServerConfig};
+/// # use std::sync::Arc;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// let mut client = NntpClient::connect(Arc::new(config)).await?;
+/// let token = CancellationToken::new();
+///
+/// let stop = token.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+///     stop.cancel();
+/// });
+///
+/// match client.fetch_article_binary_cancellable("<big@example.com>", &token).await {
+///     Err(nntp_rs::NntpError::Cancelled) if client.is_broken() => { /* reconnect */ }
+///     other => println!("{:?}", other.map(|r| r.data.len())),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenInner>,
+}
+
+#[derive(Debug, Default)]
+struct TokenInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every operation using this token
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Check whether [`cancel`](Self::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        let mut notified = pin!(self.inner.notify.notified());
+        // Register before checking the flag so a concurrent cancel is not missed
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Run `fut` until it completes or the token is cancelled
+    ///
+    /// Returns `None` if the token fired first; `fut` is then dropped.
+    pub(crate) async fn run<F: Future>(&self, fut: F) -> Option<F::Output> {
+        let mut fut = pin!(fut);
+        let mut cancelled = pin!(self.cancelled());
+        std::future::poll_fn(|cx| {
+            // Poll the operation first so finished work is never thrown away
+            if let Poll::Ready(output) = fut.as_mut().poll(cx) {
+                return Poll::Ready(Some(output));
+            }
+            cancelled.as_mut().poll(cx).map(|()| None)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_wakes_waiters() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+
+        let waiter = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        tokio::task::yield_now().await;
+        token.cancel();
+        waiter.await.unwrap();
+        assert!(token.is_cancelled());
+
+        // Already cancelled: returns at once
+        token.cancelled().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_stops_pending_future() {
+        let token = CancellationToken::new();
+        assert_eq!(token.run(async { 7 }).await, Some(7));
+
+        let stop = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            stop.cancel();
+        });
+        let result = token.run(tokio::time::sleep(Duration::from_secs(60))).await;
+        assert_eq!(result, None);
+    }
+}
@@ -0,0 +1,205 @@
+//! Cancel-Lock and Cancel-Key headers (RFC 8315)
+//!
+//! Most servers ignore a cancel (or a Supersedes) unless it proves that it
+//! comes from the poster of the original article. The original carries a
+//! Cancel-Lock header with the hash of a secret key; the cancel reveals the
+//! key in a Cancel-Key header, and the server checks that it hashes to the
+//! lock.
+//!
+//! [`CancelSecret`] derives the key of every article from one secret kept by
+//! the poster, so nothing has to be stored per article (RFC 8315 Section 4).
+//! [`ArticleBuilder::cancel_secret`](crate::ArticleBuilder::cancel_secret)
+//! adds both headers where they belong.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::ArticleBuilder;
+//! use nntp_rs::cancel_lock::{CancelSecret, verify};
+//!
+//! let secret = CancelSecret::new(b"a long random secret".to_vec(), "alice");
+//!
+//! let original = ArticleBuilder::new()
+//!     .from("alice@example.com")
+//!     .subject("Oops")
+//!     .newsgroups(vec!["misc.test"])
+//!     .cancel_secret(&secret)
+//!     .build()
+//!     .unwrap();
+//!
+//! let cancel = ArticleBuilder::cancel_of(&original)
+//!     .from("alice@example.com")
+//!     .cancel_secret(&secret)
+//!     .build()
+//!     .unwrap();
+//!
+//! let lock = &original.headers.extra["Cancel-Lock"];
+//! let key = &cancel.headers.extra["Cancel-Key"];
+//! assert!(verify(lock, key));
+//! ```
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+/// Hash algorithm label of the locks and keys generated (RFC 8315 Section 3)
+pub const SCHEME_SHA256: &str = "sha256";
+
+/// Secret from which the cancel keys of a poster's articles are derived
+///
+/// The key of an article is the Base64 of HMAC-SHA256 keyed with the secret
+/// over the user name followed by the Message-ID (RFC 8315 Section 4). The
+/// user name lets several posters share one secret, e.g. on a server that
+/// adds the headers for its users; use an empty one otherwise.
+#[derive(Clone)]
+pub struct CancelSecret {
+    secret: Vec<u8>,
+    user: String,
+}
+
+impl std::fmt::Debug for CancelSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelSecret")
+            .field("secret", &"<redacted>")
+            .field("user", &self.user)
+            .finish()
+    }
+}
+
+impl CancelSecret {
+    /// Create a secret for `user`; it should hold at least 32 random bytes
+    pub fn new(secret: impl Into<Vec<u8>>, user: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            user: user.into(),
+        }
+    }
+
+    /// Cancel-Key header value that cancels or supersedes `message_id`
+    pub fn key(&self, message_id: &str) -> String {
+        format!("{}:{}", SCHEME_SHA256, self.raw_key(message_id))
+    }
+
+    /// Cancel-Lock header value for an article with `message_id`
+    pub fn lock(&self, message_id: &str) -> String {
+        format!(
+            "{}:{}",
+            SCHEME_SHA256,
+            lock_of_key(&self.raw_key(message_id))
+        )
+    }
+
+    /// Base64 key of `message_id`, without the scheme label
+    fn raw_key(&self, message_id: &str) -> String {
+        let mut text = self.user.as_bytes().to_vec();
+        text.extend_from_slice(message_id.as_bytes());
+        STANDARD.encode(hmac_sha256(&self.secret, &text))
+    }
+}
+
+/// Check whether any key of a Cancel-Key value opens any lock of a
+/// Cancel-Lock value (RFC 8315 Section 5)
+///
+/// Both values are lists of `scheme:value` elements separated by
+/// whitespace. Only the `sha256` scheme is checked; `sha1` and unknown
+/// schemes never match.
+///
+/// ```
+/// use nntp_rs::cancel_lock::{CancelSecret, verify};
+///
+/// let secret = CancelSecret::new(b"secret".to_vec(), "");
+/// let lock = secret.lock("<a@example.com>");
+/// assert!(verify(&lock, &secret.key("<a@example.com>")));
+/// assert!(!verify(&lock, &secret.key("<b@example.com>")));
+/// ```
+pub fn verify(cancel_lock: &str, cancel_key: &str) -> bool {
+    let locks: Vec<&str> = sha256_values(cancel_lock).collect();
+    sha256_values(cancel_key).any(|key| locks.contains(&lock_of_key(key).as_str()))
+}
+
+/// Values of the `sha256` elements of a Cancel-Lock or Cancel-Key value
+fn sha256_values(header: &str) -> impl Iterator<Item = &str> {
+    header.split_whitespace().filter_map(|element| {
+        let (scheme, value) = element.split_once(':')?;
+        scheme.eq_ignore_ascii_case(SCHEME_SHA256).then_some(value)
+    })
+}
+
+/// Base64 lock that a Base64 key opens (RFC 8315 Section 3)
+fn lock_of_key(key: &str) -> String {
+    STANDARD.encode(Sha256::digest(key.as_bytes()))
+}
+
+/// HMAC-SHA256 of `text` keyed with `key` (RFC 2104)
+#[expect(clippy::expect_used)]
+fn hmac_sha256(key: &[u8], text: &[u8]) -> [u8; 32] {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(text);
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // RFC 4231 test cases 2 and 6 (key longer than a block)
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_lock_is_hash_of_key() {
+        let secret = CancelSecret::new(b"secret".to_vec(), "user");
+        let key = secret.key("<a@example.com>");
+        let lock = secret.lock("<a@example.com>");
+
+        // Computed independently with Python's hmac and hashlib
+        assert_eq!(key, "sha256:oR/mN8dXoo0ta4JRWpbcHdchg4htx6+U3Qha/i1toDQ=");
+        assert_eq!(lock, "sha256:cXhggiMo/qW+lzsCh0JLYqupFUyVcbKhQ2DKH2pu5wk=");
+
+        let raw_key = key.strip_prefix("sha256:").unwrap();
+        assert_eq!(lock, format!("sha256:{}", lock_of_key(raw_key)));
+        assert_ne!(
+            key,
+            CancelSecret::new(b"secret".to_vec(), "other").key("<a@example.com>")
+        );
+    }
+
+    #[test]
+    fn test_verify_lists_and_schemes() {
+        let secret = CancelSecret::new(b"secret".to_vec(), "");
+        let lock = format!("sha1:bNXHc6ohSmeHaRHHW56BIWZJt+4= {}", secret.lock("<a@b>"));
+        let key = format!("SHA256:bogus {}", secret.key("<a@b>"));
+
+        assert!(verify(&lock, &key));
+        assert!(!verify(&lock, "sha256:bogus"));
+        assert!(!verify("", &key));
+        let sha1_only = key.replace("sha256:", "sha1:").replace("SHA256:", "sha1:");
+        assert!(!verify(&lock, &sha1_only));
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let secret = CancelSecret::new(b"hunter2".to_vec(), "alice");
+        let debug = format!("{secret:?}");
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("alice"));
+    }
+}
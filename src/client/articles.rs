@@ -9,7 +9,8 @@
 //! - NEXT - Navigate to next article
 //! - LAST - Navigate to previous article
 
-use crate::{NntpError, NntpResponse, Result, commands, response::codes};
+use crate::response::{ResponseCode, codes};
+use crate::{NntpError, NntpResponse, Result, commands};
 use tracing::trace;
 
 use super::NntpClient;
@@ -26,15 +27,26 @@ impl NntpClient {
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn fetch_article(&mut self, id: &str) -> Result<NntpResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_article_once(id).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_article_once(&mut self, id: &str) -> Result<NntpResponse> {
         trace!("Fetching article: {}", id);
 
         let cmd = commands::article(id);
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code == codes::NO_SUCH_ARTICLE_ID
-            || response.code == codes::NO_SUCH_ARTICLE_NUMBER
-        {
+        if matches!(
+            response.response_code(),
+            ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber
+        ) {
             return Err(NntpError::NoSuchArticle(id.to_string()));
         }
 
@@ -57,6 +69,16 @@ impl NntpClient {
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn fetch_head(&mut self, id: &str) -> Result<NntpResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_head_once(id).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_head_once(&mut self, id: &str) -> Result<NntpResponse> {
         trace!("Fetching head: {}", id);
 
         let cmd = commands::head(id);
@@ -82,6 +104,16 @@ impl NntpClient {
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn fetch_body(&mut self, id: &str) -> Result<NntpResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_body_once(id).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_body_once(&mut self, id: &str) -> Result<NntpResponse> {
         trace!("Fetching body: {}", id);
 
         let cmd = commands::body(id);
@@ -98,6 +130,42 @@ impl NntpClient {
         Ok(response)
     }
 
+    /// Fetch article `number` of the currently selected group
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::NoGroupSelected`] - No newsgroup is selected, either on
+    ///   the client (nothing is sent) or according to the server (code 412)
+    /// - Any error of [`fetch_article`](Self::fetch_article)
+    pub async fn fetch_article_number(&mut self, number: u64) -> Result<NntpResponse> {
+        self.require_group()?;
+        let result = self.fetch_article(&number.to_string()).await;
+        no_group_selected(result)
+    }
+
+    /// Fetch the body of article `number` of the currently selected group
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::NoGroupSelected`] - No newsgroup is selected, either on
+    ///   the client (nothing is sent) or according to the server (code 412)
+    /// - Any error of [`fetch_body`](Self::fetch_body)
+    pub async fn fetch_body_number(&mut self, number: u64) -> Result<NntpResponse> {
+        self.require_group()?;
+        let result = self.fetch_body(&number.to_string()).await;
+        no_group_selected(result)
+    }
+
+    /// Fail early when article numbers cannot mean anything yet
+    fn require_group(&self) -> Result<()> {
+        match self.current_group {
+            Some(_) => Ok(()),
+            None => Err(NntpError::NoGroupSelected),
+        }
+    }
+
     /// Check article status without retrieving content (RFC 3977 §6.2.4)
     ///
     /// The STAT command allows checking whether an article exists and retrieving
@@ -143,29 +211,22 @@ impl NntpClient {
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn stat(&mut self, id: &str) -> Result<commands::ArticleInfo> {
+        let mut attempt = 0;
+        loop {
+            match self.stat_once(id).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    async fn stat_once(&mut self, id: &str) -> Result<commands::ArticleInfo> {
         trace!("Checking article status: {}", id);
 
         let cmd = commands::stat(id);
         self.send_command(&cmd).await?;
         let response = self.read_response().await?;
-
-        // Handle specific error codes
-        if response.code == codes::NO_SUCH_ARTICLE_ID
-            || response.code == codes::NO_SUCH_ARTICLE_NUMBER
-        {
-            return Err(NntpError::NoSuchArticle(id.to_string()));
-        }
-
-        if response.code == codes::NO_GROUP_SELECTED {
-            return Err(NntpError::NoGroupSelected);
-        }
-
-        if response.code == codes::NO_CURRENT_ARTICLE {
-            return Err(NntpError::InvalidArticleNumber);
-        }
-
-        // Parse the response
-        commands::parse_stat_response(response)
+        stat_result(id, response)
     }
 
     /// Navigate to the next article in the current newsgroup
@@ -213,15 +274,15 @@ impl NntpClient {
         let response = self.read_response().await?;
 
         // Handle specific error codes
-        if response.code == codes::NO_GROUP_SELECTED {
+        if response.response_code() == ResponseCode::NoGroupSelected {
             return Err(NntpError::NoGroupSelected);
         }
 
-        if response.code == codes::NO_CURRENT_ARTICLE {
+        if response.response_code() == ResponseCode::NoCurrentArticle {
             return Err(NntpError::InvalidArticleNumber);
         }
 
-        if response.code == codes::NO_NEXT_ARTICLE {
+        if response.response_code() == ResponseCode::NoNextArticle {
             return Err(NntpError::NoSuchArticle("no next article".to_string()));
         }
 
@@ -274,15 +335,15 @@ impl NntpClient {
         let response = self.read_response().await?;
 
         // Handle specific error codes
-        if response.code == codes::NO_GROUP_SELECTED {
+        if response.response_code() == ResponseCode::NoGroupSelected {
             return Err(NntpError::NoGroupSelected);
         }
 
-        if response.code == codes::NO_CURRENT_ARTICLE {
+        if response.response_code() == ResponseCode::NoCurrentArticle {
             return Err(NntpError::InvalidArticleNumber);
         }
 
-        if response.code == codes::NO_PREV_ARTICLE {
+        if response.response_code() == ResponseCode::NoPrevArticle {
             return Err(NntpError::NoSuchArticle("no previous article".to_string()));
         }
 
@@ -290,3 +351,25 @@ impl NntpClient {
         commands::parse_last_response(response)
     }
 }
+
+/// Map a STAT response to the article it names or the matching error
+pub(super) fn stat_result(id: &str, response: NntpResponse) -> Result<commands::ArticleInfo> {
+    match response.response_code() {
+        ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber => {
+            Err(NntpError::NoSuchArticle(id.to_string()))
+        }
+        ResponseCode::NoGroupSelected => Err(NntpError::NoGroupSelected),
+        ResponseCode::NoCurrentArticle => Err(NntpError::InvalidArticleNumber),
+        _ => commands::parse_stat_response(response),
+    }
+}
+
+/// Report a 412 to a numbered request as [`NntpError::NoGroupSelected`]
+fn no_group_selected(result: Result<NntpResponse>) -> Result<NntpResponse> {
+    match result {
+        Err(NntpError::Protocol { code, .. }) if code == codes::NO_GROUP_SELECTED => {
+            Err(NntpError::NoGroupSelected)
+        }
+        result => result,
+    }
+}
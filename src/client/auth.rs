@@ -1,17 +1,26 @@
 //! NNTP authentication support (AUTHINFO USER/PASS and SASL)
 
+use std::sync::Arc;
+
 use super::NntpClient;
 use super::state::ConnectionState;
 use crate::commands;
+use crate::config::TlsPolicy;
+use crate::credentials::Credentials;
 use crate::error::{NntpError, Result};
-use crate::response::codes;
+use crate::response::{ResponseCode, codes};
 use tracing::debug;
 
+/// Fresh copy of the SASL mechanism a session logged in with, to log in again
+pub(super) type SaslReplay = Arc<dyn Fn() -> Box<dyn crate::SaslMechanism> + Send + Sync>;
+
 impl NntpClient {
     /// Authenticate with username and password (AUTHINFO USER/PASS)
     ///
     /// Sends AUTHINFO USER followed by AUTHINFO PASS to authenticate
-    /// with the server using credentials from the client configuration.
+    /// with the server using credentials from the client configuration, or
+    /// from its [`credentials_provider`](crate::ServerConfig::credentials_provider)
+    /// if one is set.
     ///
     /// # Example
     ///
@@ -30,12 +39,15 @@ impl NntpClient {
     ///
     /// Returns an error if:
     /// - [`NntpError::Protocol`] - Already authenticated
+    /// - [`NntpError::EncryptionRequired`] - The connection is plain and
+    ///   [`TlsPolicy::Required`](crate::TlsPolicy::Required) is set, or the
+    ///   server requires TLS (code 483)
     /// - [`NntpError::AuthFailed`] - Invalid credentials
+    /// - Any error returned by the credentials provider
+    /// - [`NntpError::ConnectionLimit`] - Too many connections are open for this account
     /// - [`NntpError::ConnectionClosed`] - Server closed the connection
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn authenticate(&mut self) -> Result<()> {
-        debug!("Authenticating as {}", self.config.username);
-
         // Check if already authenticated
         if matches!(self.state, ConnectionState::Authenticated) {
             return Err(NntpError::Protocol {
@@ -43,9 +55,18 @@ impl NntpClient {
                 message: "Already authenticated".to_string(),
             });
         }
+        self.check_plaintext_auth()?;
+
+        let credentials = match &self.config.credentials_provider {
+            Some(provider) => provider.credentials(&self.config).await?,
+            None => Credentials::new(&self.config.username, &self.config.password),
+        };
+        debug!("Authenticating as {}", credentials.username);
+
+        self.apply_pre_auth_quirks().await?;
 
         // Send AUTHINFO USER
-        let cmd = commands::authinfo_user(&self.config.username);
+        let cmd = commands::authinfo_user(&credentials.username);
         self.send_command(&cmd).await?;
 
         // Mark authentication as in progress
@@ -54,24 +75,26 @@ impl NntpClient {
         let response = self.read_response().await?;
 
         // Expect 381 (continue) or 281 (already authenticated)
-        if response.code == codes::AUTH_CONTINUE {
+        if response.response_code() == ResponseCode::AuthContinue {
             // Send AUTHINFO PASS
-            let cmd = commands::authinfo_pass(&self.config.password);
+            let cmd = commands::authinfo_pass(&credentials.password);
             self.send_command(&cmd).await?;
             let response = self.read_response().await?;
 
-            if response.code != codes::AUTH_ACCEPTED {
+            if response.response_code() != ResponseCode::AuthAccepted {
                 // Reset to Ready state on failure
                 self.state = ConnectionState::Ready;
-                return Err(NntpError::AuthFailed(response.message));
+                return Err(auth_failure(response.code, response.message));
             }
-        } else if response.code != codes::AUTH_ACCEPTED {
+        } else if response.response_code() != ResponseCode::AuthAccepted {
             // Reset to Ready state on failure
             self.state = ConnectionState::Ready;
-            return Err(NntpError::AuthFailed(response.message));
+            return Err(auth_failure(response.code, response.message));
         }
 
         self.state = ConnectionState::Authenticated;
+        self.capabilities = None;
+        self.sasl_replay = None;
         debug!("Authentication successful");
         Ok(())
     }
@@ -80,6 +103,8 @@ impl NntpClient {
     ///
     /// Uses AUTHINFO SASL for authentication with pluggable mechanisms.
     /// Supports challenge-response exchange via 383 continuation responses.
+    /// A copy of `mechanism` is kept, as it was before the exchange, to log
+    /// in the same way after a reconnect or a 480 mid-session.
     ///
     /// # Arguments
     ///
@@ -105,14 +130,36 @@ impl NntpClient {
     ///
     /// Returns an error if:
     /// - [`NntpError::AuthFailed`] - Authentication rejected (code 481)
+    /// - [`NntpError::ConnectionLimit`] - Rejected because too many connections are open
     /// - [`NntpError::Protocol`] - Out of sequence (code 482) or protocol error
-    /// - [`NntpError::EncryptionRequired`] - TLS required but not enabled (code 483)
+    /// - [`NntpError::EncryptionRequired`] - TLS required but not enabled (code 483),
+    ///   or the connection is plain and [`TlsPolicy::Required`](crate::TlsPolicy::Required) is set
     /// - [`NntpError::ConnectionClosed`] - Server closed the connection
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn authenticate_sasl(
         &mut self,
-        mut mechanism: impl crate::SaslMechanism,
+        mechanism: impl crate::SaslMechanism + Clone + 'static,
     ) -> Result<()> {
+        let pristine = mechanism.clone();
+        let replay: SaslReplay = Arc::new(move || Box::new(pristine.clone()));
+        self.sasl_exchange(Box::new(mechanism)).await?;
+        self.sasl_replay = Some(replay);
+        Ok(())
+    }
+
+    /// Log in the way an earlier session did: with the SASL mechanism it
+    /// used, or with AUTHINFO USER/PASS
+    pub(super) async fn authenticate_like(&mut self, sasl: Option<SaslReplay>) -> Result<()> {
+        let Some(replay) = sasl else {
+            return self.authenticate().await;
+        };
+        self.sasl_exchange(replay()).await?;
+        self.sasl_replay = Some(replay);
+        Ok(())
+    }
+
+    /// Run an AUTHINFO SASL exchange with `mechanism`
+    async fn sasl_exchange(&mut self, mut mechanism: Box<dyn crate::SaslMechanism>) -> Result<()> {
         debug!(
             "Authenticating with SASL mechanism: {}",
             mechanism.mechanism_name()
@@ -125,6 +172,9 @@ impl NntpClient {
                 message: "Already authenticated".to_string(),
             });
         }
+        self.check_plaintext_auth()?;
+
+        self.apply_pre_auth_quirks().await?;
 
         // Get initial response from mechanism
         let initial_response = mechanism.initial_response()?;
@@ -145,7 +195,7 @@ impl NntpClient {
         let mut response = self.read_response().await?;
 
         // Handle challenge-response loop
-        while response.code == codes::SASL_CONTINUE {
+        while response.response_code() == ResponseCode::SaslContinue {
             debug!("SASL challenge received, processing...");
 
             // Extract challenge data from response message
@@ -163,18 +213,24 @@ impl NntpClient {
         }
 
         // Check final response
-        match response.code {
-            codes::AUTH_ACCEPTED => {
+        match response.response_code() {
+            ResponseCode::AuthAccepted => {
                 self.state = ConnectionState::Authenticated;
+                self.capabilities = None;
                 debug!("SASL authentication successful");
                 Ok(())
             }
-            codes::AUTH_REJECTED => {
+            ResponseCode::AuthRejected => {
                 // Reset to Ready state on failure
                 self.state = ConnectionState::Ready;
-                Err(NntpError::AuthFailed(response.message))
+                Err(auth_failure(response.code, response.message))
             }
-            codes::AUTH_OUT_OF_SEQUENCE => {
+            code if commands::is_connection_limit_response(code.code(), &response.message) => {
+                // Reset to Ready state on failure
+                self.state = ConnectionState::Ready;
+                Err(NntpError::ConnectionLimit(response.message))
+            }
+            ResponseCode::AuthOutOfSequence => {
                 // Reset to Ready state on failure
                 self.state = ConnectionState::Ready;
                 Err(NntpError::Protocol {
@@ -182,7 +238,7 @@ impl NntpClient {
                     message: format!("Authentication out of sequence: {}", response.message),
                 })
             }
-            codes::ENCRYPTION_REQUIRED => {
+            ResponseCode::EncryptionRequired => {
                 // Reset to Ready state on failure
                 self.state = ConnectionState::Ready;
                 Err(NntpError::EncryptionRequired(response.message))
@@ -197,4 +253,34 @@ impl NntpClient {
             }
         }
     }
+
+    /// Refuse to send credentials in clear text under [`TlsPolicy::Required`]
+    fn check_plaintext_auth(&self) -> Result<()> {
+        if self.config.tls_policy == TlsPolicy::Required && !self.is_tls() {
+            return Err(NntpError::EncryptionRequired(
+                "Refusing to authenticate over an unencrypted connection".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Send MODE READER first on servers that only authenticate reader sessions
+    async fn apply_pre_auth_quirks(&mut self) -> Result<()> {
+        if self.quirks.mode_reader_before_auth {
+            debug!("Quirk: sending MODE READER before authentication");
+            self.mode_reader().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Error for a rejected authentication, telling connection limits apart
+fn auth_failure(code: u16, message: String) -> NntpError {
+    if code == codes::ENCRYPTION_REQUIRED {
+        NntpError::EncryptionRequired(message)
+    } else if commands::is_connection_limit_response(code, &message) {
+        NntpError::ConnectionLimit(message)
+    } else {
+        NntpError::AuthFailed(message)
+    }
 }
@@ -0,0 +1,623 @@
+//! Streaming article bodies
+//!
+//! [`NntpClient::fetch_body_stream`] hands out the body of a BODY response as
+//! an [`AsyncRead`] that undoes dot-stuffing as data arrives, so very large
+//! bodies can be piped to disk or a decoder without being buffered.
+//! [`NntpClient::fetch_body_prefix`] builds on it to decode only the start of
+//! a body for previews.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader, ReadBuf};
+use tokio::time::{Instant, Sleep, sleep};
+use tracing::trace;
+
+use super::NntpClient;
+use crate::error::{NntpError, Result};
+use crate::response::NntpResponse;
+use crate::warnings::ParseWarning;
+use crate::yenc::decode::decode_line_bytes;
+use crate::yenc::params::{parse_ybegin, parse_ypart};
+use crate::yenc::{YencHeader, YencPart};
+use crate::{commands, response::ResponseCode};
+
+/// Longest line read while decoding a body prefix
+const MAX_PREFIX_LINE: usize = 64 * 1024;
+
+/// Position in the dot-stuffed wire format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineState {
+    /// At the start of a line
+    LineStart,
+    /// A line started with "." (consumed, not yet emitted)
+    AfterDot,
+    /// A line started with ".\r" (consumed, not yet emitted)
+    AfterDotCr,
+    /// The "\r" of a ".\r" line start still has to be emitted
+    PendingCr,
+    /// Inside a line
+    MidLine,
+    /// The terminating "." line has been consumed
+    Done,
+}
+
+/// Dot-stuffing of the lines unstuffed so far
+#[derive(Debug, Default)]
+struct StuffingCheck {
+    /// Lines started so far
+    line: u64,
+    /// First line that starts with a lone dot
+    first: Option<ParseWarning>,
+}
+
+impl StuffingCheck {
+    /// Note a line starting with a lone dot, followed by `rest` as far as buffered
+    fn lone_dot(&mut self, rest: &[u8]) {
+        let rest = memchr::memchr(b'\n', rest).map_or(rest, |end| &rest[..end]);
+        self.first.get_or_insert(ParseWarning::UnstuffedDotLine {
+            line: self.line,
+            terminator_like: rest.iter().all(u8::is_ascii_whitespace),
+        });
+    }
+}
+
+/// Article body streamed from the server
+///
+/// Created by [`NntpClient::fetch_body_stream`]. Reading yields the body
+/// bytes with line terminators kept and dot-stuffing removed, like
+/// [`fetch_body_binary`](NntpClient::fetch_body_binary), and returns EOF at
+/// the terminating "." line. Each read waits at most
+/// [`TimeoutConfig::read`](crate::TimeoutConfig::read) for more data.
+///
+/// Lines starting with an unstuffed dot are checked as for buffered bodies:
+/// with [`ServerConfig::strict_dot_stuffing`](crate::ServerConfig::strict_dot_stuffing)
+/// the read at EOF fails with an error wrapping
+/// [`NntpError::MalformedBody`] instead of returning EOF.
+///
+/// The stream borrows the client. Dropping it before EOF leaves the rest of
+/// the response unread, so the client is then marked
+/// [broken](NntpClient::is_broken) and should be discarded.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nntp_rs::{NntpClient, ServerConfig};
+/// # use std::sync::Arc;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+/// let mut body = client.fetch_body_stream("<part1@example.com>").await?;
+/// let mut file = tokio::fs::File::create("part1.yenc").await?;
+/// tokio::io::copy(&mut body, &mut file).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BodyStream<'a> {
+    client: &'a mut NntpClient,
+    code: u16,
+    message: String,
+    state: LineState,
+    stuffing: StuffingCheck,
+    /// Whole body, when the server sent it as one compressed block
+    decompressed: Option<Bytes>,
+    idle_timeout: Duration,
+    /// When waiting on the socket fails, set once the wait starts
+    deadline: Pin<Box<Sleep>>,
+    /// Whether the last poll found no data, so `deadline` is running
+    waiting: bool,
+}
+
+impl NntpClient {
+    /// Fetch an article body as a stream instead of buffering it
+    ///
+    /// Sends BODY and returns once the status line is read; the body is then
+    /// read through the returned [`BodyStream`]. Use this for bodies too large
+    /// to hold in memory. Bodies sent as a compressed block (XFEATURE COMPRESS
+    /// GZIP) are decompressed in full before streaming starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Message-ID (with angle brackets) or article number
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::NoSuchArticle`] - The article does not exist
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    pub async fn fetch_body_stream(&mut self, id: &str) -> Result<BodyStream<'_>> {
+        trace!("Fetching body (stream): {}", id);
+
+        let cmd = commands::body(id);
+        self.send_command(&cmd).await?;
+        let response = self.read_response().await?;
+
+        if matches!(
+            response.response_code(),
+            ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber
+        ) {
+            return Err(NntpError::NoSuchArticle(id.to_string()));
+        }
+        if !response.is_success() {
+            return Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            });
+        }
+
+        self.stream_data_block(response).await
+    }
+
+    /// Stream the data block following a successful multi-line status line
+    pub(super) async fn stream_data_block(
+        &mut self,
+        response: NntpResponse,
+    ) -> Result<BodyStream<'_>> {
+        let decompressed = if self.response_is_marked_compressed(&response.message) {
+            let block = self.read_compressed_block(&[]).await?;
+            self.check_block_stuffing(&block)?;
+            Some(Bytes::from(block))
+        } else {
+            None
+        };
+
+        let idle_timeout = self.config.timeouts.read;
+        Ok(BodyStream {
+            client: self,
+            code: response.code,
+            message: response.message,
+            state: LineState::LineStart,
+            stuffing: StuffingCheck::default(),
+            decompressed,
+            idle_timeout,
+            deadline: Box::pin(sleep(idle_timeout)),
+            waiting: false,
+        })
+    }
+}
+
+/// Decoded start of an article body
+///
+/// Returned by [`NntpClient::fetch_body_prefix`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyPrefix {
+    /// The `=ybegin` header, if the body is yEnc-encoded
+    pub yenc: Option<YencHeader>,
+    /// The `=ypart` header of a multi-part yEnc body
+    pub part: Option<YencPart>,
+    /// Up to the requested number of bytes: decoded data for yEnc bodies,
+    /// the raw (unstuffed) body otherwise
+    pub data: Vec<u8>,
+}
+
+impl BodyPrefix {
+    /// Check whether the body was yEnc-encoded
+    pub fn is_yenc(&self) -> bool {
+        self.yenc.is_some()
+    }
+}
+
+impl NntpClient {
+    /// Fetch only the first `max_bytes` decoded bytes of an article body
+    ///
+    /// Meant for previews such as sniffing the RAR or MKV header of a segment
+    /// or checking whether an archive is encrypted. yEnc bodies are decoded
+    /// line by line until `max_bytes` are available; other bodies are
+    /// returned raw. NNTP cannot stop a response early, so the rest of the
+    /// body is then read and discarded without buffering, keeping the
+    /// connection usable. The saving is memory and decoding work, not
+    /// transfer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Message-ID (with angle brackets) or article number
+    /// * `max_bytes` - Number of decoded bytes wanted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::NoSuchArticle`] - The article does not exist
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::InvalidResponse`] - The yEnc headers or data are malformed
+    /// - [`NntpError::Io`] - The connection failed or timed out while reading
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let prefix = client.fetch_body_prefix("<part1@example.com>", 16).await?;
+    /// if prefix.data.starts_with(b"Rar!\x1a\x07") {
+    ///     println!("RAR archive");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_body_prefix(&mut self, id: &str, max_bytes: usize) -> Result<BodyPrefix> {
+        let stream = self.fetch_body_stream(id).await?;
+        let mut reader = BufReader::new(stream);
+        let prefix = read_prefix(&mut reader, max_bytes).await;
+
+        // Drain the rest so the next command sees its own response
+        let drained = tokio::io::copy(&mut reader, &mut tokio::io::sink()).await;
+        let prefix = prefix?;
+        drained.map_err(NntpError::Io)?;
+        Ok(prefix)
+    }
+}
+
+/// Read one line (including its terminator) into `line`, bounded by [`MAX_PREFIX_LINE`]
+async fn read_prefix_line<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    line: &mut Vec<u8>,
+) -> Result<usize> {
+    line.clear();
+    let n = (&mut *reader)
+        .take(MAX_PREFIX_LINE as u64)
+        .read_until(b'\n', line)
+        .await
+        .map_err(NntpError::Io)?;
+    Ok(n)
+}
+
+/// Decode the start of a body read from `reader`
+async fn read_prefix<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    max_bytes: usize,
+) -> Result<BodyPrefix> {
+    let mut prefix = BodyPrefix {
+        yenc: None,
+        part: None,
+        data: Vec::with_capacity(max_bytes.min(MAX_PREFIX_LINE)),
+    };
+    let mut line = Vec::new();
+
+    // yEnc bodies may be preceded by blank lines
+    loop {
+        if read_prefix_line(reader, &mut line).await? == 0 {
+            return Ok(prefix);
+        }
+        if !line.trim_ascii().is_empty() {
+            break;
+        }
+        prefix.data.extend_from_slice(&line);
+    }
+
+    if !line.starts_with(b"=ybegin ") {
+        // Not yEnc: hand back the raw body
+        prefix.data.extend_from_slice(&line);
+        while prefix.data.len() < max_bytes && read_prefix_line(reader, &mut line).await? > 0 {
+            prefix.data.extend_from_slice(&line);
+        }
+        prefix.data.truncate(max_bytes);
+        return Ok(prefix);
+    }
+
+    prefix.data.clear();
+    prefix.yenc = Some(parse_ybegin(yenc_keyword_line(&line)?)?);
+    while prefix.data.len() < max_bytes && read_prefix_line(reader, &mut line).await? > 0 {
+        if line.starts_with(b"=yend") {
+            break;
+        }
+        if line.starts_with(b"=ypart ") && prefix.part.is_none() && prefix.data.is_empty() {
+            prefix.part = Some(parse_ypart(yenc_keyword_line(&line)?)?);
+            continue;
+        }
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+        decode_line_bytes(content, &mut prefix.data)?;
+    }
+    prefix.data.truncate(max_bytes);
+    Ok(prefix)
+}
+
+/// A `=ybegin`/`=ypart` line as text without its line terminator
+fn yenc_keyword_line(line: &[u8]) -> Result<&str> {
+    std::str::from_utf8(line)
+        .map(|s| s.trim_end_matches(['\r', '\n']))
+        .map_err(|_| NntpError::InvalidResponse("Invalid UTF-8 in yEnc header".to_string()))
+}
+
+impl BodyStream<'_> {
+    /// Client the body is read from
+    pub(super) fn client_mut(&mut self) -> &mut NntpClient {
+        self.client
+    }
+
+    /// Response code of the BODY command (222)
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Response message of the BODY command
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Check whether the whole body has been read
+    pub fn is_finished(&self) -> bool {
+        match &self.decompressed {
+            Some(data) => data.is_empty(),
+            None => self.state == LineState::Done,
+        }
+    }
+
+    /// Report a badly stuffed line once the body is read, failing in strict mode
+    ///
+    /// The whole response has been read by then, so the connection stays
+    /// usable either way.
+    fn settle_stuffing(&mut self) -> io::Result<()> {
+        self.client
+            .check_dot_stuffing(self.stuffing.first.take())
+            .map_err(io::Error::other)
+    }
+
+    /// Fail the read and leave the connection unusable
+    fn fail(&mut self, error: io::Error) -> Poll<io::Result<()>> {
+        self.client.mark_broken();
+        self.state = LineState::Done;
+        Poll::Ready(Err(error))
+    }
+}
+
+/// Unstuff as much wire data into `out` as fits, returning the wire bytes consumed
+fn unstuff(
+    state: &mut LineState,
+    stuffing: &mut StuffingCheck,
+    wire: &[u8],
+    out: &mut ReadBuf<'_>,
+) -> usize {
+    let mut consumed = 0;
+    while consumed < wire.len() && out.remaining() > 0 {
+        let byte = wire[consumed];
+        if *state == LineState::LineStart {
+            stuffing.line += 1;
+        }
+        *state = match *state {
+            LineState::LineStart if byte == b'.' => {
+                consumed += 1;
+                LineState::AfterDot
+            }
+            LineState::AfterDot | LineState::AfterDotCr if byte == b'\n' => {
+                consumed += 1;
+                *state = LineState::Done;
+                break;
+            }
+            LineState::AfterDot if byte == b'\r' => {
+                consumed += 1;
+                LineState::AfterDotCr
+            }
+            LineState::AfterDot => {
+                // ".." is a stuffed dot; a lone leading dot is kept as-is
+                if byte != b'.' {
+                    stuffing.lone_dot(&wire[consumed..]);
+                }
+                out.put_slice(b".");
+                consumed += usize::from(byte == b'.');
+                LineState::MidLine
+            }
+            LineState::AfterDotCr => {
+                stuffing.lone_dot(&wire[consumed..]);
+                out.put_slice(b".");
+                LineState::PendingCr
+            }
+            LineState::PendingCr => {
+                out.put_slice(b"\r");
+                LineState::MidLine
+            }
+            LineState::LineStart | LineState::MidLine => {
+                let rest = &wire[consumed..];
+                let line_end = memchr::memchr(b'\n', rest).map_or(rest.len(), |pos| pos + 1);
+                let take = line_end.min(out.remaining());
+                out.put_slice(&rest[..take]);
+                consumed += take;
+                if rest[take - 1] == b'\n' {
+                    LineState::LineStart
+                } else {
+                    LineState::MidLine
+                }
+            }
+            LineState::Done => break,
+        };
+    }
+    consumed
+}
+
+impl AsyncRead for BodyStream<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(data) = &mut this.decompressed {
+            let n = data.len().min(buf.remaining());
+            buf.put_slice(&data.split_to(n));
+            return Poll::Ready(Ok(()));
+        }
+
+        if this.state == LineState::Done {
+            return Poll::Ready(this.settle_stuffing());
+        }
+
+        let start = buf.filled().len();
+        while this.state != LineState::Done && buf.remaining() > 0 {
+            let stream = Pin::new(&mut this.client.stream);
+            let wire = match stream.poll_fill_buf(cx) {
+                Poll::Ready(Ok(wire)) => wire,
+                Poll::Ready(Err(e)) => return this.fail(e),
+                Poll::Pending => {
+                    // Time between polls is the consumer's, not the server's
+                    if !this.waiting {
+                        this.waiting = true;
+                        let deadline = Instant::now() + this.idle_timeout;
+                        this.deadline.as_mut().reset(deadline);
+                    }
+                    if this.deadline.as_mut().poll(cx).is_ready() {
+                        return this.fail(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "timed out reading article body",
+                        ));
+                    }
+                    return Poll::Pending;
+                }
+            };
+            this.waiting = false;
+            if wire.is_empty() {
+                return this.fail(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed before end of article body",
+                ));
+            }
+
+            let consumed = unstuff(&mut this.state, &mut this.stuffing, wire, buf);
+            Pin::new(&mut this.client.stream).consume(consumed);
+
+            // Keep going if only a leading dot was consumed
+            if buf.filled().len() > start {
+                break;
+            }
+        }
+        // Data read in this call is returned first, the check comes with EOF
+        if this.state == LineState::Done && buf.filled().len() == start {
+            return Poll::Ready(this.settle_stuffing());
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for BodyStream<'_> {
+    fn drop(&mut self) {
+        // The rest of the response is still on the wire
+        if self.decompressed.is_none() && self.state != LineState::Done {
+            self.client.mark_broken();
+        }
+        // A badly stuffed line not reported yet, if EOF was never read
+        let _ = self.settle_stuffing();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `wire` in pieces of `step` bytes into reads of `cap` bytes
+    fn run(wire: &[u8], step: usize, cap: usize) -> (Vec<u8>, LineState, usize) {
+        let (out, state, consumed, _) = run_checked(wire, step, cap);
+        (out, state, consumed)
+    }
+
+    /// [`run`], also returning the dot-stuffing check
+    fn run_checked(
+        wire: &[u8],
+        step: usize,
+        cap: usize,
+    ) -> (Vec<u8>, LineState, usize, StuffingCheck) {
+        let mut state = LineState::LineStart;
+        let mut stuffing = StuffingCheck::default();
+        let mut out = Vec::new();
+        let mut pos = 0;
+        while pos < wire.len() && state != LineState::Done {
+            let end = (pos + step).min(wire.len());
+            let mut storage = vec![0u8; cap];
+            let mut buf = ReadBuf::new(&mut storage);
+            pos += unstuff(&mut state, &mut stuffing, &wire[pos..end], &mut buf);
+            out.extend_from_slice(buf.filled());
+        }
+        (out, state, pos, stuffing)
+    }
+
+    #[test]
+    fn test_unstuff_any_split() {
+        let wire = b"=ybegin\r\n..a\r\n.b\r\nc..\r\n...\r\n.\rx\r\n.\r\nafter";
+        let expected = b"=ybegin\r\n.a\r\n.b\r\nc..\r\n..\r\n.\rx\r\n";
+        for step in 1..8 {
+            for cap in 1..8 {
+                let (out, state, consumed) = run(wire, step, cap);
+                assert_eq!(out, expected, "step {} cap {}", step, cap);
+                assert_eq!(state, LineState::Done);
+                // Data after the terminator is left on the wire
+                assert_eq!(&wire[consumed..], b"after");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unstuff_reports_first_lone_dot() {
+        let wire = b"a\r\n..b\r\n.c\r\n. \r\n.\r\n";
+        for step in 1..8 {
+            let (_, _, _, stuffing) = run_checked(wire, step, 64);
+            assert_eq!(
+                stuffing.first,
+                Some(ParseWarning::UnstuffedDotLine {
+                    line: 3,
+                    terminator_like: false,
+                }),
+                "step {}",
+                step
+            );
+        }
+
+        let (_, _, _, stuffing) = run_checked(b"a\r\n.\rx\r\n.\r\n", 64, 64);
+        assert_eq!(
+            stuffing.first,
+            Some(ParseWarning::UnstuffedDotLine {
+                line: 2,
+                terminator_like: false,
+            })
+        );
+        let (_, _, _, stuffing) = run_checked(b"a\r\n..b\r\n.\r\n", 64, 64);
+        assert_eq!(stuffing.first, None);
+    }
+
+    #[test]
+    fn test_unstuff_lf_terminator() {
+        let (out, state, _) = run(b"line\n.\n", 4, 64);
+        assert_eq!(out, b"line\n");
+        assert_eq!(state, LineState::Done);
+    }
+
+    #[tokio::test]
+    async fn test_read_prefix_yenc() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let encoded = crate::yenc::encode(&data, "a.bin", 128, Some((2, 3, 1001, 2000, 3000)));
+        let encoded = encoded.unwrap();
+
+        let mut reader = encoded.as_slice();
+        let prefix = read_prefix(&mut reader, 300).await.unwrap();
+        assert_eq!(prefix.yenc.as_ref().map(|h| h.name.as_str()), Some("a.bin"));
+        assert_eq!(prefix.part.as_ref().map(|p| p.begin), Some(1001));
+        assert_eq!(prefix.data, &data[..300]);
+
+        // Asking for more than the part holds stops at =yend
+        let mut reader = encoded.as_slice();
+        let prefix = read_prefix(&mut reader, 5000).await.unwrap();
+        assert_eq!(prefix.data, data);
+    }
+
+    #[tokio::test]
+    async fn test_read_prefix_plain_body() {
+        let mut reader = b"\r\nHello\r\nWorld\r\n".as_slice();
+        let prefix = read_prefix(&mut reader, 9).await.unwrap();
+        assert!(!prefix.is_yenc());
+        assert_eq!(prefix.data, b"\r\nHello\r\n");
+
+        let mut reader = b"".as_slice();
+        assert!(read_prefix(&mut reader, 9).await.unwrap().data.is_empty());
+    }
+
+    #[test]
+    fn test_unstuff_empty_body() {
+        let (out, state, consumed) = run(b".\r\n", 1, 16);
+        assert!(out.is_empty());
+        assert_eq!(state, LineState::Done);
+        assert_eq!(consumed, 3);
+    }
+}
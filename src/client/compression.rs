@@ -1,10 +1,15 @@
 use crate::Result;
 use crate::commands;
-use crate::response::codes;
+use crate::compression::GzipDetection;
+use crate::error::NntpError;
+use crate::response::ResponseCode;
+use flate2::{Decompress, FlushDecompress, Status};
 use tracing::{debug, trace};
 
 use super::NntpClient;
+use super::deflate::DeflateStream;
 use super::state::CompressionMode;
+use super::stream::NntpStream;
 
 impl NntpClient {
     /// Attempt to enable compression with fallback to GZIP
@@ -18,31 +23,36 @@ impl NntpClient {
     /// 2. XFEATURE COMPRESS GZIP (headers-only) - fallback for compatibility
     /// 3. No compression - if neither is supported
     ///
+    /// COMPRESS DEFLATE is skipped when the cached
+    /// [`capability_set`](Self::capability_set) does not list it, unless the
+    /// [`unreliable_capabilities`](crate::ServerQuirks::unreliable_capabilities)
+    /// quirk is set.
+    ///
     /// Returns `true` if any compression mode was enabled, `false` otherwise.
     /// Always returns `Ok` - compression failure is not an error.
     pub async fn try_enable_compression(&mut self) -> Result<bool> {
-        // Try RFC 8054 COMPRESS DEFLATE first (full session compression)
-        debug!("Attempting RFC 8054 COMPRESS DEFLATE");
-        self.send_command(commands::compress_deflate()).await?;
-        let response = self.read_response().await?;
-
-        if response.code == codes::COMPRESSION_ACTIVE {
-            // 206 = compression active
-            self.compression_mode = CompressionMode::FullSession;
-            debug!("RFC 8054 COMPRESS DEFLATE enabled (full session compression)");
+        // Skip COMPRESS DEFLATE when cached capabilities show it is not offered
+        let deflate_offered = self.quirks.unreliable_capabilities
+            || self
+                .capabilities
+                .as_ref()
+                .is_none_or(|caps| caps.has_arg("COMPRESS", "DEFLATE"));
+        if deflate_offered && self.try_compress_deflate().await? {
             return Ok(true);
         }
 
         // COMPRESS DEFLATE not supported, try XFEATURE COMPRESS GZIP
-        debug!(
-            "COMPRESS DEFLATE not supported (code {}), trying XFEATURE COMPRESS GZIP",
-            response.code
-        );
+        debug!("Trying XFEATURE COMPRESS GZIP");
         self.send_command(commands::xfeature_compress_gzip())
             .await?;
         let response = self.read_response().await?;
 
-        if response.is_success() {
+        let acknowledged = if self.quirks.xfeature_nonstandard_ack {
+            response.code < 400
+        } else {
+            response.is_success()
+        };
+        if acknowledged {
             // 290 or 2xx = compression enabled
             self.compression_mode = CompressionMode::HeadersOnly;
             debug!("XFEATURE COMPRESS GZIP enabled (headers-only compression)");
@@ -57,12 +67,34 @@ impl NntpClient {
         Ok(false)
     }
 
+    /// Try RFC 8054 COMPRESS DEFLATE (full session compression)
+    async fn try_compress_deflate(&mut self) -> Result<bool> {
+        debug!("Attempting RFC 8054 COMPRESS DEFLATE");
+        self.send_command(commands::compress_deflate()).await?;
+        let response = self.read_response().await?;
+
+        if response.response_code() != ResponseCode::CompressionActive {
+            debug!("COMPRESS DEFLATE not supported (code {})", response.code);
+            return Ok(false);
+        }
+
+        // 206 = compression active
+        self.start_deflate();
+        self.compression_mode = CompressionMode::FullSession;
+        debug!("RFC 8054 COMPRESS DEFLATE enabled (full session compression)");
+        Ok(true)
+    }
+
     /// Get bandwidth statistics (compressed vs decompressed bytes)
     ///
     /// Returns `(bytes_compressed, bytes_decompressed)`.
     /// Returns `(0, 0)` if compression is not enabled.
     pub fn get_bandwidth_stats(&self) -> (u64, u64) {
-        (self.bytes_compressed, self.bytes_decompressed)
+        let (compressed, decompressed) = self.stream.get_ref().deflate_totals();
+        (
+            self.bytes_compressed + compressed,
+            self.bytes_decompressed + decompressed,
+        )
     }
 
     /// Check if compression is enabled
@@ -70,59 +102,144 @@ impl NntpClient {
         self.compression_mode != CompressionMode::None
     }
 
-    /// Decompress data based on current compression mode
-    pub(super) fn maybe_decompress(&mut self, data: &[u8]) -> Result<Vec<u8>> {
-        use flate2::read::{DeflateDecoder, ZlibDecoder};
-        use std::io::Read;
-
-        match self.compression_mode {
-            CompressionMode::None => Ok(data.to_vec()),
-            CompressionMode::HeadersOnly => {
-                // Use zlib decompression (server sends zlib despite calling it "GZIP")
-                let mut decoder = ZlibDecoder::new(data);
-                // Pre-allocate: compressed data typically expands 3-5x
-                let mut decompressed = Vec::with_capacity(data.len() * 3);
-                decoder.read_to_end(&mut decompressed).map_err(|e| {
-                    crate::error::NntpError::InvalidResponse(format!(
-                        "Zlib decompression failed: {}",
-                        e
-                    ))
-                })?;
-                self.bytes_compressed += data.len() as u64;
-                self.bytes_decompressed += decompressed.len() as u64;
-                trace!(
-                    "Decompressed {} bytes to {} bytes (zlib)",
-                    data.len(),
-                    decompressed.len()
-                );
-                Ok(decompressed)
-            }
-            CompressionMode::FullSession => {
-                // Use deflate decompression
-                let mut decoder = DeflateDecoder::new(data);
-                // Pre-allocate: compressed data typically expands 3-5x
-                let mut decompressed = Vec::with_capacity(data.len() * 3);
-                decoder.read_to_end(&mut decompressed).map_err(|e| {
-                    crate::error::NntpError::InvalidResponse(format!(
-                        "Deflate decompression failed: {}",
-                        e
-                    ))
+    /// Get the strategy used to recognize headers-only compressed responses
+    pub fn gzip_detection(&self) -> &GzipDetection {
+        &self.gzip_detection
+    }
+
+    /// Override the strategy used to recognize headers-only compressed responses
+    ///
+    /// By default the strategy is picked from the server greeting via
+    /// [`GzipDetection::for_greeting`]. Use this when a provider is not
+    /// fingerprinted correctly.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{GzipDetection, NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// # let config = Arc::new(ServerConfig::tls("news.example.com", "user", "pass"));
+    /// let mut client = NntpClient::connect(config).await?;
+    /// client.set_gzip_detection(GzipDetection::AlwaysCompressed(vec!["XOVER".into()]));
+    /// client.try_enable_compression().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_gzip_detection(&mut self, detection: GzipDetection) {
+        self.gzip_detection = detection;
+    }
+
+    /// Wrap the transport in RFC 8054 compression after a 206 response
+    ///
+    /// Anything already buffered past the 206 line is the start of the
+    /// server's compressed stream, so it is handed to the decompressor.
+    fn start_deflate(&mut self) {
+        use tokio::io::AsyncBufRead;
+
+        let buffered = self.stream.buffer().to_vec();
+        std::pin::Pin::new(&mut self.stream).consume(buffered.len());
+        let transport = std::mem::replace(self.stream.get_mut(), NntpStream::Closed);
+        *self.stream.get_mut() =
+            NntpStream::Deflate(Box::new(DeflateStream::new(transport, &buffered)));
+    }
+
+    /// Record decompression statistics for a completed compressed block
+    pub(super) fn record_decompressed(&mut self, compressed: usize, decompressed: usize) {
+        self.bytes_compressed += compressed as u64;
+        self.bytes_decompressed += decompressed as u64;
+        trace!(
+            "Decompressed {} bytes to {} bytes (zlib)",
+            compressed, decompressed
+        );
+    }
+}
+
+/// Incremental zlib decoder for a headers-only compressed response block
+///
+/// Servers terminate the block in one of two ways: with an uncompressed
+/// ".\r\n" after the zlib stream, or with the ".\r\n" inside the compressed
+/// data. Decoding as bytes arrive tells the two apart reliably, instead of
+/// guessing from the raw bytes (which may end in ".\r\n" by coincidence).
+pub(super) struct GzipBlockDecoder {
+    decoder: Decompress,
+    raw: Vec<u8>,
+    output: Vec<u8>,
+    stream_end: bool,
+}
+
+impl GzipBlockDecoder {
+    pub(super) fn new() -> Self {
+        Self {
+            decoder: Decompress::new(true),
+            raw: Vec::new(),
+            output: Vec::new(),
+            stream_end: false,
+        }
+    }
+
+    /// Number of raw (compressed) bytes received so far
+    pub(super) fn raw_len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Feed raw bytes from the connection
+    ///
+    /// Returns `true` once the block, including its terminator, is complete.
+    pub(super) fn push(&mut self, data: &[u8]) -> Result<bool> {
+        self.raw.extend_from_slice(data);
+
+        while !self.stream_end {
+            let consumed = self.consumed();
+            let input = &self.raw[consumed..];
+            // Pre-allocate: compressed data typically expands 3-5x
+            self.output.reserve((input.len() * 4).max(4096));
+            let produced_before = self.output.len();
+
+            let status = self
+                .decoder
+                .decompress_vec(input, &mut self.output, FlushDecompress::None)
+                .map_err(|e| {
+                    NntpError::InvalidResponse(format!("Zlib decompression failed: {}", e))
                 })?;
-                self.bytes_compressed += data.len() as u64;
-                self.bytes_decompressed += decompressed.len() as u64;
-                trace!(
-                    "Decompressed {} bytes to {} bytes (deflate)",
-                    data.len(),
-                    decompressed.len()
-                );
-                Ok(decompressed)
+
+            if status == Status::StreamEnd {
+                self.stream_end = true;
+            } else if self.consumed() == consumed && self.output.len() == produced_before {
+                // Need more input
+                break;
             }
         }
+
+        Ok(self.stream_end && (self.terminator_in_stream() || self.terminator_after_stream()))
+    }
+
+    /// Finish the block, returning `(compressed_len, decompressed_data)` without terminator
+    pub(super) fn finish(mut self) -> (usize, Vec<u8>) {
+        if self.terminator_in_stream() {
+            self.output.truncate(self.output.len() - 3);
+        }
+        (self.consumed(), self.output)
+    }
+
+    fn consumed(&self) -> usize {
+        // total_in never exceeds the bytes we have buffered
+        usize::try_from(self.decoder.total_in()).unwrap_or(self.raw.len())
+    }
+
+    fn terminator_in_stream(&self) -> bool {
+        self.output == b".\r\n" || self.output.ends_with(b"\r\n.\r\n")
+    }
+
+    fn terminator_after_stream(&self) -> bool {
+        let trailing = &self.raw[self.consumed()..];
+        trailing.ends_with(b".\r\n") || trailing.ends_with(b".\n")
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::GzipBlockDecoder;
     use crate::client::state::CompressionMode;
     use flate2::Compression;
     use flate2::read::{DeflateDecoder, ZlibDecoder};
@@ -161,7 +278,7 @@ mod tests {
         let original_data = b"Subject: Test Article\r\nFrom: test@example.com\r\n";
         let compressed_data = compress_zlib(original_data);
 
-        // Decompress using the same logic as maybe_decompress()
+        // Decompress using the same logic as GzipBlockDecoder
         let mut decoder = ZlibDecoder::new(&compressed_data[..]);
         let mut decompressed = Vec::new();
         decoder.read_to_end(&mut decompressed).unwrap();
@@ -245,7 +362,7 @@ mod tests {
         let original_data = b"200 posting allowed\r\n";
         let compressed_data = compress_deflate(original_data);
 
-        // Decompress using the same logic as maybe_decompress()
+        // Decompress using the same logic as the client
         let mut decoder = DeflateDecoder::new(&compressed_data[..]);
         let mut decompressed = Vec::new();
         decoder.read_to_end(&mut decompressed).unwrap();
@@ -298,7 +415,7 @@ mod tests {
         let result = decoder.read_to_end(&mut decompressed);
 
         // Deflate may partially decode invalid data, so we just verify it doesn't panic
-        // The real error handling happens in maybe_decompress() which catches errors
+        // The real error handling happens in the client which catches errors
         let _ = result; // May succeed or fail depending on data pattern
     }
 
@@ -316,7 +433,7 @@ mod tests {
         let result = decoder.read_to_end(&mut decompressed);
 
         // Heavily truncated data should fail or produce incomplete output
-        // The key is that maybe_decompress() catches this and returns original data
+        // The key is that the client catches this and returns original data
         if result.is_ok() {
             assert_ne!(
                 decompressed, original_data,
@@ -466,6 +583,67 @@ mod tests {
         assert!(bytes_decompressed > 0);
     }
 
+    // ========================================================================
+    // GzipBlockDecoder Tests
+    // ========================================================================
+
+    #[test]
+    fn test_gzip_block_terminator_after_stream() {
+        let original = b"1\tSubject\tAuthor\r\n2\tOther\tAuthor\r\n";
+        let mut raw = compress_zlib(original);
+        raw.extend_from_slice(b".\r\n");
+
+        let mut block = GzipBlockDecoder::new();
+        assert!(block.push(&raw).unwrap());
+        let (compressed, data) = block.finish();
+        assert_eq!(data, original);
+        assert_eq!(compressed, raw.len() - 3);
+    }
+
+    #[test]
+    fn test_gzip_block_terminator_in_stream() {
+        let original = b"1\tSubject\tAuthor\r\n";
+        let mut with_terminator = original.to_vec();
+        with_terminator.extend_from_slice(b".\r\n");
+        let raw = compress_zlib(&with_terminator);
+
+        let mut block = GzipBlockDecoder::new();
+        assert!(block.push(&raw).unwrap());
+        let (_, data) = block.finish();
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn test_gzip_block_incremental_feed() {
+        let original = "12345\tA subject line\tposter@example.com\r\n".repeat(200);
+        let mut raw = compress_zlib(original.as_bytes());
+        raw.extend_from_slice(b".\r\n");
+
+        let mut block = GzipBlockDecoder::new();
+        let mut complete = false;
+        for chunk in raw.chunks(7) {
+            assert!(!complete, "Block completed before all data was fed");
+            complete = block.push(chunk).unwrap();
+        }
+        assert!(complete);
+        assert_eq!(block.finish().1, original.as_bytes());
+    }
+
+    #[test]
+    fn test_gzip_block_waits_for_trailing_terminator() {
+        let raw = compress_zlib(b"data\r\n");
+        let mut block = GzipBlockDecoder::new();
+        // Stream complete, but uncompressed terminator not yet received
+        assert!(!block.push(&raw).unwrap());
+        assert!(block.push(b".\r\n").unwrap());
+    }
+
+    #[test]
+    fn test_gzip_block_invalid_data() {
+        let mut block = GzipBlockDecoder::new();
+        assert!(block.push(b"not zlib at all\r\n.\r\n").is_err());
+    }
+
     // ========================================================================
     // Test Helpers
     // ========================================================================
@@ -1,31 +1,33 @@
 //! Connection management for NNTP client
 //!
-//! This module handles TCP/TLS connection establishment, socket tuning,
-//! and server greeting validation.
+//! This module handles TCP/TLS connection establishment and server greeting
+//! validation. Address resolution, socket tuning and connection racing live
+//! in the `dial` module.
 
-use crate::config::ServerConfig;
+use crate::commands;
+use crate::compression::GzipDetection;
+use crate::config::{ServerConfig, TlsPolicy};
 use crate::error::{NntpError, Result};
+use crate::latency::{self, LatencyStats};
+use crate::quirks::ServerQuirks;
+use crate::renumber::GroupWatermarks;
+use crate::response::ResponseCode;
+use std::collections::VecDeque;
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::io::BufReader;
-use tokio::net::TcpStream;
 use tokio::time::timeout;
 use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
 use tokio_rustls::rustls::client::danger::{
     HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
 };
 use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
-use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_rustls::rustls::{DigitallySignedStruct, SignatureScheme};
 use tracing::{debug, warn};
 
 use super::NntpClient;
-use super::state::ConnectionState;
-
-/// TCP connection timeout in seconds
-const TCP_CONNECT_TIMEOUT_SECS: u64 = 120;
-
-/// TLS handshake timeout in seconds
-const TLS_HANDSHAKE_TIMEOUT_SECS: u64 = 60;
+use super::state::{CompressionMode, ConnectionState};
+use super::stream::{Metered, NntpStream};
 
 /// BufReader capacity for high-throughput article downloads (256KB)
 const BUFREADER_CAPACITY: usize = 256 * 1024;
@@ -90,9 +92,11 @@ impl ServerCertVerifier for DangerousAcceptAnyCertificate {
 }
 
 impl NntpClient {
-    /// Connect to NNTP server with TLS
+    /// Connect to NNTP server
     ///
-    /// Establishes a secure connection to the NNTP server specified in the config.
+    /// Establishes a connection to the NNTP server specified in the config, using
+    /// implicit TLS if [`ServerConfig::tls`] is set and plain TCP otherwise. A plain
+    /// connection can be upgraded with [`starttls`](Self::starttls).
     /// Does not authenticate - call [`authenticate`](Self::authenticate) after connecting.
     ///
     /// # Errors
@@ -101,170 +105,84 @@ impl NntpClient {
     /// - [`NntpError::Io`] - TCP connection fails (DNS resolution, network unreachable, etc.)
     /// - [`NntpError::Tls`] - TLS handshake fails (invalid certificate, protocol error)
     /// - [`NntpError::Timeout`] - Connection or handshake times out
+    /// - [`NntpError::ConnectionLimit`] - Server refuses because too many connections are open
     /// - [`NntpError::Protocol`] - Server rejects the connection
+    /// - [`NntpError::EncryptionRequired`] - [`TlsPolicy::Required`] is set and
+    ///   the server does not offer STARTTLS or refuses it
+    ///
+    /// All resolved addresses are tried, alternating IPv6 and IPv4 and starting
+    /// a new attempt every 250ms until one connects (Happy Eyeballs, RFC 8305).
     ///
     /// # Timeouts
-    /// - TCP connection: `TCP_CONNECT_TIMEOUT_SECS` seconds
-    /// - TLS handshake: `TLS_HANDSHAKE_TIMEOUT_SECS` seconds
+    /// - TCP connection: [`TimeoutConfig::connect`](crate::TimeoutConfig::connect) per address
+    /// - TLS handshake: [`TimeoutConfig::tls_handshake`](crate::TimeoutConfig::tls_handshake)
     pub async fn connect(config: Arc<ServerConfig>) -> Result<Self> {
-        debug!("Connecting to NNTP server {}:{}", config.host, config.port);
-
-        // Create TCP connection with optimized socket buffers
-        let addr = format!("{}:{}", config.host, config.port);
-
-        // Parse the address to determine IP version
-        use std::net::ToSocketAddrs;
-        let socket_addr = addr
-            .to_socket_addrs()
-            .map_err(|e| {
-                NntpError::Io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Failed to resolve address: {}", e),
-                ))
-            })?
-            .next()
-            .ok_or_else(|| {
-                NntpError::Io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    "No address resolved",
-                ))
-            })?;
-
-        // Create socket using socket2 for buffer configuration
-        use socket2::{Domain, Protocol, Socket, Type};
-        let domain = if socket_addr.is_ipv4() {
-            Domain::IPV4
-        } else {
-            Domain::IPV6
-        };
+        debug!("Connecting to NNTP server {}", config.authority());
 
-        let socket =
-            Socket::new(domain, Type::STREAM, Some(Protocol::TCP)).map_err(NntpError::Io)?;
-
-        // Configure TCP socket for high-throughput downloads
-
-        // Set TCP_NODELAY for low-latency request/response pattern
-        socket.set_nodelay(true).map_err(NntpError::Io)?;
-
-        // Set large receive buffer for high-bandwidth downloads (4MB)
-        // This allows the OS to buffer more data, reducing the number of ACKs
-        // and improving throughput on high-latency connections
-        const RECV_BUFFER_SIZE: usize = 4 * 1024 * 1024; // 4MB
-        if let Err(e) = socket.set_recv_buffer_size(RECV_BUFFER_SIZE) {
-            warn!(
-                "Failed to set receive buffer size to {} bytes: {}",
-                RECV_BUFFER_SIZE, e
-            );
-        } else {
-            // Log the actual buffer size (OS may adjust)
-            match socket.recv_buffer_size() {
-                Ok(actual_size) => {
-                    debug!(
-                        "TCP receive buffer: requested {} bytes, actual {} bytes",
-                        RECV_BUFFER_SIZE, actual_size
-                    );
-                }
-                Err(e) => warn!("Failed to query receive buffer size: {}", e),
-            }
-        }
-
-        // Set large send buffer for command pipelining (1MB)
-        const SEND_BUFFER_SIZE: usize = 1024 * 1024; // 1MB
-        if let Err(e) = socket.set_send_buffer_size(SEND_BUFFER_SIZE) {
-            warn!(
-                "Failed to set send buffer size to {} bytes: {}",
-                SEND_BUFFER_SIZE, e
-            );
-        } else {
-            // Log the actual buffer size (OS may adjust)
-            match socket.send_buffer_size() {
-                Ok(actual_size) => {
-                    debug!(
-                        "TCP send buffer: requested {} bytes, actual {} bytes",
-                        SEND_BUFFER_SIZE, actual_size
-                    );
-                }
-                Err(e) => warn!("Failed to query send buffer size: {}", e),
-            }
-        }
+        // Resolve all addresses and race connection attempts (RFC 8305),
+        // each bounded by the configured timeout (120 seconds by default)
+        let tcp_stream = super::dial::connect_tcp(&config).await?;
+        let tcp_stream = Metered::new(tcp_stream, config.observer.clone());
 
-        // Connect with timeout (120 seconds for slow connections)
-        // socket2::Socket::connect() is blocking, so we need to spawn it in a blocking task
-        // NOTE: Connect BEFORE setting non-blocking mode
-        let socket_addr_for_connect = socket_addr;
-        let tcp_stream = timeout(
-            Duration::from_secs(TCP_CONNECT_TIMEOUT_SECS),
-            tokio::task::spawn_blocking(move || -> std::io::Result<std::net::TcpStream> {
-                // Connect while socket is still in blocking mode
-                socket.connect(&socket_addr_for_connect.into())?;
-                // Set non-blocking mode AFTER successful connect
-                socket.set_nonblocking(true)?;
-                Ok(socket.into())
-            }),
-        )
-        .await
-        .map_err(|_| NntpError::Timeout)?
-        .map_err(|e| NntpError::Io(std::io::Error::other(format!("Task join error: {}", e))))?
-        .map_err(NntpError::Io)?;
-
-        // Convert to tokio TcpStream
-        let tcp_stream = TcpStream::from_std(tcp_stream).map_err(NntpError::Io)?;
-
-        // Set up TLS - install default crypto provider if not already installed
-        use tokio_rustls::rustls::crypto::{CryptoProvider, ring};
-        let _ = CryptoProvider::install_default(ring::default_provider());
-
-        // Configure TLS based on security settings
-        let tls_config = if config.allow_insecure_tls {
-            // Insecure mode: accept any certificate (for self-signed certificates)
-            warn!("TLS certificate validation disabled - connection vulnerable to MITM attacks");
-            ClientConfig::builder()
-                .dangerous()
-                .with_custom_certificate_verifier(Arc::new(DangerousAcceptAnyCertificate))
-                .with_no_client_auth()
+        let transport = if config.tls {
+            NntpStream::Tls(Box::new(tls_handshake(&config, tcp_stream).await?))
         } else {
-            // Secure mode: validate certificates against trusted root CAs
-            let mut root_store = RootCertStore::empty();
-            root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
-
-            ClientConfig::builder()
-                .with_root_certificates(root_store)
-                .with_no_client_auth()
+            warn!("Using unencrypted connection to {}", config.host);
+            NntpStream::Plain(tcp_stream)
         };
 
-        let connector = TlsConnector::from(Arc::new(tls_config));
-        let server_name = ServerName::try_from(config.host.as_str())
-            .map_err(|e| NntpError::Tls(format!("Invalid domain: {}", e)))?
-            .to_owned();
-
-        // TLS handshake with timeout (60 seconds)
-        let tls_stream = timeout(
-            Duration::from_secs(TLS_HANDSHAKE_TIMEOUT_SECS),
-            connector.connect(server_name, tcp_stream),
-        )
-        .await
-        .map_err(|_| NntpError::Timeout)?
-        .map_err(|e| NntpError::Tls(format!("TLS handshake failed: {}", e)))?;
-
         // Use 256KB buffer for high-throughput article downloads
         // Default 8KB is too small and causes excessive syscalls
-        let stream = BufReader::with_capacity(BUFREADER_CAPACITY, tls_stream);
+        let stream = BufReader::with_capacity(BUFREADER_CAPACITY, transport);
+        let latency_window = config
+            .timeouts
+            .adaptive
+            .map_or(latency::DEFAULT_WINDOW, |adaptive| adaptive.window);
 
         let mut client = Self {
             stream,
             state: ConnectionState::Ready,
+            sasl_replay: None,
+            observer: config.observer.clone(),
+            parse_mode: config.parse_mode,
             config,
             current_group: None,
-            compression_mode: super::state::CompressionMode::None,
+            group_watermarks: GroupWatermarks::default(),
+            compression_mode: CompressionMode::None,
+            quirks: ServerQuirks::default(),
+            gzip_detection: GzipDetection::default(),
+            greeting_gzip_detection: GzipDetection::default(),
+            pending_verbs: VecDeque::new(),
+            response_verb: None,
+            last_activity: std::time::Instant::now(),
+            response_pending_since: None,
+            response_latency: LatencyStats::new(latency_window),
+            parse_warnings: Vec::new(),
             bytes_compressed: 0,
             bytes_decompressed: 0,
             is_broken: false,
+            connection_permit: None,
+            capabilities: None,
+            overview_format: None,
         };
 
         // Read server greeting
         let greeting = client.read_response().await?;
         debug!("Server greeting: {} {}", greeting.code, greeting.message);
+        client.greeting_gzip_detection = GzipDetection::for_greeting(&greeting.message);
+        let quirks = client
+            .config
+            .quirks
+            .clone()
+            .unwrap_or_else(|| ServerQuirks::from_greeting(&greeting.message));
+        if quirks.any() {
+            debug!("Applying server quirks: {:?}", quirks);
+        }
+        client.set_quirks(quirks);
 
+        if commands::is_connection_limit_response(greeting.code, &greeting.message) {
+            return Err(NntpError::ConnectionLimit(greeting.message));
+        }
         if !greeting.is_success() {
             return Err(NntpError::Protocol {
                 code: greeting.code,
@@ -272,13 +190,184 @@ impl NntpClient {
             });
         }
 
+        client.apply_tls_policy().await?;
         Ok(client)
     }
+
+    /// Upgrade a plain connection to TLS (RFC 4642)
+    ///
+    /// Sends STARTTLS and performs the TLS handshake in place on the existing
+    /// TCP connection, verifying the certificate as configured in
+    /// [`ServerConfig::allow_insecure_tls`]. Must be called before authenticating
+    /// and before enabling compression.
+    ///
+    /// Per RFC 4642 everything learned from the server before the upgrade is
+    /// discarded: the selected group is cleared, and the cached
+    /// [`capability_set`](Self::capability_set) is requested again since the
+    /// server may advertise different ones over TLS.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// let config = ServerConfig::plain("news.example.com", "user", "pass");
+    /// let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// client.starttls().await?;
+    /// assert!(client.is_tls());
+    /// client.authenticate().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::Tls`] - The connection is already encrypted or compressed, the
+    ///   server cannot start TLS (580), or the handshake fails
+    /// - [`NntpError::Protocol`] - Server does not offer STARTTLS here (e.g. 502 after authentication)
+    /// - [`NntpError::Timeout`] - Server did not respond or the handshake timed out
+    ///
+    /// A failed handshake leaves the connection unusable ([`is_broken`](Self::is_broken)).
+    pub async fn starttls(&mut self) -> Result<()> {
+        if self.is_tls() {
+            return Err(NntpError::Tls("TLS is already active".to_string()));
+        }
+        if self.compression_mode != CompressionMode::None {
+            return Err(NntpError::Tls(
+                "STARTTLS is not allowed after compression is enabled".to_string(),
+            ));
+        }
+
+        debug!("Upgrading connection to TLS (STARTTLS)");
+        self.send_command(commands::starttls()).await?;
+        let response = self.read_response().await?;
+
+        match response.response_code() {
+            ResponseCode::TlsContinue => {}
+            ResponseCode::TlsNotPossible => {
+                return Err(NntpError::Tls(format!(
+                    "Server cannot start TLS: {}",
+                    response.message
+                )));
+            }
+            _ => {
+                return Err(NntpError::Protocol {
+                    code: response.code,
+                    message: response.message,
+                });
+            }
+        }
+
+        // Anything buffered past the 382 line was sent before the handshake and
+        // must not be trusted (it could be injected by an attacker)
+        if !self.stream.buffer().is_empty() {
+            self.mark_broken();
+            return Err(NntpError::Tls(
+                "Server sent data before the TLS handshake".to_string(),
+            ));
+        }
+
+        let transport = std::mem::replace(self.stream.get_mut(), NntpStream::Closed);
+        let NntpStream::Plain(tcp_stream) = transport else {
+            self.mark_broken();
+            return Err(NntpError::Tls(
+                "Connection is not a plain TCP stream".to_string(),
+            ));
+        };
+
+        match tls_handshake(&self.config, tcp_stream).await {
+            Ok(tls_stream) => {
+                *self.stream.get_mut() = NntpStream::Tls(Box::new(tls_stream));
+            }
+            Err(e) => {
+                self.mark_broken();
+                self.state = ConnectionState::Closed;
+                return Err(e);
+            }
+        }
+
+        self.current_group = None;
+        self.capabilities = None;
+        debug!("TLS established via STARTTLS");
+        Ok(())
+    }
+
+    /// Upgrade a plain connection as asked by [`ServerConfig::tls_policy`]
+    ///
+    /// STARTTLS is only sent when CAPABILITIES advertises it (always with the
+    /// [`unreliable_capabilities`](ServerQuirks::unreliable_capabilities) quirk). A refused
+    /// upgrade leaves the plain session usable, so opportunistic upgrades
+    /// carry on without encryption; failed handshakes are always errors.
+    async fn apply_tls_policy(&mut self) -> Result<()> {
+        let policy = self.config.tls_policy;
+        if policy == TlsPolicy::Never || self.is_tls() {
+            return Ok(());
+        }
+
+        let offered = if self.quirks.unreliable_capabilities {
+            true
+        } else {
+            match self.capability_set().await {
+                Ok(caps) => caps.has("STARTTLS"),
+                // Servers without CAPABILITIES (RFC 977) cannot offer STARTTLS
+                Err(NntpError::Protocol { .. }) => false,
+                Err(e) => return Err(e),
+            }
+        };
+        if !offered {
+            let message = format!("{} does not offer STARTTLS", self.config.authority());
+            if policy == TlsPolicy::Required {
+                return Err(NntpError::EncryptionRequired(message));
+            }
+            warn!("{}, staying unencrypted", message);
+            return Ok(());
+        }
+
+        match self.starttls().await {
+            Err(NntpError::Tls(message) | NntpError::Protocol { message, .. })
+                if !self.is_broken() =>
+            {
+                if policy == TlsPolicy::Required {
+                    return Err(NntpError::EncryptionRequired(message));
+                }
+                warn!("STARTTLS refused ({}), staying unencrypted", message);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Check whether the connection is encrypted (implicit TLS or STARTTLS)
+    pub fn is_tls(&self) -> bool {
+        self.stream.get_ref().is_tls()
+    }
+}
+
+/// Perform the TLS handshake on a connected TCP stream
+///
+/// Shared by implicit TLS in [`NntpClient::connect`] and by STARTTLS.
+async fn tls_handshake(config: &ServerConfig, tcp_stream: Metered) -> Result<TlsStream<Metered>> {
+    let tls_config = super::tls::client_config(config)?;
+
+    let connector = TlsConnector::from(Arc::new(tls_config));
+    let server_name = crate::host::tls_server_name(&config.host)?;
+
+    // TLS handshake with the configured timeout (60 seconds by default)
+    timeout(
+        config.timeouts.tls_handshake,
+        connector.connect(server_name, tcp_stream),
+    )
+    .await
+    .map_err(|_| NntpError::Timeout)?
+    .map_err(|e| NntpError::Tls(format!("TLS handshake failed: {}", e)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
     use tokio_rustls::rustls::pki_types::{CertificateDer, UnixTime};
 
     // ========================================
@@ -308,14 +397,15 @@ mod tests {
     /// Test connection timeout values are reasonable
     #[test]
     fn test_timeout_constants() {
+        let timeouts = crate::config::TimeoutConfig::default();
         assert_eq!(
-            super::TCP_CONNECT_TIMEOUT_SECS,
-            120,
+            timeouts.connect,
+            Duration::from_secs(120),
             "TCP connection timeout should be 120 seconds"
         );
         assert_eq!(
-            super::TLS_HANDSHAKE_TIMEOUT_SECS,
-            60,
+            timeouts.tls_handshake,
+            Duration::from_secs(60),
             "TLS handshake timeout should be 60 seconds"
         );
     }
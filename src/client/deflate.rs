@@ -0,0 +1,314 @@
+//! RFC 8054 full-session compression
+//!
+//! After a 206 response to COMPRESS DEFLATE both directions of the connection
+//! carry a single raw DEFLATE stream each. [`DeflateStream`] wraps the
+//! transport, keeping the compressor and decompressor state for the lifetime
+//! of the connection. Every flush ends the pending output with a sync flush so
+//! the server can decode a command as soon as it is sent.
+
+use std::io::{self, IoSlice};
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Size of the buffer holding compressed data read from the transport
+const INPUT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Compressed output is written out before it grows past this many bytes
+const MAX_PENDING_OUTPUT: usize = 64 * 1024;
+
+/// Transport wrapped in persistent DEFLATE compression (RFC 8054)
+pub(super) struct DeflateStream<S> {
+    inner: S,
+    decompress: Decompress,
+    compress: Compress,
+    /// Compressed bytes read from the transport, `input[input_pos..input_len]` pending
+    input: Box<[u8]>,
+    input_pos: usize,
+    input_len: usize,
+    /// Compressed bytes not yet written, `output[output_pos..]` pending
+    output: Vec<u8>,
+    output_pos: usize,
+    /// Data was compressed since the last sync flush
+    needs_sync: bool,
+}
+
+impl<S> DeflateStream<S> {
+    /// Start compression on `inner`
+    ///
+    /// `buffered` is data already read from the transport after the 206
+    /// response; it is the start of the server's compressed stream.
+    pub(super) fn new(inner: S, buffered: &[u8]) -> Self {
+        let mut input = vec![0u8; INPUT_BUFFER_SIZE.max(buffered.len())].into_boxed_slice();
+        input[..buffered.len()].copy_from_slice(buffered);
+        Self {
+            inner,
+            decompress: Decompress::new(false),
+            compress: Compress::new(Compression::default(), false),
+            input,
+            input_pos: 0,
+            input_len: buffered.len(),
+            output: Vec::with_capacity(MAX_PENDING_OUTPUT),
+            output_pos: 0,
+            needs_sync: false,
+        }
+    }
+
+    /// The wrapped transport
+    pub(super) fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// The wrapped transport, mutably
+    pub(super) fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Returns `(compressed, decompressed)` byte counts received so far
+    pub(super) fn received_totals(&self) -> (u64, u64) {
+        (self.decompress.total_in(), self.decompress.total_out())
+    }
+
+    /// Compress `data` into the pending output
+    fn compress_into_output(&mut self, data: &[u8], flush: FlushCompress) -> io::Result<()> {
+        let mut consumed = 0;
+        loop {
+            self.output.reserve((data.len() - consumed).max(1024));
+            let before = self.compress.total_in();
+            self.compress
+                .compress_vec(&data[consumed..], &mut self.output, flush)
+                .map_err(|e| io::Error::other(format!("deflate compression failed: {}", e)))?;
+            consumed += (self.compress.total_in() - before) as usize;
+            // Output space left over means the compressor has nothing more to give
+            if consumed == data.len() && self.output.len() < self.output.capacity() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> DeflateStream<S> {
+    /// Write all pending compressed output to the transport
+    fn poll_write_output(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.output_pos < self.output.len() {
+            let n =
+                ready!(Pin::new(&mut self.inner).poll_write(cx, &self.output[self.output_pos..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.output_pos += n;
+        }
+        self.output.clear();
+        self.output_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeflateStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        loop {
+            if this.input_pos == this.input_len {
+                let mut read_buf = ReadBuf::new(&mut this.input);
+                ready!(Pin::new(&mut this.inner).poll_read(cx, &mut read_buf))?;
+                this.input_len = read_buf.filled().len();
+                this.input_pos = 0;
+                if this.input_len == 0 {
+                    // Transport closed
+                    return Poll::Ready(Ok(()));
+                }
+            }
+
+            let (in_before, out_before) = (this.decompress.total_in(), this.decompress.total_out());
+            let status = this
+                .decompress
+                .decompress(
+                    &this.input[this.input_pos..this.input_len],
+                    buf.initialize_unfilled(),
+                    FlushDecompress::None,
+                )
+                .map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("deflate decompression failed: {}", e),
+                    )
+                })?;
+            let consumed = (this.decompress.total_in() - in_before) as usize;
+            let produced = (this.decompress.total_out() - out_before) as usize;
+            this.input_pos += consumed;
+            buf.advance(produced);
+
+            if produced > 0 || status == Status::StreamEnd {
+                return Poll::Ready(Ok(()));
+            }
+            if consumed == 0 && this.input_pos < this.input_len {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "deflate stream made no progress",
+                )));
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeflateStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.output.len() >= MAX_PENDING_OUTPUT {
+            ready!(this.poll_write_output(cx))?;
+        }
+        this.compress_into_output(buf, FlushCompress::None)?;
+        this.needs_sync = true;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.output.len() >= MAX_PENDING_OUTPUT {
+            ready!(this.poll_write_output(cx))?;
+        }
+        // Compression only buffers, so every slice is taken at once
+        let mut written = 0;
+        for buf in bufs {
+            this.compress_into_output(buf, FlushCompress::None)?;
+            written += buf.len();
+        }
+        this.needs_sync = true;
+        Poll::Ready(Ok(written))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.needs_sync {
+            this.compress_into_output(&[], FlushCompress::Sync)?;
+            this.needs_sync = false;
+        }
+        ready!(this.poll_write_output(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::{DeflateDecoder, DeflateEncoder};
+    use std::io::Write;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn test_commands_are_sync_flushed() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut stream = DeflateStream::new(client, b"");
+        let mut decoder = DeflateDecoder::new(Vec::new());
+
+        for command in ["GROUP alt.test\r\n", "BODY <a@b>\r\n"] {
+            stream.write_all(command.as_bytes()).await.unwrap();
+            stream.flush().await.unwrap();
+
+            // Each command decodes completely without closing the stream
+            let mut wire = vec![0u8; 1024];
+            let n = server.read(&mut wire).await.unwrap();
+            decoder.write_all(&wire[..n]).unwrap();
+            decoder.flush().unwrap();
+            assert_eq!(decoder.get_ref().as_slice(), command.as_bytes());
+            decoder.get_mut().clear();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vectored_write_takes_every_slice() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut stream = DeflateStream::new(client, b"");
+        let commands = [
+            IoSlice::new(b"STAT <a@b>\r\n"),
+            IoSlice::new(b"STAT <c@d>\r\n"),
+        ];
+        assert_eq!(stream.write_vectored(&commands).await.unwrap(), 24);
+        stream.flush().await.unwrap();
+
+        let mut wire = vec![0u8; 1024];
+        let n = server.read(&mut wire).await.unwrap();
+        let mut decoder = DeflateDecoder::new(Vec::new());
+        decoder.write_all(&wire[..n]).unwrap();
+        decoder.flush().unwrap();
+        assert_eq!(
+            decoder.get_ref().as_slice(),
+            b"STAT <a@b>\r\nSTAT <c@d>\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reads_across_responses_and_buffered_start() {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"211 1 1 1 alt.test\r\n").unwrap();
+        encoder.flush().unwrap();
+        let first = std::mem::take(encoder.get_mut());
+        let big = "x".repeat(200_000);
+        encoder
+            .write_all(format!("222 1 <a@b>\r\n{}\r\n.\r\n", big).as_bytes())
+            .unwrap();
+        encoder.flush().unwrap();
+        let second = std::mem::take(encoder.get_mut());
+
+        let (client, mut server) = tokio::io::duplex(4096);
+        // Part of the first response was already buffered before compression started
+        let (buffered, rest) = first.split_at(first.len() / 2);
+        let stream = DeflateStream::new(client, buffered);
+        let rest = rest.to_vec();
+        tokio::spawn(async move {
+            server.write_all(&rest).await.unwrap();
+            server.write_all(&second).await.unwrap();
+        });
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "211 1 1 1 alt.test\r\n");
+        line.clear();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "222 1 <a@b>\r\n");
+        line.clear();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line.len(), big.len() + 2);
+
+        let (compressed, decompressed) = reader.get_ref().received_totals();
+        assert!(compressed < decompressed);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_input_is_an_error() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut stream = DeflateStream::new(client, b"");
+        server.write_all(&[0xff; 32]).await.unwrap();
+        let mut buf = [0u8; 64];
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
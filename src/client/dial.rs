@@ -0,0 +1,433 @@
+//! TCP connection establishment
+//!
+//! Resolves every address of the server through the configured [`Resolver`]
+//! and races connection attempts in the style of Happy Eyeballs (RFC 8305):
+//! address families are interleaved, a new attempt starts every
+//! [`CONNECTION_ATTEMPT_DELAY`] (or as soon as one fails), and the first
+//! socket to connect wins. Each socket is tuned with the
+//! configured [`SocketOptions`] and bound to the configured local address, if
+//! any, before connecting.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
+use tokio::net::{TcpSocket, TcpStream};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+use crate::config::{ServerConfig, SocketOptions};
+use crate::error::{NntpError, Result};
+use crate::host;
+use crate::resolver::{Resolver, default_resolver};
+
+/// Delay before starting the next attempt while earlier ones are pending (RFC 8305 §5)
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Resolve the configured host and connect to the first address that answers
+///
+/// IP literals are connected to directly. Hostnames go through the
+/// configured resolver, or the shared cached one. Each attempt is bounded by
+/// the connect timeout and uses the configured socket options and local
+/// address. A cached answer is dropped if no address could be reached.
+pub(super) async fn connect_tcp(config: &ServerConfig) -> Result<TcpStream> {
+    if let Some(addr) = host::literal_socket_addr(&config.host, config.port) {
+        return connect_any(
+            vec![addr],
+            config.timeouts.connect,
+            config.socket,
+            config.local_addr,
+        )
+        .await;
+    }
+
+    let resolver: &dyn Resolver = match &config.resolver {
+        Some(resolver) => resolver.as_ref(),
+        None => default_resolver(),
+    };
+    // Zones given as interface names (fe80::1%eth0) are resolved by the system
+    let name = host::unbracketed(&config.host);
+    let addrs = resolver.resolve(name, config.port).await?;
+    debug!("Resolved {} to {} address(es)", name, addrs.len());
+
+    let result = connect_any(
+        addrs,
+        config.timeouts.connect,
+        config.socket,
+        config.local_addr,
+    )
+    .await;
+    if result.is_err() {
+        resolver.invalidate(name, config.port);
+    }
+    result
+}
+
+/// Race connection attempts to `addrs`, returning the first established stream
+///
+/// With `local_addr`, addresses of the other family are skipped.
+pub(super) async fn connect_any(
+    mut addrs: Vec<SocketAddr>,
+    attempt_timeout: Duration,
+    options: SocketOptions,
+    local_addr: Option<IpAddr>,
+) -> Result<TcpStream> {
+    if let Some(local) = local_addr {
+        let resolved = addrs.len();
+        addrs.retain(|addr| addr.is_ipv4() == local.is_ipv4());
+        if addrs.is_empty() && resolved > 0 {
+            return Err(NntpError::Io(std::io::Error::new(
+                std::io::ErrorKind::AddrNotAvailable,
+                format!("No server address of the same family as {}", local),
+            )));
+        }
+    }
+
+    let mut pending = interleave_families(addrs).into_iter().peekable();
+    let mut attempts = JoinSet::new();
+    let mut last_error = None;
+
+    loop {
+        if let Some(addr) = pending.next() {
+            debug!("Connecting to {}", addr);
+            attempts.spawn(connect_addr(addr, attempt_timeout, options, local_addr));
+        }
+
+        // Wait for an attempt to finish, or give the next address a head start
+        let finished = if pending.peek().is_some() {
+            match timeout(CONNECTION_ATTEMPT_DELAY, attempts.join_next()).await {
+                Ok(finished) => finished,
+                Err(_) => continue,
+            }
+        } else {
+            attempts.join_next().await
+        };
+
+        match finished {
+            // Dropping the JoinSet aborts the attempts still in flight
+            Some(Ok(Ok(stream))) => return Ok(stream),
+            Some(Ok(Err(e))) => {
+                debug!("Connection attempt failed: {}", e);
+                last_error = Some(e);
+            }
+            Some(Err(e)) => {
+                last_error = Some(NntpError::Io(std::io::Error::other(format!(
+                    "Task join error: {}",
+                    e
+                ))));
+            }
+            None => break,
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        NntpError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "No address resolved",
+        ))
+    }))
+}
+
+/// Order addresses by alternating family, starting with the resolver's first choice
+///
+/// The relative order within each family is kept (RFC 8305 §4).
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(first) = addrs.first() else {
+        return addrs;
+    };
+    let prefer_v6 = first.is_ipv6();
+    let (preferred, other): (Vec<_>, Vec<_>) =
+        addrs.into_iter().partition(|a| a.is_ipv6() == prefer_v6);
+
+    let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+/// Connect a tuned socket to a single address
+async fn connect_addr(
+    addr: SocketAddr,
+    attempt_timeout: Duration,
+    options: SocketOptions,
+    local_addr: Option<IpAddr>,
+) -> Result<TcpStream> {
+    let socket = tuned_socket(addr, &options)?;
+    if let Some(local) = local_addr {
+        // Port 0 lets the OS pick an ephemeral port
+        socket
+            .bind(&SocketAddr::new(local, 0).into())
+            .map_err(|e| {
+                NntpError::Io(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to bind local address {}: {}", local, e),
+                ))
+            })?;
+    }
+    let socket = TcpSocket::from_std_stream(socket.into());
+    timeout(attempt_timeout, socket.connect(addr))
+        .await
+        .map_err(|_| NntpError::Timeout)?
+        .map_err(NntpError::Io)
+}
+
+/// Create a non-blocking socket configured with `options`
+fn tuned_socket(addr: SocketAddr, options: &SocketOptions) -> Result<Socket> {
+    let domain = if addr.is_ipv4() {
+        Domain::IPV4
+    } else {
+        Domain::IPV6
+    };
+    let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP)).map_err(NntpError::Io)?;
+
+    // TCP_NODELAY suits the request/response pattern of NNTP
+    socket.set_nodelay(options.nodelay).map_err(NntpError::Io)?;
+
+    if let Some(idle) = options.tcp_keepalive {
+        let keepalive = TcpKeepalive::new().with_time(idle);
+        if let Err(e) = socket.set_tcp_keepalive(&keepalive) {
+            warn!("Failed to enable TCP keepalive: {}", e);
+        }
+    }
+
+    // A large receive buffer lets the OS keep more data in flight, improving
+    // throughput on high-latency connections
+    if let Some(size) = options.recv_buffer_size {
+        match socket.set_recv_buffer_size(size) {
+            Err(e) => warn!("Failed to set receive buffer size to {} bytes: {}", size, e),
+            // Log the actual buffer size (OS may adjust)
+            Ok(()) => match socket.recv_buffer_size() {
+                Ok(actual) => debug!(
+                    "TCP receive buffer: requested {} bytes, actual {} bytes",
+                    size, actual
+                ),
+                Err(e) => warn!("Failed to query receive buffer size: {}", e),
+            },
+        }
+    }
+
+    // A large send buffer helps command pipelining
+    if let Some(size) = options.send_buffer_size {
+        match socket.set_send_buffer_size(size) {
+            Err(e) => warn!("Failed to set send buffer size to {} bytes: {}", size, e),
+            Ok(()) => match socket.send_buffer_size() {
+                Ok(actual) => debug!(
+                    "TCP send buffer: requested {} bytes, actual {} bytes",
+                    size, actual
+                ),
+                Err(e) => warn!("Failed to query send buffer size: {}", e),
+            },
+        }
+    }
+
+    socket.set_nonblocking(true).map_err(NntpError::Io)?;
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tokio::net::TcpListener;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_interleave_families() {
+        let addrs = vec![
+            addr("[2001:db8::1]:119"),
+            addr("[2001:db8::2]:119"),
+            addr("[2001:db8::3]:119"),
+            addr("192.0.2.1:119"),
+        ];
+        assert_eq!(
+            interleave_families(addrs),
+            vec![
+                addr("[2001:db8::1]:119"),
+                addr("192.0.2.1:119"),
+                addr("[2001:db8::2]:119"),
+                addr("[2001:db8::3]:119"),
+            ]
+        );
+
+        let addrs = vec![addr("192.0.2.1:119"), addr("[2001:db8::1]:119")];
+        assert_eq!(interleave_families(addrs.clone()), addrs);
+        assert!(interleave_families(Vec::new()).is_empty());
+    }
+
+    /// Address on loopback that refuses connections
+    async fn refused_addr() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_after_refused_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        let bad = refused_addr().await;
+
+        let stream = connect_any(
+            vec![bad, good],
+            Duration::from_secs(5),
+            SocketOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), good);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_address_does_not_block_others() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        // TEST-NET-1 is never routed: the attempt hangs or fails at once
+        let blackhole = addr("192.0.2.1:119");
+
+        let started = Instant::now();
+        let stream = connect_any(
+            vec![blackhole, good],
+            Duration::from_secs(30),
+            SocketOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), good);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_all_addresses_fail() {
+        let bad1 = refused_addr().await;
+        let bad2 = refused_addr().await;
+        let result = connect_any(
+            vec![bad1, bad2],
+            Duration::from_secs(5),
+            SocketOptions::default(),
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(NntpError::Io(_))));
+
+        let result = connect_any(
+            Vec::new(),
+            Duration::from_secs(5),
+            SocketOptions::default(),
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(NntpError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn test_resolves_hostname() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let config = ServerConfig::new("localhost", port, false, "user", "pass");
+        let stream = connect_tcp(&config).await;
+        // localhost may resolve to ::1 first; the IPv4 attempt must still win
+        assert!(stream.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_uses_configured_resolver() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        let resolver =
+            crate::resolver::StaticResolver::new().with_host("news.invalid", vec![good.ip()]);
+        let config = ServerConfig::new("news.invalid", good.port(), false, "user", "pass")
+            .with_resolver(std::sync::Arc::new(resolver));
+
+        let stream = connect_tcp(&config).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), good);
+    }
+
+    #[tokio::test]
+    async fn test_connects_to_ipv6_literal_without_lookup() {
+        let listener = TcpListener::bind("[::1]:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        for host in ["::1", "[::1]", "::1%0"] {
+            // A resolver that knows nothing proves the literal is not looked up
+            let resolver = crate::resolver::StaticResolver::new();
+            let config = ServerConfig::new(host, good.port(), false, "user", "pass")
+                .with_resolver(std::sync::Arc::new(resolver));
+            let stream = connect_tcp(&config).await.unwrap();
+            assert_eq!(stream.peer_addr().unwrap(), good);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_applies_socket_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        let options = SocketOptions {
+            recv_buffer_size: Some(64 * 1024),
+            send_buffer_size: None,
+            nodelay: false,
+            tcp_keepalive: Some(Duration::from_secs(30)),
+        };
+
+        let stream = connect_any(vec![good], Duration::from_secs(5), options, None)
+            .await
+            .unwrap();
+        let socket = socket2::SockRef::from(&stream);
+        assert!(!stream.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        // Linux doubles the requested size for bookkeeping overhead
+        let recv = socket.recv_buffer_size().unwrap();
+        assert!((64 * 1024..4 * 1024 * 1024).contains(&recv), "{}", recv);
+
+        let stream = connect_any(
+            vec![good],
+            Duration::from_secs(5),
+            SocketOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(stream.nodelay().unwrap());
+        assert!(!socket2::SockRef::from(&stream).keepalive().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_binds_local_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        let local: IpAddr = "127.0.0.2".parse().unwrap();
+
+        // The IPv6 address is skipped: it cannot be reached from an IPv4 source
+        let stream = connect_any(
+            vec![addr("[::1]:119"), good],
+            Duration::from_secs(5),
+            SocketOptions::default(),
+            Some(local),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stream.local_addr().unwrap().ip(), local);
+        let (_, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer.ip(), local);
+
+        let result = connect_any(
+            vec![addr("[::1]:119")],
+            Duration::from_secs(5),
+            SocketOptions::default(),
+            Some(local),
+        )
+        .await;
+        assert!(
+            matches!(result, Err(NntpError::Io(e)) if e.kind() == std::io::ErrorKind::AddrNotAvailable)
+        );
+    }
+}
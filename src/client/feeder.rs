@@ -0,0 +1,374 @@
+//! Streaming-mode feeding of articles to a peer
+//!
+//! [`NntpClient::check_many`] offers a known list of message-ids and leaves
+//! sending to the caller. A [`StreamFeeder`] takes articles from an async
+//! [`Stream`], keeps a window of CHECK and TAKETHIS commands in flight,
+//! sends each article the peer asks for as soon as it asks, and matches
+//! every response to its article by the message-id the peer echoes.
+
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::pin::{Pin, pin};
+use std::task::Poll;
+
+use bytes::Bytes;
+use futures_core::Stream;
+use tracing::debug;
+
+use super::NntpClient;
+use crate::article::Article;
+use crate::commands;
+use crate::error::{NntpError, Result};
+use crate::response::{NntpResponse, ResponseCode};
+
+/// CHECK and TAKETHIS commands in flight by default
+pub const DEFAULT_FEED_WINDOW: usize = 64;
+
+/// An article to feed, in wire form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedArticle {
+    /// Message-ID offered with CHECK and sent with TAKETHIS
+    pub message_id: String,
+    /// Article with CRLF line endings and dot-stuffing; the ".\r\n"
+    /// terminator is optional (see [`commands::validate_raw_article`])
+    pub data: Bytes,
+}
+
+impl FeedArticle {
+    /// Wrap an article already in wire form, such as one received from upstream
+    pub fn new(message_id: impl Into<String>, data: impl Into<Bytes>) -> Self {
+        Self {
+            message_id: message_id.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Serialize an [`Article`] for feeding
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the article cannot be serialized.
+    pub fn from_article(article: &Article) -> Result<Self> {
+        Ok(Self::new(
+            article.headers.message_id.clone(),
+            article.serialize_for_posting_bytes()?,
+        ))
+    }
+}
+
+/// Outcome of a feed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedReport {
+    /// Articles the peer received (239)
+    pub accepted: u64,
+    /// Articles the peer refused after receiving them (439)
+    pub rejected: u64,
+    /// Articles the peer already had or does not want (438)
+    pub not_wanted: u64,
+    /// Articles the peer asked to be offered again later (431)
+    pub deferred: u64,
+    /// Message-IDs of the deferred articles, in the order they were deferred
+    pub deferred_ids: Vec<String>,
+}
+
+impl FeedReport {
+    /// Number of articles that got an answer
+    pub fn offered(&self) -> u64 {
+        self.accepted + self.rejected + self.not_wanted + self.deferred
+    }
+}
+
+/// Feeds articles to a peer in streaming mode (RFC 4644)
+///
+/// Every article is offered with CHECK; a 238 answer sends it with TAKETHIS
+/// right away, without waiting for the other answers. Responses may arrive
+/// in any order, as long as the peer echoes the message-id; responses
+/// without one are matched to the oldest command they can answer.
+///
+/// **Note:** Call [`NntpClient::mode_stream`] before feeding.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nntp_rs::{FeedArticle, NntpClient, ServerConfig, StreamFeeder};
+/// # use futures_core::Stream;
+/// # use std::sync::Arc;
+/// # async fn example(
+/// #     articles: impl Stream<Item = FeedArticle>,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// # let config = ServerConfig::new("peer.example.com", 119, false, "", "");
+/// let mut client = NntpClient::connect(Arc::new(config)).await?;
+/// client.mode_stream().await?;
+///
+/// let report = StreamFeeder::new()
+///     .with_window(128)
+///     .feed(&mut client, articles)
+///     .await?;
+/// println!(
+///     "{} accepted, {} rejected, {} deferred",
+///     report.accepted, report.rejected, report.deferred
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamFeeder {
+    window: usize,
+}
+
+impl Default for StreamFeeder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which command a response answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Check,
+    Takethis,
+}
+
+/// A command awaiting its response
+struct InFlight {
+    phase: Phase,
+    message_id: String,
+    /// Article to send if the peer answers CHECK with 238
+    data: Bytes,
+}
+
+impl StreamFeeder {
+    /// Create a feeder with [`DEFAULT_FEED_WINDOW`]
+    pub fn new() -> Self {
+        Self {
+            window: DEFAULT_FEED_WINDOW,
+        }
+    }
+
+    /// Set how many CHECK and TAKETHIS commands may await a response at once (at least 1)
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Commands allowed in flight
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Feed every article of `articles` to the peer
+    ///
+    /// Responses are handled while the source has no article ready, so
+    /// articles the peer asked for are sent without waiting for the next one.
+    /// Returns once the source is exhausted and every command is answered.
+    ///
+    /// # Errors
+    ///
+    /// After a malformed article or an unexpected response, no more articles
+    /// are taken from the source, but every outstanding response is read
+    /// before the error is returned, so the connection stays in step.
+    /// - [`NntpError::InvalidArgument`] - An article is not valid wire
+    ///   form or has no usable message-id
+    /// - [`NntpError::InvalidResponse`] - A response echoes a message-id
+    ///   that is not in flight
+    /// - [`NntpError::Protocol`] - Peer answered with a code other than
+    ///   238, 431, 438, 239 or 439
+    /// - [`NntpError::Timeout`] - Peer did not respond in time
+    /// - Network I/O errors
+    pub async fn feed<S>(&self, client: &mut NntpClient, articles: S) -> Result<FeedReport>
+    where
+        S: Stream<Item = FeedArticle>,
+    {
+        let mut articles = pin!(articles);
+        let mut report = FeedReport::default();
+        let mut in_flight = VecDeque::with_capacity(self.window);
+        let mut batch = Vec::with_capacity(self.window);
+        let mut failure = None;
+        let mut exhausted = false;
+
+        loop {
+            // Stop offering once something went wrong, but drain what was sent
+            while failure.is_none() && !exhausted && in_flight.len() < self.window {
+                let awaiting = !in_flight.is_empty();
+                let article =
+                    match next_event(client, articles.as_mut(), &mut batch, awaiting).await? {
+                        Event::Article(article) => article,
+                        Event::Exhausted => {
+                            exhausted = true;
+                            break;
+                        }
+                        Event::Response => break,
+                    };
+                match prepare(article) {
+                    Ok(entry) => {
+                        batch.push(commands::check(&entry.message_id));
+                        in_flight.push_back(entry);
+                    }
+                    Err(e) => failure = Some(e),
+                }
+            }
+            if !batch.is_empty() {
+                client.send_commands(&batch).await?;
+                batch.clear();
+            }
+            if in_flight.is_empty() {
+                break;
+            }
+
+            let response = client.read_response().await?;
+            match settle(&mut in_flight, response, &mut report) {
+                Ok(Some(entry)) if failure.is_none() => {
+                    send_takethis(client, &entry).await?;
+                    in_flight.push_back(InFlight {
+                        phase: Phase::Takethis,
+                        data: Bytes::new(),
+                        ..entry
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+
+        debug!(
+            "Feed done: {} accepted, {} rejected, {} not wanted, {} deferred",
+            report.accepted, report.rejected, report.not_wanted, report.deferred
+        );
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(report),
+        }
+    }
+}
+
+/// What the feed waits for next
+enum Event {
+    Article(FeedArticle),
+    Exhausted,
+    /// A response arrived while the source had no article ready
+    Response,
+}
+
+/// Take the next article, or a response if one arrives first
+///
+/// Answers to what was offered must not wait for an idle source, so while
+/// `awaiting` responses the CHECKs in `batch` are sent before waiting.
+async fn next_event<S>(
+    client: &mut NntpClient,
+    mut articles: Pin<&mut S>,
+    batch: &mut Vec<String>,
+    awaiting: bool,
+) -> Result<Event>
+where
+    S: Stream<Item = FeedArticle>,
+{
+    let event = |next: Option<FeedArticle>| next.map_or(Event::Exhausted, Event::Article);
+    if let Poll::Ready(next) = poll_fn(|cx| Poll::Ready(articles.as_mut().poll_next(cx))).await {
+        return Ok(event(next));
+    }
+    if !awaiting {
+        return Ok(event(poll_fn(|cx| articles.as_mut().poll_next(cx)).await));
+    }
+
+    if !batch.is_empty() {
+        client.send_commands(batch).await?;
+        batch.clear();
+    }
+    let mut response = pin!(client.wait_for_response());
+    poll_fn(|cx| {
+        if let Poll::Ready(next) = articles.as_mut().poll_next(cx) {
+            return Poll::Ready(Ok(event(next)));
+        }
+        response
+            .as_mut()
+            .poll(cx)
+            .map(|ready| ready.map(|()| Event::Response))
+    })
+    .await
+}
+
+/// Validate an article and turn it into a CHECK awaiting its answer
+fn prepare(article: FeedArticle) -> Result<InFlight> {
+    if article.message_id.is_empty() || article.message_id.contains(char::is_whitespace) {
+        return Err(NntpError::InvalidArgument(format!(
+            "Cannot feed article with message-id {:?}",
+            article.message_id
+        )));
+    }
+    let body = commands::validate_raw_article(&article.data)?;
+    let data = article.data.slice_ref(body);
+    Ok(InFlight {
+        phase: Phase::Check,
+        message_id: article.message_id,
+        data,
+    })
+}
+
+/// Match a response to the command it answers and count it
+///
+/// Returns the article to send when the peer answered CHECK with 238.
+fn settle(
+    in_flight: &mut VecDeque<InFlight>,
+    response: NntpResponse,
+    report: &mut FeedReport,
+) -> Result<Option<InFlight>> {
+    let phase = match response.response_code() {
+        ResponseCode::CheckSend | ResponseCode::CheckLater | ResponseCode::CheckNotWanted => {
+            Phase::Check
+        }
+        ResponseCode::TakethisReceived | ResponseCode::TakethisRejected => Phase::Takethis,
+        _ => {
+            // Whatever this answered is settled, or the drain would never end
+            in_flight.pop_front();
+            return Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            });
+        }
+    };
+
+    let echoed = response
+        .message
+        .split_whitespace()
+        .next()
+        .filter(|token| token.starts_with('<'));
+    let position = in_flight
+        .iter()
+        .position(|entry| entry.phase == phase && echoed.is_none_or(|id| id == entry.message_id));
+    let Some(entry) = position.and_then(|position| in_flight.remove(position)) else {
+        in_flight.pop_front();
+        return Err(NntpError::InvalidResponse(format!(
+            "Streaming response {} for {} matches no article in flight",
+            response.code,
+            echoed.unwrap_or("no message-id")
+        )));
+    };
+
+    match response.response_code() {
+        ResponseCode::CheckSend => return Ok(Some(entry)),
+        ResponseCode::CheckLater => {
+            report.deferred += 1;
+            report.deferred_ids.push(entry.message_id);
+        }
+        ResponseCode::CheckNotWanted => report.not_wanted += 1,
+        ResponseCode::TakethisReceived => report.accepted += 1,
+        _ => report.rejected += 1,
+    }
+    Ok(None)
+}
+
+/// Send TAKETHIS with a validated article
+async fn send_takethis(client: &mut NntpClient, entry: &InFlight) -> Result<()> {
+    debug!(
+        "TAKETHIS: {} ({} bytes)",
+        entry.message_id,
+        entry.data.len()
+    );
+    // The 239/439 only comes after the upload, so it is not timed
+    let command = commands::takethis_line(&entry.message_id);
+    client.record_command(&command);
+    client.send_bytes(command.as_bytes()).await?;
+    client.send_raw_article(&entry.data).await
+}
@@ -1,6 +1,9 @@
-use crate::{NntpClient, Result, codes, commands, error::NntpError};
+use crate::{NntpClient, ResponseCode, Result, commands, error::NntpError};
 use tracing::debug;
 
+/// Maximum number of GROUP commands in flight during a snapshot
+const GROUP_SNAPSHOT_PIPELINE_DEPTH: usize = 32;
+
 impl NntpClient {
     /// Select a newsgroup
     ///
@@ -13,18 +16,32 @@ impl NntpClient {
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::InvalidResponse`] - Could not parse the server response
     pub async fn select_group(&mut self, newsgroup: &str) -> Result<commands::GroupInfo> {
+        let mut attempt = 0;
+        loop {
+            match self.select_group_once(newsgroup).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    pub(super) async fn select_group_once(
+        &mut self,
+        newsgroup: &str,
+    ) -> Result<commands::GroupInfo> {
         debug!("Selecting newsgroup: {}", newsgroup);
 
         let cmd = commands::group(newsgroup);
         self.send_command(&cmd).await?;
         let response = self.read_response().await?;
 
-        if response.code == codes::NO_SUCH_GROUP {
+        if response.response_code() == ResponseCode::NoSuchGroup {
             return Err(NntpError::NoSuchGroup(newsgroup.to_string()));
         }
 
         let info = commands::parse_group_response(response)?;
         self.current_group = Some(newsgroup.to_string());
+        self.observe_group(newsgroup, info);
 
         debug!(
             "Group {} selected: {} articles ({}-{})",
@@ -88,11 +105,11 @@ impl NntpClient {
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code == codes::NO_SUCH_GROUP {
+        if response.response_code() == ResponseCode::NoSuchGroup {
             return Err(NntpError::NoSuchGroup(newsgroup.to_string()));
         }
 
-        if response.code != codes::GROUP_SELECTED {
+        if response.response_code() != ResponseCode::GroupSelected {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -110,4 +127,98 @@ impl NntpClient {
         debug!("Found {} articles in group {}", articles.len(), newsgroup);
         Ok(articles)
     }
+
+    /// Fetch counts and watermarks for many groups at once
+    ///
+    /// Pipelines GROUP commands (up to 32 in flight) instead of waiting for each
+    /// response in turn, which is much faster than calling
+    /// [`select_group`](Self::select_group) in a loop on high-latency links.
+    ///
+    /// Groups that do not exist are reported with `info: None` rather than
+    /// failing the whole snapshot. Results are returned in the same order as
+    /// `newsgroups`.
+    ///
+    /// Note: GROUP changes the selected group. After the snapshot, the last
+    /// existing group in the list is selected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let snapshot = client
+    ///     .group_snapshot(&["alt.binaries.test", "comp.lang.rust"])
+    ///     .await?;
+    /// for group in &snapshot {
+    ///     match group.info {
+    ///         Some(info) => println!("{}: {} articles", group.name, info.count),
+    ///         None => println!("{}: no such group", group.name),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error for a group
+    /// - [`NntpError::InvalidResponse`] - Could not parse a server response
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    ///
+    /// All responses of a pipelined batch are read before an error is returned,
+    /// so the connection stays in sync.
+    pub async fn group_snapshot(
+        &mut self,
+        newsgroups: &[&str],
+    ) -> Result<Vec<commands::GroupSnapshot>> {
+        debug!("Taking snapshot of {} groups", newsgroups.len());
+
+        let mut snapshot = Vec::with_capacity(newsgroups.len());
+
+        for chunk in newsgroups.chunks(GROUP_SNAPSHOT_PIPELINE_DEPTH) {
+            // Phase 1: Send all GROUP commands in the chunk
+            for newsgroup in chunk {
+                self.send_command(&commands::group(newsgroup)).await?;
+            }
+
+            // Phase 2: Read every response so the pipeline stays in sync
+            let mut first_error = None;
+            for newsgroup in chunk {
+                let response = self.read_response().await?;
+
+                if response.response_code() == ResponseCode::NoSuchGroup {
+                    snapshot.push(commands::GroupSnapshot {
+                        name: (*newsgroup).to_string(),
+                        info: None,
+                    });
+                    continue;
+                }
+
+                match commands::parse_group_response(response) {
+                    Ok(info) => {
+                        self.current_group = Some((*newsgroup).to_string());
+                        self.observe_group(newsgroup, info);
+                        snapshot.push(commands::GroupSnapshot {
+                            name: (*newsgroup).to_string(),
+                            info: Some(info),
+                        });
+                    }
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+
+        debug!("Group snapshot complete: {} groups", snapshot.len());
+        Ok(snapshot)
+    }
 }
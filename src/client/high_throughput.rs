@@ -5,9 +5,12 @@
 //! - Command pipelining (reduces network round-trip latency)
 
 use super::NntpClient;
+use super::articles::stat_result;
+use crate::cancel::CancellationToken;
 use crate::commands;
 use crate::error::{NntpError, Result};
-use crate::response::codes;
+use crate::response::ResponseCode;
+use std::collections::VecDeque;
 use tracing::trace;
 
 impl NntpClient {
@@ -26,6 +29,19 @@ impl NntpClient {
     pub async fn fetch_article_binary(
         &mut self,
         id: &str,
+    ) -> Result<crate::response::NntpBinaryResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_article_binary_once(id).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_article_binary_once(
+        &mut self,
+        id: &str,
     ) -> Result<crate::response::NntpBinaryResponse> {
         trace!("Fetching article (binary): {}", id);
 
@@ -33,9 +49,10 @@ impl NntpClient {
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response_binary().await?;
 
-        if response.code == codes::NO_SUCH_ARTICLE_ID
-            || response.code == codes::NO_SUCH_ARTICLE_NUMBER
-        {
+        if matches!(
+            response.response_code(),
+            ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber
+        ) {
             return Err(NntpError::NoSuchArticle(id.to_string()));
         }
 
@@ -49,12 +66,53 @@ impl NntpClient {
         Ok(response)
     }
 
+    /// Fetch article as raw binary data, aborting when `token` is cancelled
+    ///
+    /// Like [`fetch_article_binary`](Self::fetch_article_binary), but returns
+    /// [`NntpError::Cancelled`] as soon as the token fires. If it fires before
+    /// the command is sent the client stays usable; otherwise the rest of the
+    /// response is still on the wire and the client is marked
+    /// [broken](Self::is_broken), so a pool discards it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::Cancelled`] - The token was cancelled
+    /// - Any error of [`fetch_article_binary`](Self::fetch_article_binary)
+    pub async fn fetch_article_binary_cancellable(
+        &mut self,
+        id: &str,
+        token: &CancellationToken,
+    ) -> Result<crate::response::NntpBinaryResponse> {
+        if token.is_cancelled() {
+            return Err(NntpError::Cancelled);
+        }
+        let outcome = token.run(self.fetch_article_binary(id)).await;
+        outcome.unwrap_or_else(|| {
+            self.mark_broken();
+            Err(NntpError::Cancelled)
+        })
+    }
+
     /// Fetch article body as raw binary data (optimized for high-throughput)
     ///
     /// Like `fetch_article_binary` but only fetches the body without headers.
     pub async fn fetch_body_binary(
         &mut self,
         id: &str,
+    ) -> Result<crate::response::NntpBinaryResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_body_binary_once(id).await {
+                Err(e) => self.recover(e, &mut attempt).await?,
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_body_binary_once(
+        &mut self,
+        id: &str,
     ) -> Result<crate::response::NntpBinaryResponse> {
         trace!("Fetching body (binary): {}", id);
 
@@ -113,63 +171,282 @@ impl NntpClient {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - [`NntpError::NoSuchArticle`] - One of the articles does not exist (pipeline aborts)
+    /// - [`NntpError::NoSuchArticle`] - One of the articles does not exist
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     /// - Any I/O error occurs during command transmission or response reading
     ///
-    /// Note: If an error occurs, the pipeline aborts and returns the error immediately.
-    /// Articles fetched before the error are discarded.
+    /// Note: If an article fails, the remaining responses are still read (so
+    /// the connection stays in sync) and the first error is returned. Articles
+    /// fetched successfully are discarded; use
+    /// [`fetch_articles_pipelined_sized`](Self::fetch_articles_pipelined_sized)
+    /// for per-article results.
     pub async fn fetch_articles_pipelined(
         &mut self,
         ids: &[&str],
         max_pipeline: usize,
     ) -> Result<Vec<crate::response::NntpBinaryResponse>> {
-        if ids.is_empty() {
-            return Ok(Vec::new());
+        let articles: Vec<(&str, u64)> = ids.iter().map(|id| (*id, 0)).collect();
+        self.fetch_articles_pipelined_sized(&articles, max_pipeline, u64::MAX)
+            .await?
+            .into_iter()
+            .collect()
+    }
+
+    /// Fetch multiple articles with pipelining, aborting when `token` is cancelled
+    ///
+    /// Like [`fetch_articles_pipelined`](Self::fetch_articles_pipelined), but
+    /// returns [`NntpError::Cancelled`] as soon as the token fires. Articles
+    /// already fetched are discarded. If the token fires before the first
+    /// command is sent the client stays usable; otherwise responses are still
+    /// outstanding and the client is marked [broken](Self::is_broken).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::Cancelled`] - The token was cancelled
+    /// - Any error of [`fetch_articles_pipelined`](Self::fetch_articles_pipelined)
+    pub async fn fetch_articles_pipelined_cancellable(
+        &mut self,
+        ids: &[&str],
+        max_pipeline: usize,
+        token: &CancellationToken,
+    ) -> Result<Vec<crate::response::NntpBinaryResponse>> {
+        if token.is_cancelled() {
+            return Err(NntpError::Cancelled);
         }
+        let outcome = token
+            .run(self.fetch_articles_pipelined(ids, max_pipeline))
+            .await;
+        outcome.unwrap_or_else(|| {
+            self.mark_broken();
+            Err(NntpError::Cancelled)
+        })
+    }
 
-        // Validate pipeline depth
-        let pipeline_depth = max_pipeline.max(1); // Minimum 1
-        let mut results = Vec::with_capacity(ids.len());
+    /// Fetch multiple articles with a pipeline window bounded by size
+    ///
+    /// Like [`fetch_articles_pipelined`](Self::fetch_articles_pipelined), but
+    /// the window slides: a new ARTICLE command is sent as soon as a response
+    /// has been read, as long as at most `max_pipeline` articles and at most
+    /// `max_inflight_bytes` of expected article data are outstanding. Large
+    /// articles therefore pipeline shallowly and small ones deeply, which keeps
+    /// the server's send queue bounded and avoids timeouts on big segments.
+    ///
+    /// # Arguments
+    ///
+    /// * `articles` - Message IDs with their expected sizes in bytes (from NZB
+    ///   segments or overview data; 0 if unknown)
+    /// * `max_pipeline` - Maximum number of articles in flight
+    /// * `max_inflight_bytes` - Maximum expected bytes in flight. One article is
+    ///   always allowed, even if it alone exceeds the limit.
+    ///
+    /// # Returns
+    ///
+    /// One result per article, in request order. Every response is read even
+    /// when some articles fail, so the connection stays usable.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// let mut client = NntpClient::connect(Arc::new(config)).await?;
+    ///
+    /// let articles = [("<part1@example.com>", 768_000), ("<part2@example.com>", 768_000)];
+    /// // At most 20 articles and 8 MiB outstanding
+    /// let results = client
+    ///     .fetch_articles_pipelined_sized(&articles, 20, 8 * 1024 * 1024)
+    ///     .await?;
+    /// for ((id, _), result) in articles.iter().zip(results) {
+    ///     match result {
+    ///         Ok(response) => println!("{}: {} bytes", id, response.data.len()),
+    ///         Err(e) => println!("{}: {}", id, e),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The outer result fails only if the connection itself fails (I/O error,
+    /// timeout, malformed response); responses may then still be outstanding,
+    /// so the client is marked [broken](Self::is_broken). Per-article results
+    /// can be:
+    /// - [`NntpError::NoSuchArticle`] - The article does not exist
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    pub async fn fetch_articles_pipelined_sized(
+        &mut self,
+        articles: &[(&str, u64)],
+        max_pipeline: usize,
+        max_inflight_bytes: u64,
+    ) -> Result<Vec<Result<crate::response::NntpBinaryResponse>>> {
+        let result = self
+            .fetch_articles_windowed(articles, max_pipeline, max_inflight_bytes)
+            .await;
+        if result.is_err() {
+            self.mark_broken();
+        }
+        result
+    }
+
+    /// The sliding window of [`fetch_articles_pipelined_sized`](Self::fetch_articles_pipelined_sized)
+    async fn fetch_articles_windowed(
+        &mut self,
+        articles: &[(&str, u64)],
+        max_pipeline: usize,
+        max_inflight_bytes: u64,
+    ) -> Result<Vec<Result<crate::response::NntpBinaryResponse>>> {
+        let max_pipeline = max_pipeline.max(1);
+        let mut results = Vec::with_capacity(articles.len());
+        let mut in_flight: VecDeque<usize> = VecDeque::with_capacity(max_pipeline);
+        let mut inflight_bytes: u64 = 0;
+        let mut next = 0;
 
         trace!(
-            "Fetching {} articles with pipeline depth {}",
-            ids.len(),
-            pipeline_depth
+            "Fetching {} articles with pipeline window of {} articles / {} bytes",
+            articles.len(),
+            max_pipeline,
+            max_inflight_bytes
         );
 
-        // Process articles in chunks based on pipeline depth
-        for chunk in ids.chunks(pipeline_depth) {
-            // Phase 1: Send all commands in the chunk without waiting for responses
-            for id in chunk {
-                let cmd = commands::article(id);
-                self.send_command(&cmd).await?;
+        let mut batch = Vec::with_capacity(max_pipeline);
+        while results.len() < articles.len() {
+            // Fill the window; the first article always fits so progress is guaranteed
+            while let Some(&(id, size)) = articles.get(next) {
+                let fits = in_flight.is_empty()
+                    || (in_flight.len() < max_pipeline
+                        && inflight_bytes.saturating_add(size) <= max_inflight_bytes);
+                if !fits {
+                    break;
+                }
+                batch.push(commands::article(id));
+                in_flight.push_back(next);
+                inflight_bytes = inflight_bytes.saturating_add(size);
+                next += 1;
+            }
+            // The whole window goes out in a single write
+            if !batch.is_empty() {
+                self.send_commands(&batch).await?;
+                batch.clear();
             }
 
-            // Phase 2: Read all responses in the same order as commands were sent
-            for id in chunk {
-                let response = self.read_multiline_response_binary().await?;
+            let Some(index) = in_flight.pop_front() else {
+                break;
+            };
+            let (id, size) = articles[index];
+            inflight_bytes = inflight_bytes.saturating_sub(size);
 
-                // Check for article not found errors
-                if response.code == codes::NO_SUCH_ARTICLE_ID
-                    || response.code == codes::NO_SUCH_ARTICLE_NUMBER
-                {
-                    return Err(NntpError::NoSuchArticle(id.to_string()));
-                }
+            let response = self.read_multiline_response_binary().await?;
+            results.push(article_result(id, response));
+        }
 
-                // Check for other protocol errors
-                if !response.is_success() {
-                    return Err(NntpError::Protocol {
-                        code: response.code,
-                        message: response.message,
-                    });
-                }
+        Ok(results)
+    }
 
-                results.push(response);
+    /// Check which articles exist, with pipelined STAT commands
+    ///
+    /// Keeps up to `pipeline_depth` STAT commands in flight, sending a new one
+    /// as each response arrives, like
+    /// [`fetch_articles_pipelined_sized`](Self::fetch_articles_pipelined_sized).
+    /// STAT transfers no article data, so deep pipelines are cheap; this is
+    /// the building block for checking an NZB's completeness on a server
+    /// before downloading it.
+    ///
+    /// # Returns
+    ///
+    /// One result per id, in request order. Every response is read even when
+    /// some articles are missing, so the connection stays usable.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, NntpError, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// let mut client = NntpClient::connect(Arc::new(config)).await?;
+    ///
+    /// let ids = ["<part1@example.com>", "<part2@example.com>"];
+    /// let results = client.stat_many(&ids, 50).await?;
+    /// let missing = results
+    ///     .iter()
+    ///     .filter(|r| matches!(r, Err(NntpError::NoSuchArticle(_))))
+    ///     .count();
+    /// println!("{} of {} articles missing", missing, ids.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The outer result fails only if the connection itself fails (I/O error,
+    /// timeout, malformed response). Per-article results can be:
+    /// - [`NntpError::NoSuchArticle`] - The article does not exist
+    /// - [`NntpError::NoGroupSelected`] - An article number was given without a selected group
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    pub async fn stat_many(
+        &mut self,
+        ids: &[&str],
+        pipeline_depth: usize,
+    ) -> Result<Vec<Result<commands::ArticleInfo>>> {
+        let pipeline_depth = pipeline_depth.max(1);
+        let mut results = Vec::with_capacity(ids.len());
+        let mut in_flight = 0;
+        let mut next = 0;
+
+        trace!(
+            "Checking {} articles with pipeline depth {}",
+            ids.len(),
+            pipeline_depth
+        );
+
+        let mut batch = Vec::with_capacity(pipeline_depth);
+        while results.len() < ids.len() {
+            // Top the window up once half of it is answered, so the small
+            // STAT lines go out in batches rather than one write per response
+            if in_flight <= pipeline_depth / 2 {
+                let refill = ids.len().min(next + pipeline_depth - in_flight);
+                batch.extend(ids[next..refill].iter().map(|id| commands::stat(id)));
+                in_flight += refill - next;
+                next = refill;
+            }
+            if !batch.is_empty() {
+                self.send_commands(&batch).await?;
+                batch.clear();
             }
+
+            let id = ids[results.len()];
+            let response = self.read_response().await?;
+            in_flight -= 1;
+            results.push(stat_result(id, response));
         }
 
         Ok(results)
     }
 }
+
+/// Map a pipelined ARTICLE response to a per-article result
+fn article_result(
+    id: &str,
+    response: crate::response::NntpBinaryResponse,
+) -> Result<crate::response::NntpBinaryResponse> {
+    if matches!(
+        response.response_code(),
+        ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber
+    ) {
+        return Err(NntpError::NoSuchArticle(id.to_string()));
+    }
+
+    if !response.is_success() {
+        return Err(NntpError::Protocol {
+            code: response.code,
+            message: response.message,
+        });
+    }
+
+    Ok(response)
+}
@@ -8,17 +8,18 @@
 //! - Timeout management
 //! - Connection error detection
 
+use super::compression::GzipBlockDecoder;
 use super::{CompressionMode, NntpClient};
 use crate::commands;
 use crate::error::{NntpError, Result};
 use crate::response::NntpResponse;
+use crate::warnings::ParseWarning;
+use std::io::IoSlice;
 use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use tokio::time::timeout;
 use tracing::trace;
 
-const SINGLE_LINE_TIMEOUT: Duration = Duration::from_secs(60);
-const MULTILINE_TIMEOUT: Duration = Duration::from_secs(180);
 const COMPRESSED_READ_BUFFER_SIZE: usize = 256 * 1024;
 const BINARY_DATA_INITIAL_CAPACITY: usize = 512 * 1024;
 /// Maximum size for a compressed block to prevent OOM from malicious/broken servers (64 MB)
@@ -33,18 +34,229 @@ fn strip_byte_stuffing(line: &str) -> &str {
     }
 }
 
+/// Check the dot-stuffing of a received body line (RFC 3977 Section 3.1.1)
+///
+/// `line` is as received, without the terminating "." line. A line starting
+/// with a single dot should never arrive: the server must double the dot.
+fn unstuffed_dot(line: &[u8], number: u64) -> Option<ParseWarning> {
+    match line {
+        [b'.', b'.', ..] => None,
+        [b'.', rest @ ..] => Some(ParseWarning::UnstuffedDotLine {
+            line: number,
+            terminator_like: rest.iter().all(u8::is_ascii_whitespace),
+        }),
+        _ => None,
+    }
+}
+
 impl NntpClient {
+    /// Report the first badly stuffed line of a body, failing it in strict mode
+    pub(super) fn check_dot_stuffing(&mut self, first: Option<ParseWarning>) -> Result<()> {
+        let Some(warning) = first else {
+            return Ok(());
+        };
+        let message = warning.to_string();
+        self.record_parse_warning(warning);
+        if self.config.strict_dot_stuffing {
+            return Err(NntpError::MalformedBody(message));
+        }
+        Ok(())
+    }
+
+    /// Check the dot-stuffing of a decompressed block, as for a plain body
+    pub(super) fn check_block_stuffing(&mut self, block: &[u8]) -> Result<()> {
+        let first = block
+            .split(|&b| b == b'\n')
+            .zip(1..)
+            .find_map(|(line, number)| unstuffed_dot(line, number));
+        self.check_dot_stuffing(first)
+    }
+
     /// Send a command to the server
     pub(super) async fn send_command(&mut self, command: &str) -> Result<()> {
-        trace!("Sending command: {}", command.trim());
+        self.record_command(command);
         self.stream.get_mut().write_all(command.as_bytes()).await?;
         self.stream.get_mut().flush().await?;
+        self.start_response_timer();
+        Ok(())
+    }
+
+    /// Send several commands in one vectored write and a single flush
+    ///
+    /// Used by pipelining so a window of commands costs one syscall (and one
+    /// TLS record) instead of one per command.
+    pub(super) async fn send_commands(&mut self, commands: &[String]) -> Result<()> {
+        for command in commands {
+            self.record_command(command);
+        }
+
+        let mut slices: Vec<IoSlice<'_>> = commands
+            .iter()
+            .map(|command| IoSlice::new(command.as_bytes()))
+            .collect();
+        let mut remaining = slices.as_mut_slice();
+        while !remaining.is_empty() {
+            let written = self.stream.get_mut().write_vectored(remaining).await?;
+            if written == 0 {
+                return Err(NntpError::Io(std::io::ErrorKind::WriteZero.into()));
+            }
+            IoSlice::advance_slices(&mut remaining, written);
+        }
+        self.stream.get_mut().flush().await?;
+        self.start_response_timer();
+        Ok(())
+    }
+
+    /// Start timing the response to the command just sent
+    ///
+    /// If an earlier response is still outstanding (pipelining), its timer
+    /// keeps running: only the first response in line reflects the server's
+    /// latency, the rest also wait for the responses ahead of them.
+    fn start_response_timer(&mut self) {
+        self.response_pending_since
+            .get_or_insert_with(std::time::Instant::now);
+    }
+
+    /// Adaptive limit for the next status line, if one applies
+    ///
+    /// Only the first response in line is limited, see
+    /// [`start_response_timer`](Self::start_response_timer).
+    fn status_line_limit(&self) -> Option<Duration> {
+        self.config.timeouts.adaptive?;
+        self.response_pending_since?;
+        Some(self.response_timeout())
+    }
+
+    /// Read a status line into `line_bytes`, within `limit` if given
+    async fn read_status_line(
+        &mut self,
+        line_bytes: &mut Vec<u8>,
+        limit: Option<Duration>,
+    ) -> Result<()> {
+        use tokio::io::AsyncBufReadExt;
+
+        // Responses come back in the order the commands were sent
+        self.response_verb = self.pending_verbs.pop_front();
+        let read = self.stream.read_until(b'\n', line_bytes);
+        match limit {
+            Some(limit) => {
+                timeout(limit, read)
+                    .await
+                    .map_err(|_| NntpError::Timeout)??;
+            }
+            None => {
+                read.await?;
+            }
+        }
+
+        if line_bytes.is_empty() {
+            return Err(NntpError::ConnectionClosed);
+        }
+        self.record_response_latency();
+        Ok(())
+    }
+
+    /// Log a command about to be sent and report it to the observer
+    pub(super) fn record_command(&mut self, command: &str) {
+        trace!("Sending command: {}", command.trim());
+        self.last_activity = std::time::Instant::now();
+        self.pending_verbs.push_back(
+            command
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        );
+        if let Some(observer) = &self.observer {
+            let authenticating = matches!(self.state, super::state::ConnectionState::InProgress);
+            observer.on_command(&crate::observer::redact_command(command, authenticating));
+        }
+    }
+
+    /// Send raw bytes to the server without modification
+    ///
+    /// Used for pre-serialized article data where byte fidelity matters.
+    pub(super) async fn send_bytes(&mut self, data: &[u8]) -> Result<()> {
+        trace!("Sending {} raw bytes", data.len());
+        self.last_activity = std::time::Instant::now();
+        self.stream.get_mut().write_all(data).await?;
+        self.stream.get_mut().flush().await?;
+        Ok(())
+    }
+
+    /// Stream an article in posting wire format, followed by the ".\r\n" terminator
+    ///
+    /// The article is serialized chunk by chunk (see
+    /// [`Article::posting_chunks`](crate::article::Article::posting_chunks)), so large
+    /// articles are never held in memory as a single string. If a limiter is given,
+    /// bandwidth is acquired for each chunk before it is written; `write_all` applies
+    /// socket backpressure between chunks.
+    pub(super) async fn send_article_chunks(
+        &mut self,
+        article: &crate::article::Article,
+        limiter: Option<&crate::ratelimit::BandwidthLimiter>,
+    ) -> Result<()> {
+        // Never ask the limiter for more than its burst size in one go
+        let max_acquire = match limiter {
+            Some(limiter) => limiter.config().await.1.max(1),
+            None => u64::MAX,
+        };
+
+        for chunk in article.posting_chunks(crate::article::DEFAULT_POSTING_CHUNK_SIZE) {
+            if let Some(limiter) = limiter {
+                let mut remaining = chunk.len() as u64;
+                while remaining > 0 {
+                    let amount = remaining.min(max_acquire);
+                    limiter.acquire(amount).await;
+                    remaining -= amount;
+                }
+            }
+            trace!("Sending article chunk of {} bytes", chunk.len());
+            self.stream.get_mut().write_all(&chunk).await?;
+        }
+
+        self.stream.get_mut().write_all(b".\r\n").await?;
+        self.stream.get_mut().flush().await?;
+        Ok(())
+    }
+
+    /// Send a raw article followed by the ".\r\n" terminator
+    ///
+    /// The raw counterpart of [`send_article_chunks`](Self::send_article_chunks)
+    /// for `post_raw()`, `ihave_raw()`, `takethis_raw()` and the feeder. The article must
+    /// already have passed [`commands::validate_raw_article`](crate::commands::validate_raw_article),
+    /// which strips any terminator it came with.
+    pub(super) async fn send_raw_article(&mut self, article: &[u8]) -> Result<()> {
+        trace!("Sending raw article of {} bytes", article.len());
+        self.last_activity = std::time::Instant::now();
+        self.stream.get_mut().write_all(article).await?;
+        self.stream.get_mut().write_all(b".\r\n").await?;
+        self.stream.get_mut().flush().await?;
+        Ok(())
+    }
+
+    /// Wait until response data arrives, without consuming it
+    ///
+    /// Cancel safe, unlike the reads, so it can race other work; the
+    /// response is then read as usual.
+    pub(super) async fn wait_for_response(&mut self) -> Result<()> {
+        use tokio::io::AsyncBufReadExt;
+
+        let buffered = timeout(self.config.timeouts.read, self.stream.fill_buf())
+            .await
+            .map_err(|_| NntpError::Timeout)??;
+        if buffered.is_empty() {
+            return Err(NntpError::ConnectionClosed);
+        }
         Ok(())
     }
 
     /// Read a single-line response
     pub(super) async fn read_response(&mut self) -> Result<NntpResponse> {
-        let result = self.read_response_with_timeout(SINGLE_LINE_TIMEOUT).await;
+        let limit = self
+            .status_line_limit()
+            .unwrap_or(self.config.timeouts.read);
+        let result = self.read_response_with_timeout(limit).await;
         // Mark connection as broken if we got invalid/garbage data
         if let Err(NntpError::InvalidResponse(_)) = &result {
             self.mark_broken();
@@ -57,22 +269,22 @@ impl NntpClient {
         &mut self,
         timeout_duration: Duration,
     ) -> Result<NntpResponse> {
-        use tokio::io::AsyncBufReadExt;
-
         let read_future = async {
             let mut line_bytes = Vec::with_capacity(512);
-            self.stream.read_until(b'\n', &mut line_bytes).await?;
-
-            if line_bytes.is_empty() {
-                return Err(NntpError::ConnectionClosed);
-            }
+            self.read_status_line(&mut line_bytes, None).await?;
 
             // Convert to string with lossy UTF-8 conversion
             let line = String::from_utf8_lossy(&line_bytes);
             let line = line.trim_end();
             trace!("Received: {}", line);
 
-            commands::parse_single_response(line)
+            let (code, message) = commands::parse_response_line_with(line, self.parse_mode)?;
+            self.observe_response(code, &message);
+            Ok(NntpResponse {
+                code,
+                message,
+                lines: vec![],
+            })
         };
 
         timeout(timeout_duration, read_future)
@@ -83,7 +295,7 @@ impl NntpClient {
     /// Read a multi-line response (ending with ".\r\n")
     pub(super) async fn read_multiline_response(&mut self) -> Result<NntpResponse> {
         let result = self
-            .read_multiline_response_with_timeout(MULTILINE_TIMEOUT)
+            .read_multiline_response_with_timeout(self.config.timeouts.multiline_read)
             .await;
         // Mark connection as broken if we got invalid/garbage data
         if let Err(NntpError::InvalidResponse(_)) = &result {
@@ -99,20 +311,19 @@ impl NntpClient {
     ) -> Result<NntpResponse> {
         use tokio::io::AsyncBufReadExt;
 
+        let status_limit = self.status_line_limit();
         let read_future = async {
             // Read first line (status)
             let mut first_line_bytes = Vec::with_capacity(512);
-            self.stream.read_until(b'\n', &mut first_line_bytes).await?;
-
-            if first_line_bytes.is_empty() {
-                return Err(NntpError::ConnectionClosed);
-            }
+            self.read_status_line(&mut first_line_bytes, status_limit)
+                .await?;
 
             let first_line = String::from_utf8_lossy(&first_line_bytes);
             let first_line = first_line.trim_end();
             trace!("Received: {}", first_line);
 
-            let (code, message) = commands::parse_response_line(first_line)?;
+            let (code, message) = commands::parse_response_line_with(first_line, self.parse_mode)?;
+            self.observe_response(code, &message);
 
             // If error response, no multi-line data follows
             if code >= 400 {
@@ -123,19 +334,12 @@ impl NntpClient {
                 });
             }
 
-            // For HeadersOnly compression mode, the server only compresses certain responses
-            // and indicates this with [COMPRESS=GZIP] in the status line
-            let response_is_compressed = self.compression_mode == CompressionMode::HeadersOnly
-                && message.contains("[COMPRESS=GZIP]");
-
-            if response_is_compressed {
-                let all_data = self.read_compressed_block().await?;
-
-                trace!("Read {} compressed bytes", all_data.len());
-
-                // Decompress the entire block
-                let decompressed = self.maybe_decompress(&all_data)?;
-                trace!("Decompressed to {} bytes", decompressed.len());
+            // For HeadersOnly compression mode, the server only compresses certain responses;
+            // how they are recognized depends on the server (see GzipDetection)
+            let mut consumed = Vec::new();
+            if self.response_is_compressed(&message, &mut consumed).await? {
+                let decompressed = self.read_compressed_block(&consumed).await?;
+                self.check_block_stuffing(&decompressed)?;
 
                 // Parse decompressed data into lines
                 let decompressed_str = String::from_utf8_lossy(&decompressed);
@@ -156,8 +360,11 @@ impl NntpClient {
             // Standard uncompressed or FullSession mode: Read line-by-line
             // Pre-allocate with conservative estimate (most multiline responses have 10-100 lines)
             let mut lines = Vec::with_capacity(64);
+            let mut bad_stuffing = None;
             loop {
                 let mut line_bytes = Vec::with_capacity(512);
+                // A byte read while sniffing for compression starts the first line
+                line_bytes.append(&mut consumed);
                 self.stream.read_until(b'\n', &mut line_bytes).await?;
 
                 if line_bytes.is_empty() {
@@ -172,9 +379,13 @@ impl NntpClient {
                     break;
                 }
 
+                if bad_stuffing.is_none() {
+                    bad_stuffing = unstuffed_dot(line.as_bytes(), lines.len() as u64 + 1);
+                }
                 // Handle byte-stuffing (lines starting with ".." become ".")
                 lines.push(strip_byte_stuffing(line).to_string());
             }
+            self.check_dot_stuffing(bad_stuffing)?;
 
             Ok(NntpResponse {
                 code,
@@ -188,11 +399,68 @@ impl NntpClient {
             .map_err(|_| NntpError::Timeout)?
     }
 
-    /// Read compressed data as binary until the uncompressed terminator (".\r\n" or ".\n")
-    async fn read_compressed_block(&mut self) -> Result<Vec<u8>> {
+    /// Decide whether the multi-line response that follows is compressed
+    ///
+    /// Only applies in HeadersOnly mode. The status line alone decides for
+    /// streamed responses (BODY, LISTGROUP, LIST ACTIVE), which are never
+    /// sniffed for zlib.
+    pub(super) fn response_is_marked_compressed(&self, message: &str) -> bool {
+        self.compression_mode == CompressionMode::HeadersOnly
+            && self.gzip_detection.is_compressed(
+                self.response_verb.as_deref().unwrap_or_default(),
+                message,
+                None,
+            )
+    }
+
+    /// Decide whether the multi-line response that follows is compressed,
+    /// sniffing its data if the detection strategy asks for it
+    ///
+    /// Only applies in HeadersOnly mode. The first two bytes of the data are
+    /// peeked at; if only one is buffered yet and it may start a zlib header,
+    /// it is consumed and appended to `consumed`, where the caller picks it
+    /// up as the start of the response.
+    pub(super) async fn response_is_compressed(
+        &mut self,
+        message: &str,
+        consumed: &mut Vec<u8>,
+    ) -> Result<bool> {
+        use tokio::io::AsyncBufReadExt;
+
+        let verb = self.response_verb.as_deref().unwrap_or_default();
+        if self.compression_mode != CompressionMode::HeadersOnly
+            || !self.gzip_detection.needs_data_prefix(verb)
+        {
+            return Ok(self.response_is_marked_compressed(message));
+        }
+
+        let buffered = self.stream.fill_buf().await?;
+        if let [first] = *buffered
+            && crate::compression::is_zlib_cmf(first)
+        {
+            self.stream.consume(1);
+            consumed.push(first);
+            let second = self.stream.fill_buf().await?.first().copied();
+            let prefix: Vec<u8> = std::iter::once(first).chain(second).collect();
+            return Ok(self
+                .gzip_detection
+                .is_compressed(verb, message, Some(&prefix)));
+        }
+        Ok(self
+            .gzip_detection
+            .is_compressed(verb, message, Some(buffered)))
+    }
+
+    /// Read and decompress a compressed block, returning the data without terminator
+    ///
+    /// Handles both a ".\r\n" terminator following the zlib stream and one
+    /// embedded at the end of the compressed data. `consumed` is the start of
+    /// the block, if any of it was already read.
+    pub(super) async fn read_compressed_block(&mut self, consumed: &[u8]) -> Result<Vec<u8>> {
         use tokio::io::AsyncReadExt;
 
-        let mut all_data = Vec::new();
+        let mut block = GzipBlockDecoder::new();
+        block.push(consumed)?;
         let mut buffer = vec![0u8; COMPRESSED_READ_BUFFER_SIZE];
 
         loop {
@@ -201,25 +469,23 @@ impl NntpClient {
                 return Err(NntpError::ConnectionClosed);
             }
 
-            all_data.extend_from_slice(&buffer[..n]);
+            let complete = block.push(&buffer[..n])?;
 
-            if all_data.len() > MAX_COMPRESSED_BLOCK_SIZE {
+            if block.raw_len() > MAX_COMPRESSED_BLOCK_SIZE {
                 return Err(NntpError::InvalidResponse(format!(
                     "Compressed block exceeds maximum size of {} bytes",
                     MAX_COMPRESSED_BLOCK_SIZE
                 )));
             }
 
-            if all_data.ends_with(b".\r\n") {
-                all_data.truncate(all_data.len() - 3);
-                break;
-            } else if all_data.ends_with(b".\n") {
-                all_data.truncate(all_data.len() - 2);
+            if complete {
                 break;
             }
         }
 
-        Ok(all_data)
+        let (compressed, decompressed) = block.finish();
+        self.record_decompressed(compressed, decompressed.len());
+        Ok(decompressed)
     }
 
     /// Read a multi-line response as raw binary data (optimized for articles)
@@ -232,7 +498,7 @@ impl NntpClient {
     pub(super) async fn read_multiline_response_binary(
         &mut self,
     ) -> Result<crate::response::NntpBinaryResponse> {
-        self.read_multiline_response_binary_with_timeout(MULTILINE_TIMEOUT)
+        self.read_multiline_response_binary_with_timeout(self.config.timeouts.multiline_read)
             .await
     }
 
@@ -243,20 +509,19 @@ impl NntpClient {
     ) -> Result<crate::response::NntpBinaryResponse> {
         use tokio::io::AsyncBufReadExt;
 
+        let status_limit = self.status_line_limit();
         let read_future = async {
             // Read first line (status) - this is always text
             let mut first_line_bytes = Vec::with_capacity(256);
-            self.stream.read_until(b'\n', &mut first_line_bytes).await?;
-
-            if first_line_bytes.is_empty() {
-                return Err(NntpError::ConnectionClosed);
-            }
+            self.read_status_line(&mut first_line_bytes, status_limit)
+                .await?;
 
             let first_line = String::from_utf8_lossy(&first_line_bytes);
             let first_line = first_line.trim_end();
             trace!("Received: {}", first_line);
 
-            let (code, message) = commands::parse_response_line(first_line)?;
+            let (code, message) = commands::parse_response_line_with(first_line, self.parse_mode)?;
+            self.observe_response(code, &message);
 
             // If error response, no multi-line data follows
             if code >= 400 {
@@ -268,12 +533,10 @@ impl NntpClient {
             }
 
             // For compressed responses, read and decompress the block
-            let response_is_compressed = self.compression_mode == CompressionMode::HeadersOnly
-                && message.contains("[COMPRESS=GZIP]");
-
-            if response_is_compressed {
-                let all_data = self.read_compressed_block().await?;
-                let decompressed = self.maybe_decompress(&all_data)?;
+            let mut consumed = Vec::new();
+            if self.response_is_compressed(&message, &mut consumed).await? {
+                let decompressed = self.read_compressed_block(&consumed).await?;
+                self.check_block_stuffing(&decompressed)?;
                 return Ok(crate::response::NntpBinaryResponse {
                     code,
                     message,
@@ -285,8 +548,12 @@ impl NntpClient {
             // but collect bytes directly instead of creating strings
             let mut data = Vec::with_capacity(BINARY_DATA_INITIAL_CAPACITY);
 
+            // A byte read while sniffing for compression starts the first line
+            let mut line_bytes = consumed;
+            line_bytes.reserve(512);
+            let mut line_number = 0;
+            let mut bad_stuffing = None;
             loop {
-                let mut line_bytes = Vec::with_capacity(512);
                 self.stream.read_until(b'\n', &mut line_bytes).await?;
 
                 if line_bytes.is_empty() {
@@ -298,23 +565,21 @@ impl NntpClient {
                     break;
                 }
 
-                // Strip trailing \r\n (NNTP line terminator, not part of payload)
-                let content_end = if line_bytes.ends_with(b"\r\n") {
-                    line_bytes.len() - 2
-                } else if line_bytes.ends_with(b"\n") {
-                    line_bytes.len() - 1
-                } else {
-                    line_bytes.len()
-                };
-                let line_content = &line_bytes[..content_end];
+                line_number += 1;
+                if bad_stuffing.is_none() {
+                    bad_stuffing = unstuffed_dot(&line_bytes, line_number);
+                }
 
-                // Handle dot-stuffing: lines starting with ".." become "."
-                if line_content.starts_with(b"..") {
-                    data.extend_from_slice(&line_content[1..]);
+                // Keep line terminators so the data retains its line structure
+                // (header/body split, yEnc lines); only undo dot-stuffing
+                if line_bytes.starts_with(b"..") {
+                    data.extend_from_slice(&line_bytes[1..]);
                 } else {
-                    data.extend_from_slice(line_content);
+                    data.extend_from_slice(&line_bytes);
                 }
+                line_bytes.clear();
             }
+            self.check_dot_stuffing(bad_stuffing)?;
 
             Ok(crate::response::NntpBinaryResponse {
                 code,
@@ -340,26 +605,48 @@ impl NntpClient {
 mod tests {
     use super::*;
 
-    /// Test that SINGLE_LINE_TIMEOUT constant is reasonable (60s)
+    #[test]
+    fn test_unstuffed_dot() {
+        assert_eq!(unstuffed_dot(b"..stuffed\r\n", 1), None);
+        assert_eq!(unstuffed_dot(b"plain\r\n", 1), None);
+        assert_eq!(
+            unstuffed_dot(b".missing\r\n", 3),
+            Some(ParseWarning::UnstuffedDotLine {
+                line: 3,
+                terminator_like: false
+            })
+        );
+        assert_eq!(
+            unstuffed_dot(b". \r\n", 4),
+            Some(ParseWarning::UnstuffedDotLine {
+                line: 4,
+                terminator_like: true
+            })
+        );
+    }
+
+    /// Test that the default single-line read timeout is reasonable (60s)
     #[test]
     fn test_single_line_timeout_value() {
-        assert_eq!(SINGLE_LINE_TIMEOUT, Duration::from_secs(60));
+        let read = crate::config::TimeoutConfig::default().read;
+        assert_eq!(read, Duration::from_secs(60));
         assert!(
-            SINGLE_LINE_TIMEOUT >= Duration::from_secs(30),
+            read >= Duration::from_secs(30),
             "Single line timeout should allow for slow networks"
         );
     }
 
-    /// Test that MULTILINE_TIMEOUT constant is reasonable (180s)
+    /// Test that the default multiline read timeout is reasonable (180s)
     #[test]
     fn test_multiline_timeout_value() {
-        assert_eq!(MULTILINE_TIMEOUT, Duration::from_secs(180));
+        let timeouts = crate::config::TimeoutConfig::default();
+        assert_eq!(timeouts.multiline_read, Duration::from_secs(180));
         assert!(
-            MULTILINE_TIMEOUT >= Duration::from_secs(120),
+            timeouts.multiline_read >= Duration::from_secs(120),
             "Multiline timeout should allow for large article bodies"
         );
         assert!(
-            MULTILINE_TIMEOUT > SINGLE_LINE_TIMEOUT,
+            timeouts.multiline_read > timeouts.read,
             "Multiline timeout should be longer than single line"
         );
     }
@@ -522,48 +809,33 @@ mod tests {
     /// Test binary dot-stuffing removal for read_multiline_response_binary
     ///
     /// Binary mode must also handle dot-stuffing but operates on bytes, not strings.
-    /// After stripping line terminators, dot-stuffing is handled on the content.
+    /// Line terminators are kept; only the stuffed leading dot is removed.
     #[test]
     fn test_binary_dot_stuffing() {
-        // Helper to simulate the binary reader logic: strip \r\n then handle dot-stuffing
+        // Helper to simulate the binary reader logic: keep the line, undo dot-stuffing
         fn process_line(line_bytes: &[u8]) -> Vec<u8> {
-            // Strip trailing \r\n
-            let content_end = if line_bytes.ends_with(b"\r\n") {
-                line_bytes.len() - 2
-            } else if line_bytes.ends_with(b"\n") {
-                line_bytes.len() - 1
-            } else {
-                line_bytes.len()
-            };
-            let line_content = &line_bytes[..content_end];
-
-            // Handle dot-stuffing
-            if line_content.starts_with(b"..") {
-                line_content[1..].to_vec()
+            if line_bytes.starts_with(b"..") {
+                line_bytes[1..].to_vec()
             } else {
-                line_content.to_vec()
+                line_bytes.to_vec()
             }
         }
 
-        // Line starting with ".." - should strip first dot AND \r\n
-        let line_bytes = b"..Binary data\r\n";
-        let processed = process_line(line_bytes);
-        assert_eq!(processed, b".Binary data");
-
-        // Normal line - strip \r\n only
-        let line_bytes = b"Binary data\r\n";
-        let processed = process_line(line_bytes);
-        assert_eq!(processed, b"Binary data");
-
-        // Three dots - strip one dot and \r\n
-        let line_bytes = b"...\r\n";
-        let processed = process_line(line_bytes);
-        assert_eq!(processed, b"..");
-
-        // LF-only line ending
-        let line_bytes = b"Data line\n";
-        let processed = process_line(line_bytes);
-        assert_eq!(processed, b"Data line");
+        // Line starting with ".." - should strip only the first dot
+        let processed = process_line(b"..Binary data\r\n");
+        assert_eq!(processed, b".Binary data\r\n");
+
+        // Normal line - unchanged, CRLF preserved
+        let processed = process_line(b"Binary data\r\n");
+        assert_eq!(processed, b"Binary data\r\n");
+
+        // Three dots - strip one dot
+        let processed = process_line(b"...\r\n");
+        assert_eq!(processed, b"..\r\n");
+
+        // LF-only line ending is preserved as received
+        let processed = process_line(b"Data line\n");
+        assert_eq!(processed, b"Data line\n");
     }
 
     /// Test binary terminator detection for optimized article fetching
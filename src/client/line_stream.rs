@@ -0,0 +1,133 @@
+//! Line-oriented parsing of streamed multi-line responses
+//!
+//! [`ListgroupStream`](super::ListgroupStream) and
+//! [`ListActiveStream`](super::ListActiveStream) both read a data block in
+//! chunks, split it into lines and parse each line into an item. This module
+//! holds that shared part; each stream supplies the line parser.
+
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::NntpClient;
+use super::body_stream::BodyStream;
+use crate::error::{NntpError, Result};
+
+/// Bytes read from the connection at a time
+const READ_CHUNK: usize = 16 * 1024;
+
+/// Parser of one line, given the client the response is read from
+pub(super) type ParseLine<T> = fn(&mut NntpClient, &str) -> Option<T>;
+
+/// Items of a multi-line response, parsed line by line as they arrive
+///
+/// Lines the parser rejects are skipped. Ends at the terminating "." line,
+/// or after the first error.
+pub(super) struct LineStream<'a, T> {
+    data: BodyStream<'a>,
+    /// Parsed items not handed out yet
+    ready: VecDeque<T>,
+    /// Start of a line whose end has not arrived yet
+    partial: Vec<u8>,
+    parse: ParseLine<T>,
+    /// Command named in the error for an overlong line
+    command: &'static str,
+    /// Longest line accepted
+    max_line: usize,
+    finished: bool,
+}
+
+impl<'a, T> LineStream<'a, T> {
+    pub(super) fn new(
+        data: BodyStream<'a>,
+        command: &'static str,
+        max_line: usize,
+        parse: ParseLine<T>,
+    ) -> Self {
+        Self {
+            data,
+            ready: VecDeque::new(),
+            partial: Vec::new(),
+            parse,
+            command,
+            max_line,
+            finished: false,
+        }
+    }
+
+    /// Parse the lines still to come with `parse` instead
+    pub(super) fn set_parser(&mut self, parse: ParseLine<T>) {
+        self.parse = parse;
+    }
+
+    /// Queue the items of the complete lines in `data`
+    fn parse_chunk(&mut self, data: &[u8]) -> Result<()> {
+        let mut rest = data;
+        while let Some(end) = memchr::memchr(b'\n', rest) {
+            self.partial.extend_from_slice(&rest[..end]);
+            rest = &rest[end + 1..];
+            if let Ok(line) = std::str::from_utf8(&self.partial)
+                && let Some(item) = (self.parse)(self.data.client_mut(), line)
+            {
+                self.ready.push_back(item);
+            }
+            self.partial.clear();
+        }
+        self.partial.extend_from_slice(rest);
+        if self.partial.len() > self.max_line {
+            return Err(NntpError::InvalidResponse(format!(
+                "{} line longer than {} bytes",
+                self.command, self.max_line
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Unpin> Stream for LineStream<'_, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            if let Some(item) = this.ready.pop_front() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            let mut buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.data).poll_read(cx, &mut buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(read_error(e))));
+                }
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    // The last line always ends in CRLF before the terminator
+                    this.finished = true;
+                }
+                Poll::Ready(Ok(())) => {
+                    if let Err(e) = this.parse_chunk(buf.filled()) {
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Error for a failed read of a streamed listing
+fn read_error(e: io::Error) -> NntpError {
+    match e.kind() {
+        io::ErrorKind::TimedOut => NntpError::Timeout,
+        _ => NntpError::Io(e),
+    }
+}
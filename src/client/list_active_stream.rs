@@ -0,0 +1,131 @@
+//! Streaming LIST ACTIVE
+//!
+//! On a full-feed server, [`NntpClient::list_active`] with `*` buffers
+//! hundreds of thousands of lines before parsing any of them.
+//! [`NntpClient::list_active_stream`] parses groups as they arrive and hands
+//! them out one at a time through a [`Stream`].
+
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tracing::debug;
+
+use super::NntpClient;
+use super::line_stream::LineStream;
+use crate::commands::{self, ActiveGroup};
+use crate::error::{NntpError, Result};
+use crate::response::ResponseCode;
+use crate::wildmat;
+
+/// Longest line accepted in a LIST ACTIVE response
+const MAX_LINE: usize = 4096;
+
+/// Groups of a LIST ACTIVE response, parsed as they arrive
+///
+/// Created by [`NntpClient::list_active_stream`]. Yields the groups in the
+/// order the server sends them and ends at the terminating "." line. Lines
+/// with fewer than four fields are skipped, as by
+/// [`NntpClient::list_active`].
+///
+/// Besides implementing [`Stream`], it has an inherent async
+/// [`next`](Self::next). Like [`BodyStream`](super::BodyStream), it
+/// borrows the client, and dropping it before the end leaves the client
+/// [broken](NntpClient::is_broken).
+pub struct ListActiveStream<'a> {
+    lines: LineStream<'a, ActiveGroup>,
+}
+
+impl NntpClient {
+    /// List active newsgroups as a stream (RFC 3977 Section 7.6.3)
+    ///
+    /// Like [`list_active`](Self::list_active), but returns once the status
+    /// line is read; groups are then parsed from the connection while the
+    /// returned [`ListActiveStream`] is polled, so memory use does not grow
+    /// with the number of groups.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let mut groups = client.list_active_stream("*").await?.record_watermarks();
+    /// while let Some(group) = groups.next().await {
+    ///     let group = group?;
+    ///     if group.status == "y" {
+    ///         println!("{} ({}-{})", group.name, group.low, group.high);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - `wildmat` is not a valid wildmat (nothing is sent)
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    ///
+    /// Errors while reading the groups are yielded by the stream, which
+    /// ends after them.
+    pub async fn list_active_stream(&mut self, wildmat: &str) -> Result<ListActiveStream<'_>> {
+        debug!("Streaming active groups matching: {}", wildmat);
+
+        wildmat::validate(wildmat)?;
+        let cmd = commands::list_active(wildmat);
+        self.send_command(&cmd).await?;
+        let response = self.read_response().await?;
+
+        if response.response_code() != ResponseCode::ListInformationFollows {
+            return Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            });
+        }
+
+        let data = self.stream_data_block(response).await?;
+        Ok(ListActiveStream {
+            lines: LineStream::new(data, "LIST ACTIVE", MAX_LINE, |_, line| {
+                commands::parse_list_active_line(line)
+            }),
+        })
+    }
+}
+
+impl ListActiveStream<'_> {
+    /// Record every group in the client's [group
+    /// watermarks](NntpClient::group_watermarks) as it is parsed
+    ///
+    /// The watermarks are recorded with
+    /// [`GroupWatermarks::observe_listing`](crate::renumber::GroupWatermarks::observe_listing),
+    /// so renumberings are detected and reported to the observer and stale
+    /// header cache entries can be purged without selecting each group.
+    /// LIST ACTIVE has no article counts, so a count that collapses is only
+    /// caught by the GROUP responses that follow.
+    pub fn record_watermarks(mut self) -> Self {
+        self.lines.set_parser(|client, line| {
+            let group = commands::parse_list_active_line(line)?;
+            client.observe_listing(&group.name, group.low, group.high);
+            Some(group)
+        });
+        self
+    }
+
+    /// Next group, or `None` at the end of the listing
+    pub async fn next(&mut self) -> Option<Result<ActiveGroup>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for ListActiveStream<'_> {
+    type Item = Result<ActiveGroup>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().lines).poll_next(cx)
+    }
+}
@@ -0,0 +1,119 @@
+//! Streaming LISTGROUP
+//!
+//! [`NntpClient::listgroup`] collects every article number before returning,
+//! which for the largest binary groups means hundreds of millions of
+//! entries. [`NntpClient::listgroup_stream`] parses the numbers as they
+//! arrive and hands them out one at a time through a [`Stream`].
+
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tracing::debug;
+
+use super::NntpClient;
+use super::line_stream::LineStream;
+use crate::error::{NntpError, Result};
+use crate::{commands, response::ResponseCode};
+
+/// Longest line accepted in a LISTGROUP response
+const MAX_LINE: usize = 1024;
+
+/// Article numbers of a LISTGROUP response, parsed as they arrive
+///
+/// Created by [`NntpClient::listgroup_stream`]. Yields the numbers in the
+/// order the server sends them and ends at the terminating "." line. Lines
+/// that are not numbers are skipped, as by [`NntpClient::listgroup`].
+///
+/// Besides implementing [`Stream`], it has an inherent async
+/// [`next`](Self::next), so no stream utility crate is needed to consume it.
+/// Like [`BodyStream`](super::BodyStream), it borrows the client, and dropping it before the
+/// end leaves the client [broken](NntpClient::is_broken).
+pub struct ListgroupStream<'a> {
+    lines: LineStream<'a, u64>,
+}
+
+impl NntpClient {
+    /// List the article numbers of a group as a stream
+    ///
+    /// Like [`listgroup`](Self::listgroup), but returns once the status line
+    /// is read; numbers are then parsed from the connection while the
+    /// returned [`ListgroupStream`] is polled, so memory use does not grow
+    /// with the size of the group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let mut numbers = client.listgroup_stream("alt.binaries.test", None).await?;
+    /// let mut count = 0u64;
+    /// while let Some(number) = numbers.next().await {
+    ///     let _ = number?;
+    ///     count += 1;
+    /// }
+    /// println!("{} articles", count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::NoSuchGroup`] - The newsgroup does not exist
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    ///
+    /// Errors while reading the numbers are yielded by the stream, which
+    /// ends after them.
+    pub async fn listgroup_stream(
+        &mut self,
+        newsgroup: &str,
+        range: Option<&str>,
+    ) -> Result<ListgroupStream<'_>> {
+        debug!("Streaming articles in group: {}", newsgroup);
+
+        let cmd = match range {
+            Some(r) => commands::listgroup_range(newsgroup, r),
+            None => commands::listgroup(newsgroup),
+        };
+        self.send_command(&cmd).await?;
+        let response = self.read_response().await?;
+
+        if response.response_code() == ResponseCode::NoSuchGroup {
+            return Err(NntpError::NoSuchGroup(newsgroup.to_string()));
+        }
+        if response.response_code() != ResponseCode::GroupSelected {
+            return Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            });
+        }
+
+        let data = self.stream_data_block(response).await?;
+        Ok(ListgroupStream {
+            lines: LineStream::new(data, "LISTGROUP", MAX_LINE, |_, line| {
+                line.trim().parse().ok()
+            }),
+        })
+    }
+}
+
+impl ListgroupStream<'_> {
+    /// Next article number, or `None` at the end of the listing
+    pub async fn next(&mut self) -> Option<Result<u64>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for ListgroupStream<'_> {
+    type Item = Result<u64>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().lines).poll_next(cx)
+    }
+}
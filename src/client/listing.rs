@@ -5,7 +5,8 @@
 use super::NntpClient;
 use crate::commands;
 use crate::error::{NntpError, Result};
-use crate::response::codes;
+use crate::response::ResponseCode;
+use crate::wildmat;
 use tracing::debug;
 
 impl NntpClient {
@@ -29,16 +30,18 @@ impl NntpClient {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - `wildmat` is not a valid wildmat (nothing is sent)
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn list_active(&mut self, wildmat: &str) -> Result<Vec<commands::ActiveGroup>> {
         debug!("Listing active groups matching: {}", wildmat);
 
+        wildmat::validate(wildmat)?;
         let cmd = commands::list_active(wildmat);
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -69,16 +72,18 @@ impl NntpClient {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - `wildmat` is not a valid wildmat (nothing is sent)
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn list_newsgroups(&mut self, wildmat: &str) -> Result<Vec<commands::NewsgroupInfo>> {
         debug!("Listing newsgroups matching: {}", wildmat);
 
+        wildmat::validate(wildmat)?;
         let cmd = commands::list_newsgroups(wildmat);
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -109,7 +114,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -154,7 +159,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -176,16 +181,18 @@ impl NntpClient {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - `wildmat` is not a valid wildmat (nothing is sent)
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn list_active_times(&mut self, wildmat: &str) -> Result<Vec<commands::GroupTime>> {
         debug!("Requesting newsgroup creation times (wildmat: {})", wildmat);
 
+        wildmat::validate(wildmat)?;
         let cmd = commands::list_active_times(wildmat);
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -197,6 +204,37 @@ impl NntpClient {
         Ok(groups)
     }
 
+    /// List default distribution patterns (LIST DISTRIB.PATS)
+    ///
+    /// Returns the `weight:wildmat:value` entries a posting agent uses to
+    /// fill in a missing Distribution header: the value of the
+    /// highest-weighted pattern that matches a newsgroup applies.
+    ///
+    /// RFC 3977 Section 7.6.5
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::Protocol`] - Server returned an error (e.g., 503 if not maintained)
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    pub async fn list_distrib_pats(&mut self) -> Result<Vec<commands::DistribPat>> {
+        debug!("Listing default distribution patterns");
+
+        self.send_command(commands::list_distrib_pats()).await?;
+        let response = self.read_multiline_response().await?;
+
+        if response.response_code() != ResponseCode::ListInformationFollows {
+            return Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            });
+        }
+
+        let pats = commands::parse_list_distrib_pats_response(response)?;
+        debug!("Retrieved {} distribution patterns", pats.len());
+        Ok(pats)
+    }
+
     /// List newsgroups with estimated article counts (RFC 6048 Section 3).
     ///
     /// Returns newsgroup information matching the wildmat pattern with estimated article counts.
@@ -241,6 +279,7 @@ impl NntpClient {
     /// Returns an error if:
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::InvalidResponse`] - Could not parse the server response
+    /// - [`NntpError::InvalidArgument`] - `wildmat` is not a valid wildmat (nothing is sent)
     ///
     /// # Note
     ///
@@ -249,11 +288,12 @@ impl NntpClient {
     pub async fn list_counts(&mut self, wildmat: &str) -> Result<Vec<commands::CountsGroup>> {
         debug!("Listing newsgroups with counts matching: {}", wildmat);
 
+        wildmat::validate(wildmat)?;
         let cmd = commands::list_counts(wildmat);
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -314,7 +354,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -367,7 +407,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -413,7 +453,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -449,7 +489,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::LIST_INFORMATION_FOLLOWS {
+        if response.response_code() != ResponseCode::ListInformationFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -522,7 +562,7 @@ impl NntpClient {
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::NEW_NEWSGROUPS_FOLLOW {
+        if response.response_code() != ResponseCode::NewNewsgroupsFollow {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -571,6 +611,7 @@ impl NntpClient {
     /// Returns an error if:
     /// - [`NntpError::Protocol`] - Server returned an error
     /// - [`NntpError::InvalidResponse`] - Could not parse the server response
+    /// - [`NntpError::InvalidArgument`] - `wildmat` is not a valid wildmat (nothing is sent)
     pub async fn newnews(
         &mut self,
         wildmat: &str,
@@ -583,6 +624,7 @@ impl NntpClient {
             date, time, wildmat, gmt
         );
 
+        wildmat::validate(wildmat)?;
         let cmd = if gmt {
             commands::newnews_gmt(wildmat, date, time)
         } else {
@@ -591,7 +633,7 @@ impl NntpClient {
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::NEW_ARTICLE_LIST_FOLLOWS {
+        if response.response_code() != ResponseCode::NewArticleListFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
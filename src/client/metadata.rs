@@ -4,14 +4,58 @@
 //! without downloading full article content. These commands are used for
 //! browsing newsgroups and building article lists.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+
 use crate::commands::{self, XoverEntry};
 use crate::error::{NntpError, Result};
-use crate::response::codes;
-use tracing::{trace, warn};
+use crate::response::ResponseCode;
+use crate::validation;
+use crate::warnings::ParseWarning;
+use tracing::{debug, trace, warn};
 
 use super::NntpClient;
 
+/// Maximum number of HDR commands in flight in [`NntpClient::hdr_many`]
+const HDR_MANY_PIPELINE_DEPTH: usize = 32;
+
+/// Overview line with its columns moved into the standard order, if needed
+fn in_standard_order<'a>(line: &'a str, format: Option<&[String]>) -> Cow<'a, str> {
+    format.map_or(Cow::Borrowed(line), |format| {
+        commands::reorder_overview_fields(line, format)
+    })
+}
+
 impl NntpClient {
+    /// LIST OVERVIEW.FMT of a server with the `overview_column_drift` quirk
+    ///
+    /// Requested once per connection. `None` without the quirk, or when the
+    /// server does not report its format, in which case lines are parsed as
+    /// sent.
+    async fn drifted_overview_format(&mut self) -> Result<Option<Vec<String>>> {
+        if !self.quirks.overview_column_drift {
+            return Ok(None);
+        }
+        if self.overview_format.is_none() {
+            let format = match self.list_overview_fmt().await {
+                Ok(format) => format,
+                Err(NntpError::Protocol { code, message }) => {
+                    warn!(
+                        "No overview format ({} {}), columns left as sent",
+                        code, message
+                    );
+                    Vec::new()
+                }
+                Err(e) => return Err(e),
+            };
+            self.overview_format = Some(format);
+        }
+        Ok(self
+            .overview_format
+            .clone()
+            .filter(|format| !format.is_empty()))
+    }
+
     /// Fetch article overview data using XOVER command (legacy name)
     ///
     /// XOVER is the legacy name for retrieving article metadata. Modern clients
@@ -27,7 +71,11 @@ impl NntpClient {
     /// # Returns
     ///
     /// Returns a [`Vec<XoverEntry>`] containing overview metadata for each article.
-    /// Failed parse lines are logged and skipped (not returned as errors).
+    /// Failed parse lines are skipped (not returned as errors) and recorded as
+    /// [`ParseWarning`]s, see [`take_parse_warnings`](Self::take_parse_warnings).
+    ///
+    /// With the [`ServerQuirks::no_over_by_msgid`](crate::ServerQuirks::no_over_by_msgid)
+    /// quirk, message-id lookups are answered from the article headers (HEAD).
     ///
     /// # Errors
     ///
@@ -37,6 +85,7 @@ impl NntpClient {
     pub async fn fetch_xover(&mut self, range: &str) -> Result<Vec<XoverEntry>> {
         trace!("Fetching XOVER: {}", range);
 
+        let format = self.drifted_overview_format().await?;
         let cmd = commands::xover(range);
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
@@ -51,12 +100,15 @@ impl NntpClient {
         // Pre-allocate: one entry per response line (minus failed parses)
         let mut entries = Vec::with_capacity(response.lines.len());
         for line in &response.lines {
-            match commands::parse_xover_line(line) {
+            match commands::parse_xover_line_with(
+                &in_standard_order(line, format.as_deref()),
+                self.parse_mode,
+            ) {
                 Ok(entry) => entries.push(entry),
-                Err(e) => {
-                    warn!("Failed to parse XOVER line: {} - {}", line, e);
-                    continue;
-                }
+                Err(e) => self.skip_malformed_line(ParseWarning::SkippedOverviewLine {
+                    line: line.clone(),
+                    reason: e.to_string(),
+                })?,
             }
         }
 
@@ -86,7 +138,8 @@ impl NntpClient {
     /// # Returns
     ///
     /// Returns a [`Vec<XoverEntry>`] containing overview metadata for each article.
-    /// Failed parse lines are logged and skipped (not returned as errors).
+    /// Failed parse lines are skipped (not returned as errors) and recorded as
+    /// [`ParseWarning`]s, see [`take_parse_warnings`](Self::take_parse_warnings).
     ///
     /// # Errors
     ///
@@ -119,6 +172,15 @@ impl NntpClient {
     pub async fn over(&mut self, range_or_msgid: &str) -> Result<Vec<XoverEntry>> {
         trace!("Fetching OVER: {}", range_or_msgid);
 
+        if self.quirks.no_over_by_msgid && range_or_msgid.starts_with('<') {
+            debug!("Quirk: emulating OVER {} with HEAD", range_or_msgid);
+            let head = self.fetch_head(range_or_msgid).await?;
+            let mut entry = commands::overview_from_headers(&head.lines);
+            entry.source_id = Some(range_or_msgid.to_string());
+            return Ok(vec![entry]);
+        }
+
+        let format = self.drifted_overview_format().await?;
         if range_or_msgid.is_empty() {
             self.send_command(commands::over_current()).await?;
         } else {
@@ -127,11 +189,11 @@ impl NntpClient {
         }
         let response = self.read_multiline_response().await?;
 
-        if response.code == codes::NO_GROUP_SELECTED {
+        if response.response_code() == ResponseCode::NoGroupSelected {
             return Err(NntpError::NoGroupSelected);
         }
 
-        if response.code == codes::NO_CURRENT_ARTICLE {
+        if response.response_code() == ResponseCode::NoCurrentArticle {
             return Err(NntpError::InvalidArticleNumber);
         }
 
@@ -142,21 +204,77 @@ impl NntpClient {
             });
         }
 
+        let source_id = range_or_msgid
+            .starts_with('<')
+            .then(|| range_or_msgid.to_string());
+
         // Pre-allocate: one entry per response line (minus failed parses)
         let mut entries = Vec::with_capacity(response.lines.len());
         for line in &response.lines {
-            match commands::parse_xover_line(line) {
-                Ok(entry) => entries.push(entry),
-                Err(e) => {
-                    warn!("Failed to parse OVER line: {} - {}", line, e);
-                    continue;
-                }
+            match commands::parse_xover_line_with(
+                &in_standard_order(line, format.as_deref()),
+                self.parse_mode,
+            ) {
+                Ok(entry) => entries.push(XoverEntry {
+                    source_id: source_id.clone(),
+                    ..entry
+                }),
+                Err(e) => self.skip_malformed_line(ParseWarning::SkippedOverviewLine {
+                    line: line.clone(),
+                    reason: e.to_string(),
+                })?,
             }
         }
 
         Ok(entries)
     }
 
+    /// Fetch overview data of a single article by message-id
+    ///
+    /// Like [`over`](Self::over) with a message-id, but returns the one entry
+    /// the server can send, tagged with the message-id in
+    /// [`source_id`](XoverEntry::source_id). No group needs to be selected.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the server has no article with that message-id (code 430).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::InvalidResponse`] - `message_id` is not a valid message-id
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// match client.over_by_msgid("<part1@example.com>").await? {
+    ///     Some(entry) => println!("{}: {} bytes", entry.subject, entry.bytes),
+    ///     None => println!("not on this server"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn over_by_msgid(&mut self, message_id: &str) -> Result<Option<XoverEntry>> {
+        validation::validate_message_id(message_id)?;
+        match self.over(message_id).await {
+            Ok(entries) => Ok(entries.into_iter().next()),
+            Err(NntpError::NoSuchArticle(_)) => Ok(None),
+            Err(NntpError::Protocol { code, .. })
+                if ResponseCode::from(code) == ResponseCode::NoSuchArticleId =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Retrieve specific header field values from articles (HDR command)
     ///
     /// Fetches the value of a specific header field from one or more articles.
@@ -216,11 +334,11 @@ impl NntpClient {
         self.send_command(&cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code == codes::NO_GROUP_SELECTED {
+        if response.response_code() == ResponseCode::NoGroupSelected {
             return Err(NntpError::NoGroupSelected);
         }
 
-        if response.code == codes::NO_CURRENT_ARTICLE {
+        if response.response_code() == ResponseCode::NoCurrentArticle {
             return Err(NntpError::InvalidArticleNumber);
         }
 
@@ -236,13 +354,227 @@ impl NntpClient {
         for line in &response.lines {
             match commands::parse_hdr_line(line) {
                 Ok(entry) => entries.push(entry),
-                Err(e) => {
-                    warn!("Failed to parse HDR line: {} - {}", line, e);
+                Err(e) => self.skip_malformed_line(ParseWarning::SkippedHdrLine {
+                    line: line.clone(),
+                    reason: e.to_string(),
+                })?,
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Retrieve one header field from many articles by message-id
+    ///
+    /// Sends one HDR command per message-id, pipelined in batches, instead of
+    /// waiting for each response in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The header field name to retrieve (e.g., "Subject", "Xref")
+    /// * `message_ids` - Message-IDs of the articles ("<id@example.com>")
+    ///
+    /// # Returns
+    ///
+    /// One result per distinct message-id, keyed by it. An empty value means
+    /// the article has no such header.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let ids = ["<a@example.com>", "<b@example.com>"];
+    /// let xrefs = client.hdr_many("Xref", &ids).await?;
+    /// for (id, xref) in &xrefs {
+    ///     match xref {
+    ///         Ok(xref) => println!("{}: {}", id, xref),
+    ///         Err(e) => println!("{}: {}", id, e),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The outer result fails if the connection fails (I/O error, timeout,
+    /// malformed response) or the server does not support HDR
+    /// ([`NntpError::Protocol`] with code 500 or 503). Per-article results can be:
+    /// - [`NntpError::NoSuchArticle`] - The article does not exist
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    ///
+    /// All responses of a pipelined batch are read before an error is returned,
+    /// so the connection stays in sync.
+    pub async fn hdr_many(
+        &mut self,
+        field: &str,
+        message_ids: &[&str],
+    ) -> Result<HashMap<String, Result<String>>> {
+        debug!("Fetching HDR {} for {} articles", field, message_ids.len());
+
+        let mut results = HashMap::with_capacity(message_ids.len());
+        for chunk in message_ids.chunks(HDR_MANY_PIPELINE_DEPTH) {
+            let batch: Vec<String> = chunk.iter().map(|id| commands::hdr(field, id)).collect();
+            self.send_commands(&batch).await?;
+
+            let mut unsupported = None;
+            for id in chunk {
+                let response = self.read_multiline_response().await?;
+                if matches!(
+                    response.response_code(),
+                    ResponseCode::CommandNotRecognized | ResponseCode::FeatureNotSupported
+                ) {
+                    unsupported.get_or_insert(NntpError::Protocol {
+                        code: response.code,
+                        message: response.message,
+                    });
                     continue;
                 }
+                let value = self.hdr_value(id, response);
+                results.insert((*id).to_string(), value);
+            }
+            if let Some(e) = unsupported {
+                return Err(e);
             }
         }
 
-        Ok(entries)
+        Ok(results)
+    }
+
+    /// Value of the header in an HDR response for a single message-id
+    fn hdr_value(&mut self, id: &str, response: crate::NntpResponse) -> Result<String> {
+        match response.response_code() {
+            ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber => {
+                return Err(NntpError::NoSuchArticle(id.to_string()));
+            }
+            _ if !response.is_success() => {
+                return Err(NntpError::Protocol {
+                    code: response.code,
+                    message: response.message,
+                });
+            }
+            _ => {}
+        }
+
+        let mut value = None;
+        for line in &response.lines {
+            match commands::parse_hdr_line(line) {
+                Ok(entry) => {
+                    value.get_or_insert(entry.value);
+                }
+                Err(e) => self.skip_malformed_line(ParseWarning::SkippedHdrLine {
+                    line: line.clone(),
+                    reason: e.to_string(),
+                })?,
+            }
+        }
+        Ok(value.unwrap_or_default())
+    }
+
+    /// Retrieve only selected header fields of a single article
+    ///
+    /// Prefers HDR (one pipelined command per field) so only the requested
+    /// values cross the wire. If the server does not support HDR, falls back
+    /// to HEAD and extracts the fields locally.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Message-ID ("<id@example.com>") or article number in the selected group
+    /// * `fields` - Header field names to retrieve (matched case-insensitively)
+    ///
+    /// # Returns
+    ///
+    /// `(field, value)` pairs in the order of `fields`. Fields the article does
+    /// not have are omitted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let headers = client
+    ///     .fetch_headers_fields("<big@example.com>", &["Subject", "From", "Date"])
+    ///     .await?;
+    /// for (field, value) in headers {
+    ///     println!("{}: {}", field, value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::NoSuchArticle`] - The article does not exist
+    /// - [`NntpError::NoGroupSelected`] - An article number was given without a selected group
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    pub async fn fetch_headers_fields(
+        &mut self,
+        id: &str,
+        fields: &[&str],
+    ) -> Result<Vec<(String, String)>> {
+        trace!("Fetching header fields {:?} for {}", fields, id);
+
+        if fields.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Pipeline one HDR per field, then read every response to stay in sync
+        for field in fields {
+            self.send_command(&commands::hdr(field, id)).await?;
+        }
+        let mut responses = Vec::with_capacity(fields.len());
+        for _ in fields {
+            responses.push(self.read_multiline_response().await?);
+        }
+
+        let hdr_unsupported = responses.iter().any(|response| {
+            matches!(
+                response.response_code(),
+                ResponseCode::CommandNotRecognized | ResponseCode::FeatureNotSupported
+            )
+        });
+        if hdr_unsupported {
+            trace!("HDR not supported, falling back to HEAD for {}", id);
+            let response = self.fetch_head(id).await.map_err(|e| match e {
+                NntpError::Protocol { code, .. }
+                    if matches!(
+                        ResponseCode::from(code),
+                        ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber
+                    ) =>
+                {
+                    NntpError::NoSuchArticle(id.to_string())
+                }
+                other => other,
+            })?;
+            return Ok(commands::extract_header_fields(&response.lines, fields));
+        }
+
+        let mut values = Vec::with_capacity(fields.len());
+        for (field, response) in fields.iter().zip(responses) {
+            match response.response_code() {
+                ResponseCode::NoSuchArticleId | ResponseCode::NoSuchArticleNumber => {
+                    return Err(NntpError::NoSuchArticle(id.to_string()));
+                }
+                ResponseCode::NoGroupSelected => return Err(NntpError::NoGroupSelected),
+                _ => {}
+            }
+
+            let entries = commands::parse_hdr_response(response)?;
+            // An empty value means the article has no such header
+            if let Some(entry) = entries.into_iter().next().filter(|e| !e.value.is_empty()) {
+                values.push(((*field).to_string(), entry.value));
+            }
+        }
+
+        Ok(values)
     }
 }
@@ -2,24 +2,54 @@
 
 mod articles;
 mod auth;
+mod body_stream;
 mod compression;
 mod connection;
+mod deflate;
+mod dial;
+mod feeder;
 mod group_ops;
 mod high_throughput;
 mod io;
+mod line_stream;
+mod list_active_stream;
+mod listgroup_stream;
 mod listing;
 mod metadata;
+mod pipeline;
 mod posting;
+mod reconnect;
 mod server;
 mod state;
+mod stream;
+mod tls;
 
+use crate::capabilities::Capabilities;
+use crate::compression::GzipDetection;
 use crate::config::ServerConfig;
+use crate::error::{NntpError, Result};
+use crate::latency::LatencyStats;
+use crate::observer::ConnectionObserver;
+use crate::quirks::ServerQuirks;
+use crate::ratelimit::ConnectionPermit;
+use crate::renumber::GroupWatermarks;
+use crate::response::ParseMode;
+use crate::warnings::ParseWarning;
+pub use body_stream::{BodyPrefix, BodyStream};
+pub use feeder::{DEFAULT_FEED_WINDOW, FeedArticle, FeedReport, StreamFeeder};
+pub use list_active_stream::ListActiveStream;
+pub use listgroup_stream::ListgroupStream;
+pub use pipeline::{DEFAULT_PIPELINE_DEPTH, Pipeline, ResponseKind};
 use state::{CompressionMode, ConnectionState};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Instant;
+use stream::NntpStream;
 use tokio::io::BufReader;
-use tokio::net::TcpStream;
-use tokio_rustls::client::TlsStream;
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Parse warnings kept per connection until taken
+const MAX_PARSE_WARNINGS: usize = 1024;
 
 /// Async NNTP client with TLS and compression support
 ///
@@ -44,25 +74,62 @@ use tracing::debug;
 /// ```
 #[must_use]
 pub struct NntpClient {
-    /// TLS stream (both reader and writer)
-    stream: BufReader<TlsStream<TcpStream>>,
+    /// Plain or TLS stream (both reader and writer)
+    stream: BufReader<NntpStream>,
     /// Connection state
     state: ConnectionState,
+    /// SASL mechanism to log in with again, if the session used SASL
+    sasl_replay: Option<auth::SaslReplay>,
     /// Server configuration
     config: Arc<ServerConfig>,
     /// Currently selected newsgroup
     current_group: Option<String>,
+    /// Watermarks of every group selected so far
+    group_watermarks: GroupWatermarks,
     /// Compression mode for this connection
     compression_mode: CompressionMode,
+    /// Workarounds for this server (from config or greeting fingerprint)
+    quirks: ServerQuirks,
+    /// How headers-only compressed responses are recognized
+    gzip_detection: GzipDetection,
+    /// Detection picked from the greeting, used when no quirk overrides it
+    greeting_gzip_detection: GzipDetection,
+    /// Verbs of the commands sent whose status lines are still to be read
+    pending_verbs: VecDeque<String>,
+    /// Verb of the command whose response is being read (for compression detection)
+    response_verb: Option<String>,
+    /// When a command was last sent (for keepalive)
+    last_activity: Instant,
+    /// When the command now awaiting its status line was sent, if timed
+    response_pending_since: Option<Instant>,
+    /// Recent status line latencies (for adaptive timeouts)
+    response_latency: LatencyStats,
+    /// Whether malformed lines are skipped or fail the command
+    parse_mode: ParseMode,
+    /// Parse warnings not yet taken by the caller
+    parse_warnings: Vec<ParseWarning>,
+    /// Instrumentation hooks for commands and responses
+    observer: Option<Arc<dyn ConnectionObserver>>,
     /// Total compressed bytes received (only when compression enabled)
     bytes_compressed: u64,
     /// Total decompressed bytes (original size)
     bytes_decompressed: u64,
     /// Whether this connection is broken (received garbage/invalid data)
     is_broken: bool,
+    /// Connection limiter slot, released when the connection is dropped
+    connection_permit: Option<ConnectionPermit>,
+    /// Last CAPABILITIES response, discarded when the server may change it
+    capabilities: Option<Capabilities>,
+    /// LIST OVERVIEW.FMT, fetched for the `overview_column_drift` quirk
+    overview_format: Option<Vec<String>>,
 }
 
 impl NntpClient {
+    /// Hold a connection limiter slot for the lifetime of this connection
+    pub(crate) fn hold_permit(&mut self, permit: ConnectionPermit) {
+        self.connection_permit = Some(permit);
+    }
+
     /// Check if this connection is broken and should be discarded
     pub fn is_broken(&self) -> bool {
         self.is_broken
@@ -73,6 +140,184 @@ impl NntpClient {
         self.is_broken = true;
     }
 
+    /// Get the server workarounds in effect for this connection
+    ///
+    /// Taken from [`ServerConfig::quirks`] if set, otherwise derived from the
+    /// server greeting via [`ServerQuirks::from_greeting`].
+    pub fn quirks(&self) -> &ServerQuirks {
+        &self.quirks
+    }
+
+    /// Get the configuration of the server this connection is to
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Override the server workarounds for this connection
+    ///
+    /// Also resets [`gzip_detection`](Self::gzip_detection) to what the
+    /// quirks and the server greeting call for.
+    pub fn set_quirks(&mut self, quirks: ServerQuirks) {
+        self.gzip_detection = if quirks.gzip_marker_absent {
+            GzipDetection::always_compressed_overview()
+        } else {
+            self.greeting_gzip_detection.clone()
+        };
+        self.quirks = quirks;
+    }
+
+    /// Set or clear the observer for this connection
+    ///
+    /// Replaces the observer taken from [`ServerConfig::observer`], for
+    /// commands, responses and socket traffic alike.
+    pub fn set_observer(&mut self, observer: Option<Arc<dyn ConnectionObserver>>) {
+        self.stream.get_mut().set_observer(observer.clone());
+        self.observer = observer;
+    }
+
+    /// Get the bytes received on the socket
+    ///
+    /// Counts everything read from the TCP connection, including TLS
+    /// overhead, with compressed data counted as compressed.
+    pub fn bytes_received(&self) -> u64 {
+        self.stream.get_ref().socket_totals().0
+    }
+
+    /// Get the bytes sent on the socket
+    ///
+    /// Counts everything written to the TCP connection, including TLS overhead.
+    pub fn bytes_sent(&self) -> u64 {
+        self.stream.get_ref().socket_totals().1
+    }
+
+    /// Report a received status line to the observer
+    fn observe_response(&self, code: u16, message: &str) {
+        if let Some(observer) = &self.observer {
+            observer.on_response(code, message);
+        }
+    }
+
+    /// Get the recent status line latencies of this connection
+    ///
+    /// Each sample is the time from sending a command to receiving its status
+    /// line. Pipelined commands and article uploads are not timed, as their
+    /// responses wait for more than the server's reaction.
+    pub fn response_latency(&self) -> &LatencyStats {
+        &self.response_latency
+    }
+
+    /// Get the timeout applied while waiting for the next status line
+    ///
+    /// This is [`TimeoutConfig::read`](crate::TimeoutConfig::read), or less
+    /// once [`TimeoutConfig::adaptive`](crate::TimeoutConfig::adaptive) has
+    /// enough latency samples to go on.
+    pub fn response_timeout(&self) -> std::time::Duration {
+        let timeouts = &self.config.timeouts;
+        match &timeouts.adaptive {
+            Some(adaptive) => adaptive.timeout(&self.response_latency, timeouts.read),
+            None => timeouts.read,
+        }
+    }
+
+    /// Time the status line just received, if its command was timed
+    fn record_response_latency(&mut self) {
+        if let Some(sent) = self.response_pending_since.take() {
+            self.response_latency.record(sent.elapsed());
+        }
+    }
+
+    /// Get the parse warnings collected on this connection
+    ///
+    /// Lines skipped in XOVER, OVER and HDR responses end up here. At most
+    /// 1024 are kept until taken; later ones are still logged and reported
+    /// to the observer.
+    pub fn parse_warnings(&self) -> &[ParseWarning] {
+        &self.parse_warnings
+    }
+
+    /// Take the collected parse warnings, leaving none
+    pub fn take_parse_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.parse_warnings)
+    }
+
+    /// Skip a malformed response line, or fail the command in strict mode
+    fn skip_malformed_line(&mut self, warning: ParseWarning) -> Result<()> {
+        if self.parse_mode == ParseMode::Strict {
+            return Err(NntpError::InvalidResponse(warning.to_string()));
+        }
+        self.record_parse_warning(warning);
+        Ok(())
+    }
+
+    /// Get how malformed server output is handled on this connection
+    ///
+    /// Taken from [`ServerConfig::parse_mode`] unless overridden with
+    /// [`set_parse_mode`](Self::set_parse_mode).
+    pub fn parse_mode(&self) -> ParseMode {
+        self.parse_mode
+    }
+
+    /// Override how malformed server output is handled on this connection
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    /// Log a parse warning, keep it for the caller and report it to the observer
+    fn record_parse_warning(&mut self, warning: ParseWarning) {
+        warn!("{}", warning);
+        if let Some(observer) = &self.observer {
+            observer.on_parse_warning(&warning);
+        }
+        if self.parse_warnings.len() < MAX_PARSE_WARNINGS {
+            self.parse_warnings.push(warning);
+        }
+    }
+
+    /// Get the watermarks recorded from GROUP responses on this connection
+    ///
+    /// Save them together with cached overview data so a renumbering that
+    /// happens between sessions is detected on the next GROUP.
+    pub fn group_watermarks(&self) -> &GroupWatermarks {
+        &self.group_watermarks
+    }
+
+    /// Replace the recorded watermarks, e.g. with ones saved earlier
+    pub fn set_group_watermarks(&mut self, watermarks: GroupWatermarks) {
+        self.group_watermarks = watermarks;
+    }
+
+    /// Record a GROUP response and report a renumbering to the observer
+    fn observe_group(&mut self, newsgroup: &str, info: crate::commands::GroupInfo) {
+        let renumbering = self.group_watermarks.observe(newsgroup, info);
+        self.report_renumbering(renumbering);
+    }
+
+    /// Record watermarks from a listing and report a renumbering to the observer
+    fn observe_listing(&mut self, newsgroup: &str, first: u64, last: u64) {
+        let renumbering = self
+            .group_watermarks
+            .observe_listing(newsgroup, first, last);
+        self.report_renumbering(renumbering);
+    }
+
+    fn report_renumbering(&self, renumbering: Option<crate::renumber::GroupRenumbering>) {
+        let Some(renumbering) = renumbering else {
+            return;
+        };
+        warn!(
+            "Group {} renumbered ({:?}): {}-{} is now {}-{}",
+            renumbering.group,
+            renumbering.reason,
+            renumbering.previous.first,
+            renumbering.previous.last,
+            renumbering.current.first,
+            renumbering.current.last
+        );
+        if let Some(observer) = &self.observer {
+            observer.on_group_renumbered(&renumbering);
+        }
+    }
+
     /// Get the currently selected newsgroup, if any
     pub fn current_group(&self) -> Option<&str> {
         self.current_group.as_deref()
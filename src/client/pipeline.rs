@@ -0,0 +1,204 @@
+//! Pipelining of arbitrary commands
+//!
+//! [`NntpClient::fetch_articles_pipelined`] and friends pipeline one command
+//! each. A [`Pipeline`] queues any mix of commands, each with the kind of
+//! response it expects, and [`NntpClient::run_pipeline`] sends them through
+//! a sliding window and collects the responses in order.
+
+use super::NntpClient;
+use crate::error::{NntpError, Result};
+use crate::response::NntpResponse;
+use tracing::trace;
+
+/// Commands in flight by default
+pub const DEFAULT_PIPELINE_DEPTH: usize = 16;
+
+/// Shape of the response a pipelined command expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseKind {
+    /// A status line only (STAT, CHECK, DATE, ...)
+    Single,
+    /// A status line followed by data up to the "." line (ARTICLE, HEAD,
+    /// OVER, LIST, ...), unless the status is an error
+    Multiline,
+}
+
+/// Queue of commands to send pipelined
+///
+/// Commands are given without the trailing CRLF. They are sent as they are:
+/// commands that change connection state (GROUP, MODE READER, COMPRESS)
+/// take effect on the server without the client tracking them.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nntp_rs::{NntpClient, Pipeline, ServerConfig};
+/// # use std::sync::Arc;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// let mut client = NntpClient::connect(Arc::new(config)).await?;
+///
+/// let mut pipeline = Pipeline::new();
+/// pipeline
+///     .single("STAT <a@example.com>")
+///     .multiline("HEAD <b@example.com>")
+///     .single("DATE");
+/// let responses = client.run_pipeline(&pipeline).await?;
+/// println!("HEAD returned {} lines", responses[1].lines.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    commands: Vec<(String, ResponseKind)>,
+    depth: usize,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    /// Create an empty pipeline with [`DEFAULT_PIPELINE_DEPTH`]
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            depth: DEFAULT_PIPELINE_DEPTH,
+        }
+    }
+
+    /// Set how many commands may await a response at once (at least 1)
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Queue a command and the kind of response it expects
+    pub fn push(&mut self, command: impl Into<String>, kind: ResponseKind) -> &mut Self {
+        self.commands.push((command.into(), kind));
+        self
+    }
+
+    /// Queue a command answered by a status line only
+    pub fn single(&mut self, command: impl Into<String>) -> &mut Self {
+        self.push(command, ResponseKind::Single)
+    }
+
+    /// Queue a command answered by a multi-line response
+    pub fn multiline(&mut self, command: impl Into<String>) -> &mut Self {
+        self.push(command, ResponseKind::Multiline)
+    }
+
+    /// Number of queued commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Check if no commands are queued
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Remove every queued command
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Wire form of every command, rejecting line breaks inside a command
+    fn wire_commands(&self) -> Result<Vec<String>> {
+        self.commands
+            .iter()
+            .map(|(command, _)| {
+                if command.is_empty() || command.contains(['\r', '\n']) {
+                    return Err(NntpError::InvalidArgument(format!(
+                        "Pipelined command must be a single non-empty line: {:?}",
+                        command
+                    )));
+                }
+                Ok(format!("{}\r\n", command))
+            })
+            .collect()
+    }
+}
+
+impl NntpClient {
+    /// Send the commands of `pipeline` pipelined and collect their responses
+    ///
+    /// Up to the pipeline's depth of commands are sent in one write before
+    /// their responses are read; each response read makes room for the next
+    /// command. Responses are returned in command order, error statuses
+    /// included, so check each one's code.
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::InvalidArgument`] - A command is empty or contains a
+    ///   line break (nothing is sent)
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    /// - Network I/O errors
+    pub async fn run_pipeline(&mut self, pipeline: &Pipeline) -> Result<Vec<NntpResponse>> {
+        let wire = pipeline.wire_commands()?;
+        trace!(
+            "Pipelining {} commands with depth {}",
+            wire.len(),
+            pipeline.depth
+        );
+
+        let mut responses = Vec::with_capacity(wire.len());
+        let mut sent = 0;
+        let mut batch = Vec::with_capacity(pipeline.depth);
+        for (_, kind) in &pipeline.commands {
+            // Top the window up and send it in a single write
+            while sent - responses.len() < pipeline.depth
+                && let Some(command) = wire.get(sent)
+            {
+                batch.push(command.clone());
+                sent += 1;
+            }
+            if !batch.is_empty() {
+                self.send_commands(&batch).await?;
+                batch.clear();
+            }
+
+            let response = match kind {
+                ResponseKind::Single => self.read_response().await?,
+                ResponseKind::Multiline => self.read_multiline_response().await?,
+            };
+            responses.push(response);
+        }
+
+        Ok(responses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_queue() {
+        let mut pipeline = Pipeline::new().with_depth(0);
+        assert_eq!(pipeline.depth, 1);
+        assert!(pipeline.is_empty());
+
+        pipeline.single("DATE").multiline("HEAD <a@b>");
+        assert_eq!(pipeline.len(), 2);
+        assert_eq!(
+            pipeline.wire_commands().unwrap(),
+            vec!["DATE\r\n", "HEAD <a@b>\r\n"]
+        );
+
+        pipeline.clear();
+        assert!(pipeline.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_rejects_line_breaks() {
+        for command in ["", "STAT <a@b>\r\nQUIT", "DATE\n"] {
+            let mut pipeline = Pipeline::new();
+            pipeline.single("DATE").single(command);
+            assert!(pipeline.wire_commands().is_err(), "{:?}", command);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::commands;
-use crate::response::codes;
+use crate::response::ResponseCode;
 use crate::{NntpError, Result};
 use tracing::debug;
 
@@ -61,8 +61,94 @@ impl NntpClient {
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
     /// - [`NntpError::Timeout`] - Server did not respond in time
     pub async fn post(&mut self, article: &crate::article::Article) -> Result<()> {
-        debug!("Posting article");
+        self.post_chunked(article, None).await
+    }
+
+    /// Post an article with its upload throttled by a bandwidth limiter
+    ///
+    /// Like [`post`](Self::post), but acquires bandwidth from `limiter` for each
+    /// chunk of the article before writing it. Useful for large binary posts
+    /// sharing an upload budget with other connections.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{BandwidthLimiter, NntpClient, ServerConfig, article::ArticleBuilder};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// // Limit uploads to 512 KB/s
+    /// let limiter = BandwidthLimiter::new(512 * 1024, None);
+    /// let article = ArticleBuilder::new()
+    ///     .from("user@example.com")
+    ///     .subject("Large post")
+    ///     .newsgroups(vec!["alt.binaries.test".to_string()])
+    ///     .body("... encoded payload ...")
+    ///     .build()?;
+    ///
+    /// client.post_throttled(&article, &limiter).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Same as [`post`](Self::post).
+    pub async fn post_throttled(
+        &mut self,
+        article: &crate::article::Article,
+        limiter: &crate::ratelimit::BandwidthLimiter,
+    ) -> Result<()> {
+        self.post_chunked(article, Some(limiter)).await
+    }
+
+    /// Post an article, streaming it in chunks
+    async fn post_chunked(
+        &mut self,
+        article: &crate::article::Article,
+        limiter: Option<&crate::ratelimit::BandwidthLimiter>,
+    ) -> Result<()> {
+        debug!("Posting article {}", article.headers.message_id);
+
+        self.begin_post().await?;
+        self.send_article_chunks(article, limiter).await?;
+        self.finish_post().await
+    }
+
+    /// Post a pre-serialized article to the server (RFC 3977 Section 6.3.1)
+    ///
+    /// Like [`post`](Self::post), but sends the given wire bytes as-is instead of
+    /// re-serializing an [`Article`](crate::article::Article). This preserves the
+    /// exact header order, folding, and encoding of articles held by relays and feeders.
+    ///
+    /// The bytes must already use CRLF line endings and dot-stuffing. A trailing
+    /// ".\r\n" terminator is optional; see [`commands::validate_raw_article`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - The raw article is malformed (checked before sending)
+    /// - [`NntpError::PostingNotPermitted`] - Server does not allow posting (440)
+    /// - [`NntpError::PostingFailed`] - Server rejected the article (441)
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    pub async fn post_raw(&mut self, article: &[u8]) -> Result<()> {
+        debug!("Posting article ({} raw bytes)", article.len());
+
+        // Validate before starting the exchange so a bad article never desyncs the stream
+        let article = commands::validate_raw_article(article)?;
+
+        self.begin_post().await?;
 
+        // Phase 2: Send article bytes (already has CRLF and dot-stuffing)
+        self.send_raw_article(article).await?;
+
+        self.finish_post().await
+    }
+
+    /// Phase 1 of POST: check authentication, send POST and expect 340
+    async fn begin_post(&mut self) -> Result<()> {
         // Verify authenticated - most servers require authentication for posting
         if !matches!(self.state, ConnectionState::Authenticated) {
             return Err(NntpError::Protocol {
@@ -77,35 +163,32 @@ impl NntpClient {
         let response = self.read_response().await?;
 
         // Check for 340 (send article text) response
-        if response.code == codes::POSTING_NOT_PERMITTED {
+        if response.response_code() == ResponseCode::PostingNotPermitted {
             return Err(NntpError::PostingNotPermitted);
         }
 
-        if response.code != codes::SEND_ARTICLE {
+        if response.response_code() != ResponseCode::SendArticle {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
             });
         }
 
-        // Phase 2: Send article text with dot-stuffing
-        let article_text = article.serialize_for_posting()?;
-
-        // Send the article body (already has CRLF and dot-stuffing)
-        self.send_command(&article_text).await?;
-
-        // Send terminating dot line
-        self.send_command(".\r\n").await?;
+        Ok(())
+    }
 
-        // Wait for final response
+    /// Final phase of POST: read the 240/441 result after the article was sent
+    async fn finish_post(&mut self) -> Result<()> {
+        // The upload started no response timer, so this waits up to the
+        // read timeout however long the server takes to store the article
         let response = self.read_response().await?;
 
         // Check result
-        if response.code == codes::POSTING_FAILED {
+        if response.response_code() == ResponseCode::PostingFailed {
             return Err(NntpError::PostingFailed(response.message));
         }
 
-        if response.code != codes::ARTICLE_POSTED {
+        if response.response_code() != ResponseCode::ArticlePosted {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -183,6 +266,49 @@ impl NntpClient {
     ) -> Result<()> {
         debug!("IHAVE: offering article {}", message_id);
 
+        self.begin_ihave(message_id).await?;
+
+        // Phase 2: Stream article text with dot-stuffing and the terminating dot line
+        self.send_article_chunks(article, None).await?;
+
+        self.finish_ihave().await
+    }
+
+    /// Offer a pre-serialized article with IHAVE (RFC 3977 Section 6.3.2)
+    ///
+    /// Like [`ihave`](Self::ihave), but sends the given wire bytes as-is instead of
+    /// re-serializing an [`Article`](crate::article::Article), so articles are
+    /// propagated exactly as they were received.
+    ///
+    /// The bytes must already use CRLF line endings and dot-stuffing. A trailing
+    /// ".\r\n" terminator is optional; see [`commands::validate_raw_article`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - The raw article is malformed (checked before sending)
+    /// - [`NntpError::ArticleNotWanted`] - Server doesn't want the article (435)
+    /// - [`NntpError::TransferNotPossible`] - Temporary failure; retry later (436)
+    /// - [`NntpError::TransferRejected`] - Permanent rejection; do not retry (437)
+    /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    pub async fn ihave_raw(&mut self, message_id: &str, article: &[u8]) -> Result<()> {
+        debug!(
+            "IHAVE: offering article {} ({} raw bytes)",
+            message_id,
+            article.len()
+        );
+
+        // Validate before starting the exchange so a bad article never desyncs the stream
+        let article = commands::validate_raw_article(article)?;
+
+        self.begin_ihave(message_id).await?;
+        self.send_raw_article(article).await?;
+        self.finish_ihave().await
+    }
+
+    /// Phase 1 of IHAVE: check authentication, offer the message-id and expect 335
+    async fn begin_ihave(&mut self, message_id: &str) -> Result<()> {
         // Verify authenticated - IHAVE is for server-to-server transfer
         if !matches!(self.state, ConnectionState::Authenticated) {
             return Err(NntpError::Protocol {
@@ -191,56 +317,45 @@ impl NntpClient {
             });
         }
 
-        // Phase 1: Send IHAVE command with message-id
         let cmd = commands::ihave(message_id);
         self.send_command(&cmd).await?;
         let response = self.read_response().await?;
 
-        // Handle first-phase responses
-        match response.code {
-            codes::ARTICLE_NOT_WANTED => {
+        match response.response_code() {
+            ResponseCode::ArticleNotWanted => {
                 debug!("Article not wanted (code 435)");
-                return Err(NntpError::ArticleNotWanted);
+                Err(NntpError::ArticleNotWanted)
             }
-            codes::TRANSFER_NOT_POSSIBLE => {
+            ResponseCode::TransferNotPossible => {
                 debug!("Transfer not possible (code 436): {}", response.message);
-                return Err(NntpError::TransferNotPossible(response.message));
+                Err(NntpError::TransferNotPossible(response.message))
             }
-            codes::SEND_ARTICLE_TRANSFER => {
+            ResponseCode::SendArticleTransfer => {
                 debug!("Server wants article (code 335), sending...");
-                // Continue to phase 2
-            }
-            _ => {
-                return Err(NntpError::Protocol {
-                    code: response.code,
-                    message: response.message,
-                });
+                Ok(())
             }
+            _ => Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            }),
         }
+    }
 
-        // Phase 2: Send article text with dot-stuffing
-        let article_text = article.serialize_for_posting()?;
-
-        // Send the article body (already has CRLF and dot-stuffing)
-        self.send_command(&article_text).await?;
-
-        // Send terminating dot line
-        self.send_command(".\r\n").await?;
-
-        // Wait for final response
+    /// Phase 2 of IHAVE: read the transfer result after the article was sent
+    async fn finish_ihave(&mut self) -> Result<()> {
+        // Not timed adaptively, as for POST
         let response = self.read_response().await?;
 
-        // Handle second-phase responses
-        match response.code {
-            codes::ARTICLE_TRANSFERRED => {
+        match response.response_code() {
+            ResponseCode::ArticleTransferred => {
                 debug!("Article transferred successfully (code 235)");
                 Ok(())
             }
-            codes::TRANSFER_NOT_POSSIBLE => {
+            ResponseCode::TransferNotPossible => {
                 debug!("Transfer failed (code 436): {}", response.message);
                 Err(NntpError::TransferNotPossible(response.message))
             }
-            codes::TRANSFER_REJECTED => {
+            ResponseCode::TransferRejected => {
                 debug!("Transfer rejected (code 437): {}", response.message);
                 Err(NntpError::TransferRejected(response.message))
             }
@@ -0,0 +1,121 @@
+//! Transparent reconnection (opt-in via [`ServerConfig::reconnect`])
+//!
+//! When a connection is closed by the server or left broken, idempotent
+//! commands are retried on a fresh connection that is brought back to the same
+//! state: STARTTLS if it was negotiated, authentication, compression and the
+//! selected newsgroup.
+//!
+//! [`ServerConfig::reconnect`]: crate::ServerConfig::reconnect
+
+use super::NntpClient;
+use super::state::{CompressionMode, ConnectionState};
+use crate::error::{NntpError, Result};
+use crate::response::codes;
+use tracing::{debug, warn};
+
+/// Check whether an error means the connection itself is gone
+fn is_disconnect(error: &NntpError) -> bool {
+    matches!(error, NntpError::ConnectionClosed | NntpError::Io(_))
+}
+
+impl NntpClient {
+    /// Re-establish this connection and restore its session state
+    ///
+    /// Opens a new connection to the same server and repeats what was done on
+    /// the old one: STARTTLS, authentication (with the same SASL mechanism,
+    /// if one was used), compression and selecting the current newsgroup.
+    /// Quirks, parse mode, gzip detection and observer overrides apply to the
+    /// new connection before any of that, and the connection limiter slot (if
+    /// any) is carried over. On success the old connection is dropped and the
+    /// client is no longer [broken](Self::is_broken).
+    ///
+    /// This is done automatically for idempotent commands when
+    /// [`ServerConfig::reconnect`](crate::ServerConfig::reconnect) is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`connect`](Self::connect),
+    /// [`starttls`](Self::starttls), [`authenticate`](Self::authenticate) or
+    /// [`select_group`](Self::select_group). The client is left unchanged.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        debug!("Reconnecting to {}", self.config.authority());
+        let mut fresh = Self::connect(self.config.clone()).await?;
+        fresh.set_observer(self.observer.clone());
+        fresh.set_quirks(self.quirks.clone());
+        fresh.set_gzip_detection(self.gzip_detection.clone());
+        fresh.parse_mode = self.parse_mode;
+
+        // The TLS policy may already have upgraded the fresh connection
+        if self.is_tls() && !fresh.is_tls() {
+            fresh.starttls().await?;
+        }
+        if matches!(self.state, ConnectionState::Authenticated) {
+            fresh.authenticate_like(self.sasl_replay.clone()).await?;
+        }
+        if self.compression_mode != CompressionMode::None {
+            fresh.try_enable_compression().await?;
+        }
+        fresh.group_watermarks = self.group_watermarks.clone();
+        if let Some(group) = &self.current_group {
+            fresh.select_group_once(group).await?;
+        }
+
+        fresh.connection_permit = self.connection_permit.take();
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Decide whether a failed idempotent command should be retried
+    ///
+    /// Returns `Ok(())` after reconnecting if `error` is a connection failure
+    /// (or left the connection broken) and
+    /// [`ServerConfig::reconnect`](crate::ServerConfig::reconnect) allows
+    /// another attempt, or after re-authenticating on a 480 with the
+    /// [`reauth_on_480`](crate::ServerQuirks::reauth_on_480) quirk; the caller
+    /// then resends the command. Otherwise returns the error to give up with.
+    pub(super) async fn recover(&mut self, mut error: NntpError, attempt: &mut u32) -> Result<()> {
+        if self.should_reauthenticate(&error, *attempt) {
+            *attempt += 1;
+            warn!(
+                "Authentication dropped mid-session ({}), re-authenticating",
+                error
+            );
+            self.state = ConnectionState::Ready;
+            self.authenticate_like(self.sasl_replay.clone()).await?;
+            return Ok(());
+        }
+
+        let Some(policy) = self.config.reconnect else {
+            return Err(error);
+        };
+        if !is_disconnect(&error) && !self.is_broken() {
+            return Err(error);
+        }
+
+        // Keep trying to reconnect until one succeeds or attempts run out
+        while *attempt < policy.max_attempts {
+            *attempt += 1;
+            warn!(
+                "Connection lost ({}), reconnect attempt {}/{}",
+                error, attempt, policy.max_attempts
+            );
+            tokio::time::sleep(policy.delay(*attempt)).await;
+            match self.reconnect().await {
+                Ok(()) => return Ok(()),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+
+    /// Check whether a 480 should be answered by logging in again
+    ///
+    /// Only on the first attempt, so a server that keeps answering 480
+    /// after a successful login does not loop.
+    fn should_reauthenticate(&self, error: &NntpError, attempt: u32) -> bool {
+        self.quirks.reauth_on_480
+            && attempt == 0
+            && matches!(self.state, ConnectionState::Authenticated)
+            && matches!(error, NntpError::Protocol { code, .. } if *code == codes::AUTH_REQUIRED)
+    }
+}
@@ -9,9 +9,15 @@ use crate::article::Article;
 use crate::capabilities::Capabilities;
 use crate::commands;
 use crate::error::{NntpError, Result};
-use crate::response::{NntpResponse, codes};
+use crate::ratelimit::BandwidthLimiter;
+use crate::response::{NntpResponse, ResponseCode};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::time::Duration;
 use tracing::debug;
 
+/// CHECK commands in flight during [`NntpClient::check_many`]
+const CHECK_PIPELINE_DEPTH: usize = 64;
+
 impl NntpClient {
     /// Request server capabilities (RFC 3977 Section 5.2)
     ///
@@ -19,6 +25,9 @@ impl NntpClient {
     /// This command can be used to detect which extensions and features
     /// the server supports before attempting to use them.
     ///
+    /// Always sends CAPABILITIES; the result also replaces the cached
+    /// [`capability_set`](Self::capability_set).
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -47,7 +56,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::CAPABILITY_LIST {
+        if response.response_code() != ResponseCode::CapabilityList {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -56,9 +65,55 @@ impl NntpClient {
 
         let caps = Capabilities::parse(&response.lines);
         debug!("Received {} capabilities", caps.list().len());
+        self.capabilities = Some(caps.clone());
         Ok(caps)
     }
 
+    /// Server capabilities, requested only when not cached
+    ///
+    /// The first call sends CAPABILITIES and keeps the result on the
+    /// connection. RFC 3977 lets the list change after MODE READER,
+    /// STARTTLS and authentication, so the cache is discarded by
+    /// [`mode_reader`](Self::mode_reader), [`starttls`](Self::starttls),
+    /// [`authenticate`](Self::authenticate) and
+    /// [`authenticate_sasl`](Self::authenticate_sasl), and the next call
+    /// asks the server again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// if client.capability_set().await?.has("STREAMING") {
+    ///     client.mode_stream().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Same as [`capabilities`](Self::capabilities) when the set is not cached.
+    pub async fn capability_set(&mut self) -> Result<&Capabilities> {
+        let caps = match self.capabilities.take() {
+            Some(caps) => caps,
+            None => self.capabilities().await?,
+        };
+        Ok(self.capabilities.insert(caps))
+    }
+
+    /// Cached server capabilities, without contacting the server
+    ///
+    /// `None` until [`capabilities`](Self::capabilities) or
+    /// [`capability_set`](Self::capability_set) has been called, and again
+    /// after a state change that may alter them.
+    pub fn cached_capabilities(&self) -> Option<&Capabilities> {
+        self.capabilities.as_ref()
+    }
+
     /// Switch to reader mode (RFC 3977 Section 5.3)
     ///
     /// Instructs the server to switch to reader mode, indicating this is a news
@@ -98,12 +153,16 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_response().await?;
 
-        match response.code {
-            codes::READY_POSTING_ALLOWED => {
+        if response.is_success() {
+            // A mode-switching server advertises a different list afterwards
+            self.capabilities = None;
+        }
+        match response.response_code() {
+            ResponseCode::ReadyPostingAllowed => {
                 debug!("Reader mode enabled - posting allowed");
                 Ok(true)
             }
-            codes::READY_NO_POSTING => {
+            ResponseCode::ReadyNoPosting => {
                 debug!("Reader mode enabled - posting not allowed");
                 Ok(false)
             }
@@ -154,7 +213,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_response().await?;
 
-        if response.code != codes::STREAMING_OK {
+        if response.response_code() != ResponseCode::StreamingOk {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -191,7 +250,7 @@ impl NntpClient {
     /// # Example
     ///
     /// ```no_run
-    /// # use nntp_rs::{NntpClient, ServerConfig, codes};
+    /// # use nntp_rs::{NntpClient, ResponseCode, ServerConfig};
     /// # use std::sync::Arc;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
@@ -203,19 +262,19 @@ impl NntpClient {
     /// let message_id = "<article123@example.com>";
     /// let response = client.check(message_id).await?;
     ///
-    /// match response.code {
-    ///     codes::CHECK_SEND => {
+    /// match response.response_code() {
+    ///     ResponseCode::CheckSend => {
     ///         println!("Server wants article - send with TAKETHIS");
     ///         // client.takethis(message_id, article_data).await?;
     ///     }
-    ///     codes::CHECK_LATER => {
+    ///     ResponseCode::CheckLater => {
     ///         println!("Server busy - retry later");
     ///     }
-    ///     codes::CHECK_NOT_WANTED => {
+    ///     ResponseCode::CheckNotWanted => {
     ///         println!("Server doesn't want article");
     ///     }
-    ///     _ => {
-    ///         println!("Unexpected response: {}", response.code);
+    ///     other => {
+    ///         println!("Unexpected response: {}", other);
     ///     }
     /// }
     /// # Ok(())
@@ -242,6 +301,81 @@ impl NntpClient {
         Ok(response)
     }
 
+    /// Offer many articles with pipelined CHECK commands (RFC 4644 Section 2.4)
+    ///
+    /// Keeps a window of CHECK commands outstanding instead of waiting for
+    /// each answer, which is what streaming mode is for. Results are in
+    /// request order, each matched to its message-id.
+    ///
+    /// **Note:** You must call [`mode_stream()`](Self::mode_stream) before using CHECK.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{CheckStatus, NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// client.mode_stream().await?;
+    ///
+    /// let offered = ["<a@example.com>", "<b@example.com>"];
+    /// for result in client.check_many(&offered).await? {
+    ///     if result.status == CheckStatus::Send {
+    ///         println!("Server wants {}", result.message_id);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Every outstanding response is read before an error is returned, so the
+    /// connection stays in step.
+    /// - [`NntpError::Protocol`] - Server answered a CHECK with something other than 238, 431 or 438
+    /// - [`NntpError::InvalidResponse`] - Server echoed a different message-id
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    /// - Network I/O errors
+    pub async fn check_many(&mut self, message_ids: &[&str]) -> Result<Vec<commands::CheckResult>> {
+        debug!("CHECK: {} articles", message_ids.len());
+
+        let mut results = Vec::with_capacity(message_ids.len());
+        let mut failure = None;
+        let mut sent = 0;
+        let mut answered = 0;
+        let mut batch = Vec::with_capacity(CHECK_PIPELINE_DEPTH);
+        while answered < sent || (failure.is_none() && sent < message_ids.len()) {
+            // Stop offering once something went wrong, but drain what was sent
+            if failure.is_none() {
+                while sent - answered < CHECK_PIPELINE_DEPTH
+                    && let Some(id) = message_ids.get(sent)
+                {
+                    batch.push(commands::check(id));
+                    sent += 1;
+                }
+                if !batch.is_empty() {
+                    self.send_commands(&batch).await?;
+                    batch.clear();
+                }
+            }
+
+            let response = self.read_response().await?;
+            match commands::parse_check_response(message_ids[answered], response) {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+            answered += 1;
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(results),
+        }
+    }
+
     /// Send an article to the server in streaming mode (RFC 4644 Section 2.5)
     ///
     /// In streaming mode, sends an article to the server without waiting for permission.
@@ -269,7 +403,7 @@ impl NntpClient {
     /// # Example
     ///
     /// ```no_run
-    /// # use nntp_rs::{NntpClient, ServerConfig, ArticleBuilder, codes};
+    /// # use nntp_rs::{NntpClient, ServerConfig, ArticleBuilder, ResponseCode};
     /// # use std::sync::Arc;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
@@ -290,15 +424,15 @@ impl NntpClient {
     /// // Send the article without asking first
     /// let response = client.takethis(&message_id, &article).await?;
     ///
-    /// match response.code {
-    ///     codes::TAKETHIS_RECEIVED => {
+    /// match response.response_code() {
+    ///     ResponseCode::TakethisReceived => {
     ///         println!("Article received successfully");
     ///     }
-    ///     codes::TAKETHIS_REJECTED => {
+    ///     ResponseCode::TakethisRejected => {
     ///         println!("Article rejected by server");
     ///     }
-    ///     _ => {
-    ///         println!("Unexpected response: {}", response.code);
+    ///     other => {
+    ///         println!("Unexpected response: {}", other);
     ///     }
     /// }
     /// # Ok(())
@@ -313,13 +447,75 @@ impl NntpClient {
     /// - Network I/O errors
     /// - Article serialization fails
     pub async fn takethis(&mut self, message_id: &str, article: &Article) -> Result<NntpResponse> {
+        self.takethis_chunked(message_id, article, None).await
+    }
+
+    /// Send an article in streaming mode with its upload throttled (RFC 4644 Section 2.5)
+    ///
+    /// Like [`takethis`](Self::takethis), but acquires bandwidth from `limiter` for
+    /// each chunk of the article before writing it.
+    ///
+    /// **Note:** You must call [`mode_stream()`](Self::mode_stream) before using TAKETHIS.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`takethis`](Self::takethis).
+    pub async fn takethis_throttled(
+        &mut self,
+        message_id: &str,
+        article: &Article,
+        limiter: &BandwidthLimiter,
+    ) -> Result<NntpResponse> {
+        self.takethis_chunked(message_id, article, Some(limiter))
+            .await
+    }
+
+    /// Send TAKETHIS, streaming the article in chunks
+    async fn takethis_chunked(
+        &mut self,
+        message_id: &str,
+        article: &Article,
+        limiter: Option<&BandwidthLimiter>,
+    ) -> Result<NntpResponse> {
         debug!("TAKETHIS: {}", message_id);
 
-        // Serialize article with CRLF and dot-stuffing
-        let article_data = article.serialize_for_posting()?;
+        let cmd = commands::takethis_line(message_id);
+        self.send_command(&cmd).await?;
+        self.send_article_chunks(article, limiter).await?;
+        let response = self.read_response().await?;
+
+        debug!(
+            "TAKETHIS response for {}: {} {}",
+            message_id, response.code, response.message
+        );
+        Ok(response)
+    }
+
+    /// Send a pre-serialized article in streaming mode (RFC 4644 Section 2.5)
+    ///
+    /// Like [`takethis`](Self::takethis), but sends the given wire bytes as-is instead of
+    /// re-serializing an [`Article`]. Feeders relaying articles between servers should
+    /// use this to preserve the exact bytes received from upstream.
+    ///
+    /// The bytes must already use CRLF line endings and dot-stuffing. A trailing
+    /// ".\r\n" terminator is optional; see [`commands::validate_raw_article`].
+    ///
+    /// **Note:** You must call [`mode_stream()`](Self::mode_stream) before using TAKETHIS.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::InvalidArgument`] - The raw article is malformed (checked before sending)
+    /// - [`NntpError::Timeout`] - Server did not respond in time
+    /// - Network I/O errors
+    pub async fn takethis_raw(&mut self, message_id: &str, article: &[u8]) -> Result<NntpResponse> {
+        debug!("TAKETHIS: {} ({} raw bytes)", message_id, article.len());
+
+        let article = commands::validate_raw_article(article)?;
 
-        let cmd = commands::takethis(message_id, &article_data);
+        let cmd = commands::takethis_line(message_id);
         self.send_command(&cmd).await?;
+        self.send_raw_article(article).await?;
         let response = self.read_response().await?;
 
         debug!(
@@ -331,10 +527,9 @@ impl NntpClient {
 
     /// Get server date/time (RFC 3977 Section 7.1)
     ///
-    /// Requests the server's current date and time in UTC.
-    ///
-    /// Returns the server timestamp in the format `YYYYMMDDhhmmss`
-    /// (e.g., "20240115123456" represents January 15, 2024 at 12:34:56 UTC).
+    /// Requests the server's current date and time in UTC, parsed from the
+    /// `YYYYMMDDhhmmss` form the server sends (e.g. "20240115123456" is
+    /// January 15, 2024 at 12:34:56 UTC).
     ///
     /// # Example
     ///
@@ -354,15 +549,56 @@ impl NntpClient {
     ///
     /// Returns an error if:
     /// - [`NntpError::Protocol`] - Server returned an unexpected error
+    /// - [`NntpError::InvalidResponse`] - The timestamp is not a valid date
     /// - [`NntpError::Timeout`] - Server did not respond in time
-    pub async fn date(&mut self) -> Result<String> {
+    pub async fn date(&mut self) -> Result<DateTime<Utc>> {
+        let timestamp = self.date_timestamp().await?;
+        commands::parse_date_response(&timestamp)
+    }
+
+    /// Difference between the server clock and the local clock
+    ///
+    /// Positive when the server is ahead. The local time is taken halfway
+    /// through the DATE round trip; DATE only has one-second resolution, so
+    /// the result is accurate to about a second. Date-based commands such as
+    /// [`newnews`](Self::newnews) take server time, so a sync that stores the
+    /// local time of its last run should shift it by the skew.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let skew = client.clock_skew().await?;
+    /// if skew.num_seconds().abs() > 60 {
+    ///     println!("Server clock is off by {}s", skew.num_seconds());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Same as [`date`](Self::date).
+    pub async fn clock_skew(&mut self) -> Result<TimeDelta> {
+        let sent = Utc::now();
+        let server = self.date().await?;
+        let received = Utc::now();
+        Ok(server - (sent + (received - sent) / 2))
+    }
+
+    /// Send DATE and return the timestamp as sent by the server
+    async fn date_timestamp(&mut self) -> Result<String> {
         debug!("Requesting server date/time");
 
         let cmd = commands::date();
         self.send_command(cmd).await?;
         let response = self.read_response().await?;
 
-        if response.code != codes::SERVER_DATE {
+        if response.response_code() != ResponseCode::ServerDate {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
@@ -381,6 +617,62 @@ impl NntpClient {
         Ok(timestamp)
     }
 
+    /// Time since the last command was sent on this connection
+    pub fn idle_time(&self) -> Duration {
+        self.last_activity.elapsed()
+    }
+
+    /// Send DATE if the connection has been idle for the keepalive interval
+    ///
+    /// Keeps long-lived connections from being dropped by provider idle
+    /// timeouts. Does nothing unless
+    /// [`ServerConfig::keepalive`](crate::ServerConfig::keepalive) is set and
+    /// [`idle_time`](Self::idle_time) has reached it.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a keepalive was sent, `false` if none was due.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = ServerConfig::tls("news.example.com", "user", "pass")
+    ///     .with_keepalive(Duration::from_secs(60));
+    /// let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// // ... later, from a periodic task ...
+    /// client.keepalive().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DATE command fails. The connection is then
+    /// marked [broken](Self::is_broken).
+    pub async fn keepalive(&mut self) -> Result<bool> {
+        let Some(interval) = self.config.keepalive else {
+            return Ok(false);
+        };
+        if self.idle_time() < interval {
+            return Ok(false);
+        }
+
+        debug!(
+            "Connection idle for {:?}, sending keepalive",
+            self.idle_time()
+        );
+        // Any 111 reply proves the connection alive, parseable or not
+        if let Err(e) = self.date_timestamp().await {
+            self.mark_broken();
+            return Err(e);
+        }
+        Ok(true)
+    }
+
     /// Request help text from the server (RFC 3977 §7.2)
     ///
     /// Returns multi-line help text from the server describing available commands
@@ -414,7 +706,7 @@ impl NntpClient {
         self.send_command(cmd).await?;
         let response = self.read_multiline_response().await?;
 
-        if response.code != codes::HELP_TEXT_FOLLOWS {
+        if response.response_code() != ResponseCode::HelpTextFollows {
             return Err(NntpError::Protocol {
                 code: response.code,
                 message: response.message,
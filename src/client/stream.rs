@@ -0,0 +1,243 @@
+//! Transport stream for NNTP connections
+//!
+//! A connection starts either in plain TCP or with implicit TLS, and a plain
+//! connection can be upgraded in place with STARTTLS (RFC 4642). Either can
+//! then be wrapped in full-session compression (RFC 8054). The TCP socket at
+//! the bottom is [`Metered`] so traffic is counted whatever is layered on top.
+
+use std::io::{self, IoSlice};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+
+use super::deflate::DeflateStream;
+use crate::observer::ConnectionObserver;
+
+/// TCP socket that counts the bytes moved and reports them to an observer
+pub(super) struct Metered {
+    inner: TcpStream,
+    bytes_in: u64,
+    bytes_out: u64,
+    observer: Option<Arc<dyn ConnectionObserver>>,
+}
+
+impl Metered {
+    pub(super) fn new(inner: TcpStream, observer: Option<Arc<dyn ConnectionObserver>>) -> Self {
+        Self {
+            inner,
+            bytes_in: 0,
+            bytes_out: 0,
+            observer,
+        }
+    }
+}
+
+impl AsyncRead for Metered {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let before = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        let n = buf.filled().len() - before;
+        this.bytes_in += n as u64;
+        if let Some(observer) = &this.observer
+            && n > 0
+        {
+            observer.on_bytes_in(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for Metered {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.bytes_out += n as u64;
+        if let Some(observer) = &this.observer
+            && n > 0
+        {
+            observer.on_bytes_out(n);
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(Pin::new(&mut this.inner).poll_write_vectored(cx, bufs))?;
+        this.bytes_out += n as u64;
+        if let Some(observer) = &this.observer
+            && n > 0
+        {
+            observer.on_bytes_out(n);
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// Plain or TLS transport underneath the client's buffered reader
+pub(super) enum NntpStream {
+    /// Unencrypted TCP (port 119, or before STARTTLS)
+    Plain(Metered),
+    /// TLS over TCP (implicit TLS, or after STARTTLS)
+    Tls(Box<TlsStream<Metered>>),
+    /// Either of the above after COMPRESS DEFLATE
+    Deflate(Box<DeflateStream<NntpStream>>),
+    /// The transport was taken for a TLS upgrade that did not complete
+    Closed,
+}
+
+impl NntpStream {
+    /// Whether the transport is encrypted
+    pub(super) fn is_tls(&self) -> bool {
+        match self {
+            Self::Tls(_) => true,
+            Self::Deflate(stream) => stream.get_ref().is_tls(),
+            Self::Plain(_) | Self::Closed => false,
+        }
+    }
+
+    /// The socket at the bottom of the transport, if still connected
+    fn metered(&self) -> Option<&Metered> {
+        match self {
+            Self::Plain(stream) => Some(stream),
+            Self::Tls(stream) => Some(stream.get_ref().0),
+            Self::Deflate(stream) => stream.get_ref().metered(),
+            Self::Closed => None,
+        }
+    }
+
+    fn metered_mut(&mut self) -> Option<&mut Metered> {
+        match self {
+            Self::Plain(stream) => Some(stream),
+            Self::Tls(stream) => Some(stream.get_mut().0),
+            Self::Deflate(stream) => stream.get_mut().metered_mut(),
+            Self::Closed => None,
+        }
+    }
+
+    /// Returns `(received, sent)` bytes on the socket
+    pub(super) fn socket_totals(&self) -> (u64, u64) {
+        self.metered()
+            .map_or((0, 0), |stream| (stream.bytes_in, stream.bytes_out))
+    }
+
+    /// Report socket traffic to `observer` from now on
+    pub(super) fn set_observer(&mut self, observer: Option<Arc<dyn ConnectionObserver>>) {
+        if let Some(stream) = self.metered_mut() {
+            stream.observer = observer;
+        }
+    }
+
+    /// Returns `(compressed, decompressed)` bytes received under full-session compression
+    pub(super) fn deflate_totals(&self) -> (u64, u64) {
+        match self {
+            Self::Deflate(stream) => stream.received_totals(),
+            _ => (0, 0),
+        }
+    }
+}
+
+fn not_connected() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotConnected,
+        "connection closed after failed TLS upgrade",
+    )
+}
+
+impl AsyncRead for NntpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            Self::Deflate(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            Self::Closed => Poll::Ready(Err(not_connected())),
+        }
+    }
+}
+
+impl AsyncWrite for NntpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            Self::Deflate(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            Self::Closed => Poll::Ready(Err(not_connected())),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_write_vectored(cx, bufs),
+            Self::Deflate(stream) => Pin::new(stream.as_mut()).poll_write_vectored(cx, bufs),
+            Self::Closed => Poll::Ready(Err(not_connected())),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Plain(stream) => stream.is_write_vectored(),
+            Self::Tls(stream) => stream.is_write_vectored(),
+            Self::Deflate(stream) => stream.is_write_vectored(),
+            Self::Closed => false,
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            Self::Deflate(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            Self::Closed => Poll::Ready(Err(not_connected())),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            Self::Deflate(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            Self::Closed => Poll::Ready(Ok(())),
+        }
+    }
+}
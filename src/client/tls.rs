@@ -0,0 +1,241 @@
+//! TLS client configuration
+//!
+//! Builds the rustls [`ClientConfig`] for a [`ServerConfig`]: webpki roots
+//! plus optional extra PEM roots, a pinned certificate fingerprint, or (when
+//! explicitly allowed) no verification at all.
+
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{self, CryptoProvider, WebPkiSupportedAlgorithms, ring};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tracing::{debug, warn};
+
+use super::connection::DangerousAcceptAnyCertificate;
+use crate::config::ServerConfig;
+use crate::error::{NntpError, Result};
+
+/// Build the TLS client configuration for a server
+pub(super) fn client_config(config: &ServerConfig) -> Result<ClientConfig> {
+    // Install default crypto provider if not already installed
+    let _ = CryptoProvider::install_default(ring::default_provider());
+
+    if config.allow_insecure_tls {
+        // Insecure mode: accept any certificate (for self-signed certificates)
+        warn!("TLS certificate validation disabled - connection vulnerable to MITM attacks");
+        return Ok(ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(DangerousAcceptAnyCertificate))
+            .with_no_client_auth());
+    }
+
+    if let Some(fingerprint) = &config.tls_pinned_sha256 {
+        debug!("Pinning server certificate to SHA-256 {}", fingerprint);
+        let verifier = PinnedCertificateVerifier::new(parse_fingerprint(fingerprint)?);
+        return Ok(ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth());
+    }
+
+    // Secure mode: validate certificates against trusted root CAs
+    let mut root_store = RootCertStore::empty();
+    root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(pem) = &config.tls_root_certs_pem {
+        let added = add_pem_roots(&mut root_store, pem)?;
+        debug!("Added {} custom root certificate(s)", added);
+    }
+
+    Ok(ClientConfig::builder()
+        .with_root_certificates(root_store)
+        .with_no_client_auth())
+}
+
+/// Add every certificate in a PEM bundle to the root store
+///
+/// Returns the number of certificates added.
+fn add_pem_roots(root_store: &mut RootCertStore, pem: &str) -> Result<usize> {
+    let certs = CertificateDer::pem_slice_iter(pem.as_bytes())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| NntpError::Tls(format!("Invalid root certificate PEM: {}", e)))?;
+    if certs.is_empty() {
+        return Err(NntpError::Tls(
+            "No certificates found in root certificate PEM".to_string(),
+        ));
+    }
+
+    let count = certs.len();
+    for cert in certs {
+        root_store
+            .add(cert)
+            .map_err(|e| NntpError::Tls(format!("Invalid root certificate: {}", e)))?;
+    }
+    Ok(count)
+}
+
+/// Parse a SHA-256 fingerprint in hex, with optional `:` separators
+pub(super) fn parse_fingerprint(fingerprint: &str) -> Result<[u8; 32]> {
+    let invalid = || {
+        NntpError::Tls(format!(
+            "Invalid pinned certificate fingerprint (expected 32 hex bytes): {}",
+            fingerprint
+        ))
+    };
+
+    let digits: Vec<u8> = fingerprint
+        .bytes()
+        .filter(|b| *b != b':' && !b.is_ascii_whitespace())
+        .collect();
+    if digits.len() != 64 {
+        return Err(invalid());
+    }
+
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(digits.chunks_exact(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+/// Certificate verifier that trusts exactly one certificate by fingerprint
+///
+/// Chain and hostname validation are skipped: the pin identifies the
+/// certificate itself. Handshake signatures are still verified against the
+/// pinned certificate's key, so a peer cannot present the certificate
+/// without holding its private key.
+#[derive(Debug)]
+pub(super) struct PinnedCertificateVerifier {
+    fingerprint: [u8; 32],
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl PinnedCertificateVerifier {
+    pub(super) fn new(fingerprint: [u8; 32]) -> Self {
+        Self {
+            fingerprint,
+            algorithms: ring::default_provider().signature_verification_algorithms,
+        }
+    }
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        let actual: [u8; 32] = Sha256::digest(end_entity.as_ref()).into();
+        if actual == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            warn!("Server certificate does not match the pinned fingerprint");
+            Err(tokio_rustls::rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fingerprint_formats() {
+        let hex = "3a1f5c9d0e227b41886af09312c45e7dab0936e14c7258bd901ef6a3276cd845";
+        let expected = parse_fingerprint(hex).unwrap();
+        assert_eq!(expected[0], 0x3a);
+        assert_eq!(expected[31], 0x45);
+
+        let colons = "3A:1F:5C:9D:0E:22:7B:41:88:6A:F0:93:12:C4:5E:7D:AB:09:36:E1:4C:72:58:BD:90:1E:F6:A3:27:6C:D8:45";
+        assert_eq!(parse_fingerprint(colons).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_fingerprint_rejects_invalid() {
+        assert!(parse_fingerprint("").is_err());
+        assert!(parse_fingerprint("abcd").is_err());
+        assert!(parse_fingerprint(&"zz".repeat(32)).is_err());
+        assert!(parse_fingerprint(&"ab".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn test_pinned_verifier_matches_fingerprint() {
+        let cert = CertificateDer::from(vec![1u8, 2, 3, 4]);
+        let fingerprint: [u8; 32] = Sha256::digest([1u8, 2, 3, 4]).into();
+        let server_name = ServerName::try_from("news.example.com").unwrap();
+        let now = UnixTime::now();
+
+        let verifier = PinnedCertificateVerifier::new(fingerprint);
+        assert!(
+            verifier
+                .verify_server_cert(&cert, &[], &server_name, &[], now)
+                .is_ok()
+        );
+
+        let other = CertificateDer::from(vec![5u8, 6, 7, 8]);
+        assert!(
+            verifier
+                .verify_server_cert(&other, &[], &server_name, &[], now)
+                .is_err()
+        );
+        assert!(!verifier.supported_verify_schemes().is_empty());
+    }
+
+    #[test]
+    fn test_root_pem_errors() {
+        let mut store = RootCertStore::empty();
+        assert!(add_pem_roots(&mut store, "").is_err());
+        assert!(add_pem_roots(&mut store, "not a certificate").is_err());
+        let garbage = "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n";
+        assert!(add_pem_roots(&mut store, garbage).is_err());
+    }
+
+    #[test]
+    fn test_client_config_modes() {
+        let config = ServerConfig::tls("news.example.com", "user", "pass");
+        assert!(client_config(&config).is_ok());
+
+        let pinned = config.clone().with_pinned_certificate("ab".repeat(32));
+        assert!(client_config(&pinned).is_ok());
+
+        let bad_pin = config.clone().with_pinned_certificate("nope");
+        assert!(matches!(client_config(&bad_pin), Err(NntpError::Tls(_))));
+
+        let bad_pem = config.with_root_certs_pem("garbage");
+        assert!(matches!(client_config(&bad_pem), Err(NntpError::Tls(_))));
+    }
+}
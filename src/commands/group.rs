@@ -25,6 +25,7 @@ pub fn newgroups_gmt(date: &str, time: &str) -> String {
 ///
 /// Contains article count and range information for a newsgroup.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupInfo {
     /// Number of articles in the group
     pub count: u64,
@@ -34,6 +35,17 @@ pub struct GroupInfo {
     pub last: u64,
 }
 
+/// Per-group result of a bulk group snapshot
+///
+/// Produced by [`NntpClient::group_snapshot`](crate::NntpClient::group_snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSnapshot {
+    /// Newsgroup name as requested
+    pub name: String,
+    /// Counts and watermarks, or `None` if the group does not exist on the server
+    pub info: Option<GroupInfo>,
+}
+
 /// Parse GROUP response to extract article count and range
 ///
 /// Response format: "211 count first last group-name"
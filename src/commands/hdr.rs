@@ -103,6 +103,76 @@ pub fn parse_hdr_response(response: NntpResponse) -> Result<Vec<HdrEntry>> {
     Ok(entries)
 }
 
+/// Extract selected header fields from raw header lines (e.g. a HEAD response)
+///
+/// Field names are matched case-insensitively and folded values are unfolded.
+/// Returns `(field, value)` pairs in the order of `fields`, using the field
+/// names as given. Fields absent from the headers are omitted; if a field
+/// occurs more than once, the first occurrence wins.
+///
+/// # Examples
+///
+/// ```
+/// # use nntp_rs::commands::extract_header_fields;
+/// let lines = vec![
+///     "From: poster@example.com".to_string(),
+///     "Subject: A long".to_string(),
+///     "\tfolded subject".to_string(),
+///     "Newsgroups: a.b,c.d".to_string(),
+/// ];
+/// let fields = extract_header_fields(&lines, &["subject", "Date", "From"]);
+/// assert_eq!(
+///     fields,
+///     vec![
+///         ("subject".to_string(), "A long folded subject".to_string()),
+///         ("From".to_string(), "poster@example.com".to_string()),
+///     ]
+/// );
+/// ```
+pub fn extract_header_fields(header_lines: &[String], fields: &[&str]) -> Vec<(String, String)> {
+    let mut found: Vec<Option<String>> = vec![None; fields.len()];
+    let mut current: Option<(usize, String)> = None;
+
+    for line in header_lines {
+        // Header section ends at the first empty line
+        if line.is_empty() {
+            break;
+        }
+
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some((_, value)) = current.as_mut() {
+                value.push('\n');
+                value.push_str(line);
+            }
+            continue;
+        }
+
+        if let Some((index, value)) = current.take() {
+            found[index] = Some(crate::article::unfold_header(&value));
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        current = fields
+            .iter()
+            .position(|field| field.eq_ignore_ascii_case(name))
+            .filter(|&index| found[index].is_none())
+            .map(|index| (index, value.trim_start().to_string()));
+    }
+
+    if let Some((index, value)) = current {
+        found[index] = Some(crate::article::unfold_header(&value));
+    }
+
+    fields
+        .iter()
+        .zip(found)
+        .filter_map(|(field, value)| value.map(|value| ((*field).to_string(), value)))
+        .collect()
+}
+
 /// Parse LIST HEADERS response (RFC 3977 §8.6)
 ///
 /// Returns a list of header field names available for the HDR command.
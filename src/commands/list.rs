@@ -29,6 +29,14 @@ pub fn list_active_times(wildmat: &str) -> String {
     format!("LIST ACTIVE.TIMES {}\r\n", wildmat)
 }
 
+/// Build LIST DISTRIB.PATS command (RFC 3977 §7.6.5)
+///
+/// Lists the patterns used to pick a default Distribution header.
+/// No arguments are permitted.
+pub fn list_distrib_pats() -> &'static str {
+    "LIST DISTRIB.PATS\r\n"
+}
+
 /// Build LIST NEWSGROUPS command (RFC 3977 §7.6.6)
 ///
 /// Lists newsgroup descriptions.
@@ -118,27 +126,35 @@ pub fn parse_list_active_response(
         });
     }
 
-    let mut groups = Vec::new();
-    for line in &response.lines {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
-            continue; // Skip malformed lines
-        }
-
-        let high = parts[1].parse().unwrap_or(0);
-        let low = parts[2].parse().unwrap_or(0);
-        // Status can be multi-character (e.g., "=group.name" alias)
-        let status = parts[3].to_string();
+    let groups = response
+        .lines
+        .iter()
+        .filter_map(|line| parse_list_active_line(line))
+        .collect();
+    Ok(groups)
+}
 
-        groups.push(crate::commands::group::ActiveGroup {
-            name: parts[0].to_string(),
-            high,
-            low,
-            status,
-        });
+/// Parse one line of a LIST ACTIVE response ("group high low status")
+///
+/// Returns `None` for a line with fewer than four fields. Unparseable
+/// watermarks are taken as 0.
+pub fn parse_list_active_line(line: &str) -> Option<crate::commands::group::ActiveGroup> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 4 {
+        return None;
     }
 
-    Ok(groups)
+    let high = parts[1].parse().unwrap_or(0);
+    let low = parts[2].parse().unwrap_or(0);
+    // Status can be multi-character (e.g., "=group.name" alias)
+    let status = parts[3].to_string();
+
+    Some(crate::commands::group::ActiveGroup {
+        name: parts[0].to_string(),
+        high,
+        low,
+        status,
+    })
 }
 
 /// Newsgroup entry from LIST COUNTS (RFC 6048 Section 3)
@@ -454,3 +470,69 @@ pub fn parse_list_active_times_response(response: NntpResponse) -> Result<Vec<Gr
 
     Ok(groups)
 }
+
+/// Default distribution pattern from LIST DISTRIB.PATS (RFC 3977 Section 7.6.5)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistribPat {
+    /// Priority of this entry; among the patterns matching a newsgroup the
+    /// one with the highest weight wins
+    pub weight: u32,
+    /// Wildmat pattern matched against newsgroup names
+    pub wildmat: String,
+    /// Distribution header value to use for matching newsgroups
+    pub distribution: String,
+}
+
+/// Parse LIST DISTRIB.PATS response into DistribPat entries (RFC 3977 Section 7.6.5)
+///
+/// Format: "weight:wildmat:value"
+///
+/// # Examples
+///
+/// ```
+/// # use nntp_rs::{commands, codes, NntpResponse};
+/// let response = NntpResponse {
+///     code: codes::LIST_INFORMATION_FOLLOWS,
+///     message: "Default distributions in form \"weight:wildmat:value\"".to_string(),
+///     lines: vec![
+///         "10:local.*:local".to_string(),
+///         "5:*:world".to_string(),
+///         "20:local.here.*:thissite".to_string(),
+///     ],
+/// };
+///
+/// let pats = commands::parse_list_distrib_pats_response(response).unwrap();
+/// assert_eq!(pats.len(), 3);
+/// assert_eq!(pats[0].weight, 10);
+/// assert_eq!(pats[0].wildmat, "local.*");
+/// assert_eq!(pats[0].distribution, "local");
+/// ```
+pub fn parse_list_distrib_pats_response(response: NntpResponse) -> Result<Vec<DistribPat>> {
+    if !response.is_success() {
+        return Err(NntpError::Protocol {
+            code: response.code,
+            message: response.message,
+        });
+    }
+
+    let mut pats = Vec::new();
+    for line in &response.lines {
+        let mut fields = line.splitn(3, ':');
+        let (Some(weight), Some(wildmat), Some(distribution)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue; // Skip lines without both separators
+        };
+        let Ok(weight) = weight.trim().parse() else {
+            continue; // Skip lines with a non-numeric weight
+        };
+
+        pats.push(DistribPat {
+            weight,
+            wildmat: wildmat.to_string(),
+            distribution: distribution.to_string(),
+        });
+    }
+
+    Ok(pats)
+}
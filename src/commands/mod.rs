@@ -8,6 +8,9 @@ pub mod list;
 pub mod over;
 pub mod response;
 
+use crate::error::{NntpError, Result};
+use crate::response::{NntpResponse, ResponseCode};
+
 // Re-export all public items for backward compatibility
 pub use article::*;
 pub use group::*;
@@ -47,13 +50,8 @@ pub fn authinfo_sasl_continue(response: &str) -> String {
 
 /// Build STARTTLS command (RFC 4642)
 ///
-/// Initiates TLS negotiation on the connection.
-///
-/// Intentionally unused (RFC 4642 API completeness): TLS support in this library is
-/// handled at the connection layer via the `use_tls` configuration option (implicit TLS),
-/// not via STARTTLS (explicit upgrade). Most modern deployments use implicit TLS on
-/// port 563 rather than STARTTLS on port 119. This function is provided for complete
-/// RFC 4642 compliance and may be used in future if explicit TLS upgrade is implemented.
+/// Initiates TLS negotiation on the connection. Used by
+/// [`NntpClient::starttls`](crate::NntpClient::starttls) to upgrade a plain connection.
 pub fn starttls() -> &'static str {
     "STARTTLS\r\n"
 }
@@ -119,6 +117,32 @@ pub fn date() -> &'static str {
     "DATE\r\n"
 }
 
+/// Parse the `yyyymmddhhmmss` timestamp of a DATE response (RFC 3977 §7.1)
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::commands::parse_date_response;
+///
+/// let date = parse_date_response("20240115123456").unwrap();
+/// assert_eq!(date.to_rfc3339(), "2024-01-15T12:34:56+00:00");
+/// assert!(parse_date_response("2024-01-15").is_err());
+/// ```
+pub fn parse_date_response(message: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    let timestamp = message.split_whitespace().next().unwrap_or_default();
+    if timestamp.len() != 14 || !timestamp.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(NntpError::InvalidResponse(format!(
+            "Invalid DATE response: {}",
+            message
+        )));
+    }
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d%H%M%S")
+        .map(|date| date.and_utc())
+        .map_err(|e| {
+            NntpError::InvalidResponse(format!("Invalid DATE response {}: {}", message, e))
+        })
+}
+
 /// Build MODE READER command (RFC 3977 §5.3)
 ///
 /// Instructs the server to switch to reader mode (for news reading clients).
@@ -165,6 +189,58 @@ pub fn check(message_id: &str) -> String {
     format!("CHECK {}\r\n", message_id)
 }
 
+/// What a server wants done with an offered article (RFC 4644 Section 2.4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// 238 - Send the article with TAKETHIS
+    Send,
+    /// 431 - Offer the article again later
+    Later,
+    /// 438 - The article is not wanted
+    NotWanted,
+}
+
+/// Answer to one CHECK command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// Message-ID of the offered article
+    pub message_id: String,
+    /// What the server wants done with it
+    pub status: CheckStatus,
+}
+
+/// Parse the response to `CHECK message_id` (RFC 4644 Section 2.4)
+///
+/// The message-id the server echoes must be `message_id`; a different one
+/// means pipelined responses are out of step with their commands. Servers
+/// that echo none are accepted.
+pub fn parse_check_response(message_id: &str, response: NntpResponse) -> Result<CheckResult> {
+    let status = match response.response_code() {
+        ResponseCode::CheckSend => CheckStatus::Send,
+        ResponseCode::CheckLater => CheckStatus::Later,
+        ResponseCode::CheckNotWanted => CheckStatus::NotWanted,
+        _ => {
+            return Err(NntpError::Protocol {
+                code: response.code,
+                message: response.message,
+            });
+        }
+    };
+    if let Some(echoed) = response.message.split_whitespace().next()
+        && echoed.starts_with('<')
+        && echoed != message_id
+    {
+        return Err(NntpError::InvalidResponse(format!(
+            "CHECK response for {} while expecting {}",
+            echoed, message_id
+        )));
+    }
+    Ok(CheckResult {
+        message_id: message_id.to_string(),
+        status,
+    })
+}
+
 /// Build TAKETHIS command with article data (RFC 4644 §2.5)
 ///
 /// Sends an article to the server in streaming mode without waiting for permission.
@@ -182,6 +258,76 @@ pub fn takethis(message_id: &str, article_data: &str) -> String {
     format!("TAKETHIS {}\r\n{}", message_id, article_data)
 }
 
+/// Build TAKETHIS command line without article data (RFC 4644 §2.5)
+///
+/// Used when the article is transmitted separately as pre-serialized bytes.
+pub fn takethis_line(message_id: &str) -> String {
+    format!("TAKETHIS {}\r\n", message_id)
+}
+
+/// Validate a pre-serialized article before sending it as-is
+///
+/// Relays and feeders often already hold the exact wire bytes of an article.
+/// This performs the minimal checks needed to keep the protocol stream intact:
+///
+/// - The article must not be empty
+/// - Every line must end with CRLF
+/// - No line may consist of a single "." (the article must already be dot-stuffed)
+///
+/// A trailing ".\r\n" terminator is accepted and stripped, so callers may pass
+/// either the article text alone or the article text including its terminator.
+///
+/// Returns the article bytes without the terminator.
+///
+/// # Examples
+///
+/// ```
+/// # use nntp_rs::commands::validate_raw_article;
+/// let raw = b"Subject: Test\r\n\r\n..dotted\r\n.\r\n";
+/// let article = validate_raw_article(raw).unwrap();
+/// assert_eq!(article, b"Subject: Test\r\n\r\n..dotted\r\n");
+///
+/// // Unstuffed lone dot would terminate the article early
+/// assert!(validate_raw_article(b"Subject: Test\r\n\r\n.\r\nmore\r\n").is_err());
+/// ```
+pub fn validate_raw_article(data: &[u8]) -> Result<&[u8]> {
+    // Only a line consisting solely of "." is a terminator ("text.\r\n" is not)
+    let article = if data == b".\r\n" {
+        &data[..0]
+    } else if data.ends_with(b"\r\n.\r\n") {
+        &data[..data.len() - 3]
+    } else {
+        data
+    };
+    if article.is_empty() {
+        return Err(NntpError::InvalidArgument(
+            "Raw article must not be empty".to_string(),
+        ));
+    }
+    if !article.ends_with(b"\r\n") {
+        return Err(NntpError::InvalidArgument(
+            "Raw article must end with CRLF".to_string(),
+        ));
+    }
+
+    for (index, line) in article.split_inclusive(|&b| b == b'\n').enumerate() {
+        if !line.ends_with(b"\r\n") {
+            return Err(NntpError::InvalidArgument(format!(
+                "Raw article line {} is not CRLF-terminated",
+                index + 1
+            )));
+        }
+        if line == b".\r\n" {
+            return Err(NntpError::InvalidArgument(format!(
+                "Raw article line {} is an unstuffed terminator",
+                index + 1
+            )));
+        }
+    }
+
+    Ok(article)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,4 +345,40 @@ mod tests {
         assert_eq!(xfeature_compress_gzip(), "XFEATURE COMPRESS GZIP\r\n");
         assert_eq!(quit(), "QUIT\r\n");
     }
+
+    #[test]
+    fn test_takethis_line() {
+        assert_eq!(takethis_line("<a@b>"), "TAKETHIS <a@b>\r\n");
+    }
+
+    #[test]
+    fn test_validate_raw_article_strips_terminator() {
+        let raw = b"Subject: x\r\n\r\nbody\r\n.\r\n";
+        assert_eq!(
+            validate_raw_article(raw).unwrap(),
+            b"Subject: x\r\n\r\nbody\r\n"
+        );
+
+        let raw = b"Subject: x\r\n\r\nbody\r\n";
+        assert_eq!(validate_raw_article(raw).unwrap(), raw);
+
+        // A line merely ending in "." is content, not a terminator
+        let raw = b"Subject: x\r\n\r\nend.\r\n";
+        assert_eq!(validate_raw_article(raw).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_validate_raw_article_rejects_malformed() {
+        assert!(validate_raw_article(b"").is_err());
+        assert!(validate_raw_article(b".\r\n").is_err());
+        assert!(validate_raw_article(b"Subject: x\r\n\r\nbody").is_err());
+        assert!(validate_raw_article(b"Subject: x\n\nbody\r\n").is_err());
+        assert!(validate_raw_article(b"Subject: x\r\n\r\n.\r\nbody\r\n").is_err());
+    }
+
+    #[test]
+    fn test_validate_raw_article_accepts_stuffed_dots() {
+        let raw = b"Subject: x\r\n\r\n..\r\n...more\r\n";
+        assert_eq!(validate_raw_article(raw).unwrap(), raw);
+    }
 }
@@ -1,7 +1,9 @@
 //! OVER/XOVER commands and overview data parsing
 
 use crate::error::{NntpError, Result};
-use crate::response::NntpResponse;
+use crate::response::{NntpResponse, ParseMode};
+use chrono::{DateTime, Duration, Utc};
+use std::borrow::Cow;
 
 /// Build XOVER command for fetching article overview data
 pub fn xover(range: &str) -> String {
@@ -28,7 +30,12 @@ pub fn list_overview_fmt() -> &'static str {
 }
 
 /// XOVER entry structure containing article metadata
+///
+/// Usually produced by parsing an overview line. To build one by hand, start
+/// from [`XoverEntry::new`] and fill in the other fields with the `with_*`
+/// methods.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct XoverEntry {
     /// Article number within the newsgroup
     pub article_number: u64,
@@ -38,6 +45,8 @@ pub struct XoverEntry {
     pub author: String,
     /// Article date string
     pub date: String,
+    /// Article date parsed from `date`, if it is a valid RFC 5322 date
+    pub timestamp: Option<DateTime<Utc>>,
     /// Unique message ID
     pub message_id: String,
     /// References to parent articles (for threading)
@@ -46,29 +55,418 @@ pub struct XoverEntry {
     pub bytes: usize,
     /// Number of lines in the article
     pub lines: usize,
+    /// Message-ID the entry was requested by (`OVER <message-id>`)
+    ///
+    /// `None` for range and current-article requests, where the entry is
+    /// identified by `article_number` within the selected group. Servers
+    /// answer message-id requests with article number 0.
+    pub source_id: Option<String>,
 }
 
+impl XoverEntry {
+    /// Create an entry with the given article number and message-ID
+    ///
+    /// All other fields are empty or zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::XoverEntry;
+    ///
+    /// let entry = XoverEntry::new(1, "<a@b>")
+    ///     .with_subject("Test Article")
+    ///     .with_author("poster@example.com")
+    ///     .with_date("Mon, 01 Jan 2024 00:00:00 +0000")
+    ///     .with_size(1024, 50);
+    /// assert!(entry.timestamp.is_some());
+    /// ```
+    pub fn new(article_number: u64, message_id: impl Into<String>) -> Self {
+        Self {
+            article_number,
+            subject: String::new(),
+            author: String::new(),
+            date: String::new(),
+            timestamp: None,
+            message_id: message_id.into(),
+            references: String::new(),
+            bytes: 0,
+            lines: 0,
+            source_id: None,
+        }
+    }
+
+    /// Set the subject line
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Set the author (From header)
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Set the date string, parsing it into `timestamp`
+    pub fn with_date(mut self, date: impl Into<String>) -> Self {
+        self.date = date.into();
+        self.timestamp = parse_overview_date(&self.date);
+        self
+    }
+
+    /// Set the references to parent articles
+    pub fn with_references(mut self, references: impl Into<String>) -> Self {
+        self.references = references.into();
+        self
+    }
+
+    /// Set the article size in bytes and lines
+    pub fn with_size(mut self, bytes: usize, lines: usize) -> Self {
+        self.bytes = bytes;
+        self.lines = lines;
+        self
+    }
+
+    /// Age of the article relative to `now`
+    ///
+    /// Returns `None` if the date could not be parsed. Articles dated in the
+    /// future (clock skew) have an age of zero.
+    pub fn age_at(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.timestamp
+            .map(|timestamp| (now - timestamp).max(Duration::zero()))
+    }
+
+    /// Age of the article relative to the current time
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::commands::parse_xover_line;
+    ///
+    /// let line = "1\tSubject\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<a@b>\t\t100\t5";
+    /// let entry = parse_xover_line(line).unwrap();
+    /// assert!(entry.age().unwrap().num_days() > 365);
+    /// ```
+    pub fn age(&self) -> Option<Duration> {
+        self.age_at(Utc::now())
+    }
+
+    /// Age of the article in whole days, if the date could be parsed
+    pub fn age_days(&self) -> Option<i64> {
+        self.age().map(|age| age.num_days())
+    }
+
+    /// Check whether the article is older than `max_age` (e.g. a server's retention)
+    ///
+    /// Returns `false` if the date could not be parsed.
+    pub fn is_older_than(&self, max_age: Duration) -> bool {
+        self.age().is_some_and(|age| age > max_age)
+    }
+}
+
+/// Parse an overview Date field into a UTC timestamp
+///
+/// Accepts RFC 5322 dates, tolerating a trailing comment such as "(UTC)"
+/// that many posting agents append. Returns `None` if the date is invalid.
+pub fn parse_overview_date(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+    // Fast path without allocating an error for the common well-formed case
+    if let Ok(timestamp) = DateTime::parse_from_rfc2822(date) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    crate::validation::parse_date(date).ok()
+}
+
+/// Borrowed overview entry pointing into the response line
+///
+/// Produced by [`parse_xover_line_ref`] without any allocation, for indexing
+/// workloads that only look at a few fields of each line. Convert to an owned
+/// [`XoverEntry`] with `XoverEntry::from`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XoverEntryRef<'a> {
+    /// Article number within the newsgroup
+    pub article_number: u64,
+    /// Article subject line
+    pub subject: &'a str,
+    /// Article author (From header)
+    pub author: &'a str,
+    /// Article date string
+    pub date: &'a str,
+    /// Unique message ID
+    pub message_id: &'a str,
+    /// References to parent articles (for threading)
+    pub references: &'a str,
+    /// Article size in bytes
+    pub bytes: usize,
+    /// Number of lines in the article
+    pub lines: usize,
+}
+
+impl XoverEntryRef<'_> {
+    /// Parse the date field into a UTC timestamp
+    ///
+    /// Done on demand because date parsing dominates the cost of a line.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        parse_overview_date(self.date)
+    }
+}
+
+impl From<XoverEntryRef<'_>> for XoverEntry {
+    fn from(entry: XoverEntryRef<'_>) -> Self {
+        Self {
+            article_number: entry.article_number,
+            subject: entry.subject.to_string(),
+            author: entry.author.to_string(),
+            date: entry.date.to_string(),
+            timestamp: entry.timestamp(),
+            message_id: entry.message_id.to_string(),
+            references: entry.references.to_string(),
+            bytes: entry.bytes,
+            lines: entry.lines,
+            source_id: None,
+        }
+    }
+}
+
+/// Number of leading overview fields (number through lines) that are parsed
+const OVERVIEW_FIELDS: usize = 8;
+
 /// Parse XOVER response line into components
 ///
 /// Format: "article-number\tsubject\tauthor\tdate\tmessage-id\treferences\tbytes\tlines\txref"
 pub fn parse_xover_line(line: &str) -> Result<XoverEntry> {
-    let parts: Vec<&str> = line.split('\t').collect();
-    if parts.len() < 8 {
-        return Err(NntpError::InvalidResponse(line.to_string()));
-    }
-
-    Ok(XoverEntry {
-        article_number: parts[0].parse().unwrap_or(0),
-        subject: parts[1].to_string(),
-        author: parts[2].to_string(),
-        date: parts[3].to_string(),
-        message_id: parts[4].to_string(),
-        references: parts[5].to_string(),
-        bytes: parts[6].parse().unwrap_or(0),
-        lines: parts[7].parse().unwrap_or(0),
+    parse_xover_line_ref(line).map(XoverEntry::from)
+}
+
+/// Parse XOVER response line without allocating
+///
+/// Fields are located with a vectorized tab search and numbers are parsed
+/// directly from the line. Extra fields after `lines` (e.g. Xref) are ignored.
+/// Unparseable numbers default to 0, as in [`parse_xover_line`].
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::commands::parse_xover_line_ref;
+///
+/// let line = "42\tSubject\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<a@b>\t\t1000\t20";
+/// let entry = parse_xover_line_ref(line).unwrap();
+/// assert_eq!(entry.article_number, 42);
+/// assert_eq!(entry.message_id, "<a@b>");
+/// assert_eq!(entry.lines, 20);
+/// ```
+pub fn parse_xover_line_ref(line: &str) -> Result<XoverEntryRef<'_>> {
+    let [
+        number,
+        subject,
+        author,
+        date,
+        message_id,
+        references,
+        bytes,
+        lines,
+    ] = overview_fields(line)?;
+    Ok(XoverEntryRef {
+        article_number: parse_decimal(number).unwrap_or(0),
+        subject,
+        author,
+        date,
+        message_id,
+        references,
+        bytes: parse_decimal_usize(bytes),
+        lines: parse_decimal_usize(lines),
     })
 }
 
+/// Parse XOVER response line, checking every column in strict mode
+///
+/// [`ParseMode::Lenient`] is [`parse_xover_line`]. [`ParseMode::Strict`] also
+/// rejects lines whose article number, byte count or line count is not a
+/// decimal number, or whose message-id is not enclosed in angle brackets.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::ParseMode;
+/// use nntp_rs::commands::parse_xover_line_with;
+///
+/// let line = "42\tSubject\tposter@example.com\tdate\t<a@b>\t\tmany\t20";
+/// assert_eq!(parse_xover_line_with(line, ParseMode::Lenient).unwrap().bytes, 0);
+/// assert!(parse_xover_line_with(line, ParseMode::Strict).is_err());
+/// ```
+pub fn parse_xover_line_with(line: &str, mode: ParseMode) -> Result<XoverEntry> {
+    if mode == ParseMode::Strict {
+        let [number, _, _, _, message_id, _, bytes, lines] = overview_fields(line)?;
+        let column = |name: &str| {
+            NntpError::InvalidResponse(format!("bad overview {} column: {}", name, line))
+        };
+        for (name, field) in [("number", number), ("bytes", bytes), ("lines", lines)] {
+            if parse_decimal(field).is_none() {
+                return Err(column(name));
+            }
+        }
+        if !(message_id.len() > 2 && message_id.starts_with('<') && message_id.ends_with('>')) {
+            return Err(column("message-id"));
+        }
+    }
+    parse_xover_line(line)
+}
+
+/// Overview columns after the article number, in RFC 3977 order
+const STANDARD_OVERVIEW_COLUMNS: [&str; OVERVIEW_FIELDS - 1] = [
+    "subject",
+    "from",
+    "date",
+    "message-id",
+    "references",
+    "bytes",
+    "lines",
+];
+
+/// Column name of a LIST OVERVIEW.FMT entry, lowercased without colons
+///
+/// "Subject:", ":bytes" and the RFC 2980 "Bytes:" all map to the plain name.
+fn overview_column_name(entry: &str) -> String {
+    let entry = entry.trim();
+    let entry = entry
+        .strip_suffix(":full")
+        .or_else(|| entry.strip_suffix(":FULL"))
+        .unwrap_or(entry);
+    entry.trim_matches(':').to_ascii_lowercase()
+}
+
+/// Move the columns of an overview line into the RFC 3977 order
+///
+/// `format` is the LIST OVERVIEW.FMT response of a server whose XOVER output
+/// does not follow the standard order. Standard columns the format lacks
+/// are left empty, and columns it adds are kept after `lines`. The line is
+/// borrowed unchanged when the format already starts with the standard
+/// columns.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::commands::{parse_xover_line, reorder_overview_fields};
+///
+/// // A server that lists the sizes before the references
+/// let format: Vec<String> = ["Subject:", "From:", "Date:", "Message-ID:", ":bytes", ":lines", "References:"]
+///     .iter()
+///     .map(|field| field.to_string())
+///     .collect();
+/// let line = "7\tsubj\tfrom\tdate\t<m@id>\t100\t2\t<ref@id>";
+/// let entry = parse_xover_line(&reorder_overview_fields(line, &format)).unwrap();
+/// assert_eq!(entry.references, "<ref@id>");
+/// assert_eq!(entry.bytes, 100);
+/// ```
+pub fn reorder_overview_fields<'a>(line: &'a str, format: &[String]) -> Cow<'a, str> {
+    let names: Vec<String> = format
+        .iter()
+        .map(|entry| overview_column_name(entry))
+        .collect();
+    let standard = names.len() >= STANDARD_OVERVIEW_COLUMNS.len()
+        && names
+            .iter()
+            .zip(STANDARD_OVERVIEW_COLUMNS)
+            .all(|(name, expected)| name == expected);
+    if standard {
+        return Cow::Borrowed(line);
+    }
+
+    let mut fields = line.split('\t');
+    let number = fields.next().unwrap_or_default();
+    let columns: Vec<&str> = fields.collect();
+    let column = |index: usize| columns.get(index).copied().unwrap_or_default();
+
+    let mut reordered = vec![number];
+    for expected in STANDARD_OVERVIEW_COLUMNS {
+        let index = names.iter().position(|name| name == expected);
+        reordered.push(index.map(column).unwrap_or_default());
+    }
+    for (index, name) in names.iter().enumerate() {
+        if !STANDARD_OVERVIEW_COLUMNS.contains(&name.as_str()) {
+            reordered.push(column(index));
+        }
+    }
+    Cow::Owned(reordered.join("\t"))
+}
+
+/// Split the leading overview fields (number through lines) out of a line
+fn overview_fields(line: &str) -> Result<[&str; OVERVIEW_FIELDS]> {
+    let mut fields = [""; OVERVIEW_FIELDS];
+    let mut tabs = memchr::memchr_iter(b'\t', line.as_bytes());
+    let mut start = 0;
+
+    for field in fields.iter_mut().take(OVERVIEW_FIELDS - 1) {
+        let Some(end) = tabs.next() else {
+            return Err(NntpError::InvalidResponse(line.to_string()));
+        };
+        // Tab is ASCII, so every split point is a char boundary
+        *field = &line[start..end];
+        start = end + 1;
+    }
+    let end = tabs.next().unwrap_or(line.len());
+    fields[OVERVIEW_FIELDS - 1] = &line[start..end];
+    Ok(fields)
+}
+
+/// Parse an unsigned decimal number, rejecting signs, whitespace and overflow
+fn parse_decimal(field: &str) -> Option<u64> {
+    if field.is_empty() {
+        return None;
+    }
+    field.bytes().try_fold(0u64, |acc, byte| {
+        let digit = byte.checked_sub(b'0').filter(|digit| *digit <= 9)?;
+        acc.checked_mul(10)?.checked_add(u64::from(digit))
+    })
+}
+
+fn parse_decimal_usize(field: &str) -> usize {
+    parse_decimal(field)
+        .and_then(|value| usize::try_from(value).ok())
+        .unwrap_or(0)
+}
+
+/// Build an overview entry from an article's header lines
+///
+/// Used to emulate OVER by message-id on servers that do not support it.
+/// The article number is 0 (RFC 3977 §8.3.2 reports 0 for message-id lookups),
+/// and `bytes`/`lines` come from the Bytes and Lines headers when present.
+pub fn overview_from_headers(header_lines: &[String]) -> XoverEntry {
+    let fields = super::extract_header_fields(
+        header_lines,
+        &[
+            "Subject",
+            "From",
+            "Date",
+            "Message-ID",
+            "References",
+            "Bytes",
+            "Lines",
+        ],
+    );
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default()
+    };
+    let date = field("Date");
+
+    XoverEntry {
+        article_number: 0,
+        subject: field("Subject"),
+        author: field("From"),
+        timestamp: parse_overview_date(&date),
+        date,
+        message_id: field("Message-ID"),
+        references: field("References"),
+        bytes: field("Bytes").trim().parse().unwrap_or(0),
+        lines: field("Lines").trim().parse().unwrap_or(0),
+        source_id: None,
+    }
+}
+
 /// Parse LIST OVERVIEW.FMT response into field names
 ///
 /// Format: One field name per line, in order of OVER/XOVER output
@@ -103,5 +501,184 @@ mod tests {
         assert_eq!(entry.message_id, "<msg@id>");
         assert_eq!(entry.bytes, 1234);
         assert_eq!(entry.lines, 50);
+        // Date without a time component is not a valid RFC 5322 date
+        assert!(entry.timestamp.is_none());
+        assert!(entry.age().is_none());
+        assert!(!entry.is_older_than(Duration::days(1)));
+    }
+
+    #[test]
+    fn test_parse_xover_line_timestamp() {
+        let line = "1\tSubject\tauthor\tTue, 02 Jan 2024 12:00:00 +0100\t<a@b>\t\t10\t1";
+        let entry = parse_xover_line(line).unwrap();
+        let timestamp = entry.timestamp.unwrap();
+        assert_eq!(timestamp.to_rfc3339(), "2024-01-02T11:00:00+00:00");
+
+        let now = timestamp + Duration::days(3);
+        assert_eq!(entry.age_at(now), Some(Duration::days(3)));
+        assert_eq!(
+            entry.age_at(timestamp - Duration::hours(1)),
+            Some(Duration::zero())
+        );
+        assert!(entry.is_older_than(Duration::days(30)));
+    }
+
+    #[test]
+    fn test_parse_overview_date_variants() {
+        assert!(parse_overview_date("Tue, 02 Jan 2024 12:00:00 GMT").is_some());
+        assert!(parse_overview_date("02 Jan 2024 12:00:00 +0000").is_some());
+        assert!(parse_overview_date("Tue, 02 Jan 2024 12:00:00 +0000 (UTC)").is_some());
+        assert!(parse_overview_date("").is_none());
+        assert!(parse_overview_date("not a date (UTC)").is_none());
+    }
+
+    #[test]
+    fn test_overview_from_headers() {
+        let headers: Vec<String> = [
+            "Path: news.example.com!not-for-mail",
+            "From: poster@example.com",
+            "Subject: Folded",
+            " subject",
+            "Date: Mon, 01 Jan 2024 00:00:00 +0000 (UTC)",
+            "Message-ID: <abc@example.com>",
+            "Lines: 42",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+        let entry = overview_from_headers(&headers);
+        assert_eq!(entry.article_number, 0);
+        assert_eq!(entry.subject, "Folded subject");
+        assert_eq!(entry.author, "poster@example.com");
+        assert!(entry.timestamp.is_some());
+        assert_eq!(entry.message_id, "<abc@example.com>");
+        assert_eq!(entry.references, "");
+        assert_eq!(entry.bytes, 0);
+        assert_eq!(entry.lines, 42);
+    }
+
+    #[test]
+    fn test_parse_xover_line_ref_borrows_fields() {
+        let line = "7\tSubject\tauthor\tTue, 02 Jan 2024 12:00:00 +0000\t<a@b>\t<r@b>\t10\t1\tXref: host a.b:7";
+        let entry = parse_xover_line_ref(line).unwrap();
+        assert_eq!(entry.article_number, 7);
+        assert_eq!(entry.subject, "Subject");
+        assert_eq!(entry.references, "<r@b>");
+        assert_eq!(entry.lines, 1);
+        assert!(entry.timestamp().is_some());
+        // Fields point into the original line
+        assert!(
+            line.as_bytes()
+                .as_ptr_range()
+                .contains(&entry.subject.as_ptr())
+        );
+
+        let owned = XoverEntry::from(entry);
+        assert_eq!(owned.message_id, "<a@b>");
+        assert_eq!(owned.timestamp, entry.timestamp());
+    }
+
+    #[test]
+    fn test_parse_xover_line_strict() {
+        let good = "7\tsubj\tfrom\tdate\t<m@id>\t\t100\t2\tXref: a";
+        assert_eq!(
+            parse_xover_line_with(good, ParseMode::Strict)
+                .unwrap()
+                .article_number,
+            7
+        );
+
+        for bad in [
+            "x\tsubj\tfrom\tdate\t<m@id>\t\t100\t2",
+            "7\tsubj\tfrom\tdate\t<m@id>\t\t\t2",
+            "7\tsubj\tfrom\tdate\t<m@id>\t\t100\t-2",
+            "7\tsubj\tfrom\tdate\tm@id\t\t100\t2",
+            "7\tsubj\tfrom\tdate\t<m@id>",
+        ] {
+            assert!(
+                parse_xover_line_with(bad, ParseMode::Strict).is_err(),
+                "{}",
+                bad
+            );
+        }
+        assert!(parse_xover_line_with("x\ts\tf\td\tm\t\t\t", ParseMode::Lenient).is_ok());
+    }
+
+    #[test]
+    fn test_reorder_overview_fields() {
+        let format = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let line = "7\tsubj\tfrom\tdate\t<m@id>\t<r@id>\t100\t2\tXref: a 7";
+
+        let standard = format(&[
+            "Subject:",
+            "From:",
+            "Date:",
+            "Message-ID:",
+            "References:",
+            ":bytes",
+            ":lines",
+            "Xref:full",
+        ]);
+        assert!(matches!(
+            reorder_overview_fields(line, &standard),
+            Cow::Borrowed(_)
+        ));
+        let rfc2980 = format(&[
+            "Subject:",
+            "From:",
+            "Date:",
+            "Message-ID:",
+            "References:",
+            "Bytes:",
+            "Lines:",
+        ]);
+        assert!(matches!(
+            reorder_overview_fields(line, &rfc2980),
+            Cow::Borrowed(_)
+        ));
+
+        // No References column, Xref before the sizes
+        let drifted = format(&[
+            "Subject:",
+            "From:",
+            "Date:",
+            "Message-ID:",
+            "Xref:full",
+            ":bytes",
+            ":lines",
+        ]);
+        let line = "7\tsubj\tfrom\tdate\t<m@id>\tXref: a 7\t100\t2";
+        assert_eq!(
+            reorder_overview_fields(line, &drifted),
+            "7\tsubj\tfrom\tdate\t<m@id>\t\t100\t2\tXref: a 7"
+        );
+        let entry = parse_xover_line(&reorder_overview_fields(line, &drifted)).unwrap();
+        assert_eq!(entry.references, "");
+        assert_eq!((entry.bytes, entry.lines), (100, 2));
+    }
+
+    #[test]
+    fn test_parse_xover_line_ref_field_count() {
+        assert!(parse_xover_line_ref("1\ta\tb\tc\t<d>\te\t10").is_err());
+        let entry = parse_xover_line_ref("1\ta\tb\tc\t<d>\te\t10\t").unwrap();
+        assert_eq!(entry.lines, 0);
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("0"), Some(0));
+        assert_eq!(parse_decimal("18446744073709551615"), Some(u64::MAX));
+        assert_eq!(parse_decimal("18446744073709551616"), None);
+        assert_eq!(parse_decimal(""), None);
+        assert_eq!(parse_decimal("+1"), None);
+        assert_eq!(parse_decimal("-1"), None);
+        assert_eq!(parse_decimal(" 1"), None);
+        assert_eq!(parse_decimal("12a"), None);
     }
 }
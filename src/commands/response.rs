@@ -1,7 +1,7 @@
 //! Core NNTP response parsing utilities
 
 use crate::error::{NntpError, Result};
-use crate::response::NntpResponse;
+use crate::response::{NntpResponse, ParseMode};
 
 /// Parse NNTP response line into code and message
 pub fn parse_response_line(line: &str) -> Result<(u16, String)> {
@@ -45,6 +45,36 @@ pub fn parse_response_line(line: &str) -> Result<(u16, String)> {
     Ok((code, message))
 }
 
+/// Parse NNTP response line, rejecting malformed ones in strict mode
+///
+/// [`ParseMode::Lenient`] is [`parse_response_line`]. [`ParseMode::Strict`]
+/// also rejects a byte order mark, a code outside 100-599 and a message not
+/// separated from the code by a space.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::ParseMode;
+/// use nntp_rs::commands::parse_response_line_with;
+///
+/// assert!(parse_response_line_with("200message", ParseMode::Lenient).is_ok());
+/// assert!(parse_response_line_with("200message", ParseMode::Strict).is_err());
+/// ```
+pub fn parse_response_line_with(line: &str, mode: ParseMode) -> Result<(u16, String)> {
+    if mode == ParseMode::Strict {
+        let bytes = line.as_bytes();
+        let well_formed = bytes.len() >= 3
+            && (b'1'..=b'5').contains(&bytes[0])
+            && bytes[1].is_ascii_digit()
+            && bytes[2].is_ascii_digit()
+            && bytes.get(3).is_none_or(|byte| *byte == b' ');
+        if !well_formed {
+            return Err(NntpError::InvalidResponse(line.chars().take(100).collect()));
+        }
+    }
+    parse_response_line(line)
+}
+
 /// Parse single-line NNTP response
 pub fn parse_single_response(line: &str) -> Result<NntpResponse> {
     let (code, message) = parse_response_line(line)?;
@@ -56,6 +86,44 @@ pub fn parse_single_response(line: &str) -> Result<NntpResponse> {
     })
 }
 
+/// Phrases providers use when refusing a connection over the account limit
+const CONNECTION_LIMIT_PHRASES: &[&str] = &[
+    "too many connection",
+    "too many simultaneous",
+    "too many concurrent",
+    "too many sessions",
+    "too many users",
+    "connection limit",
+    "max connections",
+    "maximum connections",
+    "maximum number of connections",
+    "connections exceeded",
+];
+
+/// Check whether a response refuses service because of a connection limit
+///
+/// Providers report this with 400 or 502 in the greeting, or with 481/482/502
+/// during authentication, and only the message text tells it apart from other
+/// failures (e.g. "502 Too many connections", "482 Connection limit reached").
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::commands::is_connection_limit_response;
+///
+/// assert!(is_connection_limit_response(502, "Too many connections (20)"));
+/// assert!(!is_connection_limit_response(502, "Access denied"));
+/// ```
+pub fn is_connection_limit_response(code: u16, message: &str) -> bool {
+    if !matches!(code, 400 | 481 | 482 | 502) {
+        return false;
+    }
+    let message = message.to_ascii_lowercase();
+    CONNECTION_LIMIT_PHRASES
+        .iter()
+        .any(|phrase| message.contains(phrase))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,6 +139,27 @@ mod tests {
         assert_eq!(msg, "Authentication accepted");
     }
 
+    #[test]
+    fn test_is_connection_limit_response() {
+        assert!(is_connection_limit_response(502, "Too many connections"));
+        assert!(is_connection_limit_response(
+            400,
+            "too many connections from your IP"
+        ));
+        assert!(is_connection_limit_response(
+            482,
+            "Connection limit reached for user"
+        ));
+        assert!(is_connection_limit_response(
+            481,
+            "Maximum number of connections exceeded"
+        ));
+
+        assert!(!is_connection_limit_response(502, "Permission denied"));
+        assert!(!is_connection_limit_response(481, "Authentication failed"));
+        assert!(!is_connection_limit_response(200, "Too many connections"));
+    }
+
     #[test]
     fn test_parse_response_line_invalid() {
         assert!(parse_response_line("abc").is_err());
@@ -91,6 +180,21 @@ mod tests {
         assert_eq!(msg, "message");
     }
 
+    #[test]
+    fn test_parse_response_line_strict() {
+        let strict = |line| parse_response_line_with(line, ParseMode::Strict);
+        assert_eq!(strict("200 ok").unwrap(), (200, "ok".to_string()));
+        assert_eq!(strict("205").unwrap(), (205, String::new()));
+        assert!(strict("\u{FEFF}200 ok").is_err());
+        assert!(strict("200ok").is_err());
+        assert!(strict("999 message").is_err());
+        assert!(strict("099 message").is_err());
+
+        let lenient = |line| parse_response_line_with(line, ParseMode::Lenient);
+        assert_eq!(lenient("200ok").unwrap(), (200, "ok".to_string()));
+        assert_eq!(lenient("999 message").unwrap().0, 999);
+    }
+
     #[test]
     fn test_parse_response_line_bom() {
         // BUG 3 fix: UTF-8 BOM prefix should be stripped
@@ -0,0 +1,659 @@
+//! NZB completeness reports
+//!
+//! Before downloading, an availability sweep STATs every segment of an NZB
+//! on every server ([`ServerGroup::availability_sweep`](crate::ServerGroup::availability_sweep)).
+//! [`CompletenessReport`] combines the sweep with the PAR2 index of the post
+//! to tell, per file, how many segments each server has and whether the
+//! missing ones can be repaired with the recovery volumes that are available.
+//! After the download, [`DownloadJob::completeness`](crate::DownloadJob::completeness)
+//! builds the same report from the segment statuses, so a UI can show both
+//! side by side.
+//!
+//! While downloading, [`RepairOutlook`] bounds what PAR2 can still do for
+//! the segments lost so far, so a download that can no longer be repaired
+//! is stopped early (see [`DownloadJob::abort_if_doomed`](crate::DownloadJob::abort_if_doomed)).
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::completeness::{AvailabilitySweep, CompletenessReport};
+//! use nntp_rs::parse_nzb;
+//!
+//! let nzb = parse_nzb(r#"<?xml version="1.0"?>
+//! <nzb xmlns="http://www.newzbin.com/DTD/2003/nzb">
+//!   <file poster="p" date="0" subject="[1/1] - &quot;a.bin&quot; yEnc (1/2)">
+//!     <groups><group>alt.test</group></groups>
+//!     <segments>
+//!       <segment bytes="100" number="1">part1@example.com</segment>
+//!       <segment bytes="100" number="2">part2@example.com</segment>
+//!     </segments>
+//!   </file>
+//! </nzb>"#).unwrap();
+//!
+//! let mut sweep = AvailabilitySweep::new(&nzb, vec!["news.example.com:563".to_string()]);
+//! sweep.set("news.example.com:563", 0, 0, true);
+//!
+//! let report = CompletenessReport::from_sweep(&nzb, &sweep, None);
+//! let file = &report.files[0];
+//! assert_eq!(file.filename.as_deref(), Some("a.bin"));
+//! assert_eq!((file.servers[0].found, file.missing), (1, 1));
+//! assert!(!report.is_repairable());
+//! ```
+
+use std::fmt;
+
+use crate::nzb::Nzb;
+use crate::par2::Par2File;
+
+/// Smallest share of a segment's NZB-declared size that is file data
+///
+/// yEnc adds escapes, line breaks and headers, typically 2-4% of the data.
+const MIN_DATA_RATIO: f64 = 0.9;
+
+/// Which servers have each segment of an NZB
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AvailabilitySweep {
+    /// Server ids, in the order their results are stored
+    servers: Vec<String>,
+    /// Presence of every segment, indexed by server, file, then segment
+    present: Vec<Vec<Vec<bool>>>,
+}
+
+impl AvailabilitySweep {
+    /// Create a sweep of `nzb` over `servers` with no segment found yet
+    pub fn new(nzb: &Nzb, servers: Vec<String>) -> Self {
+        let files: Vec<Vec<bool>> = nzb
+            .files
+            .iter()
+            .map(|file| vec![false; file.segments.len()])
+            .collect();
+        Self {
+            present: vec![files; servers.len()],
+            servers,
+        }
+    }
+
+    /// Server ids covered by the sweep
+    pub fn servers(&self) -> &[String] {
+        &self.servers
+    }
+
+    /// Record whether `server` has a segment
+    ///
+    /// Returns `false` if the server or segment is unknown.
+    pub fn set(&mut self, server: &str, file: usize, segment: usize, found: bool) -> bool {
+        let Some(index) = self.servers.iter().position(|s| s == server) else {
+            return false;
+        };
+        match self.present[index]
+            .get_mut(file)
+            .and_then(|f| f.get_mut(segment))
+        {
+            Some(slot) => {
+                *slot = found;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether `server` has a segment
+    pub fn has(&self, server: &str, file: usize, segment: usize) -> bool {
+        self.servers
+            .iter()
+            .position(|s| s == server)
+            .and_then(|index| self.present[index].get(file)?.get(segment).copied())
+            .unwrap_or(false)
+    }
+
+    /// Check whether any server has a segment
+    pub fn is_available(&self, file: usize, segment: usize) -> bool {
+        self.present
+            .iter()
+            .any(|files| files.get(file).and_then(|f| f.get(segment)) == Some(&true))
+    }
+}
+
+/// Segment counts of one file on one server
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerSegments {
+    /// Server id (`host:port`), or the server group name after a download
+    pub server_id: String,
+    /// Segments the server has
+    pub found: usize,
+    /// Segments the server lacks
+    pub missing: usize,
+}
+
+/// Completeness of one file of an NZB
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileCompleteness {
+    /// Subject of the post
+    pub subject: String,
+    /// File name quoted in the subject, if any
+    pub filename: Option<String>,
+    /// Whether this is a PAR2 file
+    pub is_par2: bool,
+    /// Number of segments in the NZB
+    pub total_segments: usize,
+    /// Counts per server
+    pub servers: Vec<ServerSegments>,
+    /// Segments no server has
+    pub missing: usize,
+    /// NZB-declared bytes of the segments no server has
+    pub missing_bytes: u64,
+    /// Whether the file is complete or can be repaired with the available
+    /// recovery blocks (always `false` for incomplete files without PAR2)
+    pub repairable: bool,
+}
+
+impl FileCompleteness {
+    /// Check whether every segment is available somewhere
+    pub fn is_complete(&self) -> bool {
+        self.missing == 0
+    }
+}
+
+/// Per-file availability of an NZB and whether PAR2 can make up for the gaps
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompletenessReport {
+    /// One entry per NZB file, in NZB order
+    pub files: Vec<FileCompleteness>,
+    /// Recovery blocks in PAR2 volumes whose segments are all available
+    pub recovery_blocks_available: u64,
+    /// Estimated recovery blocks needed for the missing data, if the PAR2
+    /// slice size is known
+    pub recovery_blocks_needed: Option<u64>,
+}
+
+impl CompletenessReport {
+    /// Build the report from an availability sweep
+    ///
+    /// `par2` is the parsed PAR2 index of the post; without it the slice size
+    /// is unknown and only complete files count as repairable.
+    pub fn from_sweep(nzb: &Nzb, sweep: &AvailabilitySweep, par2: Option<&Par2File>) -> Self {
+        Self::build(nzb, sweep.servers(), par2, |server, file, segment| {
+            sweep.has(server, file, segment)
+        })
+    }
+
+    /// Build the report from the presence of each segment on each of `servers`
+    pub(crate) fn build(
+        nzb: &Nzb,
+        servers: &[String],
+        par2: Option<&Par2File>,
+        has: impl Fn(&str, usize, usize) -> bool,
+    ) -> Self {
+        let slice_size = par2.and_then(Par2File::slice_size).filter(|&s| s > 0);
+        let mut recovery_blocks_available = 0;
+        let mut recovery_blocks_needed = 0;
+
+        let mut files: Vec<FileCompleteness> = nzb
+            .files
+            .iter()
+            .enumerate()
+            .map(|(f, file)| {
+                let filename = subject_filename(&file.subject).map(str::to_string);
+                let is_par2 = filename
+                    .as_deref()
+                    .is_some_and(|name| name.to_ascii_lowercase().ends_with(".par2"));
+                let available: Vec<bool> = (0..file.segments.len())
+                    .map(|s| servers.iter().any(|server| has(server, f, s)))
+                    .collect();
+                let servers = servers
+                    .iter()
+                    .map(|server| {
+                        let found = (0..file.segments.len())
+                            .filter(|&s| has(server, f, s))
+                            .count();
+                        ServerSegments {
+                            server_id: server.clone(),
+                            found,
+                            missing: file.segments.len() - found,
+                        }
+                    })
+                    .collect();
+                let missing = available.iter().filter(|&&a| !a).count();
+                let missing_bytes = file
+                    .segments
+                    .iter()
+                    .zip(&available)
+                    .filter(|(_, a)| !**a)
+                    .map(|(segment, _)| segment.bytes)
+                    .sum();
+
+                if is_par2 && missing == 0 {
+                    recovery_blocks_available +=
+                        filename.as_deref().and_then(volume_blocks).unwrap_or(0);
+                }
+                if !is_par2 && let Some(slice_size) = slice_size {
+                    let sizes = file.segments.iter().map(|s| s.bytes);
+                    recovery_blocks_needed += blocks_needed(sizes, &available, slice_size);
+                }
+
+                FileCompleteness {
+                    subject: file.subject.clone(),
+                    filename,
+                    is_par2,
+                    total_segments: file.segments.len(),
+                    servers,
+                    missing,
+                    missing_bytes,
+                    repairable: missing == 0,
+                }
+            })
+            .collect();
+
+        let recovery_blocks_needed = slice_size.map(|_| recovery_blocks_needed);
+        let can_repair =
+            recovery_blocks_needed.is_some_and(|needed| needed <= recovery_blocks_available);
+        for file in files.iter_mut().filter(|f| !f.is_par2) {
+            file.repairable |= can_repair;
+        }
+
+        Self {
+            files,
+            recovery_blocks_available,
+            recovery_blocks_needed,
+        }
+    }
+
+    /// Check whether every segment of every file is available
+    pub fn is_complete(&self) -> bool {
+        self.files.iter().all(FileCompleteness::is_complete)
+    }
+
+    /// Check whether every non-PAR2 file is complete or repairable
+    pub fn is_repairable(&self) -> bool {
+        self.files
+            .iter()
+            .filter(|f| !f.is_par2)
+            .all(|f| f.repairable)
+    }
+}
+
+/// Bounds on the PAR2 repair of a download in progress
+///
+/// Unlike the estimate in [`CompletenessReport`], both bounds are safe: the
+/// lost segments damage at least `blocks_needed` slices, and at most
+/// `blocks_available` recovery blocks can still be downloaded. Once the
+/// first exceeds the second, no outcome of the remaining segments makes the
+/// download repairable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepairOutlook {
+    /// Slices the lost segments damage, at least
+    pub blocks_needed: u64,
+    /// Recovery blocks in PAR2 volumes not lost entirely, at most
+    pub blocks_available: u64,
+}
+
+impl RepairOutlook {
+    /// Bound the repair of an NZB whose segments are lost where `lost` says
+    ///
+    /// Only files of the recovery set count as needing repair, if `par2`
+    /// describes its files. Returns `None` if the slice size is unknown, or
+    /// the block count of a PAR2 volume that may still arrive, since the
+    /// upper bound no longer holds then.
+    pub(crate) fn build(
+        nzb: &Nzb,
+        par2: &Par2File,
+        lost: impl Fn(usize, usize) -> bool,
+    ) -> Option<Self> {
+        let slice_size = par2.slice_size().filter(|&s| s > 0)?;
+        let protected = |name: Option<&str>| {
+            par2.file_descriptions.is_empty()
+                || name.is_some_and(|name| {
+                    par2.file_descriptions
+                        .values()
+                        .any(|desc| &*desc.name == name)
+                })
+        };
+
+        let mut outlook = Self {
+            blocks_needed: 0,
+            blocks_available: 0,
+        };
+        for (f, file) in nzb.files.iter().enumerate() {
+            let lost: Vec<bool> = (0..file.segments.len()).map(|s| lost(f, s)).collect();
+            let filename = subject_filename(&file.subject);
+            let volume = match filename {
+                Some(name) => name.to_ascii_lowercase().ends_with(".par2").then_some(name),
+                None => unquoted_par2_name(&file.subject),
+            };
+            match volume {
+                Some(name) if lost.iter().any(|&l| !l) => {
+                    outlook.blocks_available += volume_blocks(name)?;
+                }
+                Some(_) => {}
+                None if protected(filename) => {
+                    let sizes = file.segments.iter().map(|s| s.bytes);
+                    outlook.blocks_needed += slices_damaged(sizes, &lost, slice_size);
+                }
+                None => {}
+            }
+        }
+        Some(outlook)
+    }
+
+    /// Check whether the download can no longer be repaired
+    pub fn is_doomed(&self) -> bool {
+        self.blocks_needed > self.blocks_available
+    }
+}
+
+impl fmt::Display for RepairOutlook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lost segments need at least {} recovery blocks, at most {} remain available",
+            self.blocks_needed, self.blocks_available
+        )
+    }
+}
+
+/// File name in double quotes in a post subject
+pub(crate) fn subject_filename(subject: &str) -> Option<&str> {
+    let (_, rest) = subject.split_once('"')?;
+    let (name, _) = rest.split_once('"')?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// PAR2 file name in a post subject that does not quote it
+fn unquoted_par2_name(subject: &str) -> Option<&str> {
+    let end = subject.to_ascii_lowercase().find(".par2")? + ".par2".len();
+    let start = subject[..end]
+        .rfind(|c: char| c.is_whitespace() || "\"'[(<".contains(c))
+        .map_or(0, |i| i + 1);
+    Some(&subject[start..end])
+}
+
+/// Recovery blocks in a PAR2 volume named like `name.vol07+08.par2`
+///
+/// The index file `name.par2` holds none. Returns `None` if the name is
+/// not one of these.
+fn volume_blocks(filename: &str) -> Option<u64> {
+    let lower = filename.to_ascii_lowercase();
+    let stem = lower.strip_suffix(".par2")?;
+    let Some((_, volume)) = stem.rsplit_once(".vol") else {
+        return Some(0);
+    };
+    let (_, count) = volume.split_once('+')?;
+    count.parse().ok()
+}
+
+/// Estimate the PAR2 slices touched by the missing segments of a file
+///
+/// Segment sizes are the encoded sizes from the NZB, slightly larger than the
+/// data they carry, and every run of missing segments may straddle one extra
+/// slice boundary, so the estimate errs on the high side. It never exceeds
+/// the number of slices in the file.
+fn blocks_needed(sizes: impl Iterator<Item = u64>, available: &[bool], slice_size: u64) -> u64 {
+    let mut total = 0;
+    let mut needed = 0;
+    let mut run: u64 = 0;
+    for (bytes, &present) in sizes.zip(available) {
+        total += bytes;
+        if present {
+            if run > 0 {
+                needed += run.div_ceil(slice_size) + 1;
+            }
+            run = 0;
+        } else {
+            run += bytes;
+        }
+    }
+    if run > 0 {
+        needed += run.div_ceil(slice_size) + 1;
+    }
+    needed.min(total.div_ceil(slice_size))
+}
+
+/// Least file data in `encoded` NZB-declared bytes
+fn min_data(encoded: u64) -> u64 {
+    (encoded as f64 * MIN_DATA_RATIO) as u64
+}
+
+/// Count the slices the lost segments of a file damage, at least
+///
+/// A run of lost data touches at least as many slices as it would if it
+/// started on a slice boundary, and damages every slice it touches. Two runs
+/// can share a slice only if the data between them is shorter than a slice.
+fn slices_damaged(sizes: impl Iterator<Item = u64>, lost: &[bool], slice_size: u64) -> u64 {
+    let mut damaged = 0;
+    let mut run = 0;
+    let mut gap = None;
+    let mut finish_run = |run: u64, gap: Option<u64>| {
+        damaged += min_data(run).div_ceil(slice_size).max(1);
+        if gap.is_some_and(|gap| min_data(gap) < slice_size) {
+            damaged -= 1;
+        }
+    };
+    for (bytes, &is_lost) in sizes.zip(lost) {
+        if is_lost {
+            run += bytes;
+            continue;
+        }
+        if run > 0 {
+            finish_run(run, gap);
+            run = 0;
+            gap = Some(0);
+        }
+        gap = gap.map(|g| g + bytes);
+    }
+    if run > 0 {
+        finish_run(run, gap);
+    }
+    damaged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nzb::{NzbFile, NzbSegment};
+    use crate::par2::MainPacket;
+
+    fn file(subject: &str, segments: usize) -> NzbFile {
+        NzbFile {
+            poster: "poster".to_string(),
+            date: 0,
+            subject: subject.to_string(),
+            groups: vec!["alt.test".to_string()],
+            segments: (0..segments)
+                .map(|i| NzbSegment {
+                    bytes: 1000,
+                    number: i as u32 + 1,
+                    message_id: format!("{}.{}@example.com", subject.len(), i),
+                })
+                .collect(),
+        }
+    }
+
+    fn par2_index(slice_size: u64) -> Par2File {
+        let mut par2 = Par2File::new();
+        par2.main = Some(MainPacket {
+            slice_size,
+            file_count: 1,
+            file_ids: Vec::new(),
+            non_recoverable_file_ids: Vec::new(),
+        });
+        par2
+    }
+
+    fn post() -> Nzb {
+        Nzb {
+            meta: Default::default(),
+            files: vec![
+                file("\"movie.mkv\" yEnc (1/10)", 10),
+                file("\"movie.par2\" yEnc (1/1)", 1),
+                file("\"movie.vol0+2.par2\" yEnc (1/1)", 1),
+                file("\"movie.vol2+4.par2\" yEnc (1/2)", 2),
+            ],
+        }
+    }
+
+    fn servers() -> Vec<String> {
+        vec!["a:563".to_string(), "b:563".to_string()]
+    }
+
+    /// Every segment on server b, apart from `missing` (file, segment) pairs
+    fn sweep(nzb: &Nzb, missing: &[(usize, usize)]) -> AvailabilitySweep {
+        let mut sweep = AvailabilitySweep::new(nzb, servers());
+        for (f, file) in nzb.files.iter().enumerate() {
+            for s in 0..file.segments.len() {
+                sweep.set("b:563", f, s, !missing.contains(&(f, s)));
+            }
+        }
+        sweep
+    }
+
+    #[test]
+    fn test_subject_filename_and_volume_blocks() {
+        assert_eq!(
+            subject_filename("[01/10] - \"a b.rar\" yEnc (1/5)"),
+            Some("a b.rar")
+        );
+        assert_eq!(subject_filename("no quotes"), None);
+        assert_eq!(volume_blocks("Movie.VOL07+08.PAR2"), Some(8));
+        assert_eq!(volume_blocks("movie.par2"), Some(0));
+        assert_eq!(volume_blocks("movie.vol.par2"), None);
+        assert_eq!(volume_blocks("movie.mkv"), None);
+        assert_eq!(
+            unquoted_par2_name("[2/3] movie.vol01+02.par2 yEnc (1/1)"),
+            Some("movie.vol01+02.par2")
+        );
+        assert_eq!(unquoted_par2_name("movie.mkv yEnc (1/1)"), None);
+    }
+
+    #[test]
+    fn test_blocks_needed() {
+        let sizes = || std::iter::repeat_n(1000, 6);
+        // One run of two segments: 2 slices of 1000 plus a boundary
+        assert_eq!(
+            blocks_needed(sizes(), &[true, false, false, true, true, true], 1000),
+            3
+        );
+        // Capped at the file's slice count
+        assert_eq!(blocks_needed(sizes(), &[false; 6], 1000), 6);
+        assert_eq!(blocks_needed(sizes(), &[true; 6], 1000), 0);
+    }
+
+    #[test]
+    fn test_slices_damaged() {
+        let sizes = || std::iter::repeat_n(1000, 6);
+        assert_eq!(slices_damaged(sizes(), &[false; 6], 1000), 0);
+        // 900 bytes of data at least: one slice
+        let lost = [false, true, false, false, false, false];
+        assert_eq!(slices_damaged(sizes(), &lost, 1000), 1);
+        // 2700 bytes of data at least: three slices
+        let lost = [false, true, true, true, false, false];
+        assert_eq!(slices_damaged(sizes(), &lost, 1000), 3);
+        // Runs separated by less than a slice may share one
+        let lost = [true, false, true, false, false, false];
+        assert_eq!(slices_damaged(sizes(), &lost, 1000), 1);
+        let lost = [true, true, false, true, true, false];
+        assert_eq!(slices_damaged(sizes(), &lost, 500), 8);
+        assert_eq!(slices_damaged(sizes(), &lost, 2000), 1);
+    }
+
+    #[test]
+    fn test_repair_outlook() {
+        let nzb = post();
+        let par2 = par2_index(1000);
+        let outlook = |lost: &[(usize, usize)]| {
+            RepairOutlook::build(&nzb, &par2, |f, s| lost.contains(&(f, s))).unwrap()
+        };
+
+        // Nothing lost: both volumes may still arrive
+        let clear = outlook(&[]);
+        assert_eq!((clear.blocks_needed, clear.blocks_available), (0, 6));
+        assert!(!clear.is_doomed());
+
+        // A partly lost volume may still give its blocks
+        let partial = outlook(&[(0, 1), (0, 4), (3, 0)]);
+        assert_eq!((partial.blocks_needed, partial.blocks_available), (2, 6));
+
+        // Both volumes gone
+        let doomed = outlook(&[(0, 1), (2, 0), (3, 0), (3, 1)]);
+        assert_eq!((doomed.blocks_needed, doomed.blocks_available), (1, 0));
+        assert!(doomed.is_doomed());
+
+        assert!(RepairOutlook::build(&nzb, &Par2File::new(), |_, _| true).is_none());
+    }
+
+    #[test]
+    fn test_repair_outlook_unknown_volume() {
+        let par2 = par2_index(1000);
+        let mut nzb = post();
+        nzb.files[3].subject = "movie.vol2+4.par2 yEnc (1/2)".to_string();
+        let outlook = RepairOutlook::build(&nzb, &par2, |_, _| false).unwrap();
+        assert_eq!((outlook.blocks_needed, outlook.blocks_available), (0, 6));
+
+        // A volume that may arrive with an unknown block count bounds nothing
+        nzb.files[3].subject = "\"movie.vol2.par2\" yEnc (1/2)".to_string();
+        assert!(RepairOutlook::build(&nzb, &par2, |_, _| false).is_none());
+        nzb.files[3].subject = "movie.vol2+x.par2 yEnc (1/2)".to_string();
+        assert!(RepairOutlook::build(&nzb, &par2, |_, _| false).is_none());
+
+        // Unless it is lost entirely
+        assert!(RepairOutlook::build(&nzb, &par2, |f, _| f == 3).is_some());
+    }
+
+    #[test]
+    fn test_counts_per_server() {
+        let nzb = post();
+        let mut sweep = sweep(&nzb, &[(0, 3)]);
+        sweep.set("a:563", 0, 0, true);
+        assert!(!sweep.set("c:563", 0, 0, true));
+        assert!(!sweep.set("a:563", 0, 99, true));
+
+        let report = CompletenessReport::from_sweep(&nzb, &sweep, None);
+        let movie = &report.files[0];
+        assert_eq!(movie.filename.as_deref(), Some("movie.mkv"));
+        assert_eq!(
+            movie.servers,
+            vec![
+                ServerSegments {
+                    server_id: "a:563".to_string(),
+                    found: 1,
+                    missing: 9
+                },
+                ServerSegments {
+                    server_id: "b:563".to_string(),
+                    found: 9,
+                    missing: 1
+                },
+            ]
+        );
+        assert_eq!((movie.missing, movie.missing_bytes), (1, 1000));
+        // No PAR2 index: the gap cannot be judged
+        assert_eq!(report.recovery_blocks_needed, None);
+        assert!(!movie.repairable);
+        assert!(!report.is_complete() && !report.is_repairable());
+    }
+
+    #[test]
+    fn test_repairable_with_available_volumes() {
+        let nzb = post();
+        let par2 = par2_index(1000);
+
+        // Two missing segments need three blocks; both volumes give six
+        let report =
+            CompletenessReport::from_sweep(&nzb, &sweep(&nzb, &[(0, 1), (0, 2)]), Some(&par2));
+        assert_eq!(report.recovery_blocks_available, 6);
+        assert_eq!(report.recovery_blocks_needed, Some(3));
+        assert!(report.files[0].repairable && report.is_repairable());
+        assert!(report.files[1].is_par2);
+
+        // Losing a segment of the larger volume leaves only two blocks
+        let missing = [(0, 1), (0, 2), (3, 0)];
+        let report = CompletenessReport::from_sweep(&nzb, &sweep(&nzb, &missing), Some(&par2));
+        assert_eq!(report.recovery_blocks_available, 2);
+        assert!(!report.is_repairable());
+        assert!(!report.files[3].repairable);
+    }
+}
@@ -0,0 +1,233 @@
+//! Header-only compression detection (XFEATURE COMPRESS GZIP)
+//!
+//! Servers that implement the non-standard `XFEATURE COMPRESS GZIP` extension
+//! disagree on how a compressed response is announced:
+//!
+//! - Most mark the status line, e.g. `224 overview follows [COMPRESS=GZIP]`
+//! - Some use a different spelling or casing of the marker
+//! - Some never mark anything and always compress the responses to certain commands
+//!
+//! Some servers also place the `.` terminator inside the compressed stream
+//! instead of after it. The client handles both layouts; this module only
+//! decides *whether* a response is compressed.
+//!
+//! Servers that never mark anything are recognized by their
+//! [`gzip_marker_absent`](crate::ServerQuirks::gzip_marker_absent) quirk,
+//! which switches the client to
+//! [`GzipDetection::always_compressed_overview`].
+
+/// Status-line markers recognized by default (matched case-insensitively)
+pub const DEFAULT_GZIP_MARKERS: &[&str] = &["COMPRESS=GZIP"];
+
+/// Commands whose multi-line responses Giganews-style servers always compress
+const ALWAYS_COMPRESSED_OVERVIEW: &[&str] = &["XOVER", "OVER", "XHDR", "HDR", "XZVER", "XZHDR"];
+
+/// Commands whose responses [`GzipDetection::Auto`] sniffs for a zlib header
+///
+/// Article data may start with anything, including the two bytes of a zlib
+/// header, so only overview and header responses are sniffed.
+const SNIFFED: &[&str] = &["XOVER", "OVER", "XHDR", "HDR", "XZVER", "XZHDR"];
+
+/// Strategy for detecting compressed responses in headers-only compression mode
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::GzipDetection;
+///
+/// let detection = GzipDetection::always_compressed_overview();
+/// assert!(detection.is_compressed("XOVER", "224 Overview follows", None));
+///
+/// let detection = GzipDetection::default();
+/// assert!(detection.is_compressed("XOVER", "224 data [compress=gzip]", None));
+/// assert!(detection.is_compressed("XOVER", "224 data", Some(&[0x78, 0x9C])));
+/// assert!(!detection.is_compressed("BODY", "222 body", Some(&[0x78, 0x9C])));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum GzipDetection {
+    /// A response is compressed only if its status line contains one of these markers
+    Marker(Vec<String>),
+    /// Multi-line responses to these commands are always compressed
+    ///
+    /// Status-line markers are still honored for other commands.
+    AlwaysCompressed(Vec<String>),
+    /// Check for the default markers, then sniff overview and header
+    /// response data (XOVER, OVER, XHDR, HDR, ...) for a zlib header
+    #[default]
+    Auto,
+}
+
+impl GzipDetection {
+    /// Pick a detection strategy from the server greeting
+    ///
+    /// Recognizes Astraweb, which marks compressed responses but not always
+    /// with the bracketed form. Any other server uses [`GzipDetection::Auto`];
+    /// servers that never mark compressed responses, such as Giganews, are
+    /// handled by the
+    /// [`gzip_marker_absent`](crate::ServerQuirks::gzip_marker_absent) quirk.
+    pub fn for_greeting(greeting: &str) -> Self {
+        let greeting = greeting.to_ascii_lowercase();
+        if greeting.contains("astraweb") {
+            Self::Marker(vec!["COMPRESS=GZIP".to_string(), "GZIP".to_string()])
+        } else {
+            Self::Auto
+        }
+    }
+
+    /// Treat overview and header responses (XOVER, OVER, XHDR, HDR, ...) as always compressed
+    ///
+    /// For servers that compress these responses without marking them.
+    pub fn always_compressed_overview() -> Self {
+        Self::AlwaysCompressed(
+            ALWAYS_COMPRESSED_OVERVIEW
+                .iter()
+                .map(|cmd| (*cmd).to_string())
+                .collect(),
+        )
+    }
+
+    /// Decide whether a multi-line response is compressed
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command verb that produced the response (e.g. "XOVER")
+    /// * `status_message` - The status line text following the response code
+    /// * `data_prefix` - The first buffered bytes of the response data, if available
+    pub fn is_compressed(
+        &self,
+        command: &str,
+        status_message: &str,
+        data_prefix: Option<&[u8]>,
+    ) -> bool {
+        match self {
+            Self::Marker(markers) => contains_marker(status_message, markers),
+            Self::AlwaysCompressed(commands) => {
+                commands.iter().any(|cmd| cmd.eq_ignore_ascii_case(command))
+                    || contains_marker(status_message, DEFAULT_GZIP_MARKERS)
+            }
+            Self::Auto => {
+                contains_marker(status_message, DEFAULT_GZIP_MARKERS)
+                    || (is_sniffed(command) && data_prefix.is_some_and(is_zlib_header))
+            }
+        }
+    }
+
+    /// Whether this strategy needs a peek at the data of a response to `command`
+    ///
+    /// At least two bytes are needed to recognize a zlib header.
+    pub fn needs_data_prefix(&self, command: &str) -> bool {
+        matches!(self, Self::Auto) && is_sniffed(command)
+    }
+}
+
+/// Case-insensitive substring match against any marker
+fn contains_marker<S: AsRef<str>>(status_message: &str, markers: &[S]) -> bool {
+    let message = status_message.to_ascii_uppercase();
+    markers
+        .iter()
+        .any(|marker| message.contains(&marker.as_ref().to_ascii_uppercase()))
+}
+
+/// Whether [`GzipDetection::Auto`] sniffs the responses to `command`
+fn is_sniffed(command: &str) -> bool {
+    SNIFFED.iter().any(|cmd| cmd.eq_ignore_ascii_case(command))
+}
+
+/// Whether a byte can be the first of a zlib stream header (deflate, window
+/// of at most 32K)
+pub(crate) fn is_zlib_cmf(cmf: u8) -> bool {
+    cmf & 0x0F == 8 && cmf >> 4 <= 7
+}
+
+/// Check whether data begins with a valid zlib stream header (RFC 1950)
+///
+/// Requires the deflate method, a window size of at most 32K, no preset
+/// dictionary, and a valid header checksum. Plain text overview and header
+/// lines never satisfy all of these.
+pub fn is_zlib_header(data: &[u8]) -> bool {
+    let [cmf, flg, ..] = *data else {
+        return false;
+    };
+    let no_dictionary = flg & 0x20 == 0;
+    let checksum_ok = (u16::from(cmf) * 256 + u16::from(flg)) % 31 == 0;
+    is_zlib_cmf(cmf) && no_dictionary && checksum_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zlib_header_detection() {
+        assert!(is_zlib_header(&[0x78, 0x9C]));
+        assert!(is_zlib_header(&[0x78, 0x01, 0x00]));
+        assert!(is_zlib_header(&[0x78, 0xDA]));
+        assert!(is_zlib_header(&[0x78, 0x5E]));
+
+        assert!(!is_zlib_header(b""));
+        assert!(!is_zlib_header(&[0x78]));
+        assert!(!is_zlib_header(b"12345\tSubject"));
+        assert!(!is_zlib_header(b"xref: host"));
+        assert!(!is_zlib_header(b"=ybegin line=128"));
+        // Preset dictionary flag set
+        assert!(!is_zlib_header(&[0x78, 0xBB]));
+    }
+
+    #[test]
+    fn test_marker_detection_case_insensitive() {
+        let detection = GzipDetection::Marker(vec!["COMPRESS=GZIP".to_string()]);
+        assert!(detection.is_compressed("XOVER", "Overview [COMPRESS=GZIP]", None));
+        assert!(detection.is_compressed("XOVER", "Overview [compress=gzip]", None));
+        assert!(!detection.is_compressed("XOVER", "Overview follows", Some(&[0x78, 0x9C])));
+    }
+
+    #[test]
+    fn test_always_compressed_commands() {
+        let detection = GzipDetection::AlwaysCompressed(vec!["XOVER".to_string()]);
+        assert!(detection.is_compressed("xover", "Overview follows", None));
+        assert!(!detection.is_compressed("HEAD", "Headers follow", None));
+        assert!(detection.is_compressed("HEAD", "Headers follow [COMPRESS=GZIP]", None));
+    }
+
+    #[test]
+    fn test_auto_detection_sniffs_data() {
+        let detection = GzipDetection::Auto;
+        assert!(detection.needs_data_prefix("XOVER"));
+        assert!(detection.is_compressed("XOVER", "Overview [COMPRESS=GZIP]", None));
+        assert!(detection.is_compressed("XOVER", "Overview follows", Some(&[0x78, 0x9C])));
+        assert!(detection.is_compressed("xhdr", "Headers follow", Some(&[0x78, 0x9C])));
+        assert!(!detection.is_compressed("XOVER", "Overview follows", Some(b"1\tSubj")));
+        assert!(!detection.is_compressed("XOVER", "Overview follows", Some(b"x")));
+        assert!(!detection.is_compressed("XOVER", "Overview follows", None));
+    }
+
+    #[test]
+    fn test_auto_detection_leaves_articles_alone() {
+        let detection = GzipDetection::Auto;
+        // "x^" is a valid zlib header and a plausible start of a body
+        assert!(is_zlib_header(b"x^"));
+        for command in ["ARTICLE", "BODY", "HEAD", "LIST", ""] {
+            assert!(!detection.needs_data_prefix(command));
+            assert!(!detection.is_compressed(command, "222 body follows", Some(b"x^ and more")));
+        }
+        assert!(detection.is_compressed("BODY", "222 body [COMPRESS=GZIP]", None));
+    }
+
+    #[test]
+    fn test_for_greeting_fingerprints() {
+        // Left to the gzip_marker_absent quirk
+        assert_eq!(
+            GzipDetection::for_greeting("200 News.GigaNews.Com"),
+            GzipDetection::Auto
+        );
+        assert!(matches!(
+            GzipDetection::for_greeting("200 Welcome to Astraweb"),
+            GzipDetection::Marker(_)
+        ));
+        assert_eq!(
+            GzipDetection::for_greeting("200 news.example.com InterNetNews"),
+            GzipDetection::Auto
+        );
+        assert!(!GzipDetection::for_greeting("200 astraweb").needs_data_prefix("XOVER"));
+    }
+}
@@ -1,5 +1,203 @@
 //! NNTP server configuration
 
+use std::net::IpAddr;
+use std::time::Duration;
+
+use std::sync::Arc;
+
+use crate::credentials::CredentialsProvider;
+use crate::latency::AdaptiveTimeouts;
+use crate::observer::ConnectionObserver;
+use crate::quirks::ServerQuirks;
+use crate::resolver::Resolver;
+use crate::response::ParseMode;
+
+/// Network timeouts for a connection
+///
+/// The defaults suit bulk downloading over slow links. Latency-sensitive
+/// applications can lower them to fail fast; every expiry surfaces as
+/// [`NntpError::Timeout`](crate::NntpError::Timeout).
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ServerConfig, SocketOptions, TimeoutConfig};
+/// use std::time::Duration;
+///
+/// let config = ServerConfig::tls("news.example.com", "user", "pass").with_timeouts(TimeoutConfig {
+///     connect: Duration::from_secs(5),
+///     read: Duration::from_secs(10),
+///     ..TimeoutConfig::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TimeoutConfig {
+    /// TCP connection establishment (default: 120s)
+    pub connect: Duration,
+    /// TLS handshake, for implicit TLS and STARTTLS (default: 60s)
+    pub tls_handshake: Duration,
+    /// Single-line responses, including the greeting (default: 60s)
+    pub read: Duration,
+    /// Complete multi-line responses such as articles and listings (default: 180s)
+    pub multiline_read: Duration,
+    /// Derive the status line timeout from observed latency instead of
+    /// always waiting `read` (default: off)
+    pub adaptive: Option<AdaptiveTimeouts>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(120),
+            tls_handshake: Duration::from_secs(60),
+            read: Duration::from_secs(60),
+            multiline_read: Duration::from_secs(180),
+            adaptive: None,
+        }
+    }
+}
+
+impl TimeoutConfig {
+    /// Worst-case time to establish a ready connection (connect, TLS, greeting)
+    pub fn connection_setup(&self) -> Duration {
+        self.connect
+            .saturating_add(self.tls_handshake)
+            .saturating_add(self.read)
+    }
+}
+
+/// Automatic reconnection for a single client
+///
+/// When set on [`ServerConfig::reconnect`], a client whose connection is closed
+/// or broken reconnects, re-authenticates, re-selects its newsgroup and retries
+/// the interrupted command, as long as that command is idempotent (article
+/// retrieval, STAT and GROUP). Other commands return the error as before.
+///
+/// Reconnect attempts back off exponentially from `initial_delay` up to
+/// `max_delay`.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ReconnectPolicy, ServerConfig};
+///
+/// let config = ServerConfig::tls("news.example.com", "user", "pass")
+///     .with_reconnect(ReconnectPolicy::default());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ReconnectPolicy {
+    /// Maximum reconnect attempts per command (default: 3)
+    pub max_attempts: u32,
+    /// Delay before the first reconnect attempt (default: 100ms)
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts (default: 5s)
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before reconnect attempt `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+/// TCP socket options applied before connecting
+///
+/// The defaults favour high-throughput downloads. Lower the buffers on
+/// memory-constrained devices, where every pooled connection reserves them,
+/// or raise them on fast high-latency links. Buffer sizes are hints; the OS
+/// may round or cap them, and failures to apply them are only logged.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ServerConfig, SocketOptions};
+/// use std::time::Duration;
+///
+/// let config = ServerConfig::tls("news.example.com", "user", "pass").with_socket_options(
+///     SocketOptions {
+///         recv_buffer_size: Some(256 * 1024),
+///         tcp_keepalive: Some(Duration::from_secs(60)),
+///         ..SocketOptions::default()
+///     },
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SocketOptions {
+    /// SO_RCVBUF in bytes, `None` keeps the OS default (default: 4MB)
+    pub recv_buffer_size: Option<usize>,
+    /// SO_SNDBUF in bytes, `None` keeps the OS default (default: 1MB)
+    pub send_buffer_size: Option<usize>,
+    /// Disable Nagle's algorithm (default: `true`)
+    pub nodelay: bool,
+    /// Idle time before TCP keepalive probes are sent, `None` disables them (default: `None`)
+    ///
+    /// Unlike [`ServerConfig::keepalive`] this works below NNTP and only
+    /// keeps NAT and firewall state alive; providers still see an idle session.
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            recv_buffer_size: Some(4 * 1024 * 1024),
+            send_buffer_size: Some(1024 * 1024),
+            nodelay: true,
+            tcp_keepalive: None,
+        }
+    }
+}
+
+/// When plain connections are upgraded with STARTTLS
+///
+/// Only affects configurations with [`ServerConfig::tls`] unset; implicit TLS
+/// connections are always encrypted. Under [`Required`](Self::Required) and
+/// [`Opportunistic`](Self::Opportunistic),
+/// [`NntpClient::connect`](crate::NntpClient::connect) asks for CAPABILITIES
+/// and runs STARTTLS if the server advertises it.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ServerConfig, TlsPolicy};
+///
+/// // Port 119, but never send the password in clear text
+/// let config = ServerConfig::plain("news.example.com", "user", "pass")
+///     .with_tls_policy(TlsPolicy::Required);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TlsPolicy {
+    /// Upgrade with STARTTLS, failing with
+    /// [`NntpError::EncryptionRequired`](crate::NntpError::EncryptionRequired)
+    /// if the server does not offer it, and never authenticate in plain text
+    Required,
+    /// Upgrade with STARTTLS when offered, staying plain otherwise
+    Opportunistic,
+    /// Leave plain connections as they are (default)
+    #[default]
+    Never,
+}
+
 /// NNTP server configuration
 ///
 /// Contains all the information needed to connect to an NNTP server.
@@ -7,26 +205,27 @@
 /// # Example
 ///
 /// ```
-/// use nntp_rs::ServerConfig;
+/// use nntp_rs::{ParseMode, ServerConfig};
 ///
 /// // Recommended: use the constructor methods
 /// let config = ServerConfig::tls("news.example.com", "user", "pass");
 ///
-/// // Or construct manually
-/// let config = ServerConfig {
-///     host: "news.example.com".to_string(),
-///     port: 563,
-///     tls: true,
-///     allow_insecure_tls: false,
-///     username: "user".to_string(),
-///     password: "pass".to_string(),
-/// };
+/// // Or pick port and TLS yourself, then adjust with the `with_*` methods
+/// let config = ServerConfig::new("news.example.com", 563, true, "user", "pass")
+///     .with_max_article_size(10 * 1024 * 1024)
+///     .with_parse_mode(ParseMode::Strict);
 /// ```
 #[must_use]
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerConfig {
-    /// Server hostname (e.g., "news.example.com")
+    /// Server hostname (e.g., "news.example.com") or IP address
+    ///
+    /// IPv6 addresses may be written with or without brackets (`2001:db8::1`
+    /// or `[2001:db8::1]`), and link-local ones with a zone (`fe80::1%eth0`).
+    /// IP addresses are connected to without a lookup and TLS certificates
+    /// are checked against their IP address entries.
     pub host: String,
 
     /// Server port (typically 119 for plain, 563 for TLS)
@@ -34,12 +233,12 @@ pub struct ServerConfig {
 
     /// Use TLS/SSL encryption
     ///
-    /// **Note:** Currently, TLS is always enabled when connecting to port 563.
-    /// This field is maintained for configuration compatibility but does not
-    /// affect runtime behavior. To use a plain unencrypted connection, use port 119
-    /// (via `ServerConfig::plain()` or specify `port: 119` manually).
+    /// When `true`, the client performs a TLS handshake right after connecting
+    /// (implicit TLS, typically port 563). When `false`, the connection is plain
+    /// TCP (typically port 119) and can be upgraded with
+    /// [`NntpClient::starttls`](crate::NntpClient::starttls).
     ///
-    /// In a future major version (0.2.0+), this may be replaced with a `TlsMode` enum
+    /// In a future major version, this may be replaced with a `TlsMode` enum
     /// to eliminate the separate `allow_insecure_tls` field and provide clearer semantics.
     #[cfg_attr(feature = "serde", serde(default = "default_tls"))]
     pub tls: bool,
@@ -56,15 +255,150 @@ pub struct ServerConfig {
     /// - Certificate hostname mismatches are accepted
     /// - Invalid certificate chains are accepted
     ///
+    /// For self-signed provider certificates, prefer pinning with
+    /// [`tls_pinned_sha256`](Self::tls_pinned_sha256). This flag overrides both
+    /// the pin and any extra root certificates.
+    ///
     /// Default: `false` (secure certificate validation enabled)
     #[cfg_attr(feature = "serde", serde(default))]
     pub allow_insecure_tls: bool,
 
+    /// Extra trusted root certificates in PEM format
+    ///
+    /// Added to the built-in webpki roots, for providers whose certificates
+    /// are issued by a private CA. May contain several concatenated
+    /// certificates. Invalid PEM fails the TLS setup with
+    /// [`NntpError::Tls`](crate::NntpError::Tls). Ignored when a pin is set.
+    ///
+    /// Default: `None`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tls_root_certs_pem: Option<String>,
+
+    /// Pinned SHA-256 fingerprint of the server certificate (hex, colons optional)
+    ///
+    /// When set, the server's end-entity certificate must have exactly this
+    /// fingerprint and is then accepted without chain or hostname validation,
+    /// so self-signed certificates can be trusted safely. Handshake signatures
+    /// are still verified. Get the fingerprint with
+    /// `openssl x509 -noout -fingerprint -sha256`.
+    ///
+    /// Default: `None`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tls_pinned_sha256: Option<String>,
+
     /// Username for authentication
     pub username: String,
 
     /// Password for authentication
     pub password: String,
+
+    /// Looks up the username and password at authentication time
+    ///
+    /// When set, `username` and `password` are ignored by
+    /// [`NntpClient::authenticate`](crate::NntpClient::authenticate) and can
+    /// be left empty, so no plaintext secret is stored in the configuration.
+    /// Not serialized. See [`CredentialsProvider`].
+    ///
+    /// Default: `None`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+
+    /// Server workarounds to apply
+    ///
+    /// When `None` (the default), the client picks a profile by fingerprinting
+    /// the server greeting (see [`ServerQuirks::from_greeting`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quirks: Option<ServerQuirks>,
+
+    /// Connect, TLS handshake and read timeouts
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timeouts: TimeoutConfig,
+
+    /// Reconnect transparently when the connection drops
+    ///
+    /// Default: `None` (connection errors are returned to the caller)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reconnect: Option<ReconnectPolicy>,
+
+    /// Send DATE on connections idle for this long
+    ///
+    /// Keeps pooled connections from being closed by provider idle timeouts.
+    /// Used by [`NntpClient::keepalive`](crate::NntpClient::keepalive) and
+    /// [`NntpPool::run_keepalive`](crate::NntpPool::run_keepalive).
+    ///
+    /// Default: `None` (no keepalive)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub keepalive: Option<Duration>,
+
+    /// Largest article the server accepts, in bytes (headers and body)
+    ///
+    /// NNTP has no standard way to advertise this, so it is configured per
+    /// provider. [`UploadJob::build_part`](crate::UploadJob::build_part)
+    /// splits parts that would exceed it.
+    ///
+    /// Default: `None` (no limit)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_article_size: Option<u64>,
+
+    /// Refuse article bodies with broken dot-stuffing
+    ///
+    /// A body line starting with a single dot is always reported as a
+    /// [`ParseWarning::UnstuffedDotLine`](crate::ParseWarning::UnstuffedDotLine).
+    /// When set, the response is also failed with
+    /// [`NntpError::MalformedBody`](crate::NntpError::MalformedBody), so the
+    /// segment is fetched again (from another server with
+    /// [`ServerGroup::fetch_body_verified`](crate::ServerGroup::fetch_body_verified))
+    /// instead of corrupting the assembled file.
+    ///
+    /// Default: `false`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strict_dot_stuffing: bool,
+
+    /// Whether malformed status, overview and HDR lines are errors
+    ///
+    /// See [`ParseMode`].
+    ///
+    /// Default: [`ParseMode::Lenient`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub parse_mode: ParseMode,
+
+    /// Whether plain connections are upgraded with STARTTLS
+    ///
+    /// See [`TlsPolicy`].
+    ///
+    /// Default: [`TlsPolicy::Never`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tls_policy: TlsPolicy,
+
+    /// TCP buffer sizes, TCP_NODELAY and TCP keepalive
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub socket: SocketOptions,
+
+    /// Local source address to bind before connecting
+    ///
+    /// Routes the connection through the interface owning this address, for
+    /// multi-homed hosts or a VPN interface. Only server addresses of the same
+    /// family (IPv4 or IPv6) are tried.
+    ///
+    /// Default: `None` (the OS picks the source address)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub local_addr: Option<IpAddr>,
+
+    /// Resolves `host` to the addresses to connect to
+    ///
+    /// Not serialized. See [`Resolver`].
+    ///
+    /// Default: `None` (the system resolver, cached process-wide for 60 seconds)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub resolver: Option<Arc<dyn Resolver>>,
+
+    /// Instrumentation hooks installed on every connection to this server
+    ///
+    /// Not serialized. See [`ConnectionObserver`].
+    ///
+    /// Default: `None`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub observer: Option<Arc<dyn ConnectionObserver>>,
 }
 
 #[cfg(feature = "serde")]
@@ -94,8 +428,23 @@ impl ServerConfig {
             port,
             tls,
             allow_insecure_tls: false,
+            tls_root_certs_pem: None,
+            tls_pinned_sha256: None,
             username: username.into(),
             password: password.into(),
+            credentials_provider: None,
+            quirks: None,
+            timeouts: TimeoutConfig::default(),
+            reconnect: None,
+            keepalive: None,
+            max_article_size: None,
+            strict_dot_stuffing: false,
+            parse_mode: ParseMode::Lenient,
+            tls_policy: TlsPolicy::Never,
+            socket: SocketOptions::default(),
+            local_addr: None,
+            resolver: None,
+            observer: None,
         }
     }
 
@@ -143,6 +492,165 @@ impl ServerConfig {
         config.allow_insecure_tls = true;
         config
     }
+
+    /// Trust additional root certificates (PEM) besides the webpki roots
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use nntp_rs::ServerConfig;
+    ///
+    /// let ca = std::fs::read_to_string("provider-ca.pem").unwrap();
+    /// let config = ServerConfig::tls("news.example.com", "user", "pass")
+    ///     .with_root_certs_pem(ca);
+    /// ```
+    pub fn with_root_certs_pem(mut self, pem: impl Into<String>) -> Self {
+        self.tls_root_certs_pem = Some(pem.into());
+        self
+    }
+
+    /// Pin the server certificate by its SHA-256 fingerprint
+    ///
+    /// See [`tls_pinned_sha256`](Self::tls_pinned_sha256).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::ServerConfig;
+    ///
+    /// let config = ServerConfig::tls("localhost", "user", "pass").with_pinned_certificate(
+    ///     "3A:1F:5C:9D:0E:22:7B:41:88:6A:F0:93:12:C4:5E:7D:AB:09:36:E1:4C:72:58:BD:90:1E:F6:A3:27:6C:D8:45",
+    /// );
+    /// ```
+    pub fn with_pinned_certificate(mut self, sha256_fingerprint: impl Into<String>) -> Self {
+        self.tls_pinned_sha256 = Some(sha256_fingerprint.into());
+        self
+    }
+
+    /// Use custom TCP socket options
+    ///
+    /// See [`SocketOptions`].
+    pub fn with_socket_options(mut self, socket: SocketOptions) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    /// Look up credentials from `provider` instead of the stored username and password
+    ///
+    /// See [`credentials_provider`](Self::credentials_provider).
+    pub fn with_credentials_provider(mut self, provider: Arc<dyn CredentialsProvider>) -> Self {
+        self.credentials_provider = Some(provider);
+        self
+    }
+
+    /// `host:port` for logs and server ids, with IPv6 addresses in brackets
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::ServerConfig;
+    ///
+    /// let config = ServerConfig::new("2001:db8::119", 563, true, "user", "pass");
+    /// assert_eq!(config.authority(), "[2001:db8::119]:563");
+    /// ```
+    pub fn authority(&self) -> String {
+        crate::host::authority(&self.host, self.port)
+    }
+
+    /// Bind connections to a local source address
+    ///
+    /// See [`local_addr`](Self::local_addr).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nntp_rs::ServerConfig;
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// // Address of the VPN interface
+    /// let config = ServerConfig::tls("news.example.com", "user", "pass")
+    ///     .with_local_addr(IpAddr::V4(Ipv4Addr::new(10, 8, 0, 2)));
+    /// ```
+    pub fn with_local_addr(mut self, local_addr: IpAddr) -> Self {
+        self.local_addr = Some(local_addr);
+        self
+    }
+
+    /// Resolve the hostname with `resolver`
+    ///
+    /// See [`resolver`](Self::resolver).
+    pub fn with_resolver(mut self, resolver: Arc<dyn Resolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Report the traffic of every connection to `observer`
+    ///
+    /// See [`ConnectionObserver`].
+    pub fn with_observer(mut self, observer: Arc<dyn ConnectionObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Use custom network timeouts
+    pub fn with_timeouts(mut self, timeouts: TimeoutConfig) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Reconnect and retry idempotent commands when the connection drops
+    ///
+    /// See [`ReconnectPolicy`].
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Set the largest article the server accepts
+    ///
+    /// See [`max_article_size`](Self::max_article_size).
+    pub fn with_max_article_size(mut self, bytes: u64) -> Self {
+        self.max_article_size = Some(bytes);
+        self
+    }
+
+    /// Refuse article bodies with broken dot-stuffing
+    ///
+    /// See [`strict_dot_stuffing`](Self::strict_dot_stuffing).
+    pub fn with_strict_dot_stuffing(mut self) -> Self {
+        self.strict_dot_stuffing = true;
+        self
+    }
+
+    /// Fail commands on malformed server output instead of skipping it
+    ///
+    /// See [`ParseMode`].
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Upgrade plain connections with STARTTLS according to `policy`
+    ///
+    /// See [`TlsPolicy`].
+    pub fn with_tls_policy(mut self, policy: TlsPolicy) -> Self {
+        self.tls_policy = policy;
+        self
+    }
+
+    /// Ping connections with DATE after `interval` without a command
+    ///
+    /// See [`keepalive`](Self::keepalive).
+    pub fn with_keepalive(mut self, interval: Duration) -> Self {
+        self.keepalive = Some(interval);
+        self
+    }
+
+    /// Use an explicit quirk profile instead of fingerprinting the server
+    pub fn with_quirks(mut self, quirks: ServerQuirks) -> Self {
+        self.quirks = Some(quirks);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -187,6 +695,100 @@ mod tests {
         assert_eq!(config.password, "pass");
     }
 
+    #[test]
+    fn test_certificate_trust_builders() {
+        let config = ServerConfig::tls("news.example.com", "user", "pass");
+        assert!(config.tls_root_certs_pem.is_none());
+        assert!(config.tls_pinned_sha256.is_none());
+
+        let config = config
+            .with_root_certs_pem("-----BEGIN CERTIFICATE-----")
+            .with_pinned_certificate("AB:CD");
+        assert_eq!(
+            config.tls_root_certs_pem.as_deref(),
+            Some("-----BEGIN CERTIFICATE-----")
+        );
+        assert_eq!(config.tls_pinned_sha256.as_deref(), Some("AB:CD"));
+        assert!(!config.allow_insecure_tls);
+    }
+
+    #[test]
+    fn test_with_timeouts() {
+        let config = ServerConfig::tls("news.example.com", "user", "pass");
+        assert_eq!(config.timeouts, TimeoutConfig::default());
+
+        let timeouts = TimeoutConfig {
+            connect: Duration::from_secs(5),
+            read: Duration::from_secs(10),
+            ..TimeoutConfig::default()
+        };
+        let config = config.with_timeouts(timeouts);
+        assert_eq!(config.timeouts.connect, Duration::from_secs(5));
+        assert_eq!(config.timeouts.read, Duration::from_secs(10));
+        assert_eq!(config.timeouts.tls_handshake, Duration::from_secs(60));
+        assert_eq!(config.timeouts.multiline_read, Duration::from_secs(180));
+    }
+
+    #[test]
+    fn test_reconnect_policy() {
+        let config = ServerConfig::tls("news.example.com", "user", "pass");
+        assert!(config.reconnect.is_none());
+
+        let policy = ReconnectPolicy::default();
+        let config = config.with_reconnect(policy);
+        assert_eq!(config.reconnect, Some(policy));
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(4), Duration::from_millis(800));
+        assert_eq!(policy.delay(20), Duration::from_secs(5));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_connection_setup_timeout() {
+        assert_eq!(
+            TimeoutConfig::default().connection_setup(),
+            Duration::from_secs(240)
+        );
+        let huge = TimeoutConfig {
+            connect: Duration::MAX,
+            ..TimeoutConfig::default()
+        };
+        assert_eq!(huge.connection_setup(), Duration::MAX);
+    }
+
+    #[test]
+    fn test_with_quirks() {
+        let config = ServerConfig::tls("news.example.com", "user", "pass");
+        assert!(config.quirks.is_none());
+
+        let quirks = ServerQuirks {
+            no_over_by_msgid: true,
+            ..ServerQuirks::default()
+        };
+        let config = config.with_quirks(quirks.clone());
+        assert_eq!(config.quirks, Some(quirks));
+    }
+
+    #[test]
+    fn test_with_parse_mode() {
+        let config = ServerConfig::tls("news.example.com", "user", "pass");
+        assert_eq!(config.parse_mode, ParseMode::Lenient);
+
+        let config = config.with_parse_mode(ParseMode::Strict);
+        assert_eq!(config.parse_mode, ParseMode::Strict);
+    }
+
+    #[test]
+    fn test_with_tls_policy() {
+        let config = ServerConfig::plain("news.example.com", "user", "pass");
+        assert_eq!(config.tls_policy, TlsPolicy::Never);
+
+        let config = config.with_tls_policy(TlsPolicy::Required);
+        assert_eq!(config.tls_policy, TlsPolicy::Required);
+    }
+
     #[test]
     fn test_insecure_tls_default_false() {
         let config = ServerConfig::new("news.example.com", 563, true, "user", "pass");
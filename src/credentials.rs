@@ -0,0 +1,100 @@
+//! External credential providers
+//!
+//! By default [`NntpClient::authenticate`](crate::NntpClient::authenticate)
+//! sends the `username` and `password` stored in
+//! [`ServerConfig`](crate::ServerConfig). A [`CredentialsProvider`] set with
+//! [`ServerConfig::with_credentials_provider`](crate::ServerConfig::with_credentials_provider)
+//! is asked instead, each time a connection authenticates, so the secret can
+//! live in an OS keychain or a vault, or be prompted for on demand.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::config::ServerConfig;
+use crate::error::Result;
+
+/// Future returned by [`CredentialsProvider::credentials`]
+pub type CredentialsFuture<'a> = Pin<Box<dyn Future<Output = Result<Credentials>> + Send + 'a>>;
+
+/// Username and password for AUTHINFO USER/PASS
+///
+/// The `Debug` output hides the password.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// Username sent with AUTHINFO USER
+    pub username: String,
+    /// Password sent with AUTHINFO PASS
+    pub password: String,
+}
+
+impl Credentials {
+    /// Create credentials from a username and password
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"****")
+            .finish()
+    }
+}
+
+/// Source of credentials looked up at authentication time
+///
+/// Called once per authentication, including the re-authentication after a
+/// transparent reconnect and every new pool connection. Providers that prompt
+/// the user or query a slow backend should cache the result themselves.
+///
+/// # Example
+///
+/// ```no_run
+/// use nntp_rs::credentials::{Credentials, CredentialsFuture, CredentialsProvider};
+/// use nntp_rs::{NntpError, ServerConfig};
+/// use std::sync::Arc;
+///
+/// #[derive(Debug)]
+/// struct EnvCredentials;
+///
+/// impl CredentialsProvider for EnvCredentials {
+///     fn credentials<'a>(&'a self, server: &'a ServerConfig) -> CredentialsFuture<'a> {
+///         Box::pin(async move {
+///             let password = std::env::var("NNTP_PASSWORD")
+///                 .map_err(|_| NntpError::AuthFailed(format!("No password for {}", server.host)))?;
+///             Ok(Credentials::new("alice", password))
+///         })
+///     }
+/// }
+///
+/// let config = ServerConfig::tls("news.example.com", "", "")
+///     .with_credentials_provider(Arc::new(EnvCredentials));
+/// ```
+pub trait CredentialsProvider: Send + Sync + fmt::Debug {
+    /// Look up the credentials for `server`
+    ///
+    /// # Errors
+    ///
+    /// An error aborts the authentication and is returned unchanged by
+    /// [`NntpClient::authenticate`](crate::NntpClient::authenticate).
+    fn credentials<'a>(&'a self, server: &'a ServerConfig) -> CredentialsFuture<'a>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_hides_password() {
+        let credentials = Credentials::new("alice", "hunter2");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("alice"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
@@ -12,8 +12,9 @@
 //! ## Supported Charsets
 //! - UTF-8 (full support)
 //! - ISO-8859-1 (full support via direct byte mapping)
-//! - Windows-1252 (lossy conversion)
-//! - Other charsets use lossy conversion
+//! - With the `charsets` feature, every charset of the WHATWG Encoding
+//!   Standard (ISO-8859-*, Windows-125x, KOI8-R, Shift_JIS, GB18030, ...)
+//! - Other charsets use lossy UTF-8 conversion
 //!
 //! ## Usage Examples
 //!
@@ -161,7 +162,7 @@ pub fn decode_encoded_word(encoded: &str) -> String {
     };
 
     // Convert bytes to string based on charset
-    charset_to_string(&decoded_bytes, charset)
+    decode_charset(&decoded_bytes, charset)
 }
 
 /// Decodes a header value that may contain one or more encoded words.
@@ -229,12 +230,15 @@ pub fn decode_header_value(value: &str) -> String {
             }
         }
 
-        // Regular character
-        result.push(bytes[i] as char);
-        if bytes[i] != b' ' && bytes[i] != b'\t' {
+        // Regular character, possibly non-ASCII
+        let Some(ch) = value[i..].chars().next() else {
+            break;
+        };
+        result.push(ch);
+        if ch != ' ' && ch != '\t' {
             last_was_encoded = false;
         }
-        i += 1;
+        i += ch.len_utf8();
     }
 
     result
@@ -324,36 +328,49 @@ fn decode_quoted_printable(encoded: &str) -> Result<Vec<u8>> {
     Ok(result)
 }
 
-/// Converts bytes to a String based on the specified charset.
+/// Decode text in the MIME charset `charset` (e.g. from a Content-Type `charset=`)
 ///
-/// Currently supports:
-/// - UTF-8 (full support)
-/// - ISO-8859-1, Windows-1252, and other charsets (lossy conversion)
-fn charset_to_string(bytes: &[u8], charset: &str) -> String {
-    let charset_lower = charset.to_lowercase();
+/// UTF-8 and ISO-8859-1 are always decoded. With the `charsets` feature,
+/// every charset label the WHATWG Encoding Standard knows is decoded too
+/// (ISO-8859-*, Windows-125x, KOI8-R/U, Shift_JIS, EUC-JP, GB18030, Big5,
+/// ...). Unknown charsets, and invalid bytes, are decoded as lossy UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::encoded_words::decode_charset;
+///
+/// assert_eq!(decode_charset(b"Caf\xe9", "ISO-8859-1"), "Café");
+/// assert_eq!(decode_charset("Café".as_bytes(), "utf-8"), "Café");
+/// ```
+pub fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    let charset_lower = charset.trim().trim_matches('"').to_lowercase();
 
     match charset_lower.as_str() {
-        "utf-8" | "utf8" => {
-            // Try UTF-8 decoding, fall back to lossy if invalid
-            String::from_utf8(bytes.to_vec())
-                .unwrap_or_else(|_| String::from_utf8_lossy(bytes).to_string())
-        }
+        "utf-8" | "utf8" | "us-ascii" | "ascii" => String::from_utf8_lossy(bytes).into_owned(),
         "iso-8859-1" | "latin1" => {
             // ISO-8859-1: each byte maps directly to a Unicode code point
             bytes.iter().map(|&b| b as char).collect()
         }
-        "windows-1252" | "cp1252" => {
-            // Windows-1252: similar to ISO-8859-1 but with different mappings for 0x80-0x9F
-            // For now, use lossy conversion (full support would require encoding_rs crate)
-            String::from_utf8_lossy(bytes).to_string()
-        }
-        _ => {
-            // Unknown charset: use lossy conversion
-            String::from_utf8_lossy(bytes).to_string()
-        }
+        _ => decode_other_charset(bytes, &charset_lower),
+    }
+}
+
+/// Decode a charset other than UTF-8 and ISO-8859-1 through `encoding_rs`
+#[cfg(feature = "charsets")]
+fn decode_other_charset(bytes: &[u8], charset: &str) -> String {
+    match encoding_rs::Encoding::for_label(charset.as_bytes()) {
+        Some(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+        None => String::from_utf8_lossy(bytes).into_owned(),
     }
 }
 
+/// Decode a charset other than UTF-8 and ISO-8859-1 as lossy UTF-8
+#[cfg(not(feature = "charsets"))]
+fn decode_other_charset(bytes: &[u8], _charset: &str) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,28 +420,44 @@ mod tests {
         assert_eq!(result, b"Hello=ZZ");
     }
 
-    // Tests for charset_to_string
+    // Tests for decode_charset
     #[test]
-    fn test_charset_to_string_utf8() {
+    fn test_decode_charset_utf8() {
         let bytes = "Hello 世界".as_bytes();
-        let result = charset_to_string(bytes, "UTF-8");
+        let result = decode_charset(bytes, "UTF-8");
         assert_eq!(result, "Hello 世界");
     }
 
     #[test]
-    fn test_charset_to_string_iso_8859_1() {
+    fn test_decode_charset_iso_8859_1() {
         let bytes = &[b'C', b'a', b'f', 0xE9]; // "Café" in ISO-8859-1
-        let result = charset_to_string(bytes, "ISO-8859-1");
+        let result = decode_charset(bytes, "ISO-8859-1");
         assert_eq!(result, "Café");
     }
 
     #[test]
-    fn test_charset_to_string_unknown() {
+    fn test_decode_charset_unknown() {
         let bytes = b"Hello";
-        let result = charset_to_string(bytes, "unknown-charset");
+        let result = decode_charset(bytes, "unknown-charset");
         assert_eq!(result, "Hello");
     }
 
+    #[cfg(feature = "charsets")]
+    #[test]
+    fn test_decode_charset_legacy() {
+        // "Привет" in KOI8-R
+        let koi8 = [0xF0, 0xD2, 0xC9, 0xD7, 0xC5, 0xD4];
+        assert_eq!(decode_charset(&koi8, "KOI8-R"), "Привет");
+        // "日本" in Shift_JIS
+        assert_eq!(
+            decode_charset(&[0x93, 0xFA, 0x96, 0x7B], "Shift_JIS"),
+            "日本"
+        );
+        // Euro sign and smart quotes of Windows-1252
+        assert_eq!(decode_charset(&[0x80, 0x93, 0x94], "windows-1252"), "€“”");
+        assert_eq!(decode_charset(&[0xA4], "iso-8859-15"), "€");
+    }
+
     // Tests for decode_encoded_word
     #[test]
     fn test_decode_encoded_word_base64_utf8() {
@@ -493,6 +526,12 @@ mod tests {
         assert_eq!(result, "");
     }
 
+    #[test]
+    fn test_decode_header_value_keeps_plain_non_ascii() {
+        let result = decode_header_value("Café =?UTF-8?Q?cr=C3=A8me?= brûlée");
+        assert_eq!(result, "Café crème brûlée");
+    }
+
     // Tests for find_encoded_word_end
     #[test]
     fn test_find_encoded_word_end_valid() {
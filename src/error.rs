@@ -21,6 +21,10 @@ pub enum NntpError {
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
+    /// Argument rejected by the client before anything was sent to the server
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
     /// NNTP protocol error with response code
     #[error("NNTP error {code}: {message}")]
     Protocol {
@@ -74,6 +78,14 @@ pub enum NntpError {
     #[error("Encryption required: {0}")]
     EncryptionRequired(String),
 
+    /// Server refused the connection because too many are open for this account
+    #[error("Connection limit reached: {0}")]
+    ConnectionLimit(String),
+
+    /// Operation aborted through a [`CancellationToken`](crate::CancellationToken)
+    #[error("Operation cancelled")]
+    Cancelled,
+
     /// Connection closed unexpectedly
     #[error("Connection closed")]
     ConnectionClosed,
@@ -82,6 +94,33 @@ pub enum NntpError {
     #[error("UTF-8 error: {0}")]
     Utf8(#[from] std::string::FromUtf8Error),
 
+    /// Length, count or offset in binary data (PAR2, yEnc) is out of range or overflows
+    #[error("{context}: value {value} out of range")]
+    LengthOutOfRange {
+        /// Field or computation that failed
+        context: &'static str,
+        /// Offending value as found in the data
+        value: u64,
+    },
+
+    /// Binary data declares a size larger than the parser is willing to allocate
+    #[error("{context}: {requested} bytes exceeds the limit of {limit} bytes")]
+    AllocationTooLarge {
+        /// What was going to be allocated
+        context: &'static str,
+        /// Requested size in bytes (or elements)
+        requested: u64,
+        /// Maximum allowed
+        limit: u64,
+    },
+
+    /// Article body failed strict checks (see [`ServerConfig::strict_dot_stuffing`](crate::ServerConfig::strict_dot_stuffing))
+    ///
+    /// The whole response was read, so the connection is still usable.
+    /// Fetch the article again, preferably from another server.
+    #[error("Malformed article body: {0}")]
+    MalformedBody(String),
+
     /// Generic error
     #[error("{0}")]
     Other(String),
@@ -0,0 +1,831 @@
+//! C ABI for the decoding and verification primitives
+//!
+//! Enabled with the `ffi` feature. Build a shared or static library with:
+//!
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! cargo rustc --release --lib --features ffi --crate-type staticlib
+//! ```
+//!
+//! The matching C header is `include/nntp_rs.h`.
+//!
+//! # Conventions
+//!
+//! - Functions returning `int32_t` return [`NNTP_OK`] on success or a negative
+//!   `NNTP_ERR_*` code; [`nntp_last_error`] describes the last failure on the
+//!   calling thread.
+//! - Memory allocated by the library ([`NntpBuffer`], handles, result structs)
+//!   must be released with the matching `nntp_*_free` function.
+//! - Input pointers may be NULL only when the accompanying length is 0.
+//! - Panics never cross the ABI boundary; they are reported as [`NNTP_ERR_PANIC`].
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr::{self, NonNull};
+
+use crc32fast::Hasher as Crc32;
+use md5::{Digest, Md5};
+
+use crate::error::{NntpError, Result};
+use crate::par2::{FileStatus, Par2File};
+use crate::yenc;
+
+/// Success
+pub const NNTP_OK: i32 = 0;
+/// A required pointer was NULL or an argument was out of range
+pub const NNTP_ERR_INVALID_ARGUMENT: i32 = -1;
+/// Input data could not be decoded or parsed
+pub const NNTP_ERR_INVALID_DATA: i32 = -2;
+/// The requested item does not exist (e.g. unknown PAR2 file ID)
+pub const NNTP_ERR_NOT_FOUND: i32 = -3;
+/// An internal panic was caught
+pub const NNTP_ERR_PANIC: i32 = -99;
+
+/// CRC status: no CRC available to compare against
+pub const NNTP_CRC_UNKNOWN: i32 = -1;
+/// CRC status: CRC mismatch (data corrupted)
+pub const NNTP_CRC_MISMATCH: i32 = 0;
+/// CRC status: CRC verified
+pub const NNTP_CRC_OK: i32 = 1;
+
+/// PAR2 file status: complete and intact
+pub const NNTP_PAR2_COMPLETE: i32 = 0;
+/// PAR2 file status: damaged slices (see `damaged_slices`)
+pub const NNTP_PAR2_DAMAGED: i32 = 1;
+/// PAR2 file status: missing (no data)
+pub const NNTP_PAR2_MISSING: i32 = 2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NULs cannot be represented in a C string
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run `f`, converting errors and panics into status codes
+fn guard(f: impl FnOnce() -> std::result::Result<(), i32>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => NNTP_OK,
+        Ok(Err(code)) => code,
+        Err(_) => {
+            set_last_error("internal panic".to_string());
+            NNTP_ERR_PANIC
+        }
+    }
+}
+
+fn invalid_argument(message: &str) -> i32 {
+    set_last_error(message.to_string());
+    NNTP_ERR_INVALID_ARGUMENT
+}
+
+fn invalid_data(error: NntpError) -> i32 {
+    set_last_error(error.to_string());
+    NNTP_ERR_INVALID_DATA
+}
+
+/// Borrow an input buffer, allowing NULL only for empty input
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes if non-null.
+unsafe fn input<'a>(data: *const u8, len: usize) -> std::result::Result<&'a [u8], i32> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(invalid_argument("input pointer is NULL"));
+    }
+    // SAFETY: caller guarantees `data` is valid for `len` bytes
+    Ok(unsafe { std::slice::from_raw_parts(data, len) })
+}
+
+/// Check an output pointer, which may point to uninitialized memory
+fn output<T>(out: *mut T) -> std::result::Result<NonNull<T>, i32> {
+    NonNull::new(out).ok_or_else(|| invalid_argument("output pointer is NULL"))
+}
+
+/// Borrow a live handle created by this library
+///
+/// # Safety
+///
+/// `handle` must be NULL or point to a live, properly initialized `T`.
+unsafe fn handle<'a, T>(handle: *mut T) -> std::result::Result<&'a mut T, i32> {
+    // SAFETY: forwarded caller contract
+    unsafe { handle.as_mut() }.ok_or_else(|| invalid_argument("handle is NULL"))
+}
+
+/// Byte buffer allocated by the library
+///
+/// Release with [`nntp_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct NntpBuffer {
+    /// Buffer contents (NULL when empty)
+    pub data: *mut u8,
+    /// Number of bytes
+    pub len: usize,
+}
+
+impl NntpBuffer {
+    const EMPTY: Self = Self {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+
+    fn from_vec(data: Vec<u8>) -> Self {
+        if data.is_empty() {
+            return Self::EMPTY;
+        }
+        let boxed = data.into_boxed_slice();
+        let len = boxed.len();
+        Self {
+            data: Box::into_raw(boxed).cast::<u8>(),
+            len,
+        }
+    }
+}
+
+/// Free a buffer returned by the library and reset it to empty
+///
+/// # Safety
+///
+/// `buffer` must be NULL or point to an `NntpBuffer` filled in by this library
+/// that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_buffer_free(buffer: *mut NntpBuffer) {
+    // SAFETY: caller guarantees the pointer is NULL or valid
+    let Some(buffer) = (unsafe { buffer.as_mut() }) else {
+        return;
+    };
+    if !buffer.data.is_null() {
+        // SAFETY: data/len came from `Box<[u8]>::into_raw` in `NntpBuffer::from_vec`
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)) });
+    }
+    *buffer = NntpBuffer::EMPTY;
+}
+
+/// Get the message for the last error on this thread
+///
+/// Returns NULL if no error occurred. The string is valid until the next
+/// failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn nntp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+// ----------------------------------------------------------------------------
+// yEnc
+// ----------------------------------------------------------------------------
+
+/// Result of [`nntp_yenc_decode`]
+///
+/// Release `data` with [`nntp_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct NntpYencDecoded {
+    /// Decoded bytes
+    pub data: NntpBuffer,
+    /// CRC32 of the decoded bytes
+    pub crc32: u32,
+    /// `NNTP_CRC_OK`, `NNTP_CRC_MISMATCH` or `NNTP_CRC_UNKNOWN`
+    pub crc_status: i32,
+    /// Total file size from the =ybegin line
+    pub file_size: u64,
+    /// Part number (0 for single-part files)
+    pub part: u32,
+    /// Total parts (0 if not given)
+    pub total: u32,
+    /// 1-based offset of the part in the file (1 for single-part files)
+    pub begin: u64,
+}
+
+/// Decode a yEnc article body
+///
+/// # Safety
+///
+/// `input` must be valid for reads of `len` bytes and `out` must point to
+/// writable memory for an `NntpYencDecoded`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_yenc_decode(
+    input: *const u8,
+    len: usize,
+    out: *mut NntpYencDecoded,
+) -> i32 {
+    guard(|| {
+        // SAFETY: forwarded caller contract
+        let input = unsafe { self::input(input, len) }?;
+        let out = output(out)?;
+        let decoded = yenc::decode(input).map_err(invalid_data)?;
+
+        // SAFETY: `out` is non-null and writable (API contract)
+        unsafe {
+            out.write(NntpYencDecoded {
+                crc32: decoded.calculated_crc32,
+                crc_status: match decoded.verify_crc32() {
+                    Some(true) => NNTP_CRC_OK,
+                    Some(false) => NNTP_CRC_MISMATCH,
+                    None => NNTP_CRC_UNKNOWN,
+                },
+                file_size: decoded.header.size,
+                part: decoded.header.part.unwrap_or(0),
+                total: decoded.header.total.unwrap_or(0),
+                begin: decoded.part.as_ref().map_or(1, |part| part.begin),
+                data: NntpBuffer::from_vec(decoded.data),
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Part information for [`nntp_yenc_encode`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NntpYencPartInfo {
+    /// Part number (1-based)
+    pub part: u32,
+    /// Total number of parts
+    pub total: u32,
+    /// 1-based offset of the first byte of this part
+    pub begin: u64,
+    /// 1-based offset of the last byte of this part
+    pub end: u64,
+    /// Total file size
+    pub file_size: u64,
+}
+
+/// yEnc-encode data, optionally as one part of a multi-part file
+///
+/// `line_length` of 0 uses the customary 128. Pass NULL for `part_info` to
+/// encode a single-part file.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes, `filename` must be a
+/// NUL-terminated UTF-8 string, `part_info` must be NULL or valid, and `out`
+/// must point to writable memory for an `NntpBuffer`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_yenc_encode(
+    data: *const u8,
+    len: usize,
+    filename: *const c_char,
+    line_length: usize,
+    part_info: *const NntpYencPartInfo,
+    out: *mut NntpBuffer,
+) -> i32 {
+    guard(|| {
+        // SAFETY: forwarded caller contract
+        let data = unsafe { input(data, len) }?;
+        if filename.is_null() {
+            return Err(invalid_argument("filename is NULL"));
+        }
+        // SAFETY: caller guarantees a NUL-terminated string
+        let filename = unsafe { CStr::from_ptr(filename) }
+            .to_str()
+            .map_err(|_| invalid_argument("filename is not valid UTF-8"))?;
+        // SAFETY: caller guarantees NULL or a valid pointer
+        let part_info = unsafe { part_info.as_ref() }
+            .map(|info| (info.part, info.total, info.begin, info.end, info.file_size));
+        let out = output(out)?;
+
+        let line_length = if line_length == 0 { 128 } else { line_length };
+        let encoded = yenc::encode(data, filename, line_length, part_info).map_err(invalid_data)?;
+        // SAFETY: `out` is non-null and writable (API contract)
+        unsafe { out.write(NntpBuffer::from_vec(encoded)) };
+        Ok(())
+    })
+}
+
+// ----------------------------------------------------------------------------
+// Streaming CRC32 / MD5
+// ----------------------------------------------------------------------------
+
+/// Opaque streaming CRC32 state
+#[derive(Debug)]
+pub struct NntpCrc32 {
+    hasher: Crc32,
+}
+
+/// Create a streaming CRC32 (IEEE, as used by yEnc and PAR2)
+///
+/// Release with [`nntp_crc32_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn nntp_crc32_new() -> *mut NntpCrc32 {
+    Box::into_raw(Box::new(NntpCrc32 {
+        hasher: Crc32::new(),
+    }))
+}
+
+/// Feed data into a streaming CRC32
+///
+/// # Safety
+///
+/// `state` must come from [`nntp_crc32_new`] and `data` must be valid for
+/// reads of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_crc32_update(
+    state: *mut NntpCrc32,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    guard(|| {
+        // SAFETY: forwarded caller contract
+        let state = unsafe { handle(state) }?;
+        // SAFETY: forwarded caller contract
+        let data = unsafe { input(data, len) }?;
+        state.hasher.update(data);
+        Ok(())
+    })
+}
+
+/// Get the CRC32 of all data fed so far (the state stays usable)
+///
+/// Returns 0 if `state` is NULL.
+///
+/// # Safety
+///
+/// `state` must be NULL or come from [`nntp_crc32_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_crc32_value(state: *const NntpCrc32) -> u32 {
+    // SAFETY: caller guarantees NULL or a live state
+    unsafe { state.as_ref() }.map_or(0, |state| state.hasher.clone().finalize())
+}
+
+/// Free a streaming CRC32
+///
+/// # Safety
+///
+/// `state` must be NULL or come from [`nntp_crc32_new`] and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_crc32_free(state: *mut NntpCrc32) {
+    if !state.is_null() {
+        // SAFETY: pointer came from `Box::into_raw` in `nntp_crc32_new`
+        drop(unsafe { Box::from_raw(state) });
+    }
+}
+
+/// Opaque streaming MD5 state
+#[derive(Debug)]
+pub struct NntpMd5 {
+    hasher: Md5,
+}
+
+/// Create a streaming MD5 (as used by PAR2)
+///
+/// Release with [`nntp_md5_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn nntp_md5_new() -> *mut NntpMd5 {
+    Box::into_raw(Box::new(NntpMd5 { hasher: Md5::new() }))
+}
+
+/// Feed data into a streaming MD5
+///
+/// # Safety
+///
+/// `state` must come from [`nntp_md5_new`] and `data` must be valid for
+/// reads of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_md5_update(state: *mut NntpMd5, data: *const u8, len: usize) -> i32 {
+    guard(|| {
+        // SAFETY: forwarded caller contract
+        let state = unsafe { handle(state) }?;
+        // SAFETY: forwarded caller contract
+        let data = unsafe { input(data, len) }?;
+        state.hasher.update(data);
+        Ok(())
+    })
+}
+
+/// Write the MD5 of all data fed so far to `digest` (the state stays usable)
+///
+/// # Safety
+///
+/// `state` must come from [`nntp_md5_new`] and `digest` must point to 16
+/// writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_md5_value(state: *const NntpMd5, digest: *mut [u8; 16]) -> i32 {
+    guard(|| {
+        // SAFETY: caller guarantees NULL or a live state
+        let state = unsafe { state.as_ref() }.ok_or_else(|| invalid_argument("state is NULL"))?;
+        let digest = output(digest)?;
+        // SAFETY: `digest` is non-null and points to 16 writable bytes (API contract)
+        unsafe { digest.write(state.hasher.clone().finalize().into()) };
+        Ok(())
+    })
+}
+
+/// Free a streaming MD5
+///
+/// # Safety
+///
+/// `state` must be NULL or come from [`nntp_md5_new`] and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_md5_free(state: *mut NntpMd5) {
+    if !state.is_null() {
+        // SAFETY: pointer came from `Box::into_raw` in `nntp_md5_new`
+        drop(unsafe { Box::from_raw(state) });
+    }
+}
+
+// ----------------------------------------------------------------------------
+// PAR2
+// ----------------------------------------------------------------------------
+
+/// Opaque parsed PAR2 set
+#[derive(Debug)]
+pub struct NntpPar2 {
+    par2: Par2File,
+    /// Files in recovery set order
+    files: Vec<Par2Entry>,
+}
+
+/// File description with a NUL-terminated name for C callers
+#[derive(Debug)]
+struct Par2Entry {
+    file_id: [u8; 16],
+    name: CString,
+    length: u64,
+}
+
+/// Description of one file in a PAR2 set
+#[repr(C)]
+#[derive(Debug)]
+pub struct NntpPar2FileInfo {
+    /// PAR2 file ID
+    pub file_id: [u8; 16],
+    /// File name (owned by the `NntpPar2` handle)
+    pub name: *const c_char,
+    /// File length in bytes
+    pub length: u64,
+}
+
+/// Result of [`nntp_par2_verify_file`]
+///
+/// Release with [`nntp_par2_verification_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct NntpPar2Verification {
+    /// `NNTP_PAR2_COMPLETE`, `NNTP_PAR2_DAMAGED` or `NNTP_PAR2_MISSING`
+    pub status: i32,
+    /// File-local indices of damaged slices (NULL if none)
+    pub damaged_slices: *mut u64,
+    /// Number of damaged slice indices
+    pub damaged_count: usize,
+}
+
+/// Parse PAR2 data (an index or volume file, or several concatenated)
+///
+/// Returns NULL on failure or if the data describes no files; see
+/// [`nntp_last_error`]. Release with [`nntp_par2_free`].
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_par2_parse(data: *const u8, len: usize) -> *mut NntpPar2 {
+    let mut handle = ptr::null_mut();
+    guard(|| {
+        // SAFETY: forwarded caller contract
+        let data = unsafe { input(data, len) }?;
+        let par2 = Par2File::parse(data).map_err(invalid_data)?;
+        if par2.file_descriptions.is_empty() {
+            set_last_error("no PAR2 file descriptions found".to_string());
+            return Err(NNTP_ERR_INVALID_DATA);
+        }
+        let files = par2_files(&par2).map_err(invalid_data)?;
+        handle = Box::into_raw(Box::new(NntpPar2 { par2, files }));
+        Ok(())
+    });
+    handle
+}
+
+/// List files in recovery set order, falling back to name order without a main packet
+fn par2_files(par2: &Par2File) -> Result<Vec<Par2Entry>> {
+    let mut ids: Vec<[u8; 16]> = match &par2.main {
+        Some(main) => main.file_ids.clone(),
+        None => par2.file_descriptions.keys().copied().collect(),
+    };
+    if par2.main.is_none() {
+        ids.sort_by_key(|id| par2.file_descriptions.get(id).map(|desc| desc.name.clone()));
+    }
+
+    ids.into_iter()
+        .filter_map(|id| par2.file_descriptions.get(&id).map(|desc| (id, desc)))
+        .map(|(id, desc)| {
+            let name = CString::new(desc.name.as_bytes()).map_err(|_| {
+                NntpError::InvalidResponse("PAR2 file name contains NUL".to_string())
+            })?;
+            Ok(Par2Entry {
+                file_id: id,
+                name,
+                length: desc.length,
+            })
+        })
+        .collect()
+}
+
+/// Number of files described by a PAR2 set (0 if `par2` is NULL)
+///
+/// # Safety
+///
+/// `par2` must be NULL or come from [`nntp_par2_parse`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_par2_file_count(par2: *const NntpPar2) -> usize {
+    // SAFETY: caller guarantees NULL or a live handle
+    unsafe { par2.as_ref() }.map_or(0, |par2| par2.files.len())
+}
+
+/// Describe the file at `index` (0-based, recovery set order)
+///
+/// # Safety
+///
+/// `par2` must come from [`nntp_par2_parse`] and `out` must point to writable
+/// memory for an `NntpPar2FileInfo`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_par2_file_info(
+    par2: *const NntpPar2,
+    index: usize,
+    out: *mut NntpPar2FileInfo,
+) -> i32 {
+    guard(|| {
+        // SAFETY: caller guarantees NULL or a live handle
+        let par2 = unsafe { par2.as_ref() }.ok_or_else(|| invalid_argument("par2 is NULL"))?;
+        let out = output(out)?;
+        let entry = par2.files.get(index).ok_or_else(|| {
+            set_last_error(format!("file index {} out of range", index));
+            NNTP_ERR_NOT_FOUND
+        })?;
+        // SAFETY: `out` is non-null and writable (API contract)
+        unsafe {
+            out.write(NntpPar2FileInfo {
+                file_id: entry.file_id,
+                name: entry.name.as_ptr(),
+                length: entry.length,
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Verify file contents against the PAR2 checksums
+///
+/// Pass `len` 0 for a missing file.
+///
+/// # Safety
+///
+/// `par2` must come from [`nntp_par2_parse`], `file_id` must point to 16
+/// readable bytes, `data` must be valid for reads of `len` bytes, and `out`
+/// must point to writable memory for an `NntpPar2Verification`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_par2_verify_file(
+    par2: *const NntpPar2,
+    file_id: *const [u8; 16],
+    data: *const u8,
+    len: usize,
+    out: *mut NntpPar2Verification,
+) -> i32 {
+    guard(|| {
+        // SAFETY: caller guarantees NULL or a live handle
+        let par2 = unsafe { par2.as_ref() }.ok_or_else(|| invalid_argument("par2 is NULL"))?;
+        // SAFETY: caller guarantees NULL or 16 readable bytes
+        let file_id =
+            unsafe { file_id.as_ref() }.ok_or_else(|| invalid_argument("file_id is NULL"))?;
+        // SAFETY: forwarded caller contract
+        let data = unsafe { input(data, len) }?;
+        let out = output(out)?;
+
+        if !par2.par2.file_descriptions.contains_key(file_id) {
+            set_last_error("file ID not found in PAR2 set".to_string());
+            return Err(NNTP_ERR_NOT_FOUND);
+        }
+        let verification = par2.par2.verify_file(data, file_id).map_err(invalid_data)?;
+
+        let (status, damaged) = match verification.status {
+            FileStatus::Complete => (NNTP_PAR2_COMPLETE, Vec::new()),
+            FileStatus::Damaged(slices) => (NNTP_PAR2_DAMAGED, slices),
+            FileStatus::Missing => (NNTP_PAR2_MISSING, Vec::new()),
+        };
+        let damaged: Box<[u64]> = damaged.into_iter().map(|index| index as u64).collect();
+        let damaged_count = damaged.len();
+        // SAFETY: `out` is non-null and writable (API contract)
+        unsafe {
+            out.write(NntpPar2Verification {
+                status,
+                damaged_slices: if damaged_count == 0 {
+                    ptr::null_mut()
+                } else {
+                    Box::into_raw(damaged).cast::<u64>()
+                },
+                damaged_count,
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Free the slice list of a verification result and reset it
+///
+/// # Safety
+///
+/// `result` must be NULL or point to a result filled in by
+/// [`nntp_par2_verify_file`] that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_par2_verification_free(result: *mut NntpPar2Verification) {
+    // SAFETY: caller guarantees NULL or a valid result
+    let Some(result) = (unsafe { result.as_mut() }) else {
+        return;
+    };
+    if !result.damaged_slices.is_null() {
+        // SAFETY: pointer/len came from `Box<[u64]>::into_raw` in `nntp_par2_verify_file`
+        drop(unsafe {
+            Box::from_raw(ptr::slice_from_raw_parts_mut(
+                result.damaged_slices,
+                result.damaged_count,
+            ))
+        });
+    }
+    result.damaged_slices = ptr::null_mut();
+    result.damaged_count = 0;
+}
+
+/// Free a parsed PAR2 set
+///
+/// # Safety
+///
+/// `par2` must be NULL or come from [`nntp_par2_parse`] and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nntp_par2_free(par2: *mut NntpPar2) {
+    if !par2.is_null() {
+        // SAFETY: pointer came from `Box::into_raw` in `nntp_par2_parse`
+        drop(unsafe { Box::from_raw(par2) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yenc_round_trip() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let filename = CString::new("test.bin").unwrap();
+        let mut encoded = NntpBuffer::EMPTY;
+        let status = unsafe {
+            nntp_yenc_encode(
+                data.as_ptr(),
+                data.len(),
+                filename.as_ptr(),
+                0,
+                ptr::null(),
+                &mut encoded,
+            )
+        };
+        assert_eq!(status, NNTP_OK);
+        assert!(!encoded.data.is_null());
+
+        let mut decoded = NntpYencDecoded {
+            data: NntpBuffer::EMPTY,
+            crc32: 0,
+            crc_status: 0,
+            file_size: 0,
+            part: 0,
+            total: 0,
+            begin: 0,
+        };
+        let status = unsafe { nntp_yenc_decode(encoded.data, encoded.len, &mut decoded) };
+        assert_eq!(status, NNTP_OK);
+        let output = unsafe { std::slice::from_raw_parts(decoded.data.data, decoded.data.len) };
+        assert_eq!(output, data.as_slice());
+        assert_eq!(decoded.crc_status, NNTP_CRC_OK);
+        assert_eq!(decoded.file_size, 1000);
+        assert_eq!(decoded.begin, 1);
+
+        unsafe {
+            nntp_buffer_free(&mut encoded);
+            nntp_buffer_free(&mut decoded.data);
+        }
+        assert!(encoded.data.is_null());
+    }
+
+    #[test]
+    fn test_yenc_decode_errors() {
+        let mut decoded = std::mem::MaybeUninit::<NntpYencDecoded>::uninit();
+        let status = unsafe { nntp_yenc_decode(ptr::null(), 10, decoded.as_mut_ptr()) };
+        assert_eq!(status, NNTP_ERR_INVALID_ARGUMENT);
+
+        let garbage = b"not yenc";
+        let status =
+            unsafe { nntp_yenc_decode(garbage.as_ptr(), garbage.len(), decoded.as_mut_ptr()) };
+        assert_eq!(status, NNTP_ERR_INVALID_DATA);
+        assert!(!nntp_last_error().is_null());
+    }
+
+    #[test]
+    fn test_streaming_hashes() {
+        let crc = nntp_crc32_new();
+        let md5 = nntp_md5_new();
+        for chunk in [b"hello ".as_slice(), b"world".as_slice()] {
+            unsafe {
+                assert_eq!(nntp_crc32_update(crc, chunk.as_ptr(), chunk.len()), NNTP_OK);
+                assert_eq!(nntp_md5_update(md5, chunk.as_ptr(), chunk.len()), NNTP_OK);
+            }
+        }
+
+        assert_eq!(
+            unsafe { nntp_crc32_value(crc) },
+            crc32fast::hash(b"hello world")
+        );
+        let mut digest = [0u8; 16];
+        assert_eq!(unsafe { nntp_md5_value(md5, &mut digest) }, NNTP_OK);
+        assert_eq!(digest.as_slice(), Md5::digest(b"hello world").as_slice());
+
+        unsafe {
+            nntp_crc32_free(crc);
+            nntp_md5_free(md5);
+        }
+    }
+
+    #[test]
+    fn test_par2_verify_file() {
+        let data = b"par2 protected file contents".to_vec();
+        let file_id = [7u8; 16];
+        let hash: [u8; 16] = Md5::digest(&data).into();
+        let mut par2 = Par2File::new();
+        par2.file_descriptions.insert(
+            file_id,
+            crate::par2::FileDescriptionPacket {
+                file_id,
+                hash,
+                hash_16k: hash,
+                length: data.len() as u64,
+                name: "file.bin".into(),
+            },
+        );
+        let files = par2_files(&par2).unwrap();
+        let handle = Box::into_raw(Box::new(NntpPar2 { par2, files }));
+
+        assert_eq!(unsafe { nntp_par2_file_count(handle) }, 1);
+        let mut info = std::mem::MaybeUninit::<NntpPar2FileInfo>::uninit();
+        assert_eq!(
+            unsafe { nntp_par2_file_info(handle, 0, info.as_mut_ptr()) },
+            NNTP_OK
+        );
+        let info = unsafe { info.assume_init() };
+        assert_eq!(info.file_id, file_id);
+        assert_eq!(info.length, data.len() as u64);
+        assert_eq!(
+            unsafe { CStr::from_ptr(info.name) }.to_str().unwrap(),
+            "file.bin"
+        );
+
+        let mut result = std::mem::MaybeUninit::<NntpPar2Verification>::uninit();
+        let status = unsafe {
+            nntp_par2_verify_file(
+                handle,
+                &file_id,
+                data.as_ptr(),
+                data.len(),
+                result.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NNTP_OK);
+        let mut result = unsafe { result.assume_init() };
+        assert_eq!(result.status, NNTP_PAR2_COMPLETE);
+        unsafe { nntp_par2_verification_free(&mut result) };
+
+        let mut missing = std::mem::MaybeUninit::<NntpPar2Verification>::uninit();
+        let status = unsafe {
+            nntp_par2_verify_file(handle, &file_id, ptr::null(), 0, missing.as_mut_ptr())
+        };
+        assert_eq!(status, NNTP_OK);
+        assert_eq!(
+            unsafe { missing.assume_init_ref() }.status,
+            NNTP_PAR2_MISSING
+        );
+
+        let unknown = [0u8; 16];
+        let status = unsafe {
+            nntp_par2_verify_file(handle, &unknown, ptr::null(), 0, missing.as_mut_ptr())
+        };
+        assert_eq!(status, NNTP_ERR_NOT_FOUND);
+
+        unsafe { nntp_par2_free(handle) };
+    }
+
+    #[test]
+    fn test_par2_parse_invalid() {
+        let garbage = b"definitely not par2 data";
+        let par2 = unsafe { nntp_par2_parse(garbage.as_ptr(), garbage.len()) };
+        assert!(par2.is_null());
+        assert!(!nntp_last_error().is_null());
+        assert_eq!(unsafe { nntp_par2_file_count(ptr::null()) }, 0);
+    }
+}
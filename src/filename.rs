@@ -0,0 +1,323 @@
+//! Portable file names for downloaded files
+//!
+//! File names come from yEnc headers, subjects and PAR2 file descriptions,
+//! all chosen by whoever posted the article. Many are not valid on Windows
+//! (`:`, `?`, `CON.txt`, a trailing dot), and some try to reach outside the
+//! target directory. [`sanitize`] maps such names to ones every platform
+//! accepts; [`restore`] maps them back, so the original name can still be
+//! shown or matched against PAR2 file descriptions.
+//!
+//! Offending characters are moved into the Unicode private use area at
+//! U+F000 plus their ASCII value, the convention Cygwin and WSL use for the
+//! same problem. Names are sanitized the same way on every platform, so a
+//! download directory can be shared between systems.
+//!
+//! [`confine`] turns a posted name into a path inside a download directory,
+//! and [`create_confined`] opens it without following links planted there;
+//! [`OutputSink`](crate::output::OutputSink) writes every file this way.
+//! [`long_path`] lifts the 260 character `MAX_PATH` limit on Windows.
+//!
+//! [`sanitize`]: crate::filename::sanitize
+//! [`restore`]: crate::filename::restore
+//! [`confine`]: crate::filename::confine
+//! [`create_confined`]: crate::filename::create_confined
+//! [`long_path`]: crate::filename::long_path
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::filename::{restore, sanitize};
+//!
+//! let name = "Movie: The Sequel?.mkv";
+//! let safe = sanitize(name);
+//! assert_eq!(safe, "Movie\u{f03a} The Sequel\u{f03f}.mkv");
+//! assert_eq!(restore(&safe), name);
+//!
+//! assert_eq!(sanitize("nul.par2"), "nu\u{f06c}.par2");
+//! assert_eq!(sanitize("plain.rar"), "plain.rar");
+//! ```
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Start of the private use range invalid characters are moved to
+const ESCAPE_BASE: u32 = 0xF000;
+
+/// Device names Windows reserves regardless of extension
+const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+
+/// Map `name` to a file name valid on Windows and Unix
+///
+/// Escaped are the characters `< > : " / \ | ? *`, control characters, a
+/// trailing dot or space, and the last character of a reserved device name
+/// (`CON`, `PRN`, `AUX`, `NUL`, `COM0`-`COM9`, `LPT0`-`LPT9`). Since `/` and
+/// `\` are escaped, the result is always a single path component; `.` and
+/// `..` become `\u{f02e}` and `.\u{f02e}`.
+///
+/// Names that already contain characters from U+F000 to U+F07F are not
+/// escaped further, so [`restore`] turns those into ASCII as well.
+pub fn sanitize(name: &str) -> String {
+    let mut chars: Vec<char> = name
+        .chars()
+        .map(|c| if is_invalid(c) { escape(c) } else { c })
+        .collect();
+
+    if let Some(last) = chars.last_mut()
+        && matches!(*last, '.' | ' ')
+    {
+        *last = escape(*last);
+    }
+
+    let stem_len = chars.iter().position(|&c| c == '.').unwrap_or(chars.len());
+    let stem: String = chars[..stem_len].iter().collect();
+    if stem_len > 0 && is_reserved(stem.trim_end_matches(' ')) {
+        chars[stem_len - 1] = escape(chars[stem_len - 1]);
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Undo [`sanitize`]
+pub fn restore(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            let code = u32::from(c);
+            if (ESCAPE_BASE..ESCAPE_BASE + 0x80).contains(&code) {
+                char::from_u32(code - ESCAPE_BASE).unwrap_or(c)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Path inside `dir` for a file posted as `name`
+///
+/// Directories in `name` are dropped, whether separated by `/` or `\`, so
+/// `../../etc/passwd` and `C:\Windows\evil.dll` end up as `passwd` and
+/// `evil.dll` in `dir`. What is left goes through [`sanitize`] and the
+/// result through [`long_path`].
+///
+/// # Errors
+///
+/// An [`io::ErrorKind::InvalidInput`] error if no file name is left (`name`
+/// is empty, `.` or `..`, or ends in a separator).
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::filename::confine;
+/// use std::path::Path;
+///
+/// let path = confine(Path::new("/downloads"), "../../etc/passwd").unwrap();
+/// assert_eq!(path, Path::new("/downloads/passwd"));
+/// assert!(confine(Path::new("/downloads"), "..").is_err());
+/// ```
+pub fn confine(dir: &Path, name: &str) -> io::Result<PathBuf> {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    if matches!(file_name, "" | "." | "..") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Not a usable file name: {:?}", name),
+        ));
+    }
+    Ok(long_path(&dir.join(sanitize(file_name))))
+}
+
+/// Create (or replace) the file at `path` without following links
+///
+/// A symbolic link at `path` is refused rather than written through, since it
+/// could point anywhere. An existing file is removed first instead of being
+/// truncated, so a hard link to a file elsewhere is left untouched. The new
+/// file is created exclusively: if a link appears at `path` in the meantime,
+/// creation fails.
+///
+/// # Errors
+///
+/// - [`io::ErrorKind::InvalidInput`] - `path` is a symbolic link or a directory
+/// - Any I/O error from removing or creating the file
+pub fn create_confined(path: &Path) -> io::Result<File> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() || meta.is_dir() => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Refusing to write through {}", path.display()),
+            ));
+        }
+        Ok(_) => std::fs::remove_file(path)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    File::options().write(true).create_new(true).open(path)
+}
+
+/// Make `path` usable beyond `MAX_PATH` on Windows
+///
+/// On Windows the path is made absolute and given the `\\?\` prefix
+/// (`\\?\UNC\` for network shares), which lifts the 260 character limit of
+/// the classic API. Paths that already carry a prefix, and all paths on
+/// other platforms, are returned unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    absolute
+        .to_str()
+        .and_then(extended_length)
+        .map_or(absolute, PathBuf::from)
+}
+
+/// `\\?\` form of an absolute Windows path, if it has none yet
+fn extended_length(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{}", share));
+    }
+    let bytes = path.as_bytes();
+    let is_drive_absolute =
+        bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\';
+    is_drive_absolute.then(|| format!(r"\\?\{}", path))
+}
+
+fn is_invalid(c: char) -> bool {
+    matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') || c.is_ascii_control()
+}
+
+fn is_reserved(stem: &str) -> bool {
+    let upper = stem.to_ascii_uppercase();
+    if RESERVED_NAMES.contains(&upper.as_str()) {
+        return true;
+    }
+    match (upper.get(..3), upper.get(3..)) {
+        (Some("COM" | "LPT"), Some(digit)) => {
+            digit.len() == 1 && digit.bytes().all(|b| b.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+/// Move an ASCII character into the private use range
+fn escape(c: char) -> char {
+    char::from_u32(ESCAPE_BASE + u32::from(c)).unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_round_trips() {
+        for name in [
+            "plain.rar",
+            "a<b>c:d\"e|f?g*h.bin",
+            "../../etc/passwd",
+            r"C:\Windows\system32\evil.dll",
+            "tab\there\u{1}.txt",
+            "trailing.",
+            "trailing ",
+            "CON",
+            "con.txt",
+            "Lpt1.par2",
+            "aux .nfo",
+            ".",
+            "..",
+            "",
+            "ünïcödé 名前.mkv",
+        ] {
+            let safe = sanitize(name);
+            assert_eq!(restore(&safe), name, "{:?}", name);
+            assert!(!safe.contains(['/', '\\', ':']), "{:?}", safe);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_reserved_and_trailing() {
+        assert_eq!(sanitize("CON"), "CO\u{f04e}");
+        assert_eq!(sanitize("com7.vol00+01.par2"), "com\u{f037}.vol00+01.par2");
+        assert_eq!(sanitize("console.log"), "console.log");
+        assert_eq!(sanitize("COM10"), "COM10");
+        assert_eq!(sanitize("name."), "name\u{f02e}");
+        assert_eq!(sanitize(".."), ".\u{f02e}");
+        assert_eq!(sanitize("a/b"), "a\u{f02f}b");
+    }
+
+    #[test]
+    fn test_confine_stays_in_dir() {
+        let dir = Path::new("/downloads");
+        for (name, expected) in [
+            ("../../etc/passwd", "passwd"),
+            ("/etc/passwd", "passwd"),
+            (r"C:\Windows\evil.dll", "evil.dll"),
+            (r"..\..\CON.txt", "CO\u{f04e}.txt"),
+            ("sub/dir/file?.bin", "file\u{f03f}.bin"),
+        ] {
+            assert_eq!(
+                confine(dir, name).unwrap(),
+                dir.join(expected),
+                "{:?}",
+                name
+            );
+        }
+        for name in ["", ".", "..", "dir/", r"dir\..", "a/.."] {
+            assert!(confine(dir, name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_confined_refuses_links() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("nntp-rs-confine-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let outside = dir.join("outside.txt");
+        std::fs::write(&outside, b"keep").unwrap();
+
+        // Symbolic link planted under the name of a download
+        let link = dir.join("link.bin");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        assert!(create_confined(&link).is_err());
+
+        // Hard link: replaced, the other name keeps its content
+        let hard = dir.join("hard.bin");
+        std::fs::hard_link(&outside, &hard).unwrap();
+        create_confined(&hard).unwrap().write_all(b"new").unwrap();
+        assert_eq!(std::fs::read(&hard).unwrap(), b"new");
+        assert_eq!(std::fs::read(&outside).unwrap(), b"keep");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extended_length() {
+        assert_eq!(
+            extended_length(r"C:\downloads\file.bin").as_deref(),
+            Some(r"\\?\C:\downloads\file.bin")
+        );
+        assert_eq!(
+            extended_length("D:/downloads/file.bin").as_deref(),
+            Some(r"\\?\D:\downloads\file.bin")
+        );
+        assert_eq!(
+            extended_length(r"\\nas\share\file.bin").as_deref(),
+            Some(r"\\?\UNC\nas\share\file.bin")
+        );
+        assert_eq!(extended_length(r"\\?\C:\already"), None);
+        assert_eq!(extended_length(r"relative\file.bin"), None);
+    }
+
+    #[test]
+    fn test_long_path_is_noop_off_windows() {
+        if !cfg!(windows) {
+            let path = Path::new("/srv/downloads/file.bin");
+            assert_eq!(long_path(path), path);
+        }
+    }
+}
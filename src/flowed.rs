@@ -0,0 +1,262 @@
+//! format=flowed text (RFC 3676)
+//!
+//! Reference: https://datatracker.ietf.org/doc/html/rfc3676
+//!
+//! A `text/plain; format=flowed` body is wrapped for transport, but marks
+//! each line break it added with a trailing space (a "soft" break), so a
+//! reader can join the lines of a paragraph again and rewrap them for its
+//! window. Lines without the trailing space are "fixed" and end a
+//! paragraph. Quoted text carries its depth as leading `>` marks on every
+//! line.
+//!
+//! [`unflow`] turns such a body into one line per paragraph, and [`flow`]
+//! wraps paragraphs for posting. [`Article::unflowed_body`](crate::Article::unflowed_body)
+//! and [`ArticleBuilder::flowed_body`](crate::ArticleBuilder::flowed_body)
+//! apply them according to the Content-Type parameters.
+//!
+//! [`unflow`]: crate::flowed::unflow
+//! [`flow`]: crate::flowed::flow
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::flowed::{flow, unflow};
+//!
+//! let text = "A paragraph long enough to be wrapped at twenty columns.\n";
+//! let flowed = flow(text, 20);
+//! assert_eq!(
+//!     flowed,
+//!     "A paragraph long \nenough to be \nwrapped at twenty \ncolumns.\n"
+//! );
+//! assert_eq!(unflow(&flowed, false), text);
+//! ```
+
+/// Width the builder wraps flowed bodies at; RFC 3676 Section 4.2 allows
+/// lines of up to 78 characters
+pub const LINE_WIDTH: usize = 72;
+
+/// Usenet signature separator, a fixed line despite its trailing space
+const SIGNATURE_SEPARATOR: &str = "-- ";
+
+/// Join the soft-broken lines of a format=flowed body into paragraphs
+///
+/// Each paragraph becomes one line, prefixed with its quote marks and a
+/// space (`"> "`, `">> "`, ...) when quoted. Space-stuffing is removed.
+/// With `delsp` (the `DelSp=yes` parameter) the space marking a soft break
+/// is removed as well, for text without spaces between words.
+pub fn unflow(text: &str, delsp: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut paragraph: Option<(usize, String)> = None;
+
+    for line in text.lines() {
+        let (depth, content) = split_quote(line);
+        let content = content.strip_prefix(' ').unwrap_or(content);
+        let soft = content.ends_with(' ') && content != SIGNATURE_SEPARATOR;
+
+        // A change of quote depth ends a paragraph (RFC 3676 Section 4.5)
+        if let Some((current, text)) = paragraph.take_if(|(current, _)| *current != depth) {
+            push_paragraph(&mut out, current, &text);
+        }
+        let (_, text) = paragraph.get_or_insert_with(|| (depth, String::new()));
+        if soft && delsp {
+            text.push_str(&content[..content.len() - 1]);
+        } else {
+            text.push_str(content);
+        }
+        if !soft && let Some((depth, text)) = paragraph.take() {
+            push_paragraph(&mut out, depth, &text);
+        }
+    }
+    if let Some((depth, text)) = paragraph {
+        push_paragraph(&mut out, depth, &text);
+    }
+
+    if !text.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// Wrap text into a format=flowed body with lines of at most `width` characters
+///
+/// Every input line is a paragraph. Lines starting with `>` are quoted and
+/// are rewrapped with their quote marks; lines that could be mistaken for
+/// quotes or for a "From " line are space-stuffed, and trailing spaces are
+/// dropped so they are not read as soft breaks. A word longer than the
+/// width stays on a line of its own.
+pub fn flow(text: &str, width: usize) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 16);
+
+    for line in text.lines() {
+        if line == SIGNATURE_SEPARATOR {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        let (depth, content) = split_quote(line.trim_end_matches(' '));
+        let content = if depth > 0 {
+            content.strip_prefix(' ').unwrap_or(content)
+        } else {
+            content
+        };
+
+        let prefix = quote_prefix(depth, content.is_empty());
+        let limit = width.saturating_sub(prefix.chars().count()).max(1);
+        for segment in wrap(content, limit) {
+            out.push_str(&prefix);
+            if depth == 0 && needs_stuffing(segment) {
+                out.push(' ');
+            }
+            out.push_str(segment);
+            out.push('\n');
+        }
+    }
+
+    if !text.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// Quote depth of a line and the text after its quote marks
+fn split_quote(line: &str) -> (usize, &str) {
+    let content = line.trim_start_matches('>');
+    (line.len() - content.len(), content)
+}
+
+/// Quote marks for `depth`, followed by a space unless the line is empty
+fn quote_prefix(depth: usize, empty: bool) -> String {
+    let mut prefix = ">".repeat(depth);
+    if depth > 0 && !empty {
+        prefix.push(' ');
+    }
+    prefix
+}
+
+fn push_paragraph(out: &mut String, depth: usize, text: &str) {
+    out.push_str(&quote_prefix(depth, text.is_empty()));
+    out.push_str(text);
+    out.push('\n');
+}
+
+/// Check if an unquoted line must be space-stuffed (RFC 3676 Section 4.4)
+fn needs_stuffing(line: &str) -> bool {
+    line.starts_with(' ') || line.starts_with('>') || line.starts_with("From ")
+}
+
+/// Split `content` after spaces into segments of at most `limit` characters
+///
+/// Every segment but the last ends with the space it was split after.
+fn wrap(content: &str, limit: usize) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut rest = content;
+    while rest.chars().count() > limit {
+        let mut split = None;
+        for (count, (index, ch)) in rest.char_indices().enumerate() {
+            if ch != ' ' {
+                continue;
+            }
+            if count >= limit && split.is_some() {
+                break;
+            }
+            split = Some(index + 1);
+            if count >= limit {
+                break;
+            }
+        }
+        match split {
+            Some(split) if split < rest.len() => {
+                segments.push(&rest[..split]);
+                rest = &rest[split..];
+            }
+            _ => break,
+        }
+    }
+    segments.push(rest);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unflow_joins_soft_breaks() {
+        let body = "One two \nthree.\nFixed line.\n\nNext \npara.\n";
+        assert_eq!(
+            unflow(body, false),
+            "One two three.\nFixed line.\n\nNext para.\n"
+        );
+    }
+
+    #[test]
+    fn test_unflow_delsp() {
+        assert_eq!(
+            unflow("日本語の \n文章です。\n", true),
+            "日本語の文章です。\n"
+        );
+        assert_eq!(unflow("word \nwrap\n", false), "word wrap\n");
+    }
+
+    #[test]
+    fn test_unflow_quotes_and_stuffing() {
+        let body = "> Quoted text \n> continues.\n>> Deeper\nReply with \n From here.\n";
+        assert_eq!(
+            unflow(body, false),
+            "> Quoted text continues.\n>> Deeper\nReply with From here.\n"
+        );
+    }
+
+    #[test]
+    fn test_unflow_quote_depth_change_ends_paragraph() {
+        assert_eq!(
+            unflow("> soft \nnot quoted\n", false),
+            "> soft \nnot quoted\n"
+        );
+    }
+
+    #[test]
+    fn test_signature_separator_is_fixed() {
+        let body = "Bye \nnow.\n-- \nAlice\n";
+        assert_eq!(unflow(body, false), "Bye now.\n-- \nAlice\n");
+        assert_eq!(flow("-- \nAlice", 72), "-- \nAlice");
+    }
+
+    #[test]
+    fn test_flow_stuffs_and_trims() {
+        assert_eq!(
+            flow("From the start   \n>quoted\n indented\n", 72),
+            " From the start\n> quoted\n  indented\n"
+        );
+    }
+
+    #[test]
+    fn test_flow_quoted_paragraph() {
+        let flowed = flow(">> one two three four five six", 16);
+        assert_eq!(flowed, ">> one two \n>> three four \n>> five six");
+        assert_eq!(unflow(&flowed, false), ">> one two three four five six");
+    }
+
+    #[test]
+    fn test_flow_long_word() {
+        assert_eq!(
+            flow("see https://example.com/a/very/long/path here", 10),
+            "see \nhttps://example.com/a/very/long/path \nhere"
+        );
+        assert_eq!(flow("unbreakable", 4), "unbreakable");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "First paragraph with quite a few words in it, enough to wrap.\n\
+                    \n\
+                    > A quoted paragraph that is also long enough to need wrapping.\n\
+                    >\n\
+                    Ünïcödé wörds wrap by characters, not by bytes at all.\n";
+        for width in [20, 40, 72] {
+            let flowed = flow(text, width);
+            assert!(flowed.lines().all(|line| line.chars().count() <= width));
+            assert_eq!(unflow(&flowed, false), text);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Streaming file hashes that survive a restart
+//!
+//! Verifying a downloaded file against its PAR2 description needs the MD5
+//! of the whole file, and yEnc trailers carry a CRC32. [`StreamingHasher`]
+//! computes both while the file is written, so it does not have to be read
+//! back afterwards. Its state can be saved as a [`HashSnapshot`] (e.g. in a
+//! [`DownloadJob`](crate::DownloadJob)) and resumed after a crash without
+//! re-reading the bytes already on disk.
+//!
+//! The MD5 is computed here rather than with the `md-5` crate used
+//! elsewhere, because resuming needs the intermediate state: `md-5` keeps
+//! its chaining state private and offers no way to export or restore it.
+//! It is only used for that reason; the digests are checked against `md-5`
+//! in the tests.
+//!
+//! [`StreamingHasher`]: crate::hash::StreamingHasher
+//! [`HashSnapshot`]: crate::hash::HashSnapshot
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::hash::StreamingHasher;
+//!
+//! let mut hasher = StreamingHasher::new();
+//! hasher.update(b"first half, ");
+//! let snapshot = hasher.snapshot();
+//!
+//! // ... process restarts ...
+//! let mut hasher = StreamingHasher::resume(&snapshot).unwrap();
+//! hasher.update(b"second half");
+//! let digest = hasher.finalize();
+//! assert_eq!(digest.len, 23);
+//! assert_eq!(digest.crc32, crc32fast::hash(b"first half, second half"));
+//! ```
+
+use crate::error::{NntpError, Result};
+
+/// MD5 block size in bytes
+const MD5_BLOCK: usize = 64;
+
+/// MD5 initial state (RFC 1321 Section 3.3)
+const MD5_INIT: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+/// MD5 per-round shift amounts (RFC 1321 Section 3.4)
+const MD5_SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, //
+    5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, //
+    4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, //
+    6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+/// MD5 per-round constants, `floor(abs(sin(i + 1)) * 2^32)`
+const MD5_CONSTANTS: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, //
+    0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501, //
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, //
+    0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821, //
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, //
+    0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8, //
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, //
+    0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a, //
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, //
+    0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70, //
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, //
+    0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665, //
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, //
+    0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1, //
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, //
+    0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// Saved state of a [`StreamingHasher`]
+///
+/// Small (under 100 bytes) and serializable with the `serde` feature, so it
+/// can be written to a job journal after every segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HashSnapshot {
+    /// Bytes hashed so far
+    pub len: u64,
+    /// CRC32 of the bytes hashed so far
+    pub crc32: u32,
+    /// MD5 chaining state after the last complete 64-byte block
+    pub md5_state: [u32; 4],
+    /// Bytes after the last complete block (fewer than 64)
+    pub pending: Vec<u8>,
+}
+
+/// Final hashes of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileDigest {
+    /// Length in bytes
+    pub len: u64,
+    /// CRC32, as in yEnc trailers
+    pub crc32: u32,
+    /// MD5, as in PAR2 file descriptions
+    pub md5: [u8; 16],
+}
+
+/// Incremental CRC32 and MD5 of a file
+#[derive(Debug, Clone)]
+pub struct StreamingHasher {
+    len: u64,
+    crc: crc32fast::Hasher,
+    md5_state: [u32; 4],
+    pending: Vec<u8>,
+}
+
+impl Default for StreamingHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingHasher {
+    /// Start hashing a new file
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            crc: crc32fast::Hasher::new(),
+            md5_state: MD5_INIT,
+            pending: Vec::with_capacity(MD5_BLOCK),
+        }
+    }
+
+    /// Continue from a saved state
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::LengthOutOfRange`] - The snapshot is inconsistent: its
+    ///   pending bytes do not match its length
+    pub fn resume(snapshot: &HashSnapshot) -> Result<Self> {
+        if snapshot.pending.len() as u64 != snapshot.len % MD5_BLOCK as u64 {
+            return Err(NntpError::LengthOutOfRange {
+                context: "hash snapshot pending bytes",
+                value: snapshot.pending.len() as u64,
+            });
+        }
+        let mut pending = Vec::with_capacity(MD5_BLOCK);
+        pending.extend_from_slice(&snapshot.pending);
+        Ok(Self {
+            len: snapshot.len,
+            crc: crc32fast::Hasher::new_with_initial_len(snapshot.crc32, snapshot.len),
+            md5_state: snapshot.md5_state,
+            pending,
+        })
+    }
+
+    /// Hash the next bytes of the file
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.crc.update(data);
+        self.len += data.len() as u64;
+
+        if !self.pending.is_empty() {
+            let take = (MD5_BLOCK - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.pending.len() < MD5_BLOCK {
+                return;
+            }
+            md5_compress(&mut self.md5_state, &self.pending);
+            self.pending.clear();
+        }
+
+        let mut blocks = data.chunks_exact(MD5_BLOCK);
+        for block in &mut blocks {
+            md5_compress(&mut self.md5_state, block);
+        }
+        self.pending.extend_from_slice(blocks.remainder());
+    }
+
+    /// Bytes hashed so far
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Check if nothing has been hashed yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// CRC32 of the bytes hashed so far
+    pub fn crc32(&self) -> u32 {
+        self.crc.clone().finalize()
+    }
+
+    /// Save the current state
+    pub fn snapshot(&self) -> HashSnapshot {
+        HashSnapshot {
+            len: self.len,
+            crc32: self.crc32(),
+            md5_state: self.md5_state,
+            pending: self.pending.clone(),
+        }
+    }
+
+    /// Finish hashing and return the file's hashes
+    pub fn finalize(mut self) -> FileDigest {
+        let crc32 = self.crc32();
+
+        // RFC 1321 Section 3.1-3.2: a one bit, zeros up to 56 mod 64, then the bit length
+        let mut tail = std::mem::take(&mut self.pending);
+        tail.push(0x80);
+        while tail.len() % MD5_BLOCK != MD5_BLOCK - 8 {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&self.len.wrapping_mul(8).to_le_bytes());
+        for block in tail.chunks_exact(MD5_BLOCK) {
+            md5_compress(&mut self.md5_state, block);
+        }
+
+        let mut md5 = [0u8; 16];
+        for (out, word) in md5.chunks_exact_mut(4).zip(self.md5_state) {
+            out.copy_from_slice(&word.to_le_bytes());
+        }
+        FileDigest {
+            len: self.len,
+            crc32,
+            md5,
+        }
+    }
+}
+
+/// Apply the MD5 compression function to one 64-byte block
+fn md5_compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let f = f
+            .wrapping_add(a)
+            .wrapping_add(MD5_CONSTANTS[i])
+            .wrapping_add(words[g]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(f.rotate_left(MD5_SHIFTS[i]));
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use md5::{Digest, Md5};
+
+    fn reference(data: &[u8]) -> FileDigest {
+        FileDigest {
+            len: data.len() as u64,
+            crc32: crc32fast::hash(data),
+            md5: Md5::digest(data).into(),
+        }
+    }
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_matches_reference_hashes() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 119, 120, 128, 1000, 4096] {
+            let data = data(len);
+            let mut hasher = StreamingHasher::new();
+            hasher.update(&data);
+            assert_eq!(hasher.finalize(), reference(&data), "length {}", len);
+        }
+        // RFC 1321 test suite
+        let mut hasher = StreamingHasher::new();
+        hasher.update(b"abc");
+        assert_eq!(
+            hasher.finalize().md5,
+            [
+                0x90, 0x01, 0x50, 0x98, 0x3c, 0xd2, 0x4f, 0xb0, 0xd6, 0x96, 0x3f, 0x7d, 0x28, 0xe1,
+                0x7f, 0x72
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resume_at_any_point() {
+        let data = data(700);
+        for split in [0, 1, 63, 64, 65, 200, 699, 700] {
+            let mut first = StreamingHasher::new();
+            // Uneven chunks exercise the pending buffer
+            for chunk in data[..split].chunks(37) {
+                first.update(chunk);
+            }
+            let snapshot = first.snapshot();
+            assert_eq!(snapshot.len, split as u64);
+
+            let mut resumed = StreamingHasher::resume(&snapshot).unwrap();
+            resumed.update(&data[split..]);
+            assert_eq!(resumed.finalize(), reference(&data), "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_resume_rejects_inconsistent_snapshot() {
+        let mut snapshot = StreamingHasher::new().snapshot();
+        snapshot.len = 10;
+        assert!(StreamingHasher::resume(&snapshot).is_err());
+    }
+}
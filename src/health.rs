@@ -0,0 +1,272 @@
+//! Aggregated status snapshots for daemons
+//!
+//! [`NntpRuntime::health`](crate::NntpRuntime::health) collects the state of
+//! every registered pool, server group and limiter into one
+//! [`HealthSnapshot`], ready to be serialized for a `/health` endpoint or a
+//! status page. Job queues are owned by the application and added with
+//! [`HealthSnapshot::with_queue`].
+
+use std::time::Duration;
+
+use crate::jobs::{DownloadJob, JobStatus, UploadJob};
+use crate::ratelimit::{BandwidthLimiter, ConnectionLimiter};
+use crate::segments::FetchProgress;
+
+/// Status of everything an [`NntpRuntime`](crate::NntpRuntime) owns
+///
+/// # Example
+///
+/// ```no_run
+/// use nntp_rs::{NntpPool, NntpRuntime, QueueHealth, ServerConfig};
+///
+/// # async fn example() -> nntp_rs::Result<()> {
+/// let mut runtime = NntpRuntime::new();
+/// let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// runtime.add_pool(NntpPool::new(config, 10).await?);
+///
+/// let downloads = Vec::new();
+/// let snapshot = runtime
+///     .health()
+///     .await
+///     .with_queue(QueueHealth::from_download_jobs("downloads", &downloads));
+/// println!("healthy: {}", snapshot.healthy);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HealthSnapshot {
+    /// Every registered server group has at least one server up
+    pub healthy: bool,
+    /// Standalone connection pools
+    pub pools: Vec<PoolHealth>,
+    /// Servers of all registered server groups, in priority order per group
+    pub servers: Vec<ServerHealth>,
+    /// Job queues added by the application
+    pub queues: Vec<QueueHealth>,
+    /// Connection limiters
+    pub connection_limiters: Vec<ConnectionLimiterHealth>,
+    /// Bandwidth limiters
+    pub bandwidth_limiters: Vec<BandwidthLimiterHealth>,
+    /// Background tasks still running
+    pub running_tasks: usize,
+}
+
+impl HealthSnapshot {
+    /// Add the status of an application-owned job queue
+    pub fn with_queue(mut self, queue: QueueHealth) -> Self {
+        self.queues.push(queue);
+        self
+    }
+}
+
+/// Connection usage of one pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolHealth {
+    /// Server identifier (host:port)
+    pub server_id: String,
+    /// Configured pool size
+    pub max_size: u32,
+    /// Open connections
+    pub connections: u32,
+    /// Open connections waiting in the pool
+    pub idle_connections: u32,
+    /// Connections checked out
+    pub in_use: u32,
+    /// Current connection limit, below `max_size` while the server refuses connections
+    pub connection_limit: usize,
+}
+
+/// Up/down state, speed and error counts of one server in a group
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerHealth {
+    /// Server identifier (host:port)
+    pub server_id: String,
+    /// The server is not degraded and is used for requests
+    pub up: bool,
+    /// Ratio of successful to total requests (1.0 before the first request)
+    pub availability: f64,
+    /// Download rate over the last few seconds
+    pub bytes_per_second: u64,
+    /// Bytes downloaded in total
+    pub bytes_downloaded: u64,
+    /// Successful requests
+    pub successful_requests: u64,
+    /// Failed requests (connection errors, timeouts)
+    pub failed_requests: u64,
+    /// 430 (not found) responses
+    pub not_found_requests: u64,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Time since the last successful request
+    pub since_last_success: Option<Duration>,
+    /// Time since the last failed request
+    pub since_last_failure: Option<Duration>,
+    /// Connection pool of the server
+    pub pool: PoolHealth,
+}
+
+/// Depth and progress of a job queue
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueueHealth {
+    /// Queue name chosen by the application
+    pub name: String,
+    /// Jobs waiting to start
+    pub queued: usize,
+    /// Jobs in progress
+    pub running: usize,
+    /// Jobs paused by the user
+    pub paused: usize,
+    /// Jobs finished successfully
+    pub completed: usize,
+    /// Jobs that failed
+    pub failed: usize,
+    /// Segments or parts not yet transferred, over all jobs
+    pub pending_items: usize,
+    /// Bytes not yet transferred, over all jobs
+    pub pending_bytes: u64,
+}
+
+impl QueueHealth {
+    /// Summarize a queue of download jobs
+    pub fn from_download_jobs(name: impl Into<String>, jobs: &[DownloadJob]) -> Self {
+        let mut queue = Self {
+            name: name.into(),
+            ..Self::default()
+        };
+        for job in jobs {
+            queue.add(job.status, &job.progress());
+        }
+        queue
+    }
+
+    /// Summarize a queue of upload jobs
+    pub fn from_upload_jobs(name: impl Into<String>, jobs: &[UploadJob]) -> Self {
+        let mut queue = Self {
+            name: name.into(),
+            ..Self::default()
+        };
+        for job in jobs {
+            queue.add(job.status, &job.progress());
+        }
+        queue
+    }
+
+    fn add(&mut self, status: JobStatus, progress: &FetchProgress) {
+        match status {
+            JobStatus::Queued => self.queued += 1,
+            JobStatus::Running => self.running += 1,
+            JobStatus::Paused => self.paused += 1,
+            JobStatus::Completed => self.completed += 1,
+            JobStatus::Failed => self.failed += 1,
+        }
+        let finished =
+            progress.completed_segments + progress.failed_segments + progress.not_found_segments;
+        self.pending_items += progress.total_segments.saturating_sub(finished);
+        self.pending_bytes += progress
+            .total_bytes
+            .saturating_sub(progress.downloaded_bytes);
+    }
+}
+
+/// Slots of a [`ConnectionLimiter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectionLimiterHealth {
+    /// Configured maximum
+    pub max_connections: usize,
+    /// Current limit, lowered while the server refuses connections
+    pub current_limit: usize,
+    /// Slots free right now
+    pub available: usize,
+}
+
+impl From<&ConnectionLimiter> for ConnectionLimiterHealth {
+    fn from(limiter: &ConnectionLimiter) -> Self {
+        Self {
+            max_connections: limiter.max_connections(),
+            current_limit: limiter.current_limit(),
+            available: limiter.available(),
+        }
+    }
+}
+
+/// Configuration and fill level of a [`BandwidthLimiter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandwidthLimiterHealth {
+    /// Configured rate
+    pub bytes_per_second: u64,
+    /// Bucket capacity
+    pub burst: u64,
+    /// Bytes that can be taken without waiting
+    pub available_tokens: u64,
+}
+
+impl BandwidthLimiterHealth {
+    pub(crate) async fn collect(limiter: &BandwidthLimiter) -> Self {
+        let (bytes_per_second, burst) = limiter.config().await;
+        Self {
+            bytes_per_second,
+            burst,
+            available_tokens: limiter.available_tokens().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nzb::{Nzb, NzbFile, NzbSegment};
+    use crate::segments::SegmentStatus;
+
+    fn download_job(id: &str) -> DownloadJob {
+        let file = NzbFile {
+            poster: String::new(),
+            date: 0,
+            subject: String::new(),
+            groups: Vec::new(),
+            segments: (1..=4)
+                .map(|number| NzbSegment {
+                    bytes: 100,
+                    number,
+                    message_id: format!("{}.{}@test", id, number),
+                })
+                .collect(),
+        };
+        let nzb = Nzb {
+            meta: Default::default(),
+            files: vec![file],
+        };
+        DownloadJob::new(id, nzb, "primary", "out")
+    }
+
+    #[test]
+    fn test_queue_health_counts_jobs() {
+        let mut running = download_job("a");
+        running.status = JobStatus::Running;
+        running.set_segment(0, 0, SegmentStatus::Completed);
+        let queued = download_job("b");
+
+        let queue = QueueHealth::from_download_jobs("downloads", &[running, queued]);
+        assert_eq!(queue.name, "downloads");
+        assert_eq!((queue.queued, queue.running, queue.completed), (1, 1, 0));
+        assert_eq!(queue.pending_items, 7);
+        assert_eq!(queue.pending_bytes, 700);
+    }
+
+    #[tokio::test]
+    async fn test_limiter_health() {
+        let limiter = ConnectionLimiter::new(4);
+        let _permit = limiter.acquire().await;
+        let health = ConnectionLimiterHealth::from(&limiter);
+        assert_eq!((health.max_connections, health.available), (4, 3));
+
+        let bandwidth =
+            BandwidthLimiterHealth::collect(&BandwidthLimiter::new(1000, Some(500))).await;
+        assert_eq!((bandwidth.bytes_per_second, bandwidth.burst), (1000, 500));
+    }
+}
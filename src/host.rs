@@ -0,0 +1,133 @@
+//! Server host strings: hostnames and IP literals
+//!
+//! [`ServerConfig::host`](crate::ServerConfig::host) may hold a hostname, an
+//! IPv4 address or an IPv6 address, with or without URL-style brackets
+//! (`[2001:db8::1]`) and, for link-local addresses, a zone (`fe80::1%eth0`
+//! or `fe80::1%2`). These helpers give each consumer the form it needs.
+
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+use tokio_rustls::rustls::pki_types::ServerName;
+
+use crate::error::{NntpError, Result};
+
+/// The host without surrounding brackets
+pub(crate) fn unbracketed(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Split an IP literal into the address and its zone, if any
+///
+/// Returns `None` for hostnames.
+pub(crate) fn ip_literal(host: &str) -> Option<(IpAddr, Option<&str>)> {
+    let host = unbracketed(host);
+    if let Ok(ip) = host.parse() {
+        return Some((ip, None));
+    }
+    let (addr, zone) = host.split_once('%')?;
+    let ip: Ipv6Addr = addr.parse().ok()?;
+    (!zone.is_empty()).then_some((IpAddr::V6(ip), Some(zone)))
+}
+
+/// The socket address of an IP literal host, without any lookup
+///
+/// Returns `None` for hostnames and for zones given as interface names,
+/// which only the system resolver can map to an interface index.
+pub(crate) fn literal_socket_addr(host: &str, port: u16) -> Option<SocketAddr> {
+    match ip_literal(host)? {
+        (ip, None) => Some(SocketAddr::new(ip, port)),
+        (IpAddr::V6(ip), Some(zone)) => {
+            let scope_id = zone.parse().ok()?;
+            Some(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id)))
+        }
+        (IpAddr::V4(_), Some(_)) => None,
+    }
+}
+
+/// `host:port`, with IPv6 literals in brackets
+pub(crate) fn authority(host: &str, port: u16) -> String {
+    let host = unbracketed(host);
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// The name to verify the server certificate against
+///
+/// IP literals are checked against the certificate's IP address entries,
+/// without the zone, which only has meaning on the local machine.
+pub(crate) fn tls_server_name(host: &str) -> Result<ServerName<'static>> {
+    if let Some((ip, _)) = ip_literal(host) {
+        return Ok(ServerName::IpAddress(ip.into()));
+    }
+    ServerName::try_from(host.to_string())
+        .map_err(|e| NntpError::Tls(format!("Invalid domain: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_literal() {
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(ip_literal("2001:db8::1"), Some((v6, None)));
+        assert_eq!(ip_literal("[2001:db8::1]"), Some((v6, None)));
+        assert_eq!(
+            ip_literal("192.0.2.1"),
+            Some(("192.0.2.1".parse().unwrap(), None))
+        );
+        let link_local: IpAddr = "fe80::1".parse().unwrap();
+        assert_eq!(ip_literal("fe80::1%eth0"), Some((link_local, Some("eth0"))));
+        assert_eq!(ip_literal("[fe80::1%3]"), Some((link_local, Some("3"))));
+        assert_eq!(ip_literal("fe80::1%"), None);
+        assert_eq!(ip_literal("news.example.com"), None);
+        assert_eq!(ip_literal("[news.example.com]"), None);
+    }
+
+    #[test]
+    fn test_literal_socket_addr() {
+        assert_eq!(
+            literal_socket_addr("[::1]", 563),
+            Some("[::1]:563".parse().unwrap())
+        );
+        assert_eq!(
+            literal_socket_addr("127.0.0.1", 119),
+            Some("127.0.0.1:119".parse().unwrap())
+        );
+        let scoped = literal_socket_addr("fe80::1%3", 119).unwrap();
+        assert!(matches!(scoped, SocketAddr::V6(addr) if addr.scope_id() == 3));
+        // Interface names are left to the system resolver
+        assert_eq!(literal_socket_addr("fe80::1%eth0", 119), None);
+        assert_eq!(literal_socket_addr("news.example.com", 119), None);
+    }
+
+    #[test]
+    fn test_authority() {
+        assert_eq!(authority("news.example.com", 563), "news.example.com:563");
+        assert_eq!(authority("192.0.2.1", 119), "192.0.2.1:119");
+        assert_eq!(authority("2001:db8::1", 563), "[2001:db8::1]:563");
+        assert_eq!(authority("[2001:db8::1]", 563), "[2001:db8::1]:563");
+        assert_eq!(authority("fe80::1%eth0", 119), "[fe80::1%eth0]:119");
+    }
+
+    #[test]
+    fn test_tls_server_name() {
+        assert!(matches!(
+            tls_server_name("[fe80::1%eth0]").unwrap(),
+            ServerName::IpAddress(_)
+        ));
+        assert!(matches!(
+            tls_server_name("news.example.com").unwrap(),
+            ServerName::DnsName(_)
+        ));
+        assert!(matches!(
+            tls_server_name("not a host"),
+            Err(NntpError::Tls(_))
+        ));
+    }
+}
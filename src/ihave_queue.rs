@@ -0,0 +1,401 @@
+//! Retry queue for IHAVE offers the peer deferred
+//!
+//! A peer answers IHAVE with 436 when it cannot take an article right now
+//! (RFC 3977 Section 6.3.2); the article is meant to be offered again
+//! later. [`IhaveQueue`] offers articles through [`NntpClient::ihave`],
+//! remembers every deferred message-id with an exponentially growing retry
+//! time, and lists the ones that are due. Articles themselves are not kept:
+//! the feeding process re-reads them from its spool by message-id.
+//!
+//! The deferred list survives restarts through
+//! [`StateStore::save_deferred_offers`](crate::storage::StateStore::save_deferred_offers),
+//! or, with the `serde` feature, in any format the caller picks.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use nntp_rs::ihave_queue::{IhaveQueue, OfferOutcome};
+//! use nntp_rs::{Article, NntpClient};
+//!
+//! # async fn example(
+//! #     client: &mut NntpClient,
+//! #     spool: impl Fn(&str) -> Article,
+//! # ) -> nntp_rs::Result<()> {
+//! let mut queue = IhaveQueue::new();
+//! let article = spool("<a@example.com>");
+//! if let OfferOutcome::Deferred { retry_at } = queue.offer(client, &article).await? {
+//!     println!("Offering again at {}", retry_at);
+//! }
+//!
+//! // Later: re-offer whatever is due
+//! for message_id in queue.due(chrono::Utc::now().timestamp()) {
+//!     queue.offer(client, &spool(&message_id)).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::article::Article;
+use crate::client::NntpClient;
+use crate::error::{NntpError, Result};
+use crate::storage::{DEFERRED_NAMESPACE, StateStore};
+
+/// Default delay before the first re-offer
+const DEFAULT_INITIAL_DELAY: Duration = Duration::from_secs(60);
+
+/// Default upper bound of the delay between re-offers
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Default number of deferrals before an article is given up on
+const DEFAULT_MAX_ATTEMPTS: u32 = 10;
+
+/// An offer the peer deferred with 436
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeferredOffer {
+    /// Message-ID of the deferred article
+    pub message_id: String,
+    /// Number of times the peer deferred it
+    pub attempts: u32,
+    /// Unix time (seconds) from which it may be offered again
+    pub retry_at: i64,
+}
+
+impl DeferredOffer {
+    /// Text form stored by [`StateStore`]: attempts and retry time
+    fn encode(&self) -> String {
+        format!("{} {}", self.attempts, self.retry_at)
+    }
+
+    /// Parse the text form of the offer for `message_id`
+    fn decode(message_id: String, text: &str) -> Result<Self> {
+        let parsed = text.split_once(' ').and_then(|(attempts, retry_at)| {
+            Some((attempts.parse().ok()?, retry_at.parse().ok()?))
+        });
+        let Some((attempts, retry_at)) = parsed else {
+            return Err(NntpError::InvalidResponse(format!(
+                "Corrupt stored deferred offer for {}",
+                message_id
+            )));
+        };
+        Ok(Self {
+            message_id,
+            attempts,
+            retry_at,
+        })
+    }
+}
+
+/// Result of offering one article
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OfferOutcome {
+    /// The peer received the article (235)
+    Transferred,
+    /// The peer already has the article or does not want it (435)
+    NotWanted,
+    /// The peer refused the article after receiving it (437)
+    Rejected(String),
+    /// The peer asked for the article later (436); it is queued until `retry_at`
+    Deferred {
+        /// Unix time (seconds) from which it may be offered again
+        retry_at: i64,
+    },
+    /// The peer deferred the article too often; it left the queue
+    GaveUp,
+}
+
+/// IHAVE offers awaiting a retry
+#[derive(Debug, Clone)]
+pub struct IhaveQueue {
+    deferred: BTreeMap<String, DeferredOffer>,
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: u32,
+}
+
+impl Default for IhaveQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IhaveQueue {
+    /// Create an empty queue retrying after 1 minute, doubling up to 1 hour,
+    /// for at most 10 deferrals
+    pub fn new() -> Self {
+        Self {
+            deferred: BTreeMap::new(),
+            initial_delay: DEFAULT_INITIAL_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Set the delay before the first re-offer and the cap it doubles up to
+    pub fn with_backoff(mut self, initial_delay: Duration, max_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay.max(initial_delay);
+        self
+    }
+
+    /// Set how many deferrals an article gets before it is given up on (at least 1)
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Add offers restored from storage, e.g. after a restart
+    pub fn restore(&mut self, offers: impl IntoIterator<Item = DeferredOffer>) {
+        for offer in offers {
+            self.deferred.insert(offer.message_id.clone(), offer);
+        }
+    }
+
+    /// Offer an article with IHAVE and track a deferral
+    ///
+    /// An article that is in the queue leaves it on any answer but 436.
+    ///
+    /// # Errors
+    ///
+    /// Errors other than the peer's 435, 436 and 437 answers are returned
+    /// as they are, and leave the queue unchanged; see [`NntpClient::ihave`].
+    pub async fn offer(
+        &mut self,
+        client: &mut NntpClient,
+        article: &Article,
+    ) -> Result<OfferOutcome> {
+        let message_id = &article.headers.message_id;
+        let outcome = match client.ihave(message_id, article).await {
+            Ok(()) => OfferOutcome::Transferred,
+            Err(NntpError::ArticleNotWanted) => OfferOutcome::NotWanted,
+            Err(NntpError::TransferRejected(reason)) => OfferOutcome::Rejected(reason),
+            Err(NntpError::TransferNotPossible(reason)) => {
+                debug!("IHAVE {} deferred: {}", message_id, reason);
+                return Ok(self.defer(message_id, chrono::Utc::now().timestamp()));
+            }
+            Err(e) => return Err(e),
+        };
+        self.deferred.remove(message_id);
+        Ok(outcome)
+    }
+
+    /// Record a deferral of `message_id` at Unix time `now`
+    ///
+    /// Used by [`offer`](Self::offer); call it directly when offering by
+    /// other means.
+    pub fn defer(&mut self, message_id: &str, now: i64) -> OfferOutcome {
+        let attempts = self
+            .deferred
+            .get(message_id)
+            .map_or(1, |offer| offer.attempts.saturating_add(1));
+        if attempts > self.max_attempts {
+            debug!(
+                "Giving up on {} after {} deferrals",
+                message_id,
+                attempts - 1
+            );
+            self.deferred.remove(message_id);
+            return OfferOutcome::GaveUp;
+        }
+
+        let retry_at = now.saturating_add(self.delay(attempts).as_secs() as i64);
+        self.deferred.insert(
+            message_id.to_string(),
+            DeferredOffer {
+                message_id: message_id.to_string(),
+                attempts,
+                retry_at,
+            },
+        );
+        OfferOutcome::Deferred { retry_at }
+    }
+
+    /// Delay after deferral `attempt` (1-based)
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+
+    /// Message-IDs that may be offered again at Unix time `now`, earliest first
+    pub fn due(&self, now: i64) -> Vec<String> {
+        let mut due: Vec<&DeferredOffer> = self
+            .deferred
+            .values()
+            .filter(|offer| offer.retry_at <= now)
+            .collect();
+        due.sort_by_key(|offer| offer.retry_at);
+        due.into_iter()
+            .map(|offer| offer.message_id.clone())
+            .collect()
+    }
+
+    /// Unix time of the earliest retry, if anything is queued
+    pub fn next_retry(&self) -> Option<i64> {
+        self.deferred.values().map(|offer| offer.retry_at).min()
+    }
+
+    /// Deferred offer for `message_id`, if queued
+    pub fn get(&self, message_id: &str) -> Option<&DeferredOffer> {
+        self.deferred.get(message_id)
+    }
+
+    /// Drop `message_id` from the queue, returning whether it was queued
+    pub fn remove(&mut self, message_id: &str) -> bool {
+        self.deferred.remove(message_id).is_some()
+    }
+
+    /// Every deferred offer, by message-id
+    pub fn iter(&self) -> impl Iterator<Item = &DeferredOffer> {
+        self.deferred.values()
+    }
+
+    /// Number of deferred offers
+    pub fn len(&self) -> usize {
+        self.deferred.len()
+    }
+
+    /// Check if nothing is deferred
+    pub fn is_empty(&self) -> bool {
+        self.deferred.is_empty()
+    }
+}
+
+impl StateStore {
+    /// Store the offers deferred in `queue`, replacing every stored offer
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn save_deferred_offers(&self, queue: &IhaveQueue) -> Result<()> {
+        let records = queue
+            .iter()
+            .map(|offer| (offer.message_id.clone(), offer.encode()));
+        self.replace_records(DEFERRED_NAMESPACE, records.collect())
+            .await
+    }
+
+    /// Offers stored with [`save_deferred_offers`](Self::save_deferred_offers),
+    /// for [`IhaveQueue::restore`]
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::Utf8`] / [`NntpError::InvalidResponse`] - A stored
+    ///   offer is corrupt
+    /// - Storage backend errors
+    pub async fn load_deferred_offers(&self) -> Result<Vec<DeferredOffer>> {
+        self.records(DEFERRED_NAMESPACE)
+            .await?
+            .into_iter()
+            .map(|(message_id, value)| DeferredOffer::decode(message_id, &value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defer_backs_off_and_gives_up() {
+        let mut queue = IhaveQueue::new()
+            .with_backoff(Duration::from_secs(10), Duration::from_secs(25))
+            .with_max_attempts(3);
+
+        assert_eq!(
+            queue.defer("<a@b>", 1000),
+            OfferOutcome::Deferred { retry_at: 1010 }
+        );
+        assert_eq!(
+            queue.defer("<a@b>", 1010),
+            OfferOutcome::Deferred { retry_at: 1030 }
+        );
+        assert_eq!(
+            queue.defer("<a@b>", 1030),
+            OfferOutcome::Deferred { retry_at: 1055 }
+        );
+        assert_eq!(queue.get("<a@b>").map(|offer| offer.attempts), Some(3));
+        assert_eq!(queue.defer("<a@b>", 1055), OfferOutcome::GaveUp);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_due_in_retry_order() {
+        let mut queue = IhaveQueue::new();
+        queue.restore([
+            DeferredOffer {
+                message_id: "<late@b>".to_string(),
+                attempts: 1,
+                retry_at: 200,
+            },
+            DeferredOffer {
+                message_id: "<early@b>".to_string(),
+                attempts: 2,
+                retry_at: 100,
+            },
+            DeferredOffer {
+                message_id: "<future@b>".to_string(),
+                attempts: 1,
+                retry_at: 300,
+            },
+        ]);
+
+        assert_eq!(queue.due(250), vec!["<early@b>", "<late@b>"]);
+        assert_eq!(queue.next_retry(), Some(100));
+        assert!(queue.remove("<early@b>"));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deferred_offer_serde_roundtrip() {
+        let offer = DeferredOffer {
+            message_id: "<a@b>".to_string(),
+            attempts: 2,
+            retry_at: 1_700_000_000,
+        };
+        let json = serde_json::to_string(&offer).unwrap();
+        assert_eq!(serde_json::from_str::<DeferredOffer>(&json).unwrap(), offer);
+    }
+
+    #[tokio::test]
+    async fn test_deferred_offers_persist() {
+        let store = StateStore::memory();
+        let mut queue = IhaveQueue::new();
+        queue.defer("<kept@b>", 1000);
+        queue.defer("<kept@b>", 1100);
+        queue.defer("<sent@b>", 1000);
+        store.save_deferred_offers(&queue).await.unwrap();
+
+        queue.remove("<sent@b>");
+        store.save_deferred_offers(&queue).await.unwrap();
+
+        let mut restored = IhaveQueue::new();
+        restored.restore(store.load_deferred_offers().await.unwrap());
+        assert_eq!(
+            restored.iter().collect::<Vec<_>>(),
+            queue.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            restored.get("<kept@b>").map(|offer| offer.attempts),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_deferred_offer_is_an_error() {
+        let store = StateStore::memory();
+        store
+            .storage()
+            .put(DEFERRED_NAMESPACE, "<a@b>", b"2".to_vec())
+            .await
+            .unwrap();
+        assert!(store.load_deferred_offers().await.is_err());
+    }
+}
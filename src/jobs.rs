@@ -0,0 +1,1006 @@
+//! Resumable download and upload jobs
+//!
+//! [`DownloadJob`] and [`UploadJob`] hold everything needed to pick a
+//! transfer up again after a restart: what is being transferred, which
+//! [`ServerGroup`](crate::ServerGroup) it goes through, and the status of
+//! every segment. With the `serde` feature both serialize, so queue managers
+//! and on-disk journals share one state schema.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::jobs::DownloadJob;
+//! use nntp_rs::{SegmentStatus, parse_nzb};
+//!
+//! let nzb = parse_nzb(r#"<?xml version="1.0"?>
+//! <nzb xmlns="http://www.newzbin.com/DTD/2003/nzb">
+//!   <file poster="p" date="0" subject="a.bin">
+//!     <groups><group>alt.test</group></groups>
+//!     <segments>
+//!       <segment bytes="100" number="1">part1@example.com</segment>
+//!       <segment bytes="100" number="2">part2@example.com</segment>
+//!     </segments>
+//!   </file>
+//! </nzb>"#).unwrap();
+//!
+//! let mut job = DownloadJob::new("job-1", nzb, "primary", "/tmp/downloads");
+//! job.set_segment(0, 0, SegmentStatus::Completed);
+//! assert_eq!(job.progress().downloaded_bytes, 100);
+//! assert_eq!(job.pending_segments().count(), 1);
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::completeness::{CompletenessReport, RepairOutlook};
+use crate::error::{NntpError, Result};
+use crate::filename;
+use crate::hash::HashSnapshot;
+use crate::nzb::{Nzb, NzbFile, NzbSegment};
+use crate::output::OutputSink;
+use crate::par2::Par2File;
+use crate::segments::{FetchProgress, SegmentStatus};
+use crate::servers::ServerGroup;
+use tracing::warn;
+
+/// yEnc line length of posted parts
+const YENC_LINE_LENGTH: usize = 128;
+
+/// Length of the ".\r\n" terminator sent after an article
+const TERMINATOR_LEN: u64 = 3;
+
+/// Lifecycle of a job in a queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JobStatus {
+    /// Waiting to be started
+    #[default]
+    Queued,
+    /// Segments are being transferred
+    Running,
+    /// Stopped by the user; resumable
+    Paused,
+    /// Every segment reached a final status
+    Completed,
+    /// Stopped because of an error (see the job's `error`)
+    Failed,
+}
+
+/// Reset interrupted segments so a resumed job transfers them again
+fn reset_in_progress<'a>(statuses: impl Iterator<Item = &'a mut SegmentStatus>) {
+    for status in statuses {
+        if *status == SegmentStatus::InProgress {
+            *status = SegmentStatus::Pending;
+        }
+    }
+}
+
+/// Download of the files described by an NZB
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DownloadJob {
+    /// Caller-chosen identifier (unique within a queue)
+    pub id: String,
+    /// The NZB being downloaded
+    pub nzb: Nzb,
+    /// Name of the server group the segments are fetched from
+    pub server_group: String,
+    /// Directory the decoded files are written to
+    pub output_dir: PathBuf,
+    /// Where the job is in its lifecycle
+    pub status: JobStatus,
+    /// Reason the job failed, if it did
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub error: Option<String>,
+    /// Completeness found by the availability sweep before downloading,
+    /// filled in by [`run_precheck`](Self::run_precheck)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub precheck: Option<CompletenessReport>,
+    /// Status of every segment, indexed by file then segment
+    segments: Vec<Vec<SegmentStatus>>,
+    /// Hash state of the bytes written so far, indexed by file
+    #[cfg_attr(feature = "serde", serde(default))]
+    hashes: Vec<Option<HashSnapshot>>,
+}
+
+impl DownloadJob {
+    /// Create a queued job with every segment pending
+    pub fn new(
+        id: impl Into<String>,
+        nzb: Nzb,
+        server_group: impl Into<String>,
+        output_dir: impl Into<PathBuf>,
+    ) -> Self {
+        let segments = nzb
+            .files
+            .iter()
+            .map(|file| vec![SegmentStatus::Pending; file.segments.len()])
+            .collect();
+        let nzb_files = nzb.files.len();
+        Self {
+            id: id.into(),
+            nzb,
+            server_group: server_group.into(),
+            output_dir: output_dir.into(),
+            status: JobStatus::Queued,
+            error: None,
+            precheck: None,
+            hashes: vec![None; nzb_files],
+            segments,
+        }
+    }
+
+    /// Directory this job's files are written to until they are verified
+    ///
+    /// `.incomplete/<id>` inside the output directory, so finalizing a file
+    /// is a rename on the same filesystem.
+    pub fn incomplete_dir(&self) -> PathBuf {
+        self.output_dir
+            .join(".incomplete")
+            .join(filename::sanitize(&self.id))
+    }
+
+    /// Sink writing into this job's output directory
+    ///
+    /// Derived from the application's `base` sink, whose transform it keeps,
+    /// so a transform configured once (with [`OutputSink::with_transform`])
+    /// applies to every job. Files are staged in
+    /// [`incomplete_dir`](Self::incomplete_dir); move each into the output
+    /// directory with [`OutputSink::finalize`] once it is verified, and call
+    /// [`OutputSink::discard_staged`] when the job is done.
+    pub fn output_sink(&self, base: &OutputSink) -> OutputSink {
+        base.in_dir(&self.output_dir)
+            .with_staging(self.incomplete_dir())
+    }
+
+    /// Get the status of a segment by file and segment index
+    pub fn segment_status(&self, file: usize, segment: usize) -> Option<&SegmentStatus> {
+        self.segments.get(file)?.get(segment)
+    }
+
+    /// Record the status of a segment
+    ///
+    /// Returns `false` if there is no such segment.
+    pub fn set_segment(&mut self, file: usize, segment: usize, status: SegmentStatus) -> bool {
+        match self.segments.get_mut(file).and_then(|f| f.get_mut(segment)) {
+            Some(slot) => {
+                *slot = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Saved hash state of a file, if any
+    ///
+    /// Resume a [`StreamingHasher`](crate::hash::StreamingHasher) from it to
+    /// continue hashing where the file's written bytes end, instead of reading
+    /// them back from disk.
+    pub fn hash_state(&self, file: usize) -> Option<&HashSnapshot> {
+        self.hashes.get(file)?.as_ref()
+    }
+
+    /// Record the hash state of a file
+    ///
+    /// The snapshot must cover a contiguous prefix of the file; segments
+    /// written ahead of a gap are hashed once the gap is filled. Save it
+    /// together with the segment statuses, so both describe the same bytes.
+    /// Returns `false` if there is no such file.
+    pub fn set_hash_state(&mut self, file: usize, snapshot: HashSnapshot) -> bool {
+        if file >= self.nzb.files.len() {
+            return false;
+        }
+        // Journals written before hash states were kept have none
+        if self.hashes.len() < self.nzb.files.len() {
+            self.hashes.resize(self.nzb.files.len(), None);
+        }
+        self.hashes[file] = Some(snapshot);
+        true
+    }
+
+    /// Segments still to fetch, as `(file index, segment index, segment)`
+    pub fn pending_segments(&self) -> impl Iterator<Item = (usize, usize, &NzbSegment)> {
+        self.nzb
+            .files
+            .iter()
+            .enumerate()
+            .flat_map(move |(f, file)| {
+                file.segments
+                    .iter()
+                    .enumerate()
+                    .filter(move |(s, _)| {
+                        self.segment_status(f, *s) == Some(&SegmentStatus::Pending)
+                    })
+                    .map(move |(s, segment)| (f, s, segment))
+            })
+    }
+
+    /// Summarize progress, counting NZB-declared bytes of completed segments
+    pub fn progress(&self) -> FetchProgress {
+        let segments = self.nzb.files.iter().flat_map(|file| &file.segments);
+        let statuses = self.segments.iter().flatten();
+        progress_of(segments.map(|s| s.bytes).zip(statuses))
+    }
+
+    /// Sweep the servers for every segment and keep the result in `precheck`
+    ///
+    /// Runs [`ServerGroup::availability_sweep`] and stores the
+    /// [`CompletenessReport`] built from it, replacing any earlier one.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the sweep; `precheck` is left unchanged.
+    pub async fn run_precheck(
+        &mut self,
+        servers: &ServerGroup,
+        par2: Option<&Par2File>,
+    ) -> Result<&CompletenessReport> {
+        let sweep = servers.availability_sweep(&self.nzb).await?;
+        let report = CompletenessReport::from_sweep(&self.nzb, &sweep, par2);
+        Ok(self.precheck.insert(report))
+    }
+
+    /// Completeness of the downloaded data
+    ///
+    /// Built like the [`precheck`](Self::precheck) report, with the server
+    /// group as the only server and every segment that is not
+    /// [`Completed`](SegmentStatus::Completed) counted as missing. Compare the
+    /// two to see what the download lost against the sweep.
+    pub fn completeness(&self, par2: Option<&Par2File>) -> CompletenessReport {
+        let servers = [self.server_group.clone()];
+        CompletenessReport::build(&self.nzb, &servers, par2, |_, file, segment| {
+            self.segment_status(file, segment) == Some(&SegmentStatus::Completed)
+        })
+    }
+
+    /// Bounds on repairing the segments lost so far with PAR2
+    ///
+    /// Segments count as lost once [`NotFound`](SegmentStatus::NotFound) or
+    /// [`Failed`](SegmentStatus::Failed); mark segments whose data turned out
+    /// damaged as failed. Returns `None` if `par2` has no slice size or the
+    /// block count of a recovery volume still to come is unknown.
+    pub fn repair_outlook(&self, par2: &Par2File) -> Option<RepairOutlook> {
+        RepairOutlook::build(&self.nzb, par2, |file, segment| {
+            matches!(
+                self.segment_status(file, segment),
+                Some(SegmentStatus::NotFound | SegmentStatus::Failed)
+            )
+        })
+    }
+
+    /// Fail the job once PAR2 can no longer repair it
+    ///
+    /// Call after each lost segment. If the lost segments damage more slices
+    /// than the recovery volumes still to come can hold, the job is marked
+    /// [`Failed`](JobStatus::Failed) with the reason in `error`, and the
+    /// outlook is returned so the caller can stop fetching and report it.
+    pub fn abort_if_doomed(&mut self, par2: &Par2File) -> Option<RepairOutlook> {
+        let outlook = self.repair_outlook(par2).filter(RepairOutlook::is_doomed)?;
+        warn!("Aborting download {}: {}", self.id, outlook);
+        self.status = JobStatus::Failed;
+        self.error = Some(format!("Cannot be repaired: {}", outlook));
+        Some(outlook)
+    }
+
+    /// Prepare a job loaded from a journal to run again
+    ///
+    /// Segments that were in flight go back to pending, and a running job
+    /// goes back to the queue.
+    pub fn prepare_resume(&mut self) {
+        reset_in_progress(self.segments.iter_mut().flatten());
+        if self.status == JobStatus::Running {
+            self.status = JobStatus::Queued;
+        }
+    }
+}
+
+/// One part of a file being uploaded
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UploadPart {
+    /// Part number (1-based)
+    pub number: u32,
+    /// Byte offset of the part in the file
+    pub offset: u64,
+    /// Length of the part in bytes
+    pub length: u64,
+    /// Message-ID the part is posted under (with angle brackets)
+    ///
+    /// Assigned up front so a resumed upload reposts under the same ID and
+    /// the server rejects duplicates instead of storing them twice.
+    pub message_id: String,
+    /// Posting status
+    pub status: SegmentStatus,
+}
+
+/// A file being uploaded, split into parts
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UploadFile {
+    /// Path of the file on disk
+    pub path: PathBuf,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Parts in file order
+    pub parts: Vec<UploadPart>,
+}
+
+/// Upload of one or more files as yEnc-encoded articles
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UploadJob {
+    /// Caller-chosen identifier (unique within a queue)
+    pub id: String,
+    /// Name of the server group the parts are posted through
+    pub server_group: String,
+    /// Newsgroups every part is posted to
+    pub newsgroups: Vec<String>,
+    /// From header of the posted articles
+    pub poster: String,
+    /// Size of each part in bytes (the last part of a file may be shorter)
+    pub part_size: u64,
+    /// Files in upload order
+    pub files: Vec<UploadFile>,
+    /// Where the job is in its lifecycle
+    pub status: JobStatus,
+    /// Reason the job failed, if it did
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub error: Option<String>,
+    /// Largest article to post, in bytes; the smaller of this and the
+    /// server's limit applies
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_article_size: Option<u64>,
+    /// Split parts whose article exceeds the limit instead of failing
+    #[cfg_attr(feature = "serde", serde(default = "default_split_oversized"))]
+    pub split_oversized: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_split_oversized() -> bool {
+    true
+}
+
+impl UploadJob {
+    /// Create a queued job with no files
+    ///
+    /// # Panics
+    ///
+    /// Panics if `part_size` is 0.
+    pub fn new(
+        id: impl Into<String>,
+        server_group: impl Into<String>,
+        newsgroups: Vec<String>,
+        poster: impl Into<String>,
+        part_size: u64,
+    ) -> Self {
+        assert!(part_size > 0, "part size must be at least 1 byte");
+        Self {
+            id: id.into(),
+            server_group: server_group.into(),
+            newsgroups,
+            poster: poster.into(),
+            part_size,
+            files: Vec::new(),
+            status: JobStatus::Queued,
+            error: None,
+            max_article_size: None,
+            split_oversized: true,
+        }
+    }
+
+    /// Add a file of `size` bytes, split into pending parts
+    ///
+    /// Every part gets a fresh Message-ID in the poster's domain.
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, size: u64) {
+        // A deserialized job may carry a part size of 0
+        let part_size = self.part_size.max(1);
+        let parts = (0..size.div_ceil(part_size).max(1))
+            .map(|i| {
+                let offset = i * part_size;
+                self.new_part(i as u32 + 1, offset, part_size.min(size - offset))
+            })
+            .collect();
+        self.files.push(UploadFile {
+            path: path.into(),
+            size,
+            parts,
+        });
+    }
+
+    fn new_part(&self, number: u32, offset: u64, length: u64) -> UploadPart {
+        let domain = self
+            .poster
+            .rsplit_once('@')
+            .map(|(_, d)| d.trim_end_matches('>'))
+            .filter(|d| !d.is_empty())
+            .unwrap_or("localhost");
+        UploadPart {
+            number,
+            offset,
+            length,
+            message_id: format!("<{}@{}>", uuid::Uuid::new_v4(), domain),
+            status: SegmentStatus::Pending,
+        }
+    }
+
+    /// Build the article for a part, ready for [`post_raw`](crate::NntpClient::post_raw)
+    ///
+    /// The article is yEnc-encoded, dot-stuffed and has CRLF line endings.
+    /// If it would exceed the size limit (the smaller of
+    /// [`max_article_size`](Self::max_article_size) and `server_max`, such as
+    /// [`ServerConfig::max_article_size`](crate::ServerConfig::max_article_size))
+    /// and [`split_oversized`](Self::split_oversized) is set, the part is split
+    /// into smaller parts first. The parts of the file are then renumbered and
+    /// the subjects and yEnc headers reflect the new part count; the returned
+    /// article is for the first piece, at the same index.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Index of the file
+    /// * `part` - Index of the part within the file
+    /// * `data` - File contents starting at the part's offset (at least the part's length)
+    /// * `server_max` - Article size limit of the server, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - [`NntpError::PostingFailed`] - The article is too large and splitting is
+    ///   disabled, or other parts of the file are already posted or in flight
+    ///   (their numbering can no longer change)
+    /// - [`NntpError::Other`] - There is no such part, or `data` is too short
+    pub fn build_part(
+        &mut self,
+        file: usize,
+        part: usize,
+        data: &[u8],
+        server_max: Option<u64>,
+    ) -> Result<Vec<u8>> {
+        let length = match self.files.get(file).and_then(|f| f.parts.get(part)) {
+            Some(p) => p.length,
+            None => {
+                return Err(NntpError::Other(format!(
+                    "no part {} in file {}",
+                    part, file
+                )));
+            }
+        };
+        if (data.len() as u64) < length {
+            return Err(NntpError::Other(format!(
+                "part needs {} bytes of data, got {}",
+                length,
+                data.len()
+            )));
+        }
+
+        let article = self.part_article(file, part, data)?;
+        let limit = match (self.max_article_size, server_max) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (limit, None) | (None, limit) => limit,
+        };
+        match limit {
+            Some(limit) if article.len() as u64 + TERMINATOR_LEN > limit => {
+                self.split_part(file, part, data, limit, article.len() as u64)?;
+                self.part_article(file, part, data)
+            }
+            _ => Ok(article),
+        }
+    }
+
+    /// Replace an oversized part by enough smaller parts to fit `limit`
+    fn split_part(
+        &mut self,
+        file: usize,
+        part: usize,
+        data: &[u8],
+        limit: u64,
+        article_len: u64,
+    ) -> Result<()> {
+        let too_large = || {
+            NntpError::PostingFailed(format!(
+                "article of {} bytes exceeds the limit of {} bytes",
+                article_len, limit
+            ))
+        };
+        if !self.split_oversized {
+            return Err(too_large());
+        }
+        let parts = &self.files[file].parts;
+        if parts
+            .iter()
+            .enumerate()
+            .any(|(i, p)| i != part && p.status != SegmentStatus::Pending)
+        {
+            return Err(NntpError::PostingFailed(format!(
+                "article of {} bytes exceeds the limit of {} bytes, and parts of the file \
+                 are already posted",
+                article_len, limit
+            )));
+        }
+
+        let original = parts[part].clone();
+        let mut pieces = article_len.div_ceil(limit).max(2);
+        loop {
+            let piece_len = original.length.div_ceil(pieces);
+            if piece_len == 0 {
+                return Err(too_large());
+            }
+            self.replace_part(file, part, &original, piece_len);
+            let count = original.length.div_ceil(piece_len) as usize;
+            if self.pieces_fit(file, part..part + count, data, limit)? {
+                return Ok(());
+            }
+            // Headers alone do not fit
+            if piece_len == 1 {
+                self.replace_part(file, part, &original, original.length);
+                return Err(too_large());
+            }
+            pieces += 1;
+        }
+    }
+
+    /// Put pieces of `piece_len` bytes in place of the part at `part`, then renumber
+    fn replace_part(&mut self, file: usize, part: usize, original: &UploadPart, piece_len: u64) {
+        let count = original.length.div_ceil(piece_len).max(1);
+        let mut pieces: Vec<UploadPart> = (0..count)
+            .map(|i| {
+                let offset = original.offset + i * piece_len;
+                let length = piece_len.min(original.offset + original.length - offset);
+                self.new_part(0, offset, length)
+            })
+            .collect();
+        // The first piece keeps the Message-ID the part was announced under
+        pieces[0].message_id = original.message_id.clone();
+
+        let parts = &mut self.files[file].parts;
+        let end = parts[part..]
+            .iter()
+            .position(|p| p.offset >= original.offset + original.length)
+            .map_or(parts.len(), |n| part + n);
+        parts.splice(part..end, pieces);
+        for (i, p) in parts.iter_mut().enumerate() {
+            p.number = i as u32 + 1;
+        }
+    }
+
+    fn pieces_fit(
+        &self,
+        file: usize,
+        range: std::ops::Range<usize>,
+        data: &[u8],
+        limit: u64,
+    ) -> Result<bool> {
+        let base = self.files[file].parts[range.start].offset;
+        for index in range {
+            let skip = (self.files[file].parts[index].offset - base) as usize;
+            let article = self.part_article(file, index, &data[skip..])?;
+            if article.len() as u64 + TERMINATOR_LEN > limit {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Serialize the article for a part; `data` starts at the part's offset
+    fn part_article(&self, file: usize, part: usize, data: &[u8]) -> Result<Vec<u8>> {
+        let upload = &self.files[file];
+        let p = &upload.parts[part];
+        let name = file_name(&upload.path);
+        let total = upload.parts.len() as u32;
+        let body = crate::yenc::encode(
+            &data[..p.length as usize],
+            &name,
+            YENC_LINE_LENGTH,
+            Some((
+                p.number,
+                total,
+                p.offset + 1,
+                p.offset + p.length,
+                upload.size,
+            )),
+        )?;
+
+        let mut article = format!(
+            "From: {}\r\nNewsgroups: {}\r\nSubject: {} yEnc ({}/{})\r\nMessage-ID: {}\r\n\r\n",
+            self.poster,
+            self.newsgroups.join(","),
+            name,
+            p.number,
+            total,
+            p.message_id
+        )
+        .into_bytes();
+        article.reserve(body.len() + body.len() / 64);
+        for line in body.split_inclusive(|&b| b == b'\n') {
+            if line.starts_with(b".") {
+                article.push(b'.');
+            }
+            article.extend_from_slice(line);
+        }
+        Ok(article)
+    }
+
+    /// Record the status of a part by file and part index
+    ///
+    /// Returns `false` if there is no such part.
+    pub fn set_part(&mut self, file: usize, part: usize, status: SegmentStatus) -> bool {
+        match self.files.get_mut(file).and_then(|f| f.parts.get_mut(part)) {
+            Some(slot) => {
+                slot.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parts still to post, as `(file index, part index, part)`
+    pub fn pending_parts(&self) -> impl Iterator<Item = (usize, usize, &UploadPart)> {
+        self.files.iter().enumerate().flat_map(|(f, file)| {
+            file.parts
+                .iter()
+                .enumerate()
+                .filter(|(_, part)| part.status == SegmentStatus::Pending)
+                .map(move |(p, part)| (f, p, part))
+        })
+    }
+
+    /// Summarize progress, counting bytes of posted parts
+    pub fn progress(&self) -> FetchProgress {
+        let parts = self.files.iter().flat_map(|file| &file.parts);
+        progress_of(parts.map(|p| (p.length, &p.status)))
+    }
+
+    /// Prepare a job loaded from a journal to run again
+    ///
+    /// Parts that were in flight go back to pending (and are reposted under
+    /// their original Message-ID), and a running job goes back to the queue.
+    pub fn prepare_resume(&mut self) {
+        let parts = self.files.iter_mut().flat_map(|file| &mut file.parts);
+        reset_in_progress(parts.map(|part| &mut part.status));
+        if self.status == JobStatus::Running {
+            self.status = JobStatus::Queued;
+        }
+    }
+
+    /// Describe the posted parts as an NZB
+    ///
+    /// Only completed parts are listed; `date` is the Unix timestamp to
+    /// record for every file.
+    pub fn to_nzb(&self, date: i64) -> Nzb {
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let name = file_name(&file.path);
+                NzbFile {
+                    poster: self.poster.clone(),
+                    date,
+                    subject: format!("{} yEnc (1/{})", name, file.parts.len()),
+                    groups: self.newsgroups.clone(),
+                    segments: file
+                        .parts
+                        .iter()
+                        .filter(|part| part.status == SegmentStatus::Completed)
+                        .map(|part| NzbSegment {
+                            bytes: part.length,
+                            number: part.number,
+                            message_id: part.message_id.trim_matches(['<', '>']).to_string(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        Nzb {
+            meta: Default::default(),
+            files,
+        }
+    }
+}
+
+/// File name as posted (without directories)
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Build a progress summary from `(bytes, status)` of every segment
+fn progress_of<'a>(segments: impl Iterator<Item = (u64, &'a SegmentStatus)>) -> FetchProgress {
+    let mut progress = FetchProgress::new(0, 0);
+    for (bytes, status) in segments {
+        progress.total_segments += 1;
+        progress.total_bytes += bytes;
+        match status {
+            SegmentStatus::Completed => {
+                progress.completed_segments += 1;
+                progress.downloaded_bytes += bytes;
+            }
+            SegmentStatus::Failed => progress.failed_segments += 1,
+            SegmentStatus::NotFound => progress.not_found_segments += 1,
+            SegmentStatus::Pending | SegmentStatus::InProgress => {}
+        }
+    }
+    progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nzb() -> Nzb {
+        let segment = |number, bytes| NzbSegment {
+            bytes,
+            number,
+            message_id: format!("part{}@test", number),
+        };
+        Nzb {
+            meta: Default::default(),
+            files: vec![NzbFile {
+                poster: "poster@test".to_string(),
+                date: 0,
+                subject: "a.bin".to_string(),
+                groups: vec!["alt.test".to_string()],
+                segments: vec![segment(1, 100), segment(2, 100), segment(3, 50)],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_download_job_tracks_segments() {
+        let mut job = DownloadJob::new("1", nzb(), "primary", "/tmp");
+        assert_eq!(job.pending_segments().count(), 3);
+
+        assert!(job.set_segment(0, 0, SegmentStatus::Completed));
+        assert!(job.set_segment(0, 1, SegmentStatus::NotFound));
+        assert!(!job.set_segment(1, 0, SegmentStatus::Completed));
+
+        let pending: Vec<_> = job.pending_segments().map(|(_, s, _)| s).collect();
+        assert_eq!(pending, vec![2]);
+        let progress = job.progress();
+        assert_eq!(progress.total_bytes, 250);
+        assert_eq!(progress.downloaded_bytes, 100);
+        assert_eq!(progress.not_found_segments, 1);
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn test_download_job_stages_in_incomplete_dir() {
+        let job = DownloadJob::new("../job:1", nzb(), "primary", "/downloads");
+        let staging = Path::new("/downloads/.incomplete/..\u{f02f}job\u{f03a}1");
+        assert_eq!(job.incomplete_dir(), staging);
+
+        let sink = job.output_sink(&OutputSink::new("/elsewhere"));
+        assert_eq!(sink.staging_dir(), Some(staging));
+        assert_eq!(
+            sink.staged_path_for("a.bin").unwrap(),
+            staging.join("a.bin")
+        );
+        assert_eq!(
+            sink.path_for("a.bin").unwrap(),
+            Path::new("/downloads/a.bin")
+        );
+    }
+
+    #[test]
+    fn test_download_job_prepare_resume() {
+        let mut job = DownloadJob::new("1", nzb(), "primary", "/tmp");
+        job.status = JobStatus::Running;
+        job.set_segment(0, 0, SegmentStatus::Completed);
+        job.set_segment(0, 1, SegmentStatus::InProgress);
+
+        job.prepare_resume();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.segment_status(0, 1), Some(&SegmentStatus::Pending));
+        assert_eq!(job.segment_status(0, 0), Some(&SegmentStatus::Completed));
+    }
+
+    #[test]
+    fn test_download_job_aborts_when_doomed() {
+        use crate::par2::MainPacket;
+
+        let mut nzb = nzb();
+        nzb.files[0].subject = "\"a.bin\" yEnc (1/3)".to_string();
+        let mut volume = nzb.files[0].clone();
+        volume.subject = "\"a.vol0+1.par2\" yEnc (1/3)".to_string();
+        nzb.files.push(volume);
+        let mut par2 = Par2File::new();
+        par2.main = Some(MainPacket {
+            slice_size: 50,
+            file_count: 1,
+            file_ids: Vec::new(),
+            non_recoverable_file_ids: Vec::new(),
+        });
+
+        let mut job = DownloadJob::new("1", nzb, "primary", "/tmp");
+        job.status = JobStatus::Running;
+        job.set_segment(0, 2, SegmentStatus::NotFound);
+        assert_eq!(job.abort_if_doomed(&par2), None);
+
+        // The two lost runs damage three slices, the volume holds one
+        job.set_segment(0, 0, SegmentStatus::Failed);
+        let outlook = job.abort_if_doomed(&par2).unwrap();
+        assert_eq!((outlook.blocks_needed, outlook.blocks_available), (3, 1));
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(
+            job.error
+                .as_deref()
+                .unwrap()
+                .starts_with("Cannot be repaired")
+        );
+    }
+
+    #[test]
+    fn test_download_job_keeps_hash_state() {
+        use crate::hash::StreamingHasher;
+
+        let mut job = DownloadJob::new("1", nzb(), "primary", "/tmp");
+        assert!(job.hash_state(0).is_none());
+
+        let mut hasher = StreamingHasher::new();
+        hasher.update(&[7; 100]);
+        assert!(job.set_hash_state(0, hasher.snapshot()));
+        assert!(!job.set_hash_state(1, hasher.snapshot()));
+
+        job.prepare_resume();
+        let mut resumed = StreamingHasher::resume(job.hash_state(0).unwrap()).unwrap();
+        resumed.update(&[7; 150]);
+        assert_eq!(resumed.finalize().crc32, crc32fast::hash(&[7; 250]));
+    }
+
+    #[test]
+    fn test_upload_job_splits_files() {
+        let mut job = UploadJob::new("u", "primary", vec!["alt.test".into()], "me@host.test", 100);
+        job.add_file("dir/a.bin", 250);
+        job.add_file("empty.bin", 0);
+
+        let parts = &job.files[0].parts;
+        assert_eq!(parts.len(), 3);
+        assert_eq!((parts[2].offset, parts[2].length), (200, 50));
+        assert!(parts[0].message_id.ends_with("@host.test>"));
+        assert_ne!(parts[0].message_id, parts[1].message_id);
+        assert_eq!(job.files[1].parts.len(), 1);
+        assert_eq!(job.progress().total_bytes, 250);
+    }
+
+    #[test]
+    fn test_upload_job_resume_keeps_message_ids() {
+        let mut job = UploadJob::new("u", "primary", vec!["alt.test".into()], "me@test", 100);
+        job.add_file("a.bin", 200);
+        job.set_part(0, 0, SegmentStatus::Completed);
+        job.set_part(0, 1, SegmentStatus::InProgress);
+        let message_id = job.files[0].parts[1].message_id.clone();
+
+        job.prepare_resume();
+        let pending: Vec<_> = job.pending_parts().collect();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].2.message_id, message_id);
+
+        let nzb = job.to_nzb(1_700_000_000);
+        assert_eq!(nzb.files[0].segments.len(), 1);
+        assert_eq!(nzb.files[0].subject, "a.bin yEnc (1/2)");
+        assert!(!nzb.files[0].segments[0].message_id.contains('<'));
+    }
+
+    /// Decode the yEnc body of a built article
+    fn decode_article(article: &[u8]) -> crate::yenc::YencDecoded {
+        let split = article.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let mut body = Vec::new();
+        for line in article[split + 4..].split_inclusive(|&b| b == b'\n') {
+            body.extend_from_slice(line.strip_prefix(b"..").map_or(line, |_| &line[1..]));
+        }
+        crate::yenc::decode(&body).unwrap()
+    }
+
+    fn upload(data_len: u64, part_size: u64) -> UploadJob {
+        let mut job = UploadJob::new(
+            "u",
+            "primary",
+            vec!["alt.test".into()],
+            "me@test",
+            part_size,
+        );
+        job.add_file("dir/a.bin", data_len);
+        job
+    }
+
+    #[test]
+    fn test_build_part_within_limit() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 256) as u8).collect();
+        let mut job = upload(1000, 600);
+        let article = job.build_part(0, 1, &data[600..], Some(100_000)).unwrap();
+
+        assert!(article.starts_with(b"From: me@test\r\nNewsgroups: alt.test\r\n"));
+        assert!(article.windows(21).any(|w| w == b"Subject: a.bin yEnc ("));
+        let decoded = decode_article(&article);
+        assert_eq!(decoded.data, &data[600..]);
+        assert_eq!(job.files[0].parts.len(), 2);
+    }
+
+    #[test]
+    fn test_build_part_splits_oversized() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let mut job = upload(5000, 5000);
+        let message_id = job.files[0].parts[0].message_id.clone();
+
+        let first = job.build_part(0, 0, &data, Some(2000)).unwrap();
+        assert!(first.len() + 3 <= 2000);
+        let parts = job.files[0].parts.clone();
+        assert!(parts.len() >= 3);
+        assert_eq!(parts[0].message_id, message_id);
+        let numbers: Vec<u32> = parts.iter().map(|p| p.number).collect();
+        assert_eq!(numbers, (1..=parts.len() as u32).collect::<Vec<_>>());
+
+        let mut assembler = crate::yenc::YencMultipartAssembler::new();
+        for (index, part) in parts.iter().enumerate() {
+            let article = job
+                .build_part(0, index, &data[part.offset as usize..], Some(2000))
+                .unwrap();
+            assert!(article.len() + 3 <= 2000);
+            let subject = format!("a.bin yEnc ({}/{})", part.number, parts.len());
+            assert!(
+                article
+                    .windows(subject.len())
+                    .any(|w| w == subject.as_bytes())
+            );
+            assembler.add_part(decode_article(&article)).unwrap();
+        }
+        assert_eq!(assembler.assemble().unwrap(), data);
+        assert_eq!(job.files[0].parts, parts);
+    }
+
+    #[test]
+    fn test_build_part_job_limit_applies() {
+        let data = [b'x'; 4000];
+        let mut job = upload(4000, 4000);
+        job.max_article_size = Some(1500);
+        job.build_part(0, 0, &data, None).unwrap();
+        assert!(job.files[0].parts.len() >= 3);
+    }
+
+    #[test]
+    fn test_build_part_split_disabled() {
+        let data = [b'x'; 4000];
+        let mut job = upload(4000, 4000);
+        job.split_oversized = false;
+        let result = job.build_part(0, 0, &data, Some(1500));
+        assert!(matches!(result, Err(NntpError::PostingFailed(_))));
+        assert_eq!(job.files[0].parts.len(), 1);
+    }
+
+    #[test]
+    fn test_build_part_no_split_after_posting() {
+        let data = [b'x'; 4000];
+        let mut job = upload(4000, 2000);
+        job.set_part(0, 0, SegmentStatus::Completed);
+        let result = job.build_part(0, 1, &data[2000..], Some(1500));
+        assert!(matches!(result, Err(NntpError::PostingFailed(_))));
+        assert_eq!(job.files[0].parts.len(), 2);
+    }
+
+    #[test]
+    fn test_build_part_short_data() {
+        let mut job = upload(100, 100);
+        assert!(job.build_part(0, 0, &[0; 10], None).is_err());
+        assert!(job.build_part(1, 0, &[0; 100], None).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jobs_serde_roundtrip() {
+        let mut download = DownloadJob::new("1", nzb(), "primary", "/tmp");
+        download.set_segment(0, 2, SegmentStatus::Failed);
+        download.error = Some("segment missing".to_string());
+        let mut hasher = crate::hash::StreamingHasher::new();
+        hasher.update(b"partial");
+        download.set_hash_state(0, hasher.snapshot());
+        let json = serde_json::to_string(&download).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DownloadJob>(&json).unwrap(),
+            download
+        );
+
+        let mut upload = UploadJob::new("u", "primary", vec!["alt.test".into()], "me@test", 64);
+        upload.add_file("a.bin", 100);
+        upload.status = JobStatus::Paused;
+        let json = serde_json::to_string(&upload).unwrap();
+        assert_eq!(serde_json::from_str::<UploadJob>(&json).unwrap(), upload);
+    }
+}
@@ -0,0 +1,209 @@
+//! Response latency tracking and adaptive timeouts
+//!
+//! Fixed read timeouts have to be generous enough for the slowest server a
+//! client may meet, so a hung connection to a fast server goes unnoticed for
+//! a minute or more. [`NntpClient`](crate::NntpClient) measures how long each
+//! command waits for its status line and keeps the recent measurements in
+//! [`LatencyStats`]. With [`TimeoutConfig::adaptive`](crate::TimeoutConfig::adaptive)
+//! set, the status line timeout follows those measurements instead.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Samples kept when adaptive timeouts are not configured
+pub(crate) const DEFAULT_WINDOW: usize = 64;
+
+/// Derive status line timeouts from observed response latency
+///
+/// Once `min_samples` responses have been timed, the timeout is `multiplier`
+/// times the `percentile` latency of the last `window` responses, but never
+/// less than `floor` and never more than
+/// [`TimeoutConfig::read`](crate::TimeoutConfig::read). Until then the fixed
+/// timeout applies.
+///
+/// Only the wait for the status line adapts. Multi-line data that follows is
+/// still bounded by [`TimeoutConfig::multiline_read`](crate::TimeoutConfig::multiline_read),
+/// as its duration depends on size and bandwidth rather than latency.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{AdaptiveTimeouts, ServerConfig, TimeoutConfig};
+/// use std::time::Duration;
+///
+/// let config = ServerConfig::tls("news.example.com", "user", "pass").with_timeouts(TimeoutConfig {
+///     adaptive: Some(AdaptiveTimeouts {
+///         floor: Duration::from_secs(5),
+///         ..AdaptiveTimeouts::default()
+///     }),
+///     ..TimeoutConfig::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AdaptiveTimeouts {
+    /// Latency percentile the timeout is based on, 1-100 (default: 95)
+    pub percentile: u8,
+    /// Factor applied to the percentile latency (default: 10)
+    pub multiplier: u32,
+    /// Lower bound for the derived timeout (default: 2s)
+    pub floor: Duration,
+    /// Responses timed before the timeout adapts (default: 8)
+    pub min_samples: usize,
+    /// Number of recent responses considered (default: 64)
+    pub window: usize,
+}
+
+impl Default for AdaptiveTimeouts {
+    fn default() -> Self {
+        Self {
+            percentile: 95,
+            multiplier: 10,
+            floor: Duration::from_secs(2),
+            min_samples: 8,
+            window: DEFAULT_WINDOW,
+        }
+    }
+}
+
+impl AdaptiveTimeouts {
+    /// Timeout for the next status line, given the latency seen so far
+    ///
+    /// `ceiling` is returned until enough samples exist, and bounds the
+    /// result afterwards.
+    pub fn timeout(&self, stats: &LatencyStats, ceiling: Duration) -> Duration {
+        if stats.len() < self.min_samples.max(1) {
+            return ceiling;
+        }
+        let Some(latency) = stats.percentile(self.percentile) else {
+            return ceiling;
+        };
+        latency
+            .saturating_mul(self.multiplier)
+            .max(self.floor)
+            .min(ceiling)
+    }
+}
+
+/// Recent response latencies of one connection
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::LatencyStats;
+/// use std::time::Duration;
+///
+/// let mut stats = LatencyStats::new(3);
+/// for ms in [40, 10, 20, 30] {
+///     stats.record(Duration::from_millis(ms));
+/// }
+/// assert_eq!(stats.len(), 3);
+/// assert_eq!(stats.percentile(50), Some(Duration::from_millis(20)));
+/// assert_eq!(stats.percentile(100), Some(Duration::from_millis(30)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyStats {
+    samples: VecDeque<Duration>,
+    window: usize,
+}
+
+impl LatencyStats {
+    /// Create an empty tracker keeping the last `window` samples
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// Add a sample, dropping the oldest one if the window is full
+    pub fn record(&mut self, latency: Duration) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    /// Latency at percentile `p` (nearest rank), or `None` without samples
+    ///
+    /// `p` is clamped to 1-100.
+    pub fn percentile(&self, p: u8) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let p = usize::from(p.clamp(1, 100));
+        let rank = (p * sorted.len()).div_ceil(100);
+        sorted.get(rank.saturating_sub(1)).copied()
+    }
+
+    /// Most recent sample
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().copied()
+    }
+
+    /// Number of samples held
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Check if no response has been timed yet
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(samples_ms: &[u64]) -> LatencyStats {
+        let mut stats = LatencyStats::new(DEFAULT_WINDOW);
+        for &ms in samples_ms {
+            stats.record(Duration::from_millis(ms));
+        }
+        stats
+    }
+
+    #[test]
+    fn test_percentile() {
+        let stats = stats(&[50, 10, 40, 20, 30, 60, 70, 80, 90, 100]);
+        assert_eq!(stats.percentile(1), Some(Duration::from_millis(10)));
+        assert_eq!(stats.percentile(50), Some(Duration::from_millis(50)));
+        assert_eq!(stats.percentile(95), Some(Duration::from_millis(100)));
+        assert_eq!(stats.percentile(0), Some(Duration::from_millis(10)));
+        assert_eq!(stats.percentile(255), Some(Duration::from_millis(100)));
+        assert_eq!(stats.last(), Some(Duration::from_millis(100)));
+        assert_eq!(LatencyStats::new(0).percentile(50), None);
+    }
+
+    #[test]
+    fn test_adaptive_timeout_bounds() {
+        let ceiling = Duration::from_secs(60);
+        let adaptive = AdaptiveTimeouts {
+            min_samples: 3,
+            ..AdaptiveTimeouts::default()
+        };
+
+        // Too few samples: the fixed timeout applies
+        assert_eq!(adaptive.timeout(&stats(&[50, 50]), ceiling), ceiling);
+        // Fast server: 10 × 50ms is raised to the floor
+        assert_eq!(
+            adaptive.timeout(&stats(&[50, 50, 50]), ceiling),
+            Duration::from_secs(2)
+        );
+        // Moderate server: 10 × 800ms
+        assert_eq!(
+            adaptive.timeout(&stats(&[800, 700, 800]), ceiling),
+            Duration::from_secs(8)
+        );
+        // Slow server: capped at the fixed timeout
+        assert_eq!(
+            adaptive.timeout(&stats(&[9000, 9000, 9000]), ceiling),
+            ceiling
+        );
+    }
+}
@@ -8,58 +8,174 @@
 pub mod article;
 /// Article assembler for binary downloads
 pub mod assembler;
+/// Automatic decoding of fetched bodies
+pub mod autodecode;
+/// Backfill of historical overview data
+pub mod backfill;
+/// Blocking client for synchronous code
+pub mod blocking;
 /// Header caching for NNTP client
 pub mod cache;
+/// Cancellation of long-running operations
+pub mod cancel;
+/// Cancel-Lock and Cancel-Key headers (RFC 8315)
+pub mod cancel_lock;
 mod capabilities;
 mod client;
 /// NNTP command builders and response parsers
 pub mod commands;
+/// NZB completeness reports
+pub mod completeness;
+/// Header-only compression detection (XFEATURE COMPRESS GZIP)
+pub mod compression;
 mod config;
+/// External credential providers
+pub mod credentials;
 /// RFC 2047 Encoded Words support for international headers
 pub mod encoded_words;
 mod error;
+/// C ABI for decoding and verification primitives
+#[cfg(feature = "ffi")]
+pub mod ffi;
+/// Portable file names for downloaded files
+pub mod filename;
+/// format=flowed text (RFC 3676)
+pub mod flowed;
+/// Streaming file hashes with resumable state
+pub mod hash;
+/// Aggregated status snapshots for daemons
+pub mod health;
+mod host;
+/// Retry queue for deferred IHAVE offers
+pub mod ihave_queue;
+/// Resumable download and upload jobs
+pub mod jobs;
+/// Response latency tracking and adaptive timeouts
+pub mod latency;
 /// NZB file format parser
 pub mod nzb;
+/// Per-connection instrumentation hooks
+pub mod observer;
+/// Writing decoded files to disk
+pub mod output;
 /// PAR2 file format parser for error correction
 pub mod par2;
+/// Reassembly of MIME binaries split across articles
+pub mod partial;
+/// Verification of PGP-signed control messages
+#[cfg(feature = "pgpverify")]
+pub mod pgpverify;
 mod pool;
+#[cfg(feature = "python")]
+mod python;
+/// Per-provider workarounds for non-conforming servers
+pub mod quirks;
+/// Signature detection and reply quoting for article bodies
+pub mod quoting;
+/// Article number range sets (newsrc notation)
+pub mod rangeset;
 /// Rate limiting for bandwidth and connection management
 pub mod ratelimit;
+/// Detection of renumbered newsgroups
+pub mod renumber;
+/// End-to-end integrity reports for downloads
+pub mod report;
+/// Pluggable hostname resolution
+pub mod resolver;
 mod response;
+/// Crate-level resource ownership and orderly shutdown
+pub mod runtime;
 /// SASL authentication framework (RFC 4643)
 pub mod sasl;
 /// Segment fetcher for Usenet binary downloads
 pub mod segments;
 /// Multi-server support with automatic failover
 pub mod servers;
+/// Pluggable storage for caches and journals
+pub mod storage;
+/// Subscribed newsgroups and their settings
+pub mod subscriptions;
+/// Reply threading of overview entries and articles
+pub mod threading;
+/// news:, nntp: and nntps: URLs (RFC 5538)
+pub mod url;
+/// uuencode decoding
+pub mod uuencode;
 /// RFC 5536 Article validation utilities
 pub mod validation;
+/// Structured reports of data skipped or accepted despite defects
+pub mod warnings;
+/// Wildmat pattern matching (RFC 3977 Section 4)
+pub mod wildmat;
 /// yEnc binary encoding/decoding for Usenet
 pub mod yenc;
 
-pub use article::{Article, ArticleBuilder, ControlMessage, Headers, parse_article, parse_headers};
+pub use article::{
+    Article, ArticleBuilder, ControlMessage, HeaderMap, HeaderParser, Headers, InjectionInfo,
+    Mailbox, SanitizeReport, parse_article, parse_headers,
+};
 pub use assembler::{ArticleAssembler, PartInfo, PartStatus};
-pub use cache::{HeaderCache, LruHeaderCache};
+pub use autodecode::{DecodedBody, decode_body_auto};
+pub use backfill::{BackfillChunk, BackfillCoordinator, BackfillOrder, BackfillState};
+pub use cache::{ArticleCache, CachedBody, HeaderCache, LruHeaderCache, VerificationStatus};
+pub use cancel::CancellationToken;
+pub use cancel_lock::CancelSecret;
 pub use capabilities::Capabilities;
-pub use client::NntpClient;
-pub use commands::{ArticleInfo, DistributionInfo, GroupInfo, HdrEntry, ModeratorInfo, XoverEntry};
-pub use config::ServerConfig;
+pub use client::{
+    BodyPrefix, BodyStream, DEFAULT_FEED_WINDOW, DEFAULT_PIPELINE_DEPTH, FeedArticle, FeedReport,
+    ListActiveStream, ListgroupStream, NntpClient, Pipeline, ResponseKind, StreamFeeder,
+};
+pub use commands::{
+    ArticleInfo, CheckResult, CheckStatus, DistribPat, DistributionInfo, GroupInfo, GroupSnapshot,
+    HdrEntry, ModeratorInfo, XoverEntry, XoverEntryRef,
+};
+pub use completeness::{
+    AvailabilitySweep, CompletenessReport, FileCompleteness, RepairOutlook, ServerSegments,
+};
+pub use compression::GzipDetection;
+pub use config::{ReconnectPolicy, ServerConfig, SocketOptions, TimeoutConfig, TlsPolicy};
+pub use credentials::{Credentials, CredentialsProvider};
 pub use error::{NntpError, Result};
+pub use health::{
+    BandwidthLimiterHealth, ConnectionLimiterHealth, HealthSnapshot, PoolHealth, QueueHealth,
+    ServerHealth,
+};
+pub use ihave_queue::{DeferredOffer, IhaveQueue, OfferOutcome};
+pub use jobs::{DownloadJob, JobStatus, UploadFile, UploadJob, UploadPart};
+pub use latency::{AdaptiveTimeouts, LatencyStats};
 pub use nzb::{Nzb, NzbFile, NzbSegment, parse_nzb};
+pub use observer::ConnectionObserver;
+pub use output::{OutputSink, OutputTransform, TransformWriter};
 pub use par2::{
     CreatorPacket, FileDescriptionPacket, FileStatus, FileVerification, IfscPacket, MainPacket,
     PacketHeader, PacketType, Par2File, Par2Set, RecoverySlicePacket,
 };
+pub use partial::PartialAssembler;
 pub use pool::{NntpPool, RetryConfig};
-pub use ratelimit::{BandwidthLimiter, ConnectionLimiter, ConnectionPermit};
-pub use response::{NntpBinaryResponse, NntpResponse, codes};
+pub use quirks::ServerQuirks;
+pub use rangeset::RangeSet;
+pub use ratelimit::{BandwidthLimiter, BandwidthShare, ConnectionLimiter, ConnectionPermit};
+pub use renumber::{GroupRenumbering, GroupWatermarks, RenumberReason};
+pub use report::{IntegrityRecorder, IntegrityReport};
+pub use resolver::{CachingResolver, Resolver, StaticResolver, SystemResolver};
+pub use response::{
+    NntpBinaryResponse, NntpResponse, ParseMode, ResponseCategory, ResponseCode, codes,
+};
+pub use runtime::{NntpRuntime, ShutdownReport, ShutdownSignal};
 pub use sasl::{SaslMechanism, SaslPlain, decode_sasl_data, encode_sasl_data};
-pub use segments::{FetchConfig, FetchProgress, SegmentFetchResult, SegmentFetcher, SegmentStatus};
+pub use segments::{
+    FetchConfig, FetchProgress, RetrievalMode, SegmentFetchResult, SegmentFetcher, SegmentStatus,
+};
 pub use servers::{FailoverStrategy, GroupStats, ServerGroup, ServerStats};
+pub use storage::{FileStorage, MemoryStorage, StateStore, Storage, StorageFuture};
+pub use subscriptions::{DownloadPolicy, Subscription, SubscriptionOptions, Subscriptions};
+pub use threading::{Thread, Threadable, build_threads};
+pub use url::NewsTarget;
 pub use validation::{
     ValidationConfig, parse_date, validate_date, validate_message_id, validate_newsgroup_name,
 };
+pub use warnings::ParseWarning;
 pub use yenc::{
-    YencDecoded, YencEnd, YencHeader, YencMultipartAssembler, YencPart, decode as yenc_decode,
-    encode as yenc_encode,
+    YencDecoded, YencEnd, YencHeader, YencMultipartAssembler, YencMultipartEncoder, YencPart,
+    decode as yenc_decode, encode as yenc_encode,
 };
@@ -13,6 +13,7 @@ use std::io::Cursor;
 
 /// NZB file containing metadata and file references
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Nzb {
     /// Metadata from `<head>` section (e.g., title, password, tag, category)
     pub meta: HashMap<String, String>,
@@ -22,6 +23,7 @@ pub struct Nzb {
 
 /// A single file entry in an NZB
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NzbFile {
     /// Poster name/email
     pub poster: String,
@@ -37,6 +39,7 @@ pub struct NzbFile {
 
 /// A segment (part) of a file
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NzbSegment {
     /// Size of this segment in bytes
     pub bytes: u64,
@@ -0,0 +1,144 @@
+//! Per-connection instrumentation hooks
+//!
+//! A [`ConnectionObserver`] set on [`ServerConfig::observer`](crate::ServerConfig::observer)
+//! or with [`NntpClient::set_observer`](crate::NntpClient::set_observer) is
+//! told about every command sent, every status line received and every
+//! chunk of bytes moved over the socket. Use it to feed metrics or debug
+//! logs without parsing tracing output.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::renumber::GroupRenumbering;
+use crate::warnings::ParseWarning;
+
+/// Callbacks for the traffic of a connection
+///
+/// All methods have empty defaults, so an implementation only overrides what
+/// it needs. Callbacks run inline on the I/O path and must not block. One
+/// observer may be shared by all connections of a pool.
+///
+/// Byte counts are measured on the TCP socket: they include TLS overhead and
+/// are compressed bytes when compression is enabled, so they match the
+/// bandwidth actually used. Commands carrying credentials (AUTHINFO PASS,
+/// SASL data) are passed with the secret replaced by `****`.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ConnectionObserver, ServerConfig};
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// #[derive(Debug, Default)]
+/// struct Metrics {
+///     bytes_in: AtomicU64,
+///     errors: AtomicU64,
+/// }
+///
+/// impl ConnectionObserver for Metrics {
+///     fn on_response(&self, code: u16, _message: &str) {
+///         if code >= 400 {
+///             self.errors.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+///
+///     fn on_bytes_in(&self, bytes: usize) {
+///         self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let metrics = Arc::new(Metrics::default());
+/// let config = ServerConfig::tls("news.example.com", "user", "pass").with_observer(metrics);
+/// ```
+pub trait ConnectionObserver: Send + Sync + fmt::Debug {
+    /// A command line was sent (without the trailing CRLF)
+    fn on_command(&self, command: &str) {
+        let _ = command;
+    }
+
+    /// A response status line was received
+    fn on_response(&self, code: u16, message: &str) {
+        let _ = (code, message);
+    }
+
+    /// Bytes were read from the socket
+    fn on_bytes_in(&self, bytes: usize) {
+        let _ = bytes;
+    }
+
+    /// Bytes were written to the socket
+    fn on_bytes_out(&self, bytes: usize) {
+        let _ = bytes;
+    }
+
+    /// A GROUP response showed that a group was renumbered
+    ///
+    /// Cached data for [`GroupRenumbering::stale_range`] should be dropped,
+    /// e.g. with [`GroupRenumbering::invalidate_cache`].
+    fn on_group_renumbered(&self, renumbering: &GroupRenumbering) {
+        let _ = renumbering;
+    }
+
+    /// Part of a response was skipped or accepted despite a defect
+    fn on_parse_warning(&self, warning: &ParseWarning) {
+        let _ = warning;
+    }
+}
+
+/// Mask credentials in a command before handing it to an observer
+///
+/// `authenticating` is set while an AUTHINFO exchange is in progress, when a
+/// bare line is a SASL response.
+pub(crate) fn redact_command(command: &str, authenticating: bool) -> Cow<'_, str> {
+    let command = command.trim_end();
+    let mut words = command.split_ascii_whitespace();
+    let verb = words.next().unwrap_or_default();
+    if !verb.eq_ignore_ascii_case("AUTHINFO") {
+        return if authenticating {
+            Cow::Borrowed("****")
+        } else {
+            Cow::Borrowed(command)
+        };
+    }
+
+    match words.next() {
+        Some(kind) if kind.eq_ignore_ascii_case("PASS") => Cow::Borrowed("AUTHINFO PASS ****"),
+        Some(kind) if kind.eq_ignore_ascii_case("SASL") => match (words.next(), words.next()) {
+            (Some(mechanism), Some(_)) => Cow::Owned(format!("AUTHINFO SASL {} ****", mechanism)),
+            _ => Cow::Borrowed(command),
+        },
+        _ => Cow::Borrowed(command),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_command() {
+        assert_eq!(
+            redact_command("GROUP alt.test\r\n", false),
+            "GROUP alt.test"
+        );
+        assert_eq!(
+            redact_command("AUTHINFO USER joe\r\n", false),
+            "AUTHINFO USER joe"
+        );
+        assert_eq!(
+            redact_command("AUTHINFO PASS secret\r\n", true),
+            "AUTHINFO PASS ****"
+        );
+        assert_eq!(
+            redact_command("AUTHINFO SASL PLAIN AGpvZQBzZWNyZXQ=\r\n", false),
+            "AUTHINFO SASL PLAIN ****"
+        );
+        assert_eq!(
+            redact_command("AUTHINFO SASL PLAIN\r\n", false),
+            "AUTHINFO SASL PLAIN"
+        );
+        // SASL continuation lines are bare data
+        assert_eq!(redact_command("dGVzdA==\r\n", true), "****");
+    }
+}
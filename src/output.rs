@@ -0,0 +1,422 @@
+//! Writing decoded files to disk
+//!
+//! [`OutputSink`] is where assembled files land: a directory plus an optional
+//! [`OutputTransform`] that every byte passes through on its way to disk.
+//! With an encrypting transform (age, AES-GCM streams and the like) downloads
+//! are stored encrypted at rest without a plaintext copy ever being written.
+//!
+//! Configure one sink for the application and derive the others from it:
+//! [`DownloadJob::output_sink`](crate::DownloadJob::output_sink) gives the
+//! sink for a job's files, to hand to
+//! [`ArticleAssembler::write_to`](crate::ArticleAssembler::write_to), and
+//! [`FileStorage::with_output`](crate::storage::FileStorage::with_output)
+//! passes stored state through the same transform.
+//!
+//! With a staging directory ([`OutputSink::with_staging`]) files are written
+//! there first and only moved into the destination by
+//! [`OutputSink::finalize`], once they are verified. Media scanners and watch
+//! folders looking at the destination never see a partial file.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::output::{OutputSink, OutputTransform, TransformWriter};
+//! use std::io::{self, Read, Write};
+//! use std::sync::Arc;
+//!
+//! /// Toy transform: flips every bit
+//! struct Invert;
+//!
+//! struct InvertWriter(Box<dyn Write + Send>);
+//!
+//! impl Write for InvertWriter {
+//!     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+//!         let inverted: Vec<u8> = buf.iter().map(|b| !b).collect();
+//!         self.0.write_all(&inverted)?;
+//!         Ok(buf.len())
+//!     }
+//!     fn flush(&mut self) -> io::Result<()> {
+//!         self.0.flush()
+//!     }
+//! }
+//!
+//! impl TransformWriter for InvertWriter {
+//!     fn finish(mut self: Box<Self>) -> io::Result<()> {
+//!         self.0.flush()
+//!     }
+//! }
+//!
+//! impl OutputTransform for Invert {
+//!     fn wrap(&self, writer: Box<dyn Write + Send>) -> io::Result<Box<dyn TransformWriter>> {
+//!         Ok(Box::new(InvertWriter(writer)))
+//!     }
+//!
+//!     fn wrap_reader(&self, mut reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>> {
+//!         let mut data = Vec::new();
+//!         reader.read_to_end(&mut data)?;
+//!         let inverted: Vec<u8> = data.iter().map(|b| !b).collect();
+//!         Ok(Box::new(io::Cursor::new(inverted)))
+//!     }
+//! }
+//!
+//! let sink = OutputSink::new("/srv/downloads").with_transform(Arc::new(Invert));
+//! assert!(sink.has_transform());
+//! ```
+
+use std::fmt;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::filename;
+
+/// A writer that needs to be told when the data is complete
+///
+/// Stream ciphers and authenticated encryption write a final block or tag
+/// when the stream ends; `finish` is where that happens. Dropping a writer
+/// without finishing it may leave a truncated file.
+pub trait TransformWriter: Write + Send {
+    /// Write any trailing data and flush everything to the underlying file
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// A stream transformation applied to decoded data before it is written
+///
+/// Implementations wrap the file writer in their own writer, typically an
+/// encryptor. `wrap` is called once per file. Files the library reads back,
+/// such as [`FileStorage`](crate::storage::FileStorage) state, are opened
+/// through `wrap_reader`, which undoes the transform.
+pub trait OutputTransform: Send + Sync {
+    /// Wrap `writer` so everything written passes through the transform
+    fn wrap(&self, writer: Box<dyn Write + Send>) -> io::Result<Box<dyn TransformWriter>>;
+
+    /// Wrap `reader` of a file written through [`wrap`](Self::wrap) so it
+    /// yields the original data
+    fn wrap_reader(&self, reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>>;
+}
+
+/// Writer used when no transform is configured
+struct Passthrough(Box<dyn Write + Send>);
+
+impl Write for Passthrough {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl TransformWriter for Passthrough {
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Destination directory for decoded files, with an optional transform
+#[derive(Clone)]
+pub struct OutputSink {
+    dir: PathBuf,
+    staging: Option<PathBuf>,
+    transform: Option<Arc<dyn OutputTransform>>,
+}
+
+impl fmt::Debug for OutputSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutputSink")
+            .field("dir", &self.dir)
+            .field("staging", &self.staging)
+            .field("transform", &self.transform.is_some())
+            .finish()
+    }
+}
+
+impl OutputSink {
+    /// Write files to `dir` unchanged
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            staging: None,
+            transform: None,
+        }
+    }
+
+    /// Write files to `staging` until they are [finalized](Self::finalize)
+    ///
+    /// The staging directory is created when the first file is written. It
+    /// should be on the same filesystem as the destination, so finalizing is
+    /// an atomic rename.
+    #[must_use]
+    pub fn with_staging(mut self, staging: impl Into<PathBuf>) -> Self {
+        self.staging = Some(staging.into());
+        self
+    }
+
+    /// Pass every file through `transform` before it reaches the disk
+    #[must_use]
+    pub fn with_transform(mut self, transform: Arc<dyn OutputTransform>) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Get the destination directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get the staging directory, if files are staged
+    pub fn staging_dir(&self) -> Option<&Path> {
+        self.staging.as_deref()
+    }
+
+    /// Check if files are transformed on the way to disk
+    pub fn has_transform(&self) -> bool {
+        self.transform.is_some()
+    }
+
+    /// Same transform, writing to `dir` without staging
+    #[must_use]
+    pub fn in_dir(&self, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            staging: None,
+            transform: self.transform.clone(),
+        }
+    }
+
+    /// Pass `writer` through the transform, if any
+    ///
+    /// For files opened by the caller; [`create`](Self::create) does this
+    /// for files in the sink's directory.
+    pub fn wrap_writer(
+        &self,
+        writer: Box<dyn Write + Send>,
+    ) -> io::Result<Box<dyn TransformWriter>> {
+        match &self.transform {
+            Some(transform) => transform.wrap(writer),
+            None => Ok(Box::new(Passthrough(writer))),
+        }
+    }
+
+    /// Undo the transform, if any, on `reader` of a file written through it
+    pub fn wrap_reader(&self, reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>> {
+        match &self.transform {
+            Some(transform) => transform.wrap_reader(reader),
+            None => Ok(reader),
+        }
+    }
+
+    /// Path a file named `name` is written to
+    ///
+    /// See [`filename::confine`]: only the final component of `name` is used,
+    /// sanitized so it is valid on Windows, so names taken from subjects, yEnc
+    /// headers or PAR2 packets cannot point outside the directory.
+    pub fn path_for(&self, name: &str) -> Result<PathBuf> {
+        Ok(filename::confine(&self.dir, name)?)
+    }
+
+    /// Path the file `name` is written to before it is finalized
+    ///
+    /// Same as [`path_for`](Self::path_for) without a staging directory.
+    pub fn staged_path_for(&self, name: &str) -> Result<PathBuf> {
+        let dir = self.staging.as_ref().unwrap_or(&self.dir);
+        Ok(filename::confine(dir, name)?)
+    }
+
+    /// Create (or replace) the file `name` and return a writer for it
+    ///
+    /// The file goes into the staging directory if there is one. It is opened
+    /// with [`filename::create_confined`], which refuses symbolic links. Call
+    /// [`TransformWriter::finish`] once all data is written.
+    pub fn create(&self, name: &str) -> Result<Box<dyn TransformWriter>> {
+        if let Some(staging) = &self.staging {
+            std::fs::create_dir_all(staging)?;
+        }
+        let file = filename::create_confined(&self.staged_path_for(name)?)?;
+        Ok(self.wrap_writer(Box::new(BufWriter::new(file)))?)
+    }
+
+    /// Write `data` to the file `name`, returning its path
+    ///
+    /// With a staging directory the returned path is the staged one; call
+    /// [`finalize`](Self::finalize) once the file is verified.
+    pub fn write(&self, name: &str, data: &[u8]) -> Result<PathBuf> {
+        let path = self.staged_path_for(name)?;
+        let mut writer = self.create(name)?;
+        writer.write_all(data)?;
+        writer.finish()?;
+        Ok(path)
+    }
+
+    /// Move the staged file `name` into the destination directory
+    ///
+    /// The rename replaces any file of the same name in one step, so readers
+    /// see either the old file or the complete new one. Without a staging
+    /// directory the file is already in place and its path is returned.
+    ///
+    /// # Errors
+    ///
+    /// - Any I/O error from the rename, e.g. if `name` was never written or
+    ///   the staging directory is on another filesystem
+    pub fn finalize(&self, name: &str) -> Result<PathBuf> {
+        let target = self.path_for(name)?;
+        if self.staging.is_some() {
+            std::fs::create_dir_all(&self.dir)?;
+            std::fs::rename(self.staged_path_for(name)?, &target)?;
+        }
+        Ok(target)
+    }
+
+    /// Delete the staging directory and any files left in it
+    ///
+    /// For jobs that failed or were cancelled, and to clean up after the last
+    /// file was finalized. Does nothing without a staging directory.
+    pub fn discard_staged(&self) -> Result<()> {
+        let Some(staging) = &self.staging else {
+            return Ok(());
+        };
+        match std::fs::remove_dir_all(staging) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Xor(u8);
+
+    struct XorWriter {
+        key: u8,
+        inner: Box<dyn Write + Send>,
+    }
+
+    impl Write for XorWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mixed: Vec<u8> = buf.iter().map(|b| b ^ self.key).collect();
+            self.inner.write_all(&mixed)?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl TransformWriter for XorWriter {
+        fn finish(mut self: Box<Self>) -> io::Result<()> {
+            // Trailer, as an authentication tag would be
+            self.inner.write_all(b"END")?;
+            self.inner.flush()
+        }
+    }
+
+    impl OutputTransform for Xor {
+        fn wrap(&self, inner: Box<dyn Write + Send>) -> io::Result<Box<dyn TransformWriter>> {
+            Ok(Box::new(XorWriter { key: self.0, inner }))
+        }
+
+        fn wrap_reader(
+            &self,
+            mut reader: Box<dyn Read + Send>,
+        ) -> io::Result<Box<dyn Read + Send>> {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            let Some(sealed) = data.strip_suffix(b"END") else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing trailer",
+                ));
+            };
+            let plain: Vec<u8> = sealed.iter().map(|b| b ^ self.0).collect();
+            Ok(Box::new(io::Cursor::new(plain)))
+        }
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nntp-rs-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_passthrough_and_transformed() {
+        let dir = scratch_dir("output");
+
+        let plain = OutputSink::new(&dir);
+        let path = plain.write("plain.bin", b"abc").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"abc");
+
+        let sealed = plain.with_transform(Arc::new(Xor(0xff)));
+        let path = sealed.write("sealed.bin", b"abc").unwrap();
+        let mut expected: Vec<u8> = b"abc".iter().map(|b| b ^ 0xff).collect();
+        expected.extend_from_slice(b"END");
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+
+        let mut read = Vec::new();
+        let file = std::fs::File::open(&path).unwrap();
+        sealed
+            .wrap_reader(Box::new(file))
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, b"abc");
+
+        let moved = sealed.in_dir(dir.join("other"));
+        assert!(moved.has_transform() && moved.staging_dir().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_staged_files_appear_on_finalize() {
+        let dir = scratch_dir("staging");
+        let staging = dir.join(".incomplete").join("job");
+        let sink = OutputSink::new(&dir).with_staging(&staging);
+
+        let staged = sink.write("file.bin", b"data").unwrap();
+        assert_eq!(staged, staging.join("file.bin"));
+        assert!(!dir.join("file.bin").exists());
+
+        std::fs::write(dir.join("file.bin"), b"old").unwrap();
+        let path = sink.finalize("file.bin").unwrap();
+        assert_eq!(path, dir.join("file.bin"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"data");
+        assert!(!staged.exists());
+        assert!(sink.finalize("file.bin").is_err());
+
+        sink.write("partial.bin", b"da").unwrap();
+        sink.discard_staged().unwrap();
+        assert!(!staging.exists());
+        sink.discard_staged().unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_for_keeps_files_in_dir() {
+        let sink = OutputSink::new("/downloads");
+        assert_eq!(
+            sink.path_for("../../etc/passwd").unwrap(),
+            PathBuf::from("/downloads/passwd")
+        );
+        assert_eq!(
+            sink.path_for("file.bin").unwrap(),
+            PathBuf::from("/downloads/file.bin")
+        );
+        assert_eq!(
+            sink.path_for(r"..\..\Windows\CON.txt").unwrap(),
+            PathBuf::from("/downloads/CO\u{f04e}.txt")
+        );
+        assert_eq!(
+            sink.path_for("What? A file: part 1.bin").unwrap(),
+            PathBuf::from("/downloads/What\u{f03f} A file\u{f03a} part 1.bin")
+        );
+        assert!(sink.path_for("..").is_err());
+        assert!(sink.path_for("").is_err());
+    }
+}
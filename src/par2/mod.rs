@@ -6,6 +6,7 @@
 //! Reference: [Parity Volume Set Specification 2.0](https://parchive.sourceforge.net/docs/specifications/parity-volume-spec/article-spec.html)
 
 use crate::error::{NntpError, Result};
+use crate::warnings::ParseWarning;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -17,6 +18,9 @@ pub(super) mod verification;
 /// PAR2 packet magic bytes: "PAR2\0PKT"
 pub const PAR2_MAGIC: &[u8; 8] = b"PAR2\0PKT";
 
+/// Maximum number of input slices in a recovery set (PAR2 spec, GF(2^16) limit)
+pub const PAR2_MAX_SLICES: u64 = 32768;
+
 /// PAR2 packet header (64 bytes)
 #[derive(Debug, Clone)]
 pub struct PacketHeader {
@@ -155,6 +159,10 @@ pub enum PacketType {
     RecoverySlice,
     /// Creator packet
     Creator,
+    /// ASCII comment packet
+    AsciiComment,
+    /// Unicode comment packet
+    UnicodeComment,
     /// Unknown packet type
     Unknown([u8; 16]),
 }
@@ -168,6 +176,8 @@ impl PacketType {
             b"PAR 2.0\0IFSC\0\0\0\0" => PacketType::Ifsc,
             b"PAR 2.0\0RecvSlic" => PacketType::RecoverySlice,
             b"PAR 2.0\0Creator\0" => PacketType::Creator,
+            b"PAR 2.0\0CommASCI" => PacketType::AsciiComment,
+            b"PAR 2.0\0CommUni\0" => PacketType::UnicodeComment,
             _ => PacketType::Unknown(*bytes),
         }
     }
@@ -180,6 +190,8 @@ impl PacketType {
             PacketType::Ifsc => *b"PAR 2.0\0IFSC\0\0\0\0",
             PacketType::RecoverySlice => *b"PAR 2.0\0RecvSlic",
             PacketType::Creator => *b"PAR 2.0\0Creator\0",
+            PacketType::AsciiComment => *b"PAR 2.0\0CommASCI",
+            PacketType::UnicodeComment => *b"PAR 2.0\0CommUni\0",
             PacketType::Unknown(bytes) => *bytes,
         }
     }
@@ -200,6 +212,10 @@ pub struct Par2File {
     pub recovery_slices: Vec<RecoverySlicePacket>,
     /// Creator packet
     pub creator: Option<CreatorPacket>,
+    /// Text of the ASCII and Unicode comment packets, in file order
+    pub comments: Vec<String>,
+    /// Packets and bytes the parser skipped
+    pub warnings: Vec<ParseWarning>,
 }
 
 impl Par2File {
@@ -212,6 +228,8 @@ impl Par2File {
             ifsc_packets: HashMap::new(),
             recovery_slices: Vec::new(),
             creator: None,
+            comments: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
@@ -358,6 +376,18 @@ mod tests {
         assert_eq!(ptype.to_bytes(), *bytes);
     }
 
+    #[test]
+    fn test_packet_type_comments() {
+        for (bytes, expected) in [
+            (b"PAR 2.0\0CommASCI", PacketType::AsciiComment),
+            (b"PAR 2.0\0CommUni\0", PacketType::UnicodeComment),
+        ] {
+            let ptype = PacketType::from_bytes(bytes);
+            assert_eq!(ptype, expected);
+            assert_eq!(ptype.to_bytes(), *bytes);
+        }
+    }
+
     #[test]
     fn test_packet_type_unknown() {
         let bytes = b"Unknown\0Type\0\0\0\0";
@@ -2,12 +2,13 @@
 //!
 //! This module contains all PAR2 packet parsing logic, including:
 //! - Packet header parsing
-//! - Individual packet type parsers (Main, FileDescription, IFSC, RecoverySlice, Creator)
+//! - Individual packet type parsers (Main, FileDescription, IFSC, RecoverySlice, Creator, comments)
 //! - Par2File::parse() method
 //! - Par2Set::discover() method
 
 use super::*;
 use crate::error::{NntpError, Result};
+use crate::filename;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -29,10 +30,16 @@ const IFSC_PACKET_MIN_SIZE: usize = 16;
 /// Minimum size of Recovery Slice packet body in bytes
 const RECOVERY_SLICE_PACKET_MIN_SIZE: usize = 4;
 
+/// Convert a length field to `usize`, failing instead of truncating
+fn to_usize(value: u64, context: &'static str) -> Result<usize> {
+    usize::try_from(value).map_err(|_| NntpError::LengthOutOfRange { context, value })
+}
+
 /// Read a u32 from little-endian bytes at given offset
 fn read_u32_le(data: &[u8], offset: usize) -> Result<u32> {
-    let bytes: [u8; 4] = data
-        .get(offset..offset + 4)
+    let bytes: [u8; 4] = offset
+        .checked_add(4)
+        .and_then(|end| data.get(offset..end))
         .ok_or_else(|| {
             NntpError::InvalidResponse(format!(
                 "PAR2 packet truncated: cannot read u32 at offset {}",
@@ -51,8 +58,9 @@ fn read_u32_le(data: &[u8], offset: usize) -> Result<u32> {
 
 /// Read a u64 from little-endian bytes at given offset
 fn read_u64_le(data: &[u8], offset: usize) -> Result<u64> {
-    let bytes: [u8; 8] = data
-        .get(offset..offset + 8)
+    let bytes: [u8; 8] = offset
+        .checked_add(8)
+        .and_then(|end| data.get(offset..end))
         .ok_or_else(|| {
             NntpError::InvalidResponse(format!(
                 "PAR2 packet truncated: cannot read u64 at offset {}",
@@ -79,6 +87,10 @@ impl Par2File {
         while offset < data.len() {
             // Need at least PAR2_PACKET_HEADER_SIZE bytes for header
             if offset + PAR2_PACKET_HEADER_SIZE > data.len() {
+                par2.warnings.push(ParseWarning::TrailingPar2Data {
+                    offset: offset as u64,
+                    len: (data.len() - offset) as u64,
+                });
                 break;
             }
 
@@ -108,22 +120,25 @@ impl Par2File {
             // Parse packet body based on type
             let packet_type = PacketType::from_bytes(&header.packet_type);
             let body_offset = offset + PAR2_PACKET_HEADER_SIZE;
-            if (header.length as usize) < PAR2_PACKET_HEADER_SIZE {
+            let packet_len = to_usize(header.length, "PAR2 packet length")?;
+            if packet_len < PAR2_PACKET_HEADER_SIZE {
                 return Err(NntpError::InvalidResponse(format!(
                     "PAR2 packet length {} is smaller than header size {} at offset {}",
                     header.length, PAR2_PACKET_HEADER_SIZE, offset
                 )));
             }
-            let body_len = header.length as usize - PAR2_PACKET_HEADER_SIZE;
 
-            if body_offset + body_len > data.len() {
-                return Err(NntpError::InvalidResponse(format!(
-                    "Packet body extends beyond file at offset {}",
-                    offset
-                )));
-            }
+            let packet_end = offset
+                .checked_add(packet_len)
+                .filter(|&end| end <= data.len())
+                .ok_or_else(|| {
+                    NntpError::InvalidResponse(format!(
+                        "Packet body extends beyond file at offset {}",
+                        offset
+                    ))
+                })?;
 
-            let body = &data[body_offset..body_offset + body_len];
+            let body = &data[body_offset..packet_end];
 
             match packet_type {
                 PacketType::Main => {
@@ -144,12 +159,21 @@ impl Par2File {
                 PacketType::Creator => {
                     par2.creator = Some(parse_creator_packet(body)?);
                 }
-                PacketType::Unknown(_) => {
-                    // Skip unknown packet types
+                PacketType::AsciiComment => {
+                    par2.comments.push(parse_ascii_comment_packet(body));
+                }
+                PacketType::UnicodeComment => {
+                    par2.comments.push(parse_unicode_comment_packet(body)?);
+                }
+                PacketType::Unknown(packet_type) => {
+                    par2.warnings.push(ParseWarning::UnknownPar2Packet {
+                        offset: offset as u64,
+                        packet_type,
+                    });
                 }
             }
 
-            offset += header.length as usize;
+            offset = packet_end;
         }
 
         Ok(par2)
@@ -167,7 +191,8 @@ impl Par2Set {
     ///
     /// # Arguments
     /// * `dir` - Directory path to search for PAR2 files
-    /// * `base_name` - Base name of the PAR2 set (e.g., "myfile" for "myfile.par2")
+    /// * `base_name` - Base name of the PAR2 set (e.g., "myfile" for "myfile.par2");
+    ///   files named after its [`sanitize`](crate::filename::sanitize)d form match too
     ///
     /// # Returns
     /// Par2Set with merged recovery slices from all volumes
@@ -181,14 +206,16 @@ impl Par2Set {
     /// println!("Total recovery slices: {}", set.total_recovery_slices);
     /// ```
     pub fn discover<P: AsRef<Path>>(dir: P, base_name: &str) -> Result<Self> {
-        let dir = dir.as_ref();
+        let dir = filename::long_path(dir.as_ref());
+        // Files written through an OutputSink carry the sanitized name
+        let sanitized_base = filename::sanitize(base_name);
 
         // Find all .par2 files matching the base name
         let mut par2_files = Vec::new();
         let mut main_file: Option<PathBuf> = None;
 
         // Read directory entries
-        let entries = fs::read_dir(dir)
+        let entries = fs::read_dir(&dir)
             .map_err(|e| NntpError::InvalidResponse(format!("Failed to read directory: {}", e)))?;
 
         for entry in entries {
@@ -209,7 +236,9 @@ impl Par2Set {
             };
 
             // Check if file matches our base name
-            if !file_name.starts_with(base_name) || !file_name.ends_with(".par2") {
+            let matches_base =
+                file_name.starts_with(base_name) || file_name.starts_with(&sanitized_base);
+            if !matches_base || !file_name.ends_with(".par2") {
                 continue;
             }
 
@@ -309,16 +338,20 @@ fn parse_main_packet(data: &[u8]) -> Result<MainPacket> {
     }
     let file_count = read_u32_le(data, 8)?;
 
+    // The declared count must fit in the packet before anything is allocated
+    let available = (data.len() - MAIN_PACKET_MIN_SIZE) / MD5_HASH_SIZE;
+    if u64::from(file_count) > available as u64 {
+        return Err(NntpError::LengthOutOfRange {
+            context: "PAR2 Main packet file count",
+            value: u64::from(file_count),
+        });
+    }
+
     // Read file IDs (MD5_HASH_SIZE bytes each)
     let mut offset = MAIN_PACKET_MIN_SIZE;
-    let mut file_ids = Vec::new();
+    let mut file_ids = Vec::with_capacity(file_count as usize);
 
     for _ in 0..file_count {
-        if offset + MD5_HASH_SIZE > data.len() {
-            return Err(NntpError::InvalidResponse(
-                "Main packet file ID out of bounds".to_string(),
-            ));
-        }
         let mut file_id = [0u8; MD5_HASH_SIZE];
         file_id.copy_from_slice(&data[offset..offset + MD5_HASH_SIZE]);
         file_ids.push(file_id);
@@ -435,6 +468,28 @@ fn parse_creator_packet(data: &[u8]) -> Result<CreatorPacket> {
     Ok(CreatorPacket { client })
 }
 
+/// Parse ASCII comment packet body
+fn parse_ascii_comment_packet(data: &[u8]) -> String {
+    // Text padded with nulls to a multiple of 4 bytes
+    let end = data.iter().rposition(|&b| b != 0).map_or(0, |pos| pos + 1);
+    String::from_utf8_lossy(&data[..end]).to_string()
+}
+
+/// Parse Unicode comment packet body
+fn parse_unicode_comment_packet(data: &[u8]) -> Result<String> {
+    // MD5 of the matching ASCII comment packet, then UTF-16LE text padded
+    // with nulls to a multiple of 4 bytes
+    let text = data.get(MD5_HASH_SIZE..).ok_or_else(|| {
+        NntpError::InvalidResponse("Unicode comment packet too small".to_string())
+    })?;
+    let units: Vec<u16> = text
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    let end = units.iter().rposition(|&u| u != 0).map_or(0, |pos| pos + 1);
+    Ok(String::from_utf16_lossy(&units[..end]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -540,6 +595,31 @@ mod tests {
         assert_eq!(parsed.client, "par2cmdline-0.4");
     }
 
+    #[test]
+    fn test_parse_comment_packets() {
+        assert_eq!(parse_ascii_comment_packet(b"hello\0\0\0"), "hello");
+
+        let mut data = vec![0u8; MD5_HASH_SIZE];
+        for unit in "h\u{e9}llo".encode_utf16() {
+            data.extend_from_slice(&unit.to_le_bytes());
+        }
+        data.extend_from_slice(&[0, 0]);
+        assert_eq!(parse_unicode_comment_packet(&data).unwrap(), "h\u{e9}llo");
+        assert!(parse_unicode_comment_packet(&[0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_comment_packets_are_not_reported_as_unknown() {
+        let mut data = main_packet_header((PAR2_PACKET_HEADER_SIZE + 8) as u64);
+        data[(PAR2_MAGIC_SIZE + 8 + MD5_HASH_SIZE * 2)..PAR2_PACKET_HEADER_SIZE]
+            .copy_from_slice(b"PAR 2.0\0CommASCI");
+        data.extend_from_slice(b"note\0\0\0\0");
+
+        let par2 = Par2File::parse(&data).unwrap();
+        assert_eq!(par2.comments, ["note"]);
+        assert!(par2.warnings.is_empty());
+    }
+
     #[test]
     fn test_parse_packet_header_truncated() {
         // Header with only 10 bytes (should fail)
@@ -664,4 +744,71 @@ mod tests {
         let result = Par2File::parse(&data);
         assert!(result.is_err());
     }
+
+    /// Build a packet header with the given length field and Main packet type
+    fn main_packet_header(length: u64) -> Vec<u8> {
+        let mut data = vec![0u8; PAR2_PACKET_HEADER_SIZE];
+        data[0..PAR2_MAGIC_SIZE].copy_from_slice(PAR2_MAGIC);
+        data[PAR2_MAGIC_SIZE..(PAR2_MAGIC_SIZE + 8)].copy_from_slice(&length.to_le_bytes());
+        data[(PAR2_MAGIC_SIZE + 8 + MD5_HASH_SIZE)..(PAR2_MAGIC_SIZE + 8 + MD5_HASH_SIZE * 2)]
+            .fill(1);
+        data[(PAR2_MAGIC_SIZE + 8 + MD5_HASH_SIZE * 2)..PAR2_PACKET_HEADER_SIZE]
+            .copy_from_slice(b"PAR 2.0\0Main\0\0\0\0");
+        data
+    }
+
+    #[test]
+    fn test_skipped_packets_are_reported() {
+        let mut data = main_packet_header(PAR2_PACKET_HEADER_SIZE as u64);
+        data[(PAR2_MAGIC_SIZE + 8 + MD5_HASH_SIZE * 2)..PAR2_PACKET_HEADER_SIZE]
+            .copy_from_slice(b"PAR 2.0\0Future\0\0");
+        data.extend_from_slice(b"junk");
+
+        let par2 = Par2File::parse(&data).unwrap();
+        assert_eq!(
+            par2.warnings,
+            vec![
+                ParseWarning::UnknownPar2Packet {
+                    offset: 0,
+                    packet_type: *b"PAR 2.0\0Future\0\0",
+                },
+                ParseWarning::TrailingPar2Data {
+                    offset: PAR2_PACKET_HEADER_SIZE as u64,
+                    len: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_packet_length_overflow() {
+        // Lengths that overflow the offset arithmetic must be rejected, not wrap
+        for length in [u64::MAX, u64::MAX - 32, usize::MAX as u64] {
+            let data = main_packet_header(length);
+            assert!(Par2File::parse(&data).is_err(), "length {}", length);
+        }
+    }
+
+    #[test]
+    fn test_parse_main_packet_file_count_exceeds_body() {
+        let mut data = vec![0u8; MAIN_PACKET_MIN_SIZE + MD5_HASH_SIZE];
+        data[0..8].copy_from_slice(&1024u64.to_le_bytes());
+        // Claims 4 billion files but only has room for one
+        data[8..MAIN_PACKET_MIN_SIZE].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            parse_main_packet(&data),
+            Err(NntpError::LengthOutOfRange {
+                value: 0xFFFF_FFFF,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_read_le_offset_overflow() {
+        let data = vec![0u8; 16];
+        assert!(read_u32_le(&data, usize::MAX - 1).is_err());
+        assert!(read_u64_le(&data, usize::MAX - 4).is_err());
+    }
 }
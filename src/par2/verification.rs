@@ -7,6 +7,7 @@
 
 use super::*;
 use crate::error::{NntpError, Result};
+use crate::hash::FileDigest;
 use crc32fast::Hasher as Crc32;
 use md5::{Digest, Md5};
 use std::collections::HashMap;
@@ -15,11 +16,12 @@ use std::collections::HashMap;
 /// Returns `true` if the slice is damaged (CRC mismatch or truncated).
 fn is_slice_damaged(file_data: &[u8], ifsc: &IfscPacket, mapping: &SliceMapping) -> Option<bool> {
     let checksums = ifsc.checksums.get(mapping.file_slice_index)?;
-    let slice_start = mapping.offset as usize;
-    if slice_start >= file_data.len() {
-        return Some(true); // truncated
-    }
-    let slice_end = std::cmp::min(slice_start + mapping.size as usize, file_data.len());
+    let slice_start = match usize::try_from(mapping.offset) {
+        Ok(start) if start < file_data.len() => start,
+        _ => return Some(true), // truncated
+    };
+    let slice_size = usize::try_from(mapping.size).unwrap_or(usize::MAX);
+    let slice_end = std::cmp::min(slice_start.saturating_add(slice_size), file_data.len());
     let mut hasher = Crc32::new();
     hasher.update(&file_data[slice_start..slice_end]);
     Some(hasher.finalize() != *checksums)
@@ -40,6 +42,7 @@ impl Par2File {
         })?;
 
         let mut slice_mappings = Vec::new();
+        let mut total_slices: u64 = 0;
 
         // Iterate through files in the order specified by the Main packet
         let main = self.main.as_ref().ok_or_else(|| {
@@ -54,15 +57,20 @@ impl Par2File {
                 ))
             })?;
 
-            // Calculate number of slices for this file
-            let file_slices = if file_desc.length == 0 {
-                0
-            } else {
-                file_desc.length.div_ceil(slice_size) as usize
-            };
+            // Calculate number of slices for this file, bounded by the
+            // format's slice limit before anything is allocated
+            let file_slices = file_desc.length.div_ceil(slice_size);
+            total_slices = total_slices.saturating_add(file_slices);
+            if total_slices > PAR2_MAX_SLICES {
+                return Err(NntpError::AllocationTooLarge {
+                    context: "PAR2 slice map",
+                    requested: total_slices,
+                    limit: PAR2_MAX_SLICES,
+                });
+            }
 
             // Create mapping for each slice
-            for file_slice_idx in 0..file_slices {
+            for file_slice_idx in 0..file_slices as usize {
                 let offset = file_slice_idx as u64 * slice_size;
                 let remaining = file_desc.length.saturating_sub(offset);
                 let size = std::cmp::min(slice_size, remaining);
@@ -236,7 +244,10 @@ impl Par2File {
         } else {
             // No IFSC packet — cannot verify individual slices.
             // Since hashes already failed, mark all slices as damaged.
-            let slice_size = self.slice_size().unwrap_or(1) as usize;
+            // A slice larger than memory covers the whole file
+            let slice_size = self
+                .slice_size()
+                .map_or(1, |size| usize::try_from(size).unwrap_or(usize::MAX));
             let num_slices = if slice_size > 0 {
                 file_data.len().div_ceil(slice_size)
             } else {
@@ -275,23 +286,27 @@ impl Par2File {
     /// Vector of damaged slice indices (0-based)
     // Main packet existence is validated by slice_size() check
     fn verify_slices(&self, file_data: &[u8], ifsc: &IfscPacket) -> Result<Vec<usize>> {
-        let slice_size = self
-            .slice_size()
-            .ok_or_else(|| NntpError::InvalidResponse("No main packet found in PAR2".to_string()))?
-            as usize;
+        let slice_size = self.slice_size().ok_or_else(|| {
+            NntpError::InvalidResponse("No main packet found in PAR2".to_string())
+        })?;
+        let slice_size = usize::try_from(slice_size).map_err(|_| NntpError::LengthOutOfRange {
+            context: "PAR2 slice size",
+            value: slice_size,
+        })?;
 
         let mut damaged = Vec::new();
 
         // Verify each slice
         for (slice_idx, &expected_crc) in ifsc.checksums.iter().enumerate() {
-            let slice_start = slice_idx * slice_size;
-            let slice_end = std::cmp::min(slice_start + slice_size, file_data.len());
-
-            if slice_start >= file_data.len() {
-                // Slice is beyond file size - file is truncated
-                damaged.push(slice_idx);
-                continue;
-            }
+            let slice_start = match slice_idx.checked_mul(slice_size) {
+                Some(start) if start < file_data.len() => start,
+                _ => {
+                    // Slice is beyond file size - file is truncated
+                    damaged.push(slice_idx);
+                    continue;
+                }
+            };
+            let slice_end = std::cmp::min(slice_start.saturating_add(slice_size), file_data.len());
 
             let slice_data = &file_data[slice_start..slice_end];
 
@@ -308,6 +323,25 @@ impl Par2File {
         Ok(damaged)
     }
 
+    /// Check a file's streamed hashes against its PAR2 description
+    ///
+    /// For files hashed while they were written with a
+    /// [`StreamingHasher`](crate::hash::StreamingHasher): the length and MD5
+    /// are compared without reading the file. Returns `false` if either
+    /// differs; [`verify_file`](Self::verify_file) then finds the damaged
+    /// slices.
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::InvalidResponse`] - `file_id` is not in the PAR2 set
+    pub fn verify_digest(&self, file_id: &[u8; 16], digest: &FileDigest) -> Result<bool> {
+        let file_desc = self
+            .file_descriptions
+            .get(file_id)
+            .ok_or_else(|| NntpError::InvalidResponse("File ID not found in PAR2".to_string()))?;
+        Ok(digest.len == file_desc.length && digest.md5 == file_desc.hash)
+    }
+
     /// Verify all files in the PAR2 set
     ///
     /// # Arguments
@@ -468,6 +502,37 @@ mod tests {
         assert_eq!(slices[1].size, 1024);
     }
 
+    #[test]
+    fn test_map_slices_rejects_excessive_slice_count() {
+        let mut par2 = Par2File::new();
+
+        let file_id = [1; 16];
+        par2.main = Some(MainPacket {
+            slice_size: 4,
+            file_count: 1,
+            file_ids: vec![file_id],
+            non_recoverable_file_ids: vec![],
+        });
+        par2.file_descriptions.insert(
+            file_id,
+            FileDescriptionPacket {
+                file_id,
+                hash: [0; 16],
+                hash_16k: [0; 16],
+                length: u64::MAX,
+                name: "huge.bin".into(),
+            },
+        );
+
+        assert!(matches!(
+            par2.map_slices(),
+            Err(NntpError::AllocationTooLarge {
+                limit: PAR2_MAX_SLICES,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_verify_all_no_files() {
         let par2 = Par2File::new();
@@ -476,6 +541,35 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn test_verify_digest() {
+        let mut par2 = Par2File::new();
+        let file_id = [1; 16];
+        let file_data = vec![0xAA; 2048];
+        par2.file_descriptions.insert(
+            file_id,
+            FileDescriptionPacket {
+                file_id,
+                hash: Md5::digest(&file_data).into(),
+                hash_16k: [0; 16],
+                length: 2048,
+                name: "test.bin".into(),
+            },
+        );
+
+        let mut hasher = crate::hash::StreamingHasher::new();
+        hasher.update(&file_data);
+        let digest = hasher.finalize();
+        assert!(par2.verify_digest(&file_id, &digest).unwrap());
+
+        let truncated = FileDigest {
+            len: 1024,
+            ..digest
+        };
+        assert!(!par2.verify_digest(&file_id, &truncated).unwrap());
+        assert!(par2.verify_digest(&[2; 16], &digest).is_err());
+    }
+
     #[test]
     fn test_verify_file_no_ifsc_hash_mismatch() {
         // Set up a Par2File with a file description and main packet but no IFSC packet.
@@ -0,0 +1,519 @@
+//! Reassembly of MIME binaries split across articles
+//!
+//! Before yEnc, binaries too large for one article were posted in two ways
+//! that are still found in archives and on mail-to-news gateways:
+//!
+//! - As MIME `message/partial` articles (RFC 2046 Section 5.2.2). Each
+//!   carries `id`, `number` and (on the last part at least) `total`
+//!   parameters, and the bodies joined in order form the original message,
+//!   headers included.
+//! - As one base64 attachment cut into articles numbered "(i/N)" in the
+//!   subject. Only the first carries the MIME headers; the others hold
+//!   nothing but base64 lines.
+//!
+//! [`PartialAssembler`] takes the articles in any order and decodes the
+//! file once all parts are in, as
+//! [`ArticleAssembler`](crate::ArticleAssembler) does for yEnc posts.
+
+use std::collections::BTreeMap;
+
+use crate::article::Article;
+use crate::article::mime::header_param;
+use crate::autodecode::{DecodedBody, decode_bytes_auto};
+use crate::completeness::subject_filename;
+use crate::error::{NntpError, Result};
+
+/// Headers of the first part that describe the attachment
+const CONTENT_HEADERS: &[&str] = &[
+    "MIME-Version",
+    "Content-Type",
+    "Content-Transfer-Encoding",
+    "Content-Disposition",
+];
+
+/// How the articles being assembled were split
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scheme {
+    /// `message/partial` parts with this `id`
+    MessagePartial(String),
+    /// Base64 lines numbered in the subject, which without its counter is this
+    Subject(String),
+}
+
+/// Collects the parts of a split MIME binary and reassembles it
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::PartialAssembler;
+/// use nntp_rs::article::ArticleBuilder;
+///
+/// let part = |number: u32, body: &str| {
+///     ArticleBuilder::new()
+///         .from("poster@example.com")
+///         .subject("holiday")
+///         .newsgroups(vec!["alt.binaries.test"])
+///         .extra_header(
+///             "Content-Type",
+///             format!("message/partial; id=\"holiday@example.com\"; number={}; total=2", number),
+///         )
+///         .body(body)
+///         .build()
+///         .unwrap()
+/// };
+///
+/// let mut assembler = PartialAssembler::new();
+/// assembler.add_article(&part(2, "bG8=\r\n")).unwrap();
+/// assert_eq!(assembler.missing_parts(), vec![1]);
+/// assembler
+///     .add_article(&part(
+///         1,
+///         "Content-Type: text/plain; name=\"hi.txt\"\r\n\
+///          Content-Transfer-Encoding: base64\r\n\r\naGVs\r\n",
+///     ))
+///     .unwrap();
+///
+/// let file = assembler.assemble().unwrap();
+/// assert_eq!(file.filename(), Some("hi.txt"));
+/// assert_eq!(file.data(), b"hello");
+/// ```
+#[derive(Debug, Default)]
+pub struct PartialAssembler {
+    /// How the parts were split, set by the first part added
+    scheme: Option<Scheme>,
+    /// Bodies by part number
+    parts: BTreeMap<u32, Vec<u8>>,
+    /// Total number of parts, once a part has announced it
+    total: Option<u32>,
+    /// Content headers of a subject-numbered first part
+    content_headers: Vec<(String, String)>,
+    /// File name from the subject of the first part
+    subject_filename: Option<String>,
+}
+
+impl PartialAssembler {
+    /// Create an empty assembler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an article holding one part, returning its part number
+    ///
+    /// A `message/partial` article is numbered by its Content-Type
+    /// parameters; any other article by the last "(i/N)" or "[i/N]" in its
+    /// subject.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the article has no part
+    /// number, belongs to a different split (another `message/partial` id,
+    /// another subject once the counter is taken out, or the other scheme),
+    /// was already added, or disagrees with earlier parts on the total.
+    pub fn add_article(&mut self, article: &Article) -> Result<u32> {
+        let (scheme, number, total) = match article.content_type() {
+            Some(content_type) if is_message_partial(content_type) => {
+                let id = header_param(content_type, "id").ok_or_else(|| {
+                    NntpError::InvalidResponse("message/partial without id".to_string())
+                })?;
+                let number = header_param(content_type, "number")
+                    .and_then(|number| number.trim().parse().ok())
+                    .ok_or_else(|| {
+                        NntpError::InvalidResponse(
+                            "message/partial without a valid number".to_string(),
+                        )
+                    })?;
+                let total =
+                    header_param(content_type, "total").and_then(|total| total.trim().parse().ok());
+                (Scheme::MessagePartial(id), number, total)
+            }
+            _ => {
+                let subject = &article.headers.subject;
+                let (counter, number, total) = subject_counter(subject).ok_or_else(|| {
+                    NntpError::InvalidResponse(format!("No part number in subject: {}", subject))
+                })?;
+                let base = format!("{}{}", &subject[..counter.start], &subject[counter.end..]);
+                (
+                    Scheme::Subject(base.trim().to_string()),
+                    number,
+                    Some(total),
+                )
+            }
+        };
+
+        if self
+            .scheme
+            .as_ref()
+            .is_some_and(|current| *current != scheme)
+        {
+            return Err(NntpError::InvalidResponse(format!(
+                "Part {} belongs to a different split binary",
+                number
+            )));
+        }
+        if let (Some(current), Some(total)) = (self.total, total)
+            && current != total
+        {
+            return Err(NntpError::InvalidResponse(format!(
+                "Part {} announces {} parts, earlier parts {}",
+                number, total, current
+            )));
+        }
+        let highest = self
+            .parts
+            .keys()
+            .next_back()
+            .map_or(number, |&last| last.max(number));
+        if number == 0 || total.or(self.total).is_some_and(|total| highest > total) {
+            return Err(NntpError::InvalidResponse(format!(
+                "Part number {} out of range",
+                number
+            )));
+        }
+        if self.parts.contains_key(&number) {
+            return Err(NntpError::InvalidResponse(format!(
+                "Part {} already added",
+                number
+            )));
+        }
+
+        if matches!(scheme, Scheme::Subject(_)) && number == 1 {
+            self.content_headers = CONTENT_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = article.headers.extra.get(name)?;
+                    Some((name.to_string(), value.clone()))
+                })
+                .collect();
+            self.subject_filename = subject_filename(&article.headers.subject)
+                .or_else(|| subject_name(&article.headers.subject))
+                .map(str::to_string);
+        }
+        self.scheme = Some(scheme);
+        self.total = self.total.or(total);
+        self.parts.insert(number, article.body_bytes().to_vec());
+        Ok(number)
+    }
+
+    /// Check if every part has been added
+    pub fn is_complete(&self) -> bool {
+        self.total
+            .is_some_and(|total| self.parts.len() == total as usize)
+    }
+
+    /// Number of parts added so far
+    pub fn parts_received(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Total number of parts, once a part has announced it
+    pub fn total_parts(&self) -> Option<u32> {
+        self.total
+    }
+
+    /// Part numbers not yet added
+    ///
+    /// Until the total is known (a `message/partial` split may announce
+    /// it only on its last part), only the gaps below the highest part
+    /// added are listed.
+    pub fn missing_parts(&self) -> Vec<u32> {
+        let last = self
+            .total
+            .or_else(|| self.parts.keys().next_back().copied())
+            .unwrap_or(0);
+        (1..=last)
+            .filter(|number| !self.parts.contains_key(number))
+            .collect()
+    }
+
+    /// Join the parts in order and decode the file they carry
+    ///
+    /// The joined data is decoded like a fetched body by
+    /// [`decode_body_auto`](crate::decode_body_auto), so a
+    /// `message/partial` split of a yEnc or uuencoded post is decoded too.
+    /// A base64 attachment without a file name in its MIME headers is
+    /// named after the subject of the first part.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if parts are missing or the
+    /// joined data does not decode.
+    pub fn assemble(&self) -> Result<DecodedBody> {
+        if !self.is_complete() {
+            return Err(NntpError::InvalidResponse(format!(
+                "Cannot assemble: missing parts {:?}",
+                self.missing_parts()
+            )));
+        }
+
+        let size = self
+            .parts
+            .values()
+            .map(|body| body.len() + 2)
+            .sum::<usize>();
+        let mut joined = Vec::with_capacity(size + 256);
+        if matches!(self.scheme, Some(Scheme::Subject(_))) {
+            for (name, value) in &self.content_headers {
+                joined.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+            }
+            joined.extend_from_slice(b"\r\n");
+        }
+        for body in self.parts.values() {
+            joined.extend_from_slice(body);
+            if !body.ends_with(b"\n") {
+                joined.extend_from_slice(b"\r\n");
+            }
+        }
+
+        match decode_bytes_auto(&joined)? {
+            DecodedBody::Base64 { filename, data } => Ok(DecodedBody::Base64 {
+                filename: filename.or_else(|| self.subject_filename.clone()),
+                data,
+            }),
+            DecodedBody::Plain(_) if matches!(self.scheme, Some(Scheme::Subject(_))) => {
+                Err(NntpError::InvalidResponse(
+                    "First part does not declare a base64 attachment".to_string(),
+                ))
+            }
+            decoded => Ok(decoded),
+        }
+    }
+}
+
+/// Check for a `message/partial` Content-Type
+fn is_message_partial(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("message/partial"))
+}
+
+/// Part number and total from the last "(i/N)" or "[i/N]" in a subject
+fn subject_part(subject: &str) -> Option<(u32, u32)> {
+    subject_counter(subject).map(|(_, number, total)| (number, total))
+}
+
+/// Byte range, part number and total of the last "(i/N)" or "[i/N]" in a subject
+fn subject_counter(subject: &str) -> Option<(std::ops::Range<usize>, u32, u32)> {
+    let mut rest = subject;
+    while let Some(end) = rest.rfind([')', ']']) {
+        let open = if rest.as_bytes()[end] == b')' {
+            '('
+        } else {
+            '['
+        };
+        if let Some(start) = rest[..end].rfind(open)
+            && let Some((number, total)) = rest[start + 1..end].split_once('/')
+            && let (Ok(number), Ok(total)) = (number.trim().parse(), total.trim().parse())
+        {
+            return Some((start..end + 1, number, total));
+        }
+        rest = &rest[..end];
+    }
+    None
+}
+
+/// Subject without its part counter, as a fallback file name
+fn subject_name(subject: &str) -> Option<&str> {
+    let name = subject
+        .rsplit_once([' ', '\t'])
+        .map_or(subject, |(name, counter)| {
+            if subject_part(counter).is_some() {
+                name
+            } else {
+                subject
+            }
+        })
+        .trim();
+    (!name.is_empty() && !name.contains(' ')).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article::ArticleBuilder;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    fn article(subject: &str, content_type: Option<&str>, body: &str) -> Article {
+        let mut builder = ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject(subject)
+            .newsgroups(vec!["alt.binaries.test"])
+            .body(body);
+        if let Some(content_type) = content_type {
+            builder = builder.extra_header("Content-Type", content_type);
+        }
+        builder.build().unwrap()
+    }
+
+    /// Base64 of `data` in 76-character lines, each ending in CRLF
+    fn base64_lines(data: &[u8]) -> Vec<String> {
+        let encoded = STANDARD.encode(data);
+        encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|line| format!("{}\r\n", String::from_utf8_lossy(line)))
+            .collect()
+    }
+
+    #[test]
+    fn test_subject_part() {
+        assert_eq!(subject_part("file.bin (3/12)"), Some((3, 12)));
+        assert_eq!(
+            subject_part("[1/5] - \"a.rar\" yEnc (07/40)"),
+            Some((7, 40))
+        );
+        assert_eq!(subject_part("x [ 2 / 4 ] (note)"), Some((2, 4)));
+        assert_eq!(subject_part("no counter"), None);
+        assert_eq!(subject_name("photo.jpg (1/3)"), Some("photo.jpg"));
+        assert_eq!(subject_name("my photo (1/3)"), None);
+    }
+
+    #[test]
+    fn test_base64_split_by_subject() {
+        let data: Vec<u8> = (0..2000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let lines = base64_lines(&data);
+        let chunks: Vec<String> = lines.chunks(10).map(|chunk| chunk.concat()).collect();
+        let total = chunks.len();
+        assert!(total > 2);
+
+        let first_body = format!(
+            "This is a multi-part message in MIME format.\r\n\
+             --cut\r\n\
+             Content-Type: text/plain\r\n\r\nEnjoy.\r\n\
+             --cut\r\n\
+             Content-Type: application/octet-stream\r\n\
+             Content-Transfer-Encoding: base64\r\n\r\n{}",
+            chunks[0]
+        );
+        let mut assembler = PartialAssembler::new();
+        for (index, chunk) in chunks.iter().enumerate().rev() {
+            let subject = format!("pattern.bin ({}/{})", index + 1, total);
+            let part = if index == 0 {
+                article(&subject, Some("multipart/mixed; boundary=cut"), &first_body)
+            } else if index + 1 == total {
+                article(&subject, None, &format!("{}--cut--\r\n", chunk))
+            } else {
+                article(&subject, None, chunk)
+            };
+            assert!(!assembler.is_complete());
+            assert_eq!(assembler.add_article(&part).unwrap(), index as u32 + 1);
+        }
+
+        assert!(assembler.is_complete());
+        let file = assembler.assemble().unwrap();
+        assert_eq!(file.filename(), Some("pattern.bin"));
+        assert_eq!(file.data(), data);
+    }
+
+    #[test]
+    fn test_message_partial_total_on_last_part() {
+        let content_type = |number: u32, total: Option<u32>| {
+            let total = total.map(|total| format!("; total={}", total));
+            format!(
+                "message/partial; id=\"abc@example.com\"; number={}{}",
+                number,
+                total.unwrap_or_default()
+            )
+        };
+        let lines = base64_lines(&[42u8; 300]);
+        let mut assembler = PartialAssembler::new();
+        assembler
+            .add_article(&article(
+                "big",
+                Some(&content_type(1, None)),
+                &format!(
+                    "Content-Type: application/octet-stream\r\n\
+                     Content-Disposition: attachment; filename=\"answer.bin\"\r\n\
+                     Content-Transfer-Encoding: base64\r\n\r\n{}",
+                    lines[..2].concat()
+                ),
+            ))
+            .unwrap();
+        assert_eq!(assembler.total_parts(), None);
+        assert!(assembler.missing_parts().is_empty());
+        assert!(assembler.assemble().is_err());
+
+        assembler
+            .add_article(&article(
+                "big",
+                Some(&content_type(3, Some(3))),
+                &lines[4..].concat(),
+            ))
+            .unwrap();
+        assert_eq!(assembler.missing_parts(), vec![2]);
+        assembler
+            .add_article(&article(
+                "big",
+                Some(&content_type(2, None)),
+                &lines[2..4].concat(),
+            ))
+            .unwrap();
+
+        let file = assembler.assemble().unwrap();
+        assert_eq!(file.filename(), Some("answer.bin"));
+        assert_eq!(file.data(), [42u8; 300]);
+    }
+
+    #[test]
+    fn test_rejects_foreign_and_duplicate_parts() {
+        let partial = |id: &str, number: u32| {
+            article(
+                "x",
+                Some(&format!(
+                    "message/partial; id=\"{}\"; number={}; total=2",
+                    id, number
+                )),
+                "",
+            )
+        };
+        let mut assembler = PartialAssembler::new();
+        assembler.add_article(&partial("a@b", 1)).unwrap();
+        assert!(assembler.add_article(&partial("a@b", 1)).is_err());
+        assert!(assembler.add_article(&partial("other@b", 2)).is_err());
+        assert!(assembler.add_article(&partial("a@b", 3)).is_err());
+
+        let mut assembler = PartialAssembler::new();
+        assembler
+            .add_article(&article("x", Some("message/partial; id=c; number=4"), ""))
+            .unwrap();
+        let short = article("x", Some("message/partial; id=c; number=1; total=3"), "");
+        assert!(assembler.add_article(&short).is_err());
+        assert!(
+            assembler
+                .add_article(&article("file (2/2)", None, ""))
+                .is_err()
+        );
+        assert!(
+            PartialAssembler::new()
+                .add_article(&article("no counter", None, ""))
+                .is_err()
+        );
+
+        // Subject-numbered parts must share the subject around the counter
+        let mut assembler = PartialAssembler::new();
+        assembler
+            .add_article(&article("photo.jpg (1/5)", None, ""))
+            .unwrap();
+        assert!(
+            assembler
+                .add_article(&article("other.bin (2/5)", None, ""))
+                .is_err()
+        );
+        assert_eq!(
+            assembler
+                .add_article(&article("photo.jpg (2/5)", None, ""))
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_subject_split_without_base64_header() {
+        let mut assembler = PartialAssembler::new();
+        assembler
+            .add_article(&article("notes.txt (1/1)", None, "just text\r\n"))
+            .unwrap();
+        assert!(assembler.assemble().is_err());
+    }
+}
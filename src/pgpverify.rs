@@ -0,0 +1,858 @@
+//! Verification of PGP-signed control messages (pgpverify)
+//!
+//! Hierarchy administrators sign their newgroup, rmgroup and checkgroups
+//! messages with `signcontrol`, which puts a detached OpenPGP signature in
+//! an X-PGP-Sig header:
+//!
+//! ```text
+//! X-PGP-Sig: 2.6.3i Subject,Control,Message-ID,Date,From,Sender
+//!     iQCVAwUBN...
+//!     =abcd
+//! ```
+//!
+//! The first word is the version of the signing program, the second the
+//! headers covered by the signature, and the rest the Base64 lines of the
+//! signature. The signed text is an `X-Signed-Headers` line, each signed
+//! header as `Name: value` (empty if the article lacks it), a blank line
+//! and the body, with LF line endings. This module rebuilds that text and
+//! checks the signature against a [`Keyring`] of trusted public keys,
+//! without calling out to gpg.
+//!
+//! RSA and DSA keys and signatures (OpenPGP versions 3 and 4) with MD5,
+//! SHA-1 and SHA-2 hashes are supported, which covers the keys hierarchy
+//! administrators publish. Keys are trusted as given: self-signatures,
+//! expiry and revocation are not checked, so the keyring should only hold
+//! keys the administrator has vetted.
+//!
+//! The signature math is left to the `rsa` and `dsa` crates. Only the
+//! packet framing is parsed here: signcontrol signatures and published
+//! hierarchy keys use a small, fixed subset of OpenPGP, which does not
+//! justify depending on a full OpenPGP implementation.
+//!
+//! [`Keyring`]: crate::pgpverify::Keyring
+
+use dsa::signature::hazmat::PrehashVerifier;
+use md5::Md5;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+use crate::article::Article;
+use crate::article::parsing::split_article;
+use crate::error::{NntpError, Result};
+
+/// OpenPGP packet tags (RFC 4880 Section 4.3)
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+
+/// Public-key algorithms (RFC 4880 Section 9.1)
+const ALGO_RSA: u8 = 1;
+const ALGO_RSA_SIGN_ONLY: u8 = 3;
+const ALGO_DSA: u8 = 17;
+
+/// Signature types over a document (RFC 4880 Section 5.2.1)
+const SIG_BINARY: u8 = 0x00;
+const SIG_TEXT: u8 = 0x01;
+
+/// Issuer subpacket type (RFC 4880 Section 5.2.3.5)
+const SUBPACKET_ISSUER: u8 = 16;
+
+/// Issuer fingerprint subpacket type (RFC 9580 Section 5.2.3.35)
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+/// Trusted public keys of hierarchy administrators
+///
+/// # Example
+///
+/// ```no_run
+/// use nntp_rs::ControlMessage;
+/// use nntp_rs::pgpverify::Keyring;
+///
+/// # fn example(article: &nntp_rs::Article) -> nntp_rs::Result<()> {
+/// let keyring = Keyring::from_armored(&std::fs::read_to_string("control.asc")?)?;
+/// let signer = ControlMessage::verify_signature(article, &keyring)?;
+/// println!("Signed by {}", signer);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+    keys: Vec<PublicKey>,
+}
+
+/// A primary key or subkey that can verify signatures
+#[derive(Debug, Clone)]
+struct PublicKey {
+    key_id: [u8; 8],
+    material: KeyMaterial,
+    /// First user ID of the key (or its primary key)
+    user_id: Option<String>,
+}
+
+/// Algorithm-specific public key
+#[derive(Debug, Clone)]
+enum KeyMaterial {
+    Rsa(RsaPublicKey),
+    Dsa(dsa::VerifyingKey),
+}
+
+/// A parsed detached signature
+struct Signature {
+    sig_type: u8,
+    pubkey_algo: u8,
+    hash_algo: u8,
+    issuer: Option<[u8; 8]>,
+    /// Bytes hashed after the signed data
+    trailer: Vec<u8>,
+    /// Left 16 bits of the hash, a quick check before the math
+    hash_prefix: [u8; 2],
+    values: Vec<BigUint>,
+}
+
+impl Keyring {
+    /// Create an empty keyring
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a keyring from ASCII-armored public keys
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the text holds no armored
+    /// block or a block does not parse.
+    pub fn from_armored(text: &str) -> Result<Self> {
+        let mut keyring = Self::new();
+        keyring.add_armored(text)?;
+        Ok(keyring)
+    }
+
+    /// Add the keys of every ASCII-armored block in `text` (e.g. a file of
+    /// several exported keys), returning how many were added
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the text holds no armored
+    /// block or a block does not parse; no keys are added then.
+    pub fn add_armored(&mut self, text: &str) -> Result<usize> {
+        let blocks = dearmor_all(text)?;
+        if blocks.is_empty() {
+            return Err(NntpError::InvalidResponse(
+                "No ASCII-armored PGP block found".to_string(),
+            ));
+        }
+        let mut keys = Vec::new();
+        for block in blocks {
+            keys.extend(parse_keys(&block)?);
+        }
+        let added = keys.len();
+        self.keys.extend(keys);
+        Ok(added)
+    }
+
+    /// Add the keys of binary (unarmored) OpenPGP key packets, returning
+    /// how many were added
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the packets do not parse.
+    pub fn add_binary(&mut self, data: &[u8]) -> Result<usize> {
+        let keys = parse_keys(data)?;
+        let added = keys.len();
+        self.keys.extend(keys);
+        Ok(added)
+    }
+
+    /// Number of keys and subkeys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check if the keyring holds no keys
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Verify the X-PGP-Sig signature of a control message
+///
+/// Returns the user ID of the signing key (its key ID in hex if it has
+/// none). See [`ControlMessage::verify_signature`](crate::ControlMessage::verify_signature).
+pub(crate) fn verify_article(article: &Article, keyring: &Keyring) -> Result<String> {
+    let posting;
+    let (header_text, body) = match article.raw() {
+        Some(raw) => split_article(raw),
+        None => {
+            let mut headers = String::new();
+            article.write_posting_headers(&mut headers);
+            posting = headers;
+            (posting.as_str(), article.body.as_str())
+        }
+    };
+    let headers = header_fields(header_text);
+
+    let x_pgp_sig = unique_header(&headers, "X-PGP-Sig")?
+        .ok_or_else(|| NntpError::InvalidResponse("Article has no X-PGP-Sig header".to_string()))?;
+    let mut words = x_pgp_sig.split_whitespace();
+    let (Some(_version), Some(signed_headers)) = (words.next(), words.next()) else {
+        return Err(NntpError::InvalidResponse(
+            "Malformed X-PGP-Sig header".to_string(),
+        ));
+    };
+    let signature_base64: String = words.take_while(|word| !word.starts_with('=')).collect();
+
+    if unique_header(&headers, "Control")?.is_none() {
+        return Err(NntpError::InvalidResponse(
+            "Article is not a control message".to_string(),
+        ));
+    }
+    if !signed_headers
+        .split(',')
+        .any(|label| label.eq_ignore_ascii_case("Control"))
+    {
+        return Err(NntpError::InvalidResponse(
+            "Control header is not signed".to_string(),
+        ));
+    }
+
+    let mut signed = format!("X-Signed-Headers: {}\n", signed_headers);
+    for label in signed_headers.split(',') {
+        let value = unique_header(&headers, label)?.unwrap_or_default();
+        signed.push_str(&format!("{}: {}\n", label, value));
+    }
+    signed.push('\n');
+    signed.push_str(&body.replace("\r\n", "\n"));
+
+    let packet = decode_base64(&signature_base64)?;
+    let signature = parse_signature(&packet)?;
+    verify_signature(&signature, signed.as_bytes(), keyring)
+}
+
+/// Header fields of an article as (name, value) pairs
+///
+/// Folded values keep their line breaks, as pgpverify sees them; the one
+/// space or tab after the colon is not part of the value.
+fn header_fields(header_text: &str) -> Vec<(&str, String)> {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    for line in header_text.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push('\n');
+                value.push_str(line);
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.strip_prefix([' ', '\t']).unwrap_or(value).to_string();
+            fields.push((name, value));
+        }
+    }
+    fields
+}
+
+/// Value of header `name`, refusing duplicates that could smuggle text past
+/// the signature
+fn unique_header<'a>(fields: &'a [(&str, String)], name: &str) -> Result<Option<&'a str>> {
+    let mut values = fields
+        .iter()
+        .filter(|(field, _)| field.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str());
+    let first = values.next();
+    if values.next().is_some() {
+        return Err(NntpError::InvalidResponse(format!(
+            "Duplicate {} header in signed article",
+            name
+        )));
+    }
+    Ok(first)
+}
+
+/// Check `signature` over `data` against the keys of `keyring`
+fn verify_signature(signature: &Signature, data: &[u8], keyring: &Keyring) -> Result<String> {
+    let digest = signed_digest(signature, data)?;
+    if digest.get(..2) != Some(&signature.hash_prefix[..]) {
+        return Err(NntpError::InvalidResponse(
+            "Bad PGP signature: signed text does not match".to_string(),
+        ));
+    }
+
+    let mut candidates = keyring
+        .keys
+        .iter()
+        .filter(|key| signature.issuer.is_none_or(|issuer| issuer == key.key_id))
+        .peekable();
+    if candidates.peek().is_none() {
+        return Err(NntpError::InvalidResponse(format!(
+            "No key in keyring for signer {}",
+            signature
+                .issuer
+                .map_or_else(|| "(unknown)".to_string(), |id| hex(&id))
+        )));
+    }
+    for key in candidates {
+        if key_verifies(key, signature, &digest)? {
+            return Ok(key.user_id.clone().unwrap_or_else(|| hex(&key.key_id)));
+        }
+    }
+    Err(NntpError::InvalidResponse("Bad PGP signature".to_string()))
+}
+
+/// Hash of the signed data with the signature's trailer
+fn signed_digest(signature: &Signature, data: &[u8]) -> Result<Vec<u8>> {
+    let canonical;
+    let data = match signature.sig_type {
+        SIG_BINARY => data,
+        SIG_TEXT => {
+            canonical = canonical_text(data);
+            &canonical
+        }
+        other => {
+            return Err(NntpError::InvalidResponse(format!(
+                "PGP signature of type {:#04x} does not sign a document",
+                other
+            )));
+        }
+    };
+    let hashed = [data, &signature.trailer];
+    match signature.hash_algo {
+        1 => Ok(digest::<Md5>(&hashed)),
+        2 => Ok(digest::<Sha1>(&hashed)),
+        8 => Ok(digest::<Sha256>(&hashed)),
+        9 => Ok(digest::<Sha384>(&hashed)),
+        10 => Ok(digest::<Sha512>(&hashed)),
+        11 => Ok(digest::<Sha224>(&hashed)),
+        other => Err(NntpError::InvalidResponse(format!(
+            "Unsupported PGP hash algorithm {}",
+            other
+        ))),
+    }
+}
+
+/// Hash the concatenation of `parts`
+fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = D::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Text as a text-mode signature hashes it: with CRLF line endings
+/// (RFC 4880 Section 5.2.1); trailing whitespace is kept, as GnuPG keeps it
+fn canonical_text(data: &[u8]) -> Vec<u8> {
+    let mut canonical = Vec::with_capacity(data.len() + data.len() / 32);
+    let mut lines = data.split(|&byte| byte == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        canonical.extend_from_slice(line.strip_suffix(b"\r").unwrap_or(line));
+        if lines.peek().is_some() {
+            canonical.extend_from_slice(b"\r\n");
+        }
+    }
+    canonical
+}
+
+/// Check the signature values against one key
+fn key_verifies(key: &PublicKey, signature: &Signature, digest: &[u8]) -> Result<bool> {
+    match (
+        &key.material,
+        signature.pubkey_algo,
+        signature.values.as_slice(),
+    ) {
+        (KeyMaterial::Rsa(key), ALGO_RSA | ALGO_RSA_SIGN_ONLY, [s]) => {
+            Ok(rsa_verifies(key, s, signature.hash_algo, digest))
+        }
+        (KeyMaterial::Dsa(key), ALGO_DSA, [r, s]) => Ok(dsa_verifies(key, r, s, digest)),
+        (_, ALGO_RSA | ALGO_RSA_SIGN_ONLY | ALGO_DSA, _) => Ok(false),
+        (_, other, _) => Err(NntpError::InvalidResponse(format!(
+            "Unsupported PGP public-key algorithm {}",
+            other
+        ))),
+    }
+}
+
+/// RSASSA-PKCS1-v1_5 verification of an OpenPGP RSA signature
+fn rsa_verifies(key: &RsaPublicKey, s: &BigUint, hash_algo: u8, digest: &[u8]) -> bool {
+    let scheme = match hash_algo {
+        1 => Pkcs1v15Sign::new::<Md5>(),
+        2 => Pkcs1v15Sign::new::<Sha1>(),
+        8 => Pkcs1v15Sign::new::<Sha256>(),
+        9 => Pkcs1v15Sign::new::<Sha384>(),
+        10 => Pkcs1v15Sign::new::<Sha512>(),
+        11 => Pkcs1v15Sign::new::<Sha224>(),
+        _ => return false,
+    };
+    // The MPI drops leading zero octets, the verifier wants the key's length
+    let s = s.to_bytes_be();
+    let Some(padding) = key.size().checked_sub(s.len()) else {
+        return false;
+    };
+    let mut signature = vec![0; padding];
+    signature.extend_from_slice(&s);
+    key.verify(scheme, digest, &signature).is_ok()
+}
+
+/// DSA verification of an OpenPGP DSA signature
+///
+/// The hash is truncated to the size of `q` by the verifier.
+fn dsa_verifies(key: &dsa::VerifyingKey, r: &BigUint, s: &BigUint, digest: &[u8]) -> bool {
+    dsa::Signature::from_components(r.clone(), s.clone())
+        .is_ok_and(|signature| key.verify_prehash(digest, &signature).is_ok())
+}
+
+/// Keys and subkeys of a sequence of transferable public keys
+fn parse_keys(data: &[u8]) -> Result<Vec<PublicKey>> {
+    let mut keys: Vec<PublicKey> = Vec::new();
+    // Index of the current primary key, whose user ID its subkeys share
+    let mut primary = None;
+    for packet in packets(data) {
+        let (tag, body) = packet?;
+        match tag {
+            TAG_PUBLIC_KEY | TAG_PUBLIC_SUBKEY => {
+                let user_id = match (tag, primary) {
+                    (TAG_PUBLIC_SUBKEY, Some(index)) => keys
+                        .get(index)
+                        .and_then(|key: &PublicKey| key.user_id.clone()),
+                    _ => None,
+                };
+                if let Some(mut key) = parse_public_key(body)? {
+                    key.user_id = user_id;
+                    if tag == TAG_PUBLIC_KEY {
+                        primary = Some(keys.len());
+                    }
+                    keys.push(key);
+                } else if tag == TAG_PUBLIC_KEY {
+                    primary = None;
+                }
+            }
+            TAG_USER_ID => {
+                let key = primary.and_then(|index| keys.get_mut(index));
+                if let Some(key) = key.filter(|key| key.user_id.is_none()) {
+                    key.user_id = Some(String::from_utf8_lossy(body).into_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+    if keys.is_empty() {
+        return Err(NntpError::InvalidResponse(
+            "No RSA or DSA public key found".to_string(),
+        ));
+    }
+    Ok(keys)
+}
+
+/// Parse a public key packet; `None` for algorithms that are not supported
+fn parse_public_key(body: &[u8]) -> Result<Option<PublicKey>> {
+    let mut reader = PacketReader::new(body);
+    let version = reader.u8()?;
+    reader.take(4)?; // creation time
+    if version == 3 {
+        reader.take(2)?; // validity in days
+    } else if version != 4 {
+        return Ok(None);
+    }
+    let algo = reader.u8()?;
+    // Keys the verifiers reject as malformed are skipped like unsupported ones
+    let material = match algo {
+        ALGO_RSA | ALGO_RSA_SIGN_ONLY => {
+            let (n, e) = (reader.mpi()?, reader.mpi()?);
+            match RsaPublicKey::new(n, e) {
+                Ok(key) => KeyMaterial::Rsa(key),
+                Err(_) => return Ok(None),
+            }
+        }
+        ALGO_DSA => {
+            let (p, q, g, y) = (reader.mpi()?, reader.mpi()?, reader.mpi()?, reader.mpi()?);
+            match dsa::Components::from_components(p, q, g)
+                .and_then(|components| dsa::VerifyingKey::from_components(components, y))
+            {
+                Ok(key) => KeyMaterial::Dsa(key),
+                Err(_) => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    let key_id = match (version, &material) {
+        // Version 3 keys are identified by the low 64 bits of the modulus
+        (3, KeyMaterial::Rsa(key)) => low_64_bits(&key.n().to_bytes_be()),
+        (3, _) => return Ok(None),
+        _ => {
+            let length = u16::try_from(body.len()).map_err(|_| {
+                NntpError::InvalidResponse("PGP public key packet too long".to_string())
+            })?;
+            let mut hasher = Sha1::new();
+            hasher.update([0x99]);
+            hasher.update(length.to_be_bytes());
+            hasher.update(body);
+            low_64_bits(&hasher.finalize())
+        }
+    };
+    Ok(Some(PublicKey {
+        key_id,
+        material,
+        user_id: None,
+    }))
+}
+
+/// Parse a version 3 or 4 signature packet (RFC 4880 Section 5.2)
+fn parse_signature(data: &[u8]) -> Result<Signature> {
+    let Some(packet) = packets(data).next() else {
+        return Err(NntpError::InvalidResponse(
+            "Empty PGP signature".to_string(),
+        ));
+    };
+    let (tag, body) = packet?;
+    if tag != TAG_SIGNATURE {
+        return Err(NntpError::InvalidResponse(format!(
+            "Expected a PGP signature packet, found packet type {}",
+            tag
+        )));
+    }
+
+    let mut reader = PacketReader::new(body);
+    let mut signature = match reader.u8()? {
+        3 => parse_v3_signature_fields(&mut reader)?,
+        4 => parse_v4_signature_fields(&mut reader, body)?,
+        other => {
+            return Err(NntpError::InvalidResponse(format!(
+                "Unsupported PGP signature version {}",
+                other
+            )));
+        }
+    };
+    let prefix = reader.take(2)?;
+    signature.hash_prefix = [prefix[0], prefix[1]];
+    let value_count = if signature.pubkey_algo == ALGO_DSA {
+        2
+    } else {
+        1
+    };
+    for _ in 0..value_count {
+        signature.values.push(reader.mpi()?);
+    }
+    Ok(signature)
+}
+
+/// Fields of a version 3 signature after the version octet
+fn parse_v3_signature_fields(reader: &mut PacketReader<'_>) -> Result<Signature> {
+    if reader.u8()? != 5 {
+        return Err(NntpError::InvalidResponse(
+            "Malformed version 3 PGP signature".to_string(),
+        ));
+    }
+    // Signature type and creation time are hashed after the data
+    let trailer = reader.take(5)?.to_vec();
+    let issuer = low_64_bits(reader.take(8)?);
+    Ok(Signature {
+        sig_type: trailer[0],
+        pubkey_algo: reader.u8()?,
+        hash_algo: reader.u8()?,
+        issuer: Some(issuer),
+        trailer,
+        hash_prefix: [0; 2],
+        values: Vec::new(),
+    })
+}
+
+/// Fields of a version 4 signature after the version octet
+fn parse_v4_signature_fields(reader: &mut PacketReader<'_>, body: &[u8]) -> Result<Signature> {
+    let sig_type = reader.u8()?;
+    let pubkey_algo = reader.u8()?;
+    let hash_algo = reader.u8()?;
+    let hashed_len = usize::from(reader.u16()?);
+    let hashed = reader.take(hashed_len)?;
+    let unhashed_len = usize::from(reader.u16()?);
+    let unhashed = reader.take(unhashed_len)?;
+
+    // Version through the hashed subpackets, then 0x04 0xff and their length
+    let hashed_part = body
+        .get(..6 + hashed_len)
+        .ok_or_else(|| NntpError::InvalidResponse("Truncated PGP signature".to_string()))?;
+    let mut trailer = hashed_part.to_vec();
+    trailer.extend_from_slice(&[0x04, 0xff]);
+    trailer.extend_from_slice(&(hashed_part.len() as u32).to_be_bytes());
+
+    let issuer = match subpacket_issuer(hashed)? {
+        Some(issuer) => Some(issuer),
+        None => subpacket_issuer(unhashed)?,
+    };
+    Ok(Signature {
+        sig_type,
+        pubkey_algo,
+        hash_algo,
+        issuer,
+        trailer,
+        hash_prefix: [0; 2],
+        values: Vec::new(),
+    })
+}
+
+/// Key ID named by an issuer or issuer fingerprint subpacket
+fn subpacket_issuer(mut data: &[u8]) -> Result<Option<[u8; 8]>> {
+    let truncated = || NntpError::InvalidResponse("Truncated PGP signature subpacket".to_string());
+    while let Some(&first) = data.first() {
+        let (length, header) = match first {
+            0..192 => (usize::from(first), 1),
+            192..255 => {
+                let second = *data.get(1).ok_or_else(truncated)?;
+                (
+                    ((usize::from(first) - 192) << 8) + usize::from(second) + 192,
+                    2,
+                )
+            }
+            255 => {
+                let bytes = data.get(1..5).ok_or_else(truncated)?;
+                (read_u32(bytes) as usize, 5)
+            }
+        };
+        let subpacket = data
+            .get(header..header + length)
+            .filter(|subpacket| !subpacket.is_empty())
+            .ok_or_else(truncated)?;
+        data = &data[header + length..];
+
+        let (kind, content) = (subpacket[0] & 0x7f, &subpacket[1..]);
+        match kind {
+            SUBPACKET_ISSUER if content.len() == 8 => return Ok(Some(low_64_bits(content))),
+            // Version byte and a 20-byte v4 fingerprint, whose low 64 bits are the key ID
+            SUBPACKET_ISSUER_FINGERPRINT if content.len() == 21 => {
+                return Ok(Some(low_64_bits(content)));
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Tag and body of an OpenPGP packet
+type Packet<'a> = (u8, &'a [u8]);
+
+/// Packets of OpenPGP data (RFC 4880 Section 4.2)
+fn packets(data: &[u8]) -> impl Iterator<Item = Result<Packet<'_>>> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        match split_packet(rest) {
+            Ok((packet, tail)) => {
+                rest = tail;
+                Some(Ok(packet))
+            }
+            Err(e) => {
+                rest = &[];
+                Some(Err(e))
+            }
+        }
+    })
+}
+
+/// Split the first packet off `data`, returning it and the rest
+fn split_packet(data: &[u8]) -> Result<(Packet<'_>, &[u8])> {
+    let malformed = || NntpError::InvalidResponse("Malformed PGP packet".to_string());
+    let mut reader = PacketReader::new(data);
+    let first = reader.u8()?;
+    if first & 0x80 == 0 {
+        return Err(malformed());
+    }
+
+    let (tag, length) = if first & 0x40 != 0 {
+        // New format; partial lengths are only used for literal data
+        let length = match reader.u8()? {
+            octet @ 0..192 => usize::from(octet),
+            octet @ 192..224 => ((usize::from(octet) - 192) << 8) + usize::from(reader.u8()?) + 192,
+            255 => reader.u32()? as usize,
+            _ => return Err(malformed()),
+        };
+        (first & 0x3f, length)
+    } else {
+        let length = match first & 0x03 {
+            0 => usize::from(reader.u8()?),
+            1 => usize::from(reader.u16()?),
+            2 => reader.u32()? as usize,
+            _ => reader.remaining(),
+        };
+        ((first >> 2) & 0x0f, length)
+    };
+    let body = reader.take(length)?;
+    Ok(((tag, body), reader.rest()))
+}
+
+/// Cursor over the bytes of a packet
+struct PacketReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.data.len() < count {
+            return Err(NntpError::InvalidResponse(
+                "Truncated PGP packet".to_string(),
+            ));
+        }
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(read_u32(self.take(4)?))
+    }
+
+    /// Multiprecision integer: bit count, then big-endian bytes (RFC 4880 Section 3.2)
+    fn mpi(&mut self) -> Result<BigUint> {
+        let bits = usize::from(self.u16()?);
+        Ok(BigUint::from_bytes_be(self.take(bits.div_ceil(8))?))
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Big-endian u32 of the first four of at least four bytes
+fn read_u32(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0, |value, &byte| (value << 8) | u32::from(byte))
+}
+
+/// Last eight bytes of `bytes`, zero-padded on the left if shorter
+fn low_64_bits(bytes: &[u8]) -> [u8; 8] {
+    let mut id = [0u8; 8];
+    let tail = &bytes[bytes.len().saturating_sub(8)..];
+    id[8 - tail.len()..].copy_from_slice(tail);
+    id
+}
+
+/// Uppercase hex of a key ID
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Decode Base64 as it appears in armor and X-PGP-Sig
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    use base64::Engine;
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(compact)
+        .map_err(|e| NntpError::InvalidResponse(format!("Invalid PGP Base64: {}", e)))
+}
+
+/// Binary contents of every ASCII-armored block in `text` (RFC 4880 Section 6.2)
+fn dearmor_all(text: &str) -> Result<Vec<Vec<u8>>> {
+    let mut blocks = Vec::new();
+    let mut lines = text.lines().map(str::trim_end);
+    while lines.any(|line| line.starts_with("-----BEGIN PGP ")) {
+        // Armor headers end at the first blank line
+        if !lines.by_ref().any(str::is_empty) {
+            break;
+        }
+        let mut base64 = String::new();
+        let mut checksum = None;
+        for line in lines.by_ref() {
+            if line.starts_with("-----END PGP ") {
+                break;
+            }
+            match line.strip_prefix('=') {
+                Some(crc) => checksum = Some(crc.to_string()),
+                None => base64.push_str(line),
+            }
+        }
+        let data = decode_base64(&base64)?;
+        if let Some(checksum) = checksum {
+            check_crc24(&data, &checksum)?;
+        }
+        blocks.push(data);
+    }
+    Ok(blocks)
+}
+
+/// Verify the armor checksum of `data`
+fn check_crc24(data: &[u8], checksum: &str) -> Result<()> {
+    let expected = decode_base64(checksum)?;
+    let crc = crc24(data).to_be_bytes();
+    if expected != crc[1..] {
+        return Err(NntpError::InvalidResponse(
+            "PGP armor checksum mismatch".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// CRC-24 of the armor checksum (RFC 4880 Section 6.1)
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0x00b7_04ce;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= 0x0186_4cfb;
+            }
+        }
+    }
+    crc & 0x00ff_ffff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc24() {
+        assert_eq!(crc24(b""), 0x00b7_04ce);
+        // Check value of the CRC-24/OPENPGP catalogue entry
+        assert_eq!(crc24(b"123456789"), 0x0021_cf02);
+    }
+
+    #[test]
+    fn test_canonical_text() {
+        assert_eq!(canonical_text(b"a  \nb\r\nc"), b"a  \r\nb\r\nc");
+        assert_eq!(canonical_text(b"line\n"), b"line\r\n");
+    }
+
+    #[test]
+    fn test_low_64_bits() {
+        assert_eq!(low_64_bits(&[1, 2, 3]), [0, 0, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(low_64_bits(&[9; 20]), [9; 8]);
+    }
+
+    #[test]
+    fn test_split_packet_lengths() {
+        // Old format, one-octet length
+        let ((tag, body), rest) = split_packet(&[0x88, 0x02, 0xaa, 0xbb, 0xcc]).unwrap();
+        assert_eq!((tag, body, rest), (2, &[0xaa, 0xbb][..], &[0xcc][..]));
+        // New format, two-octet length of 192
+        let mut data = vec![0xc2, 0xc0, 0x00];
+        data.extend([7u8; 192]);
+        let ((tag, body), rest) = split_packet(&data).unwrap();
+        assert_eq!((tag, body.len(), rest.len()), (2, 192, 0));
+        // Truncated
+        assert!(split_packet(&[0x88, 0x05, 0x00]).is_err());
+        assert!(split_packet(&[0x08]).is_err());
+    }
+}
@@ -3,12 +3,19 @@
 use crate::client::NntpClient;
 use crate::config::ServerConfig;
 use crate::error::{NntpError, Result};
+use crate::health::PoolHealth;
+use crate::ratelimit::ConnectionLimiter;
+use crate::runtime::ShutdownSignal;
 use bb8::{Pool, PooledConnection};
 use rand::Rng;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tracing::{debug, warn};
 
+/// Upper bound on waiting for a pooled connection
+const POOL_CONNECTION_TIMEOUT: Duration = Duration::from_secs(120);
+
 /// Configuration for connection retry behavior
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -22,6 +29,9 @@ pub struct RetryConfig {
     pub backoff_multiplier: f64,
     /// Whether to add jitter to prevent thundering herd
     pub jitter: bool,
+    /// How long the pool runs with fewer connections after the server
+    /// reports its connection limit, in milliseconds
+    pub connection_limit_cooldown_ms: u64,
 }
 
 impl Default for RetryConfig {
@@ -32,6 +42,7 @@ impl Default for RetryConfig {
             max_backoff_ms: 10000,
             backoff_multiplier: 2.0,
             jitter: true,
+            connection_limit_cooldown_ms: 60_000,
         }
     }
 }
@@ -61,6 +72,11 @@ impl RetryConfig {
 /// type directly - they work with `PooledConnection` values returned from pool methods.
 pub struct NntpConnectionManager {
     config: Arc<ServerConfig>,
+    /// Adaptive limit on open connections, lowered when the server refuses more
+    limiter: Option<ConnectionLimiter>,
+    limit_cooldown: Duration,
+    /// Set once the pool is closed; returned connections are then dropped
+    closed: Arc<AtomicBool>,
 }
 
 impl NntpConnectionManager {
@@ -68,15 +84,28 @@ impl NntpConnectionManager {
     pub fn new(config: ServerConfig) -> Self {
         Self {
             config: Arc::new(config),
+            limiter: None,
+            limit_cooldown: Duration::ZERO,
+            closed: Arc::new(AtomicBool::new(false)),
         }
     }
-}
 
-impl bb8::ManageConnection for NntpConnectionManager {
-    type Connection = NntpClient;
-    type Error = NntpError;
+    /// Create a connection manager whose connections each hold a limiter slot
+    pub(crate) fn with_limiter(
+        config: ServerConfig,
+        limiter: ConnectionLimiter,
+        limit_cooldown: Duration,
+    ) -> Self {
+        Self {
+            config: Arc::new(config),
+            limiter: Some(limiter),
+            limit_cooldown,
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
 
-    async fn connect(&self) -> Result<Self::Connection> {
+    /// Connect, authenticate and negotiate compression
+    async fn open(&self) -> Result<NntpClient> {
         let mut client = NntpClient::connect(self.config.clone()).await?;
         client.authenticate().await?;
 
@@ -94,6 +123,41 @@ impl bb8::ManageConnection for NntpConnectionManager {
 
         Ok(client)
     }
+}
+
+impl bb8::ManageConnection for NntpConnectionManager {
+    type Connection = NntpClient;
+    type Error = NntpError;
+
+    async fn connect(&self) -> Result<Self::Connection> {
+        let Some(limiter) = &self.limiter else {
+            return self.open().await;
+        };
+
+        loop {
+            let permit = limiter.acquire().await;
+            match self.open().await {
+                Ok(mut client) => {
+                    client.hold_permit(permit);
+                    return Ok(client);
+                }
+                Err(NntpError::ConnectionLimit(message)) => {
+                    // Give up once a single connection is refused
+                    drop(permit);
+                    let limit = limiter.current_limit();
+                    if limit <= 1 {
+                        return Err(NntpError::ConnectionLimit(message));
+                    }
+                    let reduced = limiter.reduce(self.limit_cooldown);
+                    warn!(
+                        "Server connection limit reached ({}), reducing connections from {} to {}",
+                        message, limit, reduced
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 
     async fn is_valid(&self, _conn: &mut Self::Connection) -> Result<()> {
         // For now, assume connection is valid
@@ -103,7 +167,7 @@ impl bb8::ManageConnection for NntpConnectionManager {
 
     fn has_broken(&self, conn: &mut Self::Connection) -> bool {
         // Check if connection received invalid/corrupted data
-        conn.is_broken()
+        conn.is_broken() || self.closed.load(Ordering::Acquire)
     }
 }
 
@@ -114,6 +178,7 @@ impl bb8::ManageConnection for NntpConnectionManager {
 /// - Compression negotiation on new connections
 /// - Exponential backoff with jitter on failures
 /// - Broken connection detection and removal
+/// - Temporary downsizing when the server reports its connection limit
 ///
 /// # Example
 ///
@@ -145,6 +210,14 @@ impl bb8::ManageConnection for NntpConnectionManager {
 pub struct NntpPool {
     pool: Pool<NntpConnectionManager>,
     retry_config: RetryConfig,
+    limiter: ConnectionLimiter,
+    /// Keepalive interval from the server configuration
+    keepalive: Option<Duration>,
+    /// Server identifier (host:port)
+    server_id: String,
+    max_size: u32,
+    /// Shared with the connection manager
+    closed: Arc<AtomicBool>,
 }
 
 /// Calculate backoff duration with optional jitter
@@ -174,24 +247,80 @@ impl NntpPool {
         config: ServerConfig,
         max_size: u32,
         retry_config: RetryConfig,
+    ) -> Result<Self> {
+        let limiter = ConnectionLimiter::new(max_size as usize);
+        Self::with_connection_limiter(config, max_size, retry_config, limiter).await
+    }
+
+    /// Create a new NNTP connection pool that opens connections through a shared limiter
+    ///
+    /// Every open connection holds a slot of `limiter`. When the server refuses a
+    /// connection because too many are open ("502 Too many connections" and
+    /// similar), the limit is lowered by one for
+    /// [`RetryConfig::connection_limit_cooldown_ms`] and the connection is retried
+    /// once a slot frees up, instead of failing repeatedly. Pass clones of one
+    /// limiter to pools for servers that share an account limit.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use nntp_rs::{ConnectionLimiter, NntpPool, RetryConfig, ServerConfig};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Two front-ends of one provider, 20 connections in total
+    /// let limiter = ConnectionLimiter::new(20);
+    /// let eu = ServerConfig::tls("eu.news.example.com", "user", "pass");
+    /// let us = ServerConfig::tls("us.news.example.com", "user", "pass");
+    /// let eu_pool =
+    ///     NntpPool::with_connection_limiter(eu, 20, RetryConfig::default(), limiter.clone())
+    ///         .await?;
+    /// let us_pool =
+    ///     NntpPool::with_connection_limiter(us, 20, RetryConfig::default(), limiter).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_connection_limiter(
+        config: ServerConfig,
+        max_size: u32,
+        retry_config: RetryConfig,
+        limiter: ConnectionLimiter,
     ) -> Result<Self> {
         debug!(
-            "Creating NNTP connection pool for {}:{} (max size: {}, max retries: {})",
-            config.host, config.port, max_size, retry_config.max_retries
+            "Creating NNTP connection pool for {} (max size: {}, max retries: {})",
+            config.authority(),
+            max_size,
+            retry_config.max_retries
         );
 
-        let manager = NntpConnectionManager::new(config);
+        // Wait up to 120 seconds for a connection (allows for slow NNTP servers),
+        // or less when the configured timeouts would fail sooner
+        let connection_timeout = POOL_CONNECTION_TIMEOUT.min(config.timeouts.connection_setup());
+        let keepalive = config.keepalive;
+        let server_id = config.authority();
+        let manager = NntpConnectionManager::with_limiter(
+            config,
+            limiter.clone(),
+            Duration::from_millis(retry_config.connection_limit_cooldown_ms),
+        );
+        let closed = manager.closed.clone();
         let pool = Pool::builder()
             .max_size(max_size)
-            // Set connection timeout to 120 seconds (allows for slow NNTP servers)
-            .connection_timeout(Duration::from_secs(120))
+            .connection_timeout(connection_timeout)
             // Set idle connection timeout to 5 minutes
             .idle_timeout(Some(Duration::from_secs(300)))
             .build(manager)
             .await
             .map_err(|e| NntpError::Other(format!("Failed to create pool: {}", e)))?;
 
-        Ok(Self { pool, retry_config })
+        Ok(Self {
+            pool,
+            retry_config,
+            limiter,
+            keepalive,
+            server_id,
+            max_size,
+            closed,
+        })
     }
 
     /// Get a connection from the pool with automatic retry on failure
@@ -204,6 +333,7 @@ impl NntpPool {
     /// Returns [`NntpError::Other`] if all retry attempts fail. The underlying
     /// error may be a connection failure, authentication failure, or pool exhaustion.
     pub async fn get(&self) -> Result<PooledConnection<'_, NntpConnectionManager>> {
+        self.check_open()?;
         let mut last_error = None;
         let mut backoff_ms = self.retry_config.initial_backoff_ms;
 
@@ -254,12 +384,77 @@ impl NntpPool {
     /// The underlying error may be a connection failure, authentication failure,
     /// or pool exhaustion.
     pub async fn get_no_retry(&self) -> Result<PooledConnection<'_, NntpConnectionManager>> {
+        self.check_open()?;
         self.pool
             .get()
             .await
             .map_err(|e| NntpError::Other(format!("Failed to get connection from pool: {}", e)))
     }
 
+    fn check_open(&self) -> Result<()> {
+        if self.is_closed() {
+            return Err(NntpError::Other("Connection pool is closed".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Close the pool
+    ///
+    /// Further [`get`](Self::get) calls fail, idle connections are sent QUIT
+    /// and dropped, and connections still checked out are dropped when they
+    /// are returned. Called by [`NntpRuntime::shutdown`](crate::NntpRuntime::shutdown)
+    /// once the pool has drained.
+    ///
+    /// # Returns
+    ///
+    /// The number of idle connections closed.
+    pub async fn close(&self) -> usize {
+        self.mark_closed();
+        let mut closed = 0;
+        // Nothing else can check out connections any more, and returned ones
+        // are dropped, so only the idle ones are handed out here
+        while let Some(checkout) = self.checkout_idle() {
+            let Ok(mut conn) = checkout.await else {
+                break;
+            };
+            if let Err(e) = conn.quit().await {
+                debug!("QUIT failed while closing pool: {}", e);
+            }
+            closed += 1;
+        }
+        debug!("Closed {} idle connection(s) to {}", closed, self.server_id);
+        closed
+    }
+
+    /// Check out a connection if one is idle, without opening one otherwise
+    ///
+    /// The pool hands out an idle connection on the first poll of the
+    /// returned future, right after the check; poll it without awaiting
+    /// anything else first.
+    fn checkout_idle(
+        &self,
+    ) -> Option<impl Future<Output = Result<PooledConnection<'_, NntpConnectionManager>>>> {
+        if self.idle_connections() == 0 {
+            return None;
+        }
+        Some(async {
+            self.pool
+                .get()
+                .await
+                .map_err(|e| NntpError::Other(format!("Failed to get connection from pool: {}", e)))
+        })
+    }
+
+    /// Refuse checkouts and drop returned connections, without closing idle ones
+    pub(crate) fn mark_closed(&self) {
+        self.closed.store(true, Ordering::Release);
+    }
+
+    /// Check whether [`close`](Self::close) was called
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
     /// Get current pool state (for monitoring)
     ///
     /// Returns pool statistics including:
@@ -280,6 +475,107 @@ impl NntpPool {
     pub fn idle_connections(&self) -> u32 {
         self.pool.state().idle_connections
     }
+
+    /// Get the server identifier (host:port) this pool connects to
+    pub fn server_id(&self) -> &str {
+        &self.server_id
+    }
+
+    /// Get a snapshot of the pool's connections and limit
+    pub fn health(&self) -> PoolHealth {
+        let state = self.pool.state();
+        PoolHealth {
+            server_id: self.server_id.clone(),
+            max_size: self.max_size,
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            in_use: state.connections - state.idle_connections,
+            connection_limit: self.limiter.current_limit(),
+        }
+    }
+
+    /// Get the limiter gating new connections
+    ///
+    /// [`ConnectionLimiter::current_limit`] shows whether the pool is currently
+    /// running below its size because the server reported a connection limit.
+    pub fn connection_limiter(&self) -> &ConnectionLimiter {
+        &self.limiter
+    }
+
+    /// Send a keepalive on every idle connection that is due for one
+    ///
+    /// Checks out each idle connection in turn and calls
+    /// [`NntpClient::keepalive`]. Connections whose keepalive fails are marked
+    /// broken and dropped by the pool. Stops when no connection is idle, so
+    /// no connection is opened just to be pinged. Does nothing unless
+    /// [`ServerConfig::keepalive`] is set or once the pool is closed.
+    ///
+    /// # Returns
+    ///
+    /// The number of connections a keepalive was sent on.
+    pub async fn keepalive(&self) -> usize {
+        if self.keepalive.is_none() || self.is_closed() {
+            return 0;
+        }
+
+        // Idle connections are handed out oldest first and returned to the back
+        let mut sent = 0;
+        for _ in 0..self.idle_connections() {
+            let Some(checkout) = self.checkout_idle() else {
+                break;
+            };
+            let Ok(mut conn) = checkout.await else {
+                break;
+            };
+            match conn.keepalive().await {
+                Ok(true) => sent += 1,
+                Ok(false) => {}
+                Err(e) => debug!("Keepalive failed, dropping connection: {}", e),
+            }
+        }
+        sent
+    }
+
+    /// Run [`keepalive`](Self::keepalive) periodically until shutdown
+    ///
+    /// Checks every half keepalive interval, so no connection stays idle much
+    /// longer than the interval. Returns immediately if
+    /// [`ServerConfig::keepalive`] is not set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use nntp_rs::{NntpPool, NntpRuntime, ServerConfig};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// let mut runtime = NntpRuntime::new();
+    /// let config = ServerConfig::tls("news.example.com", "user", "pass")
+    ///     .with_keepalive(Duration::from_secs(120));
+    /// let pool = runtime.add_pool(NntpPool::new(config, 10).await?);
+    ///
+    /// let keepalive_pool = pool.clone();
+    /// runtime.spawn("keepalive", move |shutdown| async move {
+    ///     keepalive_pool.run_keepalive(shutdown).await;
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_keepalive(&self, mut shutdown: ShutdownSignal) {
+        let Some(interval) = self.keepalive else {
+            return;
+        };
+        let period = (interval / 2).max(Duration::from_millis(1));
+        while tokio::time::timeout(period, shutdown.cancelled())
+            .await
+            .is_err()
+        {
+            let sent = self.keepalive().await;
+            if sent > 0 {
+                debug!("Sent keepalive on {} idle connection(s)", sent);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -288,14 +584,7 @@ mod tests {
 
     #[test]
     fn test_connection_manager_creation() {
-        let config = ServerConfig {
-            host: "news.example.com".to_string(),
-            port: 563,
-            tls: true,
-            allow_insecure_tls: false,
-            username: "testuser".to_string(),
-            password: "testpass".to_string(),
-        };
+        let config = ServerConfig::tls("news.example.com", "testuser", "testpass");
 
         let manager = NntpConnectionManager::new(config);
         assert_eq!(manager.config.host, "news.example.com");
@@ -310,6 +599,7 @@ mod tests {
         assert_eq!(config.max_backoff_ms, 10000);
         assert_eq!(config.backoff_multiplier, 2.0);
         assert!(config.jitter);
+        assert_eq!(config.connection_limit_cooldown_ms, 60_000);
     }
 
     #[test]
@@ -0,0 +1,342 @@
+//! Python bindings (PyO3)
+//!
+//! Exposes NZB parsing, yEnc decoding, PAR2 verification and a simple
+//! blocking download client as the `nntp_rs` Python extension module.
+//! Build with maturin (see `pyproject.toml`):
+//!
+//! ```text
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import nntp_rs
+//!
+//! nzb = nntp_rs.parse_nzb(open("file.nzb").read())
+//! client = nntp_rs.Client("news.example.com", 563, "user", "pass")
+//! client.authenticate()
+//! for segment in nzb.files[0].segments:
+//!     part = client.download_segment(segment.message_id)
+//!     print(part.name, part.begin, len(part.data), part.crc_ok)
+//! client.quit()
+//! ```
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::client::NntpClient;
+use crate::config::ServerConfig;
+use crate::error::NntpError;
+use crate::nzb;
+use crate::par2::{FileStatus, Par2File};
+use crate::yenc;
+
+mod exceptions {
+    pyo3::create_exception!(nntp_rs, NntpError, pyo3::exceptions::PyException);
+}
+
+fn to_py_err(error: NntpError) -> PyErr {
+    exceptions::NntpError::new_err(error.to_string())
+}
+
+/// One segment (article) of an NZB file
+#[pyclass(
+    name = "NzbSegment",
+    module = "nntp_rs",
+    get_all,
+    frozen,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PyNzbSegment {
+    number: u32,
+    bytes: u64,
+    message_id: String,
+}
+
+/// One file of an NZB document
+#[pyclass(
+    name = "NzbFile",
+    module = "nntp_rs",
+    get_all,
+    frozen,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PyNzbFile {
+    subject: String,
+    poster: String,
+    date: i64,
+    groups: Vec<String>,
+    segments: Vec<PyNzbSegment>,
+    total_bytes: u64,
+}
+
+/// Parsed NZB document
+#[pyclass(name = "Nzb", module = "nntp_rs", get_all, frozen)]
+struct PyNzb {
+    meta: std::collections::HashMap<String, String>,
+    files: Vec<PyNzbFile>,
+    total_bytes: u64,
+}
+
+impl From<nzb::Nzb> for PyNzb {
+    fn from(nzb: nzb::Nzb) -> Self {
+        let total_bytes = nzb.total_bytes();
+        let files = nzb
+            .files
+            .into_iter()
+            .map(|file| PyNzbFile {
+                total_bytes: file.total_bytes(),
+                subject: file.subject,
+                poster: file.poster,
+                date: file.date,
+                groups: file.groups,
+                segments: file
+                    .segments
+                    .into_iter()
+                    .map(|segment| PyNzbSegment {
+                        number: segment.number,
+                        bytes: segment.bytes,
+                        message_id: segment.message_id,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            meta: nzb.meta,
+            files,
+            total_bytes,
+        }
+    }
+}
+
+/// Decoded yEnc part
+#[pyclass(name = "YencPart", module = "nntp_rs", frozen)]
+struct PyYencPart {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    size: u64,
+    #[pyo3(get)]
+    part: Option<u32>,
+    #[pyo3(get)]
+    total: Option<u32>,
+    /// 1-based offset of the part in the file (1 for single-part files)
+    #[pyo3(get)]
+    begin: u64,
+    /// `True`/`False` if the trailer CRC matched, `None` if there was none
+    #[pyo3(get)]
+    crc_ok: Option<bool>,
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl PyYencPart {
+    /// Decoded bytes
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.data)
+    }
+}
+
+impl From<yenc::YencDecoded> for PyYencPart {
+    fn from(decoded: yenc::YencDecoded) -> Self {
+        Self {
+            crc_ok: decoded.verify_crc32(),
+            begin: decoded.part.as_ref().map_or(1, |part| part.begin),
+            name: decoded.header.name,
+            size: decoded.header.size,
+            part: decoded.header.part,
+            total: decoded.header.total,
+            data: decoded.data,
+        }
+    }
+}
+
+/// PAR2 verification result for one file
+#[pyclass(name = "Par2FileResult", module = "nntp_rs", get_all, frozen)]
+struct PyPar2FileResult {
+    filename: String,
+    expected_size: u64,
+    /// "complete", "damaged" or "missing"
+    status: &'static str,
+    damaged_slices: Vec<usize>,
+}
+
+/// Parse an NZB document
+#[pyfunction]
+fn parse_nzb(xml: &str) -> PyResult<PyNzb> {
+    nzb::parse_nzb(xml).map(PyNzb::from).map_err(to_py_err)
+}
+
+/// Decode a yEnc-encoded article body
+#[pyfunction]
+fn yenc_decode(py: Python<'_>, data: &[u8]) -> PyResult<PyYencPart> {
+    py.detach(|| yenc::decode(data))
+        .map(PyYencPart::from)
+        .map_err(to_py_err)
+}
+
+/// Verify the files described by a PAR2 file against a directory
+///
+/// `par2_data` is the content of any PAR2 file of the set (typically the
+/// index file). Files are looked up by name in `directory`.
+#[pyfunction]
+fn par2_verify(
+    py: Python<'_>,
+    par2_data: &[u8],
+    directory: PathBuf,
+) -> PyResult<Vec<PyPar2FileResult>> {
+    py.detach(|| verify_directory(par2_data, &directory))
+}
+
+fn verify_directory(par2_data: &[u8], directory: &Path) -> PyResult<Vec<PyPar2FileResult>> {
+    let par2 = Par2File::parse(par2_data).map_err(to_py_err)?;
+    let mut results = Vec::with_capacity(par2.file_descriptions.len());
+
+    for (file_id, description) in &par2.file_descriptions {
+        let name = description.name.to_string();
+        // File names come from the PAR2 data; never follow them out of the directory
+        if name.contains(['/', '\\']) || name == ".." {
+            return Err(PyValueError::new_err(format!(
+                "Unsafe file name in PAR2: {}",
+                name
+            )));
+        }
+
+        let (status, damaged_slices) = match std::fs::read(directory.join(&name)) {
+            Ok(data) => match par2.verify_file(&data, file_id).map_err(to_py_err)?.status {
+                FileStatus::Complete => ("complete", Vec::new()),
+                FileStatus::Damaged(slices) => ("damaged", slices),
+                FileStatus::Missing => ("missing", Vec::new()),
+            },
+            Err(_) => ("missing", Vec::new()),
+        };
+        results.push(PyPar2FileResult {
+            filename: name,
+            expected_size: description.length,
+            status,
+            damaged_slices,
+        });
+    }
+
+    results.sort_by(|a, b| a.filename.cmp(&b.filename));
+    Ok(results)
+}
+
+/// Blocking NNTP client for scripts
+///
+/// Each call runs to completion on a private Tokio runtime with the GIL
+/// released, so several clients can download in parallel from Python threads.
+#[pyclass(name = "Client", module = "nntp_rs")]
+struct PyClient {
+    runtime: tokio::runtime::Runtime,
+    client: NntpClient,
+}
+
+/// Boxed client operation borrowed for the duration of one blocking call
+type ClientFuture<'c, T> = Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'c>>;
+
+impl PyClient {
+    /// Run an async client operation to completion with the GIL released
+    fn run<T, F>(&mut self, py: Python<'_>, operation: F) -> PyResult<T>
+    where
+        T: Send,
+        F: for<'c> FnOnce(&'c mut NntpClient) -> ClientFuture<'c, T> + Send,
+    {
+        let Self { runtime, client } = self;
+        py.detach(|| runtime.block_on(operation(client)))
+            .map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl PyClient {
+    /// Connect to a server (port 563 uses implicit TLS by default)
+    #[new]
+    #[pyo3(signature = (host, port = 563, username = String::new(), password = String::new(), tls = true))]
+    fn new(
+        py: Python<'_>,
+        host: String,
+        port: u16,
+        username: String,
+        password: String,
+        tls: bool,
+    ) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| to_py_err(NntpError::Io(e)))?;
+        let config = Arc::new(ServerConfig::new(host, port, tls, username, password));
+        let client = py
+            .detach(|| runtime.block_on(NntpClient::connect(config)))
+            .map_err(to_py_err)?;
+        Ok(Self { runtime, client })
+    }
+
+    /// Authenticate with the configured username and password
+    fn authenticate(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |client| Box::pin(client.authenticate()))
+    }
+
+    /// Select a newsgroup, returning `(count, first, last)`
+    fn select_group(&mut self, py: Python<'_>, group: String) -> PyResult<(u64, u64, u64)> {
+        let info = self.run(py, move |client| {
+            Box::pin(async move { client.select_group(&group).await })
+        })?;
+        Ok((info.count, info.first, info.last))
+    }
+
+    /// Fetch a whole article (headers and body) as raw bytes
+    fn fetch_article<'py>(&mut self, py: Python<'py>, id: String) -> PyResult<Bound<'py, PyBytes>> {
+        let response = self.run(py, move |client| {
+            Box::pin(async move { client.fetch_article_binary(&id).await })
+        })?;
+        Ok(PyBytes::new(py, &response.data))
+    }
+
+    /// Fetch an article body as raw bytes
+    fn fetch_body<'py>(&mut self, py: Python<'py>, id: String) -> PyResult<Bound<'py, PyBytes>> {
+        let response = self.run(py, move |client| {
+            Box::pin(async move { client.fetch_body_binary(&id).await })
+        })?;
+        Ok(PyBytes::new(py, &response.data))
+    }
+
+    /// Fetch a segment by message-id and yEnc-decode it
+    fn download_segment(&mut self, py: Python<'_>, message_id: String) -> PyResult<PyYencPart> {
+        let response = self.run(py, move |client| {
+            Box::pin(async move { client.fetch_body_binary(&message_id).await })
+        })?;
+        py.detach(|| yenc::decode(&response.data))
+            .map(PyYencPart::from)
+            .map_err(to_py_err)
+    }
+
+    /// Close the session (QUIT)
+    fn quit(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, |client| Box::pin(client.quit()))
+    }
+}
+
+/// Python module definition
+#[pymodule]
+fn nntp_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("NntpError", m.py().get_type::<exceptions::NntpError>())?;
+    m.add_function(wrap_pyfunction!(parse_nzb, m)?)?;
+    m.add_function(wrap_pyfunction!(yenc_decode, m)?)?;
+    m.add_function(wrap_pyfunction!(par2_verify, m)?)?;
+    m.add_class::<PyNzb>()?;
+    m.add_class::<PyNzbFile>()?;
+    m.add_class::<PyNzbSegment>()?;
+    m.add_class::<PyYencPart>()?;
+    m.add_class::<PyPar2FileResult>()?;
+    m.add_class::<PyClient>()?;
+    Ok(())
+}
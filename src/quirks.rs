@@ -0,0 +1,143 @@
+//! Per-provider workarounds for non-conforming server behavior
+//!
+//! Real-world NNTP servers deviate from the RFCs in small but important ways.
+//! [`ServerQuirks`] collects the known workarounds in one place. The client
+//! fingerprints the server greeting to pick a profile automatically, or uses
+//! the profile given in [`ServerConfig::quirks`](crate::ServerConfig::quirks).
+
+/// Known server workarounds applied automatically by the client and fetcher
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ServerConfig, ServerQuirks};
+///
+/// let quirks = ServerQuirks {
+///     not_found_is_transient: true,
+///     ..ServerQuirks::default()
+/// };
+/// let config = ServerConfig::tls("news.example.com", "user", "pass").with_quirks(quirks);
+/// assert!(config.quirks.is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ServerQuirks {
+    /// OVER with a message-id argument is unsupported or unreliable
+    ///
+    /// The client emulates it with HEAD and builds the overview entry locally.
+    pub no_over_by_msgid: bool,
+
+    /// MODE READER must be sent before AUTHINFO
+    ///
+    /// Typical for transit servers (e.g. INN's innd) that hand reader
+    /// connections off to a separate process, which then handles authentication.
+    pub mode_reader_before_auth: bool,
+
+    /// XFEATURE COMPRESS GZIP responses are compressed without a status-line marker
+    ///
+    /// Overview and header responses are treated as always compressed.
+    pub gzip_marker_absent: bool,
+
+    /// 430 (no such article) may be temporary, e.g. while an article propagates
+    ///
+    /// The segment fetcher retries 430 responses like other transient errors
+    /// before reporting the segment as not found.
+    pub not_found_is_transient: bool,
+
+    /// XFEATURE COMPRESS GZIP is acknowledged with a code outside 2xx
+    ///
+    /// Any reply below 400 is taken to mean headers-only compression is on.
+    pub xfeature_nonstandard_ack: bool,
+
+    /// The server drops authentication mid-session and answers 480
+    ///
+    /// Retried commands (article retrieval, STAT, GROUP) that get 480 on an
+    /// authenticated connection re-authenticate and are sent once more.
+    pub reauth_on_480: bool,
+
+    /// The CAPABILITIES list does not match what the server supports
+    ///
+    /// STARTTLS (for [`TlsPolicy`](crate::TlsPolicy)) and COMPRESS DEFLATE
+    /// are tried even when the list leaves them out.
+    pub unreliable_capabilities: bool,
+
+    /// XOVER/OVER columns are not in the RFC 3977 order
+    ///
+    /// The client reads LIST OVERVIEW.FMT once per connection and moves each
+    /// overview line's columns into the standard order before parsing it.
+    pub overview_column_drift: bool,
+}
+
+impl ServerQuirks {
+    /// Pick a quirk profile from the server greeting
+    ///
+    /// Recognized fingerprints:
+    /// - Giganews: compressed responses carry no XFEATURE marker
+    /// - INN transit server (innd): MODE READER before authentication
+    ///
+    /// Any other server gets the default (no quirks).
+    pub fn from_greeting(greeting: &str) -> Self {
+        let lower = greeting.to_ascii_lowercase();
+        let mut quirks = Self::default();
+
+        if lower.contains("giganews") {
+            quirks.gzip_marker_absent = true;
+        }
+        // innd says "InterNetNews server INN ..."; nnrpd says "InterNetNews NNRP server ..."
+        if lower.contains("internetnews server") {
+            quirks.mode_reader_before_auth = true;
+        }
+
+        quirks
+    }
+
+    /// Check whether any workaround is enabled
+    pub fn any(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_has_no_quirks() {
+        let quirks = ServerQuirks::default();
+        assert!(!quirks.any());
+        assert!(!quirks.no_over_by_msgid);
+        assert!(!quirks.mode_reader_before_auth);
+        assert!(!quirks.gzip_marker_absent);
+        assert!(!quirks.not_found_is_transient);
+        assert!(!quirks.xfeature_nonstandard_ack);
+        assert!(!quirks.reauth_on_480);
+        assert!(!quirks.unreliable_capabilities);
+        assert!(!quirks.overview_column_drift);
+    }
+
+    #[test]
+    fn test_from_greeting_giganews() {
+        let quirks = ServerQuirks::from_greeting("200 News.GigaNews.Com");
+        assert!(quirks.gzip_marker_absent);
+        assert!(!quirks.mode_reader_before_auth);
+        assert!(quirks.any());
+    }
+
+    #[test]
+    fn test_from_greeting_inn() {
+        let innd =
+            ServerQuirks::from_greeting("200 news.example.com InterNetNews server INN 2.7.1 ready");
+        assert!(innd.mode_reader_before_auth);
+
+        let nnrpd = ServerQuirks::from_greeting(
+            "200 news.example.com InterNetNews NNRP server INN 2.7.1 ready",
+        );
+        assert!(!nnrpd.mode_reader_before_auth);
+    }
+
+    #[test]
+    fn test_from_greeting_unknown() {
+        assert!(!ServerQuirks::from_greeting("200 news.example.com ready").any());
+    }
+}
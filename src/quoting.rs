@@ -0,0 +1,216 @@
+//! Signatures and quoting in article bodies
+//!
+//! By Usenet convention a signature follows a line holding exactly "-- "
+//! (dash, dash, space; RFC 3676 Section 4.3 calls it the sig dash). Readers
+//! hide or dim it, and replies quote the body without it, prefixing each
+//! line with "> ".
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::quoting::{QuoteOptions, quote, split_signature};
+//!
+//! let body = "Try the new release.\n\n> Does it build?\n-- \nAlice\n";
+//! let (text, signature) = split_signature(body);
+//! assert_eq!(text, "Try the new release.\n\n> Does it build?\n");
+//! assert_eq!(signature, Some("Alice\n"));
+//!
+//! let reply = quote(body, &QuoteOptions::default());
+//! assert_eq!(reply, "> Try the new release.\n>\n>> Does it build?\n");
+//! ```
+
+/// Line that separates a signature from the body
+pub const SIGNATURE_DELIMITER: &str = "-- ";
+
+/// How [`quote`] prefixes and wraps lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteOptions {
+    /// Character marking a quoted line (`>` by default)
+    pub quote_char: char,
+    /// Width to rewrap long lines at, quote marks included; `None` keeps
+    /// lines as they are
+    pub width: Option<usize>,
+    /// Leave out the signature of the quoted body
+    pub strip_signature: bool,
+}
+
+/// Default wrap width for quoted text
+pub const DEFAULT_QUOTE_WIDTH: usize = 72;
+
+impl Default for QuoteOptions {
+    fn default() -> Self {
+        Self {
+            quote_char: '>',
+            width: Some(DEFAULT_QUOTE_WIDTH),
+            strip_signature: true,
+        }
+    }
+}
+
+/// Split a body at its signature delimiter
+///
+/// Returns the text before the last line that is exactly "-- " and the
+/// signature after it, or the whole body and `None` if there is no
+/// delimiter. The delimiter line itself is in neither part. Both LF and
+/// CRLF line endings are recognized.
+pub fn split_signature(body: &str) -> (&str, Option<&str>) {
+    let mut delimiter = None;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let content = line.trim_end_matches('\n').trim_end_matches('\r');
+        if content == SIGNATURE_DELIMITER {
+            delimiter = Some((offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+    match delimiter {
+        Some((start, end)) => (&body[..start], Some(&body[end..])),
+        None => (body, None),
+    }
+}
+
+/// Body without its signature and signature delimiter
+pub fn strip_signature(body: &str) -> &str {
+    split_signature(body).0
+}
+
+/// Signature of a body, if it has one
+pub fn signature(body: &str) -> Option<&str> {
+    split_signature(body).1
+}
+
+/// Quote a body for a reply
+///
+/// Each line gets the quote character and a space; lines that are already
+/// quoted get just the quote character, so "> text" becomes ">> text", and
+/// empty lines get the bare character. With a `width`, longer lines are
+/// wrapped at spaces and every piece carries the line's quote marks. Lines
+/// end with "\n".
+pub fn quote(body: &str, options: &QuoteOptions) -> String {
+    let body = if options.strip_signature {
+        strip_signature(body)
+    } else {
+        body
+    };
+    let mut out = String::with_capacity(body.len() + body.len() / 8);
+
+    for line in body.lines() {
+        let line = line.trim_end();
+        // Quote marks, with any spaces between them; indentation of an
+        // unquoted line is text
+        let marks_len = if line.starts_with(options.quote_char) {
+            line.len() - line.trim_start_matches([options.quote_char, ' ']).len()
+        } else {
+            0
+        };
+        let (marks, text) = line.split_at(marks_len);
+
+        let mut prefix = String::with_capacity(marks.len() + 2);
+        prefix.push(options.quote_char);
+        prefix.push_str(marks.trim_end());
+        if text.is_empty() {
+            out.push_str(&prefix);
+            out.push('\n');
+            continue;
+        }
+        prefix.push(' ');
+
+        let limit = options.width.map_or(usize::MAX, |width| {
+            width.saturating_sub(prefix.chars().count())
+        });
+        for piece in wrap_words(text, limit.max(1)) {
+            out.push_str(&prefix);
+            out.push_str(piece);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Break `text` at spaces into pieces of at most `limit` characters
+///
+/// A word longer than `limit` is kept whole on its own piece.
+fn wrap_words(text: &str, limit: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > limit {
+        let mut split = None;
+        for (count, (index, ch)) in rest.char_indices().enumerate() {
+            // Leading indentation is not a place to break
+            if ch != ' ' || rest[..index].trim().is_empty() {
+                continue;
+            }
+            if count > limit && split.is_some() {
+                break;
+            }
+            split = Some(index);
+            if count > limit {
+                break;
+            }
+        }
+        let Some(split) = split else {
+            break;
+        };
+        pieces.push(rest[..split].trim_end());
+        rest = rest[split..].trim_start();
+    }
+    pieces.push(rest);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_signature_uses_last_delimiter() {
+        let body = "text\r\n-- \r\nnot a sig\r\n-- \r\nsig\r\n";
+        assert_eq!(
+            split_signature(body),
+            ("text\r\n-- \r\nnot a sig\r\n", Some("sig\r\n"))
+        );
+        assert_eq!(signature("no sig\n--\nmissing space\n"), None);
+        assert_eq!(strip_signature("-- "), "");
+    }
+
+    #[test]
+    fn test_quote_nests_existing_quotes() {
+        let body = "reply\n> quoted\n>> older\n> > spaced\n\n    indented";
+        assert_eq!(
+            quote(body, &QuoteOptions::default()),
+            "> reply\n>> quoted\n>>> older\n>> > spaced\n>\n>     indented\n"
+        );
+    }
+
+    #[test]
+    fn test_quote_wraps_with_prefix() {
+        let options = QuoteOptions {
+            width: Some(20),
+            ..QuoteOptions::default()
+        };
+        let quoted = quote("one two three four five six seven eight", &options);
+        assert_eq!(quoted, "> one two three four\n> five six seven\n> eight\n");
+        assert!(quoted.lines().all(|line| line.chars().count() <= 20));
+
+        let quoted = quote("> https://example.com/a/very/long/url tail", &options);
+        assert_eq!(quoted, ">> https://example.com/a/very/long/url\n>> tail\n");
+
+        let quoted = quote("    code_that_is_long(argument)", &options);
+        assert_eq!(quoted, ">     code_that_is_long(argument)\n");
+    }
+
+    #[test]
+    fn test_quote_options() {
+        let options = QuoteOptions {
+            quote_char: '|',
+            width: None,
+            strip_signature: false,
+        };
+        let body = format!("{}\n| old\n-- \nsig", "long ".repeat(30).trim_end());
+        let quoted = quote(&body, &options);
+        assert_eq!(
+            quoted,
+            format!("| {}\n|| old\n| --\n| sig\n", "long ".repeat(30).trim_end())
+        );
+    }
+}
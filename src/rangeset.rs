@@ -0,0 +1,410 @@
+//! Sets of article numbers stored as coalesced ranges
+//!
+//! Article numbers come in long runs: the articles already read in a group,
+//! the overview entries already cached, the segments of a resumed download.
+//! [`RangeSet`] stores them as sorted, non-overlapping inclusive ranges and
+//! uses the familiar newsrc notation (`1-500,502,510-600`) for text and
+//! serialization.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::error::{NntpError, Result};
+
+/// A set of article numbers stored as coalesced inclusive ranges
+///
+/// Adjacent and overlapping ranges are merged on insert, so the set always
+/// holds the minimal number of ranges.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::RangeSet;
+///
+/// let mut read: RangeSet = "1-100,105".parse().unwrap();
+/// read.insert_range(101..=103);
+/// assert_eq!(read.to_string(), "1-103,105");
+///
+/// // Articles still to fetch between the group watermarks
+/// assert_eq!(read.gaps(1..=110), vec![104..=104, 106..=110]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "String", try_from = "String")
+)]
+pub struct RangeSet {
+    /// Range start -> inclusive range end
+    ranges: BTreeMap<u64, u64>,
+}
+
+impl RangeSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single article number
+    ///
+    /// Returns `true` if the number was not already in the set.
+    pub fn insert(&mut self, number: u64) -> bool {
+        self.insert_range(number..=number)
+    }
+
+    /// Add an inclusive range of article numbers
+    ///
+    /// Returns `true` if at least one number was not already in the set.
+    /// Empty ranges (start > end) are ignored.
+    pub fn insert_range(&mut self, range: RangeInclusive<u64>) -> bool {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return false;
+        }
+
+        // Merge with a preceding range that overlaps or touches
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..=start).next_back()
+            && prev_end.saturating_add(1) >= start
+        {
+            if prev_end >= end {
+                return false;
+            }
+            start = prev_start;
+        }
+
+        // Absorb following ranges that overlap or touch
+        let absorbed: Vec<u64> = self
+            .ranges
+            .range(start..=end.saturating_add(1))
+            .map(|(&next_start, _)| next_start)
+            .collect();
+        for next_start in absorbed {
+            if let Some(next_end) = self.ranges.remove(&next_start) {
+                end = end.max(next_end);
+            }
+        }
+
+        self.ranges.insert(start, end);
+        true
+    }
+
+    /// Remove a single article number
+    ///
+    /// Returns `true` if the number was in the set.
+    pub fn remove(&mut self, number: u64) -> bool {
+        self.remove_range(number..=number)
+    }
+
+    /// Remove an inclusive range of article numbers
+    ///
+    /// Returns `true` if at least one number was removed.
+    pub fn remove_range(&mut self, range: RangeInclusive<u64>) -> bool {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return false;
+        }
+
+        let overlapping: Vec<(u64, u64)> = self
+            .overlapping(start, end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for &(range_start, range_end) in &overlapping {
+            self.ranges.remove(&range_start);
+            if range_start < start {
+                self.ranges.insert(range_start, start - 1);
+            }
+            if range_end > end {
+                self.ranges.insert(end + 1, range_end);
+            }
+        }
+
+        !overlapping.is_empty()
+    }
+
+    /// Check whether an article number is in the set
+    pub fn contains(&self, number: u64) -> bool {
+        self.ranges
+            .range(..=number)
+            .next_back()
+            .is_some_and(|(_, &end)| end >= number)
+    }
+
+    /// Merge every number of `other` into this set
+    pub fn union_with(&mut self, other: &RangeSet) {
+        for range in other.ranges() {
+            self.insert_range(range);
+        }
+    }
+
+    /// Return a new set containing the numbers of both sets
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    /// Return the ranges within `bounds` that are *not* in the set
+    ///
+    /// Typically called with a group's low/high watermarks to find the
+    /// articles that still need fetching.
+    pub fn gaps(&self, bounds: RangeInclusive<u64>) -> Vec<RangeInclusive<u64>> {
+        let (low, high) = bounds.into_inner();
+        let mut gaps = Vec::new();
+        if low > high {
+            return gaps;
+        }
+
+        let mut next = low;
+        for (&start, &end) in self.overlapping(low, high) {
+            if start > next {
+                gaps.push(next..=start - 1);
+            }
+            if end >= high {
+                return gaps;
+            }
+            next = end + 1;
+        }
+        gaps.push(next..=high);
+        gaps
+    }
+
+    /// Iterate over the stored ranges in ascending order
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..=end)
+    }
+
+    /// Iterate over every article number in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ranges().flatten()
+    }
+
+    /// Number of stored ranges
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Number of article numbers in the set (saturates at `u64::MAX`)
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().fold(0u64, |total, (&start, &end)| {
+            total.saturating_add((end - start).saturating_add(1))
+        })
+    }
+
+    /// Check whether the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Lowest article number in the set
+    pub fn first(&self) -> Option<u64> {
+        self.ranges.keys().next().copied()
+    }
+
+    /// Highest article number in the set
+    pub fn last(&self) -> Option<u64> {
+        self.ranges.values().next_back().copied()
+    }
+
+    /// Remove every number from the set
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Stored ranges that intersect `start..=end`
+    fn overlapping(&self, start: u64, end: u64) -> impl Iterator<Item = (&u64, &u64)> + '_ {
+        // A range starting before `start` may still reach into the window
+        let first = self
+            .ranges
+            .range(..start)
+            .next_back()
+            .filter(|(_, range_end)| **range_end >= start);
+        first.into_iter().chain(self.ranges.range(start..=end))
+    }
+}
+
+impl FromIterator<u64> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u64> for RangeSet {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        for number in iter {
+            self.insert(number);
+        }
+    }
+}
+
+impl fmt::Display for RangeSet {
+    /// Format in newsrc notation, e.g. `1-500,502,510-600`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (&start, &end)) in self.ranges.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            if start == end {
+                write!(f, "{}", start)?;
+            } else {
+                write!(f, "{}-{}", start, end)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RangeSet {
+    type Err = NntpError;
+
+    /// Parse newsrc notation, e.g. `1-500,502,510-600`
+    ///
+    /// Whitespace around entries is ignored and entries may overlap or be
+    /// out of order. An empty string is the empty set.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut set = RangeSet::new();
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let invalid = || NntpError::InvalidResponse(format!("Invalid range: {}", entry));
+            let (start, end) = match entry.split_once('-') {
+                Some((start, end)) => (start.trim(), end.trim()),
+                None => (entry, entry),
+            };
+            let start: u64 = start.parse().map_err(|_| invalid())?;
+            let end: u64 = end.parse().map_err(|_| invalid())?;
+            if start > end {
+                return Err(invalid());
+            }
+            set.insert_range(start..=end);
+        }
+        Ok(set)
+    }
+}
+
+impl From<RangeSet> for String {
+    fn from(set: RangeSet) -> Self {
+        set.to_string()
+    }
+}
+
+impl TryFrom<String> for RangeSet {
+    type Error = NntpError;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(s: &str) -> RangeSet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_insert_coalesces() {
+        let mut set = RangeSet::new();
+        assert!(set.insert(5));
+        assert!(set.insert(7));
+        assert_eq!(set.range_count(), 2);
+        assert!(set.insert(6));
+        assert_eq!(set.range_count(), 1);
+        assert!(!set.insert(6));
+        assert_eq!(set.to_string(), "5-7");
+
+        assert!(set.insert_range(1..=4));
+        assert!(set.insert_range(8..=10));
+        assert_eq!(set.to_string(), "1-10");
+        assert!(!set.insert_range(2..=9));
+        assert_eq!(set.len(), 10);
+    }
+
+    #[test]
+    fn test_insert_range_spanning_several() {
+        let mut set = set("1-2,5,8-9,20");
+        assert!(set.insert_range(3..=15));
+        assert_eq!(set.to_string(), "1-15,20");
+        let (high, low) = (10, 1);
+        assert!(!set.insert_range(high..=low));
+    }
+
+    #[test]
+    fn test_remove_splits_ranges() {
+        let mut set = set("1-10");
+        assert!(set.remove(5));
+        assert!(!set.remove(5));
+        assert_eq!(set.to_string(), "1-4,6-10");
+        assert!(set.remove_range(3..=7));
+        assert_eq!(set.to_string(), "1-2,8-10");
+        assert!(set.remove_range(0..=100));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_contains_first_last() {
+        let set = set("3-5,10");
+        assert!(!set.contains(2));
+        assert!(set.contains(3));
+        assert!(set.contains(5));
+        assert!(!set.contains(6));
+        assert!(set.contains(10));
+        assert_eq!(set.first(), Some(3));
+        assert_eq!(set.last(), Some(10));
+        assert_eq!(RangeSet::new().first(), None);
+    }
+
+    #[test]
+    fn test_union() {
+        let a = set("1-5,20-30");
+        let b = set("4-10,31,40");
+        assert_eq!(a.union(&b).to_string(), "1-10,20-31,40");
+    }
+
+    #[test]
+    fn test_gaps() {
+        let set = set("3-5,8,12-20");
+        assert_eq!(set.gaps(1..=15), vec![1..=2, 6..=7, 9..=11]);
+        assert_eq!(set.gaps(4..=5), Vec::<RangeInclusive<u64>>::new());
+        assert_eq!(set.gaps(18..=25), vec![21..=25]);
+        assert_eq!(RangeSet::new().gaps(1..=3), vec![1..=3]);
+        let (high, low) = (5, 1);
+        assert!(set.gaps(high..=low).is_empty());
+    }
+
+    #[test]
+    fn test_extremes_do_not_overflow() {
+        let mut set = RangeSet::new();
+        set.insert_range(u64::MAX - 1..=u64::MAX);
+        set.insert(0);
+        assert_eq!(set.gaps(0..=u64::MAX), vec![1..=u64::MAX - 2]);
+        set.insert_range(0..=u64::MAX);
+        assert_eq!(set.len(), u64::MAX);
+        assert!(set.remove(u64::MAX));
+        assert_eq!(set.last(), Some(u64::MAX - 1));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(set(" 10-12 , 1, 2,3 ").to_string(), "1-3,10-12");
+        assert!(set("").is_empty());
+        assert!("5-1".parse::<RangeSet>().is_err());
+        assert!("a-b".parse::<RangeSet>().is_err());
+        assert!("1-2-3".parse::<RangeSet>().is_err());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let set: RangeSet = [9, 1, 2, 3, 7].into_iter().collect();
+        assert_eq!(set.to_string(), "1-3,7,9");
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 2, 3, 7, 9]);
+        assert_eq!(set.ranges().count(), 3);
+    }
+}
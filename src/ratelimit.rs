@@ -3,8 +3,8 @@
 //! This module provides rate limiting capabilities using a token bucket algorithm
 //! for bandwidth throttling and connection limiting.
 
-use std::sync::Arc;
-use tokio::sync::{Mutex, Semaphore};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{Mutex, Notify, Semaphore};
 use tokio::time::{Duration, Instant};
 
 /// Token bucket rate limiter for bandwidth throttling
@@ -15,6 +15,8 @@ use tokio::time::{Duration, Instant};
 #[derive(Debug, Clone)]
 pub struct BandwidthLimiter {
     inner: Arc<Mutex<BandwidthLimiterInner>>,
+    /// Weights of the active shares handed out by [`share`](Self::share)
+    shares: Arc<ShareWeights>,
 }
 
 #[derive(Debug)]
@@ -61,6 +63,12 @@ impl BandwidthLimiter {
                 capacity,
                 last_update: Instant::now(),
             })),
+            shares: Arc::new(ShareWeights {
+                rate: bytes_per_second,
+                capacity,
+                total: StdMutex::new(0),
+                changed: Notify::new(),
+            }),
         }
     }
 
@@ -133,18 +141,275 @@ impl BandwidthLimiter {
 
         inner.tokens as u64
     }
+
+    /// Create a weighted share of this limiter for one job
+    ///
+    /// Active shares split the rate in proportion to their weights: with
+    /// shares of weight 1 and 3, the first gets a quarter of the bandwidth and
+    /// the second three quarters. Paused and dropped shares do not count, so
+    /// the remaining shares speed up at once. Bytes acquired through a share
+    /// also count against this limiter as a whole.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// use nntp_rs::BandwidthLimiter;
+    ///
+    /// let limiter = BandwidthLimiter::new(10_000_000, None);
+    /// let urgent = limiter.share(3);
+    /// let background = limiter.share(1);
+    ///
+    /// // While both are active: 7.5 MB/s and 2.5 MB/s
+    /// urgent.acquire(65536).await;
+    ///
+    /// // Pausing hands the whole rate to the other share
+    /// background.pause();
+    /// assert_eq!(urgent.current_rate(), 10_000_000);
+    /// # }
+    /// ```
+    pub fn share(&self, weight: u32) -> BandwidthShare {
+        assert!(weight > 0, "share weight must be greater than 0");
+        self.shares.adjust(0, u64::from(weight));
+        BandwidthShare {
+            inner: Arc::new(ShareInner {
+                limiter: self.clone(),
+                state: StdMutex::new(ShareState {
+                    weight: u64::from(weight),
+                    paused: false,
+                    tokens: 0.0,
+                    last_update: Instant::now(),
+                }),
+            }),
+        }
+    }
+}
+
+/// Total weight of the active shares of one limiter
+#[derive(Debug)]
+struct ShareWeights {
+    /// Rate of the limiter (bytes per second)
+    rate: u64,
+    /// Burst size of the limiter
+    capacity: f64,
+    /// Sum of the weights of unpaused shares
+    total: StdMutex<u64>,
+    /// Woken whenever the total changes or a share resumes
+    changed: Notify,
+}
+
+impl ShareWeights {
+    fn total(&self) -> u64 {
+        *self.total.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace an active weight of `old` with `new` (0 meaning inactive)
+    fn adjust(&self, old: u64, new: u64) {
+        {
+            let mut total = self.total.lock().unwrap_or_else(|e| e.into_inner());
+            *total = (*total + new).saturating_sub(old);
+        }
+        self.changed.notify_waiters();
+    }
+}
+
+/// One job's weighted share of a [`BandwidthLimiter`]
+///
+/// Created by [`BandwidthLimiter::share`]. Clones refer to the same share,
+/// so all connections of a job can acquire through it. The share's weight
+/// is released when the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct BandwidthShare {
+    inner: Arc<ShareInner>,
+}
+
+#[derive(Debug)]
+struct ShareInner {
+    limiter: BandwidthLimiter,
+    state: StdMutex<ShareState>,
+}
+
+#[derive(Debug)]
+struct ShareState {
+    weight: u64,
+    paused: bool,
+    /// Tokens of this share's own bucket (negative while in debt)
+    tokens: f64,
+    last_update: Instant,
+}
+
+impl ShareState {
+    /// Active weight (0 while paused)
+    fn active_weight(&self) -> u64 {
+        if self.paused { 0 } else { self.weight }
+    }
+}
+
+impl BandwidthShare {
+    /// Wait until `bytes` can be transferred within this share
+    ///
+    /// Waits while the share is paused. The share's rate is recomputed
+    /// whenever shares are added, removed, paused or reweighted.
+    pub async fn acquire(&self, bytes: u64) {
+        let weights = &self.inner.limiter.shares;
+        loop {
+            // Register for wakeups before looking at the state
+            let mut changed = std::pin::pin!(weights.changed.notified());
+            changed.as_mut().enable();
+
+            match self.try_take(bytes) {
+                Ok(()) => break,
+                Err(Some(wait)) => {
+                    let _ = tokio::time::timeout(wait, changed).await;
+                }
+                Err(None) => changed.await,
+            }
+        }
+
+        // The parent bucket never holds more than its burst size
+        let burst = self.inner.limiter.config().await.1.max(1);
+        let mut remaining = bytes;
+        while remaining > 0 {
+            let amount = remaining.min(burst);
+            self.inner.limiter.acquire(amount).await;
+            remaining -= amount;
+        }
+    }
+
+    /// Take `bytes` from this share's bucket, or tell how long to wait
+    ///
+    /// `Err(None)` means the share is paused.
+    fn try_take(&self, bytes: u64) -> Result<(), Option<Duration>> {
+        let weights = &self.inner.limiter.shares;
+        let mut state = self.lock_state();
+        if state.paused {
+            return Err(None);
+        }
+
+        let fraction = state.weight as f64 / weights.total().max(state.weight) as f64;
+        let rate = weights.rate as f64 * fraction;
+        let capacity = weights.capacity * fraction;
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_update).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rate).min(capacity);
+        state.last_update = now;
+
+        // Requests larger than the share's burst go into debt instead of waiting forever
+        let needed = (bytes as f64).min(capacity);
+        if state.tokens >= needed {
+            state.tokens -= bytes as f64;
+            return Ok(());
+        }
+        Err(Some(Duration::from_secs_f64(
+            (needed - state.tokens) / rate,
+        )))
+    }
+
+    /// Get this share's weight
+    pub fn weight(&self) -> u32 {
+        self.lock_state().weight as u32
+    }
+
+    /// Change this share's weight
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is 0.
+    pub fn set_weight(&self, weight: u32) {
+        assert!(weight > 0, "share weight must be greater than 0");
+        let mut state = self.lock_state();
+        let old = state.active_weight();
+        state.weight = u64::from(weight);
+        self.inner.limiter.shares.adjust(old, state.active_weight());
+    }
+
+    /// Stop this share; its bandwidth goes to the other shares at once
+    ///
+    /// [`acquire`](Self::acquire) waits until the share is resumed.
+    pub fn pause(&self) {
+        let mut state = self.lock_state();
+        if !state.paused {
+            state.paused = true;
+            self.inner.limiter.shares.adjust(state.weight, 0);
+        }
+    }
+
+    /// Resume a paused share
+    pub fn resume(&self) {
+        let mut state = self.lock_state();
+        if state.paused {
+            state.paused = false;
+            // No burst credit for the time spent paused
+            state.tokens = state.tokens.min(0.0);
+            state.last_update = Instant::now();
+            self.inner.limiter.shares.adjust(0, state.weight);
+        }
+    }
+
+    /// Check whether this share is paused
+    pub fn is_paused(&self) -> bool {
+        self.lock_state().paused
+    }
+
+    /// Get the rate this share is currently entitled to (bytes per second)
+    ///
+    /// 0 while paused.
+    pub fn current_rate(&self) -> u64 {
+        let weights = &self.inner.limiter.shares;
+        let state = self.lock_state();
+        match state.active_weight() {
+            0 => 0,
+            weight => {
+                let total = weights.total().max(weight);
+                (u128::from(weights.rate) * u128::from(weight) / u128::from(total)) as u64
+            }
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ShareState> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for ShareInner {
+    fn drop(&mut self) {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.limiter.shares.adjust(state.active_weight(), 0);
+    }
 }
 
 /// Connection limiter using semaphores
 ///
 /// Limits the number of concurrent connections to prevent overwhelming
 /// servers or exhausting local resources.
+///
+/// The limit can be lowered temporarily with [`reduce`](Self::reduce) when a
+/// provider refuses connections (e.g. "502 Too many connections"); it returns
+/// to [`max_connections`](Self::max_connections) once the cooldown expires.
+/// Clones share the same limit, so one limiter can cover several pools that
+/// count against the same provider account.
 #[derive(Debug, Clone)]
 pub struct ConnectionLimiter {
     /// Semaphore for limiting connections
     semaphore: Arc<Semaphore>,
     /// Maximum number of connections
     max_connections: usize,
+    /// Temporary reduction below `max_connections`
+    reduction: Arc<StdMutex<LimitReduction>>,
+}
+
+#[derive(Debug, Default)]
+struct LimitReduction {
+    /// Number of slots currently withheld
+    withheld: usize,
+    /// Withheld slots still held by outstanding permits (forgotten on release)
+    debt: usize,
+    /// When the withheld slots are returned
+    restore_at: Option<Instant>,
 }
 
 impl ConnectionLimiter {
@@ -166,6 +431,7 @@ impl ConnectionLimiter {
         Self {
             semaphore: Arc::new(Semaphore::new(max_connections)),
             max_connections,
+            reduction: Arc::new(StdMutex::new(LimitReduction::default())),
         }
     }
 
@@ -190,24 +456,70 @@ impl ConnectionLimiter {
     // Semaphore is never closed while ConnectionLimiter holds Arc reference
     #[expect(clippy::expect_used)]
     pub async fn acquire(&self) -> ConnectionPermit {
-        let permit = self
-            .semaphore
-            .clone()
-            .acquire_owned()
-            .await
-            .expect("BUG: semaphore closed while ConnectionLimiter holds Arc reference");
-        ConnectionPermit { _permit: permit }
+        loop {
+            // Wake up when a reduction expires so the restored slots can be used
+            let acquire = self.semaphore.clone().acquire_owned();
+            let permit = match self.restore_expired() {
+                Some(restore_at) => match tokio::time::timeout_at(restore_at, acquire).await {
+                    Ok(permit) => permit,
+                    Err(_) => continue,
+                },
+                None => acquire.await,
+            };
+            let permit =
+                permit.expect("BUG: semaphore closed while ConnectionLimiter holds Arc reference");
+            return self.permit(permit);
+        }
     }
 
     /// Try to acquire a connection permit without blocking
     ///
     /// Returns `Some(permit)` if a slot is available, `None` otherwise.
     pub fn try_acquire(&self) -> Option<ConnectionPermit> {
+        self.restore_expired();
         self.semaphore
             .clone()
             .try_acquire_owned()
             .ok()
-            .map(|permit| ConnectionPermit { _permit: permit })
+            .map(|permit| self.permit(permit))
+    }
+
+    /// Temporarily lower the limit by one connection
+    ///
+    /// Call this when the server refuses a connection because too many are
+    /// open. The limit never drops below one, and every call pushes the
+    /// restore time out to `cooldown` from now. Slots held by live
+    /// connections are withdrawn as those connections are released.
+    ///
+    /// Returns the new limit.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use nntp_rs::ConnectionLimiter;
+    /// use std::time::Duration;
+    ///
+    /// let limiter = ConnectionLimiter::new(20);
+    /// // Provider answered "502 Too many connections"
+    /// assert_eq!(limiter.reduce(Duration::from_secs(60)), 19);
+    /// ```
+    pub fn reduce(&self, cooldown: Duration) -> usize {
+        self.restore_expired();
+        let mut reduction = self.lock_reduction();
+        if reduction.withheld + 1 < self.max_connections {
+            reduction.withheld += 1;
+            if self.semaphore.forget_permits(1) == 0 {
+                reduction.debt += 1;
+            }
+        }
+        reduction.restore_at = Some(Instant::now() + cooldown);
+        self.max_connections - reduction.withheld
+    }
+
+    /// Lift any temporary reduction immediately
+    pub fn restore(&self) {
+        let mut reduction = self.lock_reduction();
+        self.restore_locked(&mut reduction);
     }
 
     /// Get the maximum number of connections
@@ -215,10 +527,60 @@ impl ConnectionLimiter {
         self.max_connections
     }
 
+    /// Get the current limit (below the maximum while reduced)
+    pub fn current_limit(&self) -> usize {
+        self.restore_expired();
+        self.max_connections - self.lock_reduction().withheld
+    }
+
     /// Get the number of available connection slots
     pub fn available(&self) -> usize {
+        self.restore_expired();
         self.semaphore.available_permits()
     }
+
+    /// Get the number of permits currently held
+    ///
+    /// Slots withheld by [`reduce`](Self::reduce) are not counted.
+    pub fn in_use(&self) -> usize {
+        self.restore_expired();
+        let reduction = self.lock_reduction();
+        // Permits handed out by the semaphore, including those still owed
+        let issued = self.max_connections - reduction.withheld + reduction.debt;
+        issued.saturating_sub(self.semaphore.available_permits())
+    }
+
+    fn permit(&self, permit: tokio::sync::OwnedSemaphorePermit) -> ConnectionPermit {
+        ConnectionPermit {
+            permit: Some(permit),
+            reduction: self.reduction.clone(),
+        }
+    }
+
+    fn lock_reduction(&self) -> std::sync::MutexGuard<'_, LimitReduction> {
+        self.reduction.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lift an expired reduction; returns the pending restore time otherwise
+    fn restore_expired(&self) -> Option<Instant> {
+        let mut reduction = self.lock_reduction();
+        match reduction.restore_at {
+            Some(restore_at) if restore_at <= Instant::now() => {
+                self.restore_locked(&mut reduction);
+                None
+            }
+            pending => pending,
+        }
+    }
+
+    fn restore_locked(&self, reduction: &mut LimitReduction) {
+        // Slots still owed by live permits simply stop being owed
+        let returned = reduction.withheld - reduction.debt;
+        if returned > 0 {
+            self.semaphore.add_permits(returned);
+        }
+        *reduction = LimitReduction::default();
+    }
 }
 
 /// RAII guard for connection permits
@@ -226,7 +588,21 @@ impl ConnectionLimiter {
 /// Automatically releases the permit when dropped.
 #[derive(Debug)]
 pub struct ConnectionPermit {
-    _permit: tokio::sync::OwnedSemaphorePermit,
+    permit: Option<tokio::sync::OwnedSemaphorePermit>,
+    reduction: Arc<StdMutex<LimitReduction>>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let mut reduction = self.reduction.lock().unwrap_or_else(|e| e.into_inner());
+        if reduction.debt > 0 {
+            // The limit was reduced while this slot was in use: withdraw it
+            reduction.debt -= 1;
+            if let Some(permit) = self.permit.take() {
+                permit.forget();
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -295,6 +671,90 @@ mod tests {
         assert_eq!(capacity, 2000);
     }
 
+    /// Acquire `chunk`-sized pieces through `share` until `until`, returning the bytes
+    async fn drain(share: BandwidthShare, chunk: u64, until: Instant) -> u64 {
+        let mut total = 0;
+        while Instant::now() < until {
+            share.acquire(chunk).await;
+            total += chunk;
+        }
+        total
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bandwidth_shares_split_by_weight() {
+        let limiter = BandwidthLimiter::new(40_000, Some(1_000));
+        let light = limiter.share(1);
+        let heavy = limiter.share(3);
+        assert_eq!(light.current_rate(), 10_000);
+        assert_eq!(heavy.current_rate(), 30_000);
+
+        let until = Instant::now() + Duration::from_secs(10);
+        let light = tokio::spawn(drain(light, 100, until));
+        let heavy = tokio::spawn(drain(heavy, 100, until));
+        let (light, heavy) = (light.await.unwrap(), heavy.await.unwrap());
+
+        let ratio = heavy as f64 / light as f64;
+        assert!((2.7..=3.3).contains(&ratio), "ratio {}", ratio);
+        assert!(light + heavy <= 40_000 * 10 + 2_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bandwidth_share_pause_frees_rate() {
+        let limiter = BandwidthLimiter::new(10_000, Some(1_000));
+        let a = limiter.share(1);
+        let b = limiter.share(1);
+        assert_eq!(b.current_rate(), 5_000);
+
+        a.pause();
+        assert!(a.is_paused());
+        assert_eq!(a.current_rate(), 0);
+        assert_eq!(b.current_rate(), 10_000);
+
+        // A paused share waits until resumed
+        let waiting = tokio::spawn({
+            let a = a.clone();
+            async move { a.acquire(10).await }
+        });
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(!waiting.is_finished());
+        a.resume();
+        waiting.await.unwrap();
+        assert_eq!(b.current_rate(), 5_000);
+
+        drop(a);
+        assert_eq!(b.current_rate(), 10_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bandwidth_share_set_weight() {
+        let limiter = BandwidthLimiter::new(9_000, None);
+        let a = limiter.share(1);
+        let b = limiter.share(2);
+        a.set_weight(7);
+        assert_eq!(a.weight(), 7);
+        assert_eq!(a.current_rate(), 7_000);
+        assert_eq!(b.current_rate(), 2_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bandwidth_share_acquires_more_than_the_burst() {
+        let limiter = BandwidthLimiter::new(1_000, Some(500));
+        let share = limiter.share(1);
+        let start = Instant::now();
+        tokio::time::timeout(Duration::from_secs(60), share.acquire(2_000))
+            .await
+            .unwrap();
+        // The first 500 bytes come from the full bucket, the rest at 1000 B/s
+        assert!(start.elapsed() >= Duration::from_millis(1_500));
+    }
+
+    #[test]
+    #[should_panic(expected = "share weight must be greater than 0")]
+    fn test_bandwidth_share_zero_weight_panics() {
+        BandwidthLimiter::new(1000, None).share(0);
+    }
+
     #[tokio::test]
     async fn test_connection_limiter_basic() {
         let limiter = ConnectionLimiter::new(2);
@@ -332,6 +792,76 @@ mod tests {
         let _limiter = BandwidthLimiter::new(0, None);
     }
 
+    #[tokio::test]
+    async fn test_connection_limiter_reduce_free_slots() {
+        let limiter = ConnectionLimiter::new(3);
+
+        assert_eq!(limiter.reduce(Duration::from_secs(60)), 2);
+        assert_eq!(limiter.current_limit(), 2);
+        assert_eq!(limiter.available(), 2);
+        assert_eq!(limiter.in_use(), 0);
+
+        let _permit1 = limiter.acquire().await;
+        let _permit2 = limiter.acquire().await;
+        assert!(limiter.try_acquire().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connection_limiter_reduce_never_below_one() {
+        let limiter = ConnectionLimiter::new(2);
+        assert_eq!(limiter.reduce(Duration::from_secs(60)), 1);
+        assert_eq!(limiter.reduce(Duration::from_secs(60)), 1);
+        assert_eq!(limiter.available(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connection_limiter_reduce_withdraws_busy_slots() {
+        let limiter = ConnectionLimiter::new(2);
+        let permit1 = limiter.acquire().await;
+        let permit2 = limiter.acquire().await;
+
+        // Both slots are in use, so the reduction applies on release
+        assert_eq!(limiter.reduce(Duration::from_secs(60)), 1);
+        assert_eq!(limiter.in_use(), 2);
+        drop(permit1);
+        assert_eq!(limiter.available(), 0);
+        assert_eq!(limiter.in_use(), 1);
+        drop(permit2);
+        assert_eq!(limiter.available(), 1);
+        assert_eq!(limiter.in_use(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_connection_limiter_restore() {
+        let limiter = ConnectionLimiter::new(3);
+        let permit = limiter.acquire().await;
+        limiter.reduce(Duration::from_secs(60));
+        limiter.reduce(Duration::from_secs(60));
+        assert_eq!(limiter.available(), 0);
+
+        limiter.restore();
+        assert_eq!(limiter.current_limit(), 3);
+        assert_eq!(limiter.available(), 2);
+        drop(permit);
+        assert_eq!(limiter.available(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_connection_limiter_restores_after_cooldown() {
+        let limiter = ConnectionLimiter::new(2);
+        limiter.reduce(Duration::from_secs(30));
+        let _held = limiter.acquire().await;
+
+        // A waiter wakes up once the cooldown returns the withheld slot
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire().await })
+        };
+        sleep(Duration::from_secs(31)).await;
+        let _second = waiter.await.unwrap();
+        assert_eq!(limiter.current_limit(), 2);
+    }
+
     #[tokio::test]
     async fn test_connection_limiter_concurrent() {
         let limiter = ConnectionLimiter::new(3);
@@ -0,0 +1,345 @@
+//! Detection of renumbered newsgroups
+//!
+//! Article numbers are only meaningful for as long as the server keeps them.
+//! When a server rebuilds a group its numbering can start over, and cached
+//! overview entries and read ranges then point at the wrong articles.
+//! [`GroupWatermarks`] remembers the last GROUP response per group and flags
+//! responses that cannot follow from normal posting and expiry.
+//!
+//! [`NntpClient`](crate::NntpClient) keeps a `GroupWatermarks` for every
+//! connection and reports renumberings to
+//! [`ConnectionObserver::on_group_renumbered`](crate::ConnectionObserver::on_group_renumbered).
+//! Persist it with [`NntpClient::group_watermarks`](crate::NntpClient::group_watermarks)
+//! to catch renumberings that happen between sessions.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+use crate::cache::HeaderCache;
+use crate::commands::GroupInfo;
+use crate::rangeset::RangeSet;
+
+/// A count smaller than the previous one by this factor, without expiry, is a collapse
+const COLLAPSE_FACTOR: u64 = 10;
+
+/// Why a GROUP response was taken as a renumbering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenumberReason {
+    /// The high watermark went down, which RFC 3977 forbids for a stable group
+    HighWatermarkDecreased,
+    /// The low watermark moved past the previous high watermark, so none of
+    /// the previously seen numbers exist any more
+    LowWatermarkPastHigh,
+    /// The count dropped by more than 90% while the low watermark did not
+    /// advance, so articles vanished without being expired
+    CountCollapsed,
+}
+
+/// Compare two GROUP responses for the same group
+///
+/// Returns `None` for changes explained by new posts and expiry, and when
+/// either response is for an empty group: nothing can be cached for an empty
+/// group, and servers disagree on the watermarks they report for one.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::commands::GroupInfo;
+/// use nntp_rs::renumber::{RenumberReason, detect_renumbering};
+///
+/// let before = GroupInfo { count: 500, first: 1000, last: 1499 };
+/// let grown = GroupInfo { count: 600, first: 1100, last: 1699 };
+/// let reset = GroupInfo { count: 500, first: 1, last: 500 };
+/// assert_eq!(detect_renumbering(&before, &grown), None);
+/// assert_eq!(
+///     detect_renumbering(&before, &reset),
+///     Some(RenumberReason::HighWatermarkDecreased)
+/// );
+/// ```
+pub fn detect_renumbering(previous: &GroupInfo, current: &GroupInfo) -> Option<RenumberReason> {
+    detect(previous, current, true)
+}
+
+/// [`detect_renumbering`], skipping the count check if either count is estimated
+fn detect(previous: &GroupInfo, current: &GroupInfo, counts: bool) -> Option<RenumberReason> {
+    if previous.count == 0 || current.count == 0 {
+        return None;
+    }
+    if current.last < previous.last {
+        return Some(RenumberReason::HighWatermarkDecreased);
+    }
+    if current.first > previous.last {
+        return Some(RenumberReason::LowWatermarkPastHigh);
+    }
+    if counts
+        && current.first <= previous.first
+        && current.count.saturating_mul(COLLAPSE_FACTOR) < previous.count
+    {
+        return Some(RenumberReason::CountCollapsed);
+    }
+    None
+}
+
+/// A detected renumbering of one group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupRenumbering {
+    /// Newsgroup name
+    pub group: String,
+    /// Watermarks recorded before
+    pub previous: GroupInfo,
+    /// Watermarks just received
+    pub current: GroupInfo,
+    /// Which check fired
+    pub reason: RenumberReason,
+}
+
+impl GroupRenumbering {
+    /// Article numbers whose cached data can no longer be trusted
+    ///
+    /// This is everything up to the old high watermark, including numbers
+    /// below the old low watermark cached before it moved up; numbers above
+    /// it were never seen and cannot be cached.
+    pub fn stale_range(&self) -> RangeInclusive<u64> {
+        0..=self.previous.last
+    }
+
+    /// Drop the stale overview entries from a header cache of this group
+    ///
+    /// Returns the number of entries removed.
+    pub fn invalidate_cache<C: HeaderCache + ?Sized>(&self, cache: &mut C) -> usize {
+        cache.remove_range(self.stale_range())
+    }
+
+    /// Drop the stale numbers from read ranges of this group
+    ///
+    /// Works on anything kept as a [`RangeSet`], such as newsrc read marks or
+    /// [`BackfillState::completed`](crate::BackfillState::completed).
+    /// Returns `true` if any number was removed.
+    pub fn invalidate_ranges(&self, ranges: &mut RangeSet) -> bool {
+        ranges.remove_range(self.stale_range())
+    }
+}
+
+/// Last known watermarks per group
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::commands::GroupInfo;
+/// use nntp_rs::renumber::GroupWatermarks;
+///
+/// let mut watermarks = GroupWatermarks::new();
+/// let first = GroupInfo { count: 3, first: 10, last: 12 };
+/// assert!(watermarks.observe("alt.test", first).is_none());
+///
+/// let reset = GroupInfo { count: 3, first: 1, last: 3 };
+/// let renumbering = watermarks.observe("alt.test", reset).unwrap();
+/// assert_eq!(renumbering.stale_range(), 0..=12);
+/// assert_eq!(watermarks.get("alt.test"), Some(&reset));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupWatermarks {
+    groups: HashMap<String, GroupInfo>,
+    /// Groups whose count was estimated from a listing
+    #[cfg_attr(feature = "serde", serde(default))]
+    estimated: HashSet<String>,
+}
+
+impl GroupWatermarks {
+    /// Create an empty set of watermarks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a GROUP response, returning the renumbering it reveals, if any
+    ///
+    /// The new watermarks replace the old ones either way, so a renumbering
+    /// is reported once. Responses for an empty group are not recorded, so
+    /// the next non-empty one is compared with the last known articles.
+    pub fn observe(&mut self, group: &str, info: GroupInfo) -> Option<GroupRenumbering> {
+        if info.count == 0 {
+            return None;
+        }
+        let estimated = self.estimated.remove(group);
+        self.record(group, info, !estimated)
+    }
+
+    /// Record watermarks from a listing without article counts, such as
+    /// LIST ACTIVE, returning the renumbering they reveal, if any
+    ///
+    /// The count is estimated as `last - first + 1`. That is only an upper
+    /// bound, so it is never compared with another count: the check for a
+    /// collapsed count is skipped for this group until the next GROUP
+    /// response. An empty range (`last < first`) is not recorded.
+    pub fn observe_listing(
+        &mut self,
+        group: &str,
+        first: u64,
+        last: u64,
+    ) -> Option<GroupRenumbering> {
+        let count = last.checked_sub(first)? + 1;
+        self.estimated.insert(group.to_string());
+        self.record(group, GroupInfo { count, first, last }, false)
+    }
+
+    /// Replace the watermarks of `group` and compare them with the old ones
+    fn record(&mut self, group: &str, info: GroupInfo, counts: bool) -> Option<GroupRenumbering> {
+        let previous = self.groups.insert(group.to_string(), info)?;
+        let reason = detect(&previous, &info, counts)?;
+        Some(GroupRenumbering {
+            group: group.to_string(),
+            previous,
+            current: info,
+            reason,
+        })
+    }
+
+    /// Check whether the recorded count of `group` is an estimate
+    ///
+    /// True once recorded by [`observe_listing`](Self::observe_listing),
+    /// until the next GROUP response.
+    pub fn is_estimated(&self, group: &str) -> bool {
+        self.estimated.contains(group)
+    }
+
+    /// Last recorded watermarks of `group`
+    pub fn get(&self, group: &str) -> Option<&GroupInfo> {
+        self.groups.get(group)
+    }
+
+    /// Every group with recorded watermarks, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &GroupInfo)> {
+        self.groups
+            .iter()
+            .map(|(group, info)| (group.as_str(), info))
+    }
+
+    /// Forget `group`, e.g. after its cached data was dropped
+    pub fn forget(&mut self, group: &str) -> Option<GroupInfo> {
+        self.estimated.remove(group);
+        self.groups.remove(group)
+    }
+
+    /// Number of groups with recorded watermarks
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Check if no group has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XoverEntry;
+    use crate::cache::LruHeaderCache;
+
+    fn info(count: u64, first: u64, last: u64) -> GroupInfo {
+        GroupInfo { count, first, last }
+    }
+
+    #[test]
+    fn test_normal_changes_are_not_renumbering() {
+        let before = info(100, 1000, 1099);
+        // New posts
+        assert_eq!(detect_renumbering(&before, &info(150, 1000, 1149)), None);
+        // Expiry
+        assert_eq!(detect_renumbering(&before, &info(20, 1080, 1099)), None);
+        // Everything expired, reported either way RFC 3977 allows
+        assert_eq!(detect_renumbering(&before, &info(0, 1100, 1099)), None);
+        assert_eq!(detect_renumbering(&before, &info(0, 0, 0)), None);
+        // Unchanged
+        assert_eq!(detect_renumbering(&before, &before), None);
+        // Nothing was known
+        assert_eq!(detect_renumbering(&info(0, 0, 0), &info(5, 1, 5)), None);
+    }
+
+    #[test]
+    fn test_detects_each_reason() {
+        let before = info(100, 1000, 1099);
+        assert_eq!(
+            detect_renumbering(&before, &info(100, 1, 100)),
+            Some(RenumberReason::HighWatermarkDecreased)
+        );
+        assert_eq!(
+            detect_renumbering(&before, &info(100, 5000, 5099)),
+            Some(RenumberReason::LowWatermarkPastHigh)
+        );
+        assert_eq!(
+            detect_renumbering(&before, &info(5, 1000, 1099)),
+            Some(RenumberReason::CountCollapsed)
+        );
+    }
+
+    #[test]
+    fn test_estimated_counts_are_not_compared() {
+        let mut watermarks = GroupWatermarks::new();
+        assert!(watermarks.observe_listing("alt.empty", 5, 4).is_none());
+        assert!(watermarks.get("alt.empty").is_none());
+
+        assert!(watermarks.observe_listing("alt.test", 1000, 1999).is_none());
+        assert_eq!(watermarks.get("alt.test"), Some(&info(1000, 1000, 1999)));
+        assert!(watermarks.is_estimated("alt.test"));
+        // Sparse group: the real count is far below the estimate
+        assert!(
+            watermarks
+                .observe("alt.test", info(50, 1000, 1999))
+                .is_none()
+        );
+        assert!(!watermarks.is_estimated("alt.test"));
+        assert_eq!(
+            watermarks
+                .observe("alt.test", info(2, 1000, 1999))
+                .unwrap()
+                .reason,
+            RenumberReason::CountCollapsed
+        );
+
+        // Watermarks are still compared
+        let renumbering = watermarks.observe_listing("alt.test", 1, 10).unwrap();
+        assert_eq!(renumbering.reason, RenumberReason::HighWatermarkDecreased);
+        watermarks.forget("alt.test");
+        assert!(!watermarks.is_estimated("alt.test"));
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let mut watermarks = GroupWatermarks::new();
+        watermarks.observe("alt.test", info(3, 10, 12));
+        let renumbering = watermarks.observe("alt.test", info(2, 1, 2)).unwrap();
+        assert_eq!(renumbering.reason, RenumberReason::HighWatermarkDecreased);
+        assert!(watermarks.observe("alt.test", info(2, 1, 2)).is_none());
+        assert!(watermarks.observe("alt.test", info(0, 0, 0)).is_none());
+        assert_eq!(watermarks.get("alt.test"), Some(&info(2, 1, 2)));
+
+        let mut cache = LruHeaderCache::new(10);
+        for n in [1, 11, 12, 20] {
+            cache.put(
+                n,
+                XoverEntry {
+                    article_number: n,
+                    subject: String::new(),
+                    author: String::new(),
+                    date: String::new(),
+                    timestamp: None,
+                    message_id: format!("<{}@example.com>", n),
+                    references: String::new(),
+                    bytes: 0,
+                    lines: 0,
+                    source_id: None,
+                },
+            );
+        }
+        assert_eq!(renumbering.invalidate_cache(&mut cache), 3);
+        assert!(!cache.contains(&1) && cache.contains(&20));
+
+        let mut read = RangeSet::new();
+        read.insert_range(1..=15);
+        assert!(renumbering.invalidate_ranges(&mut read));
+        assert_eq!(read.to_string(), "13-15");
+    }
+}
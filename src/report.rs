@@ -0,0 +1,487 @@
+//! End-to-end integrity reports for downloads
+//!
+//! Once a download has finished (repair included), an [`IntegrityReport`]
+//! records what was produced and how: every file with its final hashes and
+//! PAR2 verdict, which server supplied each segment after how many
+//! attempts, the repairs applied, and the transfer speed. With the `serde`
+//! feature it serializes (e.g. to JSON with `serde_json`), for audit trails
+//! and for bug reports against providers.
+//!
+//! Provenance is not part of a [`DownloadJob`], so it is collected while the
+//! job runs by an [`IntegrityRecorder`], which serializes as well and can be
+//! journaled next to the job.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::jobs::DownloadJob;
+//! use nntp_rs::report::IntegrityRecorder;
+//! use nntp_rs::{SegmentStatus, parse_nzb};
+//! use std::time::SystemTime;
+//!
+//! let nzb = parse_nzb(r#"<?xml version="1.0"?>
+//! <nzb xmlns="http://www.newzbin.com/DTD/2003/nzb">
+//!   <file poster="p" date="0" subject="&quot;a.bin&quot; yEnc (1/1)">
+//!     <groups><group>alt.test</group></groups>
+//!     <segments>
+//!       <segment bytes="100" number="1">part1@example.com</segment>
+//!     </segments>
+//!   </file>
+//! </nzb>"#).unwrap();
+//! let mut job = DownloadJob::new("job-1", nzb, "primary", "/tmp/downloads");
+//! let mut recorder = IntegrityRecorder::new();
+//!
+//! job.set_segment(0, 0, SegmentStatus::Completed);
+//! recorder.record_segment(0, 0, "news.example.com:563", 2, 100);
+//!
+//! let report = recorder.report(&job, None, SystemTime::now());
+//! assert_eq!(report.files[0].name.as_deref(), Some("a.bin"));
+//! assert_eq!(report.servers[0].retries, 1);
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::completeness::subject_filename;
+use crate::hash::{FileDigest, StreamingHasher};
+use crate::jobs::{DownloadJob, JobStatus};
+use crate::par2::Par2File;
+use crate::segments::SegmentStatus;
+
+/// Where one segment came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegmentSource {
+    /// Index of the segment within its file
+    pub segment: usize,
+    /// Server that supplied the segment (`host:port`)
+    pub server: String,
+    /// Requests made for the segment, including the one that succeeded
+    pub attempts: usize,
+    /// Bytes received
+    pub bytes: u64,
+}
+
+/// A repair applied to the downloaded files
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepairRecord {
+    /// Name of the repaired file
+    pub file: String,
+    /// PAR2 recovery blocks used
+    pub blocks_used: u64,
+    /// Whether the file verified after the repair
+    pub succeeded: bool,
+}
+
+/// Integrity of one downloaded file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileReport {
+    /// File name quoted in the subject, if any
+    pub name: Option<String>,
+    /// Subject of the post
+    pub subject: String,
+    /// Size announced by the NZB (encoded bytes)
+    pub nzb_bytes: u64,
+    /// Number of segments
+    pub segments: usize,
+    /// Segments downloaded
+    pub segments_completed: usize,
+    /// Final length in bytes, if the file was hashed
+    pub length: Option<u64>,
+    /// Final CRC32 as 8 hex digits, if the file was hashed
+    pub crc32: Option<String>,
+    /// Final MD5 as 32 hex digits, if the file was hashed
+    pub md5: Option<String>,
+    /// Whether the hashes match the PAR2 description (`None` without one)
+    pub par2_verified: Option<bool>,
+    /// Whether a repair was applied to the file
+    pub repaired: bool,
+    /// Where each downloaded segment came from, by segment index
+    pub sources: Vec<SegmentSource>,
+}
+
+/// Segments, bytes and retries of one server
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerReport {
+    /// Server (`host:port`)
+    pub server: String,
+    /// Segments supplied
+    pub segments: usize,
+    /// Bytes supplied, including segments that were fetched again later
+    pub bytes: u64,
+    /// Requests beyond the first for the segments it supplied
+    pub retries: usize,
+}
+
+/// Machine-readable summary of a finished download
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegrityReport {
+    /// Id of the job
+    pub job_id: String,
+    /// Final status of the job
+    pub status: JobStatus,
+    /// Reason the job failed, if it did
+    pub error: Option<String>,
+    /// Start of the download, in seconds since the Unix epoch
+    pub started_at: u64,
+    /// End of the download, in seconds since the Unix epoch
+    pub finished_at: u64,
+    /// Bytes received from all servers
+    pub bytes_downloaded: u64,
+    /// Average speed over the whole download, in bytes per second
+    pub average_speed: f64,
+    /// Requests beyond the first, over all segments
+    pub retries: usize,
+    /// Every file of the NZB, in NZB order
+    pub files: Vec<FileReport>,
+    /// Every server that supplied segments, by server name
+    pub servers: Vec<ServerReport>,
+    /// Repairs applied, in the order they were recorded
+    pub repairs: Vec<RepairRecord>,
+}
+
+/// Collects what an [`IntegrityReport`] needs while a download runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegrityRecorder {
+    started_at: SystemTime,
+    /// Sources by file index, then segment index
+    sources: BTreeMap<usize, BTreeMap<usize, SegmentSource>>,
+    /// Earlier sources of segments that were fetched again
+    #[cfg_attr(feature = "serde", serde(default))]
+    replaced: Vec<SegmentSource>,
+    /// Final hashes set after a repair, by file index
+    digests: BTreeMap<usize, FileDigest>,
+    repairs: Vec<RepairRecord>,
+}
+
+impl Default for IntegrityRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntegrityRecorder {
+    /// Start recording a download starting now
+    pub fn new() -> Self {
+        Self::started_at(SystemTime::now())
+    }
+
+    /// Start recording a download that started at `started_at`
+    pub fn started_at(started_at: SystemTime) -> Self {
+        Self {
+            started_at,
+            sources: BTreeMap::new(),
+            replaced: Vec::new(),
+            digests: BTreeMap::new(),
+            repairs: Vec::new(),
+        }
+    }
+
+    /// Record that `server` supplied segment `segment` of file `file`
+    ///
+    /// A later record for the same segment (e.g. a refetch after a CRC
+    /// error) becomes its source, with the attempts added up. The bytes of
+    /// the earlier one still count towards its server and the total.
+    pub fn record_segment(
+        &mut self,
+        file: usize,
+        segment: usize,
+        server: impl Into<String>,
+        attempts: usize,
+        bytes: u64,
+    ) {
+        let mut source = SegmentSource {
+            segment,
+            server: server.into(),
+            attempts,
+            bytes,
+        };
+        let segments = self.sources.entry(file).or_default();
+        if let Some(earlier) = segments.remove(&segment) {
+            source.attempts += earlier.attempts;
+            self.replaced.push(earlier);
+        }
+        segments.insert(segment, source);
+    }
+
+    /// Record a repair
+    pub fn record_repair(&mut self, repair: RepairRecord) {
+        self.repairs.push(repair);
+    }
+
+    /// Set the final hashes of file `file`, e.g. after a repair changed it
+    ///
+    /// Files without one are reported with the hash state kept in the job.
+    pub fn set_digest(&mut self, file: usize, digest: FileDigest) {
+        self.digests.insert(file, digest);
+    }
+
+    /// Build the report for `job`, finished at `finished_at`
+    ///
+    /// With `par2`, each file's hashes are checked against the PAR2
+    /// description of the same name.
+    pub fn report(
+        &self,
+        job: &DownloadJob,
+        par2: Option<&Par2File>,
+        finished_at: SystemTime,
+    ) -> IntegrityReport {
+        let files: Vec<FileReport> = job
+            .nzb
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let name = subject_filename(&file.subject).map(str::to_string);
+                let digest = self.digests.get(&index).copied().or_else(|| {
+                    let snapshot = job.hash_state(index)?;
+                    StreamingHasher::resume(snapshot)
+                        .ok()
+                        .map(StreamingHasher::finalize)
+                });
+                FileReport {
+                    par2_verified: digest
+                        .zip(par2)
+                        .and_then(|(digest, par2)| par2_verdict(par2, name.as_deref()?, &digest)),
+                    repaired: name
+                        .as_ref()
+                        .is_some_and(|name| self.repairs.iter().any(|r| &r.file == name)),
+                    subject: file.subject.clone(),
+                    nzb_bytes: file.total_bytes(),
+                    segments: file.segments.len(),
+                    segments_completed: (0..file.segments.len())
+                        .filter(|&segment| {
+                            job.segment_status(index, segment) == Some(&SegmentStatus::Completed)
+                        })
+                        .count(),
+                    length: digest.map(|d| d.len),
+                    crc32: digest.map(|d| format!("{:08x}", d.crc32)),
+                    md5: digest.map(|d| hex(&d.md5)),
+                    sources: self
+                        .sources
+                        .get(&index)
+                        .map(|segments| segments.values().cloned().collect())
+                        .unwrap_or_default(),
+                    name,
+                }
+            })
+            .collect();
+
+        let servers = self.server_reports();
+        let bytes_downloaded = servers.iter().map(|s| s.bytes).sum();
+        let elapsed = finished_at
+            .duration_since(self.started_at)
+            .unwrap_or(Duration::ZERO);
+        IntegrityReport {
+            job_id: job.id.clone(),
+            status: job.status,
+            error: job.error.clone(),
+            started_at: unix_seconds(self.started_at),
+            finished_at: unix_seconds(finished_at),
+            bytes_downloaded,
+            average_speed: if elapsed.is_zero() {
+                0.0
+            } else {
+                bytes_downloaded as f64 / elapsed.as_secs_f64()
+            },
+            retries: servers.iter().map(|s| s.retries).sum(),
+            files,
+            servers,
+            repairs: self.repairs.clone(),
+        }
+    }
+
+    /// Totals per server, sorted by server name
+    ///
+    /// Replaced sources only add their bytes: their attempts are counted by
+    /// the source that replaced them.
+    fn server_reports(&self) -> Vec<ServerReport> {
+        let mut servers: BTreeMap<&str, ServerReport> = BTreeMap::new();
+        let sources = self.sources.values().flat_map(BTreeMap::values);
+        for (source, replaced) in sources
+            .map(|source| (source, false))
+            .chain(self.replaced.iter().map(|source| (source, true)))
+        {
+            let server = servers
+                .entry(&source.server)
+                .or_insert_with(|| ServerReport {
+                    server: source.server.clone(),
+                    segments: 0,
+                    bytes: 0,
+                    retries: 0,
+                });
+            server.bytes += source.bytes;
+            if !replaced {
+                server.segments += 1;
+                server.retries += source.attempts.saturating_sub(1);
+            }
+        }
+        servers.into_values().collect()
+    }
+}
+
+/// Whether `digest` matches the PAR2 description named `name`, if there is one
+fn par2_verdict(par2: &Par2File, name: &str, digest: &FileDigest) -> Option<bool> {
+    let desc = par2
+        .file_descriptions
+        .values()
+        .find(|desc| &*desc.name == name)?;
+    par2.verify_digest(&desc.file_id, digest).ok()
+}
+
+/// Lowercase hex form of `bytes`
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nzb::{Nzb, NzbFile, NzbSegment};
+    use crate::par2::FileDescriptionPacket;
+    use md5::{Digest, Md5};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn job() -> DownloadJob {
+        let file = |name: &str| NzbFile {
+            poster: "poster".to_string(),
+            date: 0,
+            subject: format!("\"{}\" yEnc (1/2)", name),
+            groups: vec!["alt.test".to_string()],
+            segments: (1..=2)
+                .map(|number| NzbSegment {
+                    bytes: 100,
+                    number,
+                    message_id: format!("{}-{}@test", name, number),
+                })
+                .collect(),
+        };
+        let nzb = Nzb {
+            meta: HashMap::new(),
+            files: vec![file("a.bin"), file("b.bin")],
+        };
+        DownloadJob::new("job-1", nzb, "primary", "/tmp/downloads")
+    }
+
+    fn par2_for(name: &str, data: &[u8]) -> Par2File {
+        let file_id = [7; 16];
+        let mut par2 = Par2File::default();
+        par2.file_descriptions.insert(
+            file_id,
+            FileDescriptionPacket {
+                file_id,
+                hash: Md5::digest(data).into(),
+                hash_16k: [0; 16],
+                length: data.len() as u64,
+                name: Arc::from(name),
+            },
+        );
+        par2
+    }
+
+    #[test]
+    fn test_report_summarizes_job() {
+        let mut job = job();
+        let started = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut recorder = IntegrityRecorder::started_at(started);
+        for (file, segment, server, attempts) in
+            [(0, 0, "a:563", 1), (0, 1, "b:563", 3), (1, 0, "a:563", 1)]
+        {
+            job.set_segment(file, segment, SegmentStatus::Completed);
+            recorder.record_segment(file, segment, server, attempts, 100);
+        }
+        // Refetched from another server after a CRC error
+        recorder.record_segment(1, 0, "b:563", 1, 100);
+
+        let mut hasher = StreamingHasher::new();
+        hasher.update(b"file a");
+        job.set_hash_state(0, hasher.snapshot());
+        recorder.record_repair(RepairRecord {
+            file: "b.bin".to_string(),
+            blocks_used: 1,
+            succeeded: true,
+        });
+
+        let par2 = par2_for("a.bin", b"file a");
+        let report = recorder.report(&job, Some(&par2), started + Duration::from_secs(2));
+
+        // The bytes of the replaced segment still count for its server
+        assert_eq!(report.bytes_downloaded, 400);
+        assert_eq!(report.average_speed, 200.0);
+        assert_eq!(report.retries, 3);
+        assert_eq!(report.started_at, 1_000);
+        assert_eq!(report.servers.len(), 2);
+        assert_eq!(
+            (report.servers[0].segments, report.servers[0].bytes),
+            (1, 200)
+        );
+        assert_eq!(report.servers[1].server, "b:563");
+        assert_eq!(
+            (report.servers[1].segments, report.servers[1].retries),
+            (2, 3)
+        );
+
+        let a = &report.files[0];
+        assert_eq!(a.segments_completed, 2);
+        assert_eq!(a.length, Some(6));
+        assert_eq!(a.crc32, Some(format!("{:08x}", crc32fast::hash(b"file a"))));
+        assert_eq!(
+            a.md5.as_deref(),
+            Some(hex(&Md5::digest(b"file a")).as_str())
+        );
+        assert_eq!(a.par2_verified, Some(true));
+        assert!(!a.repaired);
+
+        let b = &report.files[1];
+        assert_eq!(b.segments_completed, 1);
+        assert_eq!((b.md5.as_ref(), b.par2_verified), (None, None));
+        assert!(b.repaired);
+        assert_eq!(b.sources[0].attempts, 2);
+    }
+
+    #[test]
+    fn test_set_digest_overrides_job_hash() {
+        let mut job = job();
+        let mut hasher = StreamingHasher::new();
+        hasher.update(b"damaged");
+        job.set_hash_state(0, hasher.snapshot());
+
+        let mut recorder = IntegrityRecorder::new();
+        let mut repaired = StreamingHasher::new();
+        repaired.update(b"file a");
+        recorder.set_digest(0, repaired.finalize());
+
+        let par2 = par2_for("a.bin", b"file a");
+        let report = recorder.report(&job, Some(&par2), SystemTime::now());
+        assert_eq!(report.files[0].par2_verified, Some(true));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_serializes() {
+        let recorder = IntegrityRecorder::new();
+        let report = recorder.report(&job(), None, SystemTime::now());
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"job_id\":\"job-1\""));
+        assert_eq!(
+            serde_json::from_str::<IntegrityReport>(&json).unwrap(),
+            report
+        );
+
+        let json = serde_json::to_string(&recorder).unwrap();
+        assert_eq!(
+            serde_json::from_str::<IntegrityRecorder>(&json).unwrap(),
+            recorder
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Pluggable hostname resolution
+//!
+//! Connections resolve the server hostname through a [`Resolver`]. Without a
+//! resolver in [`ServerConfig::resolver`](crate::ServerConfig::resolver), a
+//! process-wide [`CachingResolver`] over the system resolver is used, so a
+//! pool opening many connections resolves each host once per TTL.
+//! Applications can plug in another DNS client (hickory-dns, DNS over HTTPS)
+//! or pin hosts to fixed addresses with [`StaticResolver`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+use crate::error::{NntpError, Result};
+
+/// Future returned by [`Resolver::resolve`]
+pub type ResolveFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<SocketAddr>>> + Send + 'a>>;
+
+/// Default time a [`CachingResolver`] keeps an answer
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+/// Resolver shared by every configuration without its own
+static DEFAULT_RESOLVER: LazyLock<CachingResolver> =
+    LazyLock::new(|| CachingResolver::new(DEFAULT_TTL));
+
+/// The resolver used when a configuration does not set one
+pub(crate) fn default_resolver() -> &'static CachingResolver {
+    &DEFAULT_RESOLVER
+}
+
+/// Turns a hostname into the addresses to connect to
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::resolver::{ResolveFuture, Resolver};
+/// use std::net::SocketAddr;
+///
+/// /// Sends every connection through a local tunnel
+/// #[derive(Debug)]
+/// struct Tunnel;
+///
+/// impl Resolver for Tunnel {
+///     fn resolve<'a>(&'a self, _host: &'a str, port: u16) -> ResolveFuture<'a> {
+///         Box::pin(async move { Ok(vec![SocketAddr::from(([127, 0, 0, 1], port))]) })
+///     }
+/// }
+/// ```
+pub trait Resolver: Send + Sync + fmt::Debug {
+    /// Resolve `host` to socket addresses with `port`, in order of preference
+    ///
+    /// # Errors
+    ///
+    /// The error is returned unchanged by
+    /// [`NntpClient::connect`](crate::NntpClient::connect).
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> ResolveFuture<'a>;
+
+    /// Forget any cached answer for `host`
+    ///
+    /// Called when no resolved address could be connected to, so a stale
+    /// answer is not reused. The default does nothing.
+    fn invalidate(&self, host: &str, port: u16) {
+        let _ = (host, port);
+    }
+}
+
+/// The operating system resolver (`getaddrinfo`), run off the async threads
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host, port)).await.map_err(|e| {
+                NntpError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Failed to resolve address: {}", e),
+                ))
+            })?;
+            Ok(addrs.collect())
+        })
+    }
+}
+
+/// Caches the answers of another resolver for a fixed time
+///
+/// Failed lookups are not cached.
+#[derive(Debug)]
+pub struct CachingResolver {
+    inner: Arc<dyn Resolver>,
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, u16), CacheEntry>>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    addrs: Vec<SocketAddr>,
+    expires: Instant,
+}
+
+impl CachingResolver {
+    /// Cache the system resolver for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self::wrap(Arc::new(SystemResolver), ttl)
+    }
+
+    /// Cache the answers of `inner` for `ttl`
+    pub fn wrap(inner: Arc<dyn Resolver>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drop every cached answer
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn cached(&self, host: &str, port: u16) -> Option<Vec<SocketAddr>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&(host.to_string(), port))
+            .filter(|entry| entry.expires > Instant::now())
+            .map(|entry| entry.addrs.clone())
+    }
+}
+
+impl Resolver for CachingResolver {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            if let Some(addrs) = self.cached(host, port) {
+                debug!("Using cached addresses for {}", host);
+                return Ok(addrs);
+            }
+
+            let addrs = self.inner.resolve(host, port).await?;
+            self.entries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(
+                    (host.to_string(), port),
+                    CacheEntry {
+                        addrs: addrs.clone(),
+                        expires: Instant::now() + self.ttl,
+                    },
+                );
+            Ok(addrs)
+        })
+    }
+
+    fn invalidate(&self, host: &str, port: u16) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(host.to_string(), port));
+        self.inner.invalidate(host, port);
+    }
+}
+
+/// Fixed hostname to address mappings, like an `/etc/hosts` entry
+///
+/// Hosts without a mapping go to the fallback resolver, or fail if there is
+/// none.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::ServerConfig;
+/// use nntp_rs::resolver::{StaticResolver, SystemResolver};
+/// use std::sync::Arc;
+///
+/// let resolver = StaticResolver::new()
+///     .with_host("news.example.com", vec!["203.0.113.7".parse().unwrap()])
+///     .with_fallback(Arc::new(SystemResolver));
+/// let config = ServerConfig::tls("news.example.com", "user", "pass")
+///     .with_resolver(Arc::new(resolver));
+/// ```
+#[derive(Debug, Default)]
+pub struct StaticResolver {
+    hosts: HashMap<String, Vec<IpAddr>>,
+    fallback: Option<Arc<dyn Resolver>>,
+}
+
+impl StaticResolver {
+    /// Create a resolver without mappings or fallback
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `host` (case-insensitive) to `addrs`
+    pub fn with_host(mut self, host: impl Into<String>, addrs: Vec<IpAddr>) -> Self {
+        self.hosts.insert(host.into().to_ascii_lowercase(), addrs);
+        self
+    }
+
+    /// Resolve unmapped hosts with `fallback`
+    pub fn with_fallback(mut self, fallback: Arc<dyn Resolver>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+}
+
+impl Resolver for StaticResolver {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            if let Some(ips) = self.hosts.get(&host.to_ascii_lowercase()) {
+                return Ok(ips.iter().map(|&ip| SocketAddr::new(ip, port)).collect());
+            }
+            match &self.fallback {
+                Some(fallback) => fallback.resolve(host, port).await,
+                None => Err(NntpError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Failed to resolve address: no mapping for {}", host),
+                ))),
+            }
+        })
+    }
+
+    fn invalidate(&self, host: &str, port: u16) {
+        if let Some(fallback) = &self.fallback {
+            fallback.invalidate(host, port);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts lookups and answers with loopback
+    #[derive(Debug, Default)]
+    struct Counting(AtomicUsize);
+
+    impl Resolver for Counting {
+        fn resolve<'a>(&'a self, _host: &'a str, port: u16) -> ResolveFuture<'a> {
+            Box::pin(async move {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(vec![SocketAddr::from(([127, 0, 0, 1], port))])
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching_resolver_reuses_answers() {
+        let counting = Arc::new(Counting::default());
+        let resolver = CachingResolver::wrap(counting.clone(), Duration::from_secs(60));
+
+        for _ in 0..3 {
+            let addrs = resolver.resolve("news.example.com", 563).await.unwrap();
+            assert_eq!(addrs, vec![SocketAddr::from(([127, 0, 0, 1], 563))]);
+        }
+        resolver.resolve("news.example.com", 119).await.unwrap();
+        assert_eq!(counting.0.load(Ordering::SeqCst), 2);
+
+        resolver.invalidate("news.example.com", 563);
+        resolver.resolve("news.example.com", 563).await.unwrap();
+        assert_eq!(counting.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_caching_resolver_expires_answers() {
+        let counting = Arc::new(Counting::default());
+        let resolver = CachingResolver::wrap(counting.clone(), Duration::ZERO);
+        resolver.resolve("news.example.com", 563).await.unwrap();
+        resolver.resolve("news.example.com", 563).await.unwrap();
+        assert_eq!(counting.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_static_resolver() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let resolver = StaticResolver::new().with_host("News.Example.com", vec![ip]);
+        assert_eq!(
+            resolver.resolve("news.example.COM", 563).await.unwrap(),
+            vec![SocketAddr::new(ip, 563)]
+        );
+        assert!(resolver.resolve("other.example.com", 563).await.is_err());
+
+        let resolver = resolver.with_fallback(Arc::new(Counting::default()));
+        assert_eq!(
+            resolver.resolve("other.example.com", 119).await.unwrap(),
+            vec![SocketAddr::from(([127, 0, 0, 1], 119))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_system_resolver_resolves_literals() {
+        let addrs = SystemResolver.resolve("127.0.0.1", 119).await.unwrap();
+        assert_eq!(addrs, vec![SocketAddr::from(([127, 0, 0, 1], 119))]);
+    }
+}
@@ -1,5 +1,12 @@
 //! NNTP response types and status codes
 
+use std::fmt;
+
+use bytes::Bytes;
+
+use crate::article::{Article, Headers, parse_headers, split_article_bytes};
+use crate::error::Result;
+
 /// NNTP response with status code, message, and optional multi-line body
 #[must_use]
 #[derive(Debug, Clone)]
@@ -28,11 +35,17 @@ pub struct NntpBinaryResponse {
     pub code: u16,
     /// Status message from server
     pub message: String,
-    /// Raw binary response body (with dot-stuffing already removed)
+    /// Raw binary response body, line terminators included as received
+    /// (dot-stuffing already removed, terminating "." line excluded)
     pub data: Vec<u8>,
 }
 
 impl NntpBinaryResponse {
+    /// Get the status code as a [`ResponseCode`]
+    pub fn response_code(&self) -> ResponseCode {
+        ResponseCode::from(self.code)
+    }
+
     /// Check if response indicates success (2xx)
     pub fn is_success(&self) -> bool {
         self.code >= 200 && self.code < 300
@@ -42,9 +55,64 @@ impl NntpBinaryResponse {
     pub fn is_error(&self) -> bool {
         self.code >= 400
     }
+
+    /// Split an `ARTICLE` response into parsed headers and the raw body
+    ///
+    /// The header section (up to the first blank line) is parsed with
+    /// [`parse_headers`](crate::parse_headers); non-UTF-8 header bytes are
+    /// replaced rather than rejected. The body is returned as a zero-copy
+    /// slice of the response data, byte for byte.
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::InvalidResponse`](crate::NntpError::InvalidResponse) -
+    ///   A required header (Date, From, Message-ID, Newsgroups, Path, Subject)
+    ///   is missing
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{NntpClient, ServerConfig};
+    /// # use std::sync::Arc;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// # let config = ServerConfig::tls("news.example.com", "user", "pass");
+    /// # let mut client = NntpClient::connect(Arc::new(config)).await?;
+    /// let response = client.fetch_article_binary("<part1@example.com>").await?;
+    /// let (headers, body) = response.split_article()?;
+    /// println!("{}: {} body bytes", headers.subject, body.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_article(self) -> Result<(Headers, Bytes)> {
+        let (headers, body) = split_article_bytes(&self.data);
+        let body_start = self.data.len() - body.len();
+        let headers = parse_headers(&String::from_utf8_lossy(headers))?;
+        Ok((headers, Bytes::from(self.data).slice(body_start..)))
+    }
+
+    /// Make an ARTICLE response into an [`Article`] that keeps its body bytes
+    ///
+    /// Splits the response like [`split_article`](Self::split_article), then
+    /// builds the article with [`Article::with_body_bytes`], so binary data
+    /// in the body, such as yEnc blocks for
+    /// [`Article::extract_yenc_parts`], survives byte for byte.
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::InvalidResponse`](crate::NntpError::InvalidResponse) -
+    ///   A required header is missing
+    pub fn into_article(self) -> Result<Article> {
+        let (headers, body) = self.split_article()?;
+        Ok(Article::with_body_bytes(headers, body.to_vec()))
+    }
 }
 
 impl NntpResponse {
+    /// Get the status code as a [`ResponseCode`]
+    pub fn response_code(&self) -> ResponseCode {
+        ResponseCode::from(self.code)
+    }
+
     /// Check if response indicates success (2xx)
     pub fn is_success(&self) -> bool {
         self.code >= 200 && self.code < 300
@@ -61,6 +129,350 @@ impl NntpResponse {
     }
 }
 
+/// How malformed server output is handled
+///
+/// Lenient parsing copes with the quirks of real servers: a status line
+/// with a byte order mark or without the space after the code is accepted,
+/// and overview or HDR lines that cannot be parsed are skipped with a
+/// [`ParseWarning`](crate::ParseWarning). Strict parsing fails the command
+/// with [`NntpError::InvalidResponse`](crate::NntpError::InvalidResponse)
+/// instead, so broken peers are noticed rather than silently losing data.
+///
+/// Set per server with [`ServerConfig::parse_mode`](crate::ServerConfig::parse_mode)
+/// or per connection with
+/// [`NntpClient::set_parse_mode`](crate::NntpClient::set_parse_mode).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseMode {
+    /// Accept what can be made sense of and skip the rest (default)
+    #[default]
+    Lenient,
+    /// Reject malformed status lines and overview or HDR lines
+    Strict,
+}
+
+/// Class of a response code, from its first digit (RFC 3977 Section 3.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResponseCategory {
+    /// 1xx - Informative message
+    Informational,
+    /// 2xx - Command completed OK
+    Success,
+    /// 3xx - Command OK so far; send the rest of it
+    Continuation,
+    /// 4xx - Command was syntactically correct but failed for some reason
+    Failure,
+    /// 5xx - Command unknown, unsupported, unavailable, or syntax error
+    Error,
+    /// Anything outside 100-599
+    Unknown,
+}
+
+/// Typed NNTP response code
+///
+/// Every code in [`codes`] has a variant; anything else is kept as
+/// [`ResponseCode::Unknown`] so unexpected replies can still be reported
+/// with their number. Convert with `From<u16>` and back with `u16::from`
+/// or [`ResponseCode::code`].
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::{ResponseCategory, ResponseCode};
+///
+/// let code = ResponseCode::from(430);
+/// assert_eq!(code, ResponseCode::NoSuchArticleId);
+/// assert_eq!(code.category(), ResponseCategory::Failure);
+/// assert!(!code.is_retryable());
+///
+/// assert_eq!(ResponseCode::from(299), ResponseCode::Unknown(299));
+/// assert_eq!(ResponseCode::from(299).category(), ResponseCategory::Success);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResponseCode {
+    /// Help text follows
+    HelpTextFollows,
+    /// Capability list follows (RFC 3977 Section 5.2)
+    CapabilityList,
+    /// Server date/time (RFC 3977 Section 7.1)
+    ServerDate,
+    /// Server ready, posting allowed
+    ReadyPostingAllowed,
+    /// Server ready, no posting
+    ReadyNoPosting,
+    /// Slave status noted (RFC 977 legacy)
+    SlaveStatusNoted,
+    /// Streaming OK (RFC 4644 Section 2.3)
+    StreamingOk,
+    /// Closing connection (RFC 3977 §5.3)
+    ClosingConnection,
+    /// Compression active (RFC 8054)
+    CompressionActive,
+    /// Group selected
+    GroupSelected,
+    /// List of newsgroups follows (RFC 3977 Section 7.6)
+    ListInformationFollows,
+    /// Article follows
+    ArticleFollows,
+    /// Head follows
+    HeadFollows,
+    /// Body follows
+    BodyFollows,
+    /// Article stat
+    ArticleStat,
+    /// Overview information follows
+    OverviewInfoFollows,
+    /// Headers follow
+    HeadersFollow,
+    /// List of new articles follows (RFC 3977 Section 7.4)
+    NewArticleListFollows,
+    /// List of new newsgroups follows (RFC 3977 Section 7.3)
+    NewNewsgroupsFollow,
+    /// Article transferred OK (RFC 3977 Section 6.3.2)
+    ArticleTransferred,
+    /// Send article (RFC 4644 Section 2.4)
+    CheckSend,
+    /// Article received OK (RFC 4644 Section 2.5)
+    TakethisReceived,
+    /// Article posted successfully (RFC 3977 Section 6.3.1)
+    ArticlePosted,
+    /// Authentication accepted
+    AuthAccepted,
+    /// Send article to be transferred (RFC 3977 Section 6.3.2)
+    SendArticleTransfer,
+    /// Send article to be posted
+    SendArticle,
+    /// Continue with authentication
+    AuthContinue,
+    /// Continue with TLS negotiation (RFC 4642 Section 2.2.2)
+    TlsContinue,
+    /// SASL challenge (RFC 4643 Section 2.4)
+    SaslContinue,
+    /// Service temporarily unavailable
+    ServiceUnavailable,
+    /// Internal fault or server resource problem (RFC 3977)
+    InternalFault,
+    /// No such newsgroup
+    NoSuchGroup,
+    /// No newsgroup selected
+    NoGroupSelected,
+    /// No current article
+    NoCurrentArticle,
+    /// No next article
+    NoNextArticle,
+    /// No previous article
+    NoPrevArticle,
+    /// No article with that number
+    NoSuchArticleNumber,
+    /// No article with that message-id
+    NoSuchArticleId,
+    /// Try again later (RFC 4644 Section 2.4)
+    CheckLater,
+    /// Article not wanted (RFC 3977 Section 6.3.2)
+    ArticleNotWanted,
+    /// Transfer not possible; try again later (RFC 3977 Section 6.3.2)
+    TransferNotPossible,
+    /// Transfer rejected; do not retry (RFC 3977 Section 6.3.2)
+    TransferRejected,
+    /// Article not wanted (RFC 4644 Section 2.4)
+    CheckNotWanted,
+    /// Article rejected (RFC 4644 Section 2.5)
+    TakethisRejected,
+    /// Posting not permitted (RFC 3977 Section 6.3.1)
+    PostingNotPermitted,
+    /// Posting failed (RFC 3977 Section 6.3.1)
+    PostingFailed,
+    /// Authentication required (RFC 4643)
+    AuthRequired,
+    /// Authentication rejected
+    AuthRejected,
+    /// Authentication out of sequence
+    AuthOutOfSequence,
+    /// Encryption or authentication required (RFC 4643)
+    EncryptionRequired,
+    /// Command not recognized
+    CommandNotRecognized,
+    /// Command syntax error
+    CommandSyntaxError,
+    /// Access denied / command unavailable
+    AccessDenied,
+    /// Feature not supported / optional functionality absent (RFC 3977)
+    FeatureNotSupported,
+    /// Can not initiate TLS negotiation (RFC 4642 Section 2.2.2)
+    TlsNotPossible,
+    /// A code without a variant
+    Unknown(u16),
+}
+
+impl ResponseCode {
+    /// Numeric value of the code
+    pub fn code(self) -> u16 {
+        match self {
+            Self::HelpTextFollows => codes::HELP_TEXT_FOLLOWS,
+            Self::CapabilityList => codes::CAPABILITY_LIST,
+            Self::ServerDate => codes::SERVER_DATE,
+            Self::ReadyPostingAllowed => codes::READY_POSTING_ALLOWED,
+            Self::ReadyNoPosting => codes::READY_NO_POSTING,
+            Self::SlaveStatusNoted => codes::SLAVE_STATUS_NOTED,
+            Self::StreamingOk => codes::STREAMING_OK,
+            Self::ClosingConnection => codes::CLOSING_CONNECTION,
+            Self::CompressionActive => codes::COMPRESSION_ACTIVE,
+            Self::GroupSelected => codes::GROUP_SELECTED,
+            Self::ListInformationFollows => codes::LIST_INFORMATION_FOLLOWS,
+            Self::ArticleFollows => codes::ARTICLE_FOLLOWS,
+            Self::HeadFollows => codes::HEAD_FOLLOWS,
+            Self::BodyFollows => codes::BODY_FOLLOWS,
+            Self::ArticleStat => codes::ARTICLE_STAT,
+            Self::OverviewInfoFollows => codes::OVERVIEW_INFO_FOLLOWS,
+            Self::HeadersFollow => codes::HEADERS_FOLLOW,
+            Self::NewArticleListFollows => codes::NEW_ARTICLE_LIST_FOLLOWS,
+            Self::NewNewsgroupsFollow => codes::NEW_NEWSGROUPS_FOLLOW,
+            Self::ArticleTransferred => codes::ARTICLE_TRANSFERRED,
+            Self::CheckSend => codes::CHECK_SEND,
+            Self::TakethisReceived => codes::TAKETHIS_RECEIVED,
+            Self::ArticlePosted => codes::ARTICLE_POSTED,
+            Self::AuthAccepted => codes::AUTH_ACCEPTED,
+            Self::SendArticleTransfer => codes::SEND_ARTICLE_TRANSFER,
+            Self::SendArticle => codes::SEND_ARTICLE,
+            Self::AuthContinue => codes::AUTH_CONTINUE,
+            Self::TlsContinue => codes::TLS_CONTINUE,
+            Self::SaslContinue => codes::SASL_CONTINUE,
+            Self::ServiceUnavailable => codes::SERVICE_UNAVAILABLE,
+            Self::InternalFault => codes::INTERNAL_FAULT,
+            Self::NoSuchGroup => codes::NO_SUCH_GROUP,
+            Self::NoGroupSelected => codes::NO_GROUP_SELECTED,
+            Self::NoCurrentArticle => codes::NO_CURRENT_ARTICLE,
+            Self::NoNextArticle => codes::NO_NEXT_ARTICLE,
+            Self::NoPrevArticle => codes::NO_PREV_ARTICLE,
+            Self::NoSuchArticleNumber => codes::NO_SUCH_ARTICLE_NUMBER,
+            Self::NoSuchArticleId => codes::NO_SUCH_ARTICLE_ID,
+            Self::CheckLater => codes::CHECK_LATER,
+            Self::ArticleNotWanted => codes::ARTICLE_NOT_WANTED,
+            Self::TransferNotPossible => codes::TRANSFER_NOT_POSSIBLE,
+            Self::TransferRejected => codes::TRANSFER_REJECTED,
+            Self::CheckNotWanted => codes::CHECK_NOT_WANTED,
+            Self::TakethisRejected => codes::TAKETHIS_REJECTED,
+            Self::PostingNotPermitted => codes::POSTING_NOT_PERMITTED,
+            Self::PostingFailed => codes::POSTING_FAILED,
+            Self::AuthRequired => codes::AUTH_REQUIRED,
+            Self::AuthRejected => codes::AUTH_REJECTED,
+            Self::AuthOutOfSequence => codes::AUTH_OUT_OF_SEQUENCE,
+            Self::EncryptionRequired => codes::ENCRYPTION_REQUIRED,
+            Self::CommandNotRecognized => codes::COMMAND_NOT_RECOGNIZED,
+            Self::CommandSyntaxError => codes::COMMAND_SYNTAX_ERROR,
+            Self::AccessDenied => codes::ACCESS_DENIED,
+            Self::FeatureNotSupported => codes::FEATURE_NOT_SUPPORTED,
+            Self::TlsNotPossible => codes::TLS_NOT_POSSIBLE,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    /// Class of the code, from its first digit
+    pub fn category(self) -> ResponseCategory {
+        match self.code() {
+            100..=199 => ResponseCategory::Informational,
+            200..=299 => ResponseCategory::Success,
+            300..=399 => ResponseCategory::Continuation,
+            400..=499 => ResponseCategory::Failure,
+            500..=599 => ResponseCategory::Error,
+            _ => ResponseCategory::Unknown,
+        }
+    }
+
+    /// Check if the same command may succeed when sent again later
+    ///
+    /// True for temporary conditions the server reports as such: service
+    /// unavailable (400), internal fault (403), and the "try again later"
+    /// replies to CHECK (431) and IHAVE (436). Failures tied to the request
+    /// itself, such as a missing article, are not retryable.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::ServiceUnavailable
+                | Self::InternalFault
+                | Self::CheckLater
+                | Self::TransferNotPossible
+        )
+    }
+}
+
+impl From<u16> for ResponseCode {
+    fn from(code: u16) -> Self {
+        match code {
+            codes::HELP_TEXT_FOLLOWS => Self::HelpTextFollows,
+            codes::CAPABILITY_LIST => Self::CapabilityList,
+            codes::SERVER_DATE => Self::ServerDate,
+            codes::READY_POSTING_ALLOWED => Self::ReadyPostingAllowed,
+            codes::READY_NO_POSTING => Self::ReadyNoPosting,
+            codes::SLAVE_STATUS_NOTED => Self::SlaveStatusNoted,
+            codes::STREAMING_OK => Self::StreamingOk,
+            codes::CLOSING_CONNECTION => Self::ClosingConnection,
+            codes::COMPRESSION_ACTIVE => Self::CompressionActive,
+            codes::GROUP_SELECTED => Self::GroupSelected,
+            codes::LIST_INFORMATION_FOLLOWS => Self::ListInformationFollows,
+            codes::ARTICLE_FOLLOWS => Self::ArticleFollows,
+            codes::HEAD_FOLLOWS => Self::HeadFollows,
+            codes::BODY_FOLLOWS => Self::BodyFollows,
+            codes::ARTICLE_STAT => Self::ArticleStat,
+            codes::OVERVIEW_INFO_FOLLOWS => Self::OverviewInfoFollows,
+            codes::HEADERS_FOLLOW => Self::HeadersFollow,
+            codes::NEW_ARTICLE_LIST_FOLLOWS => Self::NewArticleListFollows,
+            codes::NEW_NEWSGROUPS_FOLLOW => Self::NewNewsgroupsFollow,
+            codes::ARTICLE_TRANSFERRED => Self::ArticleTransferred,
+            codes::CHECK_SEND => Self::CheckSend,
+            codes::TAKETHIS_RECEIVED => Self::TakethisReceived,
+            codes::ARTICLE_POSTED => Self::ArticlePosted,
+            codes::AUTH_ACCEPTED => Self::AuthAccepted,
+            codes::SEND_ARTICLE_TRANSFER => Self::SendArticleTransfer,
+            codes::SEND_ARTICLE => Self::SendArticle,
+            codes::AUTH_CONTINUE => Self::AuthContinue,
+            codes::TLS_CONTINUE => Self::TlsContinue,
+            codes::SASL_CONTINUE => Self::SaslContinue,
+            codes::SERVICE_UNAVAILABLE => Self::ServiceUnavailable,
+            codes::INTERNAL_FAULT => Self::InternalFault,
+            codes::NO_SUCH_GROUP => Self::NoSuchGroup,
+            codes::NO_GROUP_SELECTED => Self::NoGroupSelected,
+            codes::NO_CURRENT_ARTICLE => Self::NoCurrentArticle,
+            codes::NO_NEXT_ARTICLE => Self::NoNextArticle,
+            codes::NO_PREV_ARTICLE => Self::NoPrevArticle,
+            codes::NO_SUCH_ARTICLE_NUMBER => Self::NoSuchArticleNumber,
+            codes::NO_SUCH_ARTICLE_ID => Self::NoSuchArticleId,
+            codes::CHECK_LATER => Self::CheckLater,
+            codes::ARTICLE_NOT_WANTED => Self::ArticleNotWanted,
+            codes::TRANSFER_NOT_POSSIBLE => Self::TransferNotPossible,
+            codes::TRANSFER_REJECTED => Self::TransferRejected,
+            codes::CHECK_NOT_WANTED => Self::CheckNotWanted,
+            codes::TAKETHIS_REJECTED => Self::TakethisRejected,
+            codes::POSTING_NOT_PERMITTED => Self::PostingNotPermitted,
+            codes::POSTING_FAILED => Self::PostingFailed,
+            codes::AUTH_REQUIRED => Self::AuthRequired,
+            codes::AUTH_REJECTED => Self::AuthRejected,
+            codes::AUTH_OUT_OF_SEQUENCE => Self::AuthOutOfSequence,
+            codes::ENCRYPTION_REQUIRED => Self::EncryptionRequired,
+            codes::COMMAND_NOT_RECOGNIZED => Self::CommandNotRecognized,
+            codes::COMMAND_SYNTAX_ERROR => Self::CommandSyntaxError,
+            codes::ACCESS_DENIED => Self::AccessDenied,
+            codes::FEATURE_NOT_SUPPORTED => Self::FeatureNotSupported,
+            codes::TLS_NOT_POSSIBLE => Self::TlsNotPossible,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<ResponseCode> for u16 {
+    fn from(code: ResponseCode) -> Self {
+        code.code()
+    }
+}
+
+impl fmt::Display for ResponseCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
 /// NNTP response codes from RFC 3977, RFC 4643, RFC 4644, RFC 6048, and RFC 8054
 ///
 /// This module provides a comprehensive reference library of NNTP protocol response codes.
@@ -134,6 +546,8 @@ pub mod codes {
     pub const SEND_ARTICLE: u16 = 340;
     /// Continue with authentication
     pub const AUTH_CONTINUE: u16 = 381;
+    /// Continue with TLS negotiation (RFC 4642 Section 2.2.2)
+    pub const TLS_CONTINUE: u16 = 382;
     /// SASL challenge (RFC 4643 Section 2.4)
     pub const SASL_CONTINUE: u16 = 383;
 
@@ -191,6 +605,8 @@ pub mod codes {
     pub const ACCESS_DENIED: u16 = 502;
     /// Feature not supported / optional functionality absent (RFC 3977)
     pub const FEATURE_NOT_SUPPORTED: u16 = 503;
+    /// Can not initiate TLS negotiation (RFC 4642 Section 2.2.2)
+    pub const TLS_NOT_POSSIBLE: u16 = 580;
 }
 
 #[cfg(test)]
@@ -272,4 +688,168 @@ mod tests {
             .is_success()
         );
     }
+
+    #[test]
+    fn test_response_code_round_trip() {
+        for code in [
+            codes::HELP_TEXT_FOLLOWS,
+            codes::CAPABILITY_LIST,
+            codes::SERVER_DATE,
+            codes::READY_POSTING_ALLOWED,
+            codes::READY_NO_POSTING,
+            codes::SLAVE_STATUS_NOTED,
+            codes::STREAMING_OK,
+            codes::CLOSING_CONNECTION,
+            codes::COMPRESSION_ACTIVE,
+            codes::GROUP_SELECTED,
+            codes::LIST_INFORMATION_FOLLOWS,
+            codes::ARTICLE_FOLLOWS,
+            codes::HEAD_FOLLOWS,
+            codes::BODY_FOLLOWS,
+            codes::ARTICLE_STAT,
+            codes::OVERVIEW_INFO_FOLLOWS,
+            codes::HEADERS_FOLLOW,
+            codes::NEW_ARTICLE_LIST_FOLLOWS,
+            codes::NEW_NEWSGROUPS_FOLLOW,
+            codes::ARTICLE_TRANSFERRED,
+            codes::CHECK_SEND,
+            codes::TAKETHIS_RECEIVED,
+            codes::ARTICLE_POSTED,
+            codes::AUTH_ACCEPTED,
+            codes::SEND_ARTICLE_TRANSFER,
+            codes::SEND_ARTICLE,
+            codes::AUTH_CONTINUE,
+            codes::TLS_CONTINUE,
+            codes::SASL_CONTINUE,
+            codes::SERVICE_UNAVAILABLE,
+            codes::INTERNAL_FAULT,
+            codes::NO_SUCH_GROUP,
+            codes::NO_GROUP_SELECTED,
+            codes::NO_CURRENT_ARTICLE,
+            codes::NO_NEXT_ARTICLE,
+            codes::NO_PREV_ARTICLE,
+            codes::NO_SUCH_ARTICLE_NUMBER,
+            codes::NO_SUCH_ARTICLE_ID,
+            codes::CHECK_LATER,
+            codes::ARTICLE_NOT_WANTED,
+            codes::TRANSFER_NOT_POSSIBLE,
+            codes::TRANSFER_REJECTED,
+            codes::CHECK_NOT_WANTED,
+            codes::TAKETHIS_REJECTED,
+            codes::POSTING_NOT_PERMITTED,
+            codes::POSTING_FAILED,
+            codes::AUTH_REQUIRED,
+            codes::AUTH_REJECTED,
+            codes::AUTH_OUT_OF_SEQUENCE,
+            codes::ENCRYPTION_REQUIRED,
+            codes::COMMAND_NOT_RECOGNIZED,
+            codes::COMMAND_SYNTAX_ERROR,
+            codes::ACCESS_DENIED,
+            codes::FEATURE_NOT_SUPPORTED,
+            codes::TLS_NOT_POSSIBLE,
+        ] {
+            let typed = ResponseCode::from(code);
+            assert!(!matches!(typed, ResponseCode::Unknown(_)), "{}", code);
+            assert_eq!(u16::from(typed), code);
+            assert_eq!(typed.to_string(), code.to_string());
+        }
+        assert_eq!(ResponseCode::from(299), ResponseCode::Unknown(299));
+        assert_eq!(ResponseCode::Unknown(299).code(), 299);
+    }
+
+    #[test]
+    fn test_response_code_category() {
+        assert_eq!(
+            ResponseCode::CapabilityList.category(),
+            ResponseCategory::Informational
+        );
+        assert_eq!(
+            ResponseCode::GroupSelected.category(),
+            ResponseCategory::Success
+        );
+        assert_eq!(
+            ResponseCode::SendArticle.category(),
+            ResponseCategory::Continuation
+        );
+        assert_eq!(
+            ResponseCode::NoSuchGroup.category(),
+            ResponseCategory::Failure
+        );
+        assert_eq!(
+            ResponseCode::AccessDenied.category(),
+            ResponseCategory::Error
+        );
+        assert_eq!(ResponseCode::from(99).category(), ResponseCategory::Unknown);
+        assert_eq!(
+            ResponseCode::from(600).category(),
+            ResponseCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn test_response_code_retryable() {
+        assert!(ResponseCode::ServiceUnavailable.is_retryable());
+        assert!(ResponseCode::InternalFault.is_retryable());
+        assert!(ResponseCode::CheckLater.is_retryable());
+        assert!(ResponseCode::TransferNotPossible.is_retryable());
+        assert!(!ResponseCode::NoSuchArticleId.is_retryable());
+        assert!(!ResponseCode::TransferRejected.is_retryable());
+        assert!(!ResponseCode::AuthRejected.is_retryable());
+        assert!(!ResponseCode::Unknown(499).is_retryable());
+    }
+
+    fn binary_article(data: &[u8]) -> NntpBinaryResponse {
+        NntpBinaryResponse {
+            code: 220,
+            message: "0 <part1@example.com>".to_string(),
+            data: data.to_vec(),
+        }
+    }
+
+    const HEADERS: &[u8] = b"Path: news.example.com!not-for-mail\r\n\
+From: poster@example.com\r\n\
+Newsgroups: alt.binaries.test\r\n\
+Subject: test.bin (1/1)\r\n\
+Date: Mon, 1 Jan 2024 00:00:00 +0000\r\n\
+Message-ID: <part1@example.com>\r\n";
+
+    #[test]
+    fn test_split_article_binary_body() {
+        let body: &[u8] = b"=ybegin line=128 size=4 name=test.bin\r\n\xff\x00\r\n\x80\r\n";
+        let data = [HEADERS, b"\r\n", body].concat();
+
+        let (headers, split_body) = binary_article(&data).split_article().unwrap();
+        assert_eq!(headers.subject, "test.bin (1/1)");
+        assert_eq!(headers.message_id, "<part1@example.com>");
+        assert_eq!(&split_body[..], body);
+    }
+
+    #[test]
+    fn test_split_article_lf_and_empty_body() {
+        let lf_headers = String::from_utf8(HEADERS.to_vec())
+            .unwrap()
+            .replace("\r\n", "\n");
+        let data = format!("{}\nbody line\n", lf_headers);
+        let (_, body) = binary_article(data.as_bytes()).split_article().unwrap();
+        assert_eq!(&body[..], b"body line\n");
+
+        let (headers, body) = binary_article(HEADERS).split_article().unwrap();
+        assert_eq!(headers.newsgroups, vec!["alt.binaries.test"]);
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_into_article_keeps_body_bytes() {
+        let body: &[u8] = b"\xd6\x80\x41\r\n";
+        let data = [HEADERS, b"\r\n", body].concat();
+        let article = binary_article(&data).into_article().unwrap();
+        assert_eq!(article.headers.subject, "test.bin (1/1)");
+        assert_eq!(article.body_bytes(), body);
+    }
+
+    #[test]
+    fn test_split_article_missing_header() {
+        let data = b"Subject: only a subject\r\n\r\nbody\r\n";
+        assert!(binary_article(data).split_article().is_err());
+    }
 }
@@ -0,0 +1,441 @@
+//! Crate-level resource ownership and orderly shutdown
+//!
+//! Applications that embed nntp-rs typically hold several connection pools,
+//! rate limiters and long-running background tasks (keepalive, health checks,
+//! queue managers). [`NntpRuntime`] owns all of them, reports their status with
+//! [`NntpRuntime::health`] and tears them down in a fixed order with a single
+//! [`NntpRuntime::shutdown`] call:
+//!
+//! 1. Signal background tasks and wait for them to finish (abort stragglers)
+//! 2. Wait for outstanding connection permits to be released
+//! 3. Wait for pooled connections to be returned, then close the pools
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::health::{BandwidthLimiterHealth, ConnectionLimiterHealth, HealthSnapshot};
+use crate::pool::NntpPool;
+use crate::ratelimit::{BandwidthLimiter, ConnectionLimiter};
+use crate::servers::ServerGroup;
+
+/// How often drain loops re-check pools and limiters
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Shutdown notification handed to background tasks
+///
+/// Cheap to clone; every clone observes the same shutdown.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    receiver: watch::Receiver<bool>,
+}
+
+impl ShutdownSignal {
+    /// Check whether shutdown has been requested
+    pub fn is_shutdown(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Wait until shutdown is requested
+    ///
+    /// Returns immediately if shutdown was already requested, or if the
+    /// owning [`NntpRuntime`] was dropped.
+    pub async fn cancelled(&mut self) {
+        // An error means the runtime was dropped, which is a shutdown too
+        let _ = self.receiver.wait_for(|shutdown| *shutdown).await;
+    }
+}
+
+/// Outcome of [`NntpRuntime::shutdown`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Background tasks that finished on their own after the signal
+    pub tasks_stopped: usize,
+    /// Names of background tasks that had to be aborted at the deadline
+    pub tasks_aborted: Vec<String>,
+    /// Connection permits still held when the deadline passed
+    pub permits_outstanding: usize,
+    /// Pooled connections still checked out when the deadline passed
+    pub connections_in_use: u32,
+}
+
+impl ShutdownReport {
+    /// Whether everything drained before the deadline
+    pub fn is_clean(&self) -> bool {
+        self.tasks_aborted.is_empty()
+            && self.permits_outstanding == 0
+            && self.connections_in_use == 0
+    }
+}
+
+/// Owner of pools, limiters and background tasks with a single shutdown
+///
+/// # Example
+///
+/// ```no_run
+/// use nntp_rs::{ConnectionLimiter, NntpPool, NntpRuntime, ServerConfig};
+/// use std::time::Duration;
+///
+/// # async fn example() -> nntp_rs::Result<()> {
+/// let mut runtime = NntpRuntime::new();
+/// let config = ServerConfig::tls("news.example.com", "user", "pass");
+/// let pool = runtime.add_pool(NntpPool::new(config, 10).await?);
+/// let limiter = runtime.add_connection_limiter(ConnectionLimiter::new(10));
+///
+/// runtime.spawn("idle-check", move |mut shutdown| async move {
+///     loop {
+///         tokio::select! {
+///             _ = shutdown.cancelled() => break,
+///             _ = tokio::time::sleep(Duration::from_secs(60)) => {
+///                 println!("{} idle connections", pool.idle_connections());
+///             }
+///         }
+///     }
+/// });
+///
+/// // ... application work using the pool and limiter ...
+/// # drop(limiter);
+///
+/// let report = runtime.shutdown(Duration::from_secs(10)).await;
+/// assert!(report.is_clean());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NntpRuntime {
+    shutdown: watch::Sender<bool>,
+    pools: Vec<Arc<NntpPool>>,
+    server_groups: Vec<Arc<ServerGroup>>,
+    connection_limiters: Vec<ConnectionLimiter>,
+    bandwidth_limiters: Vec<BandwidthLimiter>,
+    tasks: Vec<(String, JoinHandle<()>)>,
+}
+
+impl Default for NntpRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NntpRuntime {
+    /// Create an empty runtime
+    pub fn new() -> Self {
+        let (shutdown, _) = watch::channel(false);
+        Self {
+            shutdown,
+            pools: Vec::new(),
+            server_groups: Vec::new(),
+            connection_limiters: Vec::new(),
+            bandwidth_limiters: Vec::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Take ownership of a connection pool
+    ///
+    /// Returns a shared handle for the application. Clones of the handle that
+    /// are still alive after shutdown keep the pool open.
+    pub fn add_pool(&mut self, pool: NntpPool) -> Arc<NntpPool> {
+        let pool = Arc::new(pool);
+        self.pools.push(Arc::clone(&pool));
+        pool
+    }
+
+    /// Take ownership of a server group
+    ///
+    /// Its pools are drained on shutdown like standalone pools, and its
+    /// servers appear in [`health`](Self::health).
+    pub fn add_server_group(&mut self, group: ServerGroup) -> Arc<ServerGroup> {
+        let group = Arc::new(group);
+        self.server_groups.push(Arc::clone(&group));
+        group
+    }
+
+    /// Register a connection limiter whose permits are drained on shutdown
+    pub fn add_connection_limiter(&mut self, limiter: ConnectionLimiter) -> ConnectionLimiter {
+        self.connection_limiters.push(limiter.clone());
+        limiter
+    }
+
+    /// Register a bandwidth limiter that lives as long as the runtime
+    pub fn add_bandwidth_limiter(&mut self, limiter: BandwidthLimiter) -> BandwidthLimiter {
+        self.bandwidth_limiters.push(limiter.clone());
+        limiter
+    }
+
+    /// Spawn a named background task on the current Tokio runtime
+    ///
+    /// The task receives a [`ShutdownSignal`] and should return promptly once
+    /// it fires. Tasks still running at the shutdown deadline are aborted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime.
+    pub fn spawn<F, Fut>(&mut self, name: impl Into<String>, task: F)
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        debug!("Spawning background task '{}'", name);
+        let handle = tokio::spawn(task(self.shutdown_signal()));
+        self.tasks.push((name, handle));
+    }
+
+    /// Get a shutdown signal for tasks spawned outside the runtime
+    pub fn shutdown_signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            receiver: self.shutdown.subscribe(),
+        }
+    }
+
+    /// Number of background tasks that have not finished yet
+    pub fn running_tasks(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|(_, handle)| !handle.is_finished())
+            .count()
+    }
+
+    /// Get the registered bandwidth limiters
+    pub fn bandwidth_limiters(&self) -> &[BandwidthLimiter] {
+        &self.bandwidth_limiters
+    }
+
+    /// Collect the status of every pool, server group, limiter and task
+    ///
+    /// The snapshot is healthy when every registered server group has at
+    /// least one server up. Add application-owned job queues with
+    /// [`HealthSnapshot::with_queue`].
+    pub async fn health(&self) -> HealthSnapshot {
+        let servers: Vec<_> = self
+            .server_groups
+            .iter()
+            .map(|group| group.health())
+            .collect();
+        let healthy = servers
+            .iter()
+            .all(|group| group.iter().any(|server| server.up));
+
+        let mut bandwidth_limiters = Vec::with_capacity(self.bandwidth_limiters.len());
+        for limiter in &self.bandwidth_limiters {
+            bandwidth_limiters.push(BandwidthLimiterHealth::collect(limiter).await);
+        }
+
+        HealthSnapshot {
+            healthy,
+            pools: self.pools.iter().map(|pool| pool.health()).collect(),
+            servers: servers.into_iter().flatten().collect(),
+            queues: Vec::new(),
+            connection_limiters: self
+                .connection_limiters
+                .iter()
+                .map(ConnectionLimiterHealth::from)
+                .collect(),
+            bandwidth_limiters,
+            running_tasks: self.running_tasks(),
+        }
+    }
+
+    /// Shut everything down in order, waiting at most `timeout` in total
+    ///
+    /// Background tasks are signalled first; those still running at the
+    /// deadline are aborted. Then outstanding connection permits and pooled
+    /// connections are given the remaining time to be released before the
+    /// pools are closed. The report tells what did not drain in time.
+    pub async fn shutdown(self, timeout: Duration) -> ShutdownReport {
+        let deadline = Instant::now() + timeout;
+        let mut report = ShutdownReport::default();
+        debug!(
+            "Shutting down: {} tasks, {} limiters, {} pools, {} server groups",
+            self.tasks.len(),
+            self.connection_limiters.len(),
+            self.pools.len(),
+            self.server_groups.len()
+        );
+
+        self.shutdown.send_replace(true);
+
+        for (name, mut handle) in self.tasks {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(())) => report.tasks_stopped += 1,
+                Ok(Err(e)) => {
+                    warn!("Background task '{}' failed: {}", name, e);
+                    report.tasks_stopped += 1;
+                }
+                Err(_) => {
+                    warn!("Background task '{}' did not stop in time, aborting", name);
+                    handle.abort();
+                    report.tasks_aborted.push(name);
+                }
+            }
+        }
+
+        let limiters = &self.connection_limiters;
+        report.permits_outstanding = drain_until(deadline, || {
+            limiters.iter().map(ConnectionLimiter::in_use).sum()
+        })
+        .await;
+
+        let pools = &self.pools;
+        let groups = &self.server_groups;
+        report.connections_in_use = drain_until(deadline, || {
+            pools
+                .iter()
+                .map(|pool| pool.connections_in_use())
+                .chain(groups.iter().map(|group| group.connections_in_use()))
+                .sum()
+        })
+        .await;
+
+        // Mark every pool closed up front, so only the QUITs on idle
+        // connections are cut short by the deadline
+        for pool in &self.pools {
+            pool.mark_closed();
+        }
+        for group in &self.server_groups {
+            group.mark_closed();
+        }
+        let close = async {
+            for pool in &self.pools {
+                pool.close().await;
+            }
+            for group in &self.server_groups {
+                group.close().await;
+            }
+        };
+        if tokio::time::timeout_at(deadline, close).await.is_err() {
+            warn!("Shutdown deadline passed while closing idle connections");
+        }
+
+        if !report.is_clean() {
+            warn!("Shutdown deadline passed: {:?}", report);
+        }
+        report
+    }
+}
+
+/// Poll `outstanding` until it reaches zero or the deadline passes
+async fn drain_until<T, F>(deadline: Instant, outstanding: F) -> T
+where
+    T: Default + PartialEq,
+    F: Fn() -> T,
+{
+    loop {
+        let remaining = outstanding();
+        if remaining == T::default() || Instant::now() >= deadline {
+            return remaining;
+        }
+        tokio::time::sleep(
+            DRAIN_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_signals_tasks() {
+        let mut runtime = NntpRuntime::new();
+        for name in ["keepalive", "health"] {
+            runtime.spawn(name, |mut shutdown| async move {
+                shutdown.cancelled().await;
+            });
+        }
+        assert_eq!(runtime.running_tasks(), 2);
+
+        let report = runtime.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(report.tasks_stopped, 2);
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_aborts_stuck_tasks() {
+        let mut runtime = NntpRuntime::new();
+        runtime.spawn("stuck", |_| async {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+        });
+
+        let report = runtime.shutdown(Duration::from_millis(50)).await;
+        assert_eq!(report.tasks_stopped, 0);
+        assert_eq!(report.tasks_aborted, vec!["stuck".to_string()]);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_permits() {
+        let mut runtime = NntpRuntime::new();
+        let limiter = runtime.add_connection_limiter(ConnectionLimiter::new(2));
+        let permit = limiter.acquire().await;
+        let mut shutdown = runtime.shutdown_signal();
+
+        // The permit holder releases once shutdown starts
+        tokio::spawn(async move {
+            shutdown.cancelled().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(permit);
+        });
+
+        let report = runtime.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(report.permits_outstanding, 0);
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_reports_outstanding_permits() {
+        let mut runtime = NntpRuntime::new();
+        let limiter = runtime.add_connection_limiter(ConnectionLimiter::new(3));
+        let _permit = limiter.acquire().await;
+
+        let report = runtime.shutdown(Duration::from_millis(30)).await;
+        assert_eq!(report.permits_outstanding, 1);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_ignores_withheld_slots() {
+        let mut runtime = NntpRuntime::new();
+        let limiter = runtime.add_connection_limiter(ConnectionLimiter::new(3));
+        limiter.reduce(Duration::from_secs(60));
+
+        let report = runtime.shutdown(Duration::from_millis(30)).await;
+        assert_eq!(report.permits_outstanding, 0);
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_health_collects_limiters_and_tasks() {
+        let mut runtime = NntpRuntime::new();
+        let limiter = runtime.add_connection_limiter(ConnectionLimiter::new(2));
+        runtime.add_bandwidth_limiter(BandwidthLimiter::new(1_000_000, None));
+        runtime.spawn("idle", |mut shutdown| async move {
+            shutdown.cancelled().await;
+        });
+        let _permit = limiter.acquire().await;
+
+        let health = runtime.health().await;
+        assert!(health.healthy);
+        assert_eq!(health.connection_limiters[0].available, 1);
+        assert_eq!(health.bandwidth_limiters[0].bytes_per_second, 1_000_000);
+        assert_eq!(health.running_tasks, 1);
+        assert!(health.pools.is_empty() && health.servers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_signal_fires_when_runtime_dropped() {
+        let runtime = NntpRuntime::new();
+        let mut shutdown = runtime.shutdown_signal();
+        assert!(!shutdown.is_shutdown());
+        drop(runtime);
+        tokio::time::timeout(Duration::from_secs(1), shutdown.cancelled())
+            .await
+            .unwrap();
+    }
+}
@@ -4,14 +4,19 @@
 //! progress tracking, and priority queue support.
 
 use crate::NntpClient;
+use crate::commands::XoverEntry;
 use crate::error::{NntpError, Result};
 use crate::nzb::NzbSegment;
+use crate::response::NntpResponse;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
 /// Status of a segment fetch operation
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SegmentStatus {
     /// Segment is waiting to be fetched
     Pending,
@@ -27,6 +32,7 @@ pub enum SegmentStatus {
 
 /// Information about a segment fetch result
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct SegmentFetchResult {
     /// Index of the segment in the original segments slice
     pub segment_index: usize,
@@ -36,6 +42,8 @@ pub struct SegmentFetchResult {
     pub content: Option<Vec<String>>,
     /// Error message (if failed)
     pub error: Option<String>,
+    /// Requests made for the segment, including the one that succeeded
+    pub attempts: usize,
 }
 
 /// Progress information for segment fetching
@@ -92,6 +100,32 @@ impl FetchProgress {
     }
 }
 
+/// How a [`SegmentFetcher`] looks segments up on the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RetrievalMode {
+    /// `ARTICLE <message-id>`, which every server supports
+    #[default]
+    MessageId,
+    /// `GROUP`, then `ARTICLE <number>` for segments whose number is known
+    ///
+    /// Faster on servers that index articles by group. Segments without a
+    /// known number, and numbers that turn out to hold a different article,
+    /// are fetched by message-id.
+    ArticleNumber,
+    /// Article numbers until too many of them miss on this server
+    ///
+    /// After [`ADAPTIVE_MIN_LOOKUPS`] number lookups, the fetcher switches
+    /// to message-ids if more than [`ADAPTIVE_MAX_MISS_RATE`] of them missed.
+    Adaptive,
+}
+
+/// Number lookups an adaptive fetcher makes before judging a server
+pub const ADAPTIVE_MIN_LOOKUPS: u32 = 16;
+
+/// Share of missed number lookups at which an adaptive fetcher gives up on them
+pub const ADAPTIVE_MAX_MISS_RATE: f64 = 0.25;
+
 /// Configuration for segment fetching
 #[derive(Debug, Clone)]
 pub struct FetchConfig {
@@ -100,17 +134,91 @@ pub struct FetchConfig {
     /// Whether to skip segments that are not found (430 error)
     /// If true, NotFound segments will not cause an error
     pub skip_not_found: bool,
+    /// Maximum number of articles in flight when pipelining
+    ///
+    /// 1 (the default) fetches segments one at a time. Higher values pipeline
+    /// ARTICLE commands, bounded by `max_inflight_bytes`.
+    pub pipeline_depth: usize,
+    /// Maximum NZB-declared bytes in flight when pipelining
+    ///
+    /// Caps the pipeline by data volume rather than article count, so large
+    /// segments do not overrun the server's send window. A single segment is
+    /// always allowed, even if larger.
+    pub max_inflight_bytes: u64,
+    /// How segments are looked up when their article number is known
+    ///
+    /// Article numbers come from [`SegmentFetcher::with_overview`]. Pipelined
+    /// fetches always use message-ids.
+    pub retrieval: RetrievalMode,
+    /// Per-server overrides of `retrieval`, keyed by `host:port`
+    ///
+    /// Store [`SegmentFetcher::effective_retrieval`] here to keep what an
+    /// adaptive fetcher learned about a server for the next one.
+    pub server_retrieval: HashMap<String, RetrievalMode>,
 }
 
+/// Default in-flight byte budget for pipelined fetching (8 MiB)
+pub const DEFAULT_MAX_INFLIGHT_BYTES: u64 = 8 * 1024 * 1024;
+
 impl Default for FetchConfig {
     fn default() -> Self {
         Self {
             max_retries: 3,
             skip_not_found: false,
+            pipeline_depth: 1,
+            max_inflight_bytes: DEFAULT_MAX_INFLIGHT_BYTES,
+            retrieval: RetrievalMode::MessageId,
+            server_retrieval: HashMap::new(),
         }
     }
 }
 
+/// Article numbers of segments within one group
+#[derive(Debug, Clone)]
+struct ArticleNumbers {
+    group: String,
+    /// Message-ID without angle brackets to article number
+    numbers: HashMap<String, u64>,
+}
+
+/// Message-ID without its angle brackets, as NZB files often store it
+fn bare_message_id(id: &str) -> &str {
+    id.trim().trim_start_matches('<').trim_end_matches('>')
+}
+
+/// Fetch an article by number, checking that it is the expected one
+///
+/// Article numbers from an old overview can point at a different article
+/// after renumbering; that counts as not found. The number is checked with
+/// STAT first, so a mismatch costs a status line rather than the article.
+async fn fetch_by_number(
+    client: &mut NntpClient,
+    group: &str,
+    number: u64,
+    message_id: &str,
+) -> Result<NntpResponse> {
+    if client.current_group() != Some(group) {
+        client.select_group(group).await?;
+    }
+    let number = number.to_string();
+    let info = client.stat(&number).await?;
+    if bare_message_id(&info.message_id) != bare_message_id(message_id) {
+        return Err(NntpError::NoSuchArticle(message_id.to_string()));
+    }
+    client.fetch_article(&number).await
+}
+
+/// Whether a failed number lookup means the number is no good here
+///
+/// Anything else, such as an I/O error, is a problem with the connection
+/// that the message-id would run into as well.
+fn is_number_miss(error: &NntpError) -> bool {
+    matches!(
+        error,
+        NntpError::NoSuchArticle(_) | NntpError::NoSuchGroup(_) | NntpError::Protocol { .. }
+    )
+}
+
 /// Segment fetcher for downloading NZB segments
 ///
 /// # Example
@@ -142,16 +250,108 @@ pub struct SegmentFetcher {
     client: Arc<Mutex<NntpClient>>,
     config: FetchConfig,
     progress: Arc<Mutex<FetchProgress>>,
+    /// Retrieval mode for this server (after `server_retrieval` overrides)
+    retrieval: RetrievalMode,
+    article_numbers: Option<ArticleNumbers>,
+    number_lookups: AtomicU32,
+    number_misses: AtomicU32,
 }
 
 impl SegmentFetcher {
     /// Create a new segment fetcher with the given client and configuration
     pub fn new(client: NntpClient, config: FetchConfig) -> Self {
+        let retrieval = config
+            .server_retrieval
+            .get(&client.config().authority())
+            .copied()
+            .unwrap_or(config.retrieval);
         Self {
             client: Arc::new(Mutex::new(client)),
             config,
             progress: Arc::new(Mutex::new(FetchProgress::new(0, 0))),
+            retrieval,
+            article_numbers: None,
+            number_lookups: AtomicU32::new(0),
+            number_misses: AtomicU32::new(0),
+        }
+    }
+
+    /// Use the article numbers of an overview of `group`
+    ///
+    /// Segments are matched to entries by Message-ID, with or without angle
+    /// brackets. The numbers are only used if the retrieval mode prefers
+    /// them (see [`FetchConfig::retrieval`]).
+    pub fn with_overview(mut self, group: impl Into<String>, entries: &[XoverEntry]) -> Self {
+        let numbers = entries
+            .iter()
+            .filter(|entry| entry.article_number > 0)
+            .map(|entry| {
+                (
+                    bare_message_id(&entry.message_id).to_string(),
+                    entry.article_number,
+                )
+            })
+            .collect();
+        self.article_numbers = Some(ArticleNumbers {
+            group: group.into(),
+            numbers,
+        });
+        self
+    }
+
+    /// Retrieval mode in effect on this fetcher's server
+    ///
+    /// [`RetrievalMode::Adaptive`] turns into [`RetrievalMode::MessageId`]
+    /// once number lookups have missed too often.
+    pub fn effective_retrieval(&self) -> RetrievalMode {
+        let lookups = self.number_lookups.load(Ordering::Relaxed);
+        let misses = self.number_misses.load(Ordering::Relaxed);
+        match self.retrieval {
+            RetrievalMode::Adaptive
+                if lookups >= ADAPTIVE_MIN_LOOKUPS
+                    && f64::from(misses) > f64::from(lookups) * ADAPTIVE_MAX_MISS_RATE =>
+            {
+                RetrievalMode::MessageId
+            }
+            mode => mode,
+        }
+    }
+
+    /// Group and article number to fetch a segment by, if numbers are preferred
+    fn article_number(&self, segment: &NzbSegment) -> Option<(&str, u64)> {
+        if self.effective_retrieval() == RetrievalMode::MessageId {
+            return None;
+        }
+        let numbers = self.article_numbers.as_ref()?;
+        let number = numbers.numbers.get(bare_message_id(&segment.message_id))?;
+        Some((numbers.group.as_str(), *number))
+    }
+
+    /// Fetch a segment once, by article number if preferred and known
+    ///
+    /// A number lookup that misses falls back to the message-id; other
+    /// errors are returned. Also returns whether the server reports 430 for
+    /// articles still propagating.
+    async fn fetch_once(&self, segment: &NzbSegment) -> (Result<NntpResponse>, bool) {
+        let mut client = self.client.lock().await;
+        if let Some((group, number)) = self.article_number(segment) {
+            self.number_lookups.fetch_add(1, Ordering::Relaxed);
+            match fetch_by_number(&mut client, group, number, &segment.message_id).await {
+                Ok(response) => return (Ok(response), client.quirks().not_found_is_transient),
+                Err(e) if !is_number_miss(&e) => {
+                    return (Err(e), client.quirks().not_found_is_transient);
+                }
+                Err(e) => {
+                    debug!(
+                        "Segment {} not found as {} {}, using message-id: {}",
+                        segment.number, group, number, e
+                    );
+                    self.number_misses.fetch_add(1, Ordering::Relaxed);
+                }
+            }
         }
+        let result = client.fetch_article(&segment.message_id).await;
+        (result, client.quirks().not_found_is_transient)
     }
 
     /// Get the current progress
@@ -179,10 +379,7 @@ impl SegmentFetcher {
                 );
             }
 
-            let result = {
-                let mut client = self.client.lock().await;
-                client.fetch_article(&segment.message_id).await
-            };
+            let (result, not_found_is_transient) = self.fetch_once(segment).await;
             match result {
                 Ok(response) => {
                     debug!(
@@ -201,8 +398,20 @@ impl SegmentFetcher {
                         status: SegmentStatus::Completed,
                         content: Some(response.lines),
                         error: None,
+                        attempts: attempt + 1,
                     };
                 }
+                // Some servers report 430 while an article is still propagating
+                Err(e @ NntpError::NoSuchArticle(_))
+                    if not_found_is_transient && attempt < self.config.max_retries =>
+                {
+                    debug!(
+                        "Segment {} not found yet (attempt {}), retrying",
+                        segment.number,
+                        attempt + 1
+                    );
+                    last_error = Some(e);
+                }
                 Err(NntpError::NoSuchArticle(_)) => {
                     warn!(
                         "Segment {} not found: {}",
@@ -219,6 +428,7 @@ impl SegmentFetcher {
                         status: SegmentStatus::NotFound,
                         content: None,
                         error: Some(format!("Article not found: {}", segment.message_id)),
+                        attempts: attempt + 1,
                     };
                 }
                 Err(e) => {
@@ -263,6 +473,7 @@ impl SegmentFetcher {
             status: SegmentStatus::Failed,
             content: None,
             error: Some(error_msg),
+            attempts: self.config.max_retries + 1,
         }
     }
 
@@ -273,7 +484,9 @@ impl SegmentFetcher {
     /// # Errors
     ///
     /// Returns an error if any segment fails and `skip_not_found` is false,
-    /// or if required segments cannot be fetched.
+    /// or if required segments cannot be fetched. With pipelining, also
+    /// returns the error from [`NntpClient::reconnect`] if the connection
+    /// fails mid-pipeline and cannot be re-established.
     pub async fn fetch_segments(&self, segments: &[NzbSegment]) -> Result<Vec<SegmentFetchResult>> {
         // Initialize progress
         let total_bytes: u64 = segments.iter().map(|s| s.bytes).sum();
@@ -282,30 +495,126 @@ impl SegmentFetcher {
             *progress = FetchProgress::new(segments.len(), total_bytes);
         }
 
+        if self.config.pipeline_depth > 1 {
+            return self.fetch_segments_pipelined(segments).await;
+        }
+
         let mut results = Vec::with_capacity(segments.len());
 
         for (idx, segment) in segments.iter().enumerate() {
             let result = self.fetch_segment(segment, idx).await;
+            self.check_result(segment, &result)?;
+            results.push(result);
+        }
 
-            // Check if we should fail early
-            if !self.config.skip_not_found && result.status == SegmentStatus::NotFound {
-                return Err(NntpError::NoSuchArticle(segment.message_id.clone()));
-            }
+        Ok(results)
+    }
 
-            if result.status == SegmentStatus::Failed {
-                return Err(NntpError::Other(format!(
-                    "Failed to fetch segment {}: {}",
-                    segment.number,
-                    result.error.as_deref().unwrap_or("unknown error")
-                )));
-            }
+    /// Fetch segments through a size-bounded pipeline window
+    ///
+    /// Segments that fail in the pipeline for reasons other than a definite
+    /// 430 are fetched again individually with the usual retry logic. If the
+    /// pipeline itself fails, the connection is re-established first, since
+    /// responses to the rest of the window may still be unread.
+    async fn fetch_segments_pipelined(
+        &self,
+        segments: &[NzbSegment],
+    ) -> Result<Vec<SegmentFetchResult>> {
+        let articles: Vec<(&str, u64)> = segments
+            .iter()
+            .map(|segment| (segment.message_id.as_str(), segment.bytes))
+            .collect();
+
+        let (pipelined, not_found_is_transient) = {
+            let mut client = self.client.lock().await;
+            let pipelined = match client
+                .fetch_articles_pipelined_sized(
+                    &articles,
+                    self.config.pipeline_depth,
+                    self.config.max_inflight_bytes,
+                )
+                .await
+            {
+                Ok(pipelined) => pipelined,
+                Err(e) => {
+                    warn!(
+                        "Pipelined fetch failed, falling back to single fetches: {}",
+                        e
+                    );
+                    // Responses to the rest of the window may still be on the
+                    // wire; a single fetch must not read one of them
+                    client.reconnect().await?;
+                    Vec::new()
+                }
+            };
+            (pipelined, client.quirks().not_found_is_transient)
+        };
+
+        let mut outcomes = pipelined.into_iter();
+        let mut results = Vec::with_capacity(segments.len());
+        for (idx, segment) in segments.iter().enumerate() {
+            let result = match outcomes.next() {
+                Some(Ok(response)) => {
+                    let mut progress = self.progress.lock().await;
+                    progress.completed_segments += 1;
+                    progress.downloaded_bytes += segment.bytes;
+                    drop(progress);
+
+                    SegmentFetchResult {
+                        segment_index: idx,
+                        status: SegmentStatus::Completed,
+                        content: Some(binary_lines(&response.data)),
+                        error: None,
+                        attempts: 1,
+                    }
+                }
+                Some(Err(NntpError::NoSuchArticle(_))) if !not_found_is_transient => {
+                    warn!(
+                        "Segment {} not found: {}",
+                        segment.number, segment.message_id
+                    );
+                    self.progress.lock().await.not_found_segments += 1;
+
+                    SegmentFetchResult {
+                        segment_index: idx,
+                        status: SegmentStatus::NotFound,
+                        content: None,
+                        error: Some(format!("Article not found: {}", segment.message_id)),
+                        attempts: 1,
+                    }
+                }
+                outcome => {
+                    // A failed pipelined request counts as an attempt
+                    let mut result = self.fetch_segment(segment, idx).await;
+                    result.attempts += usize::from(outcome.is_some());
+                    result
+                }
+            };
 
+            self.check_result(segment, &result)?;
             results.push(result);
         }
 
         Ok(results)
     }
 
+    /// Turn a NotFound (unless skipped) or Failed result into an error
+    fn check_result(&self, segment: &NzbSegment, result: &SegmentFetchResult) -> Result<()> {
+        if !self.config.skip_not_found && result.status == SegmentStatus::NotFound {
+            return Err(NntpError::NoSuchArticle(segment.message_id.clone()));
+        }
+
+        if result.status == SegmentStatus::Failed {
+            return Err(NntpError::Other(format!(
+                "Failed to fetch segment {}: {}",
+                segment.number,
+                result.error.as_deref().unwrap_or("unknown error")
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Fetch segments with a priority order
     ///
     /// Segments are fetched in the order specified by `priority_indices`.
@@ -350,18 +659,7 @@ impl SegmentFetcher {
             let segment = &segments[idx];
             let result = self.fetch_segment(segment, idx).await;
 
-            // Check if we should fail early
-            if !self.config.skip_not_found && result.status == SegmentStatus::NotFound {
-                return Err(NntpError::NoSuchArticle(segment.message_id.clone()));
-            }
-
-            if result.status == SegmentStatus::Failed {
-                return Err(NntpError::Other(format!(
-                    "Failed to fetch segment {}: {}",
-                    segment.number,
-                    result.error.as_deref().unwrap_or("unknown error")
-                )));
-            }
+            self.check_result(segment, &result)?;
 
             results[idx] = Some(result);
         }
@@ -374,18 +672,7 @@ impl SegmentFetcher {
 
             let result = self.fetch_segment(segment, idx).await;
 
-            // Check if we should fail early
-            if !self.config.skip_not_found && result.status == SegmentStatus::NotFound {
-                return Err(NntpError::NoSuchArticle(segment.message_id.clone()));
-            }
-
-            if result.status == SegmentStatus::Failed {
-                return Err(NntpError::Other(format!(
-                    "Failed to fetch segment {}: {}",
-                    segment.number,
-                    result.error.as_deref().unwrap_or("unknown error")
-                )));
-            }
+            self.check_result(segment, &result)?;
 
             results[idx] = Some(result);
         }
@@ -404,6 +691,17 @@ impl SegmentFetcher {
     }
 }
 
+/// Split a binary article into lines the way the text reader does
+fn binary_lines(data: &[u8]) -> Vec<String> {
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    if data.is_empty() {
+        return Vec::new();
+    }
+    data.split(|&b| b == b'\n')
+        .map(|line| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -470,6 +768,25 @@ mod tests {
         let config = FetchConfig::default();
         assert_eq!(config.max_retries, 3);
         assert!(!config.skip_not_found);
+        assert_eq!(config.pipeline_depth, 1);
+        assert_eq!(config.max_inflight_bytes, DEFAULT_MAX_INFLIGHT_BYTES);
+        assert_eq!(config.retrieval, RetrievalMode::MessageId);
+    }
+
+    #[test]
+    fn test_bare_message_id() {
+        assert_eq!(bare_message_id("<a@b>"), bare_message_id("a@b"));
+        assert_eq!(bare_message_id(" <a@b> "), "a@b");
+    }
+
+    #[test]
+    fn test_binary_lines() {
+        assert_eq!(
+            binary_lines(b"Subject: x\r\n\r\nbody\r\n"),
+            vec!["Subject: x", "", "body"]
+        );
+        assert_eq!(binary_lines(b"no newline"), vec!["no newline"]);
+        assert!(binary_lines(b"").is_empty());
     }
 
     #[test]
@@ -486,6 +803,7 @@ mod tests {
             status: SegmentStatus::Completed,
             content: Some(vec!["line1".to_string(), "line2".to_string()]),
             error: None,
+            attempts: 1,
         };
 
         let cloned = result.clone();
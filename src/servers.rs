@@ -37,14 +37,35 @@
 //! # }
 //! ```
 
+use crate::cache::{ArticleCache, CachedBody, VerificationStatus};
+use crate::completeness::AvailabilitySweep;
+use crate::health::ServerHealth;
+use crate::nzb::Nzb;
 use crate::pool::NntpConnectionManager;
 use crate::{NntpError, NntpPool, Result, ServerConfig};
 use bb8::PooledConnection;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// A message-id with angle brackets (NZB files list them without)
+fn bracketed(message_id: &str) -> Cow<'_, str> {
+    if message_id.starts_with('<') {
+        Cow::Borrowed(message_id)
+    } else {
+        Cow::Owned(format!("<{}>", message_id))
+    }
+}
+
+/// STAT commands in flight per connection during an availability sweep
+const SWEEP_PIPELINE_DEPTH: usize = 64;
+
+/// Window over which recent download rates are measured
+const RATE_WINDOW: Duration = Duration::from_secs(5);
 
 /// Per-server performance statistics
 ///
@@ -135,6 +156,52 @@ impl ServerStats {
     }
 }
 
+/// Download rate over the current and the previous [`RATE_WINDOW`]
+#[derive(Debug)]
+struct RateWindow {
+    start: Instant,
+    bytes: u64,
+    previous_bytes: u64,
+    previous_duration: Duration,
+}
+
+impl RateWindow {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            bytes: 0,
+            previous_bytes: 0,
+            previous_duration: Duration::ZERO,
+        }
+    }
+
+    /// Start a new window once the current one is full
+    fn roll(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.start);
+        if elapsed >= RATE_WINDOW {
+            self.previous_bytes = self.bytes;
+            self.previous_duration = elapsed;
+            self.start = now;
+            self.bytes = 0;
+        }
+    }
+
+    fn record(&mut self, bytes: u64) {
+        self.roll(Instant::now());
+        self.bytes += bytes;
+    }
+
+    fn bytes_per_second(&mut self) -> u64 {
+        let now = Instant::now();
+        self.roll(now);
+        let duration = self.previous_duration + now.duration_since(self.start);
+        if duration.is_zero() {
+            return 0;
+        }
+        ((self.previous_bytes + self.bytes) as f64 / duration.as_secs_f64()) as u64
+    }
+}
+
 /// Thread-safe wrapper for ServerStats
 #[derive(Debug, Clone)]
 struct AtomicServerStats {
@@ -147,6 +214,7 @@ struct AtomicServerStats {
     last_success_time: Arc<Mutex<Option<Instant>>>,
     last_failure_time: Arc<Mutex<Option<Instant>>>,
     consecutive_failures: Arc<AtomicU32>,
+    rate: Arc<Mutex<RateWindow>>,
 }
 
 impl AtomicServerStats {
@@ -161,6 +229,7 @@ impl AtomicServerStats {
             last_success_time: Arc::new(Mutex::new(None)),
             last_failure_time: Arc::new(Mutex::new(None)),
             consecutive_failures: Arc::new(AtomicU32::new(0)),
+            rate: Arc::new(Mutex::new(RateWindow::new())),
         }
     }
 
@@ -169,6 +238,10 @@ impl AtomicServerStats {
         self.successful_requests.fetch_add(1, Ordering::Relaxed);
         self.total_bytes_downloaded
             .fetch_add(bytes, Ordering::Relaxed);
+        self.rate
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(bytes);
         *self
             .last_success_time
             .lock()
@@ -342,7 +415,7 @@ impl ServerGroup {
 
         let mut servers = Vec::new();
         for (config, priority) in configs.into_iter().zip(priorities.into_iter()) {
-            let server_id = format!("{}:{}", config.host, config.port);
+            let server_id = config.authority();
             let pool = NntpPool::new(config.clone(), max_pool_size).await?;
             servers.push(ServerEntry {
                 id: server_id.clone(),
@@ -450,6 +523,166 @@ impl ServerGroup {
         }
     }
 
+    /// Fetch an article body, healing damaged copies from other servers
+    ///
+    /// Returns the cached copy if a verified one exists. Otherwise asks each
+    /// server (in strategy order) that has not already supplied a copy, checks
+    /// the yEnc CRC32 of every body received and records it in `cache` with its
+    /// server and [`VerificationStatus`]. Stops at the first verified copy.
+    ///
+    /// The cache lock is only held between network operations, so one cache
+    /// can be shared by concurrent fetches.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - Message-ID of the article (with angle brackets)
+    /// * `cache` - Article cache shared across fetches
+    ///
+    /// # Returns
+    ///
+    /// The best copy available: verified if any server supplied an intact
+    /// body, otherwise an unverified or CRC-failed copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last server error if no server supplied a copy and none is
+    /// cached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nntp_rs::{ArticleCache, FailoverStrategy, ServerConfig, ServerGroup};
+    /// # use std::sync::Mutex;
+    /// # async fn example() -> nntp_rs::Result<()> {
+    /// # let group = ServerGroup::new(
+    /// #     vec![ServerConfig::tls("news.example.com", "user", "pass")],
+    /// #     vec![100],
+    /// #     FailoverStrategy::PrimaryWithFallback,
+    /// #     5,
+    /// # ).await?;
+    /// let cache = Mutex::new(ArticleCache::new(256 * 1024 * 1024));
+    /// let body = group.fetch_body_verified("<part1@example.com>", &cache).await?;
+    /// println!("{} bytes from {} ({:?})", body.data.len(), body.server_id, body.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_body_verified(
+        &self,
+        message_id: &str,
+        cache: &Mutex<ArticleCache>,
+    ) -> Result<CachedBody> {
+        let order: Vec<String> = self
+            .get_server_order()
+            .into_iter()
+            .map(|i| self.servers[i].id.clone())
+            .collect();
+        let candidates = {
+            let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(body) = cache.best(message_id)
+                && body.status == VerificationStatus::Verified
+            {
+                return Ok(body.clone());
+            }
+            cache.refetch_servers(message_id, &order)
+        };
+
+        let mut last_error = None;
+        let mut fallback = None;
+        for server_id in candidates {
+            let response = match self.get_connection_from(&server_id).await {
+                Ok(mut conn) => conn.fetch_body_binary(message_id).await,
+                Err(e) => Err(e),
+            };
+            let data = match response {
+                Ok(response) => response.data,
+                Err(e) => {
+                    if matches!(e, NntpError::Protocol { code: 430, .. }) {
+                        self.record_not_found(&server_id);
+                    }
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+
+            self.record_success(&server_id, data.len() as u64);
+            let body = CachedBody {
+                status: VerificationStatus::of_yenc(&data),
+                server_id,
+                data: data.into(),
+            };
+            cache.lock().unwrap_or_else(|e| e.into_inner()).put(
+                &body.server_id,
+                message_id,
+                body.data.clone(),
+                body.status,
+            );
+            if body.status == VerificationStatus::Verified {
+                return Ok(body);
+            }
+            debug!(
+                "{} from {} is {:?}, trying next server",
+                message_id, body.server_id, body.status
+            );
+            // Bodies too large for the cache are not stored; keep the best one here
+            if fallback
+                .as_ref()
+                .is_none_or(|f: &CachedBody| f.status == VerificationStatus::CrcFailed)
+            {
+                fallback = Some(body);
+            }
+        }
+
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.best(message_id).cloned().or(fallback).ok_or_else(|| {
+            last_error.unwrap_or_else(|| NntpError::NoSuchArticle(message_id.to_string()))
+        })
+    }
+
+    /// STAT every segment of `nzb` on every server
+    ///
+    /// Uses one connection per server, with pipelined STAT commands (see
+    /// [`NntpClient::stat_many`](crate::NntpClient::stat_many)), and checks
+    /// the servers in priority order. Feed the result to
+    /// [`CompletenessReport::from_sweep`](crate::completeness::CompletenessReport::from_sweep)
+    /// to judge the NZB before downloading it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error other than a missing article, e.g. a server
+    /// that cannot be reached.
+    pub async fn availability_sweep(&self, nzb: &Nzb) -> Result<AvailabilitySweep> {
+        let ids = self.server_ids();
+        let mut sweep = AvailabilitySweep::new(nzb, ids.clone());
+        let segments: Vec<(usize, usize)> = nzb
+            .files
+            .iter()
+            .enumerate()
+            .flat_map(|(f, file)| (0..file.segments.len()).map(move |s| (f, s)))
+            .collect();
+        let message_ids: Vec<Cow<'_, str>> = nzb
+            .files
+            .iter()
+            .flat_map(|file| &file.segments)
+            .map(|segment| bracketed(&segment.message_id))
+            .collect();
+        let message_ids: Vec<&str> = message_ids.iter().map(|id| id.as_ref()).collect();
+
+        for server_id in &ids {
+            let mut conn = self.get_connection_from(server_id).await?;
+            let results = conn.stat_many(&message_ids, SWEEP_PIPELINE_DEPTH).await?;
+            for (&(f, s), result) in segments.iter().zip(results) {
+                let found = match result {
+                    Ok(_) => true,
+                    Err(NntpError::NoSuchArticle(_)) => false,
+                    Err(e) => return Err(e),
+                };
+                sweep.set(server_id, f, s, found);
+            }
+            debug!("Availability sweep of {} done", server_id);
+        }
+        Ok(sweep)
+    }
+
     /// Get aggregate statistics for the server group
     pub fn stats(&self) -> GroupStats {
         let mut per_server_stats = HashMap::new();
@@ -471,6 +704,67 @@ impl ServerGroup {
         }
     }
 
+    /// Get the up/down state, recent speed and pool usage of every server
+    ///
+    /// Servers are listed in priority order. A server counts as down while it
+    /// is degraded (low availability or too many consecutive failures), the
+    /// same condition that makes failover skip it.
+    pub fn health(&self) -> Vec<ServerHealth> {
+        let now = Instant::now();
+        self.servers
+            .iter()
+            .map(|server| {
+                let stats = server.stats.snapshot();
+                let bytes_per_second = server
+                    .stats
+                    .rate
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .bytes_per_second();
+                ServerHealth {
+                    server_id: server.id.clone(),
+                    up: !stats.is_degraded(self.degraded_threshold, self.max_consecutive_failures),
+                    availability: stats.availability_score(),
+                    bytes_per_second,
+                    bytes_downloaded: stats.total_bytes_downloaded,
+                    successful_requests: stats.successful_requests,
+                    failed_requests: stats.failed_requests,
+                    not_found_requests: stats.not_found_requests,
+                    consecutive_failures: stats.consecutive_failures,
+                    since_last_success: stats.last_success_time.map(|t| now.duration_since(t)),
+                    since_last_failure: stats.last_failure_time.map(|t| now.duration_since(t)),
+                    pool: server.pool.health(),
+                }
+            })
+            .collect()
+    }
+
+    /// Close the pool of every server
+    ///
+    /// See [`NntpPool::close`]. Returns the number of idle connections closed.
+    pub async fn close(&self) -> usize {
+        let mut closed = 0;
+        for server in &self.servers {
+            closed += server.pool.close().await;
+        }
+        closed
+    }
+
+    /// Mark the pool of every server closed, see [`NntpPool::mark_closed`]
+    pub(crate) fn mark_closed(&self) {
+        for server in &self.servers {
+            server.pool.mark_closed();
+        }
+    }
+
+    /// Get the number of connections checked out across all server pools
+    pub fn connections_in_use(&self) -> u32 {
+        self.servers
+            .iter()
+            .map(|server| server.pool.connections_in_use())
+            .sum()
+    }
+
     /// Get statistics for a specific server
     pub fn server_stats(&self, server_id: &str) -> Option<ServerStats> {
         self.servers
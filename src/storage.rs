@@ -0,0 +1,809 @@
+//! Pluggable storage for caches and journals
+//!
+//! State that outlives a session — cached overview entries, group
+//! watermarks, the message-ids already seen, and download or upload
+//! journals — goes through one small key-value trait, [`Storage`], instead
+//! of a file format per feature. [`MemoryStorage`] and [`FileStorage`] are
+//! built in; embedders with their own database implement the trait and pass
+//! it to a [`StateStore`], which maps each kind of state to a namespace and
+//! an encoding.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::commands::GroupInfo;
+//! use nntp_rs::renumber::GroupWatermarks;
+//! use nntp_rs::storage::StateStore;
+//!
+//! # async fn example() -> nntp_rs::Result<()> {
+//! let store = StateStore::memory();
+//!
+//! let mut watermarks = GroupWatermarks::new();
+//! watermarks.observe("alt.test", GroupInfo { count: 3, first: 10, last: 12 });
+//! store.save_watermarks(&watermarks).await?;
+//! assert_eq!(store.load_watermarks().await?, watermarks);
+//!
+//! store.mark_seen("<a@example.com>").await?;
+//! assert!(store.is_seen("<a@example.com>").await?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::XoverEntry;
+use crate::cache::HeaderCache;
+use crate::commands::{GroupInfo, parse_xover_line};
+use crate::error::{NntpError, Result};
+use crate::filename;
+use crate::output::OutputSink;
+use crate::renumber::GroupWatermarks;
+
+/// Namespace of overview entries, keyed by newsgroup
+pub const HEADERS_NAMESPACE: &str = "headers";
+
+/// Namespace of group watermarks, keyed by newsgroup
+pub const WATERMARKS_NAMESPACE: &str = "watermarks";
+
+/// Namespace of seen message-ids, keyed by message-id
+pub const SEEN_NAMESPACE: &str = "seen";
+
+/// Namespace of job journals, keyed by job id
+pub const JOURNALS_NAMESPACE: &str = "journals";
+
+/// Namespace of subscriptions, keyed by newsgroup
+pub const SUBSCRIPTIONS_NAMESPACE: &str = "subscriptions";
+
+/// Namespace of deferred IHAVE offers, keyed by message-id
+pub const DEFERRED_NAMESPACE: &str = "deferred";
+
+/// Future returned by [`Storage`] methods
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Asynchronous key-value storage
+///
+/// Values are opaque bytes stored under a key within a namespace. Keys are
+/// arbitrary non-empty strings, message-ids included; implementations must
+/// keep distinct keys apart and return them unchanged from
+/// [`keys`](Self::keys).
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::storage::{MemoryStorage, Storage};
+///
+/// # async fn example() -> nntp_rs::Result<()> {
+/// let storage = MemoryStorage::new();
+/// storage.put("journals", "job-1", b"state".to_vec()).await?;
+/// assert_eq!(storage.get("journals", "job-1").await?, Some(b"state".to_vec()));
+/// assert_eq!(storage.keys("journals").await?, vec!["job-1"]);
+/// # Ok(())
+/// # }
+/// ```
+pub trait Storage: Send + Sync + fmt::Debug {
+    /// Value stored under `key`, if any
+    ///
+    /// # Errors
+    ///
+    /// Backend errors, returned unchanged to the caller.
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>>;
+
+    /// Store `value` under `key`, replacing any previous value
+    ///
+    /// # Errors
+    ///
+    /// Backend errors, returned unchanged to the caller.
+    fn put<'a>(&'a self, namespace: &'a str, key: &'a str, value: Vec<u8>)
+    -> StorageFuture<'a, ()>;
+
+    /// Remove `key`, returning whether it was stored
+    ///
+    /// # Errors
+    ///
+    /// Backend errors, returned unchanged to the caller.
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, bool>;
+
+    /// Every key stored in `namespace`, sorted
+    ///
+    /// # Errors
+    ///
+    /// Backend errors, returned unchanged to the caller.
+    fn keys<'a>(&'a self, namespace: &'a str) -> StorageFuture<'a, Vec<String>>;
+}
+
+/// Values by key, by namespace
+type Namespaces = HashMap<String, BTreeMap<String, Vec<u8>>>;
+
+/// Storage that lives as long as the process
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    namespaces: Mutex<Namespaces>,
+}
+
+impl MemoryStorage {
+    /// Create empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Namespaces) -> T) -> T {
+        f(&mut self.namespaces.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        let value = self.with(|map| map.get(namespace)?.get(key).cloned());
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn put<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+        value: Vec<u8>,
+    ) -> StorageFuture<'a, ()> {
+        self.with(|map| {
+            map.entry(namespace.to_string())
+                .or_default()
+                .insert(key.to_string(), value)
+        });
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, bool> {
+        let removed = self.with(|map| {
+            map.get_mut(namespace)
+                .and_then(|entries| entries.remove(key))
+                .is_some()
+        });
+        Box::pin(async move { Ok(removed) })
+    }
+
+    fn keys<'a>(&'a self, namespace: &'a str) -> StorageFuture<'a, Vec<String>> {
+        let keys = self.with(|map| {
+            map.get(namespace)
+                .map(|entries| entries.keys().cloned().collect())
+                .unwrap_or_default()
+        });
+        Box::pin(async move { Ok(keys) })
+    }
+}
+
+/// Storage in a directory, one file per key
+///
+/// Each namespace is a subdirectory of the root and each key a file in it,
+/// named with [`filename::sanitize`] so any key is a valid file name.
+/// Values are written to a temporary file in the root and renamed into
+/// place, so a crash leaves either the old or the new value. File I/O runs
+/// on Tokio's blocking thread pool.
+///
+/// Values pass through the transform of an [`OutputSink`] given with
+/// [`with_output`](Self::with_output), so state can be kept encrypted at
+/// rest like downloaded files.
+///
+/// Keys that differ only in case share a file on case-insensitive file
+/// systems. Large sets of small values (such as seen message-ids) are better
+/// kept in a database behind a custom [`Storage`].
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    root: PathBuf,
+    output: OutputSink,
+}
+
+/// Counter making temporary file names unique within the process
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl FileStorage {
+    /// Store files below `root`, which is created on first write
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            output: OutputSink::new(&root),
+            root,
+        }
+    }
+
+    /// Write and read values through the transform of `sink`
+    ///
+    /// Only the transform is used; values stay below the storage root.
+    #[must_use]
+    pub fn with_output(mut self, sink: &OutputSink) -> Self {
+        self.output = sink.in_dir(&self.root);
+        self
+    }
+
+    /// Directory the files are stored in
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file for `key` in `namespace`
+    fn path(&self, namespace: &str, key: &str) -> Result<PathBuf> {
+        Ok(self.namespace_dir(namespace)?.join(file_name(key)?))
+    }
+
+    fn namespace_dir(&self, namespace: &str) -> Result<PathBuf> {
+        Ok(self.root.join(file_name(namespace)?))
+    }
+}
+
+/// File name for a namespace or key, refusing empty ones
+fn file_name(name: &str) -> Result<String> {
+    if name.is_empty() {
+        return Err(NntpError::InvalidArgument(
+            "Storage namespaces and keys must not be empty".to_string(),
+        ));
+    }
+    Ok(filename::sanitize(name))
+}
+
+/// Run blocking file I/O off the async runtime
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| NntpError::Other(format!("Storage task failed: {}", e)))?
+        .map_err(NntpError::from)
+}
+
+impl Storage for FileStorage {
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            let path = self.path(namespace, key)?;
+            let output = self.output.clone();
+            blocking(move || {
+                let file = match std::fs::File::open(path) {
+                    Ok(file) => file,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+                let mut value = Vec::new();
+                output
+                    .wrap_reader(Box::new(file))?
+                    .read_to_end(&mut value)?;
+                Ok(Some(value))
+            })
+            .await
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+        value: Vec<u8>,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let path = self.path(namespace, key)?;
+            let temp = self.root.join(format!(
+                ".{}-{}.tmp",
+                std::process::id(),
+                TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let output = self.output.clone();
+            blocking(move || {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let write = || {
+                    let file = std::fs::File::create(&temp)?;
+                    let mut writer = output.wrap_writer(Box::new(io::BufWriter::new(file)))?;
+                    writer.write_all(&value)?;
+                    writer.finish()?;
+                    std::fs::rename(&temp, &path)
+                };
+                write().inspect_err(|_| {
+                    let _ = std::fs::remove_file(&temp);
+                })
+            })
+            .await
+        })
+    }
+
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, bool> {
+        Box::pin(async move {
+            let path = self.path(namespace, key)?;
+            blocking(move || match std::fs::remove_file(path) {
+                Ok(()) => Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(e),
+            })
+            .await
+        })
+    }
+
+    fn keys<'a>(&'a self, namespace: &'a str) -> StorageFuture<'a, Vec<String>> {
+        Box::pin(async move {
+            let dir = self.namespace_dir(namespace)?;
+            blocking(move || list_keys(&dir)).await
+        })
+    }
+}
+
+/// Keys of the files in `dir`, sorted; none if it does not exist
+fn list_keys(dir: &Path) -> io::Result<Vec<String>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut keys = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file()
+            && let Some(name) = entry.file_name().to_str()
+        {
+            keys.push(filename::restore(name));
+        }
+    }
+    keys.sort();
+    Ok(keys)
+}
+
+/// Typed access to client state kept in a [`Storage`]
+///
+/// Each kind of state has its own namespace ([`HEADERS_NAMESPACE`],
+/// [`WATERMARKS_NAMESPACE`], [`SEEN_NAMESPACE`], [`JOURNALS_NAMESPACE`],
+/// [`SUBSCRIPTIONS_NAMESPACE`], [`DEFERRED_NAMESPACE`]), so one backend can
+/// hold all of it. Overview entries are stored as overview lines and
+/// watermarks, subscriptions and deferred offers as text, so they need no
+/// `serde`; journals are stored as the bytes the caller serialized them to.
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    storage: Arc<dyn Storage>,
+}
+
+impl StateStore {
+    /// Keep state in `storage`
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Keep state in a new [`MemoryStorage`]
+    pub fn memory() -> Self {
+        Self::new(Arc::new(MemoryStorage::new()))
+    }
+
+    /// Keep state in a [`FileStorage`] below `root`
+    pub fn directory(root: impl Into<PathBuf>) -> Self {
+        Self::new(Arc::new(FileStorage::new(root)))
+    }
+
+    /// Backend the state is kept in
+    pub fn storage(&self) -> &Arc<dyn Storage> {
+        &self.storage
+    }
+
+    /// Store the overview entries of `group`, replacing earlier ones
+    ///
+    /// Tabs and line breaks inside fields become spaces, as servers do in
+    /// overview data. `source_id` is not stored.
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn save_headers(&self, group: &str, entries: &[XoverEntry]) -> Result<()> {
+        let mut data = String::new();
+        for entry in entries {
+            data.push_str(&overview_line(entry));
+            data.push('\n');
+        }
+        self.storage
+            .put(HEADERS_NAMESPACE, group, data.into_bytes())
+            .await
+    }
+
+    /// Overview entries stored for `group`, empty if there are none
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::Utf8`] / [`NntpError::InvalidResponse`] - The stored
+    ///   entries are corrupt
+    /// - Storage backend errors
+    pub async fn load_headers(&self, group: &str) -> Result<Vec<XoverEntry>> {
+        let Some(data) = self.storage.get(HEADERS_NAMESPACE, group).await? else {
+            return Ok(Vec::new());
+        };
+        String::from_utf8(data)?
+            .lines()
+            .map(parse_xover_line)
+            .collect()
+    }
+
+    /// Fill `cache` with the overview entries stored for `group`
+    ///
+    /// Returns the number of entries put into the cache.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`load_headers`](Self::load_headers).
+    pub async fn restore_headers(&self, group: &str, cache: &mut dyn HeaderCache) -> Result<usize> {
+        let entries = self.load_headers(group).await?;
+        let count = entries.len();
+        for entry in entries {
+            cache.put(entry.article_number, entry);
+        }
+        Ok(count)
+    }
+
+    /// Store `watermarks`, replacing every stored group
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn save_watermarks(&self, watermarks: &GroupWatermarks) -> Result<()> {
+        let records = watermarks.iter().map(|(group, info)| {
+            let mut value = format!("{} {} {}", info.count, info.first, info.last);
+            if watermarks.is_estimated(group) {
+                value.push_str(" estimated");
+            }
+            (group.to_string(), value)
+        });
+        self.replace_records(WATERMARKS_NAMESPACE, records.collect())
+            .await
+    }
+
+    /// Watermarks stored with [`save_watermarks`](Self::save_watermarks)
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::Utf8`] / [`NntpError::InvalidResponse`] - A stored
+    ///   group is corrupt
+    /// - Storage backend errors
+    pub async fn load_watermarks(&self) -> Result<GroupWatermarks> {
+        let mut watermarks = GroupWatermarks::new();
+        for (group, value) in self.records(WATERMARKS_NAMESPACE).await? {
+            let corrupt =
+                || NntpError::InvalidResponse(format!("Corrupt stored watermarks for {}", group));
+            match value.strip_suffix(" estimated") {
+                Some(value) => {
+                    let info = parse_group_info(value).ok_or_else(corrupt)?;
+                    watermarks.observe_listing(&group, info.first, info.last);
+                }
+                None => {
+                    let info = parse_group_info(&value).ok_or_else(corrupt)?;
+                    watermarks.observe(&group, info);
+                }
+            }
+        }
+        Ok(watermarks)
+    }
+
+    /// Replace every record in `namespace` with `records` of key and text
+    ///
+    /// For collections stored one record per key; the collection's module
+    /// owns the text format.
+    pub(crate) async fn replace_records(
+        &self,
+        namespace: &str,
+        records: Vec<(String, String)>,
+    ) -> Result<()> {
+        let keys: HashSet<&str> = records.iter().map(|(key, _)| key.as_str()).collect();
+        for key in self.storage.keys(namespace).await? {
+            if !keys.contains(key.as_str()) {
+                self.storage.delete(namespace, &key).await?;
+            }
+        }
+        for (key, value) in &records {
+            self.storage
+                .put(namespace, key, value.clone().into_bytes())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Every record in `namespace`, as key and text
+    pub(crate) async fn records(&self, namespace: &str) -> Result<Vec<(String, String)>> {
+        let mut records = Vec::new();
+        for key in self.storage.keys(namespace).await? {
+            if let Some(value) = self.storage.get(namespace, &key).await? {
+                records.push((key, String::from_utf8(value)?));
+            }
+        }
+        Ok(records)
+    }
+
+    /// Remember that `message_id` was seen
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn mark_seen(&self, message_id: &str) -> Result<()> {
+        self.storage
+            .put(SEEN_NAMESPACE, message_id, Vec::new())
+            .await
+    }
+
+    /// Check if `message_id` was marked as seen
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn is_seen(&self, message_id: &str) -> Result<bool> {
+        Ok(self
+            .storage
+            .get(SEEN_NAMESPACE, message_id)
+            .await?
+            .is_some())
+    }
+
+    /// Forget that `message_id` was seen, returning whether it was
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn forget_seen(&self, message_id: &str) -> Result<bool> {
+        self.storage.delete(SEEN_NAMESPACE, message_id).await
+    }
+
+    /// Store the journal of job `job_id`
+    ///
+    /// With the `serde` feature, [`DownloadJob`](crate::DownloadJob) and
+    /// [`UploadJob`](crate::UploadJob) serialize to any format the caller
+    /// picks; the bytes are stored as given.
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn save_journal(&self, job_id: &str, data: Vec<u8>) -> Result<()> {
+        self.storage.put(JOURNALS_NAMESPACE, job_id, data).await
+    }
+
+    /// Journal stored for job `job_id`, if any
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn load_journal(&self, job_id: &str) -> Result<Option<Vec<u8>>> {
+        self.storage.get(JOURNALS_NAMESPACE, job_id).await
+    }
+
+    /// Remove the journal of a finished job, returning whether it existed
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn remove_journal(&self, job_id: &str) -> Result<bool> {
+        self.storage.delete(JOURNALS_NAMESPACE, job_id).await
+    }
+
+    /// Ids of every job with a stored journal, e.g. to resume after a restart
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn journal_ids(&self) -> Result<Vec<String>> {
+        self.storage.keys(JOURNALS_NAMESPACE).await
+    }
+}
+
+/// Overview line for `entry`, in the field order of RFC 3977 Section 8.4
+fn overview_line(entry: &XoverEntry) -> String {
+    let clean = |field: &str| field.replace(['\t', '\r', '\n'], " ");
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        entry.article_number,
+        clean(&entry.subject),
+        clean(&entry.author),
+        clean(&entry.date),
+        clean(&entry.message_id),
+        clean(&entry.references),
+        entry.bytes,
+        entry.lines
+    )
+}
+
+/// Parse watermarks stored as "count first last"
+///
+/// Counts estimated from a listing are followed by " estimated".
+fn parse_group_info(value: &str) -> Option<GroupInfo> {
+    let mut numbers = value.split(' ').map(str::parse::<u64>);
+    let info = GroupInfo {
+        count: numbers.next()?.ok()?,
+        first: numbers.next()?.ok()?,
+        last: numbers.next()?.ok()?,
+    };
+    numbers.next().is_none().then_some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::LruHeaderCache;
+
+    fn entry(number: u64, subject: &str) -> XoverEntry {
+        XoverEntry {
+            article_number: number,
+            subject: subject.to_string(),
+            author: "poster@example.com".to_string(),
+            date: "Mon, 01 Jan 2024 00:00:00 +0000".to_string(),
+            timestamp: None,
+            message_id: format!("<{}@example.com>", number),
+            references: String::new(),
+            bytes: 1000,
+            lines: 20,
+            source_id: None,
+        }
+    }
+
+    async fn exercise(storage: &dyn Storage) {
+        assert_eq!(storage.get("ns", "<a/b@c>").await.unwrap(), None);
+        storage.put("ns", "<a/b@c>", b"one".to_vec()).await.unwrap();
+        storage.put("ns", "..", b"two".to_vec()).await.unwrap();
+        storage.put("ns", "..", b"three".to_vec()).await.unwrap();
+        storage.put("other", "x", Vec::new()).await.unwrap();
+
+        assert_eq!(
+            storage.get("ns", "<a/b@c>").await.unwrap(),
+            Some(b"one".to_vec())
+        );
+        assert_eq!(
+            storage.get("ns", "..").await.unwrap(),
+            Some(b"three".to_vec())
+        );
+        assert_eq!(storage.keys("ns").await.unwrap(), vec!["..", "<a/b@c>"]);
+        assert_eq!(storage.keys("missing").await.unwrap(), Vec::<String>::new());
+
+        assert!(storage.delete("ns", "..").await.unwrap());
+        assert!(!storage.delete("ns", "..").await.unwrap());
+        assert_eq!(storage.keys("ns").await.unwrap(), vec!["<a/b@c>"]);
+        assert_eq!(storage.keys("other").await.unwrap(), vec!["x"]);
+    }
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        exercise(&MemoryStorage::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_storage() {
+        let dir = std::env::temp_dir().join(format!("nntp-rs-storage-{}", std::process::id()));
+        let storage = FileStorage::new(&dir);
+        exercise(&storage).await;
+        assert!(matches!(
+            storage.put("ns", "", Vec::new()).await,
+            Err(NntpError::InvalidArgument(_))
+        ));
+
+        // Nothing is left behind but the stored files
+        let leftovers = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_type().unwrap().is_file())
+            .count();
+        assert_eq!(leftovers, 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Flips every bit, so stored files differ from the values
+    struct Invert;
+
+    struct InvertWriter(Box<dyn Write + Send>);
+
+    impl Write for InvertWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let inverted: Vec<u8> = buf.iter().map(|b| !b).collect();
+            self.0.write_all(&inverted)?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl crate::output::TransformWriter for InvertWriter {
+        fn finish(mut self: Box<Self>) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl crate::output::OutputTransform for Invert {
+        fn wrap(
+            &self,
+            writer: Box<dyn Write + Send>,
+        ) -> io::Result<Box<dyn crate::output::TransformWriter>> {
+            Ok(Box::new(InvertWriter(writer)))
+        }
+
+        fn wrap_reader(
+            &self,
+            mut reader: Box<dyn Read + Send>,
+        ) -> io::Result<Box<dyn Read + Send>> {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            let inverted: Vec<u8> = data.iter().map(|b| !b).collect();
+            Ok(Box::new(io::Cursor::new(inverted)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_storage_output_transform() {
+        let dir =
+            std::env::temp_dir().join(format!("nntp-rs-storage-transform-{}", std::process::id()));
+        let sink = OutputSink::new("/unused").with_transform(Arc::new(Invert));
+        let storage = FileStorage::new(&dir).with_output(&sink);
+        exercise(&storage).await;
+
+        // Values round-trip, but are not stored as given
+        storage.put("ns", "plain", b"state".to_vec()).await.unwrap();
+        let raw = FileStorage::new(&dir).get("ns", "plain").await.unwrap();
+        assert_eq!(raw, Some(b"state".iter().map(|b| !b).collect()));
+        assert_eq!(
+            storage.get("ns", "plain").await.unwrap(),
+            Some(b"state".to_vec())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_state_store() {
+        let store = StateStore::memory();
+
+        let entries = [entry(1, "First"), entry(2, "Tab\tand\r\nbreak")];
+        store.save_headers("alt.test", &entries).await.unwrap();
+        let loaded = store.load_headers("alt.test").await.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].subject, "Tab and  break");
+        assert_eq!(loaded[1].message_id, "<2@example.com>");
+        assert!(store.load_headers("alt.none").await.unwrap().is_empty());
+        let mut cache = LruHeaderCache::new(10);
+        assert_eq!(
+            store.restore_headers("alt.test", &mut cache).await.unwrap(),
+            2
+        );
+        assert!(cache.contains(&1) && cache.contains(&2));
+
+        let mut watermarks = GroupWatermarks::new();
+        let info = GroupInfo {
+            count: 3,
+            first: 10,
+            last: 12,
+        };
+        watermarks.observe("alt.test", info);
+        watermarks.observe("alt.gone", info);
+        watermarks.observe_listing("alt.listed", 5, 9);
+        store.save_watermarks(&watermarks).await.unwrap();
+        watermarks.forget("alt.gone");
+        store.save_watermarks(&watermarks).await.unwrap();
+        assert_eq!(store.load_watermarks().await.unwrap(), watermarks);
+
+        assert!(!store.is_seen("<x@y>").await.unwrap());
+        store.mark_seen("<x@y>").await.unwrap();
+        assert!(store.is_seen("<x@y>").await.unwrap());
+        assert!(store.forget_seen("<x@y>").await.unwrap());
+
+        store.save_journal("job-1", b"{}".to_vec()).await.unwrap();
+        assert_eq!(store.journal_ids().await.unwrap(), vec!["job-1"]);
+        assert_eq!(
+            store.load_journal("job-1").await.unwrap(),
+            Some(b"{}".to_vec())
+        );
+        assert!(store.remove_journal("job-1").await.unwrap());
+        assert_eq!(store.load_journal("job-1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_state_is_an_error() {
+        let store = StateStore::memory();
+        let storage = store.storage();
+        storage
+            .put(WATERMARKS_NAMESPACE, "alt.test", b"3 10".to_vec())
+            .await
+            .unwrap();
+        assert!(store.load_watermarks().await.is_err());
+        storage
+            .put(HEADERS_NAMESPACE, "alt.test", vec![0xff, 0xfe])
+            .await
+            .unwrap();
+        assert!(store.load_headers("alt.test").await.is_err());
+    }
+}
@@ -0,0 +1,557 @@
+//! Subscribed newsgroups and their settings
+//!
+//! [`Subscriptions`] is the list of groups an application follows, each with
+//! its own [`SubscriptionOptions`]: how far back to sync, how often to poll,
+//! which score file applies and what to download. It is kept in a
+//! [`StateStore`](crate::storage::StateStore) like the rest of the client
+//! state, and turns a subscription into the [`BackfillState`] that syncs it.
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::commands::GroupInfo;
+//! use nntp_rs::storage::StateStore;
+//! use nntp_rs::subscriptions::{SubscriptionOptions, Subscriptions};
+//! use std::time::{Duration, SystemTime};
+//!
+//! # async fn example() -> nntp_rs::Result<()> {
+//! let mut subscriptions = Subscriptions::new();
+//! subscriptions.subscribe(
+//!     "alt.binaries.test",
+//!     SubscriptionOptions::default()
+//!         .with_sync_depth(10_000)
+//!         .with_poll_interval(Duration::from_secs(60)),
+//! );
+//!
+//! let store = StateStore::memory();
+//! store.save_subscriptions(&subscriptions).await?;
+//! let subscriptions = store.load_subscriptions().await?;
+//!
+//! for subscription in subscriptions.due(SystemTime::now()) {
+//!     let info = GroupInfo { count: 50_000, first: 1, last: 50_000 };
+//!     let state = subscription.backfill_state(&info);
+//!     assert_eq!(state.remaining_articles(), 10_000);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::backfill::{BackfillOrder, BackfillState};
+use crate::commands::GroupInfo;
+use crate::error::{NntpError, Result};
+use crate::storage::{SUBSCRIPTIONS_NAMESPACE, StateStore};
+
+/// Poll interval of a subscription without one set
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// What to download from a subscribed group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DownloadPolicy {
+    /// Overview data only; articles are fetched when asked for
+    #[default]
+    HeadersOnly,
+    /// Overview data and every new article
+    Articles,
+    /// Nothing: the group stays subscribed but is not synced
+    Paused,
+}
+
+impl DownloadPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::HeadersOnly => "headers",
+            Self::Articles => "articles",
+            Self::Paused => "paused",
+        }
+    }
+}
+
+impl fmt::Display for DownloadPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DownloadPolicy {
+    type Err = NntpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "headers" => Ok(Self::HeadersOnly),
+            "articles" => Ok(Self::Articles),
+            "paused" => Ok(Self::Paused),
+            _ => Err(NntpError::InvalidResponse(format!(
+                "Unknown download policy: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Settings of one subscribed group
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubscriptionOptions {
+    /// Newest articles to sync (`None` syncs the whole group)
+    pub sync_depth: Option<u64>,
+    /// Time between polls for new articles
+    pub poll_interval: Duration,
+    /// Score file applied to the group's articles
+    pub score_file: Option<PathBuf>,
+    /// What to download
+    pub download: DownloadPolicy,
+}
+
+impl Default for SubscriptionOptions {
+    fn default() -> Self {
+        Self {
+            sync_depth: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            score_file: None,
+            download: DownloadPolicy::default(),
+        }
+    }
+}
+
+impl SubscriptionOptions {
+    /// Sync only the newest `depth` articles
+    pub fn with_sync_depth(mut self, depth: u64) -> Self {
+        self.sync_depth = Some(depth);
+        self
+    }
+
+    /// Poll for new articles every `interval`
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Apply the score file at `path`
+    pub fn with_score_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.score_file = Some(path.into());
+        self
+    }
+
+    /// Set what to download
+    pub fn with_download(mut self, download: DownloadPolicy) -> Self {
+        self.download = download;
+        self
+    }
+}
+
+/// A subscribed group
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subscription {
+    /// Newsgroup name
+    pub group: String,
+    /// Settings of the group
+    pub options: SubscriptionOptions,
+    /// When the group was last polled (`None` if never)
+    pub last_polled: Option<SystemTime>,
+}
+
+impl Subscription {
+    /// Check if the group should be polled at `now`
+    ///
+    /// Paused groups are never due; groups never polled always are.
+    pub fn is_due(&self, now: SystemTime) -> bool {
+        if self.options.download == DownloadPolicy::Paused {
+            return false;
+        }
+        self.last_polled.is_none_or(|polled| {
+            now.duration_since(polled)
+                .is_ok_and(|elapsed| elapsed >= self.options.poll_interval)
+        })
+    }
+
+    /// Backfill covering the articles to sync, given the group's watermarks
+    ///
+    /// With a sync depth, only the newest `sync_depth` articles up to the
+    /// high watermark are covered; a depth of 0 covers none.
+    pub fn backfill_state(&self, info: &GroupInfo) -> BackfillState {
+        let low = match self.options.sync_depth {
+            // Above `last` when the depth is 0, leaving an empty range
+            Some(depth) => info
+                .first
+                .max(info.last.saturating_add(1).saturating_sub(depth)),
+            None => info.first,
+        };
+        BackfillState::new(&self.group, BackfillOrder::NewestFirst).with_bounds(low, info.last)
+    }
+
+    /// Text form stored by [`StateStore`](crate::storage::StateStore)
+    pub(crate) fn encode(&self) -> String {
+        let options = &self.options;
+        let mut text = format!(
+            "poll_interval={}\ndownload={}\n",
+            encode_duration(options.poll_interval),
+            options.download
+        );
+        if let Some(depth) = options.sync_depth {
+            text.push_str(&format!("sync_depth={}\n", depth));
+        }
+        if let Some(path) = &options.score_file {
+            let path = path.display().to_string();
+            text.push_str(&format!("score_file={}\n", escape_value(&path)));
+        }
+        if let Some(since_epoch) = self
+            .last_polled
+            .and_then(|polled| polled.duration_since(UNIX_EPOCH).ok())
+        {
+            text.push_str(&format!("last_polled={}\n", encode_duration(since_epoch)));
+        }
+        text
+    }
+
+    /// Parse the text form of the subscription to `group`
+    ///
+    /// Unknown settings are skipped, so newer versions can add some.
+    pub(crate) fn decode(group: &str, text: &str) -> Result<Self> {
+        let invalid = |line: &str| {
+            NntpError::InvalidResponse(format!("Bad stored setting for {}: {:?}", group, line))
+        };
+        let number = |line: &str, value: &str| value.parse::<u64>().map_err(|_| invalid(line));
+        let duration =
+            |line: &str, value: &str| decode_duration(value).ok_or_else(|| invalid(line));
+
+        let mut subscription = Self {
+            group: group.to_string(),
+            options: SubscriptionOptions::default(),
+            last_polled: None,
+        };
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once('=').ok_or_else(|| invalid(line))?;
+            let options = &mut subscription.options;
+            match key {
+                "poll_interval" => options.poll_interval = duration(line, value)?,
+                "download" => options.download = value.parse()?,
+                "sync_depth" => options.sync_depth = Some(number(line, value)?),
+                "score_file" => {
+                    let path = unescape_value(value).ok_or_else(|| invalid(line))?;
+                    options.score_file = Some(PathBuf::from(path));
+                }
+                "last_polled" => {
+                    subscription.last_polled = Some(UNIX_EPOCH + duration(line, value)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(subscription)
+    }
+}
+
+/// Stored form of a duration: whole seconds, with nanoseconds after a dot
+/// when there are any
+fn encode_duration(duration: Duration) -> String {
+    match duration.subsec_nanos() {
+        0 => duration.as_secs().to_string(),
+        nanos => format!("{}.{:09}", duration.as_secs(), nanos),
+    }
+}
+
+/// Parse the stored form of a duration
+fn decode_duration(text: &str) -> Option<Duration> {
+    let (secs, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = format!("{:0<9}", fraction).parse().ok()?;
+    Some(Duration::new(secs.parse().ok()?, nanos))
+}
+
+/// Escape the characters that would break a `key=value` line
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\n' => escaped.push_str("%0A"),
+            '\r' => escaped.push_str("%0D"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Undo [`escape_value`]
+fn unescape_value(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('%') {
+        unescaped.push_str(&rest[..pos]);
+        let c = match rest.get(pos + 1..pos + 3)? {
+            "25" => '%',
+            "0A" => '\n',
+            "0D" => '\r',
+            _ => return None,
+        };
+        unescaped.push(c);
+        rest = &rest[pos + 3..];
+    }
+    unescaped.push_str(rest);
+    Some(unescaped)
+}
+
+/// The subscribed groups, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subscriptions {
+    groups: BTreeMap<String, Subscription>,
+}
+
+impl Subscriptions {
+    /// Create an empty list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to `group`, returning the previous subscription if any
+    ///
+    /// Resubscribing replaces the options but keeps the last poll time.
+    pub fn subscribe(
+        &mut self,
+        group: impl Into<String>,
+        options: SubscriptionOptions,
+    ) -> Option<Subscription> {
+        let group = group.into();
+        let last_polled = self.groups.get(&group).and_then(|s| s.last_polled);
+        self.groups.insert(
+            group.clone(),
+            Subscription {
+                group,
+                options,
+                last_polled,
+            },
+        )
+    }
+
+    /// Add a subscription as it is, e.g. one loaded from storage
+    pub fn insert(&mut self, subscription: Subscription) -> Option<Subscription> {
+        self.groups.insert(subscription.group.clone(), subscription)
+    }
+
+    /// Unsubscribe from `group`, returning its subscription
+    pub fn unsubscribe(&mut self, group: &str) -> Option<Subscription> {
+        self.groups.remove(group)
+    }
+
+    /// Subscription to `group`
+    pub fn get(&self, group: &str) -> Option<&Subscription> {
+        self.groups.get(group)
+    }
+
+    /// Subscription to `group`, to change its options
+    pub fn get_mut(&mut self, group: &str) -> Option<&mut Subscription> {
+        self.groups.get_mut(group)
+    }
+
+    /// Check if `group` is subscribed
+    pub fn is_subscribed(&self, group: &str) -> bool {
+        self.groups.contains_key(group)
+    }
+
+    /// Every subscription, sorted by group name
+    pub fn iter(&self) -> impl Iterator<Item = &Subscription> {
+        self.groups.values()
+    }
+
+    /// Subscriptions to poll at `now`, see [`Subscription::is_due`]
+    pub fn due(&self, now: SystemTime) -> Vec<&Subscription> {
+        self.iter().filter(|s| s.is_due(now)).collect()
+    }
+
+    /// Record that `group` was polled at `at`, returning false if it is not
+    /// subscribed
+    pub fn mark_polled(&mut self, group: &str, at: SystemTime) -> bool {
+        match self.groups.get_mut(group) {
+            Some(subscription) => {
+                subscription.last_polled = Some(at);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of subscribed groups
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Check if no group is subscribed
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+impl StateStore {
+    /// Store `subscriptions`, replacing every stored group
+    ///
+    /// # Errors
+    ///
+    /// Storage backend errors.
+    pub async fn save_subscriptions(&self, subscriptions: &Subscriptions) -> Result<()> {
+        let records = subscriptions
+            .iter()
+            .map(|subscription| (subscription.group.clone(), subscription.encode()));
+        self.replace_records(SUBSCRIPTIONS_NAMESPACE, records.collect())
+            .await
+    }
+
+    /// Subscriptions stored with [`save_subscriptions`](Self::save_subscriptions)
+    ///
+    /// # Errors
+    ///
+    /// - [`NntpError::Utf8`] / [`NntpError::InvalidResponse`] - A stored
+    ///   subscription is corrupt
+    /// - Storage backend errors
+    pub async fn load_subscriptions(&self) -> Result<Subscriptions> {
+        let mut subscriptions = Subscriptions::new();
+        for (group, value) in self.records(SUBSCRIPTIONS_NAMESPACE).await? {
+            subscriptions.insert(Subscription::decode(&group, &value)?);
+        }
+        Ok(subscriptions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_and_polled() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let mut subscriptions = Subscriptions::new();
+        let options = SubscriptionOptions::default().with_poll_interval(Duration::from_secs(60));
+        subscriptions.subscribe("alt.a", options.clone());
+        subscriptions.subscribe(
+            "alt.paused",
+            options.clone().with_download(DownloadPolicy::Paused),
+        );
+        assert_eq!(subscriptions.due(start).len(), 1);
+
+        assert!(subscriptions.mark_polled("alt.a", start));
+        assert!(!subscriptions.mark_polled("alt.none", start));
+        assert!(
+            subscriptions
+                .due(start + Duration::from_secs(59))
+                .is_empty()
+        );
+        assert_eq!(subscriptions.due(start + Duration::from_secs(60)).len(), 1);
+
+        // Resubscribing keeps the poll time
+        subscriptions.subscribe("alt.a", options.with_sync_depth(5));
+        assert_eq!(subscriptions.get("alt.a").unwrap().last_polled, Some(start));
+    }
+
+    #[test]
+    fn test_backfill_state_honours_depth() {
+        let info = GroupInfo {
+            count: 100,
+            first: 101,
+            last: 200,
+        };
+        let mut subscriptions = Subscriptions::new();
+        subscriptions.subscribe(
+            "alt.deep",
+            SubscriptionOptions::default().with_sync_depth(10),
+        );
+        subscriptions.subscribe(
+            "alt.shallow",
+            SubscriptionOptions::default().with_sync_depth(1000),
+        );
+        subscriptions.subscribe("alt.all", SubscriptionOptions::default());
+
+        let state = subscriptions.get("alt.deep").unwrap().backfill_state(&info);
+        assert_eq!((state.low, state.high), (Some(191), Some(200)));
+
+        subscriptions.subscribe(
+            "alt.none",
+            SubscriptionOptions::default().with_sync_depth(0),
+        );
+        let state = subscriptions.get("alt.none").unwrap().backfill_state(&info);
+        assert_eq!(state.remaining_articles(), 0);
+        subscriptions.subscribe("alt.one", SubscriptionOptions::default().with_sync_depth(1));
+        let state = subscriptions.get("alt.one").unwrap().backfill_state(&info);
+        assert_eq!(state.remaining(), vec![200..=200]);
+        for group in ["alt.shallow", "alt.all"] {
+            let state = subscriptions.get(group).unwrap().backfill_state(&info);
+            assert_eq!((state.low, state.high), (Some(101), Some(200)));
+        }
+    }
+
+    #[test]
+    fn test_encoding_keeps_awkward_values() {
+        let subscription = Subscription {
+            group: "alt.test".to_string(),
+            options: SubscriptionOptions::default()
+                .with_poll_interval(Duration::from_millis(1500))
+                .with_score_file("/scores/100%\nsync_depth=1\r"),
+            last_polled: Some(UNIX_EPOCH + Duration::new(1_700_000_000, 250_000_000)),
+        };
+        let text = subscription.encode();
+        assert_eq!(text.lines().count(), 4);
+        assert_eq!(
+            Subscription::decode("alt.test", &text).unwrap(),
+            subscription
+        );
+
+        // Whole seconds are stored as before
+        let plain = Subscription::decode("alt.test", "poll_interval=60\n").unwrap();
+        assert_eq!(plain.options.poll_interval, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_encoding_round_trips() {
+        let subscription = Subscription {
+            group: "alt.test".to_string(),
+            options: SubscriptionOptions::default()
+                .with_sync_depth(500)
+                .with_poll_interval(Duration::from_secs(30))
+                .with_score_file("/home/user/.scores/alt=test")
+                .with_download(DownloadPolicy::Articles),
+            last_polled: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        };
+        let text = subscription.encode();
+        assert_eq!(
+            Subscription::decode("alt.test", &text).unwrap(),
+            subscription
+        );
+
+        let plain = Subscription::decode("alt.test", "future_option=1\n").unwrap();
+        assert_eq!(plain.options, SubscriptionOptions::default());
+        assert!(Subscription::decode("alt.test", "download=everything").is_err());
+        assert!(Subscription::decode("alt.test", "sync_depth=-1").is_err());
+        assert!(Subscription::decode("alt.test", "poll_interval=1.5s").is_err());
+        assert!(Subscription::decode("alt.test", "score_file=%zz").is_err());
+        assert!(Subscription::decode("alt.test", "garbage").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_persist() {
+        let dir =
+            std::env::temp_dir().join(format!("nntp-rs-subscriptions-{}", std::process::id()));
+        let store = StateStore::directory(&dir);
+        let mut subscriptions = Subscriptions::new();
+        subscriptions.subscribe(
+            "alt.kept",
+            SubscriptionOptions::default().with_sync_depth(5),
+        );
+        subscriptions.subscribe("alt.dropped", SubscriptionOptions::default());
+        store.save_subscriptions(&subscriptions).await.unwrap();
+
+        subscriptions.unsubscribe("alt.dropped");
+        subscriptions.mark_polled("alt.kept", std::time::UNIX_EPOCH);
+        store.save_subscriptions(&subscriptions).await.unwrap();
+        assert_eq!(store.load_subscriptions().await.unwrap(), subscriptions);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
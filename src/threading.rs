@@ -0,0 +1,640 @@
+//! Reply threading
+//!
+//! Newsreaders show a group as discussion trees, built from the References
+//! header of each article (or its In-Reply-To, if it has no References).
+//! [`build_threads`] does this with Jamie Zawinski's algorithm
+//! (<https://www.jwz.org/doc/threading.html>):
+//!
+//! - Ancestors that are not in the input (expired, or not fetched) are kept
+//!   as placeholders, so their replies stay together; placeholders with no
+//!   replies left are dropped.
+//! - Reference loops and self-references are ignored.
+//! - Roots with the same subject, once "Re:" prefixes are stripped, are
+//!   gathered into one thread, for replies from clients that drop the
+//!   References header.
+//!
+//! Works on overview entries ([`XoverEntry`]), [`Headers`] and [`Article`]s,
+//! or any type implementing [`Threadable`].
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::threading::build_threads;
+//!
+//! # fn entry(id: &str, subject: &str, references: &str) -> nntp_rs::XoverEntry {
+//! #     nntp_rs::XoverEntry::new(0, id)
+//! #         .with_subject(subject)
+//! #         .with_references(references)
+//! # }
+//! let entries = vec![
+//!     entry("<1@x>", "Rust 2024", ""),
+//!     entry("<2@x>", "Re: Rust 2024", "<1@x>"),
+//!     entry("<3@x>", "Re: Rust 2024", "<1@x> <2@x>"),
+//!     entry("<4@x>", "Other topic", ""),
+//! ];
+//!
+//! let threads = build_threads(entries);
+//! assert_eq!(threads.len(), 2);
+//! assert_eq!(threads[0].message_count(), 3);
+//!
+//! let tree: Vec<(usize, &str)> = threads[0]
+//!     .iter()
+//!     .map(|(depth, entry)| (depth, entry.message_id.as_str()))
+//!     .collect();
+//! assert_eq!(tree, [(0, "<1@x>"), (1, "<2@x>"), (2, "<3@x>")]);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::article::{Article, Headers};
+use crate::commands::XoverEntry;
+
+/// An article that can be placed in a reply tree
+pub trait Threadable {
+    /// Message-ID of the article, angle brackets included
+    fn message_id(&self) -> &str;
+
+    /// Message-IDs of the article's ancestors, oldest first
+    ///
+    /// The References header, or the first Message-ID of In-Reply-To for
+    /// articles without one.
+    fn parent_ids(&self) -> Vec<&str>;
+
+    /// Subject line
+    fn subject(&self) -> &str;
+}
+
+impl Threadable for XoverEntry {
+    fn message_id(&self) -> &str {
+        &self.message_id
+    }
+
+    fn parent_ids(&self) -> Vec<&str> {
+        message_ids(&self.references)
+    }
+
+    fn subject(&self) -> &str {
+        &self.subject
+    }
+}
+
+impl Threadable for Headers {
+    fn message_id(&self) -> &str {
+        &self.message_id
+    }
+
+    fn parent_ids(&self) -> Vec<&str> {
+        match &self.references {
+            Some(references) if !references.is_empty() => {
+                references.iter().map(String::as_str).collect()
+            }
+            _ => self
+                .extra
+                .get("In-Reply-To")
+                .and_then(|in_reply_to| message_ids(in_reply_to).into_iter().next())
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn subject(&self) -> &str {
+        &self.subject
+    }
+}
+
+impl Threadable for Article {
+    fn message_id(&self) -> &str {
+        self.headers.message_id()
+    }
+
+    fn parent_ids(&self) -> Vec<&str> {
+        self.headers.parent_ids()
+    }
+
+    fn subject(&self) -> &str {
+        Threadable::subject(&self.headers)
+    }
+}
+
+/// A node of a reply tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thread<T> {
+    /// The article, or `None` for a placeholder
+    ///
+    /// Placeholders stand for an ancestor missing from the input, or group
+    /// root articles with the same subject.
+    pub item: Option<T>,
+    /// Message-ID of the article or missing ancestor; `None` for a
+    /// placeholder grouping articles by subject
+    pub message_id: Option<String>,
+    /// Replies, in input order
+    pub children: Vec<Thread<T>>,
+}
+
+impl<T> Thread<T> {
+    /// Check if this node is a placeholder rather than an article
+    pub fn is_placeholder(&self) -> bool {
+        self.item.is_none()
+    }
+
+    /// Number of articles in this subtree, placeholders excluded
+    pub fn message_count(&self) -> usize {
+        usize::from(self.item.is_some())
+            + self
+                .children
+                .iter()
+                .map(Thread::message_count)
+                .sum::<usize>()
+    }
+
+    /// Articles of this subtree depth-first, with their depth below this node
+    ///
+    /// Placeholders are skipped but still count as a level, so replies to a
+    /// missing article appear one level deeper than their siblings' parent.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        let mut stack = vec![(0, self)];
+        std::iter::from_fn(move || {
+            while let Some((depth, node)) = stack.pop() {
+                stack.extend(node.children.iter().rev().map(|child| (depth + 1, child)));
+                if let Some(item) = &node.item {
+                    return Some((depth, item));
+                }
+            }
+            None
+        })
+    }
+}
+
+/// Build reply trees from a group's articles
+///
+/// Returns the root of each thread. Roots and replies are ordered by the
+/// position of their earliest article in `items`, so passing overview
+/// entries in article-number order lists threads by when they started.
+pub fn build_threads<T: Threadable>(items: Vec<T>) -> Vec<Thread<T>> {
+    let mut table = ContainerTable::default();
+    for (index, item) in items.iter().enumerate() {
+        table.add(index, item);
+    }
+
+    let roots = (0..table.nodes.len())
+        .filter(|&node| table.nodes[node].parent.is_none())
+        .collect();
+    let roots = table.prune(roots, true);
+    let mut roots = table.group_by_subject(roots, &items);
+    table.sort(&mut roots);
+
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    roots
+        .into_iter()
+        .map(|root| table.take_thread(root, &mut items))
+        .collect()
+}
+
+/// Subject with any "Re:" prefixes removed
+///
+/// Strips "Re:", "RE:" and counted forms like "Re\[2\]:", repeatedly, along
+/// with surrounding whitespace.
+///
+/// ```
+/// use nntp_rs::threading::base_subject;
+///
+/// assert_eq!(base_subject("Re: RE[2]:  Rust 2024"), "Rust 2024");
+/// assert_eq!(base_subject("Regarding Rust"), "Regarding Rust");
+/// ```
+pub fn base_subject(subject: &str) -> &str {
+    let mut rest = subject.trim();
+    while let Some(stripped) = strip_reply_prefix(rest) {
+        rest = stripped.trim_start();
+    }
+    rest
+}
+
+/// `subject` without one leading "Re:" or "Re[n]:"
+fn strip_reply_prefix(subject: &str) -> Option<&str> {
+    if !subject.get(..2)?.eq_ignore_ascii_case("re") {
+        return None;
+    }
+    let mut rest = &subject[2..];
+    if let Some(counted) = rest.strip_prefix('[') {
+        let end = counted.find(']')?;
+        if !counted[..end].bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        rest = &counted[end + 1..];
+    }
+    rest.strip_prefix(':')
+}
+
+/// Check if a subject starts with a reply prefix
+fn is_reply_subject(subject: &str) -> bool {
+    base_subject(subject).len() != subject.trim().len()
+}
+
+/// Message-IDs in a References or In-Reply-To value
+fn message_ids(value: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        ids.push(&rest[start..=start + len]);
+        rest = &rest[start + len + 1..];
+    }
+    ids
+}
+
+/// A position in the reply tree (JWZ "container")
+#[derive(Debug, Default)]
+struct Container {
+    /// Index of the article in the input
+    item: Option<usize>,
+    id: Option<String>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// Containers of all articles and referenced Message-IDs
+#[derive(Debug, Default)]
+struct ContainerTable {
+    nodes: Vec<Container>,
+    by_id: HashMap<String, usize>,
+}
+
+impl ContainerTable {
+    /// Place an article and link the chain of its References
+    fn add<T: Threadable>(&mut self, index: usize, item: &T) {
+        let id = item.message_id();
+        let node = match self.by_id.get(id) {
+            Some(&node) if self.nodes[node].item.is_none() => node,
+            // A duplicate Message-ID gets a container of its own
+            Some(_) => self.push(id),
+            None => self.container(id),
+        };
+        self.nodes[node].item = Some(index);
+
+        let mut previous = None;
+        for reference in item.parent_ids() {
+            let container = self.container(reference);
+            if let Some(parent) = previous
+                && self.nodes[container].parent.is_none()
+                && !self.reaches(parent, container)
+            {
+                self.link(parent, container);
+            }
+            previous = Some(container);
+        }
+
+        // The article's own References are authoritative over any parent
+        // guessed from another article's References
+        self.unlink(node);
+        if let Some(parent) = previous
+            && !self.reaches(parent, node)
+        {
+            self.link(parent, node);
+        }
+    }
+
+    /// Container for `id`, created empty if it is new
+    fn container(&mut self, id: &str) -> usize {
+        if let Some(&node) = self.by_id.get(id) {
+            return node;
+        }
+        let node = self.push(id);
+        self.by_id.insert(id.to_string(), node);
+        node
+    }
+
+    fn push(&mut self, id: &str) -> usize {
+        self.nodes.push(Container {
+            id: Some(id.to_string()),
+            ..Container::default()
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Check if `ancestor` is `node` or above it
+    fn reaches(&self, node: usize, ancestor: usize) -> bool {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.nodes[node].parent;
+        }
+        false
+    }
+
+    fn link(&mut self, parent: usize, child: usize) {
+        self.nodes[child].parent = Some(parent);
+        self.nodes[parent].children.push(child);
+    }
+
+    fn unlink(&mut self, child: usize) {
+        if let Some(parent) = self.nodes[child].parent.take() {
+            self.nodes[parent].children.retain(|&node| node != child);
+        }
+    }
+
+    /// Drop placeholders without replies and lift the replies of the others
+    ///
+    /// A placeholder root stays if it holds several replies, which would
+    /// otherwise become unrelated threads. Parent links are stale afterwards.
+    fn prune(&mut self, siblings: Vec<usize>, at_root: bool) -> Vec<usize> {
+        let mut kept = Vec::with_capacity(siblings.len());
+        for node in siblings {
+            let children = std::mem::take(&mut self.nodes[node].children);
+            let children = self.prune(children, false);
+            if self.nodes[node].item.is_none() && (!at_root || children.len() <= 1) {
+                kept.extend(children);
+                continue;
+            }
+            self.nodes[node].children = children;
+            kept.push(node);
+        }
+        kept
+    }
+
+    /// Gather roots whose subjects match once "Re:" is stripped
+    fn group_by_subject<T: Threadable>(&mut self, roots: Vec<usize>, items: &[T]) -> Vec<usize> {
+        let mut by_subject: HashMap<&str, usize> = HashMap::new();
+        for &root in &roots {
+            let Some(subject) = self.base_subject_of(root, items) else {
+                continue;
+            };
+            let replace = by_subject.get(subject).is_none_or(|&current| {
+                let (placeholder, current_placeholder) = (
+                    self.nodes[root].item.is_none(),
+                    self.nodes[current].item.is_none(),
+                );
+                (placeholder && !current_placeholder)
+                    || (placeholder == current_placeholder
+                        && self.is_reply(current, items)
+                        && !self.is_reply(root, items))
+            });
+            if replace {
+                by_subject.insert(subject, root);
+            }
+        }
+
+        let mut kept = Vec::with_capacity(roots.len());
+        for root in roots {
+            let target = self
+                .base_subject_of(root, items)
+                .and_then(|subject| by_subject.get(subject).copied());
+            match target {
+                Some(target) if target != root => self.merge(target, root, items),
+                _ => kept.push(root),
+            }
+        }
+        kept
+    }
+
+    /// Merge root `node` into root `target`, the preferred one of its subject
+    fn merge<T: Threadable>(&mut self, target: usize, node: usize, items: &[T]) {
+        let target_placeholder = self.nodes[target].item.is_none();
+        if target_placeholder && self.nodes[node].item.is_none() {
+            let children = std::mem::take(&mut self.nodes[node].children);
+            self.nodes[target].children.extend(children);
+        } else if target_placeholder
+            || (!self.is_reply(target, items) && self.is_reply(node, items))
+        {
+            self.nodes[target].children.push(node);
+        } else {
+            // Neither is clearly the reply: make them siblings under a
+            // placeholder that takes the target's place
+            let moved = Container {
+                item: self.nodes[target].item.take(),
+                id: self.nodes[target].id.take(),
+                parent: None,
+                children: std::mem::take(&mut self.nodes[target].children),
+            };
+            self.nodes.push(moved);
+            let moved = self.nodes.len() - 1;
+            self.nodes[target].children = vec![moved, node];
+        }
+    }
+
+    /// Base subject of a root, from its first reply for a placeholder
+    fn base_subject_of<'a, T: Threadable>(&self, node: usize, items: &'a [T]) -> Option<&'a str> {
+        let subject = base_subject(self.subject_of(node, items)?);
+        (!subject.is_empty()).then_some(subject)
+    }
+
+    fn subject_of<'a, T: Threadable>(&self, node: usize, items: &'a [T]) -> Option<&'a str> {
+        let node = &self.nodes[node];
+        match node.item {
+            Some(index) => Some(items[index].subject()),
+            None => node
+                .children
+                .first()
+                .and_then(|&child| self.nodes[child].item)
+                .map(|index| items[index].subject()),
+        }
+    }
+
+    fn is_reply<T: Threadable>(&self, node: usize, items: &[T]) -> bool {
+        self.nodes[node]
+            .item
+            .is_some_and(|index| is_reply_subject(items[index].subject()))
+    }
+
+    /// Order siblings by their earliest article, returning that position
+    fn sort(&mut self, siblings: &mut [usize]) -> usize {
+        let mut keys = HashMap::with_capacity(siblings.len());
+        for &node in siblings.iter() {
+            let mut children = std::mem::take(&mut self.nodes[node].children);
+            let earliest = self.sort(&mut children);
+            self.nodes[node].children = children;
+            let key = self.nodes[node]
+                .item
+                .map_or(earliest, |item| item.min(earliest));
+            keys.insert(node, key);
+        }
+        siblings.sort_by_key(|node| keys[node]);
+        siblings.first().map_or(usize::MAX, |node| keys[node])
+    }
+
+    /// Move a sorted subtree out of the table, taking its articles
+    fn take_thread<T>(&mut self, node: usize, items: &mut [Option<T>]) -> Thread<T> {
+        let children = std::mem::take(&mut self.nodes[node].children);
+        Thread {
+            item: self.nodes[node].item.and_then(|index| items[index].take()),
+            message_id: self.nodes[node].id.take(),
+            children: children
+                .into_iter()
+                .map(|child| self.take_thread(child, items))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Post {
+        id: &'static str,
+        subject: &'static str,
+        references: &'static str,
+    }
+
+    impl Threadable for Post {
+        fn message_id(&self) -> &str {
+            self.id
+        }
+
+        fn parent_ids(&self) -> Vec<&str> {
+            message_ids(self.references)
+        }
+
+        fn subject(&self) -> &str {
+            self.subject
+        }
+    }
+
+    fn post(id: &'static str, subject: &'static str, references: &'static str) -> Post {
+        Post {
+            id,
+            subject,
+            references,
+        }
+    }
+
+    /// Threads as `(depth, id)` lists, placeholders shown by Message-ID or "*"
+    fn shape(threads: &[Thread<Post>]) -> Vec<Vec<(usize, &str)>> {
+        fn walk<'a>(node: &'a Thread<Post>, depth: usize, out: &mut Vec<(usize, &'a str)>) {
+            let id = match &node.item {
+                Some(post) => post.id,
+                None => node.message_id.as_deref().unwrap_or("*"),
+            };
+            out.push((depth, id));
+            for child in &node.children {
+                walk(child, depth + 1, out);
+            }
+        }
+        threads
+            .iter()
+            .map(|thread| {
+                let mut out = Vec::new();
+                walk(thread, 0, &mut out);
+                out
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_missing_parent_keeps_placeholder() {
+        let threads = build_threads(vec![
+            post("<b@x>", "Re: Q", "<a@x>"),
+            post("<c@x>", "Re: Q", "<a@x>"),
+        ]);
+        assert_eq!(
+            shape(&threads),
+            [vec![(0, "<a@x>"), (1, "<b@x>"), (1, "<c@x>")]]
+        );
+        assert!(threads[0].is_placeholder());
+        assert_eq!(threads[0].message_count(), 2);
+    }
+
+    #[test]
+    fn test_single_reply_to_missing_root_is_promoted() {
+        let threads = build_threads(vec![post("<c@x>", "Re: Q", "<a@x> <b@x>")]);
+        assert_eq!(shape(&threads), [vec![(0, "<c@x>")]]);
+    }
+
+    #[test]
+    fn test_missing_middle_is_skipped() {
+        let threads = build_threads(vec![
+            post("<a@x>", "Q", ""),
+            post("<c@x>", "Re: Q", "<a@x> <b@x>"),
+        ]);
+        assert_eq!(shape(&threads), [vec![(0, "<a@x>"), (1, "<c@x>")]]);
+    }
+
+    #[test]
+    fn test_reply_before_parent_in_input() {
+        let threads = build_threads(vec![
+            post("<b@x>", "Re: Q", "<a@x>"),
+            post("<a@x>", "Q", ""),
+        ]);
+        assert_eq!(shape(&threads), [vec![(0, "<a@x>"), (1, "<b@x>")]]);
+    }
+
+    #[test]
+    fn test_loops_are_ignored() {
+        let threads = build_threads(vec![
+            post("<a@x>", "Q", "<b@x>"),
+            post("<b@x>", "Q", "<a@x>"),
+            post("<c@x>", "Other", "<c@x>"),
+        ]);
+        assert_eq!(threads.iter().map(Thread::message_count).sum::<usize>(), 3);
+        assert_eq!(shape(&threads)[1], [(0, "<c@x>")]);
+    }
+
+    #[test]
+    fn test_subject_fallback_grouping() {
+        let threads = build_threads(vec![
+            post("<a@x>", "Q", ""),
+            post("<b@x>", "Re: Q", ""),
+            post("<c@x>", "Other", ""),
+            post("<d@x>", "Other", ""),
+        ]);
+        assert_eq!(
+            shape(&threads),
+            [
+                vec![(0, "<a@x>"), (1, "<b@x>")],
+                vec![(0, "*"), (1, "<c@x>"), (1, "<d@x>")],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_message_ids_are_kept() {
+        let threads = build_threads(vec![post("<a@x>", "Q", ""), post("<a@x>", "Q", "")]);
+        assert_eq!(threads.iter().map(Thread::message_count).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn test_iter_depths_through_placeholder() {
+        let threads = build_threads(vec![
+            post("<b@x>", "Re: Q", "<a@x>"),
+            post("<c@x>", "Re: Q", "<a@x> <b@x>"),
+            post("<d@x>", "Re: Q", "<a@x>"),
+        ]);
+        let ids: Vec<(usize, &str)> = threads[0]
+            .iter()
+            .map(|(depth, post)| (depth, post.id))
+            .collect();
+        assert_eq!(ids, [(1, "<b@x>"), (2, "<c@x>"), (1, "<d@x>")]);
+    }
+
+    #[test]
+    fn test_base_subject_and_message_ids() {
+        assert_eq!(base_subject("re:Re[12]: x"), "x");
+        assert_eq!(base_subject("Re[x]: y"), "Re[x]: y");
+        assert!(is_reply_subject("RE: x"));
+        assert!(!is_reply_subject("Red"));
+        assert_eq!(message_ids("<a@x>,<b@x>\t <c@x"), ["<a@x>", "<b@x>"]);
+    }
+
+    #[test]
+    fn test_headers_fall_back_to_in_reply_to() {
+        let mut headers = Headers::new(
+            "Mon, 20 Jan 2025 12:00:00 +0000".to_string(),
+            "poster@example.com".to_string(),
+            "<b@x>".to_string(),
+            vec!["misc.test".to_string()],
+            "not-for-mail".to_string(),
+            "Re: Q".to_string(),
+        );
+        headers
+            .extra
+            .insert("In-Reply-To".to_string(), "<a@x> (a's message)".to_string());
+        assert_eq!(headers.parent_ids(), ["<a@x>"]);
+
+        headers.references = Some(vec!["<r@x>".to_string()]);
+        assert_eq!(headers.parent_ids(), ["<r@x>"]);
+    }
+}
@@ -0,0 +1,160 @@
+//! uuencode decoding
+//!
+//! uuencode predates yEnc and MIME and is still found on older posts and in
+//! text groups. An encoded file starts with `begin <mode> <name>`, followed
+//! by lines whose first character gives the number of bytes they carry, and
+//! ends with `end`.
+
+use crate::error::{NntpError, Result};
+
+/// A file decoded from a uuencoded block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UuDecoded {
+    /// Unix permission bits from the `begin` line (e.g. `0o644`)
+    pub mode: u32,
+    /// File name from the `begin` line
+    pub name: String,
+    /// Decoded file contents
+    pub data: Vec<u8>,
+}
+
+/// Parse a `begin <mode> <name>` line
+///
+/// Returns `None` if the line is not a uuencode header.
+pub fn parse_begin(line: &str) -> Option<(u32, String)> {
+    let rest = line.trim_end_matches(['\r', '\n']).strip_prefix("begin ")?;
+    let (mode, name) = rest.split_once(' ')?;
+    if !(3..=4).contains(&mode.len()) {
+        return None;
+    }
+    let mode = u32::from_str_radix(mode, 8).ok()?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some((mode, name.to_string()))
+}
+
+/// Decode the first uuencoded block in `input`
+///
+/// Text before the `begin` line is skipped. A missing `end` line is
+/// tolerated, since it is often lost when posts are truncated.
+///
+/// # Errors
+///
+/// Returns [`NntpError::InvalidResponse`] if there is no `begin` line or a
+/// data line is shorter than its length character announces.
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::uuencode;
+///
+/// let encoded = b"begin 644 cat.txt\n#0V%T\n`\nend\n";
+/// let decoded = uuencode::decode(encoded).unwrap();
+/// assert_eq!(decoded.name, "cat.txt");
+/// assert_eq!(decoded.mode, 0o644);
+/// assert_eq!(decoded.data, b"Cat");
+/// ```
+pub fn decode(input: &[u8]) -> Result<UuDecoded> {
+    let mut lines = input.split(|&b| b == b'\n');
+    let (mode, name) = lines
+        .by_ref()
+        .find_map(|line| parse_begin(std::str::from_utf8(line).ok()?))
+        .ok_or_else(|| NntpError::InvalidResponse("No uuencode begin line".to_string()))?;
+
+    let mut data = Vec::new();
+    for line in lines {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line == b"end" {
+            break;
+        }
+        decode_line(line, &mut data)?;
+    }
+
+    Ok(UuDecoded { mode, name, data })
+}
+
+/// Value of one uuencoded character (`` ` `` stands for 0)
+fn sixbit(c: u8) -> u8 {
+    c.wrapping_sub(b' ') & 0x3f
+}
+
+/// Decode one data line, appending to `out`
+fn decode_line(line: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    let Some((&length, encoded)) = line.split_first() else {
+        return Ok(());
+    };
+    let length = usize::from(sixbit(length));
+    if length == 0 {
+        return Ok(());
+    }
+
+    // Some encoders strip trailing spaces, which encode zero bits
+    let needed = length.div_ceil(3) * 4;
+    if encoded.len() + 2 < needed {
+        return Err(NntpError::InvalidResponse(format!(
+            "uuencode line too short: {} bytes for {} decoded",
+            encoded.len(),
+            length
+        )));
+    }
+
+    let start = out.len();
+    for group in encoded.chunks(4).take(needed / 4) {
+        let mut chars = [0u8; 4];
+        for (slot, &c) in chars.iter_mut().zip(group) {
+            *slot = sixbit(c);
+        }
+        out.push((chars[0] << 2) | (chars[1] >> 4));
+        out.push((chars[1] << 4) | (chars[2] >> 2));
+        out.push((chars[2] << 6) | chars[3]);
+    }
+    out.truncate(start + length);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_begin() {
+        assert_eq!(
+            parse_begin("begin 644 file.bin\r\n"),
+            Some((0o644, "file.bin".to_string()))
+        );
+        assert_eq!(
+            parse_begin("begin 0755 my file.sh"),
+            Some((0o755, "my file.sh".to_string()))
+        );
+        assert_eq!(parse_begin("begin 644"), None);
+        assert_eq!(parse_begin("begin 9x4 file"), None);
+        assert_eq!(parse_begin("beginning of the story"), None);
+    }
+
+    #[test]
+    fn test_decode_multiple_lines() {
+        // "The quick brown fox jumps over the lazy dog" as produced by uuencode(1)
+        let encoded = b"Some preamble\r\n\
+begin 644 fox.txt\r\n\
+K5&AE('%U:6-K(&)R;W=N(&9O>\"!J=6UP<R!O=F5R('1H92!L87IY(&1O9P``\r\n\
+`\r\n\
+end\r\n";
+        let decoded = decode(encoded).unwrap();
+        assert_eq!(decoded.name, "fox.txt");
+        assert_eq!(decoded.data, b"The quick brown fox jumps over the lazy dog");
+    }
+
+    #[test]
+    fn test_decode_tolerates_stripped_padding_and_missing_end() {
+        let decoded = decode(b"begin 600 a\n!80\n").unwrap();
+        assert_eq!(decoded.data, b"a");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(decode(b"no begin here\n").is_err());
+        assert!(decode(b"begin 644 a\nM80\n").is_err());
+    }
+}
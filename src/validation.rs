@@ -4,7 +4,7 @@
 //! All validation follows RFC 5536 specifications.
 
 use crate::{NntpError, Result};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
 
 /// Configuration options for validation behavior
 ///
@@ -221,7 +221,8 @@ pub fn validate_newsgroup_name(newsgroup: &str) -> Result<()> {
 /// Parses an RFC 5322 date-time string into a `DateTime<Utc>`
 ///
 /// Supports RFC 5322 date-time format as specified in RFC 5536 Section 3.1.1.
-/// Also supports common variations found in the wild.
+/// Also supports common variations found in the wild; see
+/// [`parse_date_with_offset`].
 ///
 /// # Examples
 ///
@@ -237,25 +238,47 @@ pub fn validate_newsgroup_name(newsgroup: &str) -> Result<()> {
 /// parse_date("Tue, 20 Jan 2026 12:00:00 GMT").unwrap();
 /// ```
 pub fn parse_date(date_str: &str) -> Result<DateTime<Utc>> {
-    // Try parsing as RFC 2822/RFC 5322 format first
-    match DateTime::parse_from_rfc2822(date_str) {
-        Ok(dt) => Ok(dt.with_timezone(&Utc)),
-        Err(_) => {
-            // Try common variations
-            // Some servers use "GMT" instead of "+0000"
-            if date_str.contains("GMT") {
-                let normalized = date_str.replace("GMT", "+0000");
-                if let Ok(dt) = DateTime::parse_from_rfc2822(&normalized) {
-                    return Ok(dt.with_timezone(&Utc));
-                }
-            }
+    parse_date_with_offset(date_str).map(|dt| dt.with_timezone(&Utc))
+}
 
-            Err(NntpError::InvalidResponse(format!(
-                "Invalid date format: {} (expected RFC 5322 format)",
-                date_str
-            )))
+/// Parses an RFC 5322 date-time string, keeping its UTC offset
+///
+/// Accepts the variations [`parse_date`] does: obsolete zone names, "GMT"
+/// where a numeric zone belongs, and a trailing comment such as "(UTC)"
+/// that many posting agents append.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::validation::parse_date_with_offset;
+///
+/// let date = parse_date_with_offset("Tue, 20 Jan 2026 13:00:00 +0100 (CET)").unwrap();
+/// assert_eq!(date.offset().local_minus_utc(), 3600);
+/// ```
+pub fn parse_date_with_offset(date_str: &str) -> Result<DateTime<FixedOffset>> {
+    let date = date_str.trim();
+    // A trailing comment, usually the zone name, is not part of the date
+    let without_comment = date
+        .rfind('(')
+        .filter(|_| date.ends_with(')'))
+        .map(|start| date[..start].trim_end());
+
+    for candidate in [Some(date), without_comment].into_iter().flatten() {
+        if let Ok(dt) = DateTime::parse_from_rfc2822(candidate) {
+            return Ok(dt);
+        }
+        // Some servers use "GMT" instead of "+0000"
+        if candidate.contains("GMT")
+            && let Ok(dt) = DateTime::parse_from_rfc2822(&candidate.replace("GMT", "+0000"))
+        {
+            return Ok(dt);
         }
     }
+
+    Err(NntpError::InvalidResponse(format!(
+        "Invalid date format: {} (expected RFC 5322 format)",
+        date_str
+    )))
 }
 
 /// Validates a date according to the provided configuration
@@ -414,6 +437,8 @@ mod tests {
 
     #[test]
     fn test_parse_date_invalid() {
+        assert!(parse_date("Mon, 20 Jan 2025 12:00:00 +0000 (UTC)").is_ok());
+        assert!(parse_date("Mon, 20 Jan 2025 12:00:00 GMT (Greenwich)").is_ok());
         assert!(parse_date("not a date").is_err());
         assert!(parse_date("2025-01-20").is_err()); // ISO format, not RFC 5322
         assert!(parse_date("").is_err());
@@ -0,0 +1,117 @@
+//! Structured reports of data skipped or accepted despite defects
+//!
+//! Parsers here are lenient: a malformed overview line is skipped rather
+//! than failing the whole response, a yEnc part whose trailer disagrees with
+//! its data is still returned. Each such decision is recorded as a
+//! [`ParseWarning`] so applications can show data-quality problems to users
+//! instead of digging them out of logs.
+//!
+//! Warnings are found on the result where it has room for them
+//! ([`YencDecoded::warnings`](crate::YencDecoded::warnings),
+//! [`Par2File::warnings`](crate::Par2File::warnings)). Responses parsed by
+//! [`NntpClient`](crate::NntpClient) are collected on the connection (see
+//! [`NntpClient::take_parse_warnings`](crate::NntpClient::take_parse_warnings))
+//! and reported to
+//! [`ConnectionObserver::on_parse_warning`](crate::ConnectionObserver::on_parse_warning).
+
+use std::fmt;
+
+/// Something a lenient parser skipped or accepted despite a defect
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseWarning {
+    /// An XOVER/OVER line could not be parsed and was left out
+    SkippedOverviewLine {
+        /// The line as received
+        line: String,
+        /// Why it was rejected
+        reason: String,
+    },
+    /// An HDR line could not be parsed and was left out
+    SkippedHdrLine {
+        /// The line as received
+        line: String,
+        /// Why it was rejected
+        reason: String,
+    },
+    /// A PAR2 packet of an unknown type was ignored
+    UnknownPar2Packet {
+        /// Byte offset of the packet in the file
+        offset: u64,
+        /// Packet type field of the header
+        packet_type: [u8; 16],
+    },
+    /// Bytes after the last complete PAR2 packet were ignored
+    TrailingPar2Data {
+        /// Byte offset of the leftover data
+        offset: u64,
+        /// Number of bytes ignored
+        len: u64,
+    },
+    /// A yEnc trailer declared a size other than the decoded length
+    YencSizeMismatch {
+        /// `size` from the `=yend` line
+        declared: u64,
+        /// Bytes actually decoded
+        decoded: u64,
+    },
+    /// A body line began with a dot the server had not doubled
+    ///
+    /// RFC 3977 Section 3.1.1 requires servers to dot-stuff such lines. Either
+    /// the server does not, so lines sent as ".." have lost a dot, or the
+    /// body is damaged. A terminator-like line (a dot followed only by
+    /// whitespace) points to a truncated or garbled response.
+    UnstuffedDotLine {
+        /// Line number within the body (1-based)
+        line: u64,
+        /// Whether the line was a dot followed only by whitespace
+        terminator_like: bool,
+    },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SkippedOverviewLine { line, reason } => {
+                write!(f, "skipped overview line ({}): {}", reason, line)
+            }
+            Self::SkippedHdrLine { line, reason } => {
+                write!(f, "skipped HDR line ({}): {}", reason, line)
+            }
+            Self::UnknownPar2Packet {
+                offset,
+                packet_type,
+            } => write!(
+                f,
+                "ignored unknown PAR2 packet {:?} at offset {}",
+                String::from_utf8_lossy(packet_type),
+                offset
+            ),
+            Self::TrailingPar2Data { offset, len } => {
+                write!(
+                    f,
+                    "ignored {} trailing PAR2 bytes at offset {}",
+                    len, offset
+                )
+            }
+            Self::YencSizeMismatch { declared, decoded } => write!(
+                f,
+                "yEnc trailer declares {} bytes, decoded {}",
+                declared, decoded
+            ),
+            Self::UnstuffedDotLine {
+                line,
+                terminator_like,
+            } => write!(
+                f,
+                "body line {} starts with an unstuffed dot{}",
+                line,
+                if *terminator_like {
+                    " (terminator-like)"
+                } else {
+                    ""
+                }
+            ),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Wildmat pattern matching (RFC 3977 Section 4)
+//!
+//! A wildmat is a comma-separated list of patterns, each optionally negated
+//! with `!` (except the first). In a pattern, `*` matches any sequence of
+//! characters and `?` matches exactly one. A name matches the wildmat if the
+//! rightmost pattern that matches it is not negated.
+//!
+//! The server applies wildmats sent with LIST ACTIVE, NEWNEWS and friends;
+//! [`Wildmat`] applies them locally, e.g. to filter cached group lists, and
+//! checks patterns before they are sent.
+//!
+//! [`Wildmat`]: crate::wildmat::Wildmat
+//!
+//! # Example
+//!
+//! ```
+//! use nntp_rs::wildmat::Wildmat;
+//!
+//! let wildmat: Wildmat = "comp.*,!comp.test*,comp.test.rust".parse().unwrap();
+//! assert!(wildmat.matches("comp.lang.c"));
+//! assert!(!wildmat.matches("comp.test.misc"));
+//! assert!(wildmat.matches("comp.test.rust"));
+//! assert!(!wildmat.matches("alt.test"));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{NntpError, Result};
+
+/// One element of a pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    /// An exact character
+    Literal(char),
+    /// `?`: any single character
+    AnyChar,
+    /// `*`: any sequence of characters, including none
+    AnySequence,
+}
+
+/// One pattern of a wildmat, with its polarity
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    negated: bool,
+    items: Vec<Item>,
+}
+
+impl Pattern {
+    /// Match the whole of `name`, backtracking to the last `*` on a mismatch
+    fn matches(&self, name: &[char]) -> bool {
+        let (mut item, mut pos) = (0, 0);
+        // Position of the last `*` and the name position it currently covers up to
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while pos < name.len() {
+            match self.items.get(item) {
+                Some(Item::AnySequence) => {
+                    backtrack = Some((item, pos));
+                    item += 1;
+                }
+                Some(Item::AnyChar) => {
+                    item += 1;
+                    pos += 1;
+                }
+                Some(Item::Literal(c)) if *c == name[pos] => {
+                    item += 1;
+                    pos += 1;
+                }
+                _ => {
+                    let Some((star, covered)) = backtrack else {
+                        return false;
+                    };
+                    // Let the `*` swallow one more character and retry
+                    backtrack = Some((star, covered + 1));
+                    item = star + 1;
+                    pos = covered + 1;
+                }
+            }
+        }
+
+        self.items[item..]
+            .iter()
+            .all(|rest| *rest == Item::AnySequence)
+    }
+}
+
+/// A parsed and validated wildmat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wildmat {
+    source: String,
+    patterns: Vec<Pattern>,
+}
+
+impl Wildmat {
+    /// Parse a wildmat, rejecting anything RFC 3977 does not allow
+    ///
+    /// Besides `*` and `?`, the characters `[`, `\` and `]` are reserved,
+    /// and whitespace, control characters and empty patterns are invalid.
+    pub fn parse(wildmat: &str) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for (index, element) in wildmat.split(',').enumerate() {
+            let (negated, body) = match element.strip_prefix('!') {
+                Some(body) if index > 0 => (true, body),
+                Some(_) => {
+                    return Err(invalid(wildmat, "the first pattern cannot be negated"));
+                }
+                None => (false, element),
+            };
+            if body.is_empty() {
+                return Err(invalid(wildmat, "empty pattern"));
+            }
+            let items = body
+                .chars()
+                .map(|c| parse_item(wildmat, c))
+                .collect::<Result<_>>()?;
+            patterns.push(Pattern { negated, items });
+        }
+        Ok(Self {
+            source: wildmat.to_string(),
+            patterns,
+        })
+    }
+
+    /// Check if `name` matches
+    ///
+    /// The rightmost matching pattern decides; names no pattern matches do
+    /// not match.
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(&name))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+
+    /// The wildmat as given
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl FromStr for Wildmat {
+    type Err = NntpError;
+
+    fn from_str(wildmat: &str) -> Result<Self> {
+        Self::parse(wildmat)
+    }
+}
+
+impl fmt::Display for Wildmat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Check that `wildmat` is valid before sending it to a server
+///
+/// # Example
+///
+/// ```
+/// use nntp_rs::wildmat::validate;
+///
+/// assert!(validate("alt.binaries.*,!alt.binaries.test").is_ok());
+/// assert!(validate("alt.*\r\nQUIT").is_err());
+/// assert!(validate("comp.[a-c]*").is_err());
+/// ```
+pub fn validate(wildmat: &str) -> Result<()> {
+    Wildmat::parse(wildmat).map(|_| ())
+}
+
+/// Classify one character of a pattern
+fn parse_item(wildmat: &str, c: char) -> Result<Item> {
+    match c {
+        '*' => Ok(Item::AnySequence),
+        '?' => Ok(Item::AnyChar),
+        '[' | '\\' | ']' => Err(invalid(wildmat, "'[', '\\' and ']' are reserved")),
+        '!' => Err(invalid(
+            wildmat,
+            "'!' is only allowed at the start of a pattern",
+        )),
+        c if c.is_whitespace() || c.is_control() => Err(invalid(
+            wildmat,
+            "whitespace and control characters are not allowed",
+        )),
+        c => Ok(Item::Literal(c)),
+    }
+}
+
+fn invalid(wildmat: &str, reason: &str) -> NntpError {
+    NntpError::InvalidArgument(format!("Invalid wildmat {:?}: {}", wildmat, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(wildmat: &str, name: &str) -> bool {
+        Wildmat::parse(wildmat).unwrap().matches(name)
+    }
+
+    #[test]
+    fn test_single_pattern() {
+        assert!(matches("*", "comp.lang.rust"));
+        assert!(matches("*", ""));
+        assert!(matches("comp.*", "comp.lang.rust"));
+        assert!(!matches("comp.*", "alt.comp"));
+        assert!(matches("*.test", "alt.test"));
+        assert!(matches("comp.lang.?", "comp.lang.c"));
+        assert!(!matches("comp.lang.?", "comp.lang.cc"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(matches("a*b*c", "abcbc"));
+        assert!(!matches("a*b*c", "abcb"));
+        assert!(matches("**.?*", "x.y"));
+        assert!(!matches("alt.test", "alt.Test"));
+        // `?` is one character, not one byte
+        assert!(matches("de.?bung", "de.übung"));
+    }
+
+    #[test]
+    fn test_rightmost_match_wins() {
+        // Examples from RFC 3977 Section 4.4
+        let wildmat = "a*,!*b,*c*";
+        assert!(matches(wildmat, "aaa"));
+        assert!(!matches(wildmat, "abb"));
+        assert!(matches(wildmat, "ccb"));
+        assert!(!matches(wildmat, "xxx"));
+
+        let wildmat = "a*,!*b";
+        assert!(matches(wildmat, "aaa"));
+        assert!(!matches(wildmat, "abb"));
+        assert!(!matches(wildmat, "ccb"));
+        assert!(!matches(wildmat, "xxx"));
+
+        let wildmat = "!*b,a*";
+        assert!(Wildmat::parse(wildmat).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_wildmats() {
+        for wildmat in [
+            "",
+            "comp.*,",
+            ",comp.*",
+            "comp.*,!",
+            "!comp.*",
+            "comp.[abc]",
+            "comp\\.lang",
+            "comp.lang rust",
+            "alt.*\r\nQUIT",
+            "comp.!lang",
+        ] {
+            assert!(
+                validate(wildmat).is_err(),
+                "{:?} should be invalid",
+                wildmat
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let wildmat: Wildmat = "comp.*,!comp.test".parse().unwrap();
+        assert_eq!(wildmat.to_string(), "comp.*,!comp.test");
+        assert_eq!(wildmat.as_str(), "comp.*,!comp.test");
+    }
+}
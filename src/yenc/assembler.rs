@@ -195,6 +195,47 @@ impl YencMultipartAssembler {
         }
     }
 
+    /// CRC32 of the file bytes in `start..end` (0-based, end exclusive)
+    ///
+    /// Returns `None` if any byte in the range has not been received yet.
+    pub(crate) fn crc32_range(&self, start: u64, end: u64) -> Option<u32> {
+        // (begin, end, data) with 0-based, end-exclusive offsets
+        let mut ranges: Vec<(u64, u64, &[u8])> = self
+            .parts
+            .values()
+            .filter_map(|decoded| {
+                let part = decoded.part.as_ref()?;
+                Some((
+                    part.begin.checked_sub(1)?,
+                    part.end,
+                    decoded.data.as_slice(),
+                ))
+            })
+            .collect();
+        ranges.sort_unstable_by_key(|(begin, _, _)| *begin);
+
+        let mut hasher = Hasher::new();
+        let mut cursor = start;
+        for (begin, part_end, data) in ranges {
+            if cursor >= end {
+                break;
+            }
+            if part_end <= cursor {
+                continue;
+            }
+            if begin > cursor {
+                return None; // gap
+            }
+            let take_end = part_end.min(end);
+            let from = usize::try_from(cursor - begin).ok()?;
+            let to = usize::try_from(take_end - begin).ok()?;
+            hasher.update(data.get(from..to)?);
+            cursor = take_end;
+        }
+
+        (cursor >= end).then(|| hasher.finalize())
+    }
+
     /// Assemble all parts into final file data
     ///
     /// # Errors
@@ -213,7 +254,21 @@ impl YencMultipartAssembler {
             .total_size
             .ok_or_else(|| NntpError::InvalidResponse("No parts added yet".to_string()))?;
 
-        let mut result = vec![0u8; total_size as usize];
+        // Parts never overlap, so unless they add up to the declared size
+        // there is a gap; checking first also bounds the allocation below by
+        // the data actually received rather than a forged header size
+        let received: u64 = self.parts.values().map(|p| p.data.len() as u64).sum();
+        if received != total_size {
+            return Err(NntpError::InvalidResponse(format!(
+                "Parts contain {} bytes but the file size is {}",
+                received, total_size
+            )));
+        }
+        let total_len = usize::try_from(total_size).map_err(|_| NntpError::LengthOutOfRange {
+            context: "yEnc file size",
+            value: total_size,
+        })?;
+        let mut result = vec![0u8; total_len];
 
         // Sort parts by part number and assemble
         let mut sorted_parts: Vec<_> = self.parts.iter().collect();
@@ -221,18 +276,23 @@ impl YencMultipartAssembler {
 
         for (_part_num, decoded) in sorted_parts {
             if let Some(part_info) = &decoded.part {
-                // yEnc uses 1-based offsets
-                let begin = (part_info.begin - 1) as usize;
-                let end = part_info.end as usize;
-
                 // Validate range
-                if end > total_size as usize {
+                if part_info.begin == 0 || part_info.begin > part_info.end {
+                    return Err(NntpError::LengthOutOfRange {
+                        context: "yEnc part begin",
+                        value: part_info.begin,
+                    });
+                }
+                if part_info.end > total_size {
                     return Err(NntpError::InvalidResponse(format!(
                         "Part range {}-{} exceeds total size {}",
                         part_info.begin, part_info.end, total_size
                     )));
                 }
 
+                // yEnc uses 1-based offsets; both fit since end <= total_len
+                let begin = (part_info.begin - 1) as usize;
+                let end = part_info.end as usize;
                 let expected_len = end - begin;
                 if decoded.data.len() != expected_len {
                     return Err(NntpError::InvalidResponse(format!(
@@ -369,6 +429,24 @@ mod tests {
         assert_eq!(assembled, full_data);
     }
 
+    #[test]
+    fn test_assembler_forged_total_size() {
+        // Both parts arrive, but the header claims a far larger file: the
+        // gap must be reported instead of allocating the declared size
+        let total_size = u64::MAX / 2;
+        let part1 = encode(b"abcd", "big.bin", 128, Some((1, 2, 1, 4, total_size))).unwrap();
+        let part2 = encode(b"efgh", "big.bin", 128, Some((2, 2, 5, 8, total_size))).unwrap();
+
+        let mut assembler = YencMultipartAssembler::new();
+        assembler.add_part(decode(&part1).unwrap()).unwrap();
+        assembler.add_part(decode(&part2).unwrap()).unwrap();
+        assert!(assembler.is_complete());
+        assert!(matches!(
+            assembler.assemble(),
+            Err(NntpError::InvalidResponse(_))
+        ));
+    }
+
     #[test]
     fn test_assembler_missing_parts() {
         let data = b"Test";
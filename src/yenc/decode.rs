@@ -3,6 +3,7 @@ use crc32fast::Hasher;
 
 use super::params::{parse_ybegin, parse_yend, parse_ypart};
 use super::types::YencDecoded;
+use crate::warnings::ParseWarning;
 
 /// Decode yEnc encoded data
 ///
@@ -75,8 +76,12 @@ pub fn decode(input: &[u8]) -> Result<YencDecoded> {
         .trim_end_matches('\r');
     let trailer = parse_yend(trailer_str)?;
 
-    // Decode data lines
-    let mut decoded = Vec::with_capacity(trailer.size as usize);
+    // Decode data lines. The decoded size can never exceed the encoded input,
+    // so a forged size in the trailer cannot force a huge allocation
+    let capacity = usize::try_from(trailer.size)
+        .unwrap_or(usize::MAX)
+        .min(input.len());
+    let mut decoded = Vec::with_capacity(capacity);
     let mut hasher = Hasher::new();
 
     for line in &lines[data_start..trailer_idx] {
@@ -87,12 +92,21 @@ pub fn decode(input: &[u8]) -> Result<YencDecoded> {
     hasher.update(&decoded);
     let calculated_crc32 = hasher.finalize();
 
+    let mut warnings = Vec::new();
+    if decoded.len() as u64 != trailer.size {
+        warnings.push(ParseWarning::YencSizeMismatch {
+            declared: trailer.size,
+            decoded: decoded.len() as u64,
+        });
+    }
+
     Ok(YencDecoded {
         header,
         part,
         trailer,
         data: decoded,
         calculated_crc32,
+        warnings,
     })
 }
 
@@ -103,7 +117,7 @@ pub fn decode(input: &[u8]) -> Result<YencDecoded> {
 ///
 /// Escape sequences: =X means (X - 64 - 42) mod 256
 /// Critical escapes: NUL(0), TAB(9), LF(10), CR(13), SPACE(32), '='(61)
-fn decode_line_bytes(line: &[u8], output: &mut Vec<u8>) -> Result<()> {
+pub(crate) fn decode_line_bytes(line: &[u8], output: &mut Vec<u8>) -> Result<()> {
     let mut i = 0;
 
     while i < line.len() {
@@ -204,6 +218,56 @@ mod tests {
         assert_eq!(result.part.as_ref().unwrap().end, 384000);
     }
 
+    #[test]
+    fn test_decode_reports_size_mismatch() {
+        let input = b"=ybegin line=128 size=2 name=test.bin\n\
+                      k\n\
+                      =yend size=2\n";
+
+        let result = decode(input).unwrap();
+        assert_eq!(result.data, b"A");
+        assert_eq!(
+            result.warnings,
+            vec![ParseWarning::YencSizeMismatch {
+                declared: 2,
+                decoded: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_huge_declared_size() {
+        // A forged size must not be used to size the output buffer
+        let input = b"=ybegin line=128 size=18446744073709551615 name=test.bin\n\
+                      k\n\
+                      =yend size=18446744073709551615\n";
+
+        let result = decode(input).unwrap();
+        assert_eq!(result.data, b"A");
+        assert!(result.data.capacity() <= input.len());
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_part_range() {
+        let zero_begin = b"=ybegin part=1 line=128 size=10 name=a.bin\n\
+                           =ypart begin=0 end=5\n\
+                           k\n\
+                           =yend size=1\n";
+        assert!(matches!(
+            decode(zero_begin),
+            Err(NntpError::LengthOutOfRange { value: 0, .. })
+        ));
+
+        let reversed = b"=ybegin part=1 line=128 size=10 name=a.bin\n\
+                         =ypart begin=6 end=5\n\
+                         k\n\
+                         =yend size=1\n";
+        assert!(matches!(
+            decode(reversed),
+            Err(NntpError::LengthOutOfRange { value: 5, .. })
+        ));
+    }
+
     #[test]
     fn test_decode_line_basic() {
         let mut output = Vec::new();
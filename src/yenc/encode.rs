@@ -24,13 +24,32 @@ pub fn encode(
     line_length: usize,
     part_info: Option<(u32, u32, u64, u64, u64)>, // (part, total_parts, begin, end, total_file_size)
 ) -> Result<Vec<u8>> {
-    // Validate line length
+    encode_block(data, filename, line_length, part_info, None)
+}
+
+/// Check that a line length is within the limits of the yEnc draft
+pub(super) fn check_line_length(line_length: usize) -> Result<()> {
     if line_length == 0 || line_length > 997 {
-        return Err(NntpError::InvalidResponse(format!(
+        return Err(NntpError::InvalidArgument(format!(
             "Invalid line length: {} (must be 1-997)",
             line_length
         )));
     }
+    Ok(())
+}
+
+/// Encode one block, as [`encode`] does
+///
+/// `file_crc32` is the CRC32 of the whole file, written as `crc32=` in the
+/// trailer of a part next to its `pcrc32=`.
+pub(super) fn encode_block(
+    data: &[u8],
+    filename: &str,
+    line_length: usize,
+    part_info: Option<(u32, u32, u64, u64, u64)>,
+    file_crc32: Option<u32>,
+) -> Result<Vec<u8>> {
+    check_line_length(line_length)?;
 
     let mut output = Vec::new();
 
@@ -69,11 +88,22 @@ pub fn encode(
     let crc32 = hasher.finalize();
 
     // Generate =yend trailer
-    if part_info.is_some() {
-        // Multi-part: include pcrc32 (part CRC)
+    if let Some((part, ..)) = part_info {
+        // Multi-part: include the part number and pcrc32 (part CRC), and the
+        // file CRC if known
         output.extend_from_slice(
-            format!("=yend size={} pcrc32={:08x}\r\n", data.len(), crc32).as_bytes(),
+            format!(
+                "=yend size={} part={} pcrc32={:08x}",
+                data.len(),
+                part,
+                crc32
+            )
+            .as_bytes(),
         );
+        if let Some(file_crc32) = file_crc32 {
+            output.extend_from_slice(format!(" crc32={:08x}", file_crc32).as_bytes());
+        }
+        output.extend_from_slice(b"\r\n");
     } else {
         // Single-part: include crc32
         output.extend_from_slice(
@@ -249,7 +279,7 @@ mod tests {
         assert!(encoded_str.contains("=ybegin part=1 total=3"));
         assert!(encoded_str.contains(&format!("size={}", total_file_size)));
         assert!(encoded_str.contains("=ypart begin=1 end=384000"));
-        assert!(encoded_str.contains("pcrc32=")); // multi-part uses pcrc32
+        assert!(encoded_str.contains("=yend size=16 part=1 pcrc32=")); // multi-part uses pcrc32
 
         // Verify round-trip
         let decoded = decode(&encoded).unwrap();
@@ -8,11 +8,17 @@
 pub mod assembler;
 pub mod decode;
 pub mod encode;
+pub mod multipart;
 pub mod params;
+pub mod stream;
 pub mod types;
+pub mod writer;
 
 // Re-export public types and functions for backward compatibility
 pub use assembler::YencMultipartAssembler;
 pub use decode::decode;
 pub use encode::encode;
+pub use multipart::YencMultipartEncoder;
+pub use stream::StreamingDecoder;
 pub use types::{YencDecoded, YencEnd, YencHeader, YencPart};
+pub use writer::{write_part, write_part_async};
@@ -0,0 +1,267 @@
+//! Splitting a file into multi-part yEnc posts
+//!
+//! [`YencMultipartEncoder`] is the posting side of
+//! [`YencMultipartAssembler`](super::YencMultipartAssembler): it cuts a file
+//! into fixed-size parts and encodes each with the headers the assembler
+//! and other newsreaders use to put it back together.
+
+use crc32fast::Hasher;
+
+use crate::{NntpError, Result};
+
+use super::encode::{check_line_length, encode_block};
+
+/// Multi-part yEnc encoder
+///
+/// Each part is a complete yEnc block ready to be used as an article body:
+/// `=ybegin part= total= line= size= name=` with the size of the whole
+/// file, `=ypart begin= end=` with the 1-based, inclusive byte range of the
+/// part, and `=yend size= part= pcrc32= crc32=` with the CRC32 of the part
+/// and of the whole file. The file CRC is written on every part, so it can
+/// be checked whichever part a reader sees first.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::yenc::{self, YencMultipartAssembler, YencMultipartEncoder};
+///
+/// let file: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+/// let encoder = YencMultipartEncoder::new(&file, "file.bin", 4000, 128).unwrap();
+/// assert_eq!(encoder.total_parts(), 3);
+/// assert_eq!(encoder.part_range(3), Some((8001, 10_000)));
+///
+/// let mut assembler = YencMultipartAssembler::new();
+/// for part in encoder.parts() {
+///     assembler.add_part(yenc::decode(&part.unwrap()).unwrap()).unwrap();
+/// }
+/// let assembled = assembler.assemble().unwrap();
+/// assert_eq!(assembled, file);
+/// assert!(assembler.verify_final_crc32(&assembled));
+/// ```
+#[derive(Debug, Clone)]
+pub struct YencMultipartEncoder<'a> {
+    data: &'a [u8],
+    filename: String,
+    part_size: usize,
+    line_length: usize,
+    total_parts: u32,
+    crc32: u32,
+}
+
+impl<'a> YencMultipartEncoder<'a> {
+    /// Create an encoder splitting `data` into parts of `part_size` bytes
+    ///
+    /// The last part holds what is left and may be shorter. `line_length`
+    /// is the encoded line length, as for [`encode`](super::encode());
+    /// 128 is common. The CRC32 of `data` is computed here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidArgument`] if `data` is empty,
+    /// `part_size` is zero, `line_length` is not 1-997, or the file would
+    /// need more than `u32::MAX` parts.
+    pub fn new(
+        data: &'a [u8],
+        filename: impl Into<String>,
+        part_size: usize,
+        line_length: usize,
+    ) -> Result<Self> {
+        check_line_length(line_length)?;
+        if data.is_empty() {
+            return Err(NntpError::InvalidArgument(
+                "Cannot split an empty file into yEnc parts".to_string(),
+            ));
+        }
+        if part_size == 0 {
+            return Err(NntpError::InvalidArgument(
+                "yEnc part size must not be zero".to_string(),
+            ));
+        }
+        let total_parts = u32::try_from(data.len().div_ceil(part_size)).map_err(|_| {
+            NntpError::InvalidArgument(format!(
+                "Part size {} splits {} bytes into too many parts",
+                part_size,
+                data.len()
+            ))
+        })?;
+
+        let mut hasher = Hasher::new();
+        hasher.update(data);
+        Ok(Self {
+            data,
+            filename: filename.into(),
+            part_size,
+            line_length,
+            total_parts,
+            crc32: hasher.finalize(),
+        })
+    }
+
+    /// Number of parts the file is split into
+    pub fn total_parts(&self) -> u32 {
+        self.total_parts
+    }
+
+    /// CRC32 of the whole file
+    pub fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
+    /// Name written in the `=ybegin` lines
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Byte range of a part as written in its `=ypart` line (1-based, inclusive)
+    ///
+    /// Returns `None` if `part` is not between 1 and
+    /// [`total_parts`](Self::total_parts).
+    pub fn part_range(&self, part: u32) -> Option<(u64, u64)> {
+        let (start, end) = self.part_bounds(part)?;
+        Some((start as u64 + 1, end as u64))
+    }
+
+    /// Encode one part, numbered from 1
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidArgument`] if `part` is not between 1
+    /// and [`total_parts`](Self::total_parts).
+    pub fn encode_part(&self, part: u32) -> Result<Vec<u8>> {
+        let (start, end) = self.part_bounds(part).ok_or_else(|| {
+            NntpError::InvalidArgument(format!(
+                "yEnc part {} out of range (1-{})",
+                part, self.total_parts
+            ))
+        })?;
+        encode_block(
+            &self.data[start..end],
+            &self.filename,
+            self.line_length,
+            Some((
+                part,
+                self.total_parts,
+                start as u64 + 1,
+                end as u64,
+                self.data.len() as u64,
+            )),
+            Some(self.crc32),
+        )
+    }
+
+    /// Encode every part in order
+    ///
+    /// Parts are encoded as the iterator is advanced, so only one is held
+    /// at a time.
+    pub fn parts(&self) -> impl Iterator<Item = Result<Vec<u8>>> + '_ {
+        (1..=self.total_parts).map(|part| self.encode_part(part))
+    }
+
+    /// Offsets of a part in `data` (0-based, end exclusive)
+    fn part_bounds(&self, part: u32) -> Option<(usize, usize)> {
+        if part == 0 || part > self.total_parts {
+            return None;
+        }
+        let start = (part as usize - 1) * self.part_size;
+        Some((start, (start + self.part_size).min(self.data.len())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yenc::{YencMultipartAssembler, decode};
+
+    fn file(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn test_parts_round_trip() {
+        let data = file(1000);
+        let encoder = YencMultipartEncoder::new(&data, "data.bin", 300, 64).unwrap();
+        assert_eq!(encoder.total_parts(), 4);
+
+        let mut assembler = YencMultipartAssembler::new();
+        // Out of order, as parts often arrive
+        for part in [3, 1, 4, 2] {
+            let decoded = decode(&encoder.encode_part(part).unwrap()).unwrap();
+            assert_eq!(decoded.header.part, Some(part));
+            assert_eq!(decoded.header.total, Some(4));
+            assert_eq!(decoded.header.size, 1000);
+            assert_eq!(decoded.trailer.crc32, Some(encoder.crc32()));
+            assert_eq!(decoded.verify_crc32(), Some(true));
+            let range = decoded.part.as_ref().unwrap();
+            assert_eq!(Some((range.begin, range.end)), encoder.part_range(part));
+            assembler.add_part(decoded).unwrap();
+        }
+        let assembled = assembler.assemble().unwrap();
+        assert_eq!(assembled, data);
+        assert!(assembler.verify_final_crc32(&assembled));
+    }
+
+    #[test]
+    fn test_part_ranges() {
+        let data = file(10);
+        let encoder = YencMultipartEncoder::new(&data, "ten.bin", 4, 128).unwrap();
+        assert_eq!(encoder.total_parts(), 3);
+        assert_eq!(encoder.part_range(1), Some((1, 4)));
+        assert_eq!(encoder.part_range(2), Some((5, 8)));
+        assert_eq!(encoder.part_range(3), Some((9, 10)));
+        assert_eq!(encoder.part_range(0), None);
+        assert_eq!(encoder.part_range(4), None);
+        assert!(matches!(
+            encoder.encode_part(4),
+            Err(NntpError::InvalidArgument(_))
+        ));
+
+        let exact = YencMultipartEncoder::new(&data, "ten.bin", 5, 128).unwrap();
+        assert_eq!(exact.total_parts(), 2);
+        assert_eq!(exact.part_range(2), Some((6, 10)));
+    }
+
+    #[test]
+    fn test_headers() {
+        let data = file(10);
+        let encoder = YencMultipartEncoder::new(&data, "ten.bin", 6, 128).unwrap();
+        let parts: Vec<_> = encoder.parts().map(Result::unwrap).collect();
+        assert_eq!(parts.len(), 2);
+        let text = String::from_utf8_lossy(&parts[1]);
+        assert!(text.starts_with(
+            "=ybegin part=2 total=2 line=128 size=10 name=ten.bin\r\n=ypart begin=7 end=10\r\n"
+        ));
+        assert!(text.ends_with(&format!(" crc32={:08x}\r\n", encoder.crc32())));
+        assert!(text.contains("=yend size=4 part=2 pcrc32="));
+    }
+
+    #[test]
+    fn test_single_part_file() {
+        let data = file(10);
+        let encoder = YencMultipartEncoder::new(&data, "small.bin", 1000, 128).unwrap();
+        assert_eq!(encoder.total_parts(), 1);
+        let decoded = decode(&encoder.encode_part(1).unwrap()).unwrap();
+        assert!(decoded.is_multipart());
+        assert_eq!(decoded.data, data);
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let data = file(10);
+        assert!(matches!(
+            YencMultipartEncoder::new(&[], "empty.bin", 10, 128),
+            Err(NntpError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            YencMultipartEncoder::new(&data, "a.bin", 0, 128),
+            Err(NntpError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            YencMultipartEncoder::new(&data, "a.bin", 10, 0),
+            Err(NntpError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            YencMultipartEncoder::new(&data, "a.bin", 10, 998),
+            Err(NntpError::InvalidArgument(_))
+        ));
+    }
+}
@@ -66,6 +66,20 @@ pub(crate) fn parse_ypart(line: &str) -> Result<YencPart> {
         .and_then(|s| s.parse().ok())
         .ok_or_else(|| NntpError::InvalidResponse("Missing 'end' parameter".to_string()))?;
 
+    // Offsets are 1-based and inclusive; anything else would underflow later
+    if begin == 0 {
+        return Err(NntpError::LengthOutOfRange {
+            context: "yEnc part begin",
+            value: begin,
+        });
+    }
+    if end < begin {
+        return Err(NntpError::LengthOutOfRange {
+            context: "yEnc part end",
+            value: end,
+        });
+    }
+
     Ok(YencPart { begin, end })
 }
 
@@ -0,0 +1,471 @@
+//! Incremental yEnc decoding
+//!
+//! [`StreamingDecoder`] decodes a yEnc block fed in chunks of any size, so
+//! decoding can run while the rest of a body is still coming in from
+//! [`NntpClient::fetch_body_stream`](crate::NntpClient::fetch_body_stream).
+
+use std::mem;
+
+use crc32fast::Hasher;
+
+use crate::warnings::ParseWarning;
+use crate::{NntpError, Result};
+
+use super::params::{parse_ybegin, parse_yend, parse_ypart};
+use super::types::{YencEnd, YencHeader, YencPart};
+
+/// Longest `=ybegin`, `=ypart` or `=yend` line accepted
+const MAX_KEYWORD_LINE: usize = 4096;
+
+/// Which part of the block the decoder is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Looking for the `=ybegin` line
+    Begin,
+    /// Decoding data lines until `=yend`
+    Data,
+    /// The `=yend` line has been read, or decoding failed
+    Done,
+}
+
+/// What the decoder knows about the current line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineState {
+    /// The line read so far may still be a keyword line; it is held in `line`
+    Keyword,
+    /// Inside a data line
+    Data,
+    /// Inside a line before `=ybegin` that is ignored
+    Skip,
+}
+
+/// Push decoder for a single yEnc block
+///
+/// Chunks can split the input anywhere, including inside an escape sequence
+/// or a `=ybegin`, `=ypart` or `=yend` line. Lines before `=ybegin` and
+/// everything after `=yend` are ignored. Decoded bytes are appended to the
+/// caller's buffer as they become available and the CRC32 of the data so
+/// far is kept up to date, so nothing but the current keyword line is held.
+///
+/// # Examples
+///
+/// ```
+/// use nntp_rs::yenc::{self, StreamingDecoder};
+///
+/// let data: Vec<u8> = (0..=255).collect();
+/// let encoded = yenc::encode(&data, "bytes.bin", 128, None).unwrap();
+///
+/// let mut decoder = StreamingDecoder::new();
+/// let mut decoded = Vec::new();
+/// for chunk in encoded.chunks(5) {
+///     decoder.feed(chunk, &mut decoded).unwrap();
+/// }
+/// decoder.finish().unwrap();
+///
+/// assert_eq!(decoded, data);
+/// assert_eq!(decoder.header().unwrap().name, "bytes.bin");
+/// assert_eq!(decoder.verify_crc32(), Some(true));
+/// ```
+#[derive(Debug)]
+pub struct StreamingDecoder {
+    phase: Phase,
+    line_state: LineState,
+    /// Start of a line that may be a keyword line
+    line: Vec<u8>,
+    /// An `=` has been read and the escaped byte has not
+    escape: bool,
+    /// A `=ypart` line may still follow the `=ybegin` line
+    expect_part: bool,
+    header: Option<YencHeader>,
+    part: Option<YencPart>,
+    trailer: Option<YencEnd>,
+    hasher: Hasher,
+    decoded_size: u64,
+    warnings: Vec<ParseWarning>,
+}
+
+impl Default for StreamingDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingDecoder {
+    /// Create a decoder waiting for a `=ybegin` line
+    pub fn new() -> Self {
+        Self {
+            phase: Phase::Begin,
+            line_state: LineState::Keyword,
+            line: Vec::new(),
+            escape: false,
+            expect_part: false,
+            header: None,
+            part: None,
+            trailer: None,
+            hasher: Hasher::new(),
+            decoded_size: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Decode the next chunk of input, appending the decoded bytes to `output`
+    ///
+    /// Returns the number of bytes appended. Input after the `=yend` line is
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if a keyword line is malformed
+    /// or too long, or a data line ends in the middle of an escape sequence,
+    /// and [`NntpError::LengthOutOfRange`] for an impossible `=ypart` range.
+    /// The decoder stops at the first error; feeding it more does nothing.
+    pub fn feed(&mut self, chunk: &[u8], output: &mut Vec<u8>) -> Result<usize> {
+        let start = output.len();
+        let result = self.decode_bytes(chunk, output, start);
+        if result.is_err() {
+            self.phase = Phase::Done;
+        }
+        if self.trailer.is_none() {
+            self.account(&output[start..]);
+        }
+        result.map(|()| output.len() - start)
+    }
+
+    /// Check that the block is complete once the input has ended
+    ///
+    /// A `=yend` line missing only its line break is accepted. Returns the
+    /// trailer; compare its sizes and CRCs with [`crc32`](Self::crc32) or
+    /// use [`verify_crc32`](Self::verify_crc32).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NntpError::InvalidResponse`] if the input had no `=ybegin`
+    /// line or ended before the `=yend` line.
+    pub fn finish(&mut self) -> Result<YencEnd> {
+        if self.phase != Phase::Done
+            && self.line_state == LineState::Keyword
+            && self.line.starts_with(b"=yend ")
+        {
+            self.feed(b"\n", &mut Vec::new())?;
+        }
+        match (&self.header, &self.trailer) {
+            (_, Some(trailer)) => Ok(trailer.clone()),
+            (None, None) => Err(NntpError::InvalidResponse(
+                "Missing =ybegin header".to_string(),
+            )),
+            (Some(_), None) => Err(NntpError::InvalidResponse(
+                "Missing =yend trailer".to_string(),
+            )),
+        }
+    }
+
+    /// Check if the `=yend` line has been read
+    pub fn is_finished(&self) -> bool {
+        self.trailer.is_some()
+    }
+
+    /// Header from the `=ybegin` line, once it has been read
+    pub fn header(&self) -> Option<&YencHeader> {
+        self.header.as_ref()
+    }
+
+    /// Part range from the `=ypart` line, if the block has one
+    pub fn part(&self) -> Option<&YencPart> {
+        self.part.as_ref()
+    }
+
+    /// Trailer from the `=yend` line, once it has been read
+    pub fn trailer(&self) -> Option<&YencEnd> {
+        self.trailer.as_ref()
+    }
+
+    /// CRC32 of the bytes decoded so far
+    pub fn crc32(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+
+    /// Number of bytes decoded so far
+    pub fn decoded_size(&self) -> u64 {
+        self.decoded_size
+    }
+
+    /// Verify the CRC32 of the decoded data against the trailer
+    ///
+    /// Like [`YencDecoded::verify_crc32`](super::YencDecoded::verify_crc32),
+    /// and `None` until the `=yend` line has been read.
+    pub fn verify_crc32(&self) -> Option<bool> {
+        let trailer = self.trailer.as_ref()?;
+        trailer
+            .pcrc32
+            .or(trailer.crc32)
+            .map(|expected| self.crc32() == expected)
+    }
+
+    /// Defects the decoder accepted
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Add decoded bytes to the running size and CRC
+    fn account(&mut self, decoded: &[u8]) {
+        self.hasher.update(decoded);
+        self.decoded_size += decoded.len() as u64;
+    }
+
+    /// Run `input` through the state machine; `start` is where this chunk's
+    /// output begins
+    fn decode_bytes(&mut self, input: &[u8], output: &mut Vec<u8>, start: usize) -> Result<()> {
+        let mut i = 0;
+        while i < input.len() && self.phase != Phase::Done {
+            match self.line_state {
+                LineState::Skip => match memchr::memchr(b'\n', &input[i..]) {
+                    Some(end) => {
+                        i += end + 1;
+                        self.line_state = LineState::Keyword;
+                    }
+                    None => i = input.len(),
+                },
+                LineState::Data => {
+                    let end = memchr::memchr(b'\n', &input[i..]).map(|end| i + end);
+                    self.data_bytes(&input[i..end.unwrap_or(input.len())], output);
+                    let Some(end) = end else {
+                        break;
+                    };
+                    self.end_data_line()?;
+                    i = end + 1;
+                }
+                LineState::Keyword => {
+                    let byte = input[i];
+                    i += 1;
+                    if byte == b'\n' {
+                        self.end_keyword_line(output, start)?;
+                    } else {
+                        self.line.push(byte);
+                        self.check_keyword_line(output)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode bytes of a data line, without its line break
+    fn data_bytes(&mut self, bytes: &[u8], output: &mut Vec<u8>) {
+        for &byte in bytes {
+            if self.escape {
+                output.push(byte.wrapping_sub(64).wrapping_sub(42));
+                self.escape = false;
+            } else if byte == b'=' {
+                self.escape = true;
+            } else if byte != b'\r' {
+                output.push(byte.wrapping_sub(42));
+            }
+        }
+    }
+
+    /// Handle the line break at the end of a data line
+    fn end_data_line(&mut self) -> Result<()> {
+        if self.escape {
+            return Err(NntpError::InvalidResponse(
+                "Incomplete escape sequence at end of line".to_string(),
+            ));
+        }
+        self.line_state = LineState::Keyword;
+        Ok(())
+    }
+
+    /// After a byte is added to `line`, decide whether it can still be a keyword line
+    fn check_keyword_line(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        let keywords: &[&[u8]] = match self.phase {
+            Phase::Begin => &[b"=ybegin "],
+            _ if self.expect_part => &[b"=ypart ", b"=yend "],
+            _ => &[b"=yend "],
+        };
+        let possible = keywords.iter().any(|keyword| {
+            let len = keyword.len().min(self.line.len());
+            keyword[..len] == self.line[..len]
+        });
+        if !possible {
+            if self.phase == Phase::Begin {
+                self.line_state = LineState::Skip;
+                self.line.clear();
+            } else {
+                let line = mem::take(&mut self.line);
+                self.line_state = LineState::Data;
+                self.expect_part = false;
+                self.data_bytes(&line, output);
+            }
+        } else if self.line.len() > MAX_KEYWORD_LINE {
+            return Err(NntpError::InvalidResponse(
+                "yEnc header line too long".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Handle the line break at the end of a line held in `line`
+    fn end_keyword_line(&mut self, output: &mut Vec<u8>, start: usize) -> Result<()> {
+        let line = mem::take(&mut self.line);
+        let text = || {
+            std::str::from_utf8(&line)
+                .map(|s| s.trim_end_matches('\r'))
+                .map_err(|_| NntpError::InvalidResponse("Invalid UTF-8 in yEnc header".to_string()))
+        };
+
+        if self.phase == Phase::Begin {
+            if line.starts_with(b"=ybegin ") {
+                self.header = Some(parse_ybegin(text()?)?);
+                self.phase = Phase::Data;
+                self.expect_part = true;
+            }
+        } else if self.expect_part && line.starts_with(b"=ypart ") {
+            self.part = Some(parse_ypart(text()?)?);
+            self.expect_part = false;
+        } else if line.starts_with(b"=yend ") {
+            let trailer = parse_yend(text()?)?;
+            self.account(&output[start..]);
+            if self.decoded_size != trailer.size {
+                self.warnings.push(ParseWarning::YencSizeMismatch {
+                    declared: trailer.size,
+                    decoded: self.decoded_size,
+                });
+            }
+            self.trailer = Some(trailer);
+            self.phase = Phase::Done;
+        } else {
+            // A short data line that looked like the start of a keyword
+            self.expect_part = false;
+            self.data_bytes(&line, output);
+            self.end_data_line()?;
+        }
+        self.line_state = LineState::Keyword;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yenc::{decode, encode};
+
+    /// Feed `input` in chunks of `size` and finish
+    fn decode_chunked(input: &[u8], size: usize) -> (StreamingDecoder, Vec<u8>) {
+        let mut decoder = StreamingDecoder::new();
+        let mut output = Vec::new();
+        for chunk in input.chunks(size) {
+            let before = output.len();
+            let appended = decoder.feed(chunk, &mut output).unwrap();
+            assert_eq!(appended, output.len() - before);
+        }
+        decoder.finish().unwrap();
+        (decoder, output)
+    }
+
+    #[test]
+    fn test_matches_decode_at_any_chunk_size() {
+        // Every byte value, so every escape sequence appears
+        let data: Vec<u8> = (0..=255u8).cycle().take(3000).collect();
+        let encoded = encode(&data, "all.bin", 64, None).unwrap();
+        let expected = decode(&encoded).unwrap();
+        for size in [1, 2, 3, 7, 64, 1000, encoded.len()] {
+            let (decoder, output) = decode_chunked(&encoded, size);
+            assert_eq!(output, data, "chunk size {}", size);
+            assert_eq!(decoder.crc32(), expected.calculated_crc32);
+            assert_eq!(decoder.decoded_size(), data.len() as u64);
+            assert_eq!(decoder.header(), Some(&expected.header));
+            assert_eq!(decoder.trailer(), Some(&expected.trailer));
+            assert_eq!(decoder.verify_crc32(), Some(true));
+            assert!(decoder.warnings().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_multipart_block() {
+        let data = b"second part of a file";
+        let encoded = encode(data, "file.bin", 128, Some((2, 3, 101, 121, 300))).unwrap();
+        for size in [1, 5, encoded.len()] {
+            let (decoder, output) = decode_chunked(&encoded, size);
+            assert_eq!(output, data);
+            assert_eq!(decoder.header().unwrap().part, Some(2));
+            assert_eq!(decoder.part().unwrap().begin, 101);
+            assert_eq!(decoder.part().unwrap().end, 121);
+            assert_eq!(decoder.verify_crc32(), Some(true));
+        }
+    }
+
+    #[test]
+    fn test_ignores_text_around_block() {
+        let mut input = b"Some text\r\n=yend is not a trailer yet\r\n\r\n".to_vec();
+        input.extend(encode(b"payload", "p.bin", 128, None).unwrap());
+        input.extend_from_slice(b"-- \r\nsignature\r\n");
+        let (decoder, output) = decode_chunked(&input, 4);
+        assert_eq!(output, b"payload");
+        assert!(decoder.is_finished());
+    }
+
+    #[test]
+    fn test_short_data_lines_like_keywords() {
+        // "=y" and "=ye" are escapes, not the start of =yend
+        let input = b"=ybegin line=128 size=3 name=a.bin\n=y\n=ye\n=yend size=3\n";
+        let (_, output) = decode_chunked(input, 1);
+        assert_eq!(output, decode(input).unwrap().data);
+        assert_eq!(output.len(), 3);
+    }
+
+    #[test]
+    fn test_trailer_without_line_break() {
+        let mut decoder = StreamingDecoder::new();
+        let mut output = Vec::new();
+        decoder
+            .feed(
+                b"=ybegin line=128 size=2 name=a.bin\nkk\n=yend size=1",
+                &mut output,
+            )
+            .unwrap();
+        assert!(!decoder.is_finished());
+        assert_eq!(decoder.finish().unwrap().size, 1);
+        assert_eq!(output, b"AA");
+        assert_eq!(
+            decoder.warnings(),
+            [ParseWarning::YencSizeMismatch {
+                declared: 1,
+                decoded: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut output = Vec::new();
+
+        let mut truncated = StreamingDecoder::new();
+        truncated
+            .feed(b"=ybegin line=128 size=2 name=a.bin\nkk\n", &mut output)
+            .unwrap();
+        assert!(matches!(
+            truncated.finish(),
+            Err(NntpError::InvalidResponse(msg)) if msg.contains("=yend")
+        ));
+
+        let mut no_header = StreamingDecoder::new();
+        no_header.feed(b"plain text\n", &mut output).unwrap();
+        assert!(matches!(
+            no_header.finish(),
+            Err(NntpError::InvalidResponse(msg)) if msg.contains("=ybegin")
+        ));
+
+        let mut dangling = StreamingDecoder::new();
+        assert!(
+            dangling
+                .feed(b"=ybegin line=128 size=2 name=a.bin\nk=\n", &mut output)
+                .is_err()
+        );
+        // Stopped at the error
+        assert_eq!(dangling.feed(b"kkk\n", &mut output).unwrap(), 0);
+
+        let mut long = StreamingDecoder::new();
+        let mut line = b"=ybegin name=".to_vec();
+        line.resize(MAX_KEYWORD_LINE + 10, b'a');
+        assert!(long.feed(&line, &mut output).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::warnings::ParseWarning;
+
 /// yEnc header from =ybegin line
 #[derive(Debug, Clone, PartialEq)]
 pub struct YencHeader {
@@ -34,7 +36,7 @@ pub struct YencEnd {
 }
 
 /// Complete yEnc decoded result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct YencDecoded {
     /// Parsed header information
     pub header: YencHeader,
@@ -46,6 +48,8 @@ pub struct YencDecoded {
     pub data: Vec<u8>,
     /// Calculated CRC32 of decoded data
     pub calculated_crc32: u32,
+    /// Defects the decoder accepted
+    pub warnings: Vec<ParseWarning>,
 }
 
 impl YencDecoded {
@@ -96,6 +100,7 @@ mod tests {
             },
             data: vec![0; 10],
             calculated_crc32: 0xDEADBEEF,
+            warnings: Vec::new(),
         };
         assert_eq!(decoded.verify_crc32(), None);
     }
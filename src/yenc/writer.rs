@@ -0,0 +1,227 @@
+//! Writing decoded parts straight into the output file
+//!
+//! [`YencMultipartAssembler`](super::YencMultipartAssembler) holds every
+//! part and the assembled file in memory. For large files, write each part
+//! at its offset as it is decoded instead, with [`write_part`] or
+//! [`write_part_async`], and only one part is in memory at a time.
+
+use std::io::{Seek, SeekFrom, Write};
+
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{NntpError, Result};
+
+use super::types::{YencHeader, YencPart};
+
+/// Write a decoded part at its offset in the file (byte `begin - 1`)
+///
+/// `header` is the part's `=ybegin` line: a range reaching past the file
+/// size it gives is refused, so a forged `=ypart` cannot make the write
+/// land terabytes into the file. The file may be written in any part order
+/// and may be shorter than the part's offset; most file systems leave the
+/// unwritten range as a hole. Call
+/// [`File::set_len`](std::fs::File::set_len) with `header.size` first to
+/// allocate the whole file up front. Check the part's CRC before writing
+/// it: nothing here verifies the data.
+///
+/// # Errors
+///
+/// Returns [`NntpError::LengthOutOfRange`] if the range is not valid,
+/// [`NntpError::InvalidResponse`] if it ends past the file size or `data`
+/// is not as long as it, and [`NntpError::Io`] if seeking or writing fails.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use nntp_rs::yenc::{self, YencMultipartEncoder};
+///
+/// let file = b"first half, second half".to_vec();
+/// let encoder = YencMultipartEncoder::new(&file, "halves.txt", 12, 128).unwrap();
+///
+/// let mut output = Cursor::new(Vec::new());
+/// for number in [2, 1] {
+///     let decoded = yenc::decode(&encoder.encode_part(number).unwrap()).unwrap();
+///     assert_eq!(decoded.verify_crc32(), Some(true));
+///     let part = decoded.part.as_ref().unwrap();
+///     yenc::write_part(&mut output, &decoded.header, part, &decoded.data).unwrap();
+/// }
+/// assert_eq!(output.into_inner(), file);
+/// ```
+pub fn write_part<W: Write + Seek>(
+    writer: &mut W,
+    header: &YencHeader,
+    part: &YencPart,
+    data: &[u8],
+) -> Result<()> {
+    let offset = part_offset(header, part, data)?;
+    writer
+        .seek(SeekFrom::Start(offset))
+        .map_err(NntpError::Io)?;
+    writer.write_all(data).map_err(NntpError::Io)
+}
+
+/// Write a decoded part at its offset in an async file (byte `begin - 1`)
+///
+/// The async version of [`write_part`], for a
+/// [`tokio::fs::File`](https://docs.rs/tokio/latest/tokio/fs/struct.File.html)
+/// or any other [`AsyncWrite`] + [`AsyncSeek`]. The data is flushed before
+/// returning, so the next part can be written through the same file.
+///
+/// # Errors
+///
+/// As for [`write_part`].
+///
+/// # Examples
+///
+/// The file name in the `=ybegin` line is chosen by the poster, so confine
+/// it to the download directory with [`filename::confine`](crate::filename::confine)
+/// before opening it:
+///
+/// ```no_run
+/// use nntp_rs::{filename, yenc};
+/// use std::path::Path;
+///
+/// # async fn example(encoded: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+/// let decoded = yenc::decode(&encoded)?;
+/// let path = filename::confine(Path::new("downloads"), &decoded.header.name)?;
+/// let mut file = tokio::fs::OpenOptions::new()
+///     .write(true)
+///     .create(true)
+///     .truncate(false)
+///     .open(&path)
+///     .await?;
+/// file.set_len(decoded.header.size).await?;
+/// if let (Some(part), Some(true)) = (&decoded.part, decoded.verify_crc32()) {
+///     yenc::write_part_async(&mut file, &decoded.header, part, &decoded.data).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn write_part_async<W: AsyncWrite + AsyncSeek + Unpin>(
+    writer: &mut W,
+    header: &YencHeader,
+    part: &YencPart,
+    data: &[u8],
+) -> Result<()> {
+    let offset = part_offset(header, part, data)?;
+    writer
+        .seek(SeekFrom::Start(offset))
+        .await
+        .map_err(NntpError::Io)?;
+    writer.write_all(data).await.map_err(NntpError::Io)?;
+    writer.flush().await.map_err(NntpError::Io)
+}
+
+/// Check the part's range against the file size and its data, returning
+/// the 0-based offset
+fn part_offset(header: &YencHeader, part: &YencPart, data: &[u8]) -> Result<u64> {
+    if part.begin == 0 || part.begin > part.end {
+        return Err(NntpError::LengthOutOfRange {
+            context: "yEnc part begin",
+            value: part.begin,
+        });
+    }
+    if part.end > header.size {
+        return Err(NntpError::InvalidResponse(format!(
+            "Part range {}-{} exceeds total size {}",
+            part.begin, part.end, header.size
+        )));
+    }
+    let expected_len = part.end - part.begin + 1;
+    if data.len() as u64 != expected_len {
+        return Err(NntpError::InvalidResponse(format!(
+            "Part data length {} doesn't match range {}-{} (expected {})",
+            data.len(),
+            part.begin,
+            part.end,
+            expected_len
+        )));
+    }
+    Ok(part.begin - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yenc::{YencMultipartEncoder, decode};
+    use std::io::Cursor;
+
+    fn part(begin: u64, end: u64) -> YencPart {
+        YencPart { begin, end }
+    }
+
+    fn header(size: u64) -> YencHeader {
+        YencHeader {
+            line: 128,
+            size,
+            name: "file.bin".to_string(),
+            part: Some(1),
+            total: Some(2),
+        }
+    }
+
+    #[test]
+    fn test_parts_in_any_order() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 256) as u8).collect();
+        let encoder = YencMultipartEncoder::new(&data, "data.bin", 256, 128).unwrap();
+        let mut output = Cursor::new(Vec::new());
+        for number in [4, 2, 1, 3] {
+            let decoded = decode(&encoder.encode_part(number).unwrap()).unwrap();
+            let part = decoded.part.as_ref().unwrap();
+            write_part(&mut output, &decoded.header, part, &decoded.data).unwrap();
+        }
+        assert_eq!(output.into_inner(), data);
+    }
+
+    #[test]
+    fn test_leaves_other_bytes_alone() {
+        let mut output = Cursor::new(vec![b'.'; 8]);
+        write_part(&mut output, &header(8), &part(3, 5), b"abc").unwrap();
+        assert_eq!(output.into_inner(), b"..abc...");
+    }
+
+    #[test]
+    fn test_rejects_bad_ranges() {
+        let mut output = Cursor::new(Vec::new());
+        assert!(matches!(
+            write_part(&mut output, &header(8), &part(0, 2), b"ab"),
+            Err(NntpError::LengthOutOfRange { value: 0, .. })
+        ));
+        assert!(matches!(
+            write_part(&mut output, &header(8), &part(3, 2), b""),
+            Err(NntpError::LengthOutOfRange { value: 3, .. })
+        ));
+        assert!(matches!(
+            write_part(&mut output, &header(8), &part(1, 4), b"abc"),
+            Err(NntpError::InvalidResponse(_))
+        ));
+        // A forged range far past the file size is never seeked to
+        assert!(matches!(
+            write_part(&mut output, &header(8), &part(1 << 40, (1 << 40) + 2), b"abc"),
+            Err(NntpError::InvalidResponse(msg)) if msg.contains("exceeds total size")
+        ));
+        assert!(matches!(
+            write_part(&mut output, &header(8), &part(7, 9), b"abc"),
+            Err(NntpError::InvalidResponse(_))
+        ));
+        assert!(output.into_inner().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_part_async() {
+        let mut output = Cursor::new(Vec::new());
+        write_part_async(&mut output, &header(6), &part(4, 6), b"def")
+            .await
+            .unwrap();
+        write_part_async(&mut output, &header(6), &part(1, 3), b"abc")
+            .await
+            .unwrap();
+        assert_eq!(output.into_inner(), b"abcdef");
+        assert!(
+            write_part_async(&mut Cursor::new(Vec::new()), &header(6), &part(1, 2), b"a")
+                .await
+                .is_err()
+        );
+    }
+}
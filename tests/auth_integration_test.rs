@@ -52,14 +52,9 @@ fn get_test_server_config() -> ServerConfig {
     let username = std::env::var("NNTP_TEST_USER").unwrap_or_else(|_| "testuser".to_string());
     let password = std::env::var("NNTP_TEST_PASS").unwrap_or_else(|_| "testpass".to_string());
 
-    ServerConfig {
-        host,
-        port,
-        tls: true,
-        username,
-        password,
-        allow_insecure_tls: true, // For testing with self-signed certs
-    }
+    let mut config = ServerConfig::new(host, port, true, username, password);
+    config.allow_insecure_tls = true; // For testing with self-signed certs
+    config
 }
 /// Test that a newly connected client is NOT authenticated
 ///
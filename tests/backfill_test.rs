@@ -0,0 +1,183 @@
+//! Backfill coordinator tests
+//!
+//! A mock server holds articles 1-95 of `alt.test`, with 1-20 expired, and
+//! answers OVER for any range inside the watermarks.
+
+use nntp_rs::{
+    BackfillCoordinator, BackfillOrder, BackfillState, NntpError, NntpPool, ServerConfig,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+const EXPIRED: u64 = 20;
+const HIGH: u64 = 95;
+
+/// Start the server; returns the config and the OVER ranges it was asked for
+async fn overview_server() -> (ServerConfig, Arc<Mutex<Vec<String>>>) {
+    lagging_server(Duration::ZERO).await
+}
+
+/// Start a server that takes `lag` to answer every OVER but the first
+async fn lagging_server(lag: Duration) -> (ServerConfig, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let log = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve(socket, log.clone(), lag));
+        }
+    });
+
+    let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+    (config, requests)
+}
+
+async fn serve(socket: TcpStream, requests: Arc<Mutex<Vec<String>>>, lag: Duration) {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(b"200 mock ready\r\n").await.unwrap();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply = if line.starts_with("AUTHINFO USER") {
+            "381 password required\r\n".to_string()
+        } else if line.starts_with("AUTHINFO PASS") {
+            "281 welcome\r\n".to_string()
+        } else if line == "GROUP alt.test" {
+            format!("211 {} 1 {} alt.test\r\n", HIGH, HIGH)
+        } else if let Some(range) = line.strip_prefix("OVER ") {
+            let first = {
+                let mut requests = requests.lock().unwrap();
+                requests.push(range.to_string());
+                requests.len() == 1
+            };
+            if !first {
+                tokio::time::sleep(lag).await;
+            }
+            overview(range)
+        } else {
+            "500 unknown command\r\n".to_string()
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn overview(range: &str) -> String {
+    let (start, end) = range.split_once('-').unwrap();
+    let start = start.parse::<u64>().unwrap().max(EXPIRED + 1);
+    let end = end.parse::<u64>().unwrap();
+    if start > end {
+        return "423 no articles in that range\r\n".to_string();
+    }
+    let mut reply = "224 overview follows\r\n".to_string();
+    for number in start..=end {
+        reply.push_str(&format!(
+            "{n}\tsubject {n}\tposter\tMon, 15 Jan 2024 12:00:00 +0000\t<{n}@test>\t\t100\t2\r\n",
+            n = number
+        ));
+    }
+    reply.push_str(".\r\n");
+    reply
+}
+
+#[tokio::test]
+async fn test_backfill_newest_first_covers_group() {
+    let (config, requests) = overview_server().await;
+    let pool = Arc::new(NntpPool::new(config, 3).await.unwrap());
+    let mut state = BackfillState::new("alt.test", BackfillOrder::NewestFirst).with_chunk_size(10);
+
+    let mut numbers = Vec::new();
+    let mut chunks = 0;
+    BackfillCoordinator::new(pool)
+        .with_concurrency(3)
+        .run(&mut state, |chunk, state| {
+            assert!(state.completed.contains(*chunk.range.start()));
+            numbers.extend(chunk.entries.iter().map(|e| e.article_number));
+            chunks += 1;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    numbers.sort_unstable();
+    assert_eq!(numbers, (EXPIRED + 1..=HIGH).collect::<Vec<_>>());
+    assert_eq!(chunks, 10);
+    assert_eq!((state.low, state.high), (Some(1), Some(HIGH)));
+    assert!(state.is_complete());
+
+    // Chunks are dispatched from the top down
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0], "86-95");
+    assert!(requests.contains(&"1-5".to_string()));
+}
+
+#[tokio::test]
+async fn test_backfill_resumes_from_saved_state() {
+    let (config, requests) = overview_server().await;
+    let pool = Arc::new(NntpPool::new(config, 2).await.unwrap());
+    let mut state = BackfillState::new("alt.test", BackfillOrder::OldestFirst)
+        .with_chunk_size(50)
+        .with_bounds(1, HIGH);
+    state.completed.insert_range(1..=60);
+
+    let coordinator = BackfillCoordinator::new(pool).with_concurrency(2);
+    coordinator.run(&mut state, |_, _| Ok(())).await.unwrap();
+
+    assert_eq!(*requests.lock().unwrap(), vec!["61-95"]);
+    assert!(state.is_complete());
+}
+
+#[tokio::test]
+async fn test_backfill_callback_error_leaves_chunk_pending() {
+    let (config, _) = overview_server().await;
+    let pool = Arc::new(NntpPool::new(config, 1).await.unwrap());
+    let mut state = BackfillState::new("alt.test", BackfillOrder::NewestFirst).with_chunk_size(40);
+
+    let coordinator = BackfillCoordinator::new(pool).with_concurrency(1);
+    let mut calls = 0;
+    let result = coordinator
+        .run(&mut state, |_, _| {
+            calls += 1;
+            if calls == 2 {
+                return Err(NntpError::Other("disk full".to_string()));
+            }
+            Ok(())
+        })
+        .await;
+
+    assert!(matches!(result, Err(NntpError::Other(_))));
+    assert_eq!(state.completed.to_string(), "56-95");
+    assert_eq!(state.remaining_articles(), 55);
+
+    coordinator.run(&mut state, |_, _| Ok(())).await.unwrap();
+    assert!(state.is_complete());
+}
+
+#[tokio::test]
+async fn test_backfill_error_returns_connections_in_sync() {
+    let (config, _) = lagging_server(Duration::from_millis(200)).await;
+    let pool = Arc::new(NntpPool::new(config, 3).await.unwrap());
+    let mut state = BackfillState::new("alt.test", BackfillOrder::NewestFirst).with_chunk_size(10);
+
+    let result = BackfillCoordinator::new(pool.clone())
+        .with_concurrency(3)
+        .run(&mut state, |_, _| {
+            Err(NntpError::Other("disk full".to_string()))
+        })
+        .await;
+    assert!(matches!(result, Err(NntpError::Other(_))));
+    assert!(state.completed.is_empty());
+
+    // The two slow chunks were read to the end, not abandoned mid-OVER
+    let mut connections = Vec::new();
+    for _ in 0..3 {
+        let mut conn = pool.get().await.unwrap();
+        assert_eq!(conn.select_group("alt.test").await.unwrap().last, HIGH);
+        connections.push(conn);
+    }
+}
@@ -0,0 +1,76 @@
+//! Blocking client tests
+//!
+//! The mock server runs on a plain thread, so these tests use no async code
+//! at all, as a synchronous caller would.
+
+use nntp_rs::ServerConfig;
+use nntp_rs::blocking::NntpClient;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+fn mock_server() -> Arc<ServerConfig> {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for socket in listener.incoming() {
+            let socket = socket.unwrap();
+            thread::spawn(move || serve(socket));
+        }
+    });
+    Arc::new(ServerConfig::new("127.0.0.1", port, false, "user", "pass"))
+}
+
+fn serve(socket: TcpStream) {
+    let mut writer = socket.try_clone().unwrap();
+    writer.write_all(b"200 mock ready\r\n").unwrap();
+    for line in BufReader::new(socket).lines() {
+        let Ok(line) = line else { break };
+        let reply: &[u8] = match line.as_str() {
+            "AUTHINFO USER user" => b"381 password required\r\n",
+            "AUTHINFO PASS pass" => b"281 welcome\r\n",
+            "GROUP alt.test" => b"211 2 1 2 alt.test\r\n",
+            "BODY <a@b>" => b"222 1 <a@b>\r\nhello\r\n.\r\n",
+            "QUIT" => b"205 bye\r\n",
+            _ => b"430 no such article\r\n",
+        };
+        if writer.write_all(reply).is_err() || line == "QUIT" {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_blocking_session() {
+    let mut client = NntpClient::connect(mock_server()).unwrap();
+    client.authenticate().unwrap();
+    assert!(client.is_authenticated());
+
+    let info = client.select_group("alt.test").unwrap();
+    assert_eq!((info.count, info.first, info.last), (2, 1, 2));
+    assert_eq!(client.current_group(), Some("alt.test"));
+
+    let body = client.fetch_body_binary("<a@b>").unwrap();
+    assert_eq!(body.data, b"hello\r\n");
+    assert!(client.fetch_body_binary("<gone@b>").is_err());
+    assert!(!client.is_broken());
+    client.quit().unwrap();
+}
+
+#[test]
+fn test_clients_work_from_separate_threads() {
+    let config = mock_server();
+    let handles: Vec<_> = (0..3)
+        .map(|_| {
+            let config = config.clone();
+            thread::spawn(move || {
+                let mut client = NntpClient::connect(config).unwrap();
+                client.fetch_body_binary("<a@b>").unwrap().data
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), b"hello\r\n");
+    }
+}
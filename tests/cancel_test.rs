@@ -0,0 +1,116 @@
+//! Cancellation tests
+//!
+//! A mock server answers `<ok@test>` in full and sends only the start of
+//! `<stall@test>`, so a read of the latter hangs until it is cancelled.
+
+use nntp_rs::{CancellationToken, NntpClient, NntpError, ServerConfig};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+async fn stalling_server() -> ServerConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve(socket));
+        }
+    });
+    ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+async fn serve(socket: TcpStream) {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(b"200 mock ready\r\n").await.unwrap();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply: &[u8] = match line.as_str() {
+            "ARTICLE <ok@test>" => b"220 0 <ok@test>\r\nSubject: ok\r\n\r\nbody\r\n.\r\n",
+            "ARTICLE <stall@test>" => b"220 0 <stall@test>\r\nSubject: stall\r\n",
+            _ => b"500 unknown command\r\n",
+        };
+        if writer.write_all(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Cancel `token` after `delay`
+fn cancel_after(token: &CancellationToken, delay: Duration) {
+    let token = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        token.cancel();
+    });
+}
+
+#[tokio::test]
+async fn test_cancelled_before_start_keeps_client_usable() {
+    let config = stalling_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let result = client
+        .fetch_article_binary_cancellable("<ok@test>", &token)
+        .await;
+    assert!(matches!(result, Err(NntpError::Cancelled)));
+    let result = client
+        .fetch_articles_pipelined_cancellable(&["<ok@test>"], 4, &token)
+        .await;
+    assert!(matches!(result, Err(NntpError::Cancelled)));
+    assert!(!client.is_broken());
+
+    let response = client.fetch_article_binary("<ok@test>").await.unwrap();
+    assert_eq!(response.code, 220);
+}
+
+#[tokio::test]
+async fn test_uncancelled_fetch_completes() {
+    let config = stalling_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    let token = CancellationToken::new();
+
+    let response = client
+        .fetch_article_binary_cancellable("<ok@test>", &token)
+        .await
+        .unwrap();
+    assert!(response.data.ends_with(b"body\r\n"));
+    let responses = client
+        .fetch_articles_pipelined_cancellable(&["<ok@test>", "<ok@test>"], 4, &token)
+        .await
+        .unwrap();
+    assert_eq!(responses.len(), 2);
+    assert!(!client.is_broken());
+}
+
+#[tokio::test]
+async fn test_cancel_mid_read_marks_client_broken() {
+    let config = stalling_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    let token = CancellationToken::new();
+    cancel_after(&token, Duration::from_millis(50));
+
+    let result = client
+        .fetch_article_binary_cancellable("<stall@test>", &token)
+        .await;
+    assert!(matches!(result, Err(NntpError::Cancelled)));
+    assert!(client.is_broken());
+}
+
+#[tokio::test]
+async fn test_cancel_pipelined_fetch() {
+    let config = stalling_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    let token = CancellationToken::new();
+    cancel_after(&token, Duration::from_millis(50));
+
+    let ids = ["<ok@test>", "<stall@test>", "<ok@test>"];
+    let result = client
+        .fetch_articles_pipelined_cancellable(&ids, 2, &token)
+        .await;
+    assert!(matches!(result, Err(NntpError::Cancelled)));
+    assert!(client.is_broken());
+}
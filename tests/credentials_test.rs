@@ -0,0 +1,126 @@
+//! Credentials provider tests
+//!
+//! A mock server accepts only `alice`/`hunter2` and logs the commands it
+//! receives; the configs under test store no credentials themselves.
+
+use nntp_rs::credentials::CredentialsFuture;
+use nntp_rs::{Credentials, CredentialsProvider, NntpClient, NntpError, NntpPool, ServerConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Start the server; returns its port and the commands it received
+async fn auth_server() -> (u16, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let commands = Arc::new(Mutex::new(Vec::new()));
+
+    let log = commands.clone();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve(socket, log.clone()));
+        }
+    });
+    (port, commands)
+}
+
+async fn serve(socket: TcpStream, commands: Arc<Mutex<Vec<String>>>) {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(b"200 mock ready\r\n").await.unwrap();
+    let mut user = String::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+        commands.lock().unwrap().push(line.clone());
+        let reply: &[u8] = if let Some(name) = line.strip_prefix("AUTHINFO USER ") {
+            user = name.to_string();
+            b"381 password required\r\n"
+        } else if let Some(password) = line.strip_prefix("AUTHINFO PASS ") {
+            if user == "alice" && password == "hunter2" {
+                b"281 welcome\r\n"
+            } else {
+                b"481 rejected\r\n"
+            }
+        } else {
+            b"500 unknown command\r\n"
+        };
+        if writer.write_all(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Hands out fixed credentials, or fails when `password` is `None`
+#[derive(Debug)]
+struct Keychain {
+    password: Option<&'static str>,
+    lookups: AtomicUsize,
+}
+
+impl Keychain {
+    fn new(password: Option<&'static str>) -> Arc<Self> {
+        Arc::new(Self {
+            password,
+            lookups: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl CredentialsProvider for Keychain {
+    fn credentials<'a>(&'a self, server: &'a ServerConfig) -> CredentialsFuture<'a> {
+        Box::pin(async move {
+            assert_eq!(server.host, "127.0.0.1");
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            let password = self
+                .password
+                .ok_or_else(|| NntpError::AuthFailed("keychain locked".to_string()))?;
+            Ok(Credentials::new("alice", password))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_provider_credentials_are_sent() {
+    let (port, commands) = auth_server().await;
+    let keychain = Keychain::new(Some("hunter2"));
+    let config = ServerConfig::new("127.0.0.1", port, false, "", "")
+        .with_credentials_provider(keychain.clone());
+
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    client.authenticate().await.unwrap();
+    assert!(client.is_authenticated());
+    assert_eq!(keychain.lookups.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        *commands.lock().unwrap(),
+        vec!["AUTHINFO USER alice", "AUTHINFO PASS hunter2"]
+    );
+}
+
+#[tokio::test]
+async fn test_provider_error_aborts_authentication() {
+    let (port, commands) = auth_server().await;
+    let config = ServerConfig::new("127.0.0.1", port, false, "alice", "hunter2")
+        .with_credentials_provider(Keychain::new(None));
+
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    let result = client.authenticate().await;
+    assert!(matches!(result, Err(NntpError::AuthFailed(message)) if message == "keychain locked"));
+    assert!(!client.is_authenticated());
+    // The stored credentials are not used as a fallback
+    assert!(commands.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_pool_asks_provider_per_connection() {
+    let (port, _) = auth_server().await;
+    let keychain = Keychain::new(Some("hunter2"));
+    let config = ServerConfig::new("127.0.0.1", port, false, "", "")
+        .with_credentials_provider(keychain.clone());
+
+    let pool = NntpPool::new(config, 2).await.unwrap();
+    let first = pool.get().await.unwrap();
+    let second = pool.get().await.unwrap();
+    assert!(first.is_authenticated() && second.is_authenticated());
+    assert_eq!(keychain.lookups.load(Ordering::SeqCst), 2);
+}
@@ -104,3 +104,138 @@ fn test_server_config_serde_defaults() {
     let config: ServerConfig = serde_json::from_str(json).unwrap();
     assert!(config.tls); // Should default to true
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_range_set_serde() {
+    let set: nntp_rs::RangeSet = "1-500,502,510-600".parse().unwrap();
+
+    // Serialized in newsrc notation
+    let json = serde_json::to_string(&set).unwrap();
+    assert_eq!(json, "\"1-500,502,510-600\"");
+
+    let deserialized: nntp_rs::RangeSet = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, set);
+    assert!(serde_json::from_str::<nntp_rs::RangeSet>("\"5-1\"").is_err());
+}
+
+/// Pool behaviour against a mock provider that enforces a connection limit
+mod connection_limit_tests {
+    use nntp_rs::{ConnectionLimiter, NntpClient, NntpError, NntpPool, RetryConfig, ServerConfig};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    struct Provider {
+        open: AtomicUsize,
+        refused: AtomicUsize,
+    }
+
+    /// Accept connections, refusing those beyond `limit` with a 502 greeting
+    async fn mock_provider(limit: usize) -> (ServerConfig, Arc<Provider>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let provider = Arc::new(Provider {
+            open: AtomicUsize::new(0),
+            refused: AtomicUsize::new(0),
+        });
+
+        let shared = provider.clone();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                let provider = shared.clone();
+                tokio::spawn(async move { serve(socket, limit, &provider).await });
+            }
+        });
+
+        let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+        (config, provider)
+    }
+
+    async fn serve(mut socket: TcpStream, limit: usize, provider: &Provider) {
+        if provider.open.fetch_add(1, Ordering::SeqCst) >= limit {
+            provider.open.fetch_sub(1, Ordering::SeqCst);
+            provider.refused.fetch_add(1, Ordering::SeqCst);
+            let _ = socket.write_all(b"502 Too many connections\r\n").await;
+            return;
+        }
+
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"200 mock ready\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply: &[u8] = if line.starts_with("AUTHINFO USER") {
+                b"381 password required\r\n"
+            } else if line.starts_with("AUTHINFO PASS") {
+                b"281 welcome\r\n"
+            } else {
+                b"500 unknown command\r\n"
+            };
+            if writer.write_all(reply).await.is_err() {
+                break;
+            }
+        }
+        provider.open.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    #[tokio::test]
+    async fn test_greeting_reports_connection_limit() {
+        let (config, _provider) = mock_provider(0).await;
+        let result = NntpClient::connect(Arc::new(config)).await;
+        assert!(matches!(result, Err(NntpError::ConnectionLimit(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pool_shrinks_to_provider_limit() {
+        let (config, provider) = mock_provider(2).await;
+        let pool = Arc::new(
+            NntpPool::with_retry_config(config, 4, RetryConfig::no_retry())
+                .await
+                .unwrap(),
+        );
+
+        let first = pool.get().await.unwrap();
+        let second = pool.get().await.unwrap();
+
+        // The third connection is refused; the pool lowers its limit and
+        // waits for a connection to come back instead of failing
+        let waiter = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.get().await.map(|_| ()) })
+        };
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(pool.connection_limiter().current_limit(), 2);
+        assert_eq!(provider.refused.load(Ordering::SeqCst), 2);
+
+        drop(first);
+        waiter.await.unwrap().unwrap();
+        drop(second);
+        assert!(provider.open.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_shared_limiter_spans_pools() {
+        let (config, _provider) = mock_provider(4).await;
+        let limiter = ConnectionLimiter::new(3);
+        let a = NntpPool::with_connection_limiter(
+            config.clone(),
+            3,
+            RetryConfig::no_retry(),
+            limiter.clone(),
+        )
+        .await
+        .unwrap();
+        let b = NntpPool::with_connection_limiter(config, 3, RetryConfig::no_retry(), limiter)
+            .await
+            .unwrap();
+
+        let _a1 = a.get().await.unwrap();
+        let _a2 = a.get().await.unwrap();
+        let _b1 = b.get().await.unwrap();
+        assert_eq!(a.connection_limiter().available(), 0);
+        assert_eq!(b.connection_limiter().available(), 0);
+    }
+}
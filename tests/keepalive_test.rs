@@ -0,0 +1,167 @@
+//! Idle keepalive and pool close tests
+//!
+//! A mock server counts DATE commands; keepalives must only be sent on
+//! connections that have been idle for the configured interval.
+
+use nntp_rs::{NntpClient, NntpPool, NntpRuntime, ServerConfig};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+const INTERVAL: Duration = Duration::from_millis(50);
+
+/// Start a server that answers DATE (or rejects it) and counts the requests
+async fn date_server(date_ok: bool) -> (ServerConfig, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let dates = Arc::new(AtomicUsize::new(0));
+
+    let counter = dates.clone();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve(socket, date_ok, counter.clone()));
+        }
+    });
+
+    let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+    (config, dates)
+}
+
+async fn serve(socket: TcpStream, date_ok: bool, dates: Arc<AtomicUsize>) {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(b"200 mock ready\r\n").await.unwrap();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply: &[u8] = if line.starts_with("AUTHINFO USER") {
+            b"381 password required\r\n"
+        } else if line.starts_with("AUTHINFO PASS") {
+            b"281 welcome\r\n"
+        } else if line == "DATE" {
+            dates.fetch_add(1, Ordering::SeqCst);
+            if date_ok {
+                b"111 20240115123456\r\n"
+            } else {
+                b"500 unknown command\r\n"
+            }
+        } else {
+            b"500 unknown command\r\n"
+        };
+        if writer.write_all(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_client_keepalive_only_when_idle() {
+    let (config, dates) = date_server(true).await;
+    let config = config.with_keepalive(INTERVAL);
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    client.authenticate().await.unwrap();
+
+    assert!(!client.keepalive().await.unwrap());
+    tokio::time::sleep(INTERVAL * 2).await;
+    assert!(client.idle_time() >= INTERVAL);
+    assert!(client.keepalive().await.unwrap());
+    assert!(client.idle_time() < INTERVAL);
+    assert!(!client.keepalive().await.unwrap());
+    assert_eq!(dates.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_client_keepalive_disabled_by_default() {
+    let (config, dates) = date_server(true).await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    tokio::time::sleep(INTERVAL).await;
+    assert!(!client.keepalive().await.unwrap());
+    assert_eq!(dates.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_pool_keepalive_pings_each_idle_connection() {
+    let (config, dates) = date_server(true).await;
+    let pool = NntpPool::new(config.with_keepalive(INTERVAL), 2)
+        .await
+        .unwrap();
+    {
+        let _a = pool.get().await.unwrap();
+        let _b = pool.get().await.unwrap();
+    }
+    assert_eq!(pool.idle_connections(), 2);
+
+    assert_eq!(pool.keepalive().await, 0);
+    tokio::time::sleep(INTERVAL * 2).await;
+    assert_eq!(pool.keepalive().await, 2);
+    assert_eq!(dates.load(Ordering::SeqCst), 2);
+    assert_eq!(pool.idle_connections(), 2);
+}
+
+#[tokio::test]
+async fn test_pool_drops_connection_when_keepalive_fails() {
+    let (config, _) = date_server(false).await;
+    let pool = NntpPool::new(config.with_keepalive(INTERVAL), 1)
+        .await
+        .unwrap();
+    drop(pool.get().await.unwrap());
+
+    tokio::time::sleep(INTERVAL * 2).await;
+    assert_eq!(pool.keepalive().await, 0);
+    assert_eq!(pool.state().connections, 0);
+}
+
+#[tokio::test]
+async fn test_run_keepalive_until_shutdown() {
+    let (config, dates) = date_server(true).await;
+    let mut runtime = NntpRuntime::new();
+    let pool = runtime.add_pool(
+        NntpPool::new(config.with_keepalive(INTERVAL), 1)
+            .await
+            .unwrap(),
+    );
+    drop(pool.get().await.unwrap());
+
+    let keepalive_pool = pool.clone();
+    runtime.spawn("keepalive", move |shutdown| async move {
+        keepalive_pool.run_keepalive(shutdown).await;
+    });
+    tokio::time::sleep(INTERVAL * 4).await;
+    drop(pool);
+
+    let report = runtime.shutdown(Duration::from_secs(5)).await;
+    assert_eq!(report.tasks_stopped, 1);
+    assert!(report.tasks_aborted.is_empty());
+    assert!(dates.load(Ordering::SeqCst) >= 1);
+}
+
+#[tokio::test]
+async fn test_close_drops_idle_and_returned_connections() {
+    let (config, _) = date_server(true).await;
+    let pool = NntpPool::new(config, 2).await.unwrap();
+    let busy = pool.get().await.unwrap();
+    drop(pool.get().await.unwrap());
+    assert_eq!(pool.state().connections, 2);
+
+    assert_eq!(pool.close().await, 1);
+    assert!(pool.is_closed());
+    assert!(pool.get().await.is_err());
+    assert_eq!(pool.state().connections, 1);
+    drop(busy);
+    assert_eq!(pool.state().connections, 0);
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_pools() {
+    let (config, _) = date_server(true).await;
+    let mut runtime = NntpRuntime::new();
+    let pool = runtime.add_pool(NntpPool::new(config, 2).await.unwrap());
+    drop(pool.get().await.unwrap());
+
+    let report = runtime.shutdown(Duration::from_secs(1)).await;
+    assert!(report.is_clean());
+    assert!(pool.is_closed());
+    assert_eq!(pool.state().connections, 0);
+}
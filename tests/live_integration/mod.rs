@@ -45,14 +45,7 @@ pub fn get_test_config() -> ServerConfig {
         .or_else(|_| std::env::var("NNTP_PASSWORD"))
         .expect("NNTP_PASS or NNTP_PASSWORD not set");
 
-    ServerConfig {
-        host,
-        port,
-        tls: true,
-        allow_insecure_tls: false,
-        username,
-        password,
-    }
+    ServerConfig::new(host, port, true, username, password)
 }
 
 /// Get Arc-wrapped server configuration
@@ -316,16 +316,15 @@ async fn test_date() {
     let config = get_test_config();
     let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
 
-    let date_str = client.date().await.unwrap();
-    println!("Server DATE: {}", date_str);
+    let date = client.date().await.unwrap();
+    println!("Server DATE: {}", date);
 
-    // Should be in format YYYYMMDDhhmmss (14 digits)
-    assert_eq!(date_str.len(), 14);
-    assert!(date_str.chars().all(|c| c.is_ascii_digit()));
+    // Parsed from YYYYMMDDhhmmss, so the year should be reasonable
+    assert!((2020..=2035).contains(&chrono::Datelike::year(&date)));
 
-    // Parse year should be reasonable
-    let year: u32 = date_str[0..4].parse().unwrap();
-    assert!((2020..=2030).contains(&year));
+    // Provider clocks should be within a few minutes of ours
+    let skew = client.clock_skew().await.unwrap();
+    assert!(skew.num_seconds().abs() < 300, "clock skew {}", skew);
 }
 
 #[tokio::test]
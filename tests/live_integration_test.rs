@@ -28,14 +28,7 @@ fn get_test_config() -> ServerConfig {
     let username = std::env::var("NNTP_USER").expect("NNTP_USER not set");
     let password = std::env::var("NNTP_PASS").expect("NNTP_PASS not set");
 
-    ServerConfig {
-        host,
-        port,
-        tls: true,
-        allow_insecure_tls: false,
-        username,
-        password,
-    }
+    ServerConfig::new(host, port, true, username, password)
 }
 
 fn get_test_group() -> String {
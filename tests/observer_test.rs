@@ -0,0 +1,313 @@
+//! Connection observer tests
+//!
+//! A recording observer is attached to a client talking to a mock server and
+//! must see every command, status line and byte, with credentials masked.
+
+use nntp_rs::{
+    ConnectionObserver, GroupRenumbering, NntpClient, NntpError, ParseWarning, RenumberReason,
+    ServerConfig,
+};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Debug, Default)]
+struct Recorder {
+    commands: Mutex<Vec<String>>,
+    responses: Mutex<Vec<u16>>,
+    bytes_in: AtomicUsize,
+    bytes_out: AtomicUsize,
+    renumberings: Mutex<Vec<GroupRenumbering>>,
+    warnings: Mutex<Vec<ParseWarning>>,
+}
+
+impl ConnectionObserver for Recorder {
+    fn on_command(&self, command: &str) {
+        self.commands.lock().unwrap().push(command.to_string());
+    }
+
+    fn on_response(&self, code: u16, _message: &str) {
+        self.responses.lock().unwrap().push(code);
+    }
+
+    fn on_bytes_in(&self, bytes: usize) {
+        self.bytes_in.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    fn on_bytes_out(&self, bytes: usize) {
+        self.bytes_out.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    fn on_group_renumbered(&self, renumbering: &GroupRenumbering) {
+        self.renumberings.lock().unwrap().push(renumbering.clone());
+    }
+
+    fn on_parse_warning(&self, warning: &ParseWarning) {
+        self.warnings.lock().unwrap().push(warning.clone());
+    }
+}
+
+async fn mock_server() -> ServerConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve(socket));
+        }
+    });
+    ServerConfig::new("127.0.0.1", port, false, "user", "secret")
+}
+
+/// BODY response whose unstuffed dot line is inside a compressed block
+fn compressed_body() -> Vec<u8> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"line one\r\n.hidden\r\n").unwrap();
+    let mut reply = b"222 0 <gzip@b> [COMPRESS=GZIP]\r\n".to_vec();
+    reply.extend(encoder.finish().unwrap());
+    reply.extend_from_slice(b".\r\n");
+    reply
+}
+
+async fn serve(socket: TcpStream) {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(b"200 mock ready\r\n").await.unwrap();
+    let compressed = compressed_body();
+    let mut group_selects = 0;
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply: &[u8] = if line == "GROUP alt.renumbered" {
+            // The server rebuilds the group after the second GROUP
+            group_selects += 1;
+            if group_selects <= 2 {
+                b"211 100 1000 1099 alt.renumbered\r\n"
+            } else {
+                b"211 3 1 3 alt.renumbered\r\n"
+            }
+        } else if line.starts_with("AUTHINFO USER") {
+            b"381 password required\r\n"
+        } else if line.starts_with("AUTHINFO PASS") {
+            b"281 welcome\r\n"
+        } else if line == "XOVER 1-2" {
+            b"224 overview follows\r\n\
+              1\tSubject\tposter@example.com\tSun, 1 Jan 2023 00:00:00 +0000\t<1@b>\t\t10\t1\r\n\
+              garbage\r\n.\r\n"
+        } else if line == "BODY <a@b>" {
+            b"222 0 <a@b>\r\nline one\r\n..dotted\r\n.\r\n"
+        } else if line == "BODY <unstuffed@b>" {
+            b"222 0 <unstuffed@b>\r\nline one\r\n.hidden\r\n..dotted\r\n.\r\n"
+        } else if line == "XFEATURE COMPRESS GZIP" {
+            b"290 feature enabled\r\n"
+        } else if line == "BODY <gzip@b>" {
+            &compressed
+        } else {
+            b"430 no such article\r\n"
+        };
+        if writer.write_all(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_observer_from_config_sees_traffic() {
+    let recorder = Arc::new(Recorder::default());
+    let config = mock_server().await.with_observer(recorder.clone());
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    client.authenticate().await.unwrap();
+    let body = client.fetch_body_binary("<a@b>").await.unwrap();
+    assert_eq!(body.data, b"line one\r\n.dotted\r\n");
+    assert!(client.fetch_body_binary("<gone@b>").await.is_err());
+
+    assert_eq!(
+        *recorder.commands.lock().unwrap(),
+        vec![
+            "AUTHINFO USER user",
+            "AUTHINFO PASS ****",
+            "BODY <a@b>",
+            "BODY <gone@b>"
+        ]
+    );
+    assert_eq!(
+        *recorder.responses.lock().unwrap(),
+        vec![200, 381, 281, 222, 430]
+    );
+
+    // Observer totals match the client's own counters
+    assert_eq!(
+        recorder.bytes_in.load(Ordering::SeqCst) as u64,
+        client.bytes_received()
+    );
+    assert_eq!(
+        recorder.bytes_out.load(Ordering::SeqCst) as u64,
+        client.bytes_sent()
+    );
+    let sent = "AUTHINFO USER user\r\nAUTHINFO PASS secret\r\nBODY <a@b>\r\nBODY <gone@b>\r\n";
+    assert_eq!(client.bytes_sent(), sent.len() as u64);
+}
+
+#[tokio::test]
+async fn test_set_observer_replaces_and_clears() {
+    let config = mock_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    // Counters run without an observer
+    assert_eq!(client.bytes_received(), "200 mock ready\r\n".len() as u64);
+
+    let recorder = Arc::new(Recorder::default());
+    client.set_observer(Some(recorder.clone()));
+    let _ = client.fetch_body_binary("<a@b>").await.unwrap();
+    client.set_observer(None);
+    let _ = client.fetch_body_binary("<a@b>").await.unwrap();
+
+    assert_eq!(*recorder.commands.lock().unwrap(), vec!["BODY <a@b>"]);
+    assert_eq!(*recorder.responses.lock().unwrap(), vec![222]);
+    assert_eq!(
+        recorder.bytes_out.load(Ordering::SeqCst),
+        "BODY <a@b>\r\n".len()
+    );
+}
+
+#[tokio::test]
+async fn test_group_renumbering_is_reported() {
+    let recorder = Arc::new(Recorder::default());
+    let config = mock_server().await.with_observer(recorder.clone());
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    client.select_group("alt.renumbered").await.unwrap();
+    client.select_group("alt.renumbered").await.unwrap();
+    assert!(recorder.renumberings.lock().unwrap().is_empty());
+
+    client.select_group("alt.renumbered").await.unwrap();
+    let renumberings = recorder.renumberings.lock().unwrap();
+    assert_eq!(renumberings.len(), 1);
+    assert_eq!(renumberings[0].group, "alt.renumbered");
+    assert_eq!(
+        renumberings[0].reason,
+        RenumberReason::HighWatermarkDecreased
+    );
+    assert_eq!(renumberings[0].stale_range(), 0..=1099);
+    assert_eq!(
+        client
+            .group_watermarks()
+            .get("alt.renumbered")
+            .map(|info| info.last),
+        Some(3)
+    );
+}
+
+#[tokio::test]
+async fn test_saved_watermarks_detect_renumbering_on_new_connection() {
+    let recorder = Arc::new(Recorder::default());
+    let config = Arc::new(mock_server().await.with_observer(recorder.clone()));
+
+    let mut client = NntpClient::connect(config.clone()).await.unwrap();
+    client.select_group("alt.renumbered").await.unwrap();
+    let saved = client.group_watermarks().clone();
+
+    // A fresh connection sees the group as rebuilt only from its third GROUP
+    let mut client = NntpClient::connect(config).await.unwrap();
+    client.set_group_watermarks(saved);
+    for _ in 0..3 {
+        client.select_group("alt.renumbered").await.unwrap();
+    }
+    assert_eq!(recorder.renumberings.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_skipped_overview_lines_are_reported() {
+    let recorder = Arc::new(Recorder::default());
+    let config = mock_server().await.with_observer(recorder.clone());
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let entries = client.fetch_xover("1-2").await.unwrap();
+    assert_eq!(entries.len(), 1);
+
+    let warnings = client.take_parse_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(
+        &warnings[0],
+        ParseWarning::SkippedOverviewLine { line, .. } if line == "garbage"
+    ));
+    assert!(client.parse_warnings().is_empty());
+    assert_eq!(*recorder.warnings.lock().unwrap(), warnings);
+}
+
+#[tokio::test]
+async fn test_unstuffed_dot_lines_are_reported() {
+    let recorder = Arc::new(Recorder::default());
+    let config = mock_server().await.with_observer(recorder.clone());
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let body = client.fetch_body_binary("<unstuffed@b>").await.unwrap();
+    assert_eq!(body.data, b"line one\r\n.hidden\r\n.dotted\r\n");
+
+    let expected = ParseWarning::UnstuffedDotLine {
+        line: 2,
+        terminator_like: false,
+    };
+    assert_eq!(client.take_parse_warnings(), vec![expected.clone()]);
+    assert_eq!(*recorder.warnings.lock().unwrap(), vec![expected]);
+}
+
+#[tokio::test]
+async fn test_strict_dot_stuffing_fails_body() {
+    let config = mock_server().await.with_strict_dot_stuffing();
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.fetch_body_binary("<unstuffed@b>").await;
+    assert!(matches!(result, Err(NntpError::MalformedBody(_))));
+
+    // The whole response was consumed
+    let body = client.fetch_body_binary("<a@b>").await.unwrap();
+    assert_eq!(body.data, b"line one\r\n.dotted\r\n");
+}
+
+#[tokio::test]
+async fn test_strict_dot_stuffing_fails_body_stream() {
+    let config = mock_server().await.with_strict_dot_stuffing();
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let mut stream = client.fetch_body_stream("<unstuffed@b>").await.unwrap();
+    let mut data = Vec::new();
+    let error = stream.read_to_end(&mut data).await.unwrap_err();
+    assert!(error.to_string().contains("unstuffed dot"), "{}", error);
+    drop(stream);
+    assert!(!client.is_broken());
+
+    let body = client.fetch_body_binary("<a@b>").await.unwrap();
+    assert_eq!(body.data, b"line one\r\n.dotted\r\n");
+}
+
+#[tokio::test]
+async fn test_body_stream_reports_unstuffed_dot_lines() {
+    let config = mock_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let mut stream = client.fetch_body_stream("<unstuffed@b>").await.unwrap();
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data).await.unwrap();
+    drop(stream);
+    assert_eq!(data, b"line one\r\n.hidden\r\n.dotted\r\n");
+    assert_eq!(
+        client.take_parse_warnings(),
+        vec![ParseWarning::UnstuffedDotLine {
+            line: 2,
+            terminator_like: false,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_strict_dot_stuffing_fails_compressed_body() {
+    let config = mock_server().await.with_strict_dot_stuffing();
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(client.try_enable_compression().await.unwrap());
+
+    let result = client.fetch_body_binary("<gzip@b>").await;
+    assert!(matches!(result, Err(NntpError::MalformedBody(_))));
+
+    let body = client.fetch_body_binary("<a@b>").await.unwrap();
+    assert_eq!(body.data, b"line one\r\n.dotted\r\n");
+}
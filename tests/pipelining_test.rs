@@ -1,7 +1,8 @@
 //! Tests for NNTP command pipelining
 //!
 //! This module tests the fetch_articles_pipelined() method which sends
-//! multiple ARTICLE commands before waiting for responses.
+//! multiple ARTICLE commands before waiting for responses, and the
+//! size-bounded window of fetch_articles_pipelined_sized().
 
 #[cfg(feature = "live-tests")]
 mod live_pipelining_tests {
@@ -17,14 +18,7 @@ mod live_pipelining_tests {
         let username = std::env::var("NNTP_USER").expect("NNTP_USER not set");
         let password = std::env::var("NNTP_PASS").expect("NNTP_PASS not set");
 
-        ServerConfig {
-            host,
-            port,
-            tls: true,
-            allow_insecure_tls: false,
-            username,
-            password,
-        }
+        ServerConfig::new(host, port, true, username, password)
     }
 
     fn get_test_group() -> String {
@@ -354,3 +348,1119 @@ mod live_pipelining_tests {
         );
     }
 }
+
+/// Size-bounded pipelining against a local mock server
+mod windowed_pipelining_tests {
+    use nntp_rs::nzb::NzbSegment;
+    use nntp_rs::{
+        ConnectionObserver, FetchConfig, NntpClient, NntpError, SegmentFetcher, SegmentStatus,
+        ServerConfig,
+    };
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server that answers ARTICLE commands one at a time, recording
+    /// the largest number of commands received but not yet answered
+    async fn mock_server(missing: &'static [&'static str]) -> (ServerConfig, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let max_outstanding = Arc::new(AtomicUsize::new(0));
+        let recorded = Arc::clone(&max_outstanding);
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            serve(socket, missing, &recorded).await;
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            max_outstanding,
+        )
+    }
+
+    async fn serve(socket: TcpStream, missing: &[&str], max_outstanding: &AtomicUsize) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut pending = VecDeque::new();
+        loop {
+            // Collect every command the client has pipelined so far
+            while let Ok(line) =
+                tokio::time::timeout(Duration::from_millis(20), lines.next_line()).await
+            {
+                let Some(line) = line.unwrap() else { return };
+                pending.push_back(line.trim_start_matches("ARTICLE ").to_string());
+            }
+            max_outstanding.fetch_max(pending.len(), Ordering::SeqCst);
+
+            let Some(id) = pending.pop_front() else {
+                continue;
+            };
+            let reply = if missing.contains(&id.as_str()) {
+                "430 No such article\r\n".to_string()
+            } else {
+                format!("220 0 {id}\r\nMessage-ID: {id}\r\n\r\nbody of {id}\r\n.\r\n")
+            };
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+    }
+
+    fn ids(count: usize) -> Vec<String> {
+        (1..=count).map(|n| format!("<{n}@test>")).collect()
+    }
+
+    #[tokio::test]
+    async fn test_window_limited_by_article_count() {
+        let (config, max_outstanding) = mock_server(&[]).await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids = ids(8);
+        let articles: Vec<(&str, u64)> = ids.iter().map(|id| (id.as_str(), 100)).collect();
+        let results = client
+            .fetch_articles_pipelined_sized(&articles, 3, u64::MAX)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 8);
+        for (id, result) in ids.iter().zip(results) {
+            let data = result.unwrap().data;
+            assert!(String::from_utf8(data).unwrap().contains(id.as_str()));
+        }
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_window_limited_by_bytes() {
+        let (config, max_outstanding) = mock_server(&[]).await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids = ids(6);
+        let articles: Vec<(&str, u64)> = ids.iter().map(|id| (id.as_str(), 400)).collect();
+        let results = client
+            .fetch_articles_pipelined_sized(&articles, 10, 1000)
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_article_still_fetched() {
+        let (config, max_outstanding) = mock_server(&[]).await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids = ids(3);
+        let articles: Vec<(&str, u64)> = ids.iter().map(|id| (id.as_str(), 5000)).collect();
+        let results = client
+            .fetch_articles_pipelined_sized(&articles, 10, 1000)
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 1);
+    }
+
+    /// Counts socket writes
+    #[derive(Debug, Default)]
+    struct WriteCounter {
+        writes: AtomicUsize,
+        bytes: AtomicUsize,
+    }
+
+    impl ConnectionObserver for WriteCounter {
+        fn on_bytes_out(&self, bytes: usize) {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.bytes.fetch_add(bytes, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_window_sent_in_one_write() {
+        let (config, max_outstanding) = mock_server(&[]).await;
+        let counter = Arc::new(WriteCounter::default());
+        let config = config.with_observer(counter.clone());
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids = ids(6);
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let responses = client.fetch_articles_pipelined(&id_refs, 6).await.unwrap();
+
+        assert_eq!(responses.len(), 6);
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 6);
+        assert_eq!(counter.writes.load(Ordering::SeqCst), 1);
+        let sent: usize = ids.iter().map(|id| "ARTICLE \r\n".len() + id.len()).sum();
+        assert_eq!(counter.bytes.load(Ordering::SeqCst), sent);
+    }
+
+    #[tokio::test]
+    async fn test_missing_article_keeps_connection_in_sync() {
+        let (config, _) = mock_server(&["<2@test>"]).await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids = ids(4);
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let result = client.fetch_articles_pipelined(&id_refs, 4).await;
+        assert!(matches!(result, Err(NntpError::NoSuchArticle(id)) if id == "<2@test>"));
+
+        // All four responses were consumed; the next fetch lines up
+        let response = client.fetch_article_binary("<4@test>").await.unwrap();
+        assert!(
+            String::from_utf8(response.data)
+                .unwrap()
+                .contains("<4@test>")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_segment_fetcher_pipelines_by_size() {
+        let (config, max_outstanding) = mock_server(&["<3@test>"]).await;
+        let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let segments: Vec<NzbSegment> = ids(5)
+            .into_iter()
+            .enumerate()
+            .map(|(n, message_id)| NzbSegment {
+                bytes: 300,
+                number: n as u32 + 1,
+                message_id,
+            })
+            .collect();
+        let config = FetchConfig {
+            skip_not_found: true,
+            pipeline_depth: 4,
+            max_inflight_bytes: 900,
+            ..FetchConfig::default()
+        };
+
+        let fetcher = SegmentFetcher::new(client, config);
+        let results = fetcher.fetch_segments(&segments).await.unwrap();
+
+        let statuses: Vec<_> = results.iter().map(|r| r.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                SegmentStatus::Completed,
+                SegmentStatus::Completed,
+                SegmentStatus::NotFound,
+                SegmentStatus::Completed,
+                SegmentStatus::Completed,
+            ]
+        );
+        assert_eq!(
+            results[0].content.as_ref().unwrap().last().unwrap(),
+            "body of <1@test>"
+        );
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 3);
+
+        let progress = fetcher.progress().await;
+        assert_eq!(progress.completed_segments, 4);
+        assert_eq!(progress.not_found_segments, 1);
+    }
+
+    /// Start a server whose first connection answers `<2@test>` with garbage,
+    /// leaving the responses after it unread; later connections behave
+    async fn garbling_server() -> (ServerConfig, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let first = accepted.fetch_add(1, Ordering::SeqCst) == 0;
+                tokio::spawn(serve_garbling(socket, first));
+            }
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            connections,
+        )
+    }
+
+    async fn serve_garbling(socket: TcpStream, garble: bool) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let id = line.trim_start_matches("ARTICLE ");
+            let reply = if garble && id == "<2@test>" {
+                "garbage\r\n".to_string()
+            } else {
+                format!("220 0 {id}\r\nMessage-ID: {id}\r\n\r\nbody of {id}\r\n.\r\n")
+            };
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_segment_fetcher_reconnects_after_pipeline_failure() {
+        let (config, connections) = garbling_server().await;
+        let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let segments: Vec<NzbSegment> = ids(4)
+            .into_iter()
+            .enumerate()
+            .map(|(n, message_id)| NzbSegment {
+                bytes: 100,
+                number: n as u32 + 1,
+                message_id,
+            })
+            .collect();
+        let config = FetchConfig {
+            pipeline_depth: 4,
+            ..FetchConfig::default()
+        };
+
+        let fetcher = SegmentFetcher::new(client, config);
+        let results = fetcher.fetch_segments(&segments).await.unwrap();
+
+        // Every segment holds its own article, not a response left over from
+        // the failed window
+        for (segment, result) in segments.iter().zip(&results) {
+            assert_eq!(result.status, SegmentStatus::Completed);
+            assert_eq!(
+                result.content.as_ref().unwrap().last().unwrap(),
+                &format!("body of {}", segment.message_id)
+            );
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+}
+
+/// Pipelined HDR by message-id against a local mock server
+mod hdr_many_tests {
+    use nntp_rs::{NntpClient, NntpError, ServerConfig};
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Start a server answering `HDR Subject <id>`; `<missing@test>` does not
+    /// exist and `<bare@test>` has no Subject. With `hdr_supported` false every
+    /// HDR is answered with 500.
+    async fn mock_server(hdr_supported: bool) -> ServerConfig {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = socket.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer
+                .write_all(b"200 mock server ready\r\n")
+                .await
+                .unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let reply = match line.strip_prefix("HDR Subject ") {
+                    _ if !hdr_supported => "500 What?\r\n".to_string(),
+                    Some("<missing@test>") => "430 No such article\r\n".to_string(),
+                    Some("<bare@test>") => "225 Headers follow\r\n0 \r\n.\r\n".to_string(),
+                    Some(id) => format!("225 Headers follow\r\n0 Subject of {id}\r\n.\r\n"),
+                    None => "500 What?\r\n".to_string(),
+                };
+                writer.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+
+        ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+    }
+
+    #[tokio::test]
+    async fn test_hdr_many_keyed_by_message_id() {
+        let config = mock_server(true).await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let mut ids: Vec<String> = (1..=70).map(|n| format!("<{n}@test>")).collect();
+        ids.push("<missing@test>".to_string());
+        ids.push("<bare@test>".to_string());
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+        let subjects = client.hdr_many("Subject", &ids).await.unwrap();
+        assert_eq!(subjects.len(), 72);
+        assert_eq!(
+            subjects["<42@test>"].as_deref().unwrap(),
+            "Subject of <42@test>"
+        );
+        assert_eq!(subjects["<bare@test>"].as_deref().unwrap(), "");
+        assert!(matches!(
+            subjects["<missing@test>"],
+            Err(NntpError::NoSuchArticle(_))
+        ));
+
+        // The connection is still in sync
+        let again = client.hdr_many("Subject", &["<1@test>"]).await.unwrap();
+        assert_eq!(again["<1@test>"].as_deref().unwrap(), "Subject of <1@test>");
+    }
+
+    #[tokio::test]
+    async fn test_hdr_many_unsupported() {
+        let config = mock_server(false).await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let result = client.hdr_many("Subject", &["<1@test>", "<2@test>"]).await;
+        assert!(matches!(result, Err(NntpError::Protocol { code: 500, .. })));
+        assert!(client.hdr_many("Subject", &[]).await.unwrap().is_empty());
+    }
+}
+
+/// Pipelined STAT against a local mock server
+mod stat_many_tests {
+    use nntp_rs::{ConnectionObserver, NntpClient, NntpError, ServerConfig};
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server answering STAT, where ids containing "missing" do not
+    /// exist, recording the largest number of unanswered commands
+    async fn mock_server() -> (ServerConfig, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let max_outstanding = Arc::new(AtomicUsize::new(0));
+        let recorded = Arc::clone(&max_outstanding);
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            serve(socket, &recorded).await;
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            max_outstanding,
+        )
+    }
+
+    async fn serve(socket: TcpStream, max_outstanding: &AtomicUsize) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut pending = VecDeque::new();
+        loop {
+            while let Ok(line) =
+                tokio::time::timeout(Duration::from_millis(20), lines.next_line()).await
+            {
+                let Some(line) = line.unwrap() else { return };
+                pending.push_back(line.trim_start_matches("STAT ").to_string());
+            }
+            max_outstanding.fetch_max(pending.len(), Ordering::SeqCst);
+
+            let Some(id) = pending.pop_front() else {
+                continue;
+            };
+            let reply = if id.contains("missing") {
+                "430 No such article\r\n".to_string()
+            } else {
+                format!("223 0 {id}\r\n")
+            };
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stat_many_reports_each_id() {
+        let (config, max_outstanding) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids = [
+            "<1@test>",
+            "<missing-2@test>",
+            "<3@test>",
+            "<4@test>",
+            "<missing-5@test>",
+            "<6@test>",
+        ];
+        let results = client.stat_many(&ids, 4).await.unwrap();
+
+        assert_eq!(results.len(), ids.len());
+        for (id, result) in ids.iter().zip(&results) {
+            if id.contains("missing") {
+                assert!(matches!(result, Err(NntpError::NoSuchArticle(m)) if m == id));
+            } else {
+                assert_eq!(result.as_ref().unwrap().message_id, *id);
+            }
+        }
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 4);
+
+        // The connection is still in sync
+        let again = client.stat_many(&["<7@test>"], 4).await.unwrap();
+        assert_eq!(again[0].as_ref().unwrap().message_id, "<7@test>");
+    }
+
+    /// Counts the writes to the socket
+    #[derive(Debug, Default)]
+    struct Writes(AtomicUsize);
+
+    impl ConnectionObserver for Writes {
+        fn on_bytes_out(&self, _bytes: usize) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stat_many_refills_the_window_in_batches() {
+        let (config, _) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+        let writes = Arc::new(Writes::default());
+        client.set_observer(Some(writes.clone()));
+
+        let ids: Vec<String> = (1..=8).map(|i| format!("<{i}@test>")).collect();
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let results = client.stat_many(&ids, 4).await.unwrap();
+
+        assert!(results.iter().all(Result::is_ok));
+        // The first four, then two at a time as half the window is answered
+        assert_eq!(writes.0.load(Ordering::SeqCst), 3);
+    }
+}
+
+/// Segment retrieval by article number against a local mock server
+mod retrieval_mode_tests {
+    use nntp_rs::nzb::NzbSegment;
+    use nntp_rs::{
+        FetchConfig, NntpClient, RetrievalMode, SegmentFetcher, SegmentStatus, ServerConfig,
+        XoverEntry,
+    };
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server holding articles 1-20 of alt.test as `<n@test>`, except
+    /// that article numbers in `stale` hold some other article and a STAT of
+    /// a number in `hang_up` closes the connection
+    async fn mock_server(
+        stale: &'static [u64],
+        hang_up: &'static [u64],
+    ) -> (ServerConfig, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&commands);
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            serve(socket, stale, hang_up, &recorded).await;
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            commands,
+        )
+    }
+
+    async fn serve(
+        socket: TcpStream,
+        stale: &[u64],
+        hang_up: &[u64],
+        commands: &Mutex<Vec<String>>,
+    ) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        while let Some(line) = lines.next_line().await.unwrap() {
+            commands.lock().unwrap().push(line.clone());
+            let reply = match line.split_once(' ') {
+                Some(("GROUP", group)) => format!("211 20 1 20 {group}\r\n"),
+                Some(("ARTICLE", id)) if id.starts_with('<') => article(0, id),
+                Some(("ARTICLE", number)) => {
+                    let number: u64 = number.parse().unwrap();
+                    article(number, &stored_id(stale, number))
+                }
+                Some(("STAT", number)) => {
+                    let number: u64 = number.parse().unwrap();
+                    if hang_up.contains(&number) {
+                        return;
+                    }
+                    format!("223 {number} {}\r\n", stored_id(stale, number))
+                }
+                _ => "500 unknown command\r\n".to_string(),
+            };
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+    }
+
+    fn stored_id(stale: &[u64], number: u64) -> String {
+        if stale.contains(&number) {
+            "<other@test>".to_string()
+        } else {
+            format!("<{number}@test>")
+        }
+    }
+
+    fn article(number: u64, id: &str) -> String {
+        format!("220 {number} {id}\r\nMessage-ID: {id}\r\n\r\nbody of {id}\r\n.\r\n")
+    }
+
+    fn segments(count: u64) -> Vec<NzbSegment> {
+        (1..=count)
+            .map(|n| NzbSegment {
+                bytes: 100,
+                number: n as u32,
+                message_id: format!("<{n}@test>"),
+            })
+            .collect()
+    }
+
+    fn overview(count: u64) -> Vec<XoverEntry> {
+        (1..=count)
+            .map(|n| {
+                XoverEntry::new(n, format!("<{n}@test>"))
+                    .with_subject(format!("file ({n}/{count})"))
+                    .with_author("poster@test")
+                    .with_size(100, 1)
+            })
+            .collect()
+    }
+
+    fn sent(commands: &Mutex<Vec<String>>, verb: &str) -> Vec<String> {
+        commands
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.starts_with(verb))
+            .cloned()
+            .collect()
+    }
+
+    async fn fetcher(config: &ServerConfig, fetch: FetchConfig, count: u64) -> SegmentFetcher {
+        let client = NntpClient::connect(Arc::new(config.clone())).await.unwrap();
+        SegmentFetcher::new(client, fetch).with_overview("alt.test", &overview(count))
+    }
+
+    #[tokio::test]
+    async fn test_fetches_by_article_number() {
+        let (config, commands) = mock_server(&[2], &[]).await;
+        let fetch = FetchConfig {
+            retrieval: RetrievalMode::ArticleNumber,
+            ..FetchConfig::default()
+        };
+        let fetcher = fetcher(&config, fetch, 3).await;
+
+        let results = fetcher.fetch_segments(&segments(3)).await.unwrap();
+        assert!(results.iter().all(|r| r.status == SegmentStatus::Completed));
+
+        // Article 2 was renumbered: STAT catches it, fetched by message-id
+        assert_eq!(sent(&commands, "STAT "), ["STAT 1", "STAT 2", "STAT 3"]);
+        assert_eq!(
+            sent(&commands, "ARTICLE "),
+            ["ARTICLE 1", "ARTICLE <2@test>", "ARTICLE 3"]
+        );
+        assert_eq!(commands.lock().unwrap()[0], "GROUP alt.test");
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_falls_back_to_message_id() {
+        let stale: &'static [u64] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        let (config, commands) = mock_server(stale, &[]).await;
+        let fetch = FetchConfig {
+            retrieval: RetrievalMode::Adaptive,
+            ..FetchConfig::default()
+        };
+        let fetcher = fetcher(&config, fetch, 20).await;
+
+        let results = fetcher.fetch_segments(&segments(20)).await.unwrap();
+        assert!(results.iter().all(|r| r.status == SegmentStatus::Completed));
+        assert_eq!(fetcher.effective_retrieval(), RetrievalMode::MessageId);
+
+        assert_eq!(sent(&commands, "STAT ").len(), 16);
+        let by_number = sent(&commands, "ARTICLE ")
+            .iter()
+            .filter(|c| !c.contains('<'))
+            .count();
+        assert_eq!(by_number, 0);
+    }
+
+    #[tokio::test]
+    async fn test_number_lookup_surfaces_connection_errors() {
+        let (config, commands) = mock_server(&[], &[1]).await;
+        let fetch = FetchConfig {
+            retrieval: RetrievalMode::ArticleNumber,
+            max_retries: 0,
+            ..FetchConfig::default()
+        };
+        let fetcher = fetcher(&config, fetch, 1).await;
+
+        let result = fetcher.fetch_segment(&segments(1)[0], 0).await;
+        assert_eq!(result.status, SegmentStatus::Failed);
+        assert!(result.error.unwrap().contains("Connection closed"));
+        assert!(sent(&commands, "ARTICLE ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_server_override() {
+        let (config, commands) = mock_server(&[], &[]).await;
+        let mut fetch = FetchConfig {
+            retrieval: RetrievalMode::ArticleNumber,
+            ..FetchConfig::default()
+        };
+        fetch
+            .server_retrieval
+            .insert(config.authority(), RetrievalMode::MessageId);
+        let fetcher = fetcher(&config, fetch, 2).await;
+
+        fetcher.fetch_segments(&segments(2)).await.unwrap();
+        assert_eq!(
+            sent(&commands, "ARTICLE "),
+            ["ARTICLE <1@test>", "ARTICLE <2@test>"]
+        );
+    }
+}
+
+/// Pipelined CHECK against a local mock server
+mod check_many_tests {
+    use nntp_rs::{CheckStatus, NntpClient, NntpError, ServerConfig};
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server answering CHECK, which already has ids containing
+    /// "have", is busy for ids containing "busy" and rejects ids containing
+    /// "bad", recording the largest number of unanswered commands
+    async fn mock_server() -> (ServerConfig, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let max_outstanding = Arc::new(AtomicUsize::new(0));
+        let recorded = Arc::clone(&max_outstanding);
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            serve(socket, &recorded).await;
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            max_outstanding,
+        )
+    }
+
+    async fn serve(socket: TcpStream, max_outstanding: &AtomicUsize) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut pending = VecDeque::new();
+        loop {
+            while let Ok(line) =
+                tokio::time::timeout(Duration::from_millis(20), lines.next_line()).await
+            {
+                let Some(line) = line.unwrap() else { return };
+                pending.push_back(line.trim_start_matches("CHECK ").to_string());
+            }
+            max_outstanding.fetch_max(pending.len(), Ordering::SeqCst);
+
+            let Some(id) = pending.pop_front() else {
+                continue;
+            };
+            let reply = if id.contains("have") {
+                format!("438 {id}\r\n")
+            } else if id.contains("busy") {
+                format!("431 {id}\r\n")
+            } else if id.contains("bad") {
+                "501 Syntax error\r\n".to_string()
+            } else {
+                format!("238 {id}\r\n")
+            };
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_many_matches_each_id() {
+        let (config, max_outstanding) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids: Vec<String> = (0..70)
+            .map(|n| match n % 3 {
+                0 => format!("<{n}@test>"),
+                1 => format!("<have-{n}@test>"),
+                _ => format!("<busy-{n}@test>"),
+            })
+            .collect();
+        let refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let results = client.check_many(&refs).await.unwrap();
+
+        assert_eq!(results.len(), ids.len());
+        for (id, result) in ids.iter().zip(&results) {
+            assert_eq!(&result.message_id, id);
+            let expected = if id.contains("have") {
+                CheckStatus::NotWanted
+            } else if id.contains("busy") {
+                CheckStatus::Later
+            } else {
+                CheckStatus::Send
+            };
+            assert_eq!(result.status, expected);
+        }
+        let outstanding = max_outstanding.load(Ordering::SeqCst);
+        assert!(outstanding > 1 && outstanding <= 64, "{}", outstanding);
+    }
+
+    #[tokio::test]
+    async fn test_check_many_error_keeps_connection_in_sync() {
+        let (config, _) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let result = client
+            .check_many(&["<1@test>", "<bad-2@test>", "<3@test>"])
+            .await;
+        assert!(matches!(result, Err(NntpError::Protocol { code: 501, .. })));
+
+        let again = client.check_many(&["<4@test>"]).await.unwrap();
+        assert_eq!(again[0].message_id, "<4@test>");
+        assert_eq!(again[0].status, CheckStatus::Send);
+    }
+}
+
+/// Generic command pipelines against a local mock server
+mod run_pipeline_tests {
+    use nntp_rs::{NntpClient, NntpError, Pipeline, ServerConfig};
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server answering STAT, HEAD and DATE, recording the largest
+    /// number of unanswered commands
+    async fn mock_server() -> (ServerConfig, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let max_outstanding = Arc::new(AtomicUsize::new(0));
+        let recorded = Arc::clone(&max_outstanding);
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            serve(socket, &recorded).await;
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            max_outstanding,
+        )
+    }
+
+    async fn serve(socket: TcpStream, max_outstanding: &AtomicUsize) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut pending = VecDeque::new();
+        loop {
+            while let Ok(line) =
+                tokio::time::timeout(Duration::from_millis(20), lines.next_line()).await
+            {
+                let Some(line) = line.unwrap() else { return };
+                pending.push_back(line);
+            }
+            max_outstanding.fetch_max(pending.len(), Ordering::SeqCst);
+
+            let Some(command) = pending.pop_front() else {
+                continue;
+            };
+            writer.write_all(reply(&command).as_bytes()).await.unwrap();
+        }
+    }
+
+    fn reply(command: &str) -> String {
+        match command.split_once(' ') {
+            Some(("STAT", id)) if id.contains("missing") => "430 No such article\r\n".into(),
+            Some(("STAT", id)) => format!("223 0 {id}\r\n"),
+            Some(("HEAD", id)) if id.contains("missing") => "430 No such article\r\n".into(),
+            Some(("HEAD", id)) => format!("221 0 {id}\r\nMessage-ID: {id}\r\n..dot\r\n.\r\n"),
+            _ if command == "DATE" => "111 20260101000000\r\n".into(),
+            _ => "500 Unknown command\r\n".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_mixes_response_kinds() {
+        let (config, max_outstanding) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let mut pipeline = Pipeline::new().with_depth(4);
+        for n in 0..10 {
+            pipeline
+                .single(format!("STAT <{n}@test>"))
+                .multiline(format!("HEAD <{n}@test>"));
+        }
+        pipeline
+            .multiline("HEAD <missing@test>")
+            .single("DATE")
+            .single("BOGUS");
+        let responses = client.run_pipeline(&pipeline).await.unwrap();
+
+        assert_eq!(responses.len(), pipeline.len());
+        for n in 0..10 {
+            assert_eq!(responses[2 * n].code, 223);
+            let head = &responses[2 * n + 1];
+            assert_eq!(head.code, 221);
+            assert_eq!(
+                head.lines,
+                vec![format!("Message-ID: <{n}@test>"), ".dot".into()]
+            );
+        }
+        assert_eq!(responses[20].code, 430);
+        assert_eq!(responses[21].code, 111);
+        assert_eq!(responses[22].code, 500);
+        let outstanding = max_outstanding.load(Ordering::SeqCst);
+        assert!(outstanding > 1 && outstanding <= 4, "{}", outstanding);
+
+        // The connection is still in sync afterwards
+        let mut pipeline = Pipeline::new();
+        pipeline.single("DATE");
+        assert_eq!(client.run_pipeline(&pipeline).await.unwrap()[0].code, 111);
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_rejects_multi_line_command() {
+        let (config, max_outstanding) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let mut pipeline = Pipeline::new();
+        pipeline.single("DATE").single("STAT <a@test>\r\nQUIT");
+        let result = client.run_pipeline(&pipeline).await;
+        assert!(matches!(result, Err(NntpError::InvalidArgument(_))));
+
+        // Nothing was sent, so the next command gets its own answer
+        let mut pipeline = Pipeline::new();
+        pipeline.single("DATE");
+        assert_eq!(client.run_pipeline(&pipeline).await.unwrap()[0].code, 111);
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), 1);
+    }
+}
+
+/// Streaming feeds against a local mock server
+mod stream_feeder_tests {
+    use bytes::Bytes;
+    use futures_core::Stream;
+    use nntp_rs::{FeedArticle, NntpClient, NntpError, ServerConfig, StreamFeeder};
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Articles handed out one at a time
+    struct Articles(VecDeque<FeedArticle>);
+
+    impl Stream for Articles {
+        type Item = FeedArticle;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<FeedArticle>> {
+            Poll::Ready(self.0.pop_front())
+        }
+    }
+
+    fn articles(ids: &[String]) -> Articles {
+        Articles(
+            ids.iter()
+                .map(|id| {
+                    let data = format!("Message-ID: {id}\r\n\r\n..dotted\r\n");
+                    FeedArticle::new(id.clone(), Bytes::from(data))
+                })
+                .collect(),
+        )
+    }
+
+    /// Start a streaming peer that answers each burst of commands in reverse
+    /// order. CHECK gets 438 for ids containing "have", 431 for "busy" and
+    /// 238 otherwise; TAKETHIS gets 439 for ids containing "junk" and 239
+    /// otherwise. Records the largest number of unanswered commands and the
+    /// number of articles received.
+    async fn mock_server() -> (ServerConfig, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let max_outstanding = Arc::new(AtomicUsize::new(0));
+        let received = Arc::new(AtomicUsize::new(0));
+        let (recorded, counted) = (Arc::clone(&max_outstanding), Arc::clone(&received));
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            serve(socket, &recorded, &counted).await;
+        });
+
+        (
+            ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+            max_outstanding,
+            received,
+        )
+    }
+
+    async fn serve(socket: TcpStream, max_outstanding: &AtomicUsize, received: &AtomicUsize) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut pending = Vec::new();
+        loop {
+            while let Ok(line) =
+                tokio::time::timeout(Duration::from_millis(20), lines.next_line()).await
+            {
+                let Some(line) = line.unwrap() else { return };
+                if skip_article(&line, &mut lines).await {
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+                pending.push(line);
+            }
+            max_outstanding.fetch_max(pending.len(), Ordering::SeqCst);
+
+            for command in pending.drain(..).rev() {
+                let reply = match command.split_once(' ') {
+                    Some(("CHECK", id)) if id.contains("have") => format!("438 {id}\r\n"),
+                    Some(("CHECK", id)) if id.contains("busy") => format!("431 {id}\r\n"),
+                    Some(("CHECK", id)) => format!("238 {id}\r\n"),
+                    Some(("TAKETHIS", id)) if id.contains("junk") => format!("439 {id}\r\n"),
+                    Some(("TAKETHIS", id)) => format!("239 {id}\r\n"),
+                    _ => "500 unknown command\r\n".to_string(),
+                };
+                writer.write_all(reply.as_bytes()).await.unwrap();
+            }
+        }
+    }
+
+    /// Read past the article that follows a TAKETHIS command line
+    ///
+    /// Returns whether there was one.
+    async fn skip_article(
+        command: &str,
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+    ) -> bool {
+        if !command.starts_with("TAKETHIS ") {
+            return false;
+        }
+        while lines.next_line().await.unwrap().unwrap() != "." {}
+        true
+    }
+
+    /// Articles handed over through a channel, as they become available
+    struct Channel(tokio::sync::mpsc::Receiver<FeedArticle>);
+
+    impl Stream for Channel {
+        type Item = FeedArticle;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FeedArticle>> {
+            self.0.poll_recv(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feed_counts_each_outcome() {
+        let (config, max_outstanding, _) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let ids: Vec<String> = (0..100)
+            .map(|n| match n % 4 {
+                0 => format!("<{n}@test>"),
+                1 => format!("<have-{n}@test>"),
+                2 => format!("<busy-{n}@test>"),
+                _ => format!("<junk-{n}@test>"),
+            })
+            .collect();
+        let report = StreamFeeder::new()
+            .with_window(8)
+            .feed(&mut client, articles(&ids))
+            .await
+            .unwrap();
+
+        assert_eq!(report.accepted, 25);
+        assert_eq!(report.rejected, 25);
+        assert_eq!(report.not_wanted, 25);
+        assert_eq!(report.deferred, 25);
+        assert_eq!(report.offered(), 100);
+        let mut deferred = report.deferred_ids.clone();
+        deferred.sort();
+        let mut busy: Vec<String> = ids.into_iter().filter(|id| id.contains("busy")).collect();
+        busy.sort();
+        assert_eq!(deferred, busy);
+
+        let outstanding = max_outstanding.load(Ordering::SeqCst);
+        assert!(outstanding > 1 && outstanding <= 8, "{}", outstanding);
+    }
+
+    #[tokio::test]
+    async fn test_feed_malformed_article_keeps_connection_in_sync() {
+        let (config, _, _) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        let mut source = articles(&["<1@test>".to_string()]);
+        source.0.push_back(FeedArticle::new(
+            "<2@test>",
+            Bytes::from_static(b"Subject: x\r\n\r\n.\r\nmore\r\n"),
+        ));
+        source.0.extend(articles(&["<3@test>".to_string()]).0);
+        let result = StreamFeeder::new().feed(&mut client, source).await;
+        assert!(matches!(result, Err(NntpError::InvalidArgument(_))));
+
+        let report = StreamFeeder::new()
+            .feed(&mut client, articles(&["<4@test>".to_string()]))
+            .await
+            .unwrap();
+        assert_eq!(report.accepted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_feed_sends_articles_while_source_is_idle() {
+        let (config, _, received) = mock_server().await;
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+        // The second article only comes once the peer has the first
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        let mut first = articles(&["<1@test>".to_string()]);
+        sender.send(first.0.pop_front().unwrap()).await.unwrap();
+        tokio::spawn(async move {
+            while received.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            let mut second = articles(&["<2@test>".to_string()]);
+            sender.send(second.0.pop_front().unwrap()).await.unwrap();
+        });
+
+        let report = tokio::time::timeout(
+            Duration::from_secs(5),
+            StreamFeeder::new().feed(&mut client, Channel(receiver)),
+        )
+        .await
+        .expect("feed stalled on an idle source")
+        .unwrap();
+        assert_eq!(report.accepted, 2);
+    }
+}
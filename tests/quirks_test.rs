@@ -0,0 +1,222 @@
+//! Server quirk workaround tests
+//!
+//! A scripted server misbehaves the way known providers do; the client must
+//! cope when the matching [`ServerQuirks`] flag is set.
+
+use nntp_rs::{GzipDetection, NntpClient, NntpError, SaslPlain, ServerConfig, ServerQuirks};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Commands received by the server
+type Transcript = Arc<Mutex<Vec<String>>>;
+
+/// Start a server that answers each command with `reply`
+async fn quirky_server(
+    quirks: ServerQuirks,
+    reply: fn(&str, &[String]) -> &'static str,
+) -> (NntpClient, Transcript) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let transcript = Transcript::default();
+
+    let shared = transcript.clone();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"200 mock ready\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let answer = {
+                let mut transcript = shared.lock().unwrap();
+                let answer = reply(&line, &transcript);
+                transcript.push(line);
+                answer
+            };
+            if writer.write_all(answer.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass").with_quirks(quirks);
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    (client, transcript)
+}
+
+/// Login replies shared by the scripted servers
+fn login(line: &str) -> Option<&'static str> {
+    if line.starts_with("AUTHINFO USER") {
+        Some("381 password required\r\n")
+    } else if line.starts_with("AUTHINFO PASS") || line.starts_with("AUTHINFO SASL") {
+        Some("281 welcome\r\n")
+    } else {
+        None
+    }
+}
+
+/// Forgets the login after the first BODY
+fn forgetful(line: &str, seen: &[String]) -> &'static str {
+    if let Some(reply) = login(line) {
+        return reply;
+    }
+    let bodies = seen.iter().filter(|line| line.starts_with("BODY")).count();
+    match (line.starts_with("BODY"), bodies) {
+        (true, 1) => "480 authentication required\r\n",
+        (true, _) => "222 0 <a@b> body\r\nhello\r\n.\r\n",
+        _ => "500 unknown command\r\n",
+    }
+}
+
+#[tokio::test]
+async fn test_reauth_on_480() {
+    let quirks = ServerQuirks {
+        reauth_on_480: true,
+        ..ServerQuirks::default()
+    };
+    let (mut client, transcript) = quirky_server(quirks, forgetful).await;
+    client.authenticate().await.unwrap();
+    let _ = client.fetch_body("<a@b>").await.unwrap();
+
+    let response = client.fetch_body("<a@b>").await.unwrap();
+    assert_eq!(response.lines, vec!["hello"]);
+    assert_eq!(
+        *transcript.lock().unwrap(),
+        vec![
+            "AUTHINFO USER user",
+            "AUTHINFO PASS pass",
+            "BODY <a@b>",
+            "BODY <a@b>",
+            "AUTHINFO USER user",
+            "AUTHINFO PASS pass",
+            "BODY <a@b>",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_reauth_on_480_replays_sasl() {
+    let quirks = ServerQuirks {
+        reauth_on_480: true,
+        ..ServerQuirks::default()
+    };
+    let (mut client, transcript) = quirky_server(quirks, forgetful).await;
+    client
+        .authenticate_sasl(SaslPlain::new("user", "pass"))
+        .await
+        .unwrap();
+    let _ = client.fetch_body("<a@b>").await.unwrap();
+
+    let response = client.fetch_body("<a@b>").await.unwrap();
+    assert_eq!(response.lines, vec!["hello"]);
+    let transcript = transcript.lock().unwrap();
+    assert!(transcript[0].starts_with("AUTHINFO SASL PLAIN "));
+    assert_eq!(transcript[1..3], ["BODY <a@b>", "BODY <a@b>"]);
+    assert_eq!(transcript[3], transcript[0]);
+    assert_eq!(transcript[4..], ["BODY <a@b>"]);
+}
+
+#[tokio::test]
+async fn test_480_without_quirk_is_an_error() {
+    let (mut client, _) = quirky_server(ServerQuirks::default(), forgetful).await;
+    client.authenticate().await.unwrap();
+    let _ = client.fetch_body("<a@b>").await.unwrap();
+
+    let result = client.fetch_body("<a@b>").await;
+    assert!(matches!(result, Err(NntpError::Protocol { code: 480, .. })));
+}
+
+#[tokio::test]
+async fn test_set_quirks_recomputes_gzip_detection() {
+    let quirks = ServerQuirks {
+        gzip_marker_absent: true,
+        ..ServerQuirks::default()
+    };
+    let (mut client, _) = quirky_server(quirks, forgetful).await;
+    assert_eq!(
+        *client.gzip_detection(),
+        GzipDetection::always_compressed_overview()
+    );
+
+    client.set_quirks(ServerQuirks::default());
+    assert_eq!(*client.gzip_detection(), GzipDetection::Auto);
+}
+
+/// Leaves COMPRESS out of its capabilities but supports it, and acknowledges
+/// XFEATURE with a 1xx code
+fn understated(line: &str, _: &[String]) -> &'static str {
+    match line {
+        "CAPABILITIES" => "101 Capability list:\r\nVERSION 2\r\nREADER\r\n.\r\n",
+        "COMPRESS DEFLATE" => "403 compression unavailable right now\r\n",
+        "XFEATURE COMPRESS GZIP" => "100 compression enabled\r\n",
+        _ => "500 unknown command\r\n",
+    }
+}
+
+#[tokio::test]
+async fn test_unreliable_capabilities_and_xfeature_ack() {
+    let quirks = ServerQuirks {
+        unreliable_capabilities: true,
+        xfeature_nonstandard_ack: true,
+        ..ServerQuirks::default()
+    };
+    let (mut client, transcript) = quirky_server(quirks, understated).await;
+    client.capability_set().await.unwrap();
+
+    assert!(client.try_enable_compression().await.unwrap());
+    assert_eq!(
+        *transcript.lock().unwrap(),
+        vec!["CAPABILITIES", "COMPRESS DEFLATE", "XFEATURE COMPRESS GZIP"]
+    );
+}
+
+#[tokio::test]
+async fn test_trusted_capabilities_and_strict_xfeature_ack() {
+    let (mut client, transcript) = quirky_server(ServerQuirks::default(), understated).await;
+    client.capability_set().await.unwrap();
+
+    assert!(!client.try_enable_compression().await.unwrap());
+    assert_eq!(
+        *transcript.lock().unwrap(),
+        vec!["CAPABILITIES", "XFEATURE COMPRESS GZIP"]
+    );
+}
+
+/// Sends Xref before the sizes and no References column
+fn drifting(line: &str, _: &[String]) -> &'static str {
+    match line {
+        "LIST OVERVIEW.FMT" => {
+            "215 Order of fields in overview database\r\n\
+             Subject:\r\nFrom:\r\nDate:\r\nMessage-ID:\r\nXref:full\r\nBytes:\r\nLines:\r\n.\r\n"
+        }
+        "XOVER 1-2" => {
+            "224 Overview information follows\r\n\
+             1\tFirst\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<one@test>\tXref: a 1\t1000\t20\r\n\
+             2\tSecond\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<two@test>\tXref: a 2\t2000\t40\r\n\
+             .\r\n"
+        }
+        _ => "500 unknown command\r\n",
+    }
+}
+
+#[tokio::test]
+async fn test_overview_column_drift() {
+    let quirks = ServerQuirks {
+        overview_column_drift: true,
+        ..ServerQuirks::default()
+    };
+    let (mut client, transcript) = quirky_server(quirks, drifting).await;
+
+    for _ in 0..2 {
+        let entries = client.fetch_xover("1-2").await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].message_id, "<two@test>");
+        assert_eq!(entries[1].references, "");
+        assert_eq!((entries[1].bytes, entries[1].lines), (2000, 40));
+    }
+    assert_eq!(
+        *transcript.lock().unwrap(),
+        vec!["LIST OVERVIEW.FMT", "XOVER 1-2", "XOVER 1-2"]
+    );
+    assert!(client.take_parse_warnings().is_empty());
+}
@@ -0,0 +1,189 @@
+//! Automatic reconnection tests
+//!
+//! A scripted server drops the first connection in the middle of a command;
+//! the client must reconnect, restore its session and retry.
+
+use nntp_rs::{
+    ConnectionObserver, GzipDetection, NntpClient, NntpError, ParseMode, ReconnectPolicy,
+    SaslPlain, ServerConfig, ServerQuirks,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Commands received, one list per accepted connection
+type Transcript = Arc<Mutex<Vec<Vec<String>>>>;
+
+/// Start a server that closes the first `drops` connections on their first BODY
+async fn flaky_server(drops: usize) -> (ServerConfig, Transcript) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let transcript = Transcript::default();
+
+    let shared = transcript.clone();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let index = {
+                let mut transcript = shared.lock().unwrap();
+                transcript.push(Vec::new());
+                transcript.len() - 1
+            };
+            tokio::spawn(serve(socket, index < drops, shared.clone(), index));
+        }
+    });
+
+    let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+    (config, transcript)
+}
+
+async fn serve(socket: TcpStream, drop_on_body: bool, transcript: Transcript, index: usize) {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer.write_all(b"200 mock ready\r\n").await.unwrap();
+    while let Ok(Some(line)) = lines.next_line().await {
+        transcript.lock().unwrap()[index].push(line.clone());
+        let reply: &[u8] = if line.starts_with("AUTHINFO USER") {
+            b"381 password required\r\n"
+        } else if line.starts_with("AUTHINFO PASS") || line.starts_with("AUTHINFO SASL") {
+            b"281 welcome\r\n"
+        } else if line.starts_with("GROUP") {
+            b"211 2 1 2 alt.test\r\n"
+        } else if line.starts_with("BODY") && drop_on_body {
+            return;
+        } else if line.starts_with("BODY") {
+            b"222 1 <a@b> body\r\nhello\r\n.\r\n"
+        } else {
+            b"500 unknown command\r\n"
+        };
+        if writer.write_all(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+    ReconnectPolicy {
+        max_attempts,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+    }
+}
+
+#[tokio::test]
+async fn test_reconnects_and_restores_session() {
+    let (config, transcript) = flaky_server(1).await;
+    let config = config.with_reconnect(fast_policy(3));
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    client.authenticate().await.unwrap();
+    client.select_group("alt.test").await.unwrap();
+
+    let body = client.fetch_body("1").await.unwrap();
+    assert_eq!(body.lines, vec!["hello"]);
+    assert!(client.is_authenticated());
+    assert_eq!(client.current_group(), Some("alt.test"));
+
+    let transcript = transcript.lock().unwrap();
+    assert_eq!(transcript.len(), 2);
+    assert_eq!(
+        transcript[1],
+        vec![
+            "AUTHINFO USER user",
+            "AUTHINFO PASS pass",
+            "GROUP alt.test",
+            "BODY 1"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_reconnect_replays_sasl() {
+    let (config, transcript) = flaky_server(1).await;
+    let config = config.with_reconnect(fast_policy(3));
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    client
+        .authenticate_sasl(SaslPlain::new("user", "pass"))
+        .await
+        .unwrap();
+
+    let body = client.fetch_body("1").await.unwrap();
+    assert_eq!(body.lines, vec!["hello"]);
+
+    let transcript = transcript.lock().unwrap();
+    assert!(transcript[0][0].starts_with("AUTHINFO SASL PLAIN "));
+    assert_eq!(transcript[1], vec![transcript[0][0].as_str(), "BODY 1"]);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let (config, transcript) = flaky_server(usize::MAX).await;
+    let config = config.with_reconnect(fast_policy(2));
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.fetch_body("<a@b>").await;
+    assert!(matches!(result, Err(NntpError::ConnectionClosed)));
+    assert_eq!(transcript.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_disabled_by_default() {
+    let (config, transcript) = flaky_server(1).await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.fetch_body("<a@b>").await;
+    assert!(matches!(result, Err(NntpError::ConnectionClosed)));
+    assert_eq!(transcript.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_manual_reconnect() {
+    let (config, transcript) = flaky_server(1).await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(client.fetch_body("<a@b>").await.is_err());
+
+    client.reconnect().await.unwrap();
+    let body = client.fetch_body("<a@b>").await.unwrap();
+    assert_eq!(body.lines, vec!["hello"]);
+    assert_eq!(transcript.lock().unwrap().len(), 2);
+}
+
+#[derive(Debug, Default)]
+struct Commands(Mutex<Vec<String>>);
+
+impl ConnectionObserver for Commands {
+    fn on_command(&self, command: &str) {
+        self.0.lock().unwrap().push(command.trim().to_string());
+    }
+}
+
+#[tokio::test]
+async fn test_reconnect_keeps_connection_overrides() {
+    let (config, _transcript) = flaky_server(1).await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    let observer = Arc::new(Commands::default());
+    client.set_observer(Some(observer.clone()));
+    let quirks = ServerQuirks {
+        xfeature_nonstandard_ack: true,
+        ..ServerQuirks::default()
+    };
+    client.set_quirks(quirks.clone());
+    client.set_gzip_detection(GzipDetection::AlwaysCompressed(vec!["XOVER".into()]));
+    client.set_parse_mode(ParseMode::Strict);
+    assert!(client.fetch_body("<a@b>").await.is_err());
+
+    client.reconnect().await.unwrap();
+    assert_eq!(client.quirks(), &quirks);
+    assert_eq!(
+        client.gzip_detection(),
+        &GzipDetection::AlwaysCompressed(vec!["XOVER".into()])
+    );
+    assert_eq!(client.parse_mode(), ParseMode::Strict);
+
+    let body = client.fetch_body("<a@b>").await.unwrap();
+    assert_eq!(body.lines, vec!["hello"]);
+    assert_eq!(
+        *observer.0.lock().unwrap(),
+        vec!["BODY <a@b>", "BODY <a@b>"]
+    );
+}
@@ -13,6 +13,7 @@ mod rfc3977 {
     mod ihave;
     mod list_active;
     mod list_active_times;
+    mod list_distrib_pats;
     mod list_headers;
     mod list_newsgroups;
     mod list_overview;
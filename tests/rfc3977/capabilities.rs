@@ -352,3 +352,88 @@ fn test_capability_no_arguments() {
     let args = caps.get_args("READER").unwrap();
     assert_eq!(args.len(), 0);
 }
+
+/// Start a server answering CAPABILITIES (counting them), MODE READER,
+/// AUTHINFO and XFEATURE, hanging up on COMPRESS
+async fn counting_server(
+    count: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut line = String::new();
+        while reader.read_line(&mut line).await.unwrap_or(0) > 0 {
+            let reply: &[u8] = match line.split_whitespace().next().unwrap_or("") {
+                "CAPABILITIES" => {
+                    count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    b"101 Capability list:\r\nVERSION 2\r\nREADER\r\nAUTHINFO USER\r\n.\r\n"
+                }
+                "MODE" => b"200 Reader mode, posting allowed\r\n",
+                "AUTHINFO" if line.contains("USER") => b"381 Password required\r\n",
+                "AUTHINFO" => b"281 Authentication accepted\r\n",
+                "XFEATURE" => b"290 feature enabled\r\n",
+                "COMPRESS" => panic!("COMPRESS sent although not advertised"),
+                _ => b"500 What?\r\n",
+            };
+            writer.write_all(reply).await.unwrap();
+            line.clear();
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_capability_set_is_cached() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let config = counting_server(count.clone()).await;
+    let mut client = nntp_rs::NntpClient::connect(Arc::new(config))
+        .await
+        .unwrap();
+    assert!(client.cached_capabilities().is_none());
+
+    assert!(client.capability_set().await.unwrap().has("READER"));
+    assert!(client.capability_set().await.unwrap().has("AUTHINFO"));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert!(client.cached_capabilities().is_some());
+}
+
+#[tokio::test]
+async fn test_capability_set_invalidated_by_state_changes() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let config = counting_server(count.clone()).await;
+    let mut client = nntp_rs::NntpClient::connect(Arc::new(config))
+        .await
+        .unwrap();
+
+    client.capability_set().await.unwrap();
+    client.mode_reader().await.unwrap();
+    assert!(client.cached_capabilities().is_none());
+    client.capability_set().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    client.authenticate().await.unwrap();
+    assert!(client.cached_capabilities().is_none());
+    client.capability_set().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    // COMPRESS DEFLATE is not advertised, so only XFEATURE is tried
+    assert!(client.try_enable_compression().await.unwrap());
+}
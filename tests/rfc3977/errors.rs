@@ -358,3 +358,310 @@ fn test_boundary_between_continuation_and_error() {
     assert!(!response_400.is_continuation());
     assert!(response_400.is_error());
 }
+
+// ========================================
+// Configurable timeouts against a stalled server
+// ========================================
+
+/// Greet, answer each command with `reply` (if any), then stall without closing
+async fn stalling_server(reply: &'static [u8]) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        while let Ok(Some(_)) = lines.next_line().await {
+            writer.write_all(reply).await.unwrap();
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+fn short_timeouts() -> nntp_rs::TimeoutConfig {
+    nntp_rs::TimeoutConfig {
+        read: std::time::Duration::from_millis(200),
+        multiline_read: std::time::Duration::from_millis(300),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_single_line_read_timeout_is_configurable() {
+    let config = stalling_server(b"").await.with_timeouts(short_timeouts());
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let result = client.select_group("alt.test").await;
+    assert!(matches!(result, Err(NntpError::Timeout)));
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_multiline_read_timeout_is_configurable() {
+    // Status line arrives, but the body never terminates
+    let config = stalling_server(b"222 1 <a@test> body follows\r\npartial line\r\n")
+        .await
+        .with_timeouts(short_timeouts());
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let result = client.fetch_body_binary("<a@test>").await;
+    assert!(matches!(result, Err(NntpError::Timeout)));
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_body_stream_times_only_the_wait_for_data() {
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    // The rest of the body comes 300ms after the first line
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        lines.next_line().await.unwrap();
+        writer
+            .write_all(b"222 1 <a@test> body\r\nfirst\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        writer.write_all(b"second\r\n.\r\n").await.unwrap();
+        lines.next_line().await.unwrap();
+    });
+    let config = nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+        .with_timeouts(short_timeouts());
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let mut stream = client.fetch_body_stream("<a@test>").await.unwrap();
+    let mut body = vec![0u8; 7];
+    stream.read_exact(&mut body).await.unwrap();
+    // Longer than the read timeout, but spent by the consumer
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    stream.read_to_end(&mut body).await.unwrap();
+    assert_eq!(body, b"first\r\nsecond\r\n");
+}
+
+/// Greet, answer the first `answered` commands promptly, then stall
+async fn server_stalling_after(answered: usize) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        let mut count = 0;
+        while let Ok(Some(_)) = lines.next_line().await {
+            count += 1;
+            if count <= answered {
+                writer.write_all(b"211 2 1 2 alt.test\r\n").await.unwrap();
+            }
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_adaptive_timeout_detects_hung_connection_quickly() {
+    use std::time::Duration;
+
+    let timeouts = nntp_rs::TimeoutConfig {
+        read: Duration::from_secs(60),
+        adaptive: Some(nntp_rs::AdaptiveTimeouts {
+            floor: Duration::from_millis(200),
+            min_samples: 3,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let config = server_stalling_after(3).await.with_timeouts(timeouts);
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+    // The greeting is not a response to a command
+    assert!(client.response_latency().is_empty());
+    assert_eq!(client.response_timeout(), Duration::from_secs(60));
+
+    for _ in 0..3 {
+        client.select_group("alt.test").await.unwrap();
+    }
+    assert_eq!(client.response_latency().len(), 3);
+    assert!(client.response_timeout() < Duration::from_secs(5));
+
+    let started = std::time::Instant::now();
+    let result = client.select_group("alt.test").await;
+    assert!(matches!(result, Err(NntpError::Timeout)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+/// Answer GROUP and POST promptly, but take `delay` to accept the article
+async fn slow_storing_server(delay: std::time::Duration) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply: &[u8] = match line.split_once(' ').map_or(line.as_str(), |(c, _)| c) {
+                "GROUP" => b"211 2 1 2 alt.test\r\n",
+                "AUTHINFO" if line.starts_with("AUTHINFO USER") => b"381 password required\r\n",
+                "AUTHINFO" => b"281 welcome\r\n",
+                "POST" => b"340 send article\r\n",
+                "." => {
+                    tokio::time::sleep(delay).await;
+                    b"240 article posted\r\n"
+                }
+                _ => continue,
+            };
+            writer.write_all(reply).await.unwrap();
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_adaptive_timeout_skips_article_upload() {
+    use std::time::Duration;
+
+    let timeouts = nntp_rs::TimeoutConfig {
+        read: Duration::from_secs(60),
+        adaptive: Some(nntp_rs::AdaptiveTimeouts {
+            floor: Duration::from_millis(200),
+            min_samples: 3,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let config = slow_storing_server(Duration::from_millis(800))
+        .await
+        .with_timeouts(timeouts);
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+    client.authenticate().await.unwrap();
+    for _ in 0..3 {
+        client.select_group("alt.test").await.unwrap();
+    }
+    assert!(client.response_timeout() < Duration::from_millis(800));
+
+    // A slow 240 after the upload is not a timeout, which would invite a
+    // duplicate post on retry
+    let samples = client.response_latency().len();
+    client
+        .post_raw(b"Message-ID: <slow@test>\r\n\r\nbody\r\n")
+        .await
+        .unwrap();
+    // Only the 340 was timed
+    assert_eq!(client.response_latency().len(), samples + 1);
+}
+
+// ========================================
+// Fetching by article number needs a selected group
+// ========================================
+
+/// Greet and answer GROUP, ARTICLE and BODY, recording every command
+async fn numbered_article_server(
+    commands: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply: &[u8] = match line.split_once(' ').map_or(line.as_str(), |(c, _)| c) {
+                "GROUP" => b"211 2 1 2 alt.test\r\n",
+                "ARTICLE" => b"220 2 <b@test>\r\nSubject: two\r\n\r\nbody\r\n.\r\n",
+                "BODY" if line == "BODY 9" => b"412 No newsgroup selected\r\n",
+                "BODY" => b"222 2 <b@test>\r\nbody\r\n.\r\n",
+                _ => b"500 Unknown command\r\n",
+            };
+            commands.lock().unwrap().push(line);
+            writer.write_all(reply).await.unwrap();
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_fetch_by_number_requires_group() {
+    let commands = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let config = numbered_article_server(std::sync::Arc::clone(&commands)).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    // Refused before anything is sent
+    assert!(matches!(
+        client.fetch_article_number(2).await,
+        Err(NntpError::NoGroupSelected)
+    ));
+    assert!(matches!(
+        client.fetch_body_number(2).await,
+        Err(NntpError::NoGroupSelected)
+    ));
+    assert!(commands.lock().unwrap().is_empty());
+
+    client.select_group("alt.test").await.unwrap();
+    let article = client.fetch_article_number(2).await.unwrap();
+    assert_eq!(article.code, 220);
+    let body = client.fetch_body_number(2).await.unwrap();
+    assert_eq!(body.lines, vec!["body"]);
+
+    // The server's 412 maps to the same error
+    assert!(matches!(
+        client.fetch_body_number(9).await,
+        Err(NntpError::NoGroupSelected)
+    ));
+    assert_eq!(
+        *commands.lock().unwrap(),
+        vec!["GROUP alt.test", "ARTICLE 2", "BODY 2", "BODY 9"]
+    );
+}
@@ -510,3 +510,47 @@ fn test_hdr_case_sensitive_header_names() {
     assert_eq!(commands::hdr("Subject", "1"), "HDR Subject 1\r\n");
     assert_eq!(commands::hdr("SUBJECT", "1"), "HDR SUBJECT 1\r\n");
 }
+
+// Local header extraction (HEAD fallback for fetch_headers_fields)
+
+#[test]
+fn test_extract_header_fields_from_head_response() {
+    let lines: Vec<String> = [
+        "Path: news.example.com!not-for-mail",
+        "From: Poster <poster@example.com>",
+        "Newsgroups: alt.test,alt.test.2,alt.test.3",
+        "Subject: Crossposted",
+        " and folded",
+        "Date: Mon, 01 Jan 2024 00:00:00 +0000",
+        "Message-ID: <big@example.com>",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let fields = commands::extract_header_fields(&lines, &["Subject", "FROM", "date"]);
+    assert_eq!(fields.len(), 3);
+    assert_eq!(
+        fields[0],
+        ("Subject".to_string(), "Crossposted and folded".to_string())
+    );
+    assert_eq!(fields[1].1, "Poster <poster@example.com>");
+    assert_eq!(fields[2].1, "Mon, 01 Jan 2024 00:00:00 +0000");
+}
+
+#[test]
+fn test_extract_header_fields_missing_and_duplicate() {
+    let lines: Vec<String> = [
+        "Subject: First",
+        "Subject: Second",
+        "",
+        "Body: not a header",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let fields = commands::extract_header_fields(&lines, &["Subject", "Body", "X-Missing"]);
+    assert_eq!(fields, vec![("Subject".to_string(), "First".to_string())]);
+    assert!(commands::extract_header_fields(&lines, &[]).is_empty());
+}
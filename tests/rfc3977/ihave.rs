@@ -315,3 +315,97 @@ fn test_ihave_vs_post_distinction() {
     assert_eq!(codes::ARTICLE_POSTED, 240);
     assert_ne!(codes::ARTICLE_TRANSFERRED, codes::ARTICLE_POSTED);
 }
+
+/// IHAVE against a peer that defers the first offer
+mod deferring_peer {
+    use nntp_rs::{ArticleBuilder, IhaveQueue, NntpClient, NntpError, OfferOutcome, ServerConfig};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Start a peer that answers the first IHAVE with 436 and takes the article afterwards
+    async fn deferring_peer() -> NntpClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = socket.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer
+                .write_all(b"200 mock server ready\r\n")
+                .await
+                .unwrap();
+            let mut offers = 0;
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(reply) = reply(&line, &mut offers) {
+                    writer.write_all(reply.as_bytes()).await.unwrap();
+                }
+            }
+        });
+
+        let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+        client.authenticate().await.unwrap();
+        client
+    }
+
+    /// Reply to a command line, if it is one; `offers` counts IHAVE commands
+    fn reply(line: &str, offers: &mut u32) -> Option<&'static str> {
+        if line.starts_with("AUTHINFO USER") {
+            Some("381 password required\r\n")
+        } else if line.starts_with("AUTHINFO PASS") {
+            Some("281 welcome\r\n")
+        } else if line.starts_with("IHAVE") {
+            *offers += 1;
+            Some(match *offers {
+                1 => "436 try again later\r\n",
+                _ => "335 send it\r\n",
+            })
+        } else if line == "." {
+            Some("235 article transferred\r\n")
+        } else {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deferred_offer_is_retried() {
+        let mut client = deferring_peer().await;
+        let article = ArticleBuilder::new()
+            .from("feeder@example.com")
+            .subject("Deferred")
+            .newsgroups(vec!["alt.test"])
+            .message_id("<deferred@example.com>")
+            .body("hello")
+            .build()
+            .unwrap();
+        let mut queue = IhaveQueue::new().with_backoff(Duration::ZERO, Duration::ZERO);
+
+        let outcome = queue.offer(&mut client, &article).await.unwrap();
+        let OfferOutcome::Deferred { retry_at } = outcome else {
+            panic!("expected a deferral, got {:?}", outcome);
+        };
+        assert_eq!(queue.due(retry_at), vec!["<deferred@example.com>"]);
+
+        let outcome = queue.offer(&mut client, &article).await.unwrap();
+        assert_eq!(outcome, OfferOutcome::Transferred);
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ihave_raw_validates_before_offering() {
+        let mut client = deferring_peer().await;
+
+        let unstuffed = b"Message-ID: <raw@example.com>\r\n\r\n.\r\nmore\r\n";
+        let result = client.ihave_raw("<raw@example.com>", unstuffed).await;
+        assert!(matches!(result, Err(NntpError::InvalidArgument(_))));
+
+        // Nothing was offered, so this is the offer the peer defers
+        let raw = b"Message-ID: <raw@example.com>\r\n\r\n..dotted\r\n.\r\n";
+        let result = client.ihave_raw("<raw@example.com>", raw).await;
+        assert!(matches!(result, Err(NntpError::TransferNotPossible(_))));
+        client.ihave_raw("<raw@example.com>", raw).await.unwrap();
+    }
+}
@@ -374,3 +374,92 @@ fn test_active_group_struct_fields() {
     assert_eq!(group.low, 50);
     assert_eq!(group.status, "y");
 }
+
+// ========================================
+// Streaming LIST ACTIVE against a mock server
+// ========================================
+
+/// Greet and answer LIST ACTIVE with `count` groups, written in small
+/// pieces; the last group is empty (high below low)
+async fn list_active_server(count: u64) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if !line.starts_with("LIST ACTIVE") {
+                writer.write_all(b"500 unknown command\r\n").await.unwrap();
+                continue;
+            }
+            let mut data = String::from("215 list of newsgroups follows\r\nmalformed line\r\n");
+            data.extend((1..count).map(|n| format!("alt.group.{n} {} 10 y\r\n", n + 10)));
+            data.push_str(&format!("alt.group.{count} 9 10 n\r\n.\r\n"));
+            for piece in data.as_bytes().chunks(4093) {
+                writer.write_all(piece).await.unwrap();
+            }
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_list_active_stream_yields_every_group() {
+    let config = list_active_server(50_000).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let mut stream = client.list_active_stream("*").await.unwrap();
+    let mut seen = 0;
+    while let Some(group) = stream.next().await {
+        let group = group.unwrap();
+        seen += 1;
+        assert_eq!(group.name, format!("alt.group.{seen}"));
+        assert_eq!(group.low, 10);
+    }
+    assert_eq!(seen, 50_000);
+    drop(stream);
+
+    // The whole response was consumed, and nothing was recorded
+    assert!(!client.is_broken());
+    assert!(client.group_watermarks().get("alt.group.1").is_none());
+    let again = client.list_active("*").await.unwrap();
+    assert_eq!(again.len(), 50_000);
+}
+
+#[tokio::test]
+async fn test_list_active_stream_records_watermarks() {
+    let config = list_active_server(3).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    assert!(client.list_active_stream("alt.[").await.is_err());
+
+    let mut stream = client
+        .list_active_stream("alt.*")
+        .await
+        .unwrap()
+        .record_watermarks();
+    while let Some(group) = stream.next().await {
+        group.unwrap();
+    }
+    drop(stream);
+
+    let watermarks = client.group_watermarks();
+    let info = watermarks.get("alt.group.2").unwrap();
+    assert_eq!((info.count, info.first, info.last), (3, 10, 12));
+    assert!(watermarks.is_estimated("alt.group.2"));
+    // Empty groups are not recorded
+    assert!(watermarks.get("alt.group.3").is_none());
+}
@@ -0,0 +1,92 @@
+//! RFC 3977 Section 7.6.5 - LIST DISTRIB.PATS Command Tests
+//!
+//! Reference: https://datatracker.ietf.org/doc/html/rfc3977#section-7.6.5
+//!
+//! Tests for the LIST DISTRIB.PATS command and response parsing.
+
+use nntp_rs::{NntpError, NntpResponse, codes, commands};
+
+fn response(lines: &[&str]) -> NntpResponse {
+    NntpResponse {
+        code: codes::LIST_INFORMATION_FOLLOWS,
+        message: "Default distributions in form \"weight:wildmat:value\"".to_string(),
+        lines: lines.iter().map(|line| line.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_list_distrib_pats_command_format() {
+    let cmd = commands::list_distrib_pats();
+    assert_eq!(cmd, "LIST DISTRIB.PATS\r\n");
+    assert_eq!(cmd.matches("\r\n").count(), 1);
+}
+
+#[test]
+fn test_parse_list_distrib_pats_rfc_example() {
+    // Example from RFC 3977 Section 7.6.5
+    let pats = commands::parse_list_distrib_pats_response(response(&[
+        "10:local.*:local",
+        "5:*:world",
+        "20:local.here.*:thissite",
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        pats,
+        vec![
+            commands::DistribPat {
+                weight: 10,
+                wildmat: "local.*".to_string(),
+                distribution: "local".to_string(),
+            },
+            commands::DistribPat {
+                weight: 5,
+                wildmat: "*".to_string(),
+                distribution: "world".to_string(),
+            },
+            commands::DistribPat {
+                weight: 20,
+                wildmat: "local.here.*".to_string(),
+                distribution: "thissite".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_list_distrib_pats_wildmat_with_list() {
+    // A wildmat may contain commas and negations, but never a colon
+    let pats = commands::parse_list_distrib_pats_response(response(&["3:comp.*,!comp.test:world"]))
+        .unwrap();
+    assert_eq!(pats[0].wildmat, "comp.*,!comp.test");
+    assert_eq!(pats[0].distribution, "world");
+}
+
+#[test]
+fn test_parse_list_distrib_pats_skips_malformed_lines() {
+    let pats = commands::parse_list_distrib_pats_response(response(&[
+        "local.*:local",
+        "ten:*:world",
+        "",
+        "1:*:world",
+    ]))
+    .unwrap();
+    assert_eq!(pats.len(), 1);
+    assert_eq!(pats[0].weight, 1);
+}
+
+#[test]
+fn test_parse_list_distrib_pats_empty() {
+    let pats = commands::parse_list_distrib_pats_response(response(&[])).unwrap();
+    assert!(pats.is_empty());
+}
+
+#[test]
+fn test_parse_list_distrib_pats_not_maintained() {
+    let result = commands::parse_list_distrib_pats_response(NntpResponse {
+        code: codes::FEATURE_NOT_SUPPORTED,
+        message: "Not maintained".to_string(),
+        lines: vec![],
+    });
+    assert!(matches!(result, Err(NntpError::Protocol { code: 503, .. })));
+}
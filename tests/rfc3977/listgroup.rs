@@ -387,3 +387,83 @@ fn test_listgroup_moderated_group() {
     assert!(response.is_success());
     assert_eq!(response.lines.len(), 10);
 }
+
+// ========================================
+// Streaming LISTGROUP against a mock server
+// ========================================
+
+/// Greet and answer LISTGROUP alt.test with `count` numbers, written in
+/// small pieces; other groups do not exist
+async fn listgroup_server(count: u64) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line != "LISTGROUP alt.test" {
+                writer.write_all(b"411 No such group\r\n").await.unwrap();
+                continue;
+            }
+            let mut data =
+                format!("211 {count} 1 {count} alt.test list follows\r\nnot-a-number\r\n");
+            data.extend((1..=count).map(|n| format!("{n}\r\n")));
+            data.push_str(".\r\n");
+            for piece in data.as_bytes().chunks(4093) {
+                writer.write_all(piece).await.unwrap();
+            }
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_listgroup_stream_yields_every_number() {
+    let config = listgroup_server(100_000).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let mut stream = client.listgroup_stream("alt.test", None).await.unwrap();
+    let mut expected = 1;
+    while let Some(number) = stream.next().await {
+        assert_eq!(number.unwrap(), expected);
+        expected += 1;
+    }
+    assert_eq!(expected, 100_001);
+    drop(stream);
+
+    // The whole response was consumed
+    assert!(!client.is_broken());
+    let again = client.listgroup("alt.test", None).await.unwrap();
+    assert_eq!(again.len(), 100_000);
+}
+
+#[tokio::test]
+async fn test_listgroup_stream_errors() {
+    let config = listgroup_server(1000).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        client.listgroup_stream("alt.none", None).await,
+        Err(nntp_rs::NntpError::NoSuchGroup(_))
+    ));
+    assert!(!client.is_broken());
+
+    // Abandoning the listing halfway leaves the connection out of sync
+    let mut stream = client.listgroup_stream("alt.test", None).await.unwrap();
+    assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+    drop(stream);
+    assert!(client.is_broken());
+}
@@ -421,3 +421,180 @@ fn test_mixed_line_endings() {
     // Line2\nLine3 is treated as a single line (with embedded \n)
     assert!(crlf_lines.iter().any(|l| l.contains("Line2\nLine3")));
 }
+
+// ========================================
+// Binary reader against a mock server
+// ========================================
+
+/// Serve one scripted multi-line response per command, then close
+async fn binary_mock_server(replies: Vec<Vec<u8>>) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        for reply in replies {
+            if lines.next_line().await.unwrap().is_none() {
+                return;
+            }
+            writer.write_all(&reply).await.unwrap();
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_binary_response_keeps_line_structure() {
+    let encoded =
+        nntp_rs::yenc::encode(b"...binary\x00\xff payload", "test.bin", 128, None).unwrap();
+    // Dot-stuff the encoded body the way a server would
+    let mut stuffed = Vec::new();
+    for line in encoded.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b".") {
+            stuffed.push(b'.');
+        }
+        stuffed.extend_from_slice(line);
+    }
+
+    let mut article = b"220 1 <bin@test> article\r\n\
+Path: news.example.com!not-for-mail\r\n\
+From: poster@example.com\r\n\
+Newsgroups: alt.binaries.test\r\n\
+Subject: test.bin (1/1)\r\n\
+Date: Mon, 1 Jan 2024 00:00:00 +0000\r\n\
+Message-ID: <bin@test>\r\n\r\n"
+        .to_vec();
+    article.extend_from_slice(&stuffed);
+    article.extend_from_slice(b".\r\n");
+
+    let mut body = b"222 1 <bin@test> body\r\n".to_vec();
+    body.extend_from_slice(&stuffed);
+    body.extend_from_slice(b".\r\n");
+
+    let config = binary_mock_server(vec![article, body]).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let (headers, article_body) = client
+        .fetch_article_binary("<bin@test>")
+        .await
+        .unwrap()
+        .split_article()
+        .unwrap();
+    assert_eq!(headers.subject, "test.bin (1/1)");
+    assert_eq!(&article_body[..], encoded.as_slice());
+
+    let response = client.fetch_body_binary("<bin@test>").await.unwrap();
+    assert_eq!(response.data, encoded);
+    let decoded = nntp_rs::yenc::decode(&response.data).unwrap();
+    assert_eq!(decoded.data, b"...binary\x00\xff payload");
+    assert_eq!(decoded.verify_crc32(), Some(true));
+}
+
+/// Dot-stuff `data` and frame it as a 222 BODY response
+fn stuffed_body_response(data: &[u8]) -> Vec<u8> {
+    let mut response = b"222 1 <big@test> body\r\n".to_vec();
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b".") {
+            response.push(b'.');
+        }
+        response.extend_from_slice(line);
+    }
+    response.extend_from_slice(b".\r\n");
+    response
+}
+
+#[tokio::test]
+async fn test_body_stream_matches_binary_read() {
+    use tokio::io::AsyncReadExt;
+
+    let payload: Vec<u8> = (0..200_000u32).map(|i| (i * 31 % 256) as u8).collect();
+    let encoded = nntp_rs::yenc::encode(&payload, "big.bin", 128, None).unwrap();
+    let config = binary_mock_server(vec![
+        stuffed_body_response(&encoded),
+        stuffed_body_response(b".\r\n..x\r\n.\r\ny\r\n"),
+        b"430 no such article\r\n".to_vec(),
+    ])
+    .await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let mut stream = client.fetch_body_stream("<big@test>").await.unwrap();
+    assert_eq!(stream.code(), 222);
+    let mut streamed = Vec::new();
+    stream.read_to_end(&mut streamed).await.unwrap();
+    assert!(stream.is_finished());
+    drop(stream);
+    assert_eq!(streamed, encoded);
+
+    // Tiny reads across dot-stuffed lines and a lone-dot line
+    let mut stream = client.fetch_body_stream("<dots@test>").await.unwrap();
+    let mut streamed = Vec::new();
+    let mut chunk = [0u8; 3];
+    loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        streamed.extend_from_slice(&chunk[..n]);
+    }
+    drop(stream);
+    assert_eq!(streamed, b".\r\n..x\r\n.\r\ny\r\n");
+
+    // The connection stays in sync after streaming
+    let result = client.fetch_body_stream("<gone@test>").await.map(drop);
+    assert!(matches!(result, Err(nntp_rs::NntpError::NoSuchArticle(_))));
+    assert!(!client.is_broken());
+}
+
+#[tokio::test]
+async fn test_body_stream_dropped_early_breaks_connection() {
+    use tokio::io::AsyncReadExt;
+
+    let config = binary_mock_server(vec![stuffed_body_response(&[b'a'; 100_000])]).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let mut stream = client.fetch_body_stream("<big@test>").await.unwrap();
+    let mut chunk = [0u8; 10];
+    stream.read_exact(&mut chunk).await.unwrap();
+    drop(stream);
+    assert!(client.is_broken());
+}
+
+#[tokio::test]
+async fn test_body_prefix_drains_rest_of_body() {
+    let payload: Vec<u8> = (0..50_000u32).map(|i| (i * 7 % 256) as u8).collect();
+    let encoded = nntp_rs::yenc::encode(&payload, "movie.mkv", 128, None).unwrap();
+    let config = binary_mock_server(vec![
+        stuffed_body_response(&encoded),
+        stuffed_body_response(b"plain text\r\n"),
+    ])
+    .await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let prefix = client.fetch_body_prefix("<big@test>", 64).await.unwrap();
+    assert_eq!(prefix.yenc.unwrap().name, "movie.mkv");
+    assert_eq!(prefix.data, &payload[..64]);
+    assert!(!client.is_broken());
+
+    // The next response is read in sync
+    let prefix = client.fetch_body_prefix("<text@test>", 64).await.unwrap();
+    assert!(!prefix.is_yenc());
+    assert_eq!(prefix.data, b"plain text\r\n");
+    assert!(!client.is_broken());
+}
@@ -350,3 +350,95 @@ fn test_over_whitespace_in_fields() {
     assert!(response.lines[0].contains("Hello World Test"));
     assert!(response.lines[0].contains("John Doe <john@example.com>"));
 }
+
+// ========================================
+// OVER by message-id against a mock server
+// ========================================
+
+/// Serve one scripted response per command
+async fn scripted_server(replies: Vec<&'static str>) -> nntp_rs::ServerConfig {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        for reply in replies {
+            if lines.next_line().await.unwrap().is_none() {
+                return;
+            }
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+
+    nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_over_by_msgid() {
+    let config = scripted_server(vec![
+        "224 Overview information follows\r\n\
+0\tPart 1\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<part1@test>\t\t768000\t6000\r\n\
+.\r\n",
+        "430 No such article\r\n",
+        "224 Overview information follows\r\n\
+5\tRange\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<five@test>\t\t100\t2\r\n\
+.\r\n",
+    ])
+    .await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let entry = client.over_by_msgid("<part1@test>").await.unwrap().unwrap();
+    assert_eq!(entry.subject, "Part 1");
+    assert_eq!(entry.article_number, 0);
+    assert_eq!(entry.source_id.as_deref(), Some("<part1@test>"));
+
+    assert!(client.over_by_msgid("<gone@test>").await.unwrap().is_none());
+    assert!(client.over_by_msgid("no-brackets").await.is_err());
+
+    // Range requests carry no source message-id
+    let entries = client.over("5").await.unwrap();
+    assert_eq!(entries[0].source_id, None);
+}
+
+#[tokio::test]
+async fn test_strict_parse_mode_rejects_bad_overview_columns() {
+    const BAD_BYTES: &str = "224 Overview information follows\r\n\
+1\tGood\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<one@test>\t\t100\t2\r\n\
+2\tBad\tposter@example.com\tMon, 01 Jan 2024 00:00:00 +0000\t<two@test>\t\tlots\t2\r\n\
+.\r\n";
+    let config = scripted_server(vec![BAD_BYTES, BAD_BYTES, "211garbled\r\n"]).await;
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    // Lenient: the bad column defaults to 0
+    let entries = client.fetch_xover("1-2").await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].bytes, 0);
+
+    client.set_parse_mode(nntp_rs::ParseMode::Strict);
+    let result = client.fetch_xover("1-2").await;
+    assert!(matches!(
+        result,
+        Err(nntp_rs::NntpError::InvalidResponse(_))
+    ));
+    assert!(!client.is_broken());
+
+    // A status line without the space after the code desyncs the session
+    let result = client.select_group("alt.test").await;
+    assert!(matches!(
+        result,
+        Err(nntp_rs::NntpError::InvalidResponse(_))
+    ));
+    assert!(client.is_broken());
+}
@@ -219,3 +219,93 @@ fn test_post_binary_content_forbidden() {
     // Binary content must be encoded (yEnc, base64, etc.)
     // before posting
 }
+
+#[test]
+fn test_post_raw_article_validation() {
+    // Pre-serialized article with terminator is accepted; terminator is stripped
+    let raw = b"From: a@example.com\r\nSubject: Raw\r\n\r\n..leading dot\r\n.\r\n";
+    let article = commands::validate_raw_article(raw).unwrap();
+    assert!(article.ends_with(b"..leading dot\r\n"));
+
+    // Bare LF line endings are rejected
+    assert!(commands::validate_raw_article(b"Subject: Raw\n\nBody\n").is_err());
+
+    // Unstuffed "." line would end the article early
+    assert!(commands::validate_raw_article(b"Subject: Raw\r\n\r\n.\r\nBody\r\n").is_err());
+}
+
+/// POST of an article with a binary body against a mock server
+mod binary_body {
+    use nntp_rs::{ArticleBuilder, NntpClient, ServerConfig};
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    /// Start a server that takes a login and one POST, and hands over the article bytes it received
+    async fn posting_server() -> (NntpClient, oneshot::Receiver<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (received_tx, received_rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = socket.into_split();
+            let mut reader = BufReader::new(reader);
+            writer
+                .write_all(b"200 mock server ready\r\n")
+                .await
+                .unwrap();
+
+            for reply in [
+                &b"381 password required\r\n"[..],
+                b"281 welcome\r\n",
+                b"340 send article\r\n",
+            ] {
+                let mut command = Vec::new();
+                reader.read_until(b'\n', &mut command).await.unwrap();
+                writer.write_all(reply).await.unwrap();
+            }
+
+            let mut article = Vec::new();
+            while !article.ends_with(b"\r\n.\r\n") {
+                if reader.read_until(b'\n', &mut article).await.unwrap() == 0 {
+                    break;
+                }
+            }
+            writer.write_all(b"240 article posted\r\n").await.unwrap();
+            received_tx.send(article).unwrap();
+        });
+
+        let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+        client.authenticate().await.unwrap();
+        (client, received_rx)
+    }
+
+    #[tokio::test]
+    async fn test_post_binary_body_unmangled() {
+        let (mut client, received) = posting_server().await;
+        // Latin-1 text and raw high bytes that are not valid UTF-8
+        let body = b"caf\xe9\n.\xff\xfe\x80\n=yend\n".to_vec();
+        let article = ArticleBuilder::new()
+            .from("poster@example.com")
+            .subject("8-bit body")
+            .newsgroups(vec!["alt.test"])
+            .body_bytes(body.clone())
+            .build()
+            .unwrap();
+        assert!(article.has_binary_body());
+        assert_eq!(article.body_bytes(), &body[..]);
+        assert!(article.serialize_for_posting().is_err());
+
+        client.post(&article).await.unwrap();
+
+        let received = received.await.unwrap();
+        assert!(received.ends_with(b"\r\n\r\ncaf\xe9\r\n..\xff\xfe\x80\r\n=yend\r\n.\r\n"));
+        assert_eq!(
+            &received[..received.len() - 3],
+            &article.serialize_for_posting_bytes().unwrap()[..]
+        );
+    }
+}
@@ -276,6 +276,80 @@ fn test_response_111_with_optional_text() {
     assert!(msg.starts_with("20240115123456"));
 }
 
+#[test]
+fn test_parse_date_response() {
+    use nntp_rs::commands::parse_date_response;
+
+    let date = parse_date_response("20240115123456").unwrap();
+    assert_eq!(date.to_rfc3339(), "2024-01-15T12:34:56+00:00");
+    let date = parse_date_response("20240229000000 UTC").unwrap();
+    assert_eq!(date.to_rfc3339(), "2024-02-29T00:00:00+00:00");
+
+    for bad in [
+        "",
+        "2024011512345",
+        "202401151234567",
+        "20241315123456",
+        "2024011512345x",
+    ] {
+        assert!(parse_date_response(bad).is_err(), "{}", bad);
+    }
+}
+
+#[tokio::test]
+async fn test_date_and_clock_skew() {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        // First answer with a fixed date, then with a clock an hour ahead
+        answer_date(&mut lines, &mut writer, "20240115123456".to_string()).await;
+        let ahead = chrono::Utc::now() + chrono::TimeDelta::hours(1);
+        answer_date(
+            &mut lines,
+            &mut writer,
+            ahead.format("%Y%m%d%H%M%S").to_string(),
+        )
+        .await;
+        answer_date(&mut lines, &mut writer, "yesterday".to_string()).await;
+    });
+
+    async fn answer_date(
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        writer: &mut tokio::net::tcp::OwnedWriteHalf,
+        timestamp: String,
+    ) {
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "DATE");
+        let reply = format!("111 {}\r\n", timestamp);
+        writer.write_all(reply.as_bytes()).await.unwrap();
+    }
+
+    let config = nntp_rs::ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+    let mut client = nntp_rs::NntpClient::connect(std::sync::Arc::new(config))
+        .await
+        .unwrap();
+
+    let date = client.date().await.unwrap();
+    assert_eq!(date.to_rfc3339(), "2024-01-15T12:34:56+00:00");
+
+    let skew = client.clock_skew().await.unwrap();
+    assert!((3598..=3601).contains(&skew.num_seconds()), "{}", skew);
+
+    let result = client.date().await;
+    assert!(matches!(
+        result,
+        Err(nntp_rs::NntpError::InvalidResponse(_))
+    ));
+}
+
 #[test]
 fn test_response_1xx_is_informative() {
     // RFC 3977 §3.1: 1xx codes are informative
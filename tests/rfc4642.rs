@@ -0,0 +1,10 @@
+//! RFC 4642 - Using Transport Layer Security (TLS) with NNTP (STARTTLS)
+//!
+//! These tests drive the STARTTLS exchange and certificate verification
+//! against scripted local servers.
+//! https://datatracker.ietf.org/doc/html/rfc4642
+
+mod rfc4642 {
+    mod starttls;
+    mod verification;
+}
@@ -0,0 +1,164 @@
+//! RFC 4642 STARTTLS upgrade tests
+//!
+//! A plain-TCP mock server answers STARTTLS with a scripted reply. The TLS
+//! handshake itself is not exercised here (it needs a trusted certificate);
+//! these tests cover the command exchange and failure handling.
+
+use nntp_rs::{NntpClient, NntpError, ServerConfig, TlsPolicy};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Start a server that greets, then answers STARTTLS with `reply` and closes
+async fn mock_server(reply: &'static str) -> ServerConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "STARTTLS\r\n");
+        writer.write_all(reply.as_bytes()).await.unwrap();
+
+        // Keep the connection open until the client hangs up
+        line.clear();
+        let _ = reader.read_line(&mut line).await;
+    });
+
+    ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_plain_connection_is_not_tls() {
+    let config = mock_server("580 unused\r\n").await;
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(!client.is_tls());
+}
+
+#[tokio::test]
+async fn test_starttls_not_possible() {
+    let config = mock_server("580 Can not initiate TLS negotiation\r\n").await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.starttls().await;
+    assert!(matches!(result, Err(NntpError::Tls(_))));
+    assert!(!client.is_tls());
+    assert!(!client.is_broken());
+}
+
+#[tokio::test]
+async fn test_starttls_unavailable() {
+    let config = mock_server("502 Command unavailable\r\n").await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.starttls().await;
+    assert!(matches!(result, Err(NntpError::Protocol { code: 502, .. })));
+    assert!(!client.is_broken());
+}
+
+#[tokio::test]
+async fn test_starttls_rejects_data_before_handshake() {
+    let config = mock_server("382 Continue with TLS negotiation\r\n200 injected\r\n").await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.starttls().await;
+    assert!(matches!(result, Err(NntpError::Tls(_))));
+    assert!(client.is_broken());
+    assert!(!client.is_tls());
+}
+
+/// Start a server that greets, then answers each command with the next reply
+async fn scripted_server(replies: &'static [&'static str], policy: TlsPolicy) -> ServerConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+
+        let mut line = String::new();
+        for reply in replies {
+            line.clear();
+            if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                return;
+            }
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+        line.clear();
+        let _ = reader.read_line(&mut line).await;
+    });
+
+    ServerConfig::new("127.0.0.1", port, false, "user", "pass").with_tls_policy(policy)
+}
+
+const CAPS_WITHOUT_STARTTLS: &str = "101 Capability list:\r\nVERSION 2\r\nREADER\r\n.\r\n";
+const CAPS_WITH_STARTTLS: &str = "101 Capability list:\r\nVERSION 2\r\nSTARTTLS\r\n.\r\n";
+
+#[tokio::test]
+async fn test_required_policy_without_starttls_fails() {
+    let config = scripted_server(&[CAPS_WITHOUT_STARTTLS], TlsPolicy::Required).await;
+    let result = NntpClient::connect(Arc::new(config)).await;
+    assert!(matches!(result, Err(NntpError::EncryptionRequired(_))));
+}
+
+#[tokio::test]
+async fn test_required_policy_refused_starttls_fails() {
+    let config = scripted_server(
+        &[
+            CAPS_WITH_STARTTLS,
+            "580 Can not initiate TLS negotiation\r\n",
+        ],
+        TlsPolicy::Required,
+    )
+    .await;
+    let result = NntpClient::connect(Arc::new(config)).await;
+    assert!(matches!(result, Err(NntpError::EncryptionRequired(_))));
+}
+
+#[tokio::test]
+async fn test_opportunistic_policy_stays_plain() {
+    let config = scripted_server(&[CAPS_WITHOUT_STARTTLS], TlsPolicy::Opportunistic).await;
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(!client.is_tls());
+
+    let config = scripted_server(
+        &[
+            CAPS_WITH_STARTTLS,
+            "580 Can not initiate TLS negotiation\r\n",
+        ],
+        TlsPolicy::Opportunistic,
+    )
+    .await;
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(!client.is_tls());
+    assert!(!client.is_broken());
+}
+
+#[tokio::test]
+async fn test_opportunistic_policy_without_capabilities_command() {
+    let config = scripted_server(&["500 What?\r\n"], TlsPolicy::Opportunistic).await;
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(!client.is_tls());
+}
+
+#[tokio::test]
+async fn test_authinfo_encryption_required() {
+    let config = scripted_server(&["483 Encryption required\r\n"], TlsPolicy::Never).await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    let result = client.authenticate().await;
+    assert!(matches!(result, Err(NntpError::EncryptionRequired(_))));
+}
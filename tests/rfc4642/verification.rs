@@ -0,0 +1,181 @@
+//! Certificate verification with custom roots and pinning
+//!
+//! An implicit-TLS mock server presents certificates generated on the fly:
+//! a self-signed one (for pinning) or one issued by a private CA (for extra
+//! PEM roots). Neither is trusted by the default webpki roots.
+
+use nntp_rs::{NntpClient, NntpError, ServerConfig};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+/// Server certificate chain and key, plus the PEM of its trust anchor
+struct TestCert {
+    chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    root_pem: String,
+}
+
+impl TestCert {
+    fn self_signed() -> Self {
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec!["127.0.0.1".to_string()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+        Self {
+            root_pem: cert.pem(),
+            chain: vec![cert.der().clone()],
+            key: PrivatePkcs8KeyDer::from(key.serialize_der()).into(),
+        }
+    }
+
+    fn issued_by_private_ca() -> Self {
+        Self::issued_by_private_ca_for("127.0.0.1")
+    }
+
+    fn issued_by_private_ca_for(name: &str) -> Self {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec![name.to_string()])
+            .unwrap()
+            .signed_by(&key, &ca, &ca_key)
+            .unwrap();
+        Self {
+            root_pem: ca.pem(),
+            chain: vec![cert.der().clone()],
+            key: PrivatePkcs8KeyDer::from(key.serialize_der()).into(),
+        }
+    }
+
+    fn fingerprint(&self) -> String {
+        Sha256::digest(&self.chain[0])
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+/// Start an implicit-TLS server that greets and waits for the client to hang up
+async fn tls_server(cert: &TestCert) -> ServerConfig {
+    tls_server_at(cert, "127.0.0.1").await
+}
+
+/// Start the server on the loopback address `ip`, which is also the config host
+async fn tls_server_at(cert: &TestCert, ip: &str) -> ServerConfig {
+    let tls_config = tokio_rustls::rustls::ServerConfig::builder_with_provider(Arc::new(
+        ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(cert.chain.clone(), cert.key.clone_key())
+    .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+    let listener = TcpListener::bind((ip, 0)).await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        // A rejected certificate aborts the handshake on the client side
+        let Ok(mut stream) = acceptor.accept(socket).await else {
+            return;
+        };
+        stream
+            .write_all(b"200 mock server ready\r\n")
+            .await
+            .unwrap();
+        stream.flush().await.unwrap();
+
+        let mut line = String::new();
+        let _ = BufReader::new(stream).read_line(&mut line).await;
+    });
+
+    ServerConfig::new(ip, port, true, "user", "pass")
+}
+
+#[tokio::test]
+async fn test_untrusted_certificate_rejected_by_default() {
+    let cert = TestCert::self_signed();
+    let config = tls_server(&cert).await;
+
+    let result = NntpClient::connect(Arc::new(config)).await;
+    assert!(matches!(result, Err(NntpError::Tls(_))));
+}
+
+#[tokio::test]
+async fn test_pinned_self_signed_certificate() {
+    let cert = TestCert::self_signed();
+    let config = tls_server(&cert)
+        .await
+        .with_pinned_certificate(cert.fingerprint());
+
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(client.is_tls());
+}
+
+#[tokio::test]
+async fn test_pin_mismatch_rejected() {
+    let cert = TestCert::self_signed();
+    let other = TestCert::self_signed();
+    let config = tls_server(&cert)
+        .await
+        .with_pinned_certificate(other.fingerprint());
+
+    let result = NntpClient::connect(Arc::new(config)).await;
+    assert!(matches!(result, Err(NntpError::Tls(_))));
+}
+
+#[tokio::test]
+async fn test_private_ca_root() {
+    let cert = TestCert::issued_by_private_ca();
+    let config = tls_server(&cert)
+        .await
+        .with_root_certs_pem(cert.root_pem.clone());
+
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(client.is_tls());
+}
+
+#[tokio::test]
+async fn test_wrong_private_ca_rejected() {
+    let cert = TestCert::issued_by_private_ca();
+    let other = TestCert::issued_by_private_ca();
+    let config = tls_server(&cert).await.with_root_certs_pem(other.root_pem);
+
+    let result = NntpClient::connect(Arc::new(config)).await;
+    assert!(matches!(result, Err(NntpError::Tls(_))));
+}
+
+#[tokio::test]
+async fn test_ipv6_literal_verified_against_ip_address() {
+    let cert = TestCert::issued_by_private_ca_for("::1");
+    let mut config = tls_server_at(&cert, "::1")
+        .await
+        .with_root_certs_pem(cert.root_pem.clone());
+    config.host = "[::1]".to_string();
+
+    let client = NntpClient::connect(Arc::new(config)).await.unwrap();
+    assert!(client.is_tls());
+}
+
+#[tokio::test]
+async fn test_ipv6_literal_not_in_certificate_rejected() {
+    let cert = TestCert::issued_by_private_ca_for("127.0.0.1");
+    let config = tls_server_at(&cert, "::1")
+        .await
+        .with_root_certs_pem(cert.root_pem.clone());
+
+    let result = NntpClient::connect(Arc::new(config)).await;
+    assert!(matches!(result, Err(NntpError::Tls(_))));
+}
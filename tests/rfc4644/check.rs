@@ -9,7 +9,7 @@
 //!
 //! Reference: https://datatracker.ietf.org/doc/html/rfc4644#section-2.4
 
-use nntp_rs::{NntpResponse, codes, commands};
+use nntp_rs::{CheckStatus, NntpError, NntpResponse, codes, commands};
 #[test]
 fn test_check_format() {
     let cmd = commands::check("<article123@example.com>");
@@ -360,3 +360,49 @@ fn test_check_response_without_message_id() {
     assert_eq!(response.code, 238);
     assert!(response.is_success());
 }
+
+// Response Parsing
+
+fn check_response(code: u16, message: &str) -> NntpResponse {
+    NntpResponse {
+        code,
+        message: message.to_string(),
+        lines: vec![],
+    }
+}
+
+#[test]
+fn test_parse_check_response_statuses() {
+    let id = "<abc@example.com>";
+    for (code, status) in [
+        (codes::CHECK_SEND, CheckStatus::Send),
+        (codes::CHECK_LATER, CheckStatus::Later),
+        (codes::CHECK_NOT_WANTED, CheckStatus::NotWanted),
+    ] {
+        let result = commands::parse_check_response(id, check_response(code, id)).unwrap();
+        assert_eq!(result.message_id, id);
+        assert_eq!(result.status, status);
+    }
+}
+
+#[test]
+fn test_parse_check_response_rejects_other_message_id() {
+    let response = check_response(codes::CHECK_SEND, "<other@example.com>");
+    assert!(matches!(
+        commands::parse_check_response("<abc@example.com>", response),
+        Err(NntpError::InvalidResponse(_))
+    ));
+
+    // No message-id echoed at all is tolerated
+    let response = check_response(codes::CHECK_SEND, "Send article");
+    assert!(commands::parse_check_response("<abc@example.com>", response).is_ok());
+}
+
+#[test]
+fn test_parse_check_response_error_code() {
+    let response = check_response(500, "Command not recognized");
+    assert!(matches!(
+        commands::parse_check_response("<abc@example.com>", response),
+        Err(NntpError::Protocol { code: 500, .. })
+    ));
+}
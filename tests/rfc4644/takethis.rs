@@ -366,3 +366,27 @@ fn test_takethis_response_without_message_id() {
     // Note: In real pipelined scenario, this would be problematic
     // but the response is still valid NNTP
 }
+
+#[test]
+fn test_takethis_line_has_no_article_data() {
+    let cmd = commands::takethis_line("<raw@example.com>");
+    assert_eq!(cmd, "TAKETHIS <raw@example.com>\r\n");
+    assert_eq!(cmd.matches("\r\n").count(), 1);
+}
+
+#[test]
+fn test_takethis_raw_article_matches_serialized_form() {
+    // Raw bytes followed by the terminator are exactly what TAKETHIS would send
+    let article = b"Message-ID: <raw@example.com>\r\n\r\nBody\r\n";
+    let validated = commands::validate_raw_article(article).unwrap();
+
+    let mut wire = commands::takethis_line("<raw@example.com>").into_bytes();
+    wire.extend_from_slice(validated);
+    wire.extend_from_slice(b".\r\n");
+
+    let expected = format!(
+        "{}.\r\n",
+        commands::takethis("<raw@example.com>", std::str::from_utf8(article).unwrap())
+    );
+    assert_eq!(wire, expected.as_bytes());
+}
@@ -5,6 +5,7 @@
 mod rfc5536 {
     mod builder;
     mod encoded_words;
+    mod header_order;
     mod headers;
     mod mime;
 }
@@ -0,0 +1,99 @@
+//! Header order and spelling survive a parse and reserialize round trip
+
+use nntp_rs::{ArticleBuilder, parse_article};
+
+const RELAYED: &str = "Path: relay.example.net!not-for-mail\r\n\
+X-Trace: first\r\n\
+Message-Id: <order@example.com>\r\n\
+From: poster@example.com\r\n\
+MIME-Version: 1.0\r\n\
+content-type: text/plain; charset=utf-8\r\n\
+Newsgroups: misc.test\r\n\
+Subject: Order\r\n\
+Lines: 1\r\n\
+X-Trace: second\r\n\
+Date: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+Xref: local.example.net misc.test:42\r\n\
+\r\n\
+Body\r\n";
+
+fn header_lines(serialized: &str) -> Vec<&str> {
+    serialized
+        .split("\r\n")
+        .take_while(|line| !line.is_empty())
+        .collect()
+}
+
+#[test]
+fn test_round_trip_keeps_order_and_case() {
+    let article = parse_article(RELAYED).unwrap();
+    let serialized = article.serialize_for_posting().unwrap();
+
+    let mut expected = header_lines(RELAYED);
+    expected.retain(|line| !line.starts_with("Xref:"));
+    assert_eq!(header_lines(&serialized), expected);
+    assert!(serialized.ends_with("\r\n\r\nBody\r\n"));
+}
+
+#[test]
+fn test_extra_lookup_ignores_case() {
+    let article = parse_article(RELAYED).unwrap();
+    let extra = &article.headers.extra;
+
+    assert_eq!(extra["Content-Type"], "text/plain; charset=utf-8");
+    assert_eq!(extra.get("x-trace").unwrap(), "first");
+    assert_eq!(
+        extra.get_all("X-Trace").collect::<Vec<_>>(),
+        ["first", "second"]
+    );
+    let names: Vec<&String> = extra.keys().collect();
+    assert_eq!(
+        names,
+        ["X-Trace", "MIME-Version", "content-type", "X-Trace"]
+    );
+    assert_eq!(article.content_type(), Some("text/plain; charset=utf-8"));
+}
+
+#[test]
+fn test_edits_keep_position_and_new_headers_follow() {
+    let mut article = parse_article(RELAYED).unwrap();
+    article.headers.subject = "Edited".to_string();
+    article.headers.organization = Some("Example".to_string());
+    article.headers.extra.remove("MIME-Version");
+    article
+        .headers
+        .extra
+        .insert("X-Added".to_string(), "yes".to_string());
+
+    let serialized = article.serialize_for_posting().unwrap();
+    let lines = header_lines(&serialized);
+
+    assert_eq!(lines[6], "Subject: Edited");
+    assert!(!lines.iter().any(|line| line.starts_with("MIME-Version")));
+    assert_eq!(
+        lines[lines.len() - 2..],
+        ["Organization: Example", "X-Added: yes"]
+    );
+}
+
+#[test]
+fn test_built_article_keeps_extra_header_order() {
+    let names = ["X-Zeta", "X-Alpha", "X-Middle", "X-Beta"];
+    let builder = names
+        .iter()
+        .fold(ArticleBuilder::new(), |builder, name| {
+            builder.extra_header(*name, "value")
+        })
+        .from("poster@example.com")
+        .subject("Built")
+        .newsgroups(vec!["misc.test"]);
+
+    let serialized = builder.build_for_posting().unwrap();
+    let extras: Vec<String> = header_lines(&serialized)
+        .into_iter()
+        .filter(|line| line.starts_with("X-"))
+        .map(str::to_string)
+        .collect();
+    let expected: Vec<String> = names.iter().map(|name| format!("{name}: value")).collect();
+    assert_eq!(extras, expected);
+}
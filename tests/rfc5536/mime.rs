@@ -421,3 +421,247 @@ fn test_multipart_alternative_html_and_text() {
             .contains("multipart/alternative")
     );
 }
+
+// ========================================
+// Multipart body parsing (RFC 2046 Section 5.1)
+// ========================================
+
+mod multipart {
+    use super::create_basic_headers;
+    use nntp_rs::article::mime::{MimeContent, parse_multipart};
+    use nntp_rs::article::{Article, MimePart};
+
+    fn multipart_article(content_type: &str, body: &str) -> Article {
+        let mut headers = create_basic_headers();
+        headers
+            .extra
+            .insert("Content-Type".to_string(), content_type.to_string());
+        Article::new(headers, body.to_string())
+    }
+
+    #[test]
+    fn test_nested_multipart() {
+        let body = "This is a MIME message.\r\n\
+                    --outer\r\n\
+                    Content-Type: multipart/alternative;\r\n \
+                    boundary=\"inner\"\r\n\
+                    \r\n\
+                    --inner\r\n\
+                    Content-Type: text/plain; charset=utf-8\r\n\
+                    \r\n\
+                    plain text\r\n\
+                    --inner\r\n\
+                    Content-Type: text/html\r\n\
+                    \r\n\
+                    <p>html</p>\r\n\
+                    --inner--\r\n\
+                    --outer\r\n\
+                    Content-Type: application/octet-stream; name=\"data;1.bin\"\r\n\
+                    Content-Transfer-Encoding: base64\r\n\
+                    \r\n\
+                    AAEC\r\n\
+                    Aw\r\n\
+                    --outer--\r\n\
+                    epilogue\r\n";
+        let article = multipart_article("multipart/mixed; boundary=outer", body);
+        let parts = article.mime_parts().unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].media_type(), "multipart/alternative");
+        let alternatives = parts[0].parts();
+        assert_eq!(alternatives.len(), 2);
+        assert_eq!(alternatives[0].param("charset").as_deref(), Some("utf-8"));
+        assert_eq!(alternatives[0].text(), Some("plain text"));
+        assert_eq!(alternatives[1].text(), Some("<p>html</p>"));
+
+        assert_eq!(parts[1].filename().as_deref(), Some("data;1.bin"));
+        assert_eq!(parts[1].data(), Some(&[0u8, 1, 2, 3][..]));
+
+        let top = MimePart {
+            headers: Vec::new(),
+            content: MimeContent::Multipart(parts),
+        };
+        let leaves: Vec<String> = top.leaves().iter().map(|part| part.media_type()).collect();
+        assert_eq!(
+            leaves,
+            vec!["text/plain", "text/html", "application/octet-stream"]
+        );
+    }
+
+    #[test]
+    fn test_quoted_printable_and_defaults() {
+        let body = "--b\n\
+                    Content-Transfer-Encoding: Quoted-Printable\n\
+                    \n\
+                    Caf=C3=A9 au lait, a long line that was =\n\
+                    wrapped   \n\
+                    second line =3D fine\n\
+                    --b   \n\
+                    \n\
+                    no headers, no closing boundary\n";
+        let parts = parse_multipart("Multipart/Mixed; Boundary=\"b\"", body).unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[0].text(),
+            Some("Café au lait, a long line that was wrapped\nsecond line = fine")
+        );
+        assert!(parts[1].headers.is_empty());
+        assert_eq!(parts[1].content_type(), "text/plain");
+        assert_eq!(parts[1].text(), Some("no headers, no closing boundary"));
+    }
+
+    #[test]
+    fn test_multipart_errors() {
+        assert!(parse_multipart("text/plain", "body").is_err());
+        assert!(parse_multipart("multipart/mixed", "body").is_err());
+        assert!(
+            multipart_article("multipart/mixed; boundary=b", "--b\n\n")
+                .mime_parts()
+                .is_ok()
+        );
+
+        let bad_base64 = "--b\nContent-Transfer-Encoding: base64\n\n!!!\n--b--\n";
+        assert!(parse_multipart("multipart/mixed; boundary=b", bad_base64).is_err());
+
+        // Every level opens the next one
+        let mut deep = String::new();
+        for level in 0..20 {
+            deep.push_str(&format!(
+                "--b{level}\nContent-Type: multipart/mixed; boundary=b{}\n\n",
+                level + 1
+            ));
+        }
+        assert!(parse_multipart("multipart/mixed; boundary=b0", &deep).is_err());
+
+        let plain = Article::new(create_basic_headers(), "text".to_string());
+        assert!(plain.mime_parts().is_err());
+    }
+}
+
+mod decoded_body {
+    use super::*;
+
+    fn encoded_article(content_type: &str, encoding: &str, body: &str) -> Article {
+        let mut headers = create_basic_headers();
+        headers
+            .extra
+            .insert("Content-Type".to_string(), content_type.to_string());
+        headers.extra.insert(
+            "Content-Transfer-Encoding".to_string(),
+            encoding.to_string(),
+        );
+        Article::new(headers, body.to_string())
+    }
+
+    #[test]
+    fn test_decoded_body_quoted_printable_latin1() {
+        let article = encoded_article(
+            "text/plain; charset=ISO-8859-1",
+            "Quoted-Printable",
+            "Caf=E9 au lait, soft=\r\nbreak\r\nnext line",
+        );
+        assert_eq!(
+            article.decoded_body().unwrap(),
+            "Café au lait, softbreak\nnext line"
+        );
+    }
+
+    #[test]
+    fn test_decoded_body_base64_utf8() {
+        // "Grüße\n" in UTF-8
+        let article = encoded_article("text/plain; charset=utf-8", "base64", "R3LDvMOfZQo=");
+        assert_eq!(article.decoded_body().unwrap(), "Grüße\n");
+
+        let bad = encoded_article("text/plain", "base64", "!!!");
+        assert!(bad.decoded_body().is_err());
+    }
+
+    #[test]
+    fn test_decoded_body_identity_untouched() {
+        let article = encoded_article("text/plain; charset=iso-8859-1", "8bit", "déjà vu");
+        assert_eq!(article.decoded_body().unwrap(), "déjà vu");
+
+        let plain = Article::new(create_basic_headers(), "plain".to_string());
+        assert_eq!(plain.decoded_body().unwrap(), "plain");
+    }
+
+    #[test]
+    fn test_decoded_body_8bit_bytes_in_charset() {
+        let mut headers = create_basic_headers();
+        headers.extra.insert(
+            "Content-Type".to_string(),
+            "text/plain; charset=iso-8859-1".to_string(),
+        );
+        headers
+            .extra
+            .insert("Content-Transfer-Encoding".to_string(), "8bit".to_string());
+        // "déjà vu" in ISO-8859-1
+        let article = Article::with_body_bytes(headers, b"d\xe9j\xe0 vu".to_vec());
+        assert_eq!(article.decoded_body().unwrap(), "déjà vu");
+    }
+
+    #[test]
+    fn test_part_decoded_text() {
+        let body = "--b\r\n\
+                    Content-Type: text/plain; charset=iso-8859-1\r\n\
+                    Content-Transfer-Encoding: quoted-printable\r\n\
+                    \r\n\
+                    na=EFve\r\n\
+                    --b--\r\n";
+        let parts =
+            nntp_rs::article::mime::parse_multipart("multipart/mixed; boundary=b", body).unwrap();
+        assert_eq!(parts[0].text(), None);
+        assert_eq!(parts[0].decoded_text().as_deref(), Some("naïve"));
+    }
+
+    #[cfg(feature = "charsets")]
+    #[test]
+    fn test_decoded_body_legacy_charsets() {
+        // "Привет" in KOI8-R
+        let article = encoded_article(
+            "text/plain; charset=koi8-r",
+            "quoted-printable",
+            "=F0=D2=C9=D7=C5=D4",
+        );
+        assert_eq!(article.decoded_body().unwrap(), "Привет");
+
+        // "日本" in Shift_JIS
+        let article = encoded_article("text/plain; charset=Shift_JIS", "base64", "k/qWew==");
+        assert_eq!(article.decoded_body().unwrap(), "日本");
+    }
+}
+
+mod unflowed_body {
+    use super::*;
+
+    fn article(content_type: &str, body: &str) -> Article {
+        let mut headers = create_basic_headers();
+        headers
+            .extra
+            .insert("Content-Type".to_string(), content_type.to_string());
+        Article::new(headers, body.to_string())
+    }
+
+    #[test]
+    fn test_flowed_with_delsp_and_quoted_printable() {
+        let mut article = article(
+            "text/plain; charset=utf-8; format=\"Flowed\"; DelSp=Yes",
+            // A soft break's space is encoded, as QP drops trailing spaces
+            "Gr=C3=BC=C3=9F=20\r\ne dich.\r\n",
+        );
+        article.headers.extra.insert(
+            "Content-Transfer-Encoding".to_string(),
+            "quoted-printable".to_string(),
+        );
+        assert!(article.is_flowed());
+        assert_eq!(article.unflowed_body().unwrap(), "Grüße dich.");
+    }
+
+    #[test]
+    fn test_fixed_body_is_unchanged() {
+        let article = article("text/plain; charset=utf-8", "soft \r\nbreak\r\n");
+        assert!(!article.is_flowed());
+        assert_eq!(article.unflowed_body().unwrap(), "soft \r\nbreak\r\n");
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Reference: https://datatracker.ietf.org/doc/html/rfc5537#section-5
 
-use nntp_rs::article::{Article, ControlMessage, Headers};
+use nntp_rs::article::{Article, ArticleBuilder, ControlMessage, Headers};
+use nntp_rs::cancel_lock::{CancelSecret, verify};
 #[test]
 fn test_is_control_message_true() {
     let mut headers = Headers::new(
@@ -482,3 +483,121 @@ fn test_control_message_inequality() {
     };
     assert_ne!(msg1, msg2);
 }
+
+// CANCEL BUILDER AND CANCEL-LOCK TESTS (RFC 8315)
+
+fn locked_original(secret: &CancelSecret) -> Article {
+    ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Oops")
+        .newsgroups(vec!["misc.test", "alt.test"])
+        .distribution("nl")
+        .message_id("<original@example.com>")
+        .cancel_secret(secret)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_cancel_of_builds_cancel_control_message() {
+    let original = locked_original(&CancelSecret::new(b"secret".to_vec(), "alice"));
+    let cancel = ArticleBuilder::cancel_of(&original)
+        .from("alice@example.com")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        cancel.parse_control_message(),
+        Some(ControlMessage::Cancel {
+            message_id: "<original@example.com>".to_string()
+        })
+    );
+    assert_eq!(cancel.headers.subject, "cmsg cancel <original@example.com>");
+    assert_eq!(cancel.headers.newsgroups, vec!["misc.test", "alt.test"]);
+    assert_eq!(cancel.headers.distribution, Some("nl".to_string()));
+    assert!(!cancel.headers.extra.contains_key("Cancel-Key"));
+}
+
+#[test]
+fn test_cancel_key_opens_original_lock() {
+    let secret = CancelSecret::new(b"secret".to_vec(), "alice");
+    let original = locked_original(&secret);
+    let cancel = ArticleBuilder::cancel_of(&original)
+        .from("alice@example.com")
+        .cancel_secret(&secret)
+        .build()
+        .unwrap();
+
+    let lock = &original.headers.extra["Cancel-Lock"];
+    let key = &cancel.headers.extra["Cancel-Key"];
+    assert!(verify(lock, key));
+    // A cancel is not itself locked
+    assert!(!cancel.headers.extra.contains_key("Cancel-Lock"));
+
+    let serialized = cancel.serialize_for_posting().unwrap();
+    assert!(serialized.contains(&format!("Cancel-Key: {key}\r\n")));
+
+    let stranger = CancelSecret::new(b"other secret".to_vec(), "alice");
+    assert!(!verify(lock, &stranger.key("<original@example.com>")));
+}
+
+#[test]
+fn test_superseding_article_is_locked_and_keyed() {
+    let secret = CancelSecret::new(b"secret".to_vec(), "alice");
+    let original = locked_original(&secret);
+    let replacement = ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Oops, fixed")
+        .newsgroups(vec!["misc.test"])
+        .message_id("<replacement@example.com>")
+        .supersedes("<original@example.com>")
+        .cancel_secret(&secret)
+        .build()
+        .unwrap();
+
+    assert!(verify(
+        &original.headers.extra["Cancel-Lock"],
+        &replacement.headers.extra["Cancel-Key"]
+    ));
+    assert_eq!(
+        replacement.headers.extra["Cancel-Lock"],
+        secret.lock("<replacement@example.com>")
+    );
+}
+
+#[test]
+fn test_cancel_secret_keeps_explicit_headers() {
+    let secret = CancelSecret::new(b"secret".to_vec(), "");
+    let article = ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Test")
+        .newsgroups(vec!["misc.test"])
+        .extra_header("Cancel-Lock", "sha256:preset")
+        .cancel_secret(&secret)
+        .build()
+        .unwrap();
+
+    assert_eq!(article.headers.extra["Cancel-Lock"], "sha256:preset");
+}
+
+#[test]
+fn test_build_parts_locks_each_part() {
+    let secret = CancelSecret::new(b"secret".to_vec(), "alice");
+    let parts = ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Long")
+        .newsgroups(vec!["misc.test"])
+        .message_id("<long@example.com>")
+        .body("text\n".repeat(2000))
+        .cancel_secret(&secret)
+        .build_parts(4096)
+        .unwrap();
+
+    assert!(parts.len() > 2);
+    for part in &parts {
+        let id = &part.headers.message_id;
+        assert_eq!(part.headers.extra["Cancel-Lock"], secret.lock(id));
+        assert!(verify(&part.headers.extra["Cancel-Lock"], &secret.key(id)));
+        assert!(part.serialize_for_posting_bytes().unwrap().len() + 3 <= 4096);
+    }
+}
@@ -0,0 +1,127 @@
+//! RFC 5537 Section 3.4.3 - Followup Tests
+//!
+//! Reference: https://datatracker.ietf.org/doc/html/rfc5537#section-3.4.3
+
+use nntp_rs::article::{Article, ArticleBuilder, parse_article};
+
+fn parent(extra_headers: &str) -> Article {
+    let raw = format!(
+        "From: alice@example.com\r\n\
+         Subject: Lifetimes\r\n\
+         Newsgroups: comp.lang.rust,comp.lang.c\r\n\
+         Date: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+         Message-ID: <parent@example.com>\r\n\
+         Path: news.example.com!not-for-mail\r\n\
+         {extra_headers}\
+         \r\n\
+         Body\r\n"
+    );
+    parse_article(&raw).unwrap()
+}
+
+fn followup(parent: &Article) -> ArticleBuilder {
+    ArticleBuilder::new()
+        .reply_to_article(parent)
+        .from("bob@example.com")
+        .body("Reply")
+}
+
+#[test]
+fn test_followup_to_article_without_references() {
+    let reply = followup(&parent("")).build().unwrap();
+
+    assert_eq!(reply.headers.subject, "Re: Lifetimes");
+    assert_eq!(
+        reply.headers.newsgroups,
+        vec!["comp.lang.rust", "comp.lang.c"]
+    );
+    assert_eq!(
+        reply.headers.references,
+        Some(vec!["<parent@example.com>".to_string()])
+    );
+    assert_eq!(reply.headers.distribution, None);
+}
+
+#[test]
+fn test_followup_appends_to_references() {
+    let parent = parent("References: <root@example.com> <mid@example.com>\r\n");
+    let reply = followup(&parent).build().unwrap();
+
+    assert_eq!(
+        reply.headers.references,
+        Some(vec![
+            "<root@example.com>".to_string(),
+            "<mid@example.com>".to_string(),
+            "<parent@example.com>".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn test_followup_keeps_existing_re_prefix() {
+    for subject in ["Re: Lifetimes", "RE: Lifetimes", "re:Lifetimes"] {
+        let parent = ArticleBuilder::new()
+            .from("alice@example.com")
+            .subject(subject)
+            .newsgroups(vec!["comp.lang.rust"])
+            .build()
+            .unwrap();
+        let reply = followup(&parent).build().unwrap();
+        assert_eq!(reply.headers.subject, subject);
+    }
+}
+
+#[test]
+fn test_followup_honors_followup_to() {
+    let parent = parent("Followup-To: comp.lang.rust\r\nDistribution: nl\r\n");
+    let reply = followup(&parent).build().unwrap();
+
+    assert_eq!(reply.headers.newsgroups, vec!["comp.lang.rust"]);
+    assert_eq!(reply.headers.distribution, Some("nl".to_string()));
+    assert_eq!(reply.headers.followup_to, None);
+}
+
+#[test]
+fn test_followup_to_poster_is_not_posted() {
+    let parent = parent("Followup-To: poster\r\n");
+    assert!(parent.headers.followup_to.is_some());
+
+    let reply = followup(&parent);
+    assert!(reply.clone().build().is_err());
+
+    // The poster can still be overruled explicitly
+    let reply = reply.newsgroups(vec!["comp.lang.rust"]).build().unwrap();
+    assert_eq!(reply.headers.newsgroups, vec!["comp.lang.rust"]);
+}
+
+#[test]
+fn test_followup_shortens_long_references() {
+    let ancestors: Vec<String> = (0..40)
+        .map(|n| format!("<ancestor-{n:02}@long-domain-name.example.com>"))
+        .collect();
+    let parent = ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Long thread")
+        .newsgroups(vec!["comp.lang.rust"])
+        .message_id("<parent@example.com>")
+        .references(ancestors.clone())
+        .build()
+        .unwrap();
+
+    let reply = followup(&parent).build().unwrap();
+    let references = reply.headers.references.unwrap();
+    let line = format!("References: {}", references.join(" "));
+
+    assert!(line.len() <= 998);
+    assert!(references.len() < ancestors.len() + 1);
+    assert_eq!(references[0], ancestors[0]);
+    assert_eq!(
+        references[references.len() - 4..],
+        [
+            ancestors[37].clone(),
+            ancestors[38].clone(),
+            ancestors[39].clone(),
+            "<parent@example.com>".to_string(),
+        ]
+    );
+}
@@ -3,4 +3,7 @@
 //! Reference: https://datatracker.ietf.org/doc/html/rfc5537
 
 pub mod control;
+pub mod followup;
+#[cfg(feature = "pgpverify")]
+pub mod pgpverify;
 pub mod supersedes;
@@ -0,0 +1,230 @@
+//! RFC 5537 Section 5.2 - Signed Control Message Tests (pgpverify)
+//!
+//! Keys and signatures were made with GnuPG in the way `signcontrol` signs
+//! control messages.
+//!
+//! Reference: https://datatracker.ietf.org/doc/html/rfc5537#section-5.2
+//! Reference: ftp://ftp.isc.org/pub/pgpcontrol/FORMAT
+
+use nntp_rs::article::{ControlMessage, parse_article};
+use nntp_rs::pgpverify::Keyring;
+
+/// RSA 2048 key of "Test Hierarchy <control@example.com>"
+const RSA_KEY: &str = r#"-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mQENBGrUIHABCAC/z9EDZsWMG3XI6Vo9v4cUZz2CDOLFWz+ZQQQjc4u30Ub7vaGC
+DSs4JdrKiAROgm8N+6D/a1JxO5JXDiRHnlnouNg5cNPHE3K01LcnYH5xMdreurFO
+uI8HgJgJOojPDMhqwvTEawC/lkaqcUM55aSFzOZe6lwdoMG1MaBkjvT5cl08DK1d
+fYAegJlNBFpUIGWgWtV3cffepWHaNRDCefqPnFFRAimzxlWMBQ5iPIZGGSE3Ke23
+yXRmELNV5+hHrhP3p15tzuaocZK1se5ql20IQDT43lMzs7dUxqESp4+1+PmK5+GS
+ES8e3lEuzXqgpnWo5Oz7cR00PfMahczSfKf1ABEBAAG0JFRlc3QgSGllcmFyY2h5
+IDxjb250cm9sQGV4YW1wbGUuY29tPokBTgQTAQoAOBYhBCSTOqsHJj3fnlSO2zOO
+yRyUTXjGBQJq1CBwAhsDBQsJCAcCBhUKCQgLAgQWAgMBAh4BAheAAAoJEDOOyRyU
+TXjGF70H/A98zO6q/2wfqUF7OFloqoq5sRHvTCAK78fSke4AsysYv1x/QtMBh02C
+tB0Lq2Tip8J2Hx41yFOmjxxrnCR/kUZ6lsW+PYDMP4IsXQShMZEolndsW/WpEG4F
+ferv3FZ3ZMcb9WYLMz+xlXklOiG466uAtpE+eUw8OVJoM42bI89dLdty1QhpmTjK
+VX+HTtzBcaguCZKCNkzay1eAgnB66Ggi5S2imRRiwEXDa7vq5QV1cFXvzK4NFf6V
+gx1ze3lVBUuO6RZr0v67m1x7uHJ2bRyLRx9SXADy+UoJLdCzbFHSeuJ0RS1vCkO5
+oXsx0842IefCXJvHMgpfkM0OzmRtER8=
+=jwvG
+-----END PGP PUBLIC KEY BLOCK-----"#;
+
+/// DSA 2048 key of "DSA Hierarchy <dsa@example.com>"
+const DSA_KEY: &str = r#"-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mQMuBGrUIHERCADQ17iiMShZG0TPy/VA4D7OL9JrvfXElRT0DOkrGzX3DDwHyWmz
+32stzUv24OooYwKx0++Ub6L0YeRUmpnjCyhP2ASxtwOyQUSLc65fuY2dyk75sgJu
+9Gp+yk417rE64bcfNYm9edrqI1SnNWxic1V09JQ0KWualunizYD4qtJ4eK70kLGQ
+YF+Jxs5ZGX8NzUH2iD7GfvVx64FbUi4lGzKIfuMJodgjrd/HiR9HbDn8pLkaRZl+
+xPu/cHxfWaR1EP7JFxQspxq+bQBoSr7Xcfwdkz8ABLe1vAzuTac6byHIqycI61Rn
+u5DVgTsiv8CkSnmob9DiF89lHOUfVz5LRb3fAQDD5KeBaSZDHflP+uZ1mmet93Z8
+M8m000EEQYW7c+yWYwf+NGIrFaaZiBfx93Psaygjx4TI1O7lpnffnI24QoEjDxeV
+tGzL1xDvGXyik6KZaPIzEb8ICbGaxGh5mD4MY9o6fsRoPtK3tcf1smE9aajzZSVd
+y2+R2tW3GmzZtFtmB9rCQO1IpotDsZPV/0DQSMXJkpfgvqQm97ae3dP8h/plvVpd
+GqsNd1NQICiSOaHcZEyaTY3tEVVXuEW8vy8EOSwvghOmEo/RoN6zYdaubwiU1KmD
+i4jOt3wg8e6QJVTufZe1udYIEDykUjDCrLa56kiq2/ljsU/xeTVrdYvVoLRcSAI7
+d8R2tlztdfkpt3YyYUTuLgplRABs9aHgqP6AIeuH0Qf+PZsk6mD9SFPylRWOGW3q
+y+WteIH0DzUqIyGkQAtwmIn5oAjT55VRNGu/EUQGdnq93018CskcoTkBY7OeWZ8a
+Uu3oAFMzZjA8S585wQlj5XyrPtvXwb3Sm2K5AXR8Lb6+OMjAqjYlc3lGXNWtZU7Q
+STjbcjDQ/Wg9NGLmUCa0nD9XKSw0xc5TyD/tJBbof5V7HxhwhHkUmey///pvrfFn
+8VSB+g+nnJghxUDXD1KbCTRgrSk4ViQYUOPFXzMsQfCvMo0iUrVFuDK/K60aLERQ
+LTlWGCpEgP9T5MtTq6X0FiIeQ8raB6i2p7GMwg5mn7WPhdgTL36bqD626nRancdV
+YrQfRFNBIEhpZXJhcmNoeSA8ZHNhQGV4YW1wbGUuY29tPoiQBBMRCAA4FiEEeAKj
+2OrGY73axIxESQGFpcTdnaEFAmrUIHECGwMFCwkIBwIGFQoJCAsCBBYCAwECHgEC
+F4AACgkQSQGFpcTdnaEr8QD+NuzWFBPbuaxMQs7aE6toVSK07Zfx5S9AXgj2kqD6
+vz8BAIN76/ny1lYJOg1NQHfsHgxdRp2KkwhHYQ8cyfz3fYaG
+=etO9
+-----END PGP PUBLIC KEY BLOCK-----"#;
+
+/// newgroup signed with the RSA key; a body line ends in spaces and the
+/// absent Sender header is signed as empty
+const NEWGROUP: &str = concat!(
+    "Path: news.example.com!not-for-mail\r\n",
+    "From: Test Hierarchy <control@example.com>\r\n",
+    "Newsgroups: test.rust\r\n",
+    "Subject: cmsg newgroup test.rust\r\n",
+    "Control: newgroup test.rust\r\n",
+    "Approved: control@example.com\r\n",
+    "Message-ID: <newgroup-test.rust-20251018@example.com>\r\n",
+    "Date: Sat, 18 Oct 2025 12:00:00 +0000\r\n",
+    "X-PGP-Sig: GnuPG Subject,Control,Message-ID,Date,From,Sender\r\n",
+    "\tiQFIBAEBCAAyFiEEJJM6qwcmPd+eVI7bM47JHJRNeMYFAmrUIIAUHGNvbnRyb2xA\r\n",
+    "\tZXhhbXBsZS5jb20ACgkQM47JHJRNeMYUhwf/cUKM7mBzAtqHSyeRs8pj2eDugwRJ\r\n",
+    "\tPL6KVrmjZUX3c68HzanuFkp8sQXUHqQXWzy9A2JAk69md/aQFpPxdjo50cgMNSU3\r\n",
+    "\tG90Gk1TVRVna9wAzhdcwAdm0BQ6BpHqStjI1QAPLBbwBZKYOcIDcvnp9E0wxyQ26\r\n",
+    "\tQoHNe/+KTXorByhN+Bnv+5P5HiHvun33uiGL6tP1emuAkPBdaOwf+Cg8IrsyuZ/E\r\n",
+    "\tmpxrHQltSJjOxzbZFCINGT2UdeORBCLH3N/oIOr50THPJ5KjjV/7B7SKblIdB5VL\r\n",
+    "\t2BVNZvJyLa1sKpGuufyFkWCGX9N+tUx8lq4KB8bwceZMtDOoSdZ2gnrkfw==\r\n",
+    "\t=35lk\r\n",
+    "\r\n",
+    "test.rust is an unmoderated newsgroup for Rust.  \r\n",
+    "\r\n",
+    "For your newsgroups file:\r\n",
+    "test.rust\tThe Rust programming language.\r\n",
+);
+
+/// rmgroup signed with the DSA key, without Sender among the signed headers
+const RMGROUP: &str = concat!(
+    "Path: news.example.com!not-for-mail\r\n",
+    "From: DSA Hierarchy <dsa@example.com>\r\n",
+    "Newsgroups: test.rust\r\n",
+    "Subject: cmsg rmgroup test.old\r\n",
+    "Control: rmgroup test.old\r\n",
+    "Approved: control@example.com\r\n",
+    "Message-ID: <rmgroup-test.old-20251018@example.com>\r\n",
+    "Date: Sat, 18 Oct 2025 12:00:00 +0000\r\n",
+    "X-PGP-Sig: GnuPG Subject,Control,Message-ID,Date,From\r\n",
+    "\tiIYEAREIAC4WIQR4AqPY6sZjvdrEjERJAYWlxN2doQUCatQggBAcZHNhQGV4YW1w\r\n",
+    "\tbGUuY29tAAoJEEkBhaXE3Z2hiegA/AoAF7yCsJUrFs6qE/Px+ome0fVZsUDZewAg\r\n",
+    "\toU0Y/zcKAQCiyMeChnxn9OFTJMTKcOtIDU9m1vzV4LOFJsa1IoiXFg==\r\n",
+    "\t=dxt3\r\n",
+    "\r\n",
+    "Please remove test.old.\r\n",
+);
+
+/// checkgroups signed with the RSA key over SHA-1, in binary mode
+const CHECKGROUPS: &str = concat!(
+    "Path: news.example.com!not-for-mail\r\n",
+    "From: Test Hierarchy <control@example.com>\r\n",
+    "Newsgroups: test.rust\r\n",
+    "Subject: cmsg checkgroups test #2025101801\r\n",
+    "Control: checkgroups test #2025101801\r\n",
+    "Approved: control@example.com\r\n",
+    "Message-ID: <checkgroups-test-20251018@example.com>\r\n",
+    "Date: Sat, 18 Oct 2025 12:00:00 +0000\r\n",
+    "X-PGP-Sig: GnuPG Subject,Control,Message-ID,Date,From,Sender\r\n",
+    "\tiQFIBAABAgAyFiEEJJM6qwcmPd+eVI7bM47JHJRNeMYFAmrUIXgUHGNvbnRyb2xA\r\n",
+    "\tZXhhbXBsZS5jb20ACgkQM47JHJRNeMaSLggAvNt1svfowVn4Bqt0Hga6D72Nz8GK\r\n",
+    "\tTv9DhyFAGG8/hnzwc3KRQLQomJKyxm6pJPbu16edG5VqiYPfxTw8a3ClKpSAPILU\r\n",
+    "\todUqgkaZrCrkcQXLYV5TC2P3bhhCHQyoofqgFYb6+290VnDk8tbOGUqf1OzrYsHy\r\n",
+    "\twYHnDIbFWKffZJVbVdOzjCBTH8YRYfUYewUUkoxd/QaEFnOhTs4d31TlylMl61E+\r\n",
+    "\taDqFWnnBhRyIeHFCF+ty/1+fM4RqI6ZpuNndTYJtDzEIWCdWd1H5kOOEIKIvVMBN\r\n",
+    "\tyI12Q+skEl6NNjZQaXeS7YM5d51I8bh0dNIT+AvykUA7b7kosm7i8e2E/g==\r\n",
+    "\t=YHot\r\n",
+    "\r\n",
+    "test.rust\tThe Rust programming language.\r\n",
+);
+
+fn keyring() -> Keyring {
+    let mut keyring = Keyring::from_armored(RSA_KEY).unwrap();
+    keyring.add_armored(DSA_KEY).unwrap();
+    keyring
+}
+
+fn verify(raw: &str) -> nntp_rs::Result<String> {
+    ControlMessage::verify_signature(&parse_article(raw).unwrap(), &keyring())
+}
+
+#[test]
+fn test_keyring_from_armored() {
+    let keyring = Keyring::from_armored(RSA_KEY).unwrap();
+    assert_eq!(keyring.len(), 1);
+    assert!(Keyring::new().is_empty());
+    assert!(Keyring::from_armored("no key here").is_err());
+
+    let corrupted = RSA_KEY.replacen("mQEN", "mQEO", 1);
+    assert!(Keyring::from_armored(&corrupted).is_err());
+}
+
+#[test]
+fn test_verify_rsa_signed_newgroup() {
+    assert_eq!(
+        verify(NEWGROUP).unwrap(),
+        "Test Hierarchy <control@example.com>"
+    );
+}
+
+#[test]
+fn test_verify_dsa_signed_rmgroup() {
+    assert_eq!(verify(RMGROUP).unwrap(), "DSA Hierarchy <dsa@example.com>");
+}
+
+#[test]
+fn test_verify_binary_sha1_signed_checkgroups() {
+    assert_eq!(
+        verify(CHECKGROUPS).unwrap(),
+        "Test Hierarchy <control@example.com>"
+    );
+    // Binary-mode signatures cover the exact bytes
+    let forged = CHECKGROUPS.replace("language.", "language. ");
+    assert!(verify(&forged).is_err());
+}
+
+#[test]
+fn test_verify_accepts_lf_line_endings() {
+    assert!(verify(&NEWGROUP.replace("\r\n", "\n")).is_ok());
+}
+
+#[test]
+fn test_verify_rejects_tampering() {
+    // Signed header changed
+    let forged = NEWGROUP.replace("newgroup test.rust", "newgroup test.rust moderated");
+    assert!(verify(&forged).is_err());
+
+    // Body changed
+    let forged = NEWGROUP.replace("unmoderated", "moderated");
+    assert!(verify(&forged).is_err());
+
+    // Signed header that was absent added
+    let forged = NEWGROUP.replace("Approved:", "Sender: evil@example.com\r\nApproved:");
+    assert!(verify(&forged).is_err());
+
+    // Signed header duplicated
+    let forged = NEWGROUP.replace("Approved:", "Control: rmgroup test.rust\r\nApproved:");
+    assert!(verify(&forged).is_err());
+}
+
+#[test]
+fn test_verify_unsigned_headers_may_change() {
+    let relayed = NEWGROUP.replace(
+        "Path: news.example.com!not-for-mail",
+        "Path: relay.example.net!news.example.com!not-for-mail",
+    );
+    assert!(verify(&relayed).is_ok());
+}
+
+#[test]
+fn test_verify_unknown_signer() {
+    let rsa_only = Keyring::from_armored(RSA_KEY).unwrap();
+    let article = parse_article(RMGROUP).unwrap();
+    let err = ControlMessage::verify_signature(&article, &rsa_only).unwrap_err();
+    assert!(err.to_string().contains("No key in keyring"));
+}
+
+#[test]
+fn test_verify_requires_signed_control() {
+    let unsigned = parse_article(
+        "From: a@example.com\r\nNewsgroups: test.rust\r\nSubject: hi\r\n\
+         Message-ID: <x@example.com>\r\nDate: Sat, 18 Oct 2025 12:00:00 +0000\r\n\
+         Path: not-for-mail\r\nControl: rmgroup test.rust\r\n\r\nbody\r\n",
+    )
+    .unwrap();
+    assert!(ControlMessage::verify_signature(&unsigned, &keyring()).is_err());
+
+    let control_not_signed = NEWGROUP.replace(
+        "GnuPG Subject,Control,Message-ID",
+        "GnuPG Subject,Message-ID",
+    );
+    let err = verify(&control_not_signed).unwrap_err();
+    assert!(err.to_string().contains("Control header is not signed"));
+}
@@ -4,6 +4,7 @@
 //! Reference: https://datatracker.ietf.org/doc/html/rfc5536#section-3.2.12
 
 use nntp_rs::article::{ArticleBuilder, parse_article};
+use nntp_rs::cancel_lock::{CancelSecret, verify};
 
 // PARSING TESTS
 
@@ -331,3 +332,102 @@ Body.
     assert!(article.headers.supersedes.is_some());
     // Header folding should be handled by unfold_header
 }
+
+// SUPERSEDE WORKFLOW TESTS
+
+const INJECTED: &str = "Path: news.example.com!not-for-mail\r\n\
+From: alice@example.com\r\n\
+Newsgroups: misc.test,misc.misc\r\n\
+Subject: Meeting on Mondy\r\n\
+Message-ID: <meeting@example.com>\r\n\
+Date: Mon, 20 Jan 2025 12:00:00 +0000\r\n\
+References: <agenda@example.com>\r\n\
+Organization: Example\r\n\
+Injection-Info: news.example.com; posting-host=\"client.example.com\"\r\n\
+NNTP-Posting-Date: Mon, 20 Jan 2025 12:00:01 +0000\r\n\
+Cancel-Lock: sha256:old\r\n\
+X-Mood: hopeful\r\n\
+Xref: news.example.com misc.test:7\r\n\
+\r\n\
+See you there.\r\n";
+
+#[test]
+fn test_supersede_copies_original() {
+    let original = parse_article(INJECTED).unwrap();
+    let replacement = ArticleBuilder::supersede(&original)
+        .subject("Meeting on Monday")
+        .build()
+        .unwrap();
+    let headers = &replacement.headers;
+
+    assert_eq!(headers.supersedes.as_deref(), Some("<meeting@example.com>"));
+    assert_ne!(headers.message_id, "<meeting@example.com>");
+    assert_eq!(headers.path, "not-for-mail");
+    assert_eq!(headers.subject, "Meeting on Monday");
+    assert_eq!(headers.from, "alice@example.com");
+    assert_eq!(headers.newsgroups, ["misc.test", "misc.misc"]);
+    assert_eq!(
+        headers.references.as_deref(),
+        Some(&["<agenda@example.com>".to_string()][..])
+    );
+    assert_eq!(headers.organization.as_deref(), Some("Example"));
+    assert_eq!(headers.xref, None);
+    assert_eq!(replacement.body, "See you there.\r\n");
+
+    let names: Vec<&String> = headers.extra.keys().collect();
+    assert_eq!(names, ["X-Mood"]);
+}
+
+#[test]
+fn test_supersede_with_cancel_secret() {
+    let secret = CancelSecret::new(b"secret".to_vec(), "alice");
+    let original = ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Draft")
+        .newsgroups(vec!["misc.test"])
+        .cancel_secret(&secret)
+        .build()
+        .unwrap();
+
+    let replacement = ArticleBuilder::supersede(&original)
+        .body("Final")
+        .cancel_secret(&secret)
+        .build()
+        .unwrap();
+
+    assert!(verify(
+        &original.headers.extra["Cancel-Lock"],
+        &replacement.headers.extra["Cancel-Key"]
+    ));
+    assert_eq!(
+        replacement.headers.extra["Cancel-Lock"],
+        secret.lock(&replacement.headers.message_id)
+    );
+}
+
+#[test]
+fn test_supersede_rejects_control_message() {
+    let original = ArticleBuilder::new()
+        .from("admin@example.com")
+        .subject("cmsg newgroup misc.new")
+        .newsgroups(vec!["misc.new"])
+        .control("newgroup misc.new")
+        .build()
+        .unwrap();
+
+    assert!(ArticleBuilder::supersede(&original).build().is_err());
+}
+
+#[test]
+fn test_supersede_keeps_binary_body() {
+    let original = ArticleBuilder::new()
+        .from("alice@example.com")
+        .subject("Binary")
+        .newsgroups(vec!["alt.binaries.test"])
+        .body_bytes(vec![0xff, 0x00, b'\n'])
+        .build()
+        .unwrap();
+
+    let replacement = ArticleBuilder::supersede(&original).build().unwrap();
+    assert_eq!(replacement.body_bytes(), original.body_bytes());
+}
@@ -5,4 +5,5 @@
 
 mod rfc8054 {
     mod compression;
+    mod session;
 }
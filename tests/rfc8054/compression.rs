@@ -471,3 +471,92 @@ fn test_zlib_compression_levels() {
     // Note: for small data, this might not always hold
     println!("Compression sizes by level: {:?}", sizes);
 }
+
+// ========================================
+// XFEATURE COMPRESS GZIP Detection Against a Mock Server
+// ========================================
+
+mod gzip_detection_tests {
+    use super::*;
+    use nntp_rs::{NntpClient, Pipeline, ServerConfig};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    const OVERVIEW: &str = "1\tFirst\ta@example.com\tMon, 20 Jan 2025 12:00:00 +0000\t<1@test>\t\t100\t5\r\n\
+                            2\tSecond\ta@example.com\tMon, 20 Jan 2025 12:00:00 +0000\t<2@test>\t\t100\t5\r\n";
+
+    /// Start a server offering only XFEATURE COMPRESS GZIP, which compresses
+    /// XOVER and OVER without a marker and sends the first compressed byte on
+    /// its own
+    async fn unmarked_gzip_server() -> NntpClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = socket.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"200 mock ready\r\n").await.unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let reply: Vec<u8> = if line.starts_with("XOVER") || line.starts_with("OVER") {
+                    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(OVERVIEW.as_bytes()).unwrap();
+                    let compressed = encoder.finish().unwrap();
+                    writer.write_all(b"224 overview follows\r\n").await.unwrap();
+                    writer.write_all(&compressed[..1]).await.unwrap();
+                    writer.flush().await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    [&compressed[1..], b".\r\n"].concat()
+                } else if line.starts_with("BODY") {
+                    // "x^" is also a valid zlib header
+                    b"222 0 <x@test> body\r\nx^ marks the spot\r\n.\r\n".to_vec()
+                } else if line == "XFEATURE COMPRESS GZIP" {
+                    b"290 compression enabled\r\n".to_vec()
+                } else {
+                    b"503 not supported\r\n".to_vec()
+                };
+                writer.write_all(&reply).await.unwrap();
+            }
+        });
+
+        let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+        let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+        assert!(client.try_enable_compression().await.unwrap());
+        client
+    }
+
+    #[tokio::test]
+    async fn test_auto_detection_waits_for_two_bytes() {
+        let mut client = unmarked_gzip_server().await;
+        let entries = client.fetch_xover("1-2").await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].subject, "Second");
+    }
+
+    #[tokio::test]
+    async fn test_auto_detection_sniffs_over() {
+        let mut client = unmarked_gzip_server().await;
+        let entries = client.over("1-2").await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].subject, "First");
+    }
+
+    #[tokio::test]
+    async fn test_auto_detection_sniffs_only_overview() {
+        let mut client = unmarked_gzip_server().await;
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .multiline("BODY <x@test>")
+            .multiline("BODY <x@test>")
+            .multiline("XOVER 1-2");
+        let responses = client.run_pipeline(&pipeline).await.unwrap();
+
+        // Each response is judged by its own command, not the last one sent
+        assert_eq!(responses[0].lines, vec!["x^ marks the spot"]);
+        assert_eq!(responses[1].lines, vec!["x^ marks the spot"]);
+        assert_eq!(responses[2].lines.len(), 2);
+        assert!(responses[2].lines[0].starts_with("1\tFirst"));
+    }
+}
@@ -0,0 +1,141 @@
+//! RFC 8054 full-session compression against a compressing mock server
+//!
+//! After the 206 response the server reads a raw DEFLATE stream of commands
+//! and answers in one continuous DEFLATE stream, keeping dictionary state
+//! across responses like INN and Diablo do.
+
+use flate2::Compression;
+use flate2::write::{DeflateDecoder, DeflateEncoder};
+use nntp_rs::{ConnectionObserver, NntpClient, ServerConfig};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Commands as decoded by the server after compression started
+type Received = Arc<Mutex<Vec<String>>>;
+
+async fn deflate_server() -> (ServerConfig, Received) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = Received::default();
+
+    let shared = received.clone();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        serve(socket, shared).await;
+    });
+
+    (
+        ServerConfig::new("127.0.0.1", port, false, "user", "pass"),
+        received,
+    )
+}
+
+fn reply(command: &str) -> String {
+    if command.starts_with("GROUP") {
+        "211 3 1 3 alt.test\r\n".to_string()
+    } else if command.starts_with("BODY") {
+        let body = "..leading dot and some repetitive article text\r\n".repeat(500);
+        format!("222 1 <a@b> body\r\n{}.\r\n", body)
+    } else {
+        "500 unknown command\r\n".to_string()
+    }
+}
+
+async fn serve(mut socket: TcpStream, received: Received) {
+    socket.write_all(b"200 mock ready\r\n").await.unwrap();
+
+    // Plain text until COMPRESS DEFLATE (the command fits in one read)
+    let mut buf = vec![0u8; 4096];
+    let n = socket.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], b"COMPRESS DEFLATE\r\n");
+    socket
+        .write_all(b"206 compression active\r\n")
+        .await
+        .unwrap();
+
+    let mut decoder = DeflateDecoder::new(Vec::new());
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        if n == 0 {
+            return;
+        }
+        decoder.write_all(&buf[..n]).unwrap();
+        decoder.flush().unwrap();
+
+        let decoded = std::mem::take(decoder.get_mut());
+        for command in String::from_utf8(decoded).unwrap().lines() {
+            received.lock().unwrap().push(command.to_string());
+            encoder.write_all(reply(command).as_bytes()).unwrap();
+        }
+        encoder.flush().unwrap();
+        let compressed = std::mem::take(encoder.get_mut());
+        socket.write_all(&compressed).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_full_session_compression_round_trip() {
+    let (config, received) = deflate_server().await;
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    assert!(client.try_enable_compression().await.unwrap());
+    let group = client.select_group("alt.test").await.unwrap();
+    assert_eq!(group.count, 3);
+
+    // Later responses depend on dictionary state from earlier ones
+    for _ in 0..3 {
+        let body = client.fetch_body("<a@b>").await.unwrap();
+        assert_eq!(body.lines.len(), 500);
+        assert_eq!(
+            body.lines[0],
+            ".leading dot and some repetitive article text"
+        );
+    }
+    let binary = client.fetch_body_binary("<a@b>").await.unwrap();
+    assert!(binary.data.starts_with(b".leading dot"));
+
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![
+            "GROUP alt.test",
+            "BODY <a@b>",
+            "BODY <a@b>",
+            "BODY <a@b>",
+            "BODY <a@b>"
+        ]
+    );
+    let (compressed, decompressed) = client.get_bandwidth_stats();
+    assert!(compressed > 0);
+    assert!(compressed * 10 < decompressed);
+    assert!(!client.is_broken());
+}
+
+/// Counts bytes read from the socket
+#[derive(Debug, Default)]
+struct ByteCounter(AtomicUsize);
+
+impl ConnectionObserver for ByteCounter {
+    fn on_bytes_in(&self, bytes: usize) {
+        self.0.fetch_add(bytes, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_observer_counts_compressed_bytes() {
+    let (config, _) = deflate_server().await;
+    let counter = Arc::new(ByteCounter::default());
+    let config = config.with_observer(counter.clone());
+    let mut client = NntpClient::connect(Arc::new(config)).await.unwrap();
+
+    assert!(client.try_enable_compression().await.unwrap());
+    let body = client.fetch_body_binary("<a@b>").await.unwrap();
+
+    // Socket traffic is the compressed stream, far smaller than the body
+    let received = counter.0.load(Ordering::SeqCst);
+    assert_eq!(received as u64, client.bytes_received());
+    assert!(received * 10 < body.data.len());
+}
@@ -31,6 +31,29 @@ async fn test_server_group_creation() {
     assert_eq!(group.server_ids().len(), 2);
 }
 
+#[tokio::test]
+async fn test_server_group_health() {
+    let configs = vec![
+        ServerConfig::new("news.example.com", 119, false, "user", "pass"),
+        ServerConfig::new("news2.example.com", 119, false, "user", "pass"),
+    ];
+    let group = ServerGroup::new(configs, vec![100, 50], FailoverStrategy::RoundRobin, 5)
+        .await
+        .unwrap();
+    group.record_success("news.example.com:119", 1_000_000);
+
+    let health = group.health();
+    assert_eq!(health.len(), 2);
+    assert!(health.iter().all(|server| server.up));
+    assert_eq!(health[0].server_id, "news.example.com:119");
+    assert_eq!(health[0].bytes_downloaded, 1_000_000);
+    assert!(health[0].bytes_per_second > 0);
+    assert!(health[0].since_last_success.is_some());
+    assert_eq!(health[1].bytes_per_second, 0);
+    assert_eq!(health[1].pool.max_size, 5);
+    assert_eq!(health[1].pool.in_use, 0);
+}
+
 #[tokio::test]
 async fn test_server_group_mismatched_priorities() {
     let configs = vec![
@@ -90,3 +113,259 @@ fn test_failover_strategy_variants() {
     let debug_str = format!("{:?}", strategies[0]);
     assert!(debug_str.contains("PrimaryWithFallback"));
 }
+
+mod healing {
+    use nntp_rs::{
+        ArticleCache, FailoverStrategy, NntpError, ServerConfig, ServerGroup, VerificationStatus,
+        yenc_encode,
+    };
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server that answers every BODY with `body` (or 430 if `None`)
+    async fn mock_server(body: Option<Vec<u8>>) -> (ServerConfig, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve(socket, body.clone(), counter.clone()));
+            }
+        });
+
+        let config = ServerConfig::new("127.0.0.1", port, false, "user", "pass");
+        (config, requests)
+    }
+
+    async fn serve(socket: TcpStream, body: Option<Vec<u8>>, requests: Arc<AtomicUsize>) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"200 mock ready\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply = if line.starts_with("AUTHINFO USER") {
+                b"381 password required\r\n".to_vec()
+            } else if line.starts_with("AUTHINFO PASS") {
+                b"281 welcome\r\n".to_vec()
+            } else if line.starts_with("BODY") {
+                requests.fetch_add(1, Ordering::SeqCst);
+                match &body {
+                    Some(body) => [b"222 0 <a@b> body\r\n", body.as_slice(), b".\r\n"].concat(),
+                    None => b"430 no such article\r\n".to_vec(),
+                }
+            } else {
+                b"500 unknown command\r\n".to_vec()
+            };
+            if writer.write_all(&reply).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn segment() -> Vec<u8> {
+        yenc_encode(b"segment payload", "file.bin", 128, None).unwrap()
+    }
+
+    /// Flip one encoded data byte so the CRC no longer matches
+    fn damaged_segment() -> Vec<u8> {
+        let mut body = segment();
+        let pos = body.iter().position(|&b| b == b'\n').unwrap() + 1;
+        body[pos] = body[pos].wrapping_add(1);
+        body
+    }
+
+    async fn group(configs: Vec<ServerConfig>) -> ServerGroup {
+        let priorities = (0..configs.len() as u32).rev().collect();
+        ServerGroup::new(
+            configs,
+            priorities,
+            FailoverStrategy::PrimaryWithFallback,
+            2,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_heals_crc_failure_from_alternate_server() {
+        let (primary, primary_requests) = mock_server(Some(damaged_segment())).await;
+        let (backup, backup_requests) = mock_server(Some(segment())).await;
+        let group = group(vec![primary, backup]).await;
+        let ids = group.server_ids();
+        let cache = Mutex::new(ArticleCache::new(1024 * 1024));
+
+        let body = group.fetch_body_verified("<a@b>", &cache).await.unwrap();
+        assert_eq!(body.status, VerificationStatus::Verified);
+        assert_eq!(body.server_id, ids[1]);
+        assert_eq!(body.data, segment());
+
+        // Both copies are cached with their origin and status
+        {
+            let mut cache = cache.lock().unwrap();
+            let damaged = cache.get(&ids[0], "<a@b>").unwrap();
+            assert_eq!(damaged.status, VerificationStatus::CrcFailed);
+        }
+
+        // A verified copy is served from the cache
+        group.fetch_body_verified("<a@b>", &cache).await.unwrap();
+        assert_eq!(primary_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(backup_requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_untried_servers_are_refetched() {
+        let (primary, primary_requests) = mock_server(Some(segment())).await;
+        let (backup, backup_requests) = mock_server(Some(damaged_segment())).await;
+        let group = group(vec![primary, backup]).await;
+        let ids = group.server_ids();
+        let cache = Mutex::new(ArticleCache::new(1024 * 1024));
+
+        // The primary's copy was cached earlier but failed verification
+        cache.lock().unwrap().put(
+            &ids[0],
+            "<a@b>",
+            damaged_segment(),
+            VerificationStatus::CrcFailed,
+        );
+
+        let body = group.fetch_body_verified("<a@b>", &cache).await.unwrap();
+        assert_eq!(body.status, VerificationStatus::CrcFailed);
+        assert_eq!(primary_requests.load(Ordering::SeqCst), 0);
+        assert_eq!(backup_requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_everywhere() {
+        let (primary, _) = mock_server(None).await;
+        let (backup, _) = mock_server(None).await;
+        let group = group(vec![primary, backup]).await;
+        let cache = Mutex::new(ArticleCache::new(1024 * 1024));
+
+        let result = group.fetch_body_verified("<a@b>", &cache).await;
+        assert!(matches!(result, Err(NntpError::Protocol { code: 430, .. })));
+        assert!(cache.lock().unwrap().is_empty());
+        assert_eq!(group.stats().total_not_found, 2);
+    }
+}
+
+mod availability {
+    use nntp_rs::jobs::DownloadJob;
+    use nntp_rs::{CompletenessReport, FailoverStrategy, ServerConfig, ServerGroup, parse_nzb};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a server that has exactly the articles in `ids`
+    async fn mock_server(ids: &'static [&'static str]) -> ServerConfig {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve(socket, ids));
+            }
+        });
+        ServerConfig::new("127.0.0.1", port, false, "user", "pass")
+    }
+
+    async fn serve(socket: TcpStream, ids: &'static [&'static str]) {
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"200 mock ready\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let reply = if line.starts_with("AUTHINFO USER") {
+                "381 password required\r\n".to_string()
+            } else if line.starts_with("AUTHINFO PASS") {
+                "281 welcome\r\n".to_string()
+            } else if let Some(id) = line.strip_prefix("STAT ") {
+                if ids.contains(&id) {
+                    format!("223 0 {}\r\n", id)
+                } else {
+                    "430 no such article\r\n".to_string()
+                }
+            } else {
+                "500 unknown command\r\n".to_string()
+            };
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep_counts_segments_per_server() {
+        let primary = mock_server(&["<p1@x>", "<p2@x>"]).await;
+        let backup = mock_server(&["<p2@x>", "<p3@x>"]).await;
+        let group = ServerGroup::new(
+            vec![primary, backup],
+            vec![100, 50],
+            FailoverStrategy::PrimaryWithFallback,
+            1,
+        )
+        .await
+        .unwrap();
+        let ids = group.server_ids();
+        let nzb = parse_nzb(
+            r#"<?xml version="1.0"?>
+<nzb xmlns="http://www.newzbin.com/DTD/2003/nzb">
+  <file poster="p" date="0" subject="&quot;a.bin&quot; yEnc (1/4)">
+    <groups><group>alt.test</group></groups>
+    <segments>
+      <segment bytes="100" number="1">p1@x</segment>
+      <segment bytes="100" number="2">p2@x</segment>
+      <segment bytes="100" number="3">p3@x</segment>
+      <segment bytes="100" number="4">p4@x</segment>
+    </segments>
+  </file>
+</nzb>"#,
+        )
+        .unwrap();
+
+        let sweep = group.availability_sweep(&nzb).await.unwrap();
+        assert!(sweep.has(&ids[0], 0, 0) && !sweep.has(&ids[0], 0, 2));
+        assert!(sweep.is_available(0, 2) && !sweep.is_available(0, 3));
+
+        let report = CompletenessReport::from_sweep(&nzb, &sweep, None);
+        let file = &report.files[0];
+        assert_eq!(file.servers[0].found, 2);
+        assert_eq!(file.servers[1].found, 2);
+        assert_eq!((file.missing, file.missing_bytes), (1, 100));
+        assert!(!report.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_job_precheck_keeps_the_sweep_report() {
+        let server = mock_server(&["<p1@x>"]).await;
+        let group = ServerGroup::new(
+            vec![server],
+            vec![100],
+            FailoverStrategy::PrimaryWithFallback,
+            1,
+        )
+        .await
+        .unwrap();
+        let nzb = parse_nzb(
+            r#"<?xml version="1.0"?>
+<nzb xmlns="http://www.newzbin.com/DTD/2003/nzb">
+  <file poster="p" date="0" subject="&quot;a.bin&quot; yEnc (1/2)">
+    <groups><group>alt.test</group></groups>
+    <segments>
+      <segment bytes="100" number="1">p1@x</segment>
+      <segment bytes="100" number="2">p2@x</segment>
+    </segments>
+  </file>
+</nzb>"#,
+        )
+        .unwrap();
+        let mut job = DownloadJob::new("job", nzb, "primary", "/tmp/downloads");
+
+        let report = job.run_precheck(&group, None).await.unwrap();
+        assert_eq!(report.files[0].missing, 1);
+        assert!(!report.is_complete());
+        assert!(job.precheck.is_some());
+    }
+}
@@ -28,14 +28,7 @@ fn get_test_config() -> ServerConfig {
     let username = std::env::var("NNTP_USER").expect("NNTP_USER not set");
     let password = std::env::var("NNTP_PASS").expect("NNTP_PASS not set");
 
-    ServerConfig {
-        host,
-        port,
-        tls: true,
-        allow_insecure_tls: false,
-        username,
-        password,
-    }
+    ServerConfig::new(host, port, true, username, password)
 }
 
 /// Test that socket tuning doesn't break basic connection
@@ -170,14 +163,13 @@ async fn test_socket_tuning_multiple_connections() {
 async fn test_socket_tuning_respects_timeout() {
     // Create a config pointing to a non-existent server
     // This should timeout properly even with socket tuning
-    let config = ServerConfig {
-        host: "192.0.2.1".to_string(), // TEST-NET-1 (non-routable)
-        port: 563,
-        tls: true,
-        allow_insecure_tls: false,
-        username: "test".to_string(),
-        password: "test".to_string(),
-    };
+    let config = ServerConfig::new(
+        "192.0.2.1".to_string(),
+        563,
+        true,
+        "test".to_string(),
+        "test".to_string(),
+    );
 
     // Connection should timeout (not hang indefinitely)
     let start = std::time::Instant::now();